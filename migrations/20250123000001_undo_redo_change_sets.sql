@@ -0,0 +1,34 @@
+-- Multi-level undo/redo with grouped change sets
+-- Replaces the fixed 5-entry undo buffer with a configurable-depth stack
+
+-- A change set is one user-level step (e.g. "Moved 12 nodes") made of one or more undo_history entries
+-- is_open: writes are appended to an open change set until it is ended
+-- undone: change sets with undone = 1 form the redo stack
+CREATE TABLE IF NOT EXISTS undo_change_sets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    topology_id INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    is_open BOOLEAN NOT NULL DEFAULT FALSE,
+    undone BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    FOREIGN KEY (topology_id) REFERENCES topologies(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_undo_change_sets_topology ON undo_change_sets(topology_id, undone, id);
+
+-- Depth is now enforced per change set by the server (see ui_settings.undo_depth)
+DROP TRIGGER IF EXISTS trim_undo_history;
+
+-- new_state: JSON snapshot after the change (NULL for deletes), needed for redo
+-- previous_state is the JSON literal 'null' for creates
+-- action_type now also accepts 'create'
+ALTER TABLE undo_history ADD COLUMN change_set_id INTEGER REFERENCES undo_change_sets(id) ON DELETE CASCADE;
+ALTER TABLE undo_history ADD COLUMN new_state TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_undo_history_change_set ON undo_history(change_set_id);
+
+-- Existing entries have no post-change state and cannot be redone, so drop them
+DELETE FROM undo_history;
+
+-- Maximum number of undoable change sets kept per topology (0 = unlimited)
+ALTER TABLE ui_settings ADD COLUMN undo_depth INTEGER NOT NULL DEFAULT 100;
@@ -0,0 +1,18 @@
+-- Named topology revisions (checkpoints) that can be previewed and restored
+-- Unlike the undo stack, revisions are never trimmed automatically
+
+CREATE TABLE IF NOT EXISTS topology_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    topology_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    note TEXT,                    -- Optional author note
+    snapshot TEXT NOT NULL,       -- TopologyDocument JSON of the topology, nodes and connections
+    node_count INTEGER NOT NULL DEFAULT 0,
+    connection_count INTEGER NOT NULL DEFAULT 0,
+    restored_from INTEGER,        -- Revision this one was restored from (NULL for manual checkpoints)
+    created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    FOREIGN KEY (topology_id) REFERENCES topologies(id) ON DELETE CASCADE,
+    FOREIGN KEY (restored_from) REFERENCES topology_revisions(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_topology_revisions_topology ON topology_revisions(topology_id, created_at DESC);
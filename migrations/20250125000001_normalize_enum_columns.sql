@@ -0,0 +1,75 @@
+-- Normalize node_type, connection_type and status to the canonical lowercase values
+-- used by the NodeType, ConnectionType and ConnectionStatus enums, then enforce them
+-- with triggers (same approach as check_flow_direction_*)
+
+-- Canonical casing and separators ("Load-Balancer" -> "load_balancer")
+UPDATE nodes SET node_type = REPLACE(REPLACE(LOWER(TRIM(node_type)), '-', '_'), ' ', '_');
+UPDATE connections SET connection_type = REPLACE(REPLACE(LOWER(TRIM(connection_type)), '-', '_'), ' ', '_');
+UPDATE connections SET status = REPLACE(REPLACE(LOWER(TRIM(status)), '-', '_'), ' ', '_');
+
+-- Legacy aliases
+UPDATE nodes SET node_type = 'load_balancer' WHERE node_type IN ('loadbalancer', 'lb');
+UPDATE nodes SET node_type = 'application' WHERE node_type = 'app';
+UPDATE nodes SET node_type = 'host' WHERE node_type = 'client';
+UPDATE connections SET connection_type = 'ethernet' WHERE connection_type = 'eth';
+UPDATE connections SET connection_type = 'fiber' WHERE connection_type = 'fibre';
+UPDATE connections SET connection_type = 'wireless' WHERE connection_type = 'wifi';
+UPDATE connections SET status = 'active' WHERE status = 'up';
+UPDATE connections SET status = 'inactive' WHERE status = 'down';
+
+-- Anything still unrecognized (or NULL) falls back to the column default
+UPDATE nodes SET node_type = 'host'
+WHERE node_type NOT IN ('host', 'router', 'switch', 'server', 'firewall', 'load_balancer', 'cloud', 'application');
+UPDATE connections SET connection_type = 'ethernet'
+WHERE connection_type IS NULL OR connection_type NOT IN ('ethernet', 'fiber', 'wireless', 'vpn', 'wan');
+UPDATE connections SET status = 'active'
+WHERE status IS NULL OR status NOT IN ('active', 'inactive', 'degraded', 'error');
+
+CREATE TRIGGER check_node_type_insert
+BEFORE INSERT ON nodes
+FOR EACH ROW
+WHEN NEW.node_type NOT IN ('host', 'router', 'switch', 'server', 'firewall', 'load_balancer', 'cloud', 'application')
+BEGIN
+    SELECT RAISE(ABORT, 'node_type must be one of: host, router, switch, server, firewall, load_balancer, cloud, application');
+END;
+
+CREATE TRIGGER check_node_type_update
+BEFORE UPDATE OF node_type ON nodes
+FOR EACH ROW
+WHEN NEW.node_type NOT IN ('host', 'router', 'switch', 'server', 'firewall', 'load_balancer', 'cloud', 'application')
+BEGIN
+    SELECT RAISE(ABORT, 'node_type must be one of: host, router, switch, server, firewall, load_balancer, cloud, application');
+END;
+
+-- The columns are nullable, so NULL has to be rejected explicitly
+CREATE TRIGGER check_connection_type_insert
+BEFORE INSERT ON connections
+FOR EACH ROW
+WHEN NEW.connection_type IS NULL OR NEW.connection_type NOT IN ('ethernet', 'fiber', 'wireless', 'vpn', 'wan')
+BEGIN
+    SELECT RAISE(ABORT, 'connection_type must be one of: ethernet, fiber, wireless, vpn, wan');
+END;
+
+CREATE TRIGGER check_connection_type_update
+BEFORE UPDATE OF connection_type ON connections
+FOR EACH ROW
+WHEN NEW.connection_type IS NULL OR NEW.connection_type NOT IN ('ethernet', 'fiber', 'wireless', 'vpn', 'wan')
+BEGIN
+    SELECT RAISE(ABORT, 'connection_type must be one of: ethernet, fiber, wireless, vpn, wan');
+END;
+
+CREATE TRIGGER check_connection_status_insert
+BEFORE INSERT ON connections
+FOR EACH ROW
+WHEN NEW.status IS NULL OR NEW.status NOT IN ('active', 'inactive', 'degraded', 'error')
+BEGIN
+    SELECT RAISE(ABORT, 'status must be one of: active, inactive, degraded, error');
+END;
+
+CREATE TRIGGER check_connection_status_update
+BEFORE UPDATE OF status ON connections
+FOR EACH ROW
+WHEN NEW.status IS NULL OR NEW.status NOT IN ('active', 'inactive', 'degraded', 'error')
+BEGIN
+    SELECT RAISE(ABORT, 'status must be one of: active, inactive, degraded, error');
+END;
@@ -0,0 +1,41 @@
+-- Normalize node and connection colors to the "R,G,B[,A]" form read by the Rgb type
+-- Colors used to be free-form strings; unparseable values rendered as black or gray
+
+-- Drop stray whitespace ("100, 150, 255" -> "100,150,255")
+UPDATE nodes SET color = REPLACE(TRIM(color), ' ', '');
+UPDATE connections SET color = REPLACE(TRIM(color), ' ', '');
+
+-- Convert hex #rrggbb to "R,G,B"
+UPDATE nodes SET color =
+    ((INSTR('0123456789abcdef', SUBSTR(LOWER(color), 2, 1)) - 1) * 16 + INSTR('0123456789abcdef', SUBSTR(LOWER(color), 3, 1)) - 1) || ',' ||
+    ((INSTR('0123456789abcdef', SUBSTR(LOWER(color), 4, 1)) - 1) * 16 + INSTR('0123456789abcdef', SUBSTR(LOWER(color), 5, 1)) - 1) || ',' ||
+    ((INSTR('0123456789abcdef', SUBSTR(LOWER(color), 6, 1)) - 1) * 16 + INSTR('0123456789abcdef', SUBSTR(LOWER(color), 7, 1)) - 1)
+WHERE LENGTH(color) = 7 AND LOWER(color) GLOB '#[0-9a-f][0-9a-f][0-9a-f][0-9a-f][0-9a-f][0-9a-f]';
+
+UPDATE connections SET color =
+    ((INSTR('0123456789abcdef', SUBSTR(LOWER(color), 2, 1)) - 1) * 16 + INSTR('0123456789abcdef', SUBSTR(LOWER(color), 3, 1)) - 1) || ',' ||
+    ((INSTR('0123456789abcdef', SUBSTR(LOWER(color), 4, 1)) - 1) * 16 + INSTR('0123456789abcdef', SUBSTR(LOWER(color), 5, 1)) - 1) || ',' ||
+    ((INSTR('0123456789abcdef', SUBSTR(LOWER(color), 6, 1)) - 1) * 16 + INSTR('0123456789abcdef', SUBSTR(LOWER(color), 7, 1)) - 1)
+WHERE LENGTH(color) = 7 AND LOWER(color) GLOB '#[0-9a-f][0-9a-f][0-9a-f][0-9a-f][0-9a-f][0-9a-f]';
+
+-- Reset anything that still isn't 3 or 4 comma-separated values in 0-255:
+-- only digits and commas, 2-3 commas, no empty parts, no part above 255
+UPDATE nodes SET color = '100,150,255'
+WHERE color IS NULL
+   OR color GLOB '*[^0-9,]*'
+   OR LENGTH(color) - LENGTH(REPLACE(color, ',', '')) NOT IN (2, 3)
+   OR color GLOB ',*' OR color GLOB '*,' OR color GLOB '*,,*'
+   OR color GLOB '*[0-9][0-9][0-9][0-9]*'
+   OR color GLOB '[3-9][0-9][0-9]*' OR color GLOB '*,[3-9][0-9][0-9]*'
+   OR color GLOB '2[6-9][0-9]*' OR color GLOB '*,2[6-9][0-9]*'
+   OR color GLOB '25[6-9]*' OR color GLOB '*,25[6-9]*';
+
+UPDATE connections SET color = '128,128,128'
+WHERE color IS NULL
+   OR color GLOB '*[^0-9,]*'
+   OR LENGTH(color) - LENGTH(REPLACE(color, ',', '')) NOT IN (2, 3)
+   OR color GLOB ',*' OR color GLOB '*,' OR color GLOB '*,,*'
+   OR color GLOB '*[0-9][0-9][0-9][0-9]*'
+   OR color GLOB '[3-9][0-9][0-9]*' OR color GLOB '*,[3-9][0-9][0-9]*'
+   OR color GLOB '2[6-9][0-9]*' OR color GLOB '*,2[6-9][0-9]*'
+   OR color GLOB '25[6-9]*' OR color GLOB '*,25[6-9]*';
@@ -0,0 +1,88 @@
+-- Named interfaces (ports) on nodes, and the ports each connection plugs into
+
+CREATE TABLE IF NOT EXISTS interfaces (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    node_id INTEGER NOT NULL,
+    name TEXT NOT NULL,                          -- e.g. 'Gi0/0/1', 'eth0'
+    speed_mbps INTEGER,
+    mac_address TEXT,                            -- normalized to 'aa:bb:cc:dd:ee:ff'
+    ip_address TEXT,                             -- address or CIDR, e.g. '10.0.0.1/24'
+    admin_state TEXT NOT NULL DEFAULT 'up',      -- up, down
+    description TEXT,
+    created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    FOREIGN KEY (node_id) REFERENCES nodes(id) ON DELETE CASCADE,
+    UNIQUE (node_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_interfaces_node ON interfaces(node_id);
+
+CREATE TRIGGER check_interface_admin_state_insert
+BEFORE INSERT ON interfaces
+FOR EACH ROW
+WHEN NEW.admin_state NOT IN ('up', 'down')
+BEGIN
+    SELECT RAISE(ABORT, 'admin_state must be one of: up, down');
+END;
+
+CREATE TRIGGER check_interface_admin_state_update
+BEFORE UPDATE OF admin_state ON interfaces
+FOR EACH ROW
+WHEN NEW.admin_state NOT IN ('up', 'down')
+BEGIN
+    SELECT RAISE(ABORT, 'admin_state must be one of: up, down');
+END;
+
+-- NULL = the link attaches to the node without a specific port
+-- Deleting an interface leaves the connection in place, unattached on that end
+ALTER TABLE connections ADD COLUMN source_interface_id INTEGER REFERENCES interfaces(id) ON DELETE SET NULL;
+ALTER TABLE connections ADD COLUMN target_interface_id INTEGER REFERENCES interfaces(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_connections_source_interface ON connections(source_interface_id);
+CREATE INDEX IF NOT EXISTS idx_connections_target_interface ON connections(target_interface_id);
+
+-- Each end's interface must belong to that end's node, and a port can carry only one link
+-- (as source or target). `id IS NOT NEW.id` lets a connection keep its own ports on update.
+CREATE TRIGGER check_connection_interfaces_insert
+BEFORE INSERT ON connections
+FOR EACH ROW
+WHEN NEW.source_interface_id IS NOT NULL OR NEW.target_interface_id IS NOT NULL
+BEGIN
+    SELECT RAISE(ABORT, 'source interface does not belong to the source node')
+    WHERE NEW.source_interface_id IS NOT NULL
+      AND NOT EXISTS (SELECT 1 FROM interfaces WHERE id = NEW.source_interface_id AND node_id = NEW.source_node_id);
+    SELECT RAISE(ABORT, 'target interface does not belong to the target node')
+    WHERE NEW.target_interface_id IS NOT NULL
+      AND NOT EXISTS (SELECT 1 FROM interfaces WHERE id = NEW.target_interface_id AND node_id = NEW.target_node_id);
+    SELECT RAISE(ABORT, 'a connection cannot use the same interface at both ends')
+    WHERE NEW.source_interface_id = NEW.target_interface_id;
+    SELECT RAISE(ABORT, 'interface is already used by another connection')
+    WHERE EXISTS (
+        SELECT 1 FROM connections
+        WHERE id IS NOT NEW.id
+          AND (source_interface_id IN (NEW.source_interface_id, NEW.target_interface_id)
+               OR target_interface_id IN (NEW.source_interface_id, NEW.target_interface_id))
+    );
+END;
+
+CREATE TRIGGER check_connection_interfaces_update
+BEFORE UPDATE OF source_node_id, target_node_id, source_interface_id, target_interface_id ON connections
+FOR EACH ROW
+WHEN NEW.source_interface_id IS NOT NULL OR NEW.target_interface_id IS NOT NULL
+BEGIN
+    SELECT RAISE(ABORT, 'source interface does not belong to the source node')
+    WHERE NEW.source_interface_id IS NOT NULL
+      AND NOT EXISTS (SELECT 1 FROM interfaces WHERE id = NEW.source_interface_id AND node_id = NEW.source_node_id);
+    SELECT RAISE(ABORT, 'target interface does not belong to the target node')
+    WHERE NEW.target_interface_id IS NOT NULL
+      AND NOT EXISTS (SELECT 1 FROM interfaces WHERE id = NEW.target_interface_id AND node_id = NEW.target_node_id);
+    SELECT RAISE(ABORT, 'a connection cannot use the same interface at both ends')
+    WHERE NEW.source_interface_id = NEW.target_interface_id;
+    SELECT RAISE(ABORT, 'interface is already used by another connection')
+    WHERE EXISTS (
+        SELECT 1 FROM connections
+        WHERE id IS NOT NEW.id
+          AND (source_interface_id IN (NEW.source_interface_id, NEW.target_interface_id)
+               OR target_interface_id IN (NEW.source_interface_id, NEW.target_interface_id))
+    );
+END;
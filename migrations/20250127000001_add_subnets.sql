@@ -0,0 +1,18 @@
+-- Per-topology address plan (IPv4 and IPv6 prefixes)
+-- CIDR, gateway and VLAN are validated by the server before they are written
+
+CREATE TABLE IF NOT EXISTS subnets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    topology_id INTEGER NOT NULL,
+    cidr TEXT NOT NULL,                          -- canonical network prefix, e.g. '10.0.1.0/24'
+    name TEXT NOT NULL DEFAULT '',
+    gateway TEXT,                                -- host address inside the prefix
+    vlan_id INTEGER,                             -- 1-4094
+    description TEXT,
+    created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    FOREIGN KEY (topology_id) REFERENCES topologies(id) ON DELETE CASCADE,
+    UNIQUE (topology_id, cidr)
+);
+
+CREATE INDEX IF NOT EXISTS idx_subnets_topology ON subnets(topology_id);
@@ -0,0 +1,78 @@
+-- Hierarchical groups (site > building > rack, security zones) that nodes belong to
+-- A group's parent and members must be in the same topology; cycles are rejected by the server
+
+CREATE TABLE IF NOT EXISTS node_groups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    topology_id INTEGER NOT NULL,
+    parent_id INTEGER,                           -- NULL = top level
+    name TEXT NOT NULL,
+    group_type TEXT NOT NULL DEFAULT 'site',     -- site, building, rack, zone
+    color TEXT NOT NULL DEFAULT '120,160,220',   -- "R,G,B", drawn translucent
+    collapsed BOOLEAN NOT NULL DEFAULT 0,        -- drawn as a single proxy node
+    created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    FOREIGN KEY (topology_id) REFERENCES topologies(id) ON DELETE CASCADE,
+    FOREIGN KEY (parent_id) REFERENCES node_groups(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_node_groups_topology ON node_groups(topology_id);
+CREATE INDEX IF NOT EXISTS idx_node_groups_parent ON node_groups(parent_id);
+
+CREATE TRIGGER check_node_group_type_insert
+BEFORE INSERT ON node_groups
+FOR EACH ROW
+WHEN NEW.group_type NOT IN ('site', 'building', 'rack', 'zone')
+BEGIN
+    SELECT RAISE(ABORT, 'group_type must be one of: site, building, rack, zone');
+END;
+
+CREATE TRIGGER check_node_group_type_update
+BEFORE UPDATE OF group_type ON node_groups
+FOR EACH ROW
+WHEN NEW.group_type NOT IN ('site', 'building', 'rack', 'zone')
+BEGIN
+    SELECT RAISE(ABORT, 'group_type must be one of: site, building, rack, zone');
+END;
+
+CREATE TRIGGER check_node_group_parent_insert
+BEFORE INSERT ON node_groups
+FOR EACH ROW
+WHEN NEW.parent_id IS NOT NULL
+BEGIN
+    SELECT RAISE(ABORT, 'parent group belongs to another topology')
+    WHERE NOT EXISTS (SELECT 1 FROM node_groups WHERE id = NEW.parent_id AND topology_id = NEW.topology_id);
+END;
+
+CREATE TRIGGER check_node_group_parent_update
+BEFORE UPDATE OF parent_id ON node_groups
+FOR EACH ROW
+WHEN NEW.parent_id IS NOT NULL
+BEGIN
+    SELECT RAISE(ABORT, 'a group cannot be its own parent')
+    WHERE NEW.parent_id = NEW.id;
+    SELECT RAISE(ABORT, 'parent group belongs to another topology')
+    WHERE NOT EXISTS (SELECT 1 FROM node_groups WHERE id = NEW.parent_id AND topology_id = NEW.topology_id);
+END;
+
+-- NULL = the node isn't in any group; deleting a group leaves its members ungrouped
+ALTER TABLE nodes ADD COLUMN group_id INTEGER REFERENCES node_groups(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_nodes_group ON nodes(group_id);
+
+CREATE TRIGGER check_node_group_insert
+BEFORE INSERT ON nodes
+FOR EACH ROW
+WHEN NEW.group_id IS NOT NULL
+BEGIN
+    SELECT RAISE(ABORT, 'group belongs to another topology')
+    WHERE NOT EXISTS (SELECT 1 FROM node_groups WHERE id = NEW.group_id AND topology_id = NEW.topology_id);
+END;
+
+CREATE TRIGGER check_node_group_update
+BEFORE UPDATE OF group_id ON nodes
+FOR EACH ROW
+WHEN NEW.group_id IS NOT NULL
+BEGIN
+    SELECT RAISE(ABORT, 'group belongs to another topology')
+    WHERE NOT EXISTS (SELECT 1 FROM node_groups WHERE id = NEW.group_id AND topology_id = NEW.topology_id);
+END;
@@ -0,0 +1,43 @@
+-- Free-form tags on nodes and connections, used by the filter query language
+-- Tags belong to a topology; names are unique per topology regardless of case
+
+CREATE TABLE IF NOT EXISTS tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    topology_id INTEGER NOT NULL,
+    name TEXT NOT NULL COLLATE NOCASE,
+    created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    FOREIGN KEY (topology_id) REFERENCES topologies(id) ON DELETE CASCADE,
+    UNIQUE (topology_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_tags_topology ON tags(topology_id);
+
+-- Each row tags exactly one node or one connection
+CREATE TABLE IF NOT EXISTS item_tags (
+    tag_id INTEGER NOT NULL,
+    node_id INTEGER,
+    connection_id INTEGER,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE,
+    FOREIGN KEY (node_id) REFERENCES nodes(id) ON DELETE CASCADE,
+    FOREIGN KEY (connection_id) REFERENCES connections(id) ON DELETE CASCADE,
+    CHECK ((node_id IS NULL) != (connection_id IS NULL))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_item_tags_node ON item_tags(node_id, tag_id) WHERE node_id IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_item_tags_connection ON item_tags(connection_id, tag_id) WHERE connection_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_item_tags_tag ON item_tags(tag_id);
+
+CREATE TRIGGER check_item_tags_topology
+BEFORE INSERT ON item_tags
+FOR EACH ROW
+BEGIN
+    SELECT RAISE(ABORT, 'tag belongs to another topology')
+    WHERE NOT EXISTS (
+        SELECT 1 FROM tags t
+        WHERE t.id = NEW.tag_id
+          AND t.topology_id = COALESCE(
+              (SELECT topology_id FROM nodes WHERE id = NEW.node_id),
+              (SELECT topology_id FROM connections WHERE id = NEW.connection_id)
+          )
+    );
+END;
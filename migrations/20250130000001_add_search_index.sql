@@ -0,0 +1,140 @@
+-- Full-text search over topologies, nodes and connections (FTS5)
+-- The index is kept in sync by triggers; search_documents defines what each entity contributes
+
+-- rowid encodes the entity: topology = id * 3, node = id * 3 + 1, connection = id * 3 + 2
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+    kind UNINDEXED,          -- topology, node, connection
+    item_id UNINDEXED,
+    topology_id UNINDEXED,
+    name,                    -- topology or node name (empty for connections)
+    detail                   -- description, IPs, vendor, model, metadata
+);
+
+CREATE VIEW IF NOT EXISTS search_documents AS
+SELECT id * 3 AS doc_id, 'topology' AS kind, id AS item_id, id AS topology_id,
+       name, COALESCE(description, '') AS detail
+FROM topologies
+UNION ALL
+SELECT n.id * 3 + 1, 'node', n.id, n.topology_id, n.name,
+       COALESCE(n.ip_address, '') || ' ' || n.vendor || ' ' || n.model_name || ' '
+           || COALESCE((SELECT group_concat(i.ip_address, ' ') FROM interfaces i WHERE i.node_id = n.id), '') || ' '
+           || COALESCE(n.metadata, '')
+FROM nodes n
+UNION ALL
+SELECT c.id * 3 + 2, 'connection', c.id, c.topology_id, '', COALESCE(c.metadata, '')
+FROM connections c;
+
+INSERT INTO search_index(rowid, kind, item_id, topology_id, name, detail)
+SELECT doc_id, kind, item_id, topology_id, name, detail FROM search_documents;
+
+-- Topologies
+CREATE TRIGGER search_index_topology_insert
+AFTER INSERT ON topologies
+FOR EACH ROW
+BEGIN
+    INSERT INTO search_index(rowid, kind, item_id, topology_id, name, detail)
+    SELECT doc_id, kind, item_id, topology_id, name, detail FROM search_documents
+    WHERE kind = 'topology' AND item_id = NEW.id;
+END;
+
+CREATE TRIGGER search_index_topology_update
+AFTER UPDATE OF name, description ON topologies
+FOR EACH ROW
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.id * 3;
+    INSERT INTO search_index(rowid, kind, item_id, topology_id, name, detail)
+    SELECT doc_id, kind, item_id, topology_id, name, detail FROM search_documents
+    WHERE kind = 'topology' AND item_id = NEW.id;
+END;
+
+CREATE TRIGGER search_index_topology_delete
+AFTER DELETE ON topologies
+FOR EACH ROW
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.id * 3;
+END;
+
+-- Nodes
+CREATE TRIGGER search_index_node_insert
+AFTER INSERT ON nodes
+FOR EACH ROW
+BEGIN
+    INSERT INTO search_index(rowid, kind, item_id, topology_id, name, detail)
+    SELECT doc_id, kind, item_id, topology_id, name, detail FROM search_documents
+    WHERE kind = 'node' AND item_id = NEW.id;
+END;
+
+CREATE TRIGGER search_index_node_update
+AFTER UPDATE OF name, vendor, model_name, ip_address, metadata ON nodes
+FOR EACH ROW
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.id * 3 + 1;
+    INSERT INTO search_index(rowid, kind, item_id, topology_id, name, detail)
+    SELECT doc_id, kind, item_id, topology_id, name, detail FROM search_documents
+    WHERE kind = 'node' AND item_id = NEW.id;
+END;
+
+CREATE TRIGGER search_index_node_delete
+AFTER DELETE ON nodes
+FOR EACH ROW
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.id * 3 + 1;
+END;
+
+-- Interface addresses are part of their node's entry
+CREATE TRIGGER search_index_interface_insert
+AFTER INSERT ON interfaces
+FOR EACH ROW
+BEGIN
+    DELETE FROM search_index WHERE rowid = NEW.node_id * 3 + 1;
+    INSERT INTO search_index(rowid, kind, item_id, topology_id, name, detail)
+    SELECT doc_id, kind, item_id, topology_id, name, detail FROM search_documents
+    WHERE kind = 'node' AND item_id = NEW.node_id;
+END;
+
+CREATE TRIGGER search_index_interface_update
+AFTER UPDATE OF ip_address ON interfaces
+FOR EACH ROW
+BEGIN
+    DELETE FROM search_index WHERE rowid = NEW.node_id * 3 + 1;
+    INSERT INTO search_index(rowid, kind, item_id, topology_id, name, detail)
+    SELECT doc_id, kind, item_id, topology_id, name, detail FROM search_documents
+    WHERE kind = 'node' AND item_id = NEW.node_id;
+END;
+
+CREATE TRIGGER search_index_interface_delete
+AFTER DELETE ON interfaces
+FOR EACH ROW
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.node_id * 3 + 1;
+    INSERT INTO search_index(rowid, kind, item_id, topology_id, name, detail)
+    SELECT doc_id, kind, item_id, topology_id, name, detail FROM search_documents
+    WHERE kind = 'node' AND item_id = OLD.node_id;
+END;
+
+-- Connections
+CREATE TRIGGER search_index_connection_insert
+AFTER INSERT ON connections
+FOR EACH ROW
+BEGIN
+    INSERT INTO search_index(rowid, kind, item_id, topology_id, name, detail)
+    SELECT doc_id, kind, item_id, topology_id, name, detail FROM search_documents
+    WHERE kind = 'connection' AND item_id = NEW.id;
+END;
+
+CREATE TRIGGER search_index_connection_update
+AFTER UPDATE OF metadata ON connections
+FOR EACH ROW
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.id * 3 + 2;
+    INSERT INTO search_index(rowid, kind, item_id, topology_id, name, detail)
+    SELECT doc_id, kind, item_id, topology_id, name, detail FROM search_documents
+    WHERE kind = 'connection' AND item_id = NEW.id;
+END;
+
+CREATE TRIGGER search_index_connection_delete
+AFTER DELETE ON connections
+FOR EACH ROW
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.id * 3 + 2;
+END;
@@ -0,0 +1,31 @@
+-- Append-only audit log of every change: who did what to which entity, and when
+-- Rows outlive the topologies and users they mention, so there are no foreign keys
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    user_id INTEGER,
+    actor TEXT NOT NULL,                  -- Username at the time of the change
+    topology_id INTEGER,                  -- NULL for account-level changes
+    entity_type TEXT NOT NULL,            -- 'topology', 'node', 'connection', ...
+    entity_id INTEGER,
+    action TEXT NOT NULL,                 -- 'create', 'update', 'delete', 'restore', 'undo', ...
+    summary TEXT NOT NULL,
+    before_state TEXT,                    -- JSON (changed fields only for updates)
+    after_state TEXT                      -- JSON (changed fields only for updates)
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_topology ON audit_log(topology_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_log_user ON audit_log(user_id, created_at);
+
+CREATE TRIGGER IF NOT EXISTS audit_log_no_update
+BEFORE UPDATE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit_log is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_log_no_delete
+BEFORE DELETE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit_log is append-only');
+END;
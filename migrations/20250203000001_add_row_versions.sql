@@ -0,0 +1,5 @@
+-- Row versions for optimistic concurrency on nodes and connections
+-- Every write bumps the version; updates naming an expected version only apply while it still matches
+
+ALTER TABLE nodes ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE connections ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
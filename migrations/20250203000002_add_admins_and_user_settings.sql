@@ -0,0 +1,14 @@
+-- Administrators, who may change the server-wide ui_settings row, and per-user settings
+-- The oldest account becomes the administrator; later ones register as regular users
+
+ALTER TABLE users ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT 0;
+
+UPDATE users SET is_admin = 1 WHERE id = (SELECT MIN(id) FROM users);
+
+-- Replaces ui_settings.last_topology_id, which every account shared
+CREATE TABLE IF NOT EXISTS user_settings (
+    user_id INTEGER PRIMARY KEY,
+    last_topology_id INTEGER,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (last_topology_id) REFERENCES topologies(id) ON DELETE SET NULL
+);
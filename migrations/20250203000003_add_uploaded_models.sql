@@ -0,0 +1,14 @@
+-- Who uploaded each model in models.upload_dir; only they and administrators may
+-- replace or delete it. Uploads from before this table existed have no row.
+
+CREATE TABLE IF NOT EXISTS uploaded_models (
+    node_type TEXT NOT NULL,
+    vendor TEXT NOT NULL,
+    name TEXT NOT NULL,                   -- File name without .glb
+    user_id INTEGER,                      -- NULL once the uploader's account is deleted
+    created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    PRIMARY KEY (node_type, vendor, name),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_uploaded_models_vendor ON uploaded_models(vendor);
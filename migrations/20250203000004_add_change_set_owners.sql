@@ -0,0 +1,10 @@
+-- Open change sets belong to the session that began them, so edits from other
+-- viewers of the topology start their own undo steps instead of joining it.
+-- owner: the session or API token key that began the set ('' for the local user)
+-- last_used_at: open sets idle for longer than the server's timeout are closed
+
+ALTER TABLE undo_change_sets ADD COLUMN owner TEXT NOT NULL DEFAULT '';
+ALTER TABLE undo_change_sets ADD COLUMN last_used_at INTEGER NOT NULL DEFAULT 0;
+
+-- Sets left open before owners were recorded can't be matched to anyone
+UPDATE undo_change_sets SET is_open = FALSE WHERE is_open = TRUE;
@@ -0,0 +1,22 @@
+-- Both ends of a connection must be nodes of the connection's own topology, like a
+-- node's group and a tag's items. (A port's node is checked by check_connection_interfaces.)
+
+CREATE TRIGGER check_connection_nodes_insert
+BEFORE INSERT ON connections
+FOR EACH ROW
+BEGIN
+    SELECT RAISE(ABORT, 'source node belongs to another topology')
+    WHERE NOT EXISTS (SELECT 1 FROM nodes WHERE id = NEW.source_node_id AND topology_id = NEW.topology_id);
+    SELECT RAISE(ABORT, 'target node belongs to another topology')
+    WHERE NOT EXISTS (SELECT 1 FROM nodes WHERE id = NEW.target_node_id AND topology_id = NEW.topology_id);
+END;
+
+CREATE TRIGGER check_connection_nodes_update
+BEFORE UPDATE OF topology_id, source_node_id, target_node_id ON connections
+FOR EACH ROW
+BEGIN
+    SELECT RAISE(ABORT, 'source node belongs to another topology')
+    WHERE NOT EXISTS (SELECT 1 FROM nodes WHERE id = NEW.source_node_id AND topology_id = NEW.topology_id);
+    SELECT RAISE(ABORT, 'target node belongs to another topology')
+    WHERE NOT EXISTS (SELECT 1 FROM nodes WHERE id = NEW.target_node_id AND topology_id = NEW.topology_id);
+END;
@@ -0,0 +1,460 @@
+-- PostgreSQL schema, equivalent to the SQLite migrations in the parent directory up to
+-- 20250201000001_add_audit_log. Later schema changes need a migration in both places.
+--
+-- Timestamps are Unix seconds (BIGINT) like on SQLite; enum columns are checked with
+-- CHECK constraints, cross-row rules with triggers raising the same messages.
+
+CREATE FUNCTION unix_now() RETURNS BIGINT AS $$
+    SELECT EXTRACT(EPOCH FROM now())::BIGINT
+$$ LANGUAGE SQL STABLE;
+
+-- Topologies, nodes and connections
+
+CREATE TABLE topologies (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    created_at BIGINT NOT NULL DEFAULT unix_now(),
+    updated_at BIGINT NOT NULL DEFAULT unix_now()
+);
+
+CREATE TABLE node_groups (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    topology_id BIGINT NOT NULL REFERENCES topologies(id) ON DELETE CASCADE,
+    parent_id BIGINT REFERENCES node_groups(id) ON DELETE SET NULL,  -- NULL = top level
+    name TEXT NOT NULL,
+    group_type TEXT NOT NULL DEFAULT 'site' CHECK (group_type IN ('site', 'building', 'rack', 'zone')),
+    color TEXT NOT NULL DEFAULT '120,160,220',
+    collapsed BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at BIGINT NOT NULL DEFAULT unix_now(),
+    updated_at BIGINT NOT NULL DEFAULT unix_now()
+);
+
+CREATE INDEX idx_node_groups_topology ON node_groups(topology_id);
+CREATE INDEX idx_node_groups_parent ON node_groups(parent_id);
+
+CREATE TABLE nodes (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    topology_id BIGINT NOT NULL REFERENCES topologies(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    node_type TEXT NOT NULL DEFAULT 'host' CHECK (node_type IN (
+        'host', 'router', 'switch', 'server', 'firewall', 'load_balancer', 'cloud', 'application'
+    )),
+    vendor TEXT NOT NULL DEFAULT 'generic',
+    model_name TEXT NOT NULL DEFAULT 'blob-router',
+    ip_address TEXT,
+    position_x DOUBLE PRECISION DEFAULT 0.0,
+    position_y DOUBLE PRECISION DEFAULT 0.0,
+    position_z DOUBLE PRECISION DEFAULT 0.0,
+    rotation_x DOUBLE PRECISION NOT NULL DEFAULT 0.0,
+    rotation_y DOUBLE PRECISION NOT NULL DEFAULT 0.0,
+    rotation_z DOUBLE PRECISION NOT NULL DEFAULT 0.0,
+    scale DOUBLE PRECISION NOT NULL DEFAULT 1.0,
+    color TEXT NOT NULL DEFAULT '100,150,255',
+    visible BOOLEAN NOT NULL DEFAULT TRUE,
+    group_id BIGINT REFERENCES node_groups(id) ON DELETE SET NULL,
+    metadata TEXT,
+    created_at BIGINT NOT NULL DEFAULT unix_now(),
+    updated_at BIGINT NOT NULL DEFAULT unix_now()
+);
+
+CREATE INDEX idx_nodes_topology ON nodes(topology_id);
+CREATE INDEX idx_nodes_visible ON nodes(visible);
+CREATE INDEX idx_nodes_group ON nodes(group_id);
+
+CREATE TABLE interfaces (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    node_id BIGINT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    speed_mbps BIGINT,
+    mac_address TEXT,
+    ip_address TEXT,
+    admin_state TEXT NOT NULL DEFAULT 'up' CHECK (admin_state IN ('up', 'down')),
+    description TEXT,
+    created_at BIGINT NOT NULL DEFAULT unix_now(),
+    updated_at BIGINT NOT NULL DEFAULT unix_now(),
+    UNIQUE (node_id, name)
+);
+
+CREATE INDEX idx_interfaces_node ON interfaces(node_id);
+
+CREATE TABLE connections (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    topology_id BIGINT NOT NULL REFERENCES topologies(id) ON DELETE CASCADE,
+    source_node_id BIGINT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    target_node_id BIGINT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    connection_type TEXT NOT NULL DEFAULT 'ethernet' CHECK (connection_type IN ('ethernet', 'fiber', 'wireless', 'vpn', 'wan')),
+    bandwidth_mbps BIGINT,
+    latency_ms DOUBLE PRECISION,
+    baseline_packet_loss_pct DOUBLE PRECISION NOT NULL DEFAULT 0.0
+        CHECK (baseline_packet_loss_pct >= 0.0 AND baseline_packet_loss_pct <= 10.0),
+    status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'inactive', 'degraded', 'error')),
+    color TEXT NOT NULL DEFAULT '128,128,128',
+    carries_traffic BOOLEAN NOT NULL DEFAULT TRUE,
+    flow_direction TEXT NOT NULL DEFAULT 'source_to_target'
+        CHECK (flow_direction IN ('source_to_target', 'target_to_source', 'bidirectional')),
+    source_interface_id BIGINT REFERENCES interfaces(id) ON DELETE SET NULL,
+    target_interface_id BIGINT REFERENCES interfaces(id) ON DELETE SET NULL,
+    metadata TEXT,
+    created_at BIGINT NOT NULL DEFAULT unix_now(),
+    updated_at BIGINT NOT NULL DEFAULT unix_now(),
+    CHECK (source_node_id != target_node_id)
+);
+
+CREATE INDEX idx_connections_topology ON connections(topology_id);
+CREATE INDEX idx_connections_source ON connections(source_node_id);
+CREATE INDEX idx_connections_target ON connections(target_node_id);
+CREATE INDEX idx_connections_source_interface ON connections(source_interface_id);
+CREATE INDEX idx_connections_target_interface ON connections(target_interface_id);
+
+CREATE FUNCTION touch_updated_at() RETURNS trigger AS $$
+BEGIN
+    NEW.updated_at := unix_now();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER update_topology_timestamp BEFORE UPDATE ON topologies
+FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+CREATE TRIGGER update_node_timestamp BEFORE UPDATE ON nodes
+FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+CREATE TRIGGER update_connection_timestamp BEFORE UPDATE ON connections
+FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+
+CREATE FUNCTION check_node_group_parent() RETURNS trigger AS $$
+BEGIN
+    IF NEW.parent_id IS NULL THEN
+        RETURN NEW;
+    END IF;
+    IF NEW.parent_id = NEW.id THEN
+        RAISE EXCEPTION 'a group cannot be its own parent';
+    END IF;
+    IF NOT EXISTS (SELECT 1 FROM node_groups WHERE id = NEW.parent_id AND topology_id = NEW.topology_id) THEN
+        RAISE EXCEPTION 'parent group belongs to another topology';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER check_node_group_parent_insert BEFORE INSERT ON node_groups
+FOR EACH ROW EXECUTE FUNCTION check_node_group_parent();
+CREATE TRIGGER check_node_group_parent_update BEFORE UPDATE OF parent_id ON node_groups
+FOR EACH ROW EXECUTE FUNCTION check_node_group_parent();
+
+CREATE FUNCTION check_node_group() RETURNS trigger AS $$
+BEGIN
+    IF NEW.group_id IS NOT NULL
+       AND NOT EXISTS (SELECT 1 FROM node_groups WHERE id = NEW.group_id AND topology_id = NEW.topology_id) THEN
+        RAISE EXCEPTION 'group belongs to another topology';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER check_node_group_insert BEFORE INSERT ON nodes
+FOR EACH ROW EXECUTE FUNCTION check_node_group();
+CREATE TRIGGER check_node_group_update BEFORE UPDATE OF group_id ON nodes
+FOR EACH ROW EXECUTE FUNCTION check_node_group();
+
+-- A connection's ports must belong to its end nodes, and a port carries one link
+CREATE FUNCTION check_connection_interfaces() RETURNS trigger AS $$
+BEGIN
+    IF NEW.source_interface_id IS NULL AND NEW.target_interface_id IS NULL THEN
+        RETURN NEW;
+    END IF;
+    IF NEW.source_interface_id IS NOT NULL
+       AND NOT EXISTS (SELECT 1 FROM interfaces WHERE id = NEW.source_interface_id AND node_id = NEW.source_node_id) THEN
+        RAISE EXCEPTION 'source interface does not belong to the source node';
+    END IF;
+    IF NEW.target_interface_id IS NOT NULL
+       AND NOT EXISTS (SELECT 1 FROM interfaces WHERE id = NEW.target_interface_id AND node_id = NEW.target_node_id) THEN
+        RAISE EXCEPTION 'target interface does not belong to the target node';
+    END IF;
+    IF NEW.source_interface_id = NEW.target_interface_id THEN
+        RAISE EXCEPTION 'a connection cannot use the same interface at both ends';
+    END IF;
+    IF EXISTS (
+        SELECT 1 FROM connections
+        WHERE id IS DISTINCT FROM NEW.id
+          AND (source_interface_id IN (NEW.source_interface_id, NEW.target_interface_id)
+               OR target_interface_id IN (NEW.source_interface_id, NEW.target_interface_id))
+    ) THEN
+        RAISE EXCEPTION 'interface is already used by another connection';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER check_connection_interfaces_insert BEFORE INSERT ON connections
+FOR EACH ROW EXECUTE FUNCTION check_connection_interfaces();
+CREATE TRIGGER check_connection_interfaces_update
+BEFORE UPDATE OF source_node_id, target_node_id, source_interface_id, target_interface_id ON connections
+FOR EACH ROW EXECUTE FUNCTION check_connection_interfaces();
+
+-- IP address management
+
+CREATE TABLE subnets (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    topology_id BIGINT NOT NULL REFERENCES topologies(id) ON DELETE CASCADE,
+    cidr TEXT NOT NULL,
+    name TEXT NOT NULL DEFAULT '',
+    gateway TEXT,
+    vlan_id BIGINT,
+    description TEXT,
+    created_at BIGINT NOT NULL DEFAULT unix_now(),
+    updated_at BIGINT NOT NULL DEFAULT unix_now(),
+    UNIQUE (topology_id, cidr)
+);
+
+CREATE INDEX idx_subnets_topology ON subnets(topology_id);
+
+-- Tags
+
+CREATE TABLE tags (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    topology_id BIGINT NOT NULL REFERENCES topologies(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    created_at BIGINT NOT NULL DEFAULT unix_now()
+);
+
+-- Tag names are unique per topology regardless of case (COLLATE NOCASE on SQLite)
+CREATE UNIQUE INDEX idx_tags_topology_name ON tags(topology_id, LOWER(name));
+CREATE INDEX idx_tags_topology ON tags(topology_id);
+
+CREATE TABLE item_tags (
+    tag_id BIGINT NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    node_id BIGINT REFERENCES nodes(id) ON DELETE CASCADE,
+    connection_id BIGINT REFERENCES connections(id) ON DELETE CASCADE,
+    CHECK ((node_id IS NULL) != (connection_id IS NULL))
+);
+
+CREATE UNIQUE INDEX idx_item_tags_node ON item_tags(node_id, tag_id) WHERE node_id IS NOT NULL;
+CREATE UNIQUE INDEX idx_item_tags_connection ON item_tags(connection_id, tag_id) WHERE connection_id IS NOT NULL;
+CREATE INDEX idx_item_tags_tag ON item_tags(tag_id);
+
+CREATE FUNCTION check_item_tags_topology() RETURNS trigger AS $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM tags t
+        WHERE t.id = NEW.tag_id
+          AND t.topology_id = COALESCE(
+              (SELECT topology_id FROM nodes WHERE id = NEW.node_id),
+              (SELECT topology_id FROM connections WHERE id = NEW.connection_id)
+          )
+    ) THEN
+        RAISE EXCEPTION 'tag belongs to another topology';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER check_item_tags_topology BEFORE INSERT ON item_tags
+FOR EACH ROW EXECUTE FUNCTION check_item_tags_topology();
+
+-- Traffic metrics
+
+CREATE TABLE traffic_metrics (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    node_id BIGINT NOT NULL REFERENCES nodes(id) ON DELETE CASCADE,
+    timestamp BIGINT NOT NULL DEFAULT unix_now(),
+    bytes_in BIGINT DEFAULT 0,
+    bytes_out BIGINT DEFAULT 0,
+    packets_in BIGINT DEFAULT 0,
+    packets_out BIGINT DEFAULT 0,
+    packet_loss_percent DOUBLE PRECISION DEFAULT 0.0,
+    cpu_usage_percent DOUBLE PRECISION,
+    memory_usage_percent DOUBLE PRECISION
+);
+
+CREATE INDEX idx_traffic_node ON traffic_metrics(node_id);
+CREATE INDEX idx_traffic_timestamp ON traffic_metrics(timestamp);
+
+CREATE TABLE connection_traffic_metrics (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    connection_id BIGINT NOT NULL REFERENCES connections(id) ON DELETE CASCADE,
+    timestamp BIGINT NOT NULL DEFAULT unix_now(),
+    throughput_mbps DOUBLE PRECISION DEFAULT 0.0,
+    packets_per_sec BIGINT DEFAULT 0,
+    latency_ms DOUBLE PRECISION DEFAULT 0.0,
+    packet_loss_pct DOUBLE PRECISION DEFAULT 0.0,
+    utilization_pct DOUBLE PRECISION DEFAULT 0.0,
+    bytes_transferred BIGINT DEFAULT 0,
+    packets_transferred BIGINT DEFAULT 0
+);
+
+CREATE INDEX idx_conn_traffic_connection ON connection_traffic_metrics(connection_id);
+CREATE INDEX idx_conn_traffic_timestamp ON connection_traffic_metrics(timestamp);
+CREATE INDEX idx_conn_traffic_conn_time ON connection_traffic_metrics(connection_id, timestamp);
+CREATE INDEX idx_conn_traffic_utilization ON connection_traffic_metrics(utilization_pct DESC, timestamp DESC);
+
+-- UI settings (single row)
+
+CREATE TABLE ui_settings (
+    id BIGINT PRIMARY KEY CHECK (id = 1),
+    show_grid BOOLEAN NOT NULL DEFAULT TRUE,
+    show_x_axis BOOLEAN NOT NULL DEFAULT TRUE,
+    show_y_axis BOOLEAN NOT NULL DEFAULT TRUE,
+    show_z_axis BOOLEAN NOT NULL DEFAULT TRUE,
+    ambient_intensity DOUBLE PRECISION NOT NULL DEFAULT 0.4,
+    key_light_intensity DOUBLE PRECISION NOT NULL DEFAULT 1.5,
+    fill_light_intensity DOUBLE PRECISION NOT NULL DEFAULT 0.6,
+    rim_light_intensity DOUBLE PRECISION NOT NULL DEFAULT 0.3,
+    use_environment_lighting BOOLEAN NOT NULL DEFAULT FALSE,
+    environment_map TEXT NOT NULL DEFAULT 'studio_small_09_2k.hdr',
+    last_topology_id BIGINT,
+    undo_depth BIGINT NOT NULL DEFAULT 100,
+    -- "YYYY-MM-DD HH:MM:SS" UTC text, as SQLite's CURRENT_TIMESTAMP
+    created_at TEXT NOT NULL DEFAULT to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS'),
+    updated_at TEXT NOT NULL DEFAULT to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS')
+);
+
+CREATE FUNCTION touch_ui_settings() RETURNS trigger AS $$
+BEGIN
+    NEW.updated_at := to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS');
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER update_ui_settings_timestamp BEFORE UPDATE ON ui_settings
+FOR EACH ROW EXECUTE FUNCTION touch_ui_settings();
+
+-- Image-based lighting defaults, as set by the SQLite migrations
+INSERT INTO ui_settings (id, ambient_intensity, key_light_intensity, fill_light_intensity, rim_light_intensity)
+VALUES (1, 1.0, 1.0, 0.0, 0.0);
+
+-- Undo/redo
+
+CREATE TABLE undo_change_sets (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    topology_id BIGINT NOT NULL REFERENCES topologies(id) ON DELETE CASCADE,
+    label TEXT NOT NULL,
+    is_open BOOLEAN NOT NULL DEFAULT FALSE,
+    undone BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at BIGINT NOT NULL DEFAULT unix_now()
+);
+
+CREATE INDEX idx_undo_change_sets_topology ON undo_change_sets(topology_id, undone, id);
+
+CREATE TABLE undo_history (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    topology_id BIGINT NOT NULL REFERENCES topologies(id) ON DELETE CASCADE,
+    change_set_id BIGINT REFERENCES undo_change_sets(id) ON DELETE CASCADE,
+    entity_type TEXT NOT NULL,
+    entity_id BIGINT NOT NULL,
+    action_type TEXT NOT NULL,
+    previous_state TEXT NOT NULL,
+    new_state TEXT,
+    timestamp TEXT NOT NULL DEFAULT to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS')
+);
+
+CREATE INDEX idx_undo_history_topology ON undo_history(topology_id, timestamp DESC);
+CREATE INDEX idx_undo_history_change_set ON undo_history(change_set_id);
+
+-- Revisions
+
+CREATE TABLE topology_revisions (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    topology_id BIGINT NOT NULL REFERENCES topologies(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    note TEXT,
+    snapshot TEXT NOT NULL,
+    node_count BIGINT NOT NULL DEFAULT 0,
+    connection_count BIGINT NOT NULL DEFAULT 0,
+    restored_from BIGINT REFERENCES topology_revisions(id) ON DELETE SET NULL,
+    created_at BIGINT NOT NULL DEFAULT unix_now()
+);
+
+CREATE INDEX idx_topology_revisions_topology ON topology_revisions(topology_id, created_at DESC);
+
+-- Users, sessions, API tokens and per-topology roles
+
+CREATE TABLE users (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    username TEXT NOT NULL,
+    password_hash TEXT NOT NULL,
+    created_at BIGINT NOT NULL DEFAULT unix_now()
+);
+
+CREATE UNIQUE INDEX idx_users_username ON users(LOWER(username));
+
+CREATE TABLE sessions (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    session_hash TEXT NOT NULL UNIQUE,
+    created_at BIGINT NOT NULL DEFAULT unix_now(),
+    expires_at BIGINT NOT NULL
+);
+
+CREATE INDEX idx_sessions_user ON sessions(user_id);
+
+CREATE TABLE api_tokens (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    token_prefix TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at BIGINT NOT NULL DEFAULT unix_now(),
+    last_used_at BIGINT
+);
+
+CREATE INDEX idx_api_tokens_user ON api_tokens(user_id);
+
+CREATE TABLE topology_permissions (
+    topology_id BIGINT NOT NULL REFERENCES topologies(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL CHECK (role IN ('owner', 'editor', 'viewer')),
+    created_at BIGINT NOT NULL DEFAULT unix_now(),
+    PRIMARY KEY (topology_id, user_id)
+);
+
+CREATE INDEX idx_topology_permissions_user ON topology_permissions(user_id);
+
+-- Audit log (append-only)
+
+CREATE TABLE audit_log (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    created_at BIGINT NOT NULL DEFAULT unix_now(),
+    user_id BIGINT,
+    actor TEXT NOT NULL,
+    topology_id BIGINT,
+    entity_type TEXT NOT NULL,
+    entity_id BIGINT,
+    action TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    before_state TEXT,
+    after_state TEXT
+);
+
+CREATE INDEX idx_audit_log_topology ON audit_log(topology_id, created_at);
+CREATE INDEX idx_audit_log_user ON audit_log(user_id, created_at);
+
+CREATE FUNCTION audit_log_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_no_update BEFORE UPDATE OR DELETE ON audit_log
+FOR EACH ROW EXECUTE FUNCTION audit_log_append_only();
+
+-- Full-text search
+--
+-- The SQLite build keeps an FTS5 table in sync with triggers; here search reads a view
+-- that builds each document's tsvector on the fly, names weighted above details.
+
+CREATE VIEW search_documents AS
+SELECT kind, item_id, topology_id, name, detail,
+       setweight(to_tsvector('simple', name), 'A') || setweight(to_tsvector('simple', detail), 'B') AS document
+FROM (
+    SELECT 'topology' AS kind, id AS item_id, id AS topology_id, name, COALESCE(description, '') AS detail
+    FROM topologies
+    UNION ALL
+    SELECT 'node', n.id, n.topology_id, n.name,
+           COALESCE(n.ip_address, '') || ' ' || n.vendor || ' ' || n.model_name || ' '
+               || COALESCE((SELECT string_agg(i.ip_address, ' ') FROM interfaces i WHERE i.node_id = n.id), '') || ' '
+               || COALESCE(n.metadata, '')
+    FROM nodes n
+    UNION ALL
+    SELECT 'connection', c.id, c.topology_id, '', COALESCE(c.metadata, '')
+    FROM connections c
+) documents;
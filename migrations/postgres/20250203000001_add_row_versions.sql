@@ -0,0 +1,5 @@
+-- Row versions for optimistic concurrency on nodes and connections (see the SQLite
+-- migration of the same name)
+
+ALTER TABLE nodes ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
+ALTER TABLE connections ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
@@ -0,0 +1,10 @@
+-- Administrators and per-user settings (see the SQLite migration of the same name)
+
+ALTER TABLE users ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE users SET is_admin = TRUE WHERE id = (SELECT MIN(id) FROM users);
+
+CREATE TABLE user_settings (
+    user_id BIGINT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    last_topology_id BIGINT REFERENCES topologies(id) ON DELETE SET NULL
+);
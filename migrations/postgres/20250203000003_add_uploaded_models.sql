@@ -0,0 +1,12 @@
+-- Who uploaded each model (see the SQLite migration of the same name)
+
+CREATE TABLE uploaded_models (
+    node_type TEXT NOT NULL,
+    vendor TEXT NOT NULL,
+    name TEXT NOT NULL,
+    user_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at BIGINT NOT NULL DEFAULT unix_now(),
+    PRIMARY KEY (node_type, vendor, name)
+);
+
+CREATE INDEX idx_uploaded_models_vendor ON uploaded_models(vendor);
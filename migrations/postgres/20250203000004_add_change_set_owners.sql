@@ -0,0 +1,6 @@
+-- Owner and idle time of open change sets (see the SQLite migration of the same name)
+
+ALTER TABLE undo_change_sets ADD COLUMN owner TEXT NOT NULL DEFAULT '';
+ALTER TABLE undo_change_sets ADD COLUMN last_used_at BIGINT NOT NULL DEFAULT 0;
+
+UPDATE undo_change_sets SET is_open = FALSE WHERE is_open = TRUE;
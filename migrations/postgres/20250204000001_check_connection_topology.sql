@@ -0,0 +1,19 @@
+-- Both ends of a connection must be in its topology (see the SQLite migration of the same name)
+
+CREATE FUNCTION check_connection_nodes() RETURNS trigger AS $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM nodes WHERE id = NEW.source_node_id AND topology_id = NEW.topology_id) THEN
+        RAISE EXCEPTION 'source node belongs to another topology';
+    END IF;
+    IF NOT EXISTS (SELECT 1 FROM nodes WHERE id = NEW.target_node_id AND topology_id = NEW.topology_id) THEN
+        RAISE EXCEPTION 'target node belongs to another topology';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER check_connection_nodes_insert BEFORE INSERT ON connections
+FOR EACH ROW EXECUTE FUNCTION check_connection_nodes();
+CREATE TRIGGER check_connection_nodes_update
+BEFORE UPDATE OF topology_id, source_node_id, target_node_id ON connections
+FOR EACH ROW EXECUTE FUNCTION check_connection_nodes();
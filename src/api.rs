@@ -1,34 +1,59 @@
 use crate::models::{
-    Connection, ConnectionTrafficMetric, CreateConnection, CreateNode, CreateTopology, Node,
-    Topology, TopologyFull, UISettings, UpdateConnection, UpdateNode, UpdateTopology,
-    UpdateUISettings, VendorListResponse,
+    ApiToken, AppError, AuditExportFormat, AuditFilter, AuditLogEntry, Connection, ConnectionTrafficMetric,
+    CreateConnection, CreateInterface, CreateNode, CreateNodeGroup, CreateRevision, CreateSubnet,
+    CreateTopology, Credentials, DiffSource, FilterMatches, ImportReport, Interface, IpamReport,
+    ModelValidation, NewApiToken, Node, NodeGroup, NodeType, RevisionPreview, Role, SearchHit,
+    Subnet, Tag, Topology, TopologyDiff, TopologyFull, TopologyPermission, TopologyRevision, UISettings,
+    UpdateConnection, UpdateInterface, UpdateNode, UpdateNodeGroup, UpdateSubnet, UpdateTopology,
+    UpdateError, UpdateUISettings, User, VendorListResponse,
 };
 use leptos::prelude::*;
+use leptos::server_fn::client::{browser::BrowserClient, Client};
+use leptos::server_fn::error::FromServerFnError;
+use leptos::server_fn::Bytes;
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "ssr")]
-use crate::models::{ModelInfo, VendorInfo};
+use crate::models::{
+    diff_topologies, group_subtree, group_tree, normalize_mac, normalize_tag_name,
+    parse_address, validate_group_name, validate_interface_ip, ConnectionStatus, ConnectionType,
+    FilterQuery, ImportRejection, IpConflict, IpHolder, IpPrefix, Rgb, SubnetMismatch,
+    SubnetUsage, TagAssignment, TopologyDocument, TrafficMetric, MAX_SEARCH_RESULTS,
+    validate_credentials, audit_diff, audit_log_to_csv, AuditAction, AuditEntity, LiveEvent,
+    MAX_AUDIT_ENTRIES, check_model_slug, check_vendor_icon, validate_glb, ModelUpload, ModelUploadReport,
+};
+#[cfg(feature = "ssr")]
+use crate::config::{AuthMode, NtbConfig};
+#[cfg(feature = "ssr")]
+use crate::db::{unix_now, Db, DbConnection, DbPool};
 #[cfg(feature = "ssr")]
-use sqlx::{FromRow, Row, SqlitePool};
+use sqlx::{FromRow, Row};
 
 /// Get all topologies from the database
 #[server(GetTopologies, "/api")]
-pub async fn get_topologies() -> Result<Vec<Topology>, ServerFnError> {
+pub async fn get_topologies() -> Result<Vec<Topology>, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
+        let user = require_user(&pool).await?;
+
+        // Only the topologies the user has a role on
         let topologies = sqlx::query_as::<_, Topology>(
-            "SELECT id, name, description, created_at, updated_at FROM topologies ORDER BY created_at DESC"
+            "SELECT t.id, t.name, t.description, t.created_at, t.updated_at
+             FROM topologies t
+             JOIN topology_permissions p ON p.topology_id = t.id AND p.user_id = $1
+             ORDER BY t.created_at DESC"
         )
+        .bind(user.id)
         .fetch_all(&pool)
         .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+        .map_err(|e| db_error("Database error", e))?;
 
         Ok(topologies)
     }
@@ -41,33 +66,48 @@ pub async fn get_topologies() -> Result<Vec<Topology>, ServerFnError> {
 
 /// Create a new topology
 #[server(CreateTopologyFn, "/api")]
-pub async fn create_topology(data: CreateTopology) -> Result<Topology, ServerFnError> {
+pub async fn create_topology(data: CreateTopology) -> Result<Topology, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_user(&pool).await?;
+
+        let mut tx = pool
+            .begin()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| db_error("Failed to start transaction", e))?;
 
-        let result = sqlx::query("INSERT INTO topologies (name, description) VALUES (?, ?)")
+        let id = sqlx::query_scalar::<_, i64>("INSERT INTO topologies (name, description) VALUES ($1, $2) RETURNING id")
             .bind(&data.name)
             .bind(&data.description)
-            .execute(&pool)
+            .fetch_one(&mut *tx)
             .await
-            .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+            .map_err(|e| db_error("Database error", e))?;
 
-        let id = result.last_insert_rowid();
+        grant_role(&mut tx, id, user.id, Role::Owner)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
 
         // Fetch the created topology
         let topology = sqlx::query_as::<_, Topology>(
-            "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = ?",
+            "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = $1",
         )
         .bind(id)
-        .fetch_one(&pool)
+        .fetch_one(&mut *tx)
         .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+        .map_err(|e| db_error("Database error", e))?;
+
+        record_audit(&mut *tx, &user, AuditRecord::topology(AuditAction::Create, None, Some(&topology))).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
 
         Ok(topology)
     }
@@ -79,53 +119,68 @@ pub async fn create_topology(data: CreateTopology) -> Result<Topology, ServerFnE
 }
 
 /// Update topology (name and/or description)
-#[server(UpdateTopologyFn, "/api")]
-pub async fn update_topology(id: i64, data: UpdateTopology) -> Result<Topology, ServerFnError> {
+#[server(UpdateTopologyFn, "/api", client = LiveClient)]
+pub async fn update_topology(id: i64, data: UpdateTopology) -> Result<Topology, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        // Build dynamic UPDATE query
-        let mut query_str = "UPDATE topologies SET updated_at = CURRENT_TIMESTAMP".to_string();
+        let user = require_role(&pool, id, Role::Editor).await?;
 
-        if data.name.is_some() {
-            query_str.push_str(", name = ?");
-        }
-        if data.description.is_some() {
-            query_str.push_str(", description = ?");
-        }
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
 
-        query_str.push_str(" WHERE id = ?");
+        let previous = sqlx::query_as::<_, Topology>(
+            "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::not_found(format!("Topology not found: {}", e)))?;
 
-        let mut query = sqlx::query(&query_str);
+        // Build dynamic UPDATE query
+        let mut query = sqlx::QueryBuilder::<Db>::new("UPDATE topologies SET updated_at = ");
+        query.push_bind(unix_now());
 
         if let Some(ref name) = data.name {
-            query = query.bind(name);
+            query.push(", name = ").push_bind(name);
         }
         if let Some(ref description) = data.description {
-            query = query.bind(description);
+            query.push(", description = ").push_bind(description);
         }
 
-        query = query.bind(id);
+        query.push(" WHERE id = ").push_bind(id);
 
         query
-            .execute(&pool)
+            .build()
+            .execute(&mut *tx)
             .await
-            .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+            .map_err(|e| db_error("Database error", e))?;
 
         // Fetch and return updated topology
         let topology = sqlx::query_as::<_, Topology>(
-            "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = ?",
+            "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = $1",
         )
         .bind(id)
-        .fetch_one(&pool)
+        .fetch_one(&mut *tx)
         .await
-        .map_err(|e| ServerFnError::new(format!("Topology not found: {}", e)))?;
+        .map_err(|e| AppError::not_found(format!("Topology not found: {}", e)))?;
+
+        let record = AuditRecord::topology(AuditAction::Update, Some(&previous), Some(&topology));
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(id, LiveEvent::TopologyChanged).await;
 
         Ok(topology)
     }
@@ -137,22 +192,45 @@ pub async fn update_topology(id: i64, data: UpdateTopology) -> Result<Topology,
 }
 
 /// Delete a topology
-#[server(DeleteTopology, "/api")]
-pub async fn delete_topology(id: i64) -> Result<(), ServerFnError> {
+#[server(DeleteTopology, "/api", client = LiveClient)]
+pub async fn delete_topology(id: i64) -> Result<(), AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_role(&pool, id, Role::Owner).await?;
+
+        let topology = sqlx::query_as::<_, Topology>(
+            "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| AppError::not_found(format!("Topology not found: {}", e)))?;
+
+        let mut tx = pool
+            .begin()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| db_error("Failed to start transaction", e))?;
 
-        sqlx::query("DELETE FROM topologies WHERE id = ?")
+        sqlx::query("DELETE FROM topologies WHERE id = $1")
             .bind(id)
-            .execute(&pool)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        record_audit(&mut *tx, &user, AuditRecord::topology(AuditAction::Delete, Some(&topology), None)).await?;
+
+        tx.commit()
             .await
-            .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(id, LiveEvent::TopologyDeleted).await;
 
         Ok(())
     }
@@ -163,52 +241,142 @@ pub async fn delete_topology(id: i64) -> Result<(), ServerFnError> {
     }
 }
 
-/// Get complete topology with all nodes and connections
-#[server(GetTopologyFull, "/api")]
-pub async fn get_topology_full(id: i64) -> Result<TopologyFull, ServerFnError> {
+/// Deep-copy a topology with its nodes, connections, groups and tags (and optionally traffic history)
+///
+/// Everything is copied in one transaction with fresh IDs. `name` defaults to "<name> (copy)".
+#[server(DuplicateTopology, "/api")]
+pub async fn duplicate_topology(
+    id: i64,
+    name: Option<String>,
+    include_traffic: bool,
+) -> Result<Topology, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
+        use std::collections::HashMap;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_role(&pool, id, Role::Viewer).await?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let source = load_topology_full(&mut tx, id).await?;
+        let name = name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("{} (copy)", source.topology.name));
+
+        let topology_id = sqlx::query_scalar::<_, i64>("INSERT INTO topologies (name, description) VALUES ($1, $2) RETURNING id")
+            .bind(&name)
+            .bind(&source.topology.description)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| db_error("Failed to create topology", e))?;
+        grant_role(&mut tx, topology_id, user.id, Role::Owner)
+            .await
+            .map_err(|e| db_error("Failed to create topology", e))?;
+
+        // Old ID -> new ID (groups first, so nodes can refer to them)
+        let mut group_id_map = HashMap::new();
+        for (group, _) in group_tree(&source.groups) {
+            let parent_id = group.parent_id.and_then(|id| group_id_map.get(&id).copied());
+            let new_id = insert_group_copy(&mut tx, topology_id, group, parent_id)
+                .await
+                .map_err(|e| db_error(format!("Failed to copy group '{}'", group.name), e))?;
+            group_id_map.insert(group.id, new_id);
+        }
+        let mut node_id_map = HashMap::new();
+        for node in &source.nodes {
+            let group_id = node.group_id.and_then(|id| group_id_map.get(&id).copied());
+            let new_id = insert_node_copy(&mut tx, topology_id, &Node { group_id, ..node.clone() })
+                .await
+                .map_err(|e| db_error(format!("Failed to copy node '{}'", node.name), e))?;
+            node_id_map.insert(node.id, new_id);
+        }
+        let mut interface_id_map = HashMap::new();
+        for interface in &source.interfaces {
+            let new_id = insert_interface_copy(&mut tx, node_id_map[&interface.node_id], interface)
+                .await
+                .map_err(|e| db_error(format!("Failed to copy interface '{}'", interface.name), e))?;
+            interface_id_map.insert(interface.id, new_id);
+        }
+        let mut connection_id_map = HashMap::new();
+        for connection in &source.connections {
+            // Dropping `tx` on the error rolls the partial copy back
+            let copy = remap_connection(connection, &node_id_map, &interface_id_map).ok_or_else(|| {
+                AppError::internal(format!("Connection {} ends outside topology {}", connection.id, id))
+            })?;
+            let new_id = insert_connection_copy(&mut tx, topology_id, &copy)
+                .await
+                .map_err(|e| db_error(format!("Failed to copy connection {}", connection.id), e))?;
+            connection_id_map.insert(connection.id, new_id);
+        }
+        for subnet in &source.subnets {
+            insert_subnet_copy(&mut tx, topology_id, subnet)
+                .await
+                .map_err(|e| db_error(format!("Failed to copy subnet {}", subnet.cidr), e))?;
+        }
+        insert_tag_copies(&mut tx, topology_id, &source.tags, &source.tag_assignments, &node_id_map, &connection_id_map)
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| db_error("Failed to copy tags", e))?;
+
+        if include_traffic {
+            for (old_id, new_id) in &node_id_map {
+                sqlx::query(
+                    "INSERT INTO traffic_metrics (node_id, timestamp, bytes_in, bytes_out, packets_in, packets_out, packet_loss_percent, cpu_usage_percent, memory_usage_percent)
+                     SELECT $1, timestamp, bytes_in, bytes_out, packets_in, packets_out, packet_loss_percent, cpu_usage_percent, memory_usage_percent
+                     FROM traffic_metrics WHERE node_id = $2 ORDER BY id"
+                )
+                .bind(new_id)
+                .bind(old_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| db_error("Failed to copy traffic history", e))?;
+            }
+            for (old_id, new_id) in &connection_id_map {
+                sqlx::query(
+                    "INSERT INTO connection_traffic_metrics (connection_id, timestamp, throughput_mbps, packets_per_sec, latency_ms, packet_loss_pct, utilization_pct, bytes_transferred, packets_transferred)
+                     SELECT $1, timestamp, throughput_mbps, packets_per_sec, latency_ms, packet_loss_pct, utilization_pct, bytes_transferred, packets_transferred
+                     FROM connection_traffic_metrics WHERE connection_id = $2 ORDER BY id"
+                )
+                .bind(new_id)
+                .bind(old_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| db_error("Failed to copy traffic history", e))?;
+            }
+        }
 
-        // Fetch topology
         let topology = sqlx::query_as::<_, Topology>(
-            "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = ?",
+            "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = $1",
         )
-        .bind(id)
-        .fetch_one(&pool)
+        .bind(topology_id)
+        .fetch_one(&mut *tx)
         .await
-        .map_err(|e| ServerFnError::new(format!("Topology not found: {}", e)))?;
-
-        // Fetch all nodes for this topology
-        let nodes = sqlx::query_as::<_, Node>(
-            "SELECT id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, metadata, created_at, updated_at
-             FROM nodes WHERE topology_id = ? ORDER BY created_at"
+        .map_err(|e| db_error("Database error", e))?;
+
+        let record = AuditRecord::new(
+            Some(topology_id),
+            AuditEntity::Topology,
+            Some(topology_id),
+            AuditAction::Create,
+            format!("Duplicate topology '{}' as '{}'", source.topology.name, topology.name),
         )
-        .bind(id)
-        .fetch_all(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+        .states(None, Some(&topology));
+        record_audit(&mut *tx, &user, record).await?;
 
-        // Fetch all connections for this topology
-        let connections = sqlx::query_as::<_, Connection>(
-            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, metadata, created_at, updated_at
-             FROM connections WHERE topology_id = ? ORDER BY created_at"
-        )
-        .bind(id)
-        .fetch_all(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
 
-        Ok(TopologyFull {
-            topology,
-            nodes,
-            connections,
-        })
+        Ok(topology)
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -217,32 +385,25 @@ pub async fn get_topology_full(id: i64) -> Result<TopologyFull, ServerFnError> {
     }
 }
 
-// ============================================================================
-// Node CRUD Operations
-// ============================================================================
-
-/// Get a single node by ID
-#[server(GetNode, "/api")]
-pub async fn get_node(id: i64) -> Result<Node, ServerFnError> {
+/// Get complete topology with all nodes and connections
+#[server(GetTopologyFull, "/api")]
+pub async fn get_topology_full(id: i64) -> Result<TopologyFull, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        let node = sqlx::query_as::<_, Node>(
-            "SELECT id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, metadata, created_at, updated_at
-             FROM nodes WHERE id = ?"
-        )
-        .bind(id)
-        .fetch_one(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Node not found: {}", e)))?;
+        require_role(&pool, id, Role::Viewer).await?;
 
-        Ok(node)
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        load_topology_full(&mut conn, id).await
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -251,247 +412,360 @@ pub async fn get_node(id: i64) -> Result<Node, ServerFnError> {
     }
 }
 
-/// Create a new node
-#[server(CreateNodeFn, "/api")]
-pub async fn create_node(data: CreateNode) -> Result<Node, ServerFnError> {
-    #[cfg(feature = "ssr")]
-    {
-        use axum::Extension;
-        use leptos_axum::extract;
-
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
-            .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
-
-        // Use default position if not provided
-        let pos_x = data.position_x.unwrap_or(0.0);
-        let pos_y = data.position_y.unwrap_or(0.0);
-        let pos_z = data.position_z.unwrap_or(0.0);
-        // Native Blender Z-up coordinate system (no rotation needed)
-        let rot_x = data.rotation_x.unwrap_or(0.0);
-        let rot_y = data.rotation_y.unwrap_or(0.0);
-        let rot_z = data.rotation_z.unwrap_or(0.0);
-        let scale = data.scale.unwrap_or(1.0);
-        let color = data.color.unwrap_or_else(|| "100,150,255".to_string()); // Default blue
-        let visible = data.visible.unwrap_or(true); // Default to visible
-        let vendor = data.vendor.unwrap_or_else(|| "generic".to_string());
-        let model_name = data
-            .model_name
-            .unwrap_or_else(|| format!("blob-{}", data.node_type));
-
-        let result = sqlx::query(
-            "INSERT INTO nodes (topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, metadata)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
-        )
-        .bind(data.topology_id)
-        .bind(&data.name)
-        .bind(&data.node_type)
-        .bind(&vendor)
-        .bind(&model_name)
-        .bind(&data.ip_address)
-        .bind(pos_x)
-        .bind(pos_y)
-        .bind(pos_z)
-        .bind(rot_x)
-        .bind(rot_y)
-        .bind(rot_z)
-        .bind(scale)
-        .bind(&color)
-        .bind(visible)
-        .bind(&data.metadata)
-        .execute(&pool)
+/// Load a topology with all of its nodes, interfaces, connections, subnets, groups and tags
+#[cfg(feature = "ssr")]
+async fn load_topology_full(
+    conn: &mut DbConnection,
+    id: i64,
+) -> Result<TopologyFull, AppError> {
+    // Fetch topology
+    let topology = sqlx::query_as::<_, Topology>(
+        "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| AppError::not_found(format!("Topology not found: {}", e)))?;
+
+    // Fetch all nodes for this topology
+    let nodes = sqlx::query_as::<_, Node>(
+        "SELECT id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, group_id, metadata, created_at, updated_at, version
+         FROM nodes WHERE topology_id = $1 ORDER BY created_at"
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| db_error("Database error", e))?;
+
+    // Fetch all connections for this topology
+    let connections = sqlx::query_as::<_, Connection>(
+        "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata, created_at, updated_at, version
+         FROM connections WHERE topology_id = $1 ORDER BY created_at"
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| db_error("Database error", e))?;
+
+    // Fetch the interfaces of those nodes
+    let interfaces = sqlx::query_as::<_, Interface>(&format!(
+        "SELECT {} FROM interfaces WHERE node_id IN (SELECT id FROM nodes WHERE topology_id = $1) ORDER BY node_id, id",
+        INTERFACE_COLUMNS
+    ))
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| db_error("Database error", e))?;
+
+    let subnets = fetch_topology_subnets(&mut *conn, id)
         .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
-
-        let id = result.last_insert_rowid();
-
-        // Fetch the created node
-        let node = sqlx::query_as::<_, Node>(
-            "SELECT id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, metadata, created_at, updated_at
-             FROM nodes WHERE id = ?"
-        )
-        .bind(id)
-        .fetch_one(&pool)
+        .map_err(|e| db_error("Database error", e))?;
+    let groups = fetch_topology_groups(&mut *conn, id)
         .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
-
-        Ok(node)
-    }
-
-    #[cfg(not(feature = "ssr"))]
-    {
-        unreachable!("Server function called on client")
-    }
+        .map_err(|e| db_error("Database error", e))?;
+    let tags = fetch_topology_tags(&mut *conn, id)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+    let tag_assignments = fetch_topology_tag_assignments(&mut *conn, id)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+
+    Ok(TopologyFull {
+        topology,
+        nodes,
+        connections,
+        interfaces,
+        subnets,
+        groups,
+        tags,
+        tag_assignments,
+    })
 }
 
-/// Update an existing node
-#[server(UpdateNodeFn, "/api")]
-pub async fn update_node(id: i64, data: UpdateNode) -> Result<Node, ServerFnError> {
+// ============================================================================
+// Topology Import
+// ============================================================================
+
+/// Import a topology from an exported `TopologyDocument` in a single transaction
+///
+/// The whole document is validated before anything is written. Invalid nodes,
+/// connections, groups and tags are skipped and listed in the report; if an insert fails, nothing is kept.
+#[server(ImportTopology, "/api")]
+pub async fn import_topology(json_content: String) -> Result<ImportReport, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
+        use std::collections::{HashMap, HashSet};
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
-            .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
-
-        // Get topology_id for undo history
-        let topology_id: i64 = sqlx::query_scalar("SELECT topology_id FROM nodes WHERE id = ?")
-            .bind(id)
-            .fetch_one(&pool)
-            .await
-            .map_err(|e| ServerFnError::new(format!("Node not found: {}", e)))?;
-
-        // Save undo history before updating
-        save_node_undo_history(&pool, id, topology_id)
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to save undo history: {}", e)))?;
-
-        // Build dynamic UPDATE query based on which fields are provided
-        let mut updates = Vec::new();
-        let mut query_str = "UPDATE nodes SET ".to_string();
-
-        if let Some(ref name) = data.name {
-            updates.push(("name = ?", name.clone()));
-        }
-        if let Some(ref node_type) = data.node_type {
-            updates.push(("node_type = ?", node_type.clone()));
-        }
-        if data.position_x.is_some() || data.position_y.is_some() || data.position_z.is_some() {
-            if let Some(pos_x) = data.position_x {
-                updates.push(("position_x = ?", pos_x.to_string()));
-            }
-            if let Some(pos_y) = data.position_y {
-                updates.push(("position_y = ?", pos_y.to_string()));
-            }
-            if let Some(pos_z) = data.position_z {
-                updates.push(("position_z = ?", pos_z.to_string()));
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_user(&pool).await?;
+
+        // Older exports are migrated to the current format before validation; unknown
+        // enum values are imported as their defaults and reported as warnings
+        let (document, source_format_version, warnings) =
+            TopologyDocument::from_json_with_warnings(&json_content).map_err(AppError::invalid)?;
+        let view_settings = document.view_settings.clone();
+        let camera = document.camera;
+        let data = document.into_topology_full();
+
+        let mut rejected = Vec::new();
+
+        // Validate subnets (one per prefix)
+        let mut seen_cidrs = HashSet::new();
+        let mut subnets = Vec::new();
+        for subnet in &data.subnets {
+            let result = validate_subnet(&subnet.cidr, subnet.gateway.as_deref(), subnet.vlan_id).and_then(|prefix| {
+                if seen_cidrs.insert(prefix) {
+                    Ok(prefix)
+                } else {
+                    Err(format!("Duplicate subnet {}", prefix))
+                }
+            });
+            match result {
+                Ok(prefix) => subnets.push(Subnet { cidr: prefix.to_string(), ..subnet.clone() }),
+                Err(reason) => rejected.push(ImportRejection {
+                    entity_type: "subnet".to_string(),
+                    source_id: subnet.id,
+                    reason,
+                }),
             }
         }
 
-        if updates.is_empty() && data.ip_address.is_none() && data.visible.is_none() && data.metadata.is_none() {
-            return Err(ServerFnError::new("No fields to update"));
+        // Validate nodes (a repeated ID is rejected after its first occurrence). Addresses
+        // are checked against the subnets and each other, as `check_node_address` does.
+        let prefixes = subnet_prefixes(&subnets);
+        let mut taken_addresses = HashMap::new();
+        let mut seen_node_ids = HashSet::new();
+        let mut nodes = Vec::new();
+        for node in &data.nodes {
+            let result = if seen_node_ids.insert(node.id) {
+                validate_import_node(node).and_then(|()| {
+                    check_import_address(node.ip_address.as_deref(), node.id, &prefixes, &mut taken_addresses)
+                })
+            } else {
+                Err(format!("Duplicate node ID {}", node.id))
+            };
+            match result {
+                Ok(()) => nodes.push(node),
+                Err(reason) => rejected.push(ImportRejection {
+                    entity_type: "node".to_string(),
+                    source_id: node.id,
+                    reason,
+                }),
+            }
         }
 
-        // Simpler approach: just update all fields that are provided
-        query_str.push_str("updated_at = CURRENT_TIMESTAMP");
-
-        if data.name.is_some() {
-            query_str.push_str(", name = ?");
-        }
-        if data.node_type.is_some() {
-            query_str.push_str(", node_type = ?");
-        }
-        if data.vendor.is_some() {
-            query_str.push_str(", vendor = ?");
-        }
-        if data.model_name.is_some() {
-            query_str.push_str(", model_name = ?");
-        }
-        if data.ip_address.is_some() {
-            query_str.push_str(", ip_address = ?");
-        }
-        if data.position_x.is_some() {
-            query_str.push_str(", position_x = ?");
-        }
-        if data.position_y.is_some() {
-            query_str.push_str(", position_y = ?");
-        }
-        if data.position_z.is_some() {
-            query_str.push_str(", position_z = ?");
-        }
-        if data.rotation_x.is_some() {
-            query_str.push_str(", rotation_x = ?");
-        }
-        if data.rotation_y.is_some() {
-            query_str.push_str(", rotation_y = ?");
-        }
-        if data.rotation_z.is_some() {
-            query_str.push_str(", rotation_z = ?");
+        if nodes.is_empty() {
+            return Err(AppError::invalid(
+                "Topology must contain at least one valid node",
+            ));
         }
-        if data.scale.is_some() {
-            query_str.push_str(", scale = ?");
-        }
-        if data.color.is_some() {
-            query_str.push_str(", color = ?");
+
+        // Validate interfaces against the accepted nodes (names are unique per node)
+        let accepted_node_ids: HashSet<i64> = nodes.iter().map(|n| n.id).collect();
+        let mut seen_interface_names = HashSet::new();
+        let mut interface_nodes = HashMap::new(); // interface ID -> node ID
+        let mut interfaces = Vec::new();
+        for interface in &data.interfaces {
+            let result = if interface_nodes.contains_key(&interface.id) {
+                Err(format!("Duplicate interface ID {}", interface.id))
+            } else if !accepted_node_ids.contains(&interface.node_id) {
+                Err(format!("Node {} was not imported", interface.node_id))
+            } else if !seen_interface_names.insert((interface.node_id, interface.name.trim().to_string())) {
+                Err(format!("Duplicate interface name '{}' on node {}", interface.name, interface.node_id))
+            } else {
+                validate_interface(&interface.name, interface.mac_address.as_deref(), interface.ip_address.as_deref())
+                    .and_then(|()| {
+                        let address = interface.ip_address.as_deref();
+                        check_import_address(address, interface.node_id, &prefixes, &mut taken_addresses)
+                    })
+            };
+            match result {
+                Ok(()) => {
+                    interface_nodes.insert(interface.id, interface.node_id);
+                    interfaces.push(interface);
+                }
+                Err(reason) => rejected.push(ImportRejection {
+                    entity_type: "interface".to_string(),
+                    source_id: interface.id,
+                    reason,
+                }),
+            }
         }
-        if data.visible.is_some() {
-            query_str.push_str(", visible = ?");
+
+        // Validate connections against the nodes and interfaces that will actually be imported
+        let mut seen_connection_ids = HashSet::new();
+        let mut used_interfaces = HashSet::new();
+        let mut connections = Vec::new();
+        for connection in &data.connections {
+            let result = if seen_connection_ids.insert(connection.id) {
+                validate_import_connection(connection, &accepted_node_ids, &interface_nodes, &mut used_interfaces)
+            } else {
+                Err(format!("Duplicate connection ID {}", connection.id))
+            };
+            match result {
+                Ok(()) => connections.push(connection),
+                Err(reason) => rejected.push(ImportRejection {
+                    entity_type: "connection".to_string(),
+                    source_id: connection.id,
+                    reason,
+                }),
+            }
         }
-        if data.metadata.is_some() {
-            query_str.push_str(", metadata = ?");
+
+        // Validate groups (a parent or member group that wasn't imported is dropped)
+        let mut seen_group_ids = HashSet::new();
+        let mut groups = Vec::new();
+        for group in &data.groups {
+            let result = if seen_group_ids.insert(group.id) {
+                validate_group_name(&group.name)
+            } else {
+                Err(format!("Duplicate group ID {}", group.id))
+            };
+            match result {
+                Ok(()) => groups.push(group.clone()),
+                Err(reason) => rejected.push(ImportRejection {
+                    entity_type: "group".to_string(),
+                    source_id: group.id,
+                    reason,
+                }),
+            }
         }
 
-        query_str.push_str(" WHERE id = ?");
+        // Validate tags (assignments to items that weren't imported are dropped)
+        let mut seen_tag_ids = HashSet::new();
+        let mut tags = Vec::new();
+        for tag in &data.tags {
+            let result = if seen_tag_ids.insert(tag.id) {
+                normalize_tag_name(&tag.name)
+            } else {
+                Err(format!("Duplicate tag ID {}", tag.id))
+            };
+            match result {
+                Ok(name) => tags.push(Tag { name, ..tag.clone() }),
+                Err(reason) => rejected.push(ImportRejection {
+                    entity_type: "tag".to_string(),
+                    source_id: tag.id,
+                    reason,
+                }),
+            }
+        }
 
-        let mut query = sqlx::query(&query_str);
+        // Everything below runs in one transaction - dropping `tx` on error rolls it back
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
 
-        // Bind values in the same order
-        if let Some(ref name) = data.name {
-            query = query.bind(name);
-        }
-        if let Some(ref node_type) = data.node_type {
-            query = query.bind(node_type);
-        }
-        if let Some(ref vendor) = data.vendor {
-            query = query.bind(vendor);
-        }
-        if let Some(ref model_name) = data.model_name {
-            query = query.bind(model_name);
-        }
-        if let Some(ref ip) = data.ip_address {
-            query = query.bind(ip);
-        }
-        if let Some(pos_x) = data.position_x {
-            query = query.bind(pos_x);
-        }
-        if let Some(pos_y) = data.position_y {
-            query = query.bind(pos_y);
-        }
-        if let Some(pos_z) = data.position_z {
-            query = query.bind(pos_z);
-        }
-        if let Some(rot_x) = data.rotation_x {
-            query = query.bind(rot_x);
-        }
-        if let Some(rot_y) = data.rotation_y {
-            query = query.bind(rot_y);
-        }
-        if let Some(rot_z) = data.rotation_z {
-            query = query.bind(rot_z);
-        }
-        if let Some(scale) = data.scale {
-            query = query.bind(scale);
+        // Prefix the name to avoid confusion with the original topology
+        let topology_id = sqlx::query_scalar::<_, i64>("INSERT INTO topologies (name, description) VALUES ($1, $2) RETURNING id")
+            .bind(format!("Imported {}", data.topology.name))
+            .bind(&data.topology.description)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| db_error("Failed to create topology", e))?;
+        grant_role(&mut tx, topology_id, user.id, Role::Owner)
+            .await
+            .map_err(|e| db_error("Failed to create topology", e))?;
+
+        // Insert groups (parents first) and nodes, remapping document IDs to newly assigned IDs.
+        // A parent cycle in a hand-edited document is broken where it is first reached.
+        let mut group_id_map = HashMap::new();
+        for (group, _) in group_tree(&groups) {
+            let parent_id = group.parent_id.and_then(|id| group_id_map.get(&id).copied());
+            let new_id = insert_group_copy(&mut tx, topology_id, group, parent_id)
+                .await
+                .map_err(|e| db_error(format!("Failed to import group '{}'", group.name), e))?;
+            group_id_map.insert(group.id, new_id);
         }
-        if let Some(ref color) = data.color {
-            query = query.bind(color);
+
+        let mut node_id_map = HashMap::new();
+        for node in &nodes {
+            let group_id = node.group_id.and_then(|id| group_id_map.get(&id).copied());
+            let new_id = insert_node_copy(&mut tx, topology_id, &Node { group_id, ..(*node).clone() })
+                .await
+                .map_err(|e| db_error(format!("Failed to import node '{}'", node.name), e))?;
+            node_id_map.insert(node.id, new_id);
         }
-        if let Some(visible) = data.visible {
-            query = query.bind(visible);
+
+        let mut interface_id_map = HashMap::new();
+        for interface in &interfaces {
+            let new_id = insert_interface_copy(&mut tx, node_id_map[&interface.node_id], interface)
+                .await
+                .map_err(|e| db_error(format!("Failed to import interface '{}'", interface.name), e))?;
+            interface_id_map.insert(interface.id, new_id);
         }
-        if let Some(ref metadata) = data.metadata {
-            query = query.bind(metadata);
+
+        // Insert connections, keeping traffic flow settings and attached interfaces
+        let mut connection_id_map = HashMap::new();
+        for connection in &connections {
+            // Validation above dropped connections to rejected nodes and interfaces
+            let copy = remap_connection(connection, &node_id_map, &interface_id_map).ok_or_else(|| {
+                AppError::internal(format!("Connection {} ends at an entity that wasn't imported", connection.id))
+            })?;
+            let new_id = insert_connection_copy(&mut tx, topology_id, &copy)
+                .await
+                .map_err(|e| db_error(format!("Failed to import connection {}", connection.id), e))?;
+            connection_id_map.insert(connection.id, new_id);
         }
 
-        query = query.bind(id);
+        for subnet in &subnets {
+            insert_subnet_copy(&mut tx, topology_id, subnet)
+                .await
+                .map_err(|e| db_error(format!("Failed to import subnet {}", subnet.cidr), e))?;
+        }
 
-        query
-            .execute(&pool)
-            .await
-            .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+        let tags_created =
+            insert_tag_copies(&mut tx, topology_id, &tags, &data.tag_assignments, &node_id_map, &connection_id_map)
+                .await
+                .map_err(|e| db_error("Failed to import tags", e))?;
 
-        // Fetch the updated node
-        let node = sqlx::query_as::<_, Node>(
-            "SELECT id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, metadata, created_at, updated_at
-             FROM nodes WHERE id = ?"
+        let topology = sqlx::query_as::<_, Topology>(
+            "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = $1",
         )
-        .bind(id)
-        .fetch_one(&pool)
+        .bind(topology_id)
+        .fetch_one(&mut *tx)
         .await
-        .map_err(|e| ServerFnError::new(format!("Node not found: {}", e)))?;
+        .map_err(|e| db_error("Database error", e))?;
+
+        let record = AuditRecord::new(
+            Some(topology_id),
+            AuditEntity::Topology,
+            Some(topology_id),
+            AuditAction::Create,
+            format!(
+                "Import topology '{}' ({} nodes, {} connections)",
+                topology.name,
+                nodes.len(),
+                connections.len()
+            ),
+        )
+        .states(None, Some(&topology));
+        record_audit(&mut *tx, &user, record).await?;
 
-        Ok(node)
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit import", e))?;
+
+        Ok(ImportReport {
+            topology,
+            nodes_created: nodes.len(),
+            connections_created: connections.len(),
+            interfaces_created: interfaces.len(),
+            subnets_created: subnets.len(),
+            groups_created: groups.len(),
+            tags_created,
+            node_id_map: nodes.iter().map(|n| (n.id, node_id_map[&n.id])).collect(),
+            rejected,
+            warnings,
+            source_format_version,
+            view_settings,
+            camera,
+        })
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -500,59 +774,312 @@ pub async fn update_node(id: i64, data: UpdateNode) -> Result<Node, ServerFnErro
     }
 }
 
-/// Delete a node
-#[server(DeleteNode, "/api")]
-pub async fn delete_node(id: i64) -> Result<(), ServerFnError> {
+/// Insert a copy of a node into a topology, returning the new node ID
+///
+/// `node.group_id` must already refer to a group in the target topology.
+#[cfg(feature = "ssr")]
+async fn insert_node_copy(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    node: &Node,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query_scalar::<_, i64>(
+        "INSERT INTO nodes (topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, group_id, metadata)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17) RETURNING id"
+    )
+    .bind(topology_id)
+    .bind(&node.name)
+    .bind(node.node_type)
+    .bind(&node.vendor)
+    .bind(&node.model_name)
+    .bind(&node.ip_address)
+    .bind(node.position_x)
+    .bind(node.position_y)
+    .bind(node.position_z)
+    .bind(node.rotation_x)
+    .bind(node.rotation_y)
+    .bind(node.rotation_z)
+    .bind(node.scale)
+    .bind(node.color)
+    .bind(node.visible)
+    .bind(node.group_id)
+    .bind(&node.metadata)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(id)
+}
+
+/// Insert a copy of an interface onto a node, returning the new interface ID
+#[cfg(feature = "ssr")]
+async fn insert_interface_copy(
+    conn: &mut DbConnection,
+    node_id: i64,
+    interface: &Interface,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query_scalar::<_, i64>(
+        "INSERT INTO interfaces (node_id, name, speed_mbps, mac_address, ip_address, admin_state, description)
+         VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id",
+    )
+    .bind(node_id)
+    .bind(interface.name.trim())
+    .bind(interface.speed_mbps)
+    .bind(&interface.mac_address)
+    .bind(&interface.ip_address)
+    .bind(interface.admin_state)
+    .bind(&interface.description)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(id)
+}
+
+/// A connection with its endpoints and interfaces moved to their copies
+///
+/// `None` if an endpoint or interface wasn't copied (e.g. a connection that reaches
+/// into another topology), so the caller can fail instead of indexing a missing ID.
+#[cfg(feature = "ssr")]
+fn remap_connection(
+    connection: &Connection,
+    node_id_map: &std::collections::HashMap<i64, i64>,
+    interface_id_map: &std::collections::HashMap<i64, i64>,
+) -> Option<Connection> {
+    let interface = |id: Option<i64>| match id {
+        Some(id) => interface_id_map.get(&id).map(|&id| Some(id)),
+        None => Some(None),
+    };
+    Some(Connection {
+        source_node_id: *node_id_map.get(&connection.source_node_id)?,
+        target_node_id: *node_id_map.get(&connection.target_node_id)?,
+        source_interface_id: interface(connection.source_interface_id)?,
+        target_interface_id: interface(connection.target_interface_id)?,
+        ..connection.clone()
+    })
+}
+
+/// Insert a copy of a connection into a topology, returning the new ID
+///
+/// Its endpoints and interfaces must already refer to the target topology (see
+/// `remap_connection`).
+#[cfg(feature = "ssr")]
+async fn insert_connection_copy(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    connection: &Connection,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query_scalar::<_, i64>(
+        "INSERT INTO connections (topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14) RETURNING id"
+    )
+    .bind(topology_id)
+    .bind(connection.source_node_id)
+    .bind(connection.target_node_id)
+    .bind(connection.connection_type)
+    .bind(connection.bandwidth_mbps)
+    .bind(connection.latency_ms)
+    .bind(connection.baseline_packet_loss_pct.unwrap_or(0.0))
+    .bind(connection.status)
+    .bind(connection.color)
+    .bind(connection.carries_traffic)
+    .bind(connection.flow_direction)
+    .bind(connection.source_interface_id)
+    .bind(connection.target_interface_id)
+    .bind(&connection.metadata)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(id)
+}
+
+/// Check a node from an imported document before it is inserted
+#[cfg(feature = "ssr")]
+fn validate_import_node(node: &Node) -> Result<(), String> {
+    if node.name.trim().is_empty() {
+        return Err("Node name is empty".to_string());
+    }
+
+    let transform = [
+        node.position_x,
+        node.position_y,
+        node.position_z,
+        node.rotation_x,
+        node.rotation_y,
+        node.rotation_z,
+        node.scale,
+    ];
+    if transform.iter().any(|v| !v.is_finite()) {
+        return Err(format!("Node '{}' has a non-finite position, rotation or scale", node.name));
+    }
+    if node.scale <= 0.0 {
+        return Err(format!("Node '{}' has non-positive scale {}", node.name, node.scale));
+    }
+
+    Ok(())
+}
+
+/// Check a connection from an imported document against the accepted node IDs
+#[cfg(feature = "ssr")]
+fn validate_import_connection(
+    connection: &Connection,
+    node_ids: &std::collections::HashSet<i64>,
+    interface_nodes: &std::collections::HashMap<i64, i64>,
+    used_interfaces: &mut std::collections::HashSet<i64>,
+) -> Result<(), String> {
+    if !node_ids.contains(&connection.source_node_id) {
+        return Err(format!("Source node {} was not imported", connection.source_node_id));
+    }
+    if !node_ids.contains(&connection.target_node_id) {
+        return Err(format!("Target node {} was not imported", connection.target_node_id));
+    }
+    if connection.source_node_id == connection.target_node_id {
+        return Err("Connection links a node to itself".to_string());
+    }
+    if let Some(loss) = connection.baseline_packet_loss_pct {
+        if !(0.0..=10.0).contains(&loss) {
+            return Err(format!("baseline_packet_loss_pct {} is outside 0.0-10.0", loss));
+        }
+    }
+
+    let ends = [
+        ("Source", connection.source_interface_id, connection.source_node_id),
+        ("Target", connection.target_interface_id, connection.target_node_id),
+    ];
+    for (end, interface_id, node_id) in ends {
+        let Some(interface_id) = interface_id else { continue };
+        match interface_nodes.get(&interface_id) {
+            None => return Err(format!("{} interface {} was not imported", end, interface_id)),
+            Some(owner) if *owner != node_id => {
+                return Err(format!("{} interface {} does not belong to node {}", end, interface_id, node_id))
+            }
+            Some(_) => {}
+        }
+        if used_interfaces.contains(&interface_id) {
+            return Err(format!("Interface {} is already used by another connection", interface_id));
+        }
+    }
+    // Only claim the ports once the whole connection is accepted
+    used_interfaces.extend(connection.source_interface_id);
+    used_interfaces.extend(connection.target_interface_id);
+
+    Ok(())
+}
+
+// ============================================================================
+// Topology Revisions
+// ============================================================================
+//
+// Revisions are named checkpoints of a whole topology, stored as `TopologyDocument`
+// JSON so older snapshots are migrated like imported files. Restoring never deletes
+// revisions: the current state is checkpointed first and the restore is recorded
+// as a new revision.
+
+/// Columns selected for `TopologyRevision` (everything except the snapshot)
+#[cfg(feature = "ssr")]
+const REVISION_COLUMNS: &str =
+    "id, topology_id, name, note, node_count, connection_count, restored_from, created_at";
+
+/// Save a topology state as a new revision
+#[cfg(feature = "ssr")]
+async fn insert_revision(
+    conn: &mut DbConnection,
+    full: &TopologyFull,
+    name: &str,
+    note: Option<&str>,
+    restored_from: Option<i64>,
+) -> Result<TopologyRevision, AppError> {
+    let snapshot = serde_json::to_string(&TopologyDocument::new(full.clone(), None, None))
+        .map_err(|e| AppError::internal(format!("JSON serialization error: {}", e)))?;
+
+    let id = sqlx::query_scalar::<_, i64>(
+        "INSERT INTO topology_revisions (topology_id, name, note, snapshot, node_count, connection_count, restored_from)
+         VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id"
+    )
+    .bind(full.topology.id)
+    .bind(name)
+    .bind(note)
+    .bind(snapshot)
+    .bind(full.nodes.len() as i64)
+    .bind(full.connections.len() as i64)
+    .bind(restored_from)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| db_error("Failed to create revision", e))?;
+
+    fetch_revision(conn, id).await
+}
+
+/// Get a revision's metadata by ID
+#[cfg(feature = "ssr")]
+async fn fetch_revision(
+    conn: &mut DbConnection,
+    id: i64,
+) -> Result<TopologyRevision, AppError> {
+    sqlx::query_as::<_, TopologyRevision>(&format!(
+        "SELECT {} FROM topology_revisions WHERE id = $1",
+        REVISION_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| AppError::not_found(format!("Revision not found: {}", e)))
+}
+
+/// Get a revision along with its decoded snapshot
+#[cfg(feature = "ssr")]
+async fn fetch_revision_preview(
+    conn: &mut DbConnection,
+    id: i64,
+) -> Result<RevisionPreview, AppError> {
+    let revision = fetch_revision(conn, id).await?;
+    let snapshot: String = sqlx::query_scalar("SELECT snapshot FROM topology_revisions WHERE id = $1")
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+    let document = TopologyDocument::from_json(&snapshot)
+        .map_err(|e| AppError::internal(format!("Revision snapshot is unreadable: {}", e)))?;
+
+    Ok(RevisionPreview {
+        revision,
+        snapshot: document.into_topology_full(),
+    })
+}
+
+/// Checkpoint the current state of a topology as a named revision
+#[server(CreateTopologyRevision, "/api")]
+pub async fn create_topology_revision(data: CreateRevision) -> Result<TopologyRevision, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        // Get topology_id and save undo history before deleting
-        let topology_id: i64 = sqlx::query_scalar("SELECT topology_id FROM nodes WHERE id = ?")
-            .bind(id)
-            .fetch_one(&pool)
-            .await
-            .map_err(|e| ServerFnError::new(format!("Node not found: {}", e)))?;
+        let user = require_role(&pool, data.topology_id, Role::Editor).await?;
 
-        // Get the node before deletion
-        let node = sqlx::query_as::<_, Node>(
-            "SELECT id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, metadata, created_at, updated_at
-             FROM nodes WHERE id = ?"
-        )
-        .bind(id)
-        .fetch_one(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Node not found: {}", e)))?;
+        let name = data.name.trim();
+        if name.is_empty() {
+            return Err(AppError::invalid("Revision name cannot be empty"));
+        }
+        let note = data.note.as_deref().map(str::trim).filter(|note| !note.is_empty());
 
-        // Serialize node to JSON for undo
-        let previous_state = serde_json::to_string(&node)
-            .map_err(|e| ServerFnError::new(format!("JSON serialization error: {}", e)))?;
+        // Read and write in one transaction so the snapshot is consistent
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
 
-        // Insert into undo_history with action_type = 'delete'
-        sqlx::query(
-            "INSERT INTO undo_history (topology_id, entity_type, entity_id, action_type, previous_state)
-             VALUES (?, 'node', ?, 'delete', ?)"
-        )
-        .bind(topology_id)
-        .bind(id)
-        .bind(previous_state)
-        .execute(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Failed to save undo history: {}", e)))?;
+        let full = load_topology_full(&mut tx, data.topology_id).await?;
+        let revision = insert_revision(&mut tx, &full, name, note, None).await?;
+        record_audit(&mut *tx, &user, AuditRecord::revision(AuditAction::Create, &revision)).await?;
 
-        // Now delete the node
-        sqlx::query("DELETE FROM nodes WHERE id = ?")
-            .bind(id)
-            .execute(&pool)
+        tx.commit()
             .await
-            .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
 
-        Ok(())
+        Ok(revision)
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -561,32 +1088,30 @@ pub async fn delete_node(id: i64) -> Result<(), ServerFnError> {
     }
 }
 
-// ============================================================================
-// Connection CRUD Operations
-// ============================================================================
-
-/// Get a single connection by ID
-#[server(GetConnection, "/api")]
-pub async fn get_connection(id: i64) -> Result<Connection, ServerFnError> {
+/// List the revisions of a topology, newest first
+#[server(GetTopologyRevisions, "/api")]
+pub async fn get_topology_revisions(topology_id: i64) -> Result<Vec<TopologyRevision>, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        let connection = sqlx::query_as::<_, Connection>(
-            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, metadata, created_at, updated_at
-             FROM connections WHERE id = ?"
-        )
-        .bind(id)
-        .fetch_one(&pool)
+        require_role(&pool, topology_id, Role::Viewer).await?;
+
+        let revisions = sqlx::query_as::<_, TopologyRevision>(&format!(
+            "SELECT {} FROM topology_revisions WHERE topology_id = $1 ORDER BY id DESC",
+            REVISION_COLUMNS
+        ))
+        .bind(topology_id)
+        .fetch_all(&pool)
         .await
-        .map_err(|e| ServerFnError::new(format!("Connection not found: {}", e)))?;
+        .map_err(|e| db_error("Database error", e))?;
 
-        Ok(connection)
+        Ok(revisions)
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -595,56 +1120,25 @@ pub async fn get_connection(id: i64) -> Result<Connection, ServerFnError> {
     }
 }
 
-/// Create a new connection
-#[server(CreateConnectionFn, "/api")]
-pub async fn create_connection(data: CreateConnection) -> Result<Connection, ServerFnError> {
+/// Get a revision with the topology state it captured, without restoring it
+#[server(GetTopologyRevision, "/api")]
+pub async fn get_topology_revision(id: i64) -> Result<RevisionPreview, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
-
-        // Use defaults if not provided
-        let conn_type = data
-            .connection_type
-            .unwrap_or_else(|| "ethernet".to_string());
-        let status = data.status.unwrap_or_else(|| "active".to_string());
-        let color = data.color.unwrap_or_else(|| "128,128,128".to_string());
-
-        let result = sqlx::query(
-            "INSERT INTO connections (topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, metadata)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
-        )
-        .bind(data.topology_id)
-        .bind(data.source_node_id)
-        .bind(data.target_node_id)
-        .bind(&conn_type)
-        .bind(&data.bandwidth_mbps)
-        .bind(&data.latency_ms)
-        .bind(&data.baseline_packet_loss_pct)
-        .bind(&status)
-        .bind(&color)
-        .bind(&data.metadata)
-        .execute(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        let id = result.last_insert_rowid();
+        require_item_role(&pool, TopologyItem::Revision(id), Role::Viewer).await?;
 
-        // Fetch the created connection
-        let connection = sqlx::query_as::<_, Connection>(
-            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, metadata, created_at, updated_at
-             FROM connections WHERE id = ?"
-        )
-        .bind(id)
-        .fetch_one(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
-
-        Ok(connection)
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        fetch_revision_preview(&mut conn, id).await
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -653,116 +1147,253 @@ pub async fn create_connection(data: CreateConnection) -> Result<Connection, Ser
     }
 }
 
-/// Update an existing connection
-#[server(UpdateConnectionFn, "/api")]
-pub async fn update_connection(
-    id: i64,
-    data: UpdateConnection,
-) -> Result<Connection, ServerFnError> {
+/// Restore a topology's nodes and connections to a revision
+///
+/// The current state is checkpointed first, the restored state is recorded as a new
+/// revision (which is returned), and the restore is one undo step. Traffic history of
+/// the nodes and connections the revision still has is kept.
+#[server(RestoreTopologyRevision, "/api", client = LiveClient)]
+pub async fn restore_topology_revision(id: i64) -> Result<TopologyRevision, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
+        use std::collections::{BTreeMap, HashSet};
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        // Get topology_id for undo history
-        let topology_id: i64 =
-            sqlx::query_scalar("SELECT topology_id FROM connections WHERE id = ?")
-                .bind(id)
-                .fetch_one(&pool)
-                .await
-                .map_err(|e| ServerFnError::new(format!("Connection not found: {}", e)))?;
+        let user = require_item_role(&pool, TopologyItem::Revision(id), Role::Editor).await?;
 
-        // Save undo history before updating
-        save_connection_undo_history(&pool, id, topology_id)
+        let mut tx = pool
+            .begin()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to save undo history: {}", e)))?;
-
-        // Build dynamic UPDATE query
-        let mut query_str = "UPDATE connections SET updated_at = CURRENT_TIMESTAMP".to_string();
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        let RevisionPreview { revision, snapshot } = fetch_revision_preview(&mut tx, id).await?;
+        let topology_id = revision.topology_id;
+
+        // Keep the state being replaced
+        let current = load_topology_full(&mut tx, topology_id).await?;
+        insert_revision(
+            &mut tx,
+            &current,
+            &format!("Before restoring '{}'", revision.name),
+            None,
+            None,
+        )
+        .await?;
 
-        if let Some(_) = data.connection_type {
-            query_str.push_str(", connection_type = ?");
-        }
-        if let Some(_) = data.bandwidth_mbps {
-            query_str.push_str(", bandwidth_mbps = ?");
-        }
-        if let Some(_) = data.latency_ms {
-            query_str.push_str(", latency_ms = ?");
-        }
-        if let Some(_) = data.baseline_packet_loss_pct {
-            query_str.push_str(", baseline_packet_loss_pct = ?");
-        }
-        if let Some(_) = data.status {
-            query_str.push_str(", status = ?");
-        }
-        if let Some(_) = data.color {
-            query_str.push_str(", color = ?");
-        }
-        if let Some(_) = data.carries_traffic {
-            query_str.push_str(", carries_traffic = ?");
-        }
-        if let Some(_) = data.flow_direction {
-            query_str.push_str(", flow_direction = ?");
+        // Each session has at most one open change set; close this session's before the
+        // restore starts its own, leaving other sessions' in-progress edits alone
+        let owner = change_set_owner().await;
+        sqlx::query(
+            "UPDATE undo_change_sets SET is_open = FALSE WHERE topology_id = $1 AND is_open = TRUE AND owner = $2",
+        )
+        .bind(topology_id)
+        .bind(&owner)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+        let change_set_id = start_change_set(&mut tx, topology_id, &owner, &format!("Restore '{}'", revision.name), false)
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+
+        // Traffic history of the nodes and connections the revision brings back is kept
+        let node_ids: HashSet<i64> = snapshot.nodes.iter().map(|n| n.id).collect();
+        let connection_ids: HashSet<i64> = snapshot.connections.iter().map(|c| c.id).collect();
+        let node_metrics = sqlx::query_as::<_, TrafficMetric>(
+            "SELECT id, node_id, timestamp, bytes_in, bytes_out, packets_in, packets_out, packet_loss_percent, cpu_usage_percent, memory_usage_percent
+             FROM traffic_metrics WHERE node_id IN (SELECT id FROM nodes WHERE topology_id = $1) ORDER BY id"
+        )
+        .bind(topology_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| db_error("Failed to read traffic history", e))?;
+        let connection_metrics = sqlx::query_as::<_, ConnectionTrafficMetric>(
+            "SELECT id, connection_id, timestamp, throughput_mbps, packets_per_sec, latency_ms, packet_loss_pct, utilization_pct, bytes_transferred, packets_transferred
+             FROM connection_traffic_metrics WHERE connection_id IN (SELECT id FROM connections WHERE topology_id = $1) ORDER BY id"
+        )
+        .bind(topology_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| db_error("Failed to read traffic history", e))?;
+        let mut kept_node_metrics: BTreeMap<i64, Vec<TrafficMetric>> = BTreeMap::new();
+        for metric in node_metrics.into_iter().filter(|m| node_ids.contains(&m.node_id)) {
+            kept_node_metrics.entry(metric.node_id).or_default().push(metric);
         }
-        if let Some(_) = data.metadata {
-            query_str.push_str(", metadata = ?");
+        let mut kept_connection_metrics: BTreeMap<i64, Vec<ConnectionTrafficMetric>> = BTreeMap::new();
+        for metric in connection_metrics.into_iter().filter(|m| connection_ids.contains(&m.connection_id)) {
+            kept_connection_metrics.entry(metric.connection_id).or_default().push(metric);
         }
 
-        query_str.push_str(" WHERE id = ?");
-
-        let mut query = sqlx::query(&query_str);
+        // Replace nodes, interfaces, connections, groups, tags and subnets with the snapshot,
+        // keeping the original IDs (except for subnets, which nothing refers to). Everything
+        // deleted and created goes into the change set, so one undo brings the state back.
+        for node in &current.nodes {
+            record_node_delete(&mut tx, change_set_id, node)
+                .await
+                .map_err(|e| db_error("Failed to save undo history", e))?;
+            sqlx::query("DELETE FROM nodes WHERE id = $1")
+                .bind(node.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| db_error("Database error", e))?;
+        }
 
-        // Bind values in the same order
-        if let Some(ref conn_type) = data.connection_type {
-            query = query.bind(conn_type);
+        // Groups, subnets and the tag list are swapped as a whole. Their entries sit between
+        // the node deletes and creates, so undo puts them back before the old nodes that
+        // refer to them, and redo before the new ones.
+        replace_groups(&mut tx, topology_id, &snapshot.groups)
+            .await
+            .map_err(|e| db_error("Failed to restore groups", e))?;
+        let entry = UndoEntry::new("topology_groups", topology_id, "update", Some(&current.groups), Some(&snapshot.groups))?;
+        insert_undo_entry(&mut tx, topology_id, change_set_id, entry)
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+        replace_subnets(&mut tx, topology_id, &snapshot.subnets)
+            .await
+            .map_err(|e| db_error("Failed to restore subnets", e))?;
+        let entry = UndoEntry::new("topology_subnets", topology_id, "update", Some(&current.subnets), Some(&snapshot.subnets))?;
+        insert_undo_entry(&mut tx, topology_id, change_set_id, entry)
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+        let restored = replace_tags(&mut tx, topology_id, &snapshot.tags)
+            .await
+            .map_err(|e| db_error("Failed to restore tags", e))?;
+        if !restored {
+            return Err(AppError::conflict("A tag ID of the revision is now used by another topology"));
         }
-        if let Some(bw) = data.bandwidth_mbps {
-            query = query.bind(bw);
+        let entry = UndoEntry::new("topology_tags", topology_id, "update", Some(&current.tags), Some(&snapshot.tags))?;
+        insert_undo_entry(&mut tx, topology_id, change_set_id, entry)
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+
+        for node in &snapshot.nodes {
+            let node = Node { topology_id, ..node.clone() };
+            let restored = upsert_node(&mut tx, &node)
+                .await
+                .map_err(|e| db_error(format!("Failed to restore node '{}'", node.name), e))?;
+            if !restored {
+                return Err(AppError::conflict(format!(
+                    "Node ID {} of the revision is now used by another topology",
+                    node.id
+                )));
+            }
+            insert_undo_entry(&mut tx, topology_id, change_set_id, UndoEntry::node("create", None, Some(&node))?)
+                .await
+                .map_err(|e| db_error("Failed to save undo history", e))?;
         }
-        if let Some(latency) = data.latency_ms {
-            query = query.bind(latency);
+        let mut interfaces_by_node: BTreeMap<i64, Vec<Interface>> = BTreeMap::new();
+        for interface in &snapshot.interfaces {
+            interfaces_by_node.entry(interface.node_id).or_default().push(interface.clone());
         }
-        if let Some(packet_loss) = data.baseline_packet_loss_pct {
-            query = query.bind(packet_loss);
+        for (node_id, interfaces) in &interfaces_by_node {
+            for interface in interfaces {
+                let restored = upsert_interface(&mut tx, interface)
+                    .await
+                    .map_err(|e| db_error(format!("Failed to restore interface '{}'", interface.name), e))?;
+                if !restored {
+                    return Err(AppError::conflict(format!(
+                        "Interface ID {} of the revision is now used by another node",
+                        interface.id
+                    )));
+                }
+            }
+            let entry = UndoEntry::new("node_interfaces", *node_id, "create", None, Some(interfaces))?;
+            insert_undo_entry(&mut tx, topology_id, change_set_id, entry)
+                .await
+                .map_err(|e| db_error("Failed to save undo history", e))?;
         }
-        if let Some(ref status) = data.status {
-            query = query.bind(status);
+        for connection in &snapshot.connections {
+            let connection = Connection { topology_id, ..connection.clone() };
+            let restored = upsert_connection(&mut tx, &connection)
+                .await
+                .map_err(|e| db_error(format!("Failed to restore connection {}", connection.id), e))?;
+            if !restored {
+                return Err(AppError::conflict(format!(
+                    "Connection ID {} of the revision is now used by another topology",
+                    connection.id
+                )));
+            }
+            insert_undo_entry(
+                &mut tx,
+                topology_id,
+                change_set_id,
+                UndoEntry::connection("create", None, Some(&connection))?,
+            )
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
         }
-        if let Some(ref color) = data.color {
-            query = query.bind(color);
+        let mut node_tags: BTreeMap<i64, Vec<String>> = BTreeMap::new();
+        let mut connection_tags: BTreeMap<i64, Vec<String>> = BTreeMap::new();
+        for assignment in &snapshot.tag_assignments {
+            sqlx::query("INSERT INTO item_tags (tag_id, node_id, connection_id) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING")
+                .bind(assignment.tag_id)
+                .bind(assignment.node_id)
+                .bind(assignment.connection_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| db_error("Failed to restore tags", e))?;
+            let Some(tag) = snapshot.tags.iter().find(|tag| tag.id == assignment.tag_id) else {
+                continue;
+            };
+            match (assignment.node_id, assignment.connection_id) {
+                (Some(node_id), _) => node_tags.entry(node_id).or_default().push(tag.name.clone()),
+                (None, Some(connection_id)) => connection_tags.entry(connection_id).or_default().push(tag.name.clone()),
+                (None, None) => {}
+            }
         }
-        if let Some(carries_traffic) = data.carries_traffic {
-            query = query.bind(carries_traffic);
+        let tag_entries = node_tags
+            .iter()
+            .map(|(id, names)| ("node_tags", id, names))
+            .chain(connection_tags.iter().map(|(id, names)| ("connection_tags", id, names)));
+        for (entity_type, id, names) in tag_entries {
+            let entry = UndoEntry::new(entity_type, *id, "create", None, Some(names))?;
+            insert_undo_entry(&mut tx, topology_id, change_set_id, entry)
+                .await
+                .map_err(|e| db_error("Failed to save undo history", e))?;
         }
-        if let Some(ref flow_direction) = data.flow_direction {
-            query = query.bind(flow_direction);
+
+        // Put back the traffic history that went with the deleted rows
+        for (node_id, metrics) in &kept_node_metrics {
+            insert_node_traffic(&mut tx, metrics)
+                .await
+                .map_err(|e| db_error("Failed to restore traffic history", e))?;
+            let entry = UndoEntry::new("node_traffic", *node_id, "create", None, Some(metrics))?;
+            insert_undo_entry(&mut tx, topology_id, change_set_id, entry)
+                .await
+                .map_err(|e| db_error("Failed to save undo history", e))?;
         }
-        if let Some(ref metadata) = data.metadata {
-            query = query.bind(metadata);
+        for (connection_id, metrics) in &kept_connection_metrics {
+            insert_connection_traffic(&mut tx, metrics)
+                .await
+                .map_err(|e| db_error("Failed to restore traffic history", e))?;
+            let entry = UndoEntry::new("connection_traffic", *connection_id, "create", None, Some(metrics))?;
+            insert_undo_entry(&mut tx, topology_id, change_set_id, entry)
+                .await
+                .map_err(|e| db_error("Failed to save undo history", e))?;
         }
 
-        query = query.bind(id);
+        let restored = load_topology_full(&mut tx, topology_id).await?;
+        let new_revision = insert_revision(
+            &mut tx,
+            &restored,
+            &format!("Restored '{}'", revision.name),
+            revision.note.as_deref(),
+            Some(revision.id),
+        )
+        .await?;
+        record_audit(&mut *tx, &user, AuditRecord::revision(AuditAction::Restore, &revision)).await?;
 
-        query
-            .execute(&pool)
+        tx.commit()
             .await
-            .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
 
-        // Fetch the updated connection
-        let connection = sqlx::query_as::<_, Connection>(
-            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, metadata, created_at, updated_at
-             FROM connections WHERE id = ?"
-        )
-        .bind(id)
-        .fetch_one(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Connection not found: {}", e)))?;
+        publish_live(topology_id, LiveEvent::TopologyChanged).await;
 
-        Ok(connection)
+        Ok(new_revision)
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -771,57 +1402,36 @@ pub async fn update_connection(
     }
 }
 
-/// Delete a connection
-#[server(DeleteConnection, "/api")]
-pub async fn delete_connection(id: i64) -> Result<(), ServerFnError> {
+/// Delete a revision
+#[server(DeleteTopologyRevision, "/api")]
+pub async fn delete_topology_revision(id: i64) -> Result<(), AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
-            .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
-
-        // Get topology_id and save undo history before deleting
-        let topology_id: i64 = sqlx::query_scalar("SELECT topology_id FROM connections WHERE id = ?")
-            .bind(id)
-            .fetch_one(&pool)
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Connection not found: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        // Get the connection before deletion
-        let connection = sqlx::query_as::<_, Connection>(
-            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, metadata, created_at, updated_at
-             FROM connections WHERE id = ?"
-        )
-        .bind(id)
-        .fetch_one(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Connection not found: {}", e)))?;
+        let user = require_item_role(&pool, TopologyItem::Revision(id), Role::Editor).await?;
 
-        // Serialize connection to JSON for undo
-        let previous_state = serde_json::to_string(&connection)
-            .map_err(|e| ServerFnError::new(format!("JSON serialization error: {}", e)))?;
-
-        // Insert into undo_history with action_type = 'delete'
-        sqlx::query(
-            "INSERT INTO undo_history (topology_id, entity_type, entity_id, action_type, previous_state)
-             VALUES (?, 'connection', ?, 'delete', ?)"
-        )
-        .bind(topology_id)
-        .bind(id)
-        .bind(previous_state)
-        .execute(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Failed to save undo history: {}", e)))?;
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
 
-        // Now delete the connection
-        sqlx::query("DELETE FROM connections WHERE id = ?")
+        let revision = fetch_revision(&mut tx, id).await?;
+        sqlx::query("DELETE FROM topology_revisions WHERE id = $1")
             .bind(id)
-            .execute(&pool)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        record_audit(&mut *tx, &user, AuditRecord::revision(AuditAction::Delete, &revision)).await?;
+
+        tx.commit()
             .await
-            .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
 
         Ok(())
     }
@@ -832,42 +1442,54 @@ pub async fn delete_connection(id: i64) -> Result<(), ServerFnError> {
     }
 }
 
-/// Swap source and target nodes for a connection (reverse direction)
-#[server(SwapConnectionDirection, "/api")]
-pub async fn swap_connection_direction(id: i64) -> Result<Connection, ServerFnError> {
+// ============================================================================
+// Topology Diff
+// ============================================================================
+
+/// Load one side of a comparison
+#[cfg(feature = "ssr")]
+async fn load_diff_source(
+    conn: &mut DbConnection,
+    source: DiffSource,
+) -> Result<TopologyFull, AppError> {
+    match source.revision_id {
+        Some(revision_id) => {
+            let preview = fetch_revision_preview(conn, revision_id).await?;
+            if preview.revision.topology_id != source.topology_id {
+                return Err(AppError::invalid(format!(
+                    "Revision {} does not belong to topology {}",
+                    revision_id, source.topology_id
+                )));
+            }
+            Ok(preview.snapshot)
+        }
+        None => load_topology_full(conn, source.topology_id).await,
+    }
+}
+
+/// Structural diff between two topologies or revisions (base -> target)
+#[server(CompareTopologies, "/api")]
+pub async fn compare_topologies(base: DiffSource, target: DiffSource) -> Result<TopologyDiff, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        // Swap source_node_id and target_node_id
-        sqlx::query(
-            "UPDATE connections
-             SET source_node_id = target_node_id,
-                 target_node_id = source_node_id,
-                 updated_at = CURRENT_TIMESTAMP
-             WHERE id = ?",
-        )
-        .bind(id)
-        .execute(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+        require_role(&pool, base.topology_id, Role::Viewer).await?;
+        require_role(&pool, target.topology_id, Role::Viewer).await?;
 
-        // Fetch the updated connection
-        let connection = sqlx::query_as::<_, Connection>(
-            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, metadata, created_at, updated_at
-             FROM connections WHERE id = ?"
-        )
-        .bind(id)
-        .fetch_one(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Connection not found: {}", e)))?;
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        let base = load_diff_source(&mut conn, base).await?;
+        let target = load_diff_source(&mut conn, target).await?;
 
-        Ok(connection)
+        Ok(diff_topologies(&base, &target))
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -877,33 +1499,33 @@ pub async fn swap_connection_direction(id: i64) -> Result<Connection, ServerFnEr
 }
 
 // ============================================================================
-// UI Settings Functions
+// Node CRUD Operations
 // ============================================================================
 
-/// Get UI settings (single row, id=1)
-#[server(GetUISettings, "/api")]
-pub async fn get_ui_settings() -> Result<UISettings, ServerFnError> {
+/// Get a single node by ID
+#[server(GetNode, "/api")]
+pub async fn get_node(id: i64) -> Result<Node, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        let settings = sqlx::query_as::<_, UISettings>(
-            "SELECT id, show_grid, show_x_axis, show_y_axis, show_z_axis,
-                    ambient_intensity, key_light_intensity, fill_light_intensity, rim_light_intensity,
-                    use_environment_lighting, environment_map,
-                    created_at, updated_at
-             FROM ui_settings WHERE id = 1"
+        require_item_role(&pool, TopologyItem::Node(id), Role::Viewer).await?;
+
+        let node = sqlx::query_as::<_, Node>(
+            "SELECT id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, group_id, metadata, created_at, updated_at, version
+             FROM nodes WHERE id = $1"
         )
+        .bind(id)
         .fetch_one(&pool)
         .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+        .map_err(|e| AppError::not_found(format!("Node not found: {}", e)))?;
 
-        Ok(settings)
+        Ok(node)
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -912,90 +1534,244 @@ pub async fn get_ui_settings() -> Result<UISettings, ServerFnError> {
     }
 }
 
-/// Update UI settings (only updates provided fields)
-#[server(UpdateUISettingsFn, "/api")]
-pub async fn update_ui_settings(data: UpdateUISettings) -> Result<UISettings, ServerFnError> {
+/// Create a new node
+#[server(CreateNodeFn, "/api", client = LiveClient)]
+pub async fn create_node(data: CreateNode) -> Result<Node, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        // Build dynamic UPDATE query
-        let mut updates = Vec::new();
-        let mut values: Vec<String> = Vec::new();
+        let user = require_role(&pool, data.topology_id, Role::Editor).await?;
 
-        if let Some(show_grid) = data.show_grid {
-            updates.push("show_grid = ?");
-            values.push(if show_grid { "1" } else { "0" }.to_string());
-        }
+        let vendor = data.vendor.unwrap_or_else(|| "generic".to_string());
+        let model_name = data
+            .model_name
+            .unwrap_or_else(|| format!("blob-{}", data.node_type));
+        // Rotation and scale default to the model's manifest entry, if it has one
+        let model = model_registry().await?.model(data.node_type, &vendor, &model_name);
+        let [default_rot_x, default_rot_y, default_rot_z] =
+            model.as_ref().map(|m| m.default_rotation).unwrap_or_default();
 
-        if let Some(show_x_axis) = data.show_x_axis {
-            updates.push("show_x_axis = ?");
-            values.push(if show_x_axis { "1" } else { "0" }.to_string());
-        }
+        // Use default position if not provided
+        let pos_x = data.position_x.unwrap_or(0.0);
+        let pos_y = data.position_y.unwrap_or(0.0);
+        let pos_z = data.position_z.unwrap_or(0.0);
+        // Native Blender Z-up coordinate system (no rotation needed)
+        let rot_x = data.rotation_x.unwrap_or(default_rot_x);
+        let rot_y = data.rotation_y.unwrap_or(default_rot_y);
+        let rot_z = data.rotation_z.unwrap_or(default_rot_z);
+        let scale = data.scale.unwrap_or(model.as_ref().map_or(1.0, |m| m.default_scale));
+        let color = data.color.unwrap_or(Rgb::NODE_DEFAULT);
+        let visible = data.visible.unwrap_or(true); // Default to visible
 
-        if let Some(show_y_axis) = data.show_y_axis {
-            updates.push("show_y_axis = ?");
-            values.push(if show_y_axis { "1" } else { "0" }.to_string());
-        }
+        // The node, its undo entry and its audit entry are saved together or not at all
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let ip_address = check_node_address(&mut tx, data.topology_id, None, data.ip_address.as_deref()).await?;
 
-        if let Some(show_z_axis) = data.show_z_axis {
-            updates.push("show_z_axis = ?");
-            values.push(if show_z_axis { "1" } else { "0" }.to_string());
-        }
+        let id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO nodes (topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, group_id, metadata)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17) RETURNING id"
+        )
+        .bind(data.topology_id)
+        .bind(&data.name)
+        .bind(data.node_type)
+        .bind(&vendor)
+        .bind(&model_name)
+        .bind(&ip_address)
+        .bind(pos_x)
+        .bind(pos_y)
+        .bind(pos_z)
+        .bind(rot_x)
+        .bind(rot_y)
+        .bind(rot_z)
+        .bind(scale)
+        .bind(color)
+        .bind(visible)
+        .bind(data.group_id)
+        .bind(&data.metadata)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
 
-        if let Some(ambient_intensity) = data.ambient_intensity {
-            updates.push("ambient_intensity = ?");
-            values.push(ambient_intensity.to_string());
-        }
 
-        if let Some(key_light_intensity) = data.key_light_intensity {
-            updates.push("key_light_intensity = ?");
-            values.push(key_light_intensity.to_string());
-        }
+        // Fetch the created node
+        let node = sqlx::query_as::<_, Node>(
+            "SELECT id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, group_id, metadata, created_at, updated_at, version
+             FROM nodes WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
 
-        if let Some(fill_light_intensity) = data.fill_light_intensity {
-            updates.push("fill_light_intensity = ?");
-            values.push(fill_light_intensity.to_string());
-        }
+        // Record the creation for undo/redo
+        record_node_change(&mut tx, "create", None, Some(&node))
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+        record_audit(&mut *tx, &user, AuditRecord::node(AuditAction::Create, None, Some(&node))).await?;
 
-        if let Some(rim_light_intensity) = data.rim_light_intensity {
-            updates.push("rim_light_intensity = ?");
-            values.push(rim_light_intensity.to_string());
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(node.topology_id, LiveEvent::NodeUpserted { node: node.clone() }).await;
+
+        Ok(node)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Update an existing node
+///
+/// With `expected_version` set, the update only applies if the node hasn't changed
+/// since; otherwise it fails with `UpdateError::Conflict` carrying the current node.
+#[server(UpdateNodeFn, "/api", client = LiveClient)]
+pub async fn update_node(id: i64, data: UpdateNode) -> Result<Node, UpdateError<Node>> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_item_role(&pool, TopologyItem::Node(id), Role::Editor).await?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        // Snapshot the node before updating (for undo)
+        let previous = sqlx::query_as::<_, Node>(
+            "SELECT id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, group_id, metadata, created_at, updated_at, version
+             FROM nodes WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::not_found(format!("Node not found: {}", e)))?;
+
+        if data.expected_version.is_some_and(|expected| expected != previous.version) {
+            return Err(UpdateError::Conflict(previous));
         }
 
-        if let Some(use_environment_lighting) = data.use_environment_lighting {
-            updates.push("use_environment_lighting = ?");
-            values.push(if use_environment_lighting { "1" } else { "0" }.to_string());
+        // A blank address clears it
+        let ip_address = match data.ip_address.as_deref() {
+            Some(ip) => Some(check_node_address(&mut tx, previous.topology_id, Some(id), Some(ip)).await?),
+            None => None,
+        };
+
+        if data.is_empty() {
+            return Err(AppError::invalid("No fields to update").into());
         }
 
-        if let Some(environment_map) = data.environment_map {
-            updates.push("environment_map = ?");
-            values.push(environment_map);
+        // Build dynamic UPDATE query based on which fields are provided
+        let mut query = sqlx::QueryBuilder::<Db>::new("UPDATE nodes SET version = version + 1, updated_at = ");
+        query.push_bind(unix_now());
+
+        if let Some(ref name) = data.name {
+            query.push(", name = ").push_bind(name);
+        }
+        if let Some(node_type) = data.node_type {
+            query.push(", node_type = ").push_bind(node_type);
+        }
+        if let Some(ref vendor) = data.vendor {
+            query.push(", vendor = ").push_bind(vendor);
+        }
+        if let Some(ref model_name) = data.model_name {
+            query.push(", model_name = ").push_bind(model_name);
+        }
+        if let Some(ref ip) = ip_address {
+            query.push(", ip_address = ").push_bind(ip);
+        }
+        if let Some(pos_x) = data.position_x {
+            query.push(", position_x = ").push_bind(pos_x);
+        }
+        if let Some(pos_y) = data.position_y {
+            query.push(", position_y = ").push_bind(pos_y);
+        }
+        if let Some(pos_z) = data.position_z {
+            query.push(", position_z = ").push_bind(pos_z);
+        }
+        if let Some(rot_x) = data.rotation_x {
+            query.push(", rotation_x = ").push_bind(rot_x);
+        }
+        if let Some(rot_y) = data.rotation_y {
+            query.push(", rotation_y = ").push_bind(rot_y);
+        }
+        if let Some(rot_z) = data.rotation_z {
+            query.push(", rotation_z = ").push_bind(rot_z);
+        }
+        if let Some(scale) = data.scale {
+            query.push(", scale = ").push_bind(scale);
+        }
+        if let Some(color) = data.color {
+            query.push(", color = ").push_bind(color);
+        }
+        if let Some(visible) = data.visible {
+            query.push(", visible = ").push_bind(visible);
+        }
+        if let Some(group_id) = data.group_id {
+            // 0 = no group
+            query.push(", group_id = ").push_bind(Some(group_id).filter(|&id| id != 0));
+        }
+        if let Some(ref metadata) = data.metadata {
+            query.push(", metadata = ").push_bind(metadata);
         }
 
-        if updates.is_empty() {
-            return Err(ServerFnError::new("No fields to update"));
+        query.push(" WHERE id = ").push_bind(id);
+        // Re-checked in the statement itself in case someone saved since the snapshot
+        if let Some(expected) = data.expected_version {
+            query.push(" AND version = ").push_bind(expected);
         }
 
-        let query = format!("UPDATE ui_settings SET {} WHERE id = 1", updates.join(", "));
+        let result = query
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(constraint_error)?;
+
+        // Fetch the updated node
+        let node = sqlx::query_as::<_, Node>(
+            "SELECT id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, group_id, metadata, created_at, updated_at, version
+             FROM nodes WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::not_found(format!("Node not found: {}", e)))?;
 
-        let mut query_builder = sqlx::query(&query);
-        for value in &values {
-            query_builder = query_builder.bind(value);
+        if result.rows_affected() == 0 {
+            return Err(UpdateError::Conflict(node));
         }
 
-        query_builder
-            .execute(&pool)
+        // Record before/after states for undo/redo
+        record_node_change(&mut tx, "update", Some(&previous), Some(&node))
             .await
-            .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+        record_audit(&mut *tx, &user, AuditRecord::node(AuditAction::Update, Some(&previous), Some(&node))).await?;
 
-        // Fetch and return updated settings
-        get_ui_settings().await
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(node.topology_id, LiveEvent::NodeUpserted { node: node.clone() }).await;
+
+        Ok(node)
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -1004,112 +1780,60 @@ pub async fn update_ui_settings(data: UpdateUISettings) -> Result<UISettings, Se
     }
 }
 
-/// Get available vendors and models for a specific node type
-/// Scans the {site_root}/models/{node_type}/ directory for vendor folders
-#[server(GetVendorsForType, "/api")]
-pub async fn get_vendors_for_type(node_type: String) -> Result<VendorListResponse, ServerFnError> {
+/// Delete a node
+#[server(DeleteNode, "/api", client = LiveClient)]
+pub async fn delete_node(id: i64) -> Result<(), AppError> {
     #[cfg(feature = "ssr")]
     {
-        use leptos::prelude::get_configuration;
-        use std::fs;
-        use std::path::Path;
-
-        // Get site_root from Leptos configuration (idiomatic for deployment)
-        let conf = get_configuration(None).map_err(|e| {
-            ServerFnError::new(format!("Failed to get Leptos configuration: {}", e))
-        })?;
-        let site_root = &conf.leptos_options.site_root;
+        use axum::Extension;
+        use leptos_axum::extract;
 
-        let models_path = format!("{}/models/{}", site_root, node_type);
-        let models_dir = Path::new(&models_path);
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        if !models_dir.exists() {
-            return Ok(VendorListResponse {
-                node_type: node_type.clone(),
-                vendors: vec![],
-            });
-        }
+        let user = require_item_role(&pool, TopologyItem::Node(id), Role::Editor).await?;
 
-        let mut vendors = Vec::new();
+        // Get the node before deletion
+        let node = sqlx::query_as::<_, Node>(
+            "SELECT id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, group_id, metadata, created_at, updated_at, version
+             FROM nodes WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| AppError::not_found(format!("Node not found: {}", e)))?;
 
-        // Read vendor directories
-        let entries = fs::read_dir(models_dir)
-            .map_err(|e| ServerFnError::new(format!("Failed to read models directory: {}", e)))?;
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
 
-        for entry in entries {
-            let entry = entry.map_err(|e| {
-                ServerFnError::new(format!("Failed to read directory entry: {}", e))
-            })?;
-            let path = entry.path();
-
-            if path.is_dir() {
-                let vendor_name = path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-
-                // Scan for .glb files in vendor directory
-                let mut models = Vec::new();
-
-                if let Ok(model_entries) = fs::read_dir(&path) {
-                    for model_entry in model_entries {
-                        if let Ok(model_entry) = model_entry {
-                            let model_path = model_entry.path();
-                            if let Some(ext) = model_path.extension() {
-                                if ext == "glb" {
-                                    if let Some(file_name) =
-                                        model_path.file_name().and_then(|n| n.to_str())
-                                    {
-                                        // Remove .glb extension for storage
-                                        let file_name_no_ext = file_name.trim_end_matches(".glb");
-                                        let display_name =
-                                            file_name_no_ext.replace("-", " ").replace("_", " ");
-
-                                        models.push(ModelInfo {
-                                            file_name: file_name_no_ext.to_string(),
-                                            display_name: capitalize_words(&display_name),
-                                        });
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+        // Snapshot the node and the connections its deletion cascades to, as one undo step
+        let label = format!("Delete node '{}'", node.name);
+        let change_set_id = current_change_set(&mut tx, node.topology_id, &label)
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+        record_node_delete(&mut tx, change_set_id, &node)
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
 
-                // Check if vendor icon exists
-                let icon_path = format!("{}/icons/vendors/{}.svg", site_root, vendor_name);
-                let has_icon = Path::new(&icon_path).exists();
+        // Now delete the node (connections and metrics cascade)
+        sqlx::query("DELETE FROM nodes WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
 
-                let display_name =
-                    capitalize_words(&vendor_name.replace("-", " ").replace("_", " "));
+        record_audit(&mut *tx, &user, AuditRecord::node(AuditAction::Delete, Some(&node), None)).await?;
 
-                vendors.push(VendorInfo {
-                    name: vendor_name,
-                    display_name,
-                    has_icon,
-                    is_available: !models.is_empty(),
-                    models,
-                });
-            }
-        }
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
 
-        // Sort vendors: generic first, then available vendors, then unavailable
-        vendors.sort_by(|a, b| {
-            if a.name == "generic" {
-                std::cmp::Ordering::Less
-            } else if b.name == "generic" {
-                std::cmp::Ordering::Greater
-            } else if a.is_available && !b.is_available {
-                std::cmp::Ordering::Less
-            } else if !a.is_available && b.is_available {
-                std::cmp::Ordering::Greater
-            } else {
-                a.display_name.cmp(&b.display_name)
-            }
-        });
+        publish_live(node.topology_id, LiveEvent::NodeDeleted { id }).await;
 
-        Ok(VendorListResponse { node_type, vendors })
+        Ok(())
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -1118,201 +1842,252 @@ pub async fn get_vendors_for_type(node_type: String) -> Result<VendorListRespons
     }
 }
 
-#[cfg(feature = "ssr")]
-fn capitalize_words(s: &str) -> String {
-    s.split_whitespace()
-        .map(|word| {
-            let mut chars = word.chars();
-            match chars.next() {
-                None => String::new(),
-                Some(first) => first.to_uppercase().chain(chars).collect(),
-            }
-        })
-        .collect::<Vec<_>>()
-        .join(" ")
-}
-
 // ============================================================================
-// Traffic Monitoring (Phase 6)
+// Connection CRUD Operations
 // ============================================================================
 
-/// Generate mock traffic data for a specific topology
-/// This simulates realistic network traffic patterns for demonstration purposes
-#[server(GenerateMockTraffic, "/api")]
-pub async fn generate_mock_traffic(
-    topology_id: i64,
-    traffic_level: String,
-) -> Result<usize, ServerFnError> {
+/// Get a single connection by ID
+#[server(GetConnection, "/api")]
+pub async fn get_connection(id: i64) -> Result<Connection, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        // Get all connections for this topology
-        let connections = sqlx::query_as::<_, Connection>(
-            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, metadata, created_at, updated_at
-             FROM connections WHERE topology_id = ?"
+        require_item_role(&pool, TopologyItem::Connection(id), Role::Viewer).await?;
+
+        let connection = sqlx::query_as::<_, Connection>(
+            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata, created_at, updated_at, version
+             FROM connections WHERE id = $1"
         )
-        .bind(topology_id)
-        .fetch_all(&pool)
+        .bind(id)
+        .fetch_one(&pool)
         .await
-        .map_err(|e| ServerFnError::new(format!("Failed to fetch connections: {}", e)))?;
-
-        if connections.is_empty() {
-            return Ok(0);
-        }
+        .map_err(|e| AppError::not_found(format!("Connection not found: {}", e)))?;
 
-        // Traffic level acts as a BASELINE activity multiplier
-        // Individual link properties (bandwidth, latency, type) determine actual utilization
-        // This allows per-link automatic traffic level calculation based on throughput
-        let traffic_multiplier = match traffic_level.as_str() {
-            "low" => 0.3,    // 30% baseline activity
-            "medium" => 0.6, // 60% baseline activity
-            "high" => 0.9,   // 90% baseline activity
-            _ => 0.6,        // default to medium
-        };
+        Ok(connection)
+    }
 
-        use rand::Rng;
-        use rand::SeedableRng;
-        // Use StdRng which is Send-safe for async contexts
-        let mut rng = rand::rngs::StdRng::from_entropy();
-        let current_timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
 
-        let mut metrics_created = 0;
+/// Create a new connection
+#[server(CreateConnectionFn, "/api", client = LiveClient)]
+pub async fn create_connection(data: CreateConnection) -> Result<Connection, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
 
-        for connection in connections {
-            // Skip inactive or error connections
-            if connection.status != "active" {
-                continue;
-            }
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-            // Get link properties with realistic defaults
-            let bandwidth_capacity = connection.bandwidth_mbps.unwrap_or(1000) as f64; // Default: 1 Gbps
-            let base_latency = connection.latency_ms.unwrap_or(10.0); // Default: 10ms
-            let baseline_packet_loss = connection.baseline_packet_loss_pct.unwrap_or(0.0); // Default: 0% (no packet loss)
+        let user = require_role(&pool, data.topology_id, Role::Editor).await?;
 
-            // Connection type affects throughput efficiency
-            let type_efficiency = match connection.connection_type.as_str() {
-                "Fiber" => 0.95,    // Fiber is most efficient
-                "Ethernet" => 0.85, // Ethernet slightly less
-                "Wireless" => 0.70, // Wireless has more overhead
-                "VPN" => 0.75,      // VPN has encryption overhead
-                _ => 0.80,          // Default efficiency
-            };
+        // Use defaults if not provided
+        let conn_type = data.connection_type.unwrap_or_default();
+        let status = data.status.unwrap_or_default();
+        let color = data.color.unwrap_or(Rgb::CONNECTION_DEFAULT);
 
-            // Calculate realistic throughput using Mathis formula
-            // Throughput = Bandwidth × TrafficLevel × TypeEfficiency × RTT_factor × PacketLoss_factor
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        // The Editor role covers data.topology_id only, so the endpoints must be in it too
+        check_connection_endpoints(
+            &mut tx,
+            data.topology_id,
+            [
+                (data.source_node_id, data.source_interface_id),
+                (data.target_node_id, data.target_interface_id),
+            ],
+        )
+        .await?;
 
-            // Calculate base throughput (for display purposes - what user sees in tooltip)
-            // This SHOULD be reduced by latency/packet loss to show realistic throughput
-            let rtt_factor = 1.0 / (1.0 + base_latency / 50.0); // Higher latency = lower throughput
-            let packet_loss_factor = if baseline_packet_loss > 0.0 {
-                1.0 / (1.0 + baseline_packet_loss / 10.0) // Higher packet loss = lower throughput
-            } else {
-                1.0
-            };
+        let id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO connections (topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, source_interface_id, target_interface_id, metadata)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) RETURNING id"
+        )
+        .bind(data.topology_id)
+        .bind(data.source_node_id)
+        .bind(data.target_node_id)
+        .bind(conn_type)
+        .bind(data.bandwidth_mbps)
+        .bind(data.latency_ms)
+        .bind(data.baseline_packet_loss_pct.unwrap_or(0.0))
+        .bind(status)
+        .bind(color)
+        .bind(data.source_interface_id)
+        .bind(data.target_interface_id)
+        .bind(&data.metadata)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(constraint_error)?;
 
-            let base_throughput = bandwidth_capacity
-                * traffic_multiplier
-                * type_efficiency
-                * rtt_factor
-                * packet_loss_factor;
-            let throughput_variance = rng.gen_range(0.8..1.2); // ±20% variance for realism
-            let throughput_mbps = (base_throughput * throughput_variance).max(0.1);
 
-            // Calculate utilization for COLOR/HEALTH (NOT just throughput/bandwidth)
-            // Key insight: degraded links (high latency, packet loss) should show ORANGE/RED even with low throughput
-            // Because the link is UNHEALTHY, not just underutilized
+        // Fetch the created connection
+        let connection = sqlx::query_as::<_, Connection>(
+            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata, created_at, updated_at, version
+             FROM connections WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
 
-            // Start with base traffic level utilization (ignoring degradation)
-            let ideal_throughput = bandwidth_capacity * traffic_multiplier * type_efficiency;
-            let base_utilization = (ideal_throughput / bandwidth_capacity * 100.0).min(100.0);
+        // Record the creation for undo/redo
+        record_connection_change(&mut tx, "create", None, Some(&connection))
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+        record_audit(&mut *tx, &user, AuditRecord::connection(AuditAction::Create, None, Some(&connection))).await?;
 
-            // Degradation factors INCREASE apparent utilization (makes link appear more stressed)
-            let latency_penalty = if base_latency > 50.0 {
-                // High latency increases apparent utilization
-                (base_latency - 50.0) / 2.0 // Each 2ms over 50ms adds 1% utilization
-            } else {
-                0.0
-            };
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
 
-            let packet_loss_penalty = baseline_packet_loss * 10.0; // Each 1% packet loss adds 10% utilization
+        publish_live(connection.topology_id, LiveEvent::ConnectionUpserted { connection: connection.clone() }).await;
 
-            // Final utilization = base + degradation penalties
-            let utilization_pct =
-                (base_utilization + latency_penalty + packet_loss_penalty).min(100.0);
+        Ok(connection)
+    }
 
-            // Packets per second (roughly 1000 packets per Mbps for standard Ethernet)
-            let packets_per_sec = (throughput_mbps * 1000.0) as i64;
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
 
-            // Latency: base latency + congestion penalty (higher utilization = higher latency)
-            let base_latency = connection.latency_ms.unwrap_or(10.0);
-            let congestion_penalty = if utilization_pct > 70.0 {
-                // Heavy congestion: significant latency increase
-                (utilization_pct - 70.0) * rng.gen_range(0.5..1.5)
-            } else if utilization_pct > 40.0 {
-                // Moderate congestion: slight latency increase
-                (utilization_pct - 40.0) * rng.gen_range(0.1..0.3)
-            } else {
-                0.0
-            };
-            let jitter = rng.gen_range(-2.0..3.0); // Natural network jitter
-            let latency_ms = (base_latency + congestion_penalty + jitter).max(0.1);
+/// Update an existing connection
+///
+/// Like `update_node`, an `expected_version` that no longer matches fails the update
+/// with `UpdateError::Conflict` carrying the current connection.
+#[server(UpdateConnectionFn, "/api", client = LiveClient)]
+pub async fn update_connection(
+    id: i64,
+    data: UpdateConnection,
+) -> Result<Connection, UpdateError<Connection>> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
 
-            // Packet loss: baseline + congestion-based
-            // Baseline packet loss is the inherent loss rate of the link (user-configured)
-            // Congestion packet loss increases exponentially with utilization (simulated)
-            let congestion_packet_loss: f64 = if utilization_pct > 90.0 {
-                // Critical: severe packet loss
-                rng.gen_range(2.0..5.0)
-            } else if utilization_pct > 80.0 {
-                // High: noticeable packet loss
-                rng.gen_range(0.5..2.0)
-            } else if utilization_pct > 60.0 {
-                // Moderate: occasional packet loss
-                rng.gen_range(0.1..0.5)
-            } else {
-                // Low: minimal packet loss
-                rng.gen_range(0.0..0.1)
-            };
-            // Total packet loss = baseline (from link properties) + congestion (from utilization)
-            let packet_loss_pct = (baseline_packet_loss + congestion_packet_loss).min(100.0);
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-            // Calculate bytes and packets transferred (for 1 second interval)
-            let bytes_transferred = (throughput_mbps * 125000.0) as i64; // Convert Mbps to bytes/sec
-            let packets_transferred = packets_per_sec;
+        let user = require_item_role(&pool, TopologyItem::Connection(id), Role::Editor).await?;
 
-            // Insert metric into database
-            let _result = sqlx::query(
-                "INSERT INTO connection_traffic_metrics
-                 (connection_id, timestamp, throughput_mbps, packets_per_sec, latency_ms,
-                  packet_loss_pct, utilization_pct, bytes_transferred, packets_transferred)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-            )
-            .bind(connection.id)
-            .bind(current_timestamp)
-            .bind(throughput_mbps)
-            .bind(packets_per_sec)
-            .bind(latency_ms)
-            .bind(packet_loss_pct)
-            .bind(utilization_pct)
-            .bind(bytes_transferred)
-            .bind(packets_transferred)
-            .execute(&pool)
+        let mut tx = pool
+            .begin()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to insert traffic metric: {}", e)))?;
+            .map_err(|e| db_error("Failed to start transaction", e))?;
 
-            metrics_created += 1;
+        // Snapshot the connection before updating (for undo)
+        let previous = sqlx::query_as::<_, Connection>(
+            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata, created_at, updated_at, version
+             FROM connections WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::not_found(format!("Connection not found: {}", e)))?;
+
+        if data.expected_version.is_some_and(|expected| expected != previous.version) {
+            return Err(UpdateError::Conflict(previous));
         }
 
-        Ok(metrics_created)
+        // Refuse to touch a connection that reaches outside its topology (rows written
+        // before the schema checked this)
+        check_connection_endpoints(
+            &mut tx,
+            previous.topology_id,
+            [
+                (previous.source_node_id, previous.source_interface_id),
+                (previous.target_node_id, previous.target_interface_id),
+            ],
+        )
+        .await?;
+
+        // Build dynamic UPDATE query
+        let mut query = sqlx::QueryBuilder::<Db>::new("UPDATE connections SET version = version + 1, updated_at = ");
+        query.push_bind(unix_now());
+
+        if let Some(conn_type) = data.connection_type {
+            query.push(", connection_type = ").push_bind(conn_type);
+        }
+        if let Some(bw) = data.bandwidth_mbps {
+            query.push(", bandwidth_mbps = ").push_bind(bw);
+        }
+        if let Some(latency) = data.latency_ms {
+            query.push(", latency_ms = ").push_bind(latency);
+        }
+        if let Some(packet_loss) = data.baseline_packet_loss_pct {
+            query.push(", baseline_packet_loss_pct = ").push_bind(packet_loss);
+        }
+        if let Some(status) = data.status {
+            query.push(", status = ").push_bind(status);
+        }
+        if let Some(color) = data.color {
+            query.push(", color = ").push_bind(color);
+        }
+        if let Some(carries_traffic) = data.carries_traffic {
+            query.push(", carries_traffic = ").push_bind(carries_traffic);
+        }
+        if let Some(flow_direction) = data.flow_direction {
+            query.push(", flow_direction = ").push_bind(flow_direction);
+        }
+        if let Some(ref metadata) = data.metadata {
+            query.push(", metadata = ").push_bind(metadata);
+        }
+
+        query.push(" WHERE id = ").push_bind(id);
+        // Re-checked in the statement itself in case someone saved since the snapshot
+        if let Some(expected) = data.expected_version {
+            query.push(" AND version = ").push_bind(expected);
+        }
+
+        let result = query
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        // Fetch the updated connection
+        let connection = sqlx::query_as::<_, Connection>(
+            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata, created_at, updated_at, version
+             FROM connections WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::not_found(format!("Connection not found: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(UpdateError::Conflict(connection));
+        }
+
+        // Record before/after states for undo/redo
+        record_connection_change(&mut tx, "update", Some(&previous), Some(&connection))
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+        let record = AuditRecord::connection(AuditAction::Update, Some(&previous), Some(&connection));
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(connection.topology_id, LiveEvent::ConnectionUpserted { connection: connection.clone() }).await;
+
+        Ok(connection)
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -1321,127 +2096,5403 @@ pub async fn generate_mock_traffic(
     }
 }
 
-/// Get latest traffic metrics for all connections in a topology
-#[server(GetConnectionTrafficMetrics, "/api")]
-pub async fn get_connection_traffic_metrics(
-    topology_id: i64,
-) -> Result<Vec<ConnectionTrafficMetric>, ServerFnError> {
+/// Delete a connection
+#[server(DeleteConnection, "/api", client = LiveClient)]
+pub async fn delete_connection(id: i64) -> Result<(), AppError> {
     #[cfg(feature = "ssr")]
     {
-        use crate::models::ConnectionTrafficMetric;
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        // Get latest metric for each connection (using subquery for max timestamp per connection)
-        let metrics = sqlx::query_as::<_, ConnectionTrafficMetric>(
-            "SELECT ctm.*
-             FROM connection_traffic_metrics ctm
-             INNER JOIN connections c ON ctm.connection_id = c.id
-             INNER JOIN (
-                 SELECT connection_id, MAX(timestamp) as max_timestamp
-                 FROM connection_traffic_metrics
-                 GROUP BY connection_id
-             ) latest ON ctm.connection_id = latest.connection_id
-                     AND ctm.timestamp = latest.max_timestamp
-             WHERE c.topology_id = ?
-             ORDER BY ctm.utilization_pct DESC",
+        let user = require_item_role(&pool, TopologyItem::Connection(id), Role::Editor).await?;
+
+        // Get the connection before deletion
+        let connection = sqlx::query_as::<_, Connection>(
+            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata, created_at, updated_at, version
+             FROM connections WHERE id = $1"
         )
-        .bind(topology_id)
-        .fetch_all(&pool)
+        .bind(id)
+        .fetch_one(&pool)
         .await
-        .map_err(|e| ServerFnError::new(format!("Failed to fetch traffic metrics: {}", e)))?;
+        .map_err(|e| AppError::not_found(format!("Connection not found: {}", e)))?;
 
-        Ok(metrics)
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        // Snapshot the connection and its traffic metrics before deleting
+        let change_set_id = current_change_set(&mut tx, connection.topology_id, "Delete connection")
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+        record_connection_delete(&mut tx, change_set_id, &connection)
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+
+        // Now delete the connection (metrics cascade)
+        sqlx::query("DELETE FROM connections WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        record_audit(&mut *tx, &user, AuditRecord::connection(AuditAction::Delete, Some(&connection), None)).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(connection.topology_id, LiveEvent::ConnectionDeleted { id }).await;
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Swap source and target nodes for a connection (reverse direction)
+#[server(SwapConnectionDirection, "/api", client = LiveClient)]
+pub async fn swap_connection_direction(id: i64) -> Result<Connection, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_item_role(&pool, TopologyItem::Connection(id), Role::Editor).await?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        // Snapshot the connection before swapping (for undo)
+        let previous = sqlx::query_as::<_, Connection>(
+            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata, created_at, updated_at, version
+             FROM connections WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::not_found(format!("Connection not found: {}", e)))?;
+
+        // Swap source and target nodes, keeping each interface with its node
+        sqlx::query(
+            "UPDATE connections
+             SET source_node_id = target_node_id,
+                 target_node_id = source_node_id,
+                 source_interface_id = target_interface_id,
+                 target_interface_id = source_interface_id,
+                 version = version + 1,
+                 updated_at = $1
+             WHERE id = $2",
+        )
+        .bind(unix_now())
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+
+        // Fetch the updated connection
+        let connection = sqlx::query_as::<_, Connection>(
+            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata, created_at, updated_at, version
+             FROM connections WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::not_found(format!("Connection not found: {}", e)))?;
+
+        // Record before/after states for undo/redo
+        record_connection_change(&mut tx, "update", Some(&previous), Some(&connection))
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+        let record = AuditRecord::connection(AuditAction::Update, Some(&previous), Some(&connection));
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(connection.topology_id, LiveEvent::ConnectionUpserted { connection: connection.clone() }).await;
+
+        Ok(connection)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+// ============================================================================
+// Interface CRUD Operations
+// ============================================================================
+//
+// Interfaces are named ports on a node. A connection may name the port it uses at
+// each end; triggers on `connections` check the port belongs to that end's node and
+// isn't already used by another link.
+
+/// Columns selected for `Interface`
+#[cfg(feature = "ssr")]
+const INTERFACE_COLUMNS: &str =
+    "id, node_id, name, speed_mbps, mac_address, ip_address, admin_state, description, created_at, updated_at";
+
+/// Trimmed text, or None when blank
+#[cfg(feature = "ssr")]
+fn non_empty(value: &str) -> Option<String> {
+    Some(value.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// Check the user-editable fields of an interface
+#[cfg(feature = "ssr")]
+fn validate_interface(name: &str, mac_address: Option<&str>, ip_address: Option<&str>) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Interface name is empty".to_string());
+    }
+    if let Some(mac) = mac_address.and_then(non_empty) {
+        normalize_mac(&mac)?;
+    }
+    if let Some(ip) = ip_address.and_then(non_empty) {
+        validate_interface_ip(&ip)?;
+    }
+    Ok(())
+}
+
+/// A database failure, prefixed with what was being done
+#[cfg(feature = "ssr")]
+fn db_error(context: impl std::fmt::Display, e: sqlx::Error) -> AppError {
+    let AppError { kind, message } = AppError::from(e);
+    AppError::new(kind, format!("{}: {}", context, message))
+}
+
+/// Report a constraint or validation trigger failure by its own message
+#[cfg(feature = "ssr")]
+fn constraint_error(e: sqlx::Error) -> AppError {
+    match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => AppError::conflict(db.message()),
+        sqlx::Error::Database(db) => AppError::invalid(db.message()),
+        other => db_error("Database error", other),
+    }
+}
+
+/// Check that both ends of a connection are nodes of its topology, and each port is on its end's node
+///
+/// The schema's triggers refuse the same rows; checking first keeps another topology's
+/// nodes from being named (or probed) and reports which end is wrong.
+#[cfg(feature = "ssr")]
+async fn check_connection_endpoints(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    ends: [(i64, Option<i64>); 2],
+) -> Result<(), AppError> {
+    for ((node_id, interface_id), end) in ends.into_iter().zip(["Source", "Target"]) {
+        let node_topology = sqlx::query_scalar::<_, i64>("SELECT topology_id FROM nodes WHERE id = $1")
+            .bind(node_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        if node_topology != Some(topology_id) {
+            return Err(AppError::not_found(format!("{} node {} not found in this topology", end, node_id)));
+        }
+
+        let Some(interface_id) = interface_id else {
+            continue;
+        };
+        let interface_node = sqlx::query_scalar::<_, i64>("SELECT node_id FROM interfaces WHERE id = $1")
+            .bind(interface_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        if interface_node != Some(node_id) {
+            return Err(AppError::invalid(format!(
+                "{} interface {} does not belong to node {}",
+                end, interface_id, node_id
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+async fn fetch_interface(conn: &mut DbConnection, id: i64) -> Result<Interface, AppError> {
+    sqlx::query_as::<_, Interface>(&format!("SELECT {} FROM interfaces WHERE id = $1", INTERFACE_COLUMNS))
+        .bind(id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| AppError::not_found(format!("Interface not found: {}", e)))
+}
+
+/// Every interface of a node, in creation order
+#[cfg(feature = "ssr")]
+async fn fetch_node_interfaces(conn: &mut DbConnection, node_id: i64) -> Result<Vec<Interface>, sqlx::Error> {
+    sqlx::query_as::<_, Interface>(&format!(
+        "SELECT {} FROM interfaces WHERE node_id = $1 ORDER BY id",
+        INTERFACE_COLUMNS
+    ))
+    .bind(node_id)
+    .fetch_all(conn)
+    .await
+}
+
+/// Record a change to a node's interfaces as one undo step (or as part of the open change set)
+///
+/// The whole interface list of the node is snapshotted on both sides, so undo and redo
+/// also remove interfaces the other side doesn't have.
+#[cfg(feature = "ssr")]
+async fn record_interfaces_change(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    node_id: i64,
+    label: &str,
+    before: &[Interface],
+    detached: &[(Connection, Connection)],
+) -> Result<(), sqlx::Error> {
+    let after = fetch_node_interfaces(conn, node_id).await?;
+    let change_set_id = current_change_set(conn, topology_id, label).await?;
+    // Connections go first: undo walks the change set backwards, so the interface is
+    // back before the connections are plugged into it again
+    for (previous, connection) in detached {
+        let entry = UndoEntry::connection("update", Some(previous), Some(connection))?;
+        insert_undo_entry(conn, topology_id, change_set_id, entry).await?;
+    }
+    let entry = UndoEntry::new("node_interfaces", node_id, "update", Some(&before), Some(&after.as_slice()))?;
+    insert_undo_entry(conn, topology_id, change_set_id, entry).await
+}
+
+/// Topology a node belongs to
+#[cfg(feature = "ssr")]
+async fn node_topology_id(conn: &mut DbConnection, node_id: i64) -> Result<i64, AppError> {
+    sqlx::query_scalar::<_, i64>("SELECT topology_id FROM nodes WHERE id = $1")
+        .bind(node_id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| AppError::not_found(format!("Node not found: {}", e)))
+}
+
+/// Get all interfaces of a node
+#[server(GetNodeInterfaces, "/api")]
+pub async fn get_node_interfaces(node_id: i64) -> Result<Vec<Interface>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_item_role(&pool, TopologyItem::Node(node_id), Role::Viewer).await?;
+
+        let interfaces = sqlx::query_as::<_, Interface>(&format!(
+            "SELECT {} FROM interfaces WHERE node_id = $1 ORDER BY id",
+            INTERFACE_COLUMNS
+        ))
+        .bind(node_id)
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+
+        Ok(interfaces)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Add an interface to a node
+#[server(CreateInterfaceFn, "/api", client = LiveClient)]
+pub async fn create_interface(data: CreateInterface) -> Result<Interface, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_item_role(&pool, TopologyItem::Node(data.node_id), Role::Editor).await?;
+
+        validate_interface(&data.name, data.mac_address.as_deref(), data.ip_address.as_deref())
+            .map_err(AppError::invalid)?;
+        let mac_address = data.mac_address.as_deref().and_then(non_empty).map(|mac| normalize_mac(&mac)).transpose()
+            .map_err(AppError::invalid)?;
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let topology_id = node_topology_id(&mut tx, data.node_id).await?;
+        let ip_address = check_node_address(&mut tx, topology_id, Some(data.node_id), data.ip_address.as_deref()).await?;
+        let before = fetch_node_interfaces(&mut tx, data.node_id)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        let id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO interfaces (node_id, name, speed_mbps, mac_address, ip_address, admin_state, description)
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id",
+        )
+        .bind(data.node_id)
+        .bind(data.name.trim())
+        .bind(data.speed_mbps.filter(|speed| *speed > 0))
+        .bind(&mac_address)
+        .bind(&ip_address)
+        .bind(data.admin_state.unwrap_or_default())
+        .bind(data.description.as_deref().and_then(non_empty))
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                AppError::conflict(format!("Node already has an interface named '{}'", data.name.trim()))
+            }
+            other => constraint_error(other),
+        })?;
+
+        let interface = fetch_interface(&mut tx, id).await?;
+        let label = format!("Create interface '{}'", interface.name);
+        record_interfaces_change(&mut tx, topology_id, interface.node_id, &label, &before, &[])
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+        let record = AuditRecord::interface(topology_id, AuditAction::Create, None, Some(&interface));
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(topology_id, LiveEvent::TopologyChanged).await;
+
+        Ok(interface)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Update an interface (blank text fields and a speed of 0 clear the value)
+///
+/// Sent as JSON: the default URL encoding turns an empty string into `None`,
+/// which would keep the old value instead of clearing it.
+#[server(UpdateInterfaceFn, "/api", input = leptos::server_fn::codec::Json, client = LiveClient)]
+pub async fn update_interface(id: i64, data: UpdateInterface) -> Result<Interface, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_item_role(&pool, TopologyItem::Interface(id), Role::Editor).await?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let current = fetch_interface(&mut tx, id).await?;
+        let previous = current.clone();
+        let topology_id = node_topology_id(&mut tx, current.node_id).await?;
+        let name = data.name.as_deref().unwrap_or(&current.name).trim().to_string();
+        validate_interface(&name, data.mac_address.as_deref(), data.ip_address.as_deref())
+            .map_err(AppError::invalid)?;
+
+        // Fields that weren't provided keep their current value
+        let speed_mbps = match data.speed_mbps {
+            Some(speed) => Some(speed).filter(|speed| *speed > 0),
+            None => current.speed_mbps,
+        };
+        let mac_address = match data.mac_address.as_deref() {
+            Some(mac) => non_empty(mac).map(|mac| normalize_mac(&mac)).transpose().map_err(AppError::invalid)?,
+            None => current.mac_address,
+        };
+        let ip_address = match data.ip_address.as_deref() {
+            Some(ip) => check_node_address(&mut tx, topology_id, Some(current.node_id), Some(ip)).await?,
+            None => current.ip_address,
+        };
+        let description = match data.description.as_deref() {
+            Some(description) => non_empty(description),
+            None => current.description,
+        };
+        let before = fetch_node_interfaces(&mut tx, current.node_id)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        sqlx::query(
+            "UPDATE interfaces
+             SET name = $1, speed_mbps = $2, mac_address = $3, ip_address = $4, admin_state = $5, description = $6,
+                 updated_at = $7
+             WHERE id = $8",
+        )
+        .bind(&name)
+        .bind(speed_mbps)
+        .bind(&mac_address)
+        .bind(&ip_address)
+        .bind(data.admin_state.unwrap_or(current.admin_state))
+        .bind(&description)
+        .bind(unix_now())
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                AppError::conflict(format!("Node already has an interface named '{}'", name))
+            }
+            other => constraint_error(other),
+        })?;
+
+        let interface = fetch_interface(&mut tx, id).await?;
+        let label = format!("Update interface '{}'", interface.name);
+        record_interfaces_change(&mut tx, topology_id, interface.node_id, &label, &before, &[])
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+        let record = AuditRecord::interface(topology_id, AuditAction::Update, Some(&previous), Some(&interface));
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(topology_id, LiveEvent::TopologyChanged).await;
+
+        Ok(interface)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Delete an interface (connections using it stay, detached from that port)
+#[server(DeleteInterface, "/api", client = LiveClient)]
+pub async fn delete_interface(id: i64) -> Result<(), AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_item_role(&pool, TopologyItem::Interface(id), Role::Editor).await?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let interface = fetch_interface(&mut tx, id).await?;
+        let topology_id = node_topology_id(&mut tx, interface.node_id).await?;
+        let before = fetch_node_interfaces(&mut tx, interface.node_id)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        // Connections plugged into the port are detached from it (ON DELETE SET NULL);
+        // their undo entries plug them back in
+        let attached = sqlx::query_as::<_, Connection>(
+            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata, created_at, updated_at, version
+             FROM connections WHERE source_interface_id = $1 OR target_interface_id = $2 ORDER BY id"
+        )
+        .bind(id)
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+        let detached: Vec<(Connection, Connection)> = attached
+            .into_iter()
+            .map(|previous| {
+                let mut connection = previous.clone();
+                connection.source_interface_id = connection.source_interface_id.filter(|port| *port != id);
+                connection.target_interface_id = connection.target_interface_id.filter(|port| *port != id);
+                (previous, connection)
+            })
+            .collect();
+
+        sqlx::query("DELETE FROM interfaces WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        let label = format!("Delete interface '{}'", interface.name);
+        record_interfaces_change(&mut tx, topology_id, interface.node_id, &label, &before, &detached)
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+        let record = AuditRecord::interface(topology_id, AuditAction::Delete, Some(&interface), None);
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(topology_id, LiveEvent::TopologyChanged).await;
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Attach a connection's ends to specific interfaces (None = node-level, no port)
+#[server(SetConnectionInterfaces, "/api", client = LiveClient)]
+pub async fn set_connection_interfaces(
+    id: i64,
+    source_interface_id: Option<i64>,
+    target_interface_id: Option<i64>,
+) -> Result<Connection, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_item_role(&pool, TopologyItem::Connection(id), Role::Editor).await?;
+
+        let select = "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata, created_at, updated_at, version
+             FROM connections WHERE id = $1";
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        // Snapshot the connection before updating (for undo)
+        let previous = sqlx::query_as::<_, Connection>(select)
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| AppError::not_found(format!("Connection not found: {}", e)))?;
+
+        // Ownership and "one link per port" are enforced by check_connection_interfaces_update
+        sqlx::query(
+            "UPDATE connections SET source_interface_id = $1, target_interface_id = $2, version = version + 1, updated_at = $3
+             WHERE id = $4",
+        )
+        .bind(source_interface_id)
+        .bind(target_interface_id)
+        .bind(unix_now())
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(constraint_error)?;
+
+        let connection = sqlx::query_as::<_, Connection>(select)
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| AppError::not_found(format!("Connection not found: {}", e)))?;
+
+        record_connection_change(&mut tx, "update", Some(&previous), Some(&connection))
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+        let record = AuditRecord::connection(AuditAction::Update, Some(&previous), Some(&connection));
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(connection.topology_id, LiveEvent::ConnectionUpserted { connection: connection.clone() }).await;
+
+        Ok(connection)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+// ============================================================================
+// IP Address Management
+// ============================================================================
+//
+// Subnets are the address plan of a topology. Node and interface addresses are
+// checked against them (and against each other) when they are written; addresses
+// stored before validation existed are listed by `get_ipam_report` instead.
+
+/// Columns selected for `Subnet`
+#[cfg(feature = "ssr")]
+const SUBNET_COLUMNS: &str = "id, topology_id, cidr, name, gateway, vlan_id, description, created_at, updated_at";
+
+#[cfg(feature = "ssr")]
+async fn fetch_subnet(conn: &mut DbConnection, id: i64) -> Result<Subnet, AppError> {
+    sqlx::query_as::<_, Subnet>(&format!("SELECT {} FROM subnets WHERE id = $1", SUBNET_COLUMNS))
+        .bind(id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| AppError::not_found(format!("Subnet not found: {}", e)))
+}
+
+#[cfg(feature = "ssr")]
+async fn fetch_topology_subnets(
+    conn: &mut DbConnection,
+    topology_id: i64,
+) -> Result<Vec<Subnet>, sqlx::Error> {
+    sqlx::query_as::<_, Subnet>(&format!(
+        "SELECT {} FROM subnets WHERE topology_id = $1 ORDER BY id",
+        SUBNET_COLUMNS
+    ))
+    .bind(topology_id)
+    .fetch_all(conn)
+    .await
+}
+
+/// Every address held by the nodes of a topology and by their interfaces
+#[cfg(feature = "ssr")]
+async fn fetch_ip_holders(
+    conn: &mut DbConnection,
+    topology_id: i64,
+) -> Result<Vec<IpHolder>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (i64, String, Option<i64>, Option<String>, String)>(
+        "SELECT id, name, NULL, NULL, ip_address FROM nodes
+         WHERE topology_id = $1 AND TRIM(COALESCE(ip_address, '')) != ''
+         UNION ALL
+         SELECT n.id, n.name, i.id, i.name, i.ip_address FROM interfaces i JOIN nodes n ON n.id = i.node_id
+         WHERE n.topology_id = $2 AND TRIM(COALESCE(i.ip_address, '')) != ''",
+    )
+    .bind(topology_id)
+    .bind(topology_id)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(node_id, node_name, interface_id, interface_name, address)| IpHolder {
+            node_id,
+            node_name,
+            interface_id,
+            interface_name,
+            address,
+        })
+        .collect())
+}
+
+/// Parsed prefixes of a topology's subnets (rows that don't parse are skipped)
+#[cfg(feature = "ssr")]
+fn subnet_prefixes(subnets: &[Subnet]) -> Vec<(IpPrefix, &Subnet)> {
+    subnets
+        .iter()
+        .filter_map(|subnet| subnet.cidr.parse::<IpPrefix>().ok().map(|prefix| (prefix, subnet)))
+        .collect()
+}
+
+/// The most specific subnet containing an address
+#[cfg(feature = "ssr")]
+fn containing_subnet<'a>(
+    prefixes: &[(IpPrefix, &'a Subnet)],
+    address: std::net::IpAddr,
+) -> Option<(IpPrefix, &'a Subnet)> {
+    prefixes
+        .iter()
+        .filter(|(prefix, _)| prefix.contains(address))
+        .max_by_key(|(prefix, _)| prefix.prefix_len())
+        .copied()
+}
+
+/// Check an address about to be given to a node (or one of its interfaces)
+///
+/// Returns the trimmed address, or None when it is blank. The address has to parse,
+/// can't be the network or broadcast address of a subnet, and can't be in use by
+/// another node of the topology.
+#[cfg(feature = "ssr")]
+async fn check_node_address(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    node_id: Option<i64>,
+    address: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    let Some(address) = address.and_then(non_empty) else {
+        return Ok(None);
+    };
+    let (ip, _) = parse_address(&address).map_err(AppError::invalid)?;
+
+    let subnets = fetch_topology_subnets(&mut *conn, topology_id)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+    if let Some((prefix, subnet)) = containing_subnet(&subnet_prefixes(&subnets), ip) {
+        if !prefix.is_host(ip) {
+            return Err(AppError::invalid(format!(
+                "{} is the network or broadcast address of subnet {}",
+                ip, subnet.cidr
+            )));
+        }
+    }
+
+    let holders = fetch_ip_holders(&mut *conn, topology_id)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+    let taken_by = holders.iter().find(|holder| {
+        Some(holder.node_id) != node_id
+            && parse_address(&holder.address).is_ok_and(|(other, _)| other == ip)
+    });
+    if let Some(holder) = taken_by {
+        return Err(AppError::conflict(format!(
+            "IP address {} is already used by {}",
+            ip,
+            holder.label()
+        )));
+    }
+
+    Ok(Some(address))
+}
+
+/// Check the address of a node (or interface) in an imported document
+///
+/// The same rules as `check_node_address`, against the document's subnets and the
+/// addresses accepted so far (`taken` maps each to its node, and gains this one).
+#[cfg(feature = "ssr")]
+fn check_import_address(
+    address: Option<&str>,
+    node_id: i64,
+    prefixes: &[(IpPrefix, &Subnet)],
+    taken: &mut std::collections::HashMap<std::net::IpAddr, i64>,
+) -> Result<(), String> {
+    let Some(address) = address.and_then(non_empty) else {
+        return Ok(());
+    };
+    let (ip, _) = parse_address(&address)?;
+    if let Some((prefix, subnet)) = containing_subnet(prefixes, ip) {
+        if !prefix.is_host(ip) {
+            return Err(format!("{} is the network or broadcast address of subnet {}", ip, subnet.cidr));
+        }
+    }
+    match taken.get(&ip) {
+        Some(&other) if other != node_id => Err(format!("IP address {} is already used by node {}", ip, other)),
+        _ => {
+            taken.insert(ip, node_id);
+            Ok(())
+        }
+    }
+}
+
+/// Check the user-editable fields of a subnet, returning the canonical CIDR
+#[cfg(feature = "ssr")]
+fn validate_subnet(cidr: &str, gateway: Option<&str>, vlan_id: Option<i64>) -> Result<IpPrefix, String> {
+    let prefix: IpPrefix = cidr.parse()?;
+    if let Some(gateway) = gateway.and_then(non_empty) {
+        let (ip, _) = parse_address(&gateway)?;
+        if !prefix.is_host(ip) {
+            return Err(format!("Gateway {} is not a host address in {}", ip, prefix));
+        }
+    }
+    if let Some(vlan_id) = vlan_id {
+        if !(1..=4094).contains(&vlan_id) {
+            return Err(format!("VLAN ID {} is outside 1-4094", vlan_id));
+        }
+    }
+    Ok(prefix)
+}
+
+/// Lowest free host address in a subnet (the gateway counts as taken)
+#[cfg(feature = "ssr")]
+fn next_free_address(prefix: &IpPrefix, subnet: &Subnet, holders: &[IpHolder]) -> Option<String> {
+    let mut taken: std::collections::HashSet<std::net::IpAddr> = holders
+        .iter()
+        .filter_map(|holder| parse_address(&holder.address).ok())
+        .map(|(ip, _)| ip)
+        .collect();
+    if let Some((gateway, _)) = subnet.gateway.as_deref().and_then(|gateway| parse_address(gateway).ok()) {
+        taken.insert(gateway);
+    }
+    prefix.first_free(&taken).map(|ip| ip.to_string())
+}
+
+/// Insert a copy of a subnet into a topology
+#[cfg(feature = "ssr")]
+async fn insert_subnet_copy(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    subnet: &Subnet,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO subnets (topology_id, cidr, name, gateway, vlan_id, description) VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(topology_id)
+    .bind(&subnet.cidr)
+    .bind(&subnet.name)
+    .bind(&subnet.gateway)
+    .bind(subnet.vlan_id)
+    .bind(&subnet.description)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Replace every subnet of a topology with copies of `subnets`
+#[cfg(feature = "ssr")]
+async fn replace_subnets(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    subnets: &[Subnet],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM subnets WHERE topology_id = $1")
+        .bind(topology_id)
+        .execute(&mut *conn)
+        .await?;
+    for subnet in subnets {
+        insert_subnet_copy(conn, topology_id, subnet).await?;
+    }
+    Ok(())
+}
+
+/// Get all subnets of a topology
+#[server(GetSubnets, "/api")]
+pub async fn get_subnets(topology_id: i64) -> Result<Vec<Subnet>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_role(&pool, topology_id, Role::Viewer).await?;
+
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        fetch_topology_subnets(&mut conn, topology_id)
+            .await
+            .map_err(|e| db_error("Database error", e))
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Add a subnet to a topology
+#[server(CreateSubnetFn, "/api")]
+pub async fn create_subnet(data: CreateSubnet) -> Result<Subnet, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_role(&pool, data.topology_id, Role::Editor).await?;
+
+        let prefix = validate_subnet(&data.cidr, data.gateway.as_deref(), data.vlan_id)
+            .map_err(AppError::invalid)?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO subnets (topology_id, cidr, name, gateway, vlan_id, description) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+        )
+        .bind(data.topology_id)
+        .bind(prefix.to_string())
+        .bind(data.name.as_deref().map(str::trim).unwrap_or_default())
+        .bind(data.gateway.as_deref().and_then(non_empty))
+        .bind(data.vlan_id)
+        .bind(data.description.as_deref().and_then(non_empty))
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                AppError::conflict(format!("Subnet {} already exists in this topology", prefix))
+            }
+            other => constraint_error(other),
+        })?;
+
+        let subnet = fetch_subnet(&mut tx, id).await?;
+        record_audit(&mut *tx, &user, AuditRecord::subnet(AuditAction::Create, None, Some(&subnet))).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        Ok(subnet)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Update a subnet (a blank gateway or description, or VLAN 0, clears the value)
+///
+/// Sent as JSON for the same reason as `update_interface`.
+#[server(UpdateSubnetFn, "/api", input = leptos::server_fn::codec::Json)]
+pub async fn update_subnet(id: i64, data: UpdateSubnet) -> Result<Subnet, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_item_role(&pool, TopologyItem::Subnet(id), Role::Editor).await?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let current = fetch_subnet(&mut tx, id).await?;
+        let previous = current.clone();
+
+        // Fields that weren't provided keep their current value
+        let cidr = data.cidr.unwrap_or(current.cidr);
+        let gateway = match data.gateway.as_deref() {
+            Some(gateway) => non_empty(gateway),
+            None => current.gateway,
+        };
+        let vlan_id = match data.vlan_id {
+            Some(vlan_id) => Some(vlan_id).filter(|vlan_id| *vlan_id != 0),
+            None => current.vlan_id,
+        };
+        let description = match data.description.as_deref() {
+            Some(description) => non_empty(description),
+            None => current.description,
+        };
+        let name = data.name.map(|name| name.trim().to_string()).unwrap_or(current.name);
+        let prefix = validate_subnet(&cidr, gateway.as_deref(), vlan_id).map_err(AppError::invalid)?;
+
+        sqlx::query(
+            "UPDATE subnets
+             SET cidr = $1, name = $2, gateway = $3, vlan_id = $4, description = $5, updated_at = $6
+             WHERE id = $7",
+        )
+        .bind(prefix.to_string())
+        .bind(&name)
+        .bind(&gateway)
+        .bind(vlan_id)
+        .bind(&description)
+        .bind(unix_now())
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                AppError::conflict(format!("Subnet {} already exists in this topology", prefix))
+            }
+            other => constraint_error(other),
+        })?;
+
+        let subnet = fetch_subnet(&mut tx, id).await?;
+        record_audit(&mut *tx, &user, AuditRecord::subnet(AuditAction::Update, Some(&previous), Some(&subnet))).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        Ok(subnet)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Delete a subnet (addresses in it are kept)
+#[server(DeleteSubnet, "/api")]
+pub async fn delete_subnet(id: i64) -> Result<(), AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_item_role(&pool, TopologyItem::Subnet(id), Role::Editor).await?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let subnet = fetch_subnet(&mut tx, id).await?;
+        sqlx::query("DELETE FROM subnets WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        record_audit(&mut *tx, &user, AuditRecord::subnet(AuditAction::Delete, Some(&subnet), None)).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Next free host address in a subnet, or None when it is full
+#[server(NextFreeIp, "/api")]
+pub async fn next_free_ip(subnet_id: i64) -> Result<Option<String>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_item_role(&pool, TopologyItem::Subnet(subnet_id), Role::Viewer).await?;
+
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        let subnet = fetch_subnet(&mut conn, subnet_id).await?;
+        let prefix: IpPrefix = subnet.cidr.parse().map_err(AppError::invalid)?;
+
+        let holders = fetch_ip_holders(&mut conn, subnet.topology_id)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        Ok(next_free_address(&prefix, &subnet, &holders))
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Subnet usage, duplicate addresses and links between subnets for a topology
+#[server(GetIpamReport, "/api")]
+pub async fn get_ipam_report(topology_id: i64) -> Result<IpamReport, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+        use std::collections::{BTreeMap, HashMap};
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_role(&pool, topology_id, Role::Viewer).await?;
+
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        let full = load_topology_full(&mut conn, topology_id).await?;
+        let holders = fetch_ip_holders(&mut conn, topology_id)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        let prefixes = subnet_prefixes(&full.subnets);
+
+        let mut report = IpamReport::default();
+        let mut addresses = Vec::new(); // (parsed address, holder)
+        for holder in &holders {
+            match parse_address(&holder.address) {
+                Ok((ip, _)) => addresses.push((ip, holder)),
+                Err(_) => report.invalid.push(holder.clone()),
+            }
+        }
+
+        // Duplicates: one address on more than one node (a node may repeat its own address
+        // on an interface)
+        let mut by_address: BTreeMap<std::net::IpAddr, Vec<&IpHolder>> = BTreeMap::new();
+        for (ip, holder) in &addresses {
+            by_address.entry(*ip).or_default().push(holder);
+        }
+        for (ip, holders) in by_address {
+            let mut node_ids: Vec<i64> = holders.iter().map(|holder| holder.node_id).collect();
+            node_ids.sort_unstable();
+            node_ids.dedup();
+            if node_ids.len() > 1 {
+                report.conflicts.push(IpConflict {
+                    address: ip.to_string(),
+                    holders: holders.into_iter().cloned().collect(),
+                });
+            }
+        }
+
+        for (prefix, subnet) in &prefixes {
+            let mut used: Vec<std::net::IpAddr> =
+                addresses.iter().map(|(ip, _)| *ip).filter(|ip| prefix.contains(*ip)).collect();
+            used.sort_unstable();
+            used.dedup();
+            report.subnets.push(SubnetUsage {
+                subnet: (*subnet).clone(),
+                used: used.len(),
+                capacity: prefix.capacity(),
+                next_free: next_free_address(prefix, subnet, &holders),
+            });
+        }
+        if !prefixes.is_empty() {
+            report.unassigned = addresses
+                .iter()
+                .filter(|(ip, _)| containing_subnet(&prefixes, *ip).is_none())
+                .map(|(_, holder)| (*holder).clone())
+                .collect();
+        }
+
+        // Each end of a link is addressed by its interface when it has one, else by the node.
+        // The subnet is the most specific defined one, or the address's own prefix length.
+        let nodes: HashMap<i64, &Node> = full.nodes.iter().map(|node| (node.id, node)).collect();
+        let end_address = |node_id: i64, interface_id: Option<i64>| -> Option<(IpHolder, String)> {
+            let holder = interface_id
+                .and_then(|id| holders.iter().find(|holder| holder.interface_id == Some(id)))
+                .or_else(|| holders.iter().find(|holder| holder.node_id == node_id && holder.interface_id.is_none()))?;
+            let (ip, len) = parse_address(&holder.address).ok()?;
+            let subnet = match containing_subnet(&prefixes, ip) {
+                Some((prefix, _)) => prefix,
+                None => IpPrefix::containing(ip, len?)?,
+            };
+            Some((holder.clone(), subnet.to_string()))
+        };
+        for connection in &full.connections {
+            let routed = [connection.source_node_id, connection.target_node_id]
+                .iter()
+                .any(|id| nodes.get(id).is_some_and(|node| node.node_type.is_layer3()));
+            if routed {
+                continue;
+            }
+            let source = end_address(connection.source_node_id, connection.source_interface_id);
+            let target = end_address(connection.target_node_id, connection.target_interface_id);
+            if let (Some((source, source_subnet)), Some((target, target_subnet))) = (source, target) {
+                if source_subnet != target_subnet {
+                    report.mismatched_links.push(SubnetMismatch {
+                        connection_id: connection.id,
+                        source,
+                        source_subnet,
+                        target,
+                        target_subnet,
+                    });
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+// ============================================================================
+// Node Groups
+// ============================================================================
+//
+// Groups (sites, buildings, racks, security zones) nest through `parent_id` and
+// nodes join them through `nodes.group_id`. Group edits aren't undoable, but moving
+// a group is recorded as one undo step of node moves.
+
+/// Columns selected for `NodeGroup`
+#[cfg(feature = "ssr")]
+const GROUP_COLUMNS: &str = "id, topology_id, parent_id, name, group_type, color, collapsed, created_at, updated_at";
+
+#[cfg(feature = "ssr")]
+async fn fetch_group(conn: &mut DbConnection, id: i64) -> Result<NodeGroup, AppError> {
+    sqlx::query_as::<_, NodeGroup>(&format!("SELECT {} FROM node_groups WHERE id = $1", GROUP_COLUMNS))
+        .bind(id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| AppError::not_found(format!("Group not found: {}", e)))
+}
+
+#[cfg(feature = "ssr")]
+async fn fetch_topology_groups(
+    conn: &mut DbConnection,
+    topology_id: i64,
+) -> Result<Vec<NodeGroup>, sqlx::Error> {
+    sqlx::query_as::<_, NodeGroup>(&format!(
+        "SELECT {} FROM node_groups WHERE topology_id = $1 ORDER BY id",
+        GROUP_COLUMNS
+    ))
+    .bind(topology_id)
+    .fetch_all(conn)
+    .await
+}
+
+/// Insert a copy of a group into a topology under `parent_id`, returning the new group ID
+#[cfg(feature = "ssr")]
+async fn insert_group_copy(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    group: &NodeGroup,
+    parent_id: Option<i64>,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query_scalar::<_, i64>(
+        "INSERT INTO node_groups (topology_id, parent_id, name, group_type, color, collapsed) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+    )
+    .bind(topology_id)
+    .bind(parent_id)
+    .bind(&group.name)
+    .bind(group.group_type)
+    .bind(group.color)
+    .bind(group.collapsed)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(id)
+}
+
+/// Insert a group with its original ID (its parent must already exist)
+#[cfg(feature = "ssr")]
+async fn insert_group_with_id(conn: &mut DbConnection, group: &NodeGroup) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO node_groups (id, topology_id, parent_id, name, group_type, color, collapsed, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+    )
+    .bind(group.id)
+    .bind(group.topology_id)
+    .bind(group.parent_id)
+    .bind(&group.name)
+    .bind(group.group_type)
+    .bind(group.color)
+    .bind(group.collapsed)
+    .bind(group.created_at)
+    .bind(group.updated_at)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Replace every group of a topology with `groups`, keeping their IDs
+///
+/// Nodes in a removed group are left ungrouped.
+#[cfg(feature = "ssr")]
+async fn replace_groups(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    groups: &[NodeGroup],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM node_groups WHERE topology_id = $1")
+        .bind(topology_id)
+        .execute(&mut *conn)
+        .await?;
+    for (group, _) in group_tree(groups) {
+        insert_group_with_id(conn, &NodeGroup { topology_id, ..group.clone() }).await?;
+    }
+    Ok(())
+}
+
+/// Check that `parent_id` can be the parent of `group_id` (same topology, no cycle)
+#[cfg(feature = "ssr")]
+async fn check_group_parent(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    group_id: Option<i64>,
+    parent_id: i64,
+) -> Result<(), AppError> {
+    let groups = fetch_topology_groups(conn, topology_id)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+    if !groups.iter().any(|group| group.id == parent_id) {
+        return Err(AppError::invalid(format!("Group {} is not in this topology", parent_id)));
+    }
+    if let Some(group_id) = group_id {
+        if group_subtree(&groups, group_id).contains(&parent_id) {
+            return Err(AppError::invalid("A group cannot be moved inside itself"));
+        }
+    }
+    Ok(())
+}
+
+/// Get all groups of a topology
+#[server(GetNodeGroups, "/api")]
+pub async fn get_node_groups(topology_id: i64) -> Result<Vec<NodeGroup>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_role(&pool, topology_id, Role::Viewer).await?;
+
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        fetch_topology_groups(&mut conn, topology_id)
+            .await
+            .map_err(|e| db_error("Database error", e))
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Add a group to a topology
+#[server(CreateNodeGroupFn, "/api", client = LiveClient)]
+pub async fn create_node_group(data: CreateNodeGroup) -> Result<NodeGroup, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_role(&pool, data.topology_id, Role::Editor).await?;
+
+        validate_group_name(&data.name).map_err(AppError::invalid)?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        if let Some(parent_id) = data.parent_id {
+            check_group_parent(&mut tx, data.topology_id, None, parent_id).await?;
+        }
+
+        let id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO node_groups (topology_id, parent_id, name, group_type, color) VALUES ($1, $2, $3, $4, $5) RETURNING id",
+        )
+        .bind(data.topology_id)
+        .bind(data.parent_id)
+        .bind(data.name.trim())
+        .bind(data.group_type)
+        .bind(data.color.unwrap_or_else(|| data.group_type.default_color()))
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(constraint_error)?;
+
+        let group = fetch_group(&mut tx, id).await?;
+        record_audit(&mut *tx, &user, AuditRecord::group(AuditAction::Create, None, Some(&group))).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(group.topology_id, LiveEvent::TopologyChanged).await;
+
+        Ok(group)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Update a group: rename, re-parent, recolor, or collapse it to a single proxy node
+#[server(UpdateNodeGroupFn, "/api", client = LiveClient)]
+pub async fn update_node_group(id: i64, data: UpdateNodeGroup) -> Result<NodeGroup, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_item_role(&pool, TopologyItem::Group(id), Role::Editor).await?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let current = fetch_group(&mut tx, id).await?;
+        let previous = current.clone();
+
+        // Fields that weren't provided keep their current value
+        let name = match data.name {
+            Some(name) => {
+                validate_group_name(&name).map_err(AppError::invalid)?;
+                name.trim().to_string()
+            }
+            None => current.name,
+        };
+        let parent_id = match data.parent_id {
+            Some(0) => None,
+            Some(parent_id) => {
+                check_group_parent(&mut tx, current.topology_id, Some(id), parent_id).await?;
+                Some(parent_id)
+            }
+            None => current.parent_id,
+        };
+
+        sqlx::query(
+            "UPDATE node_groups
+             SET parent_id = $1, name = $2, group_type = $3, color = $4, collapsed = $5, updated_at = $6
+             WHERE id = $7",
+        )
+        .bind(parent_id)
+        .bind(&name)
+        .bind(data.group_type.unwrap_or(current.group_type))
+        .bind(data.color.unwrap_or(current.color))
+        .bind(data.collapsed.unwrap_or(current.collapsed))
+        .bind(unix_now())
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(constraint_error)?;
+
+        let group = fetch_group(&mut tx, id).await?;
+        let record = AuditRecord::group(AuditAction::Update, Some(&previous), Some(&group));
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(group.topology_id, LiveEvent::TopologyChanged).await;
+
+        Ok(group)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Delete a group; its member nodes and child groups move up to its parent
+#[server(DeleteNodeGroup, "/api", client = LiveClient)]
+pub async fn delete_node_group(id: i64) -> Result<(), AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_item_role(&pool, TopologyItem::Group(id), Role::Editor).await?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let group = fetch_group(&mut tx, id).await?;
+
+        sqlx::query("UPDATE nodes SET group_id = $1, version = version + 1 WHERE group_id = $2")
+            .bind(group.parent_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        sqlx::query("UPDATE node_groups SET parent_id = $1 WHERE parent_id = $2")
+            .bind(group.parent_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        sqlx::query("DELETE FROM node_groups WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        record_audit(&mut *tx, &user, AuditRecord::group(AuditAction::Delete, Some(&group), None)).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(group.topology_id, LiveEvent::TopologyChanged).await;
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Move every node in a group (including its child groups) by the same offset
+///
+/// Recorded as a single undo step. Returns the moved nodes.
+#[server(MoveNodeGroup, "/api", client = LiveClient)]
+pub async fn move_node_group(id: i64, dx: f64, dy: f64, dz: f64) -> Result<Vec<Node>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_item_role(&pool, TopologyItem::Group(id), Role::Editor).await?;
+
+        if ![dx, dy, dz].iter().all(|d| d.is_finite()) {
+            return Err(AppError::invalid("Offset must be a finite number"));
+        }
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let group = fetch_group(&mut tx, id).await?;
+        let groups = fetch_topology_groups(&mut tx, group.topology_id)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        let subtree = group_subtree(&groups, id);
+
+        let mut query = sqlx::QueryBuilder::<Db>::new(
+            "SELECT id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, group_id, metadata, created_at, updated_at, version
+             FROM nodes WHERE group_id IN (",
+        );
+        let mut group_ids = query.separated(", ");
+        for group_id in &subtree {
+            group_ids.push_bind(*group_id);
+        }
+        query.push(") ORDER BY id");
+        let members = query
+            .build_query_as::<Node>()
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        let label = format!("Move group '{}'", group.name);
+        let change_set_id = current_change_set(&mut tx, group.topology_id, &label)
+            .await
+            .map_err(|e| db_error("Failed to save undo history", e))?;
+
+        let mut moved = Vec::with_capacity(members.len());
+        for node in members {
+            let after = Node {
+                position_x: node.position_x + dx,
+                position_y: node.position_y + dy,
+                position_z: node.position_z + dz,
+                version: node.version + 1,
+                ..node.clone()
+            };
+            sqlx::query(
+                "UPDATE nodes SET position_x = $1, position_y = $2, position_z = $3, version = version + 1, updated_at = $4
+                 WHERE id = $5",
+            )
+                .bind(after.position_x)
+                .bind(after.position_y)
+                .bind(after.position_z)
+                .bind(unix_now())
+                .bind(node.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| db_error("Database error", e))?;
+            let entry = UndoEntry::node("update", Some(&node), Some(&after))
+                .map_err(|e| db_error("Failed to save undo history", e))?;
+            insert_undo_entry(&mut tx, group.topology_id, change_set_id, entry)
+                .await
+                .map_err(|e| db_error("Failed to save undo history", e))?;
+            record_audit(&mut *tx, &user, AuditRecord::node(AuditAction::Update, Some(&node), Some(&after))).await?;
+            moved.push(after);
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(group.topology_id, LiveEvent::TopologyChanged).await;
+
+        Ok(moved)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+// ============================================================================
+// Tags and Filtering
+// ============================================================================
+//
+// Tags are free-form labels shared by the nodes and connections of a topology
+// (`tags`, with `item_tags` linking them to items). Filter queries such as
+// `type:router AND tag:core` are parsed by `FilterQuery` and evaluated against
+// the whole topology; the result is either highlighted by the viewport or written
+// to the nodes' `visible` flags as one undo step.
+
+/// Columns selected for `Tag`
+#[cfg(feature = "ssr")]
+const TAG_COLUMNS: &str = "id, topology_id, name, created_at";
+
+/// A node or connection that tags attach to
+#[cfg(feature = "ssr")]
+#[derive(Debug, Clone, Copy)]
+enum TaggedItem {
+    Node(i64),
+    Connection(i64),
+}
+
+#[cfg(feature = "ssr")]
+impl TaggedItem {
+    /// `item_tags` column referring to this kind of item
+    fn column(self) -> &'static str {
+        match self {
+            TaggedItem::Node(_) => "node_id",
+            TaggedItem::Connection(_) => "connection_id",
+        }
+    }
+
+    fn id(self) -> i64 {
+        match self {
+            TaggedItem::Node(id) | TaggedItem::Connection(id) => id,
+        }
+    }
+
+    async fn topology_id(self, conn: &mut DbConnection) -> Result<i64, AppError> {
+        match self {
+            TaggedItem::Node(id) => node_topology_id(conn, id).await,
+            TaggedItem::Connection(id) => sqlx::query_scalar::<_, i64>("SELECT topology_id FROM connections WHERE id = $1")
+                .bind(id)
+                .fetch_one(conn)
+                .await
+                .map_err(|e| AppError::not_found(format!("Connection not found: {}", e))),
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+async fn fetch_topology_tags(conn: &mut DbConnection, topology_id: i64) -> Result<Vec<Tag>, sqlx::Error> {
+    sqlx::query_as::<_, Tag>(&format!(
+        "SELECT {} FROM tags WHERE topology_id = $1 ORDER BY LOWER(name)",
+        TAG_COLUMNS
+    ))
+    .bind(topology_id)
+    .fetch_all(conn)
+    .await
+}
+
+#[cfg(feature = "ssr")]
+async fn fetch_topology_tag_assignments(
+    conn: &mut DbConnection,
+    topology_id: i64,
+) -> Result<Vec<TagAssignment>, sqlx::Error> {
+    sqlx::query_as::<_, TagAssignment>(
+        "SELECT it.tag_id, it.node_id, it.connection_id FROM item_tags it JOIN tags t ON t.id = it.tag_id
+         WHERE t.topology_id = $1 ORDER BY it.tag_id, it.node_id, it.connection_id",
+    )
+    .bind(topology_id)
+    .fetch_all(conn)
+    .await
+}
+
+/// Tags of a node or connection, by name
+#[cfg(feature = "ssr")]
+async fn fetch_item_tags(conn: &mut DbConnection, item: TaggedItem) -> Result<Vec<Tag>, sqlx::Error> {
+    sqlx::query_as::<_, Tag>(&format!(
+        "SELECT t.id, t.topology_id, t.name, t.created_at FROM tags t JOIN item_tags it ON it.tag_id = t.id
+         WHERE it.{} = $1 ORDER BY LOWER(t.name)",
+        item.column()
+    ))
+    .bind(item.id())
+    .fetch_all(conn)
+    .await
+}
+
+/// Replace the tags of a node or connection, creating tags that don't exist yet
+///
+/// `names` must already be validated with `normalize_tag_name`.
+#[cfg(feature = "ssr")]
+async fn replace_item_tags(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    item: TaggedItem,
+    names: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query(&format!("DELETE FROM item_tags WHERE {} = $1", item.column()))
+        .bind(item.id())
+        .execute(&mut *conn)
+        .await?;
+    for name in names {
+        sqlx::query("INSERT INTO tags (topology_id, name) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(topology_id)
+            .bind(name)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query(&format!(
+            "INSERT INTO item_tags (tag_id, {}) SELECT id, $1 FROM tags WHERE topology_id = $2 AND LOWER(name) = LOWER($3) ON CONFLICT DO NOTHING",
+            item.column()
+        ))
+        .bind(item.id())
+        .bind(topology_id)
+        .bind(name)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Insert a tag with its original ID, or rename it in place if it still exists
+///
+/// Returns false, writing nothing, if the ID belongs to a tag of another topology.
+#[cfg(feature = "ssr")]
+async fn upsert_tag(conn: &mut DbConnection, tag: &Tag) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO tags (id, topology_id, name, created_at) VALUES ($1, $2, $3, $4)
+         ON CONFLICT(id) DO UPDATE SET name = excluded.name
+         WHERE tags.topology_id = excluded.topology_id",
+    )
+    .bind(tag.id)
+    .bind(tag.topology_id)
+    .bind(&tag.name)
+    .bind(tag.created_at)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Replace every tag of a topology with `tags`, keeping their IDs
+///
+/// Removed tags take their assignments with them. Returns false if a tag ID belongs to
+/// another topology; the caller rolls back.
+#[cfg(feature = "ssr")]
+async fn replace_tags(conn: &mut DbConnection, topology_id: i64, tags: &[Tag]) -> Result<bool, sqlx::Error> {
+    sqlx::query("DELETE FROM tags WHERE topology_id = $1")
+        .bind(topology_id)
+        .execute(&mut *conn)
+        .await?;
+    for tag in tags {
+        if !upsert_tag(conn, &Tag { topology_id, ..tag.clone() }).await? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Copy tags and their assignments into a topology, returning how many tags were created
+///
+/// Assignments whose node or connection isn't in the ID maps are dropped; tags whose
+/// names differ only by case are merged.
+#[cfg(feature = "ssr")]
+async fn insert_tag_copies(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    tags: &[Tag],
+    assignments: &[TagAssignment],
+    node_id_map: &std::collections::HashMap<i64, i64>,
+    connection_id_map: &std::collections::HashMap<i64, i64>,
+) -> Result<usize, sqlx::Error> {
+    let mut tag_id_map = std::collections::HashMap::new();
+    let mut created = 0;
+    for tag in tags {
+        let result = sqlx::query("INSERT INTO tags (topology_id, name) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(topology_id)
+            .bind(&tag.name)
+            .execute(&mut *conn)
+            .await?;
+        created += result.rows_affected() as usize;
+        let new_id: i64 = sqlx::query_scalar("SELECT id FROM tags WHERE topology_id = $1 AND LOWER(name) = LOWER($2)")
+            .bind(topology_id)
+            .bind(&tag.name)
+            .fetch_one(&mut *conn)
+            .await?;
+        tag_id_map.insert(tag.id, new_id);
+    }
+
+    for assignment in assignments {
+        let Some(&tag_id) = tag_id_map.get(&assignment.tag_id) else { continue };
+        let node_id = assignment.node_id.map(|id| node_id_map.get(&id).copied());
+        let connection_id = assignment.connection_id.map(|id| connection_id_map.get(&id).copied());
+        let (node_id, connection_id) = match (node_id, connection_id) {
+            (Some(Some(node_id)), None) => (Some(node_id), None),
+            (None, Some(Some(connection_id))) => (None, Some(connection_id)),
+            _ => continue,
+        };
+        sqlx::query("INSERT INTO item_tags (tag_id, node_id, connection_id) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING")
+            .bind(tag_id)
+            .bind(node_id)
+            .bind(connection_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(created)
+}
+
+/// Validate a list of tag names, dropping case-insensitive duplicates
+#[cfg(feature = "ssr")]
+fn normalize_tag_names(names: &[String]) -> Result<Vec<String>, AppError> {
+    let mut normalized: Vec<String> = Vec::with_capacity(names.len());
+    for name in names {
+        let name = normalize_tag_name(name).map_err(AppError::invalid)?;
+        if !normalized.iter().any(|existing| existing.eq_ignore_ascii_case(&name)) {
+            normalized.push(name);
+        }
+    }
+    Ok(normalized)
+}
+
+/// Get every tag used in a topology
+#[server(GetTags, "/api")]
+pub async fn get_tags(topology_id: i64) -> Result<Vec<Tag>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_role(&pool, topology_id, Role::Viewer).await?;
+
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        fetch_topology_tags(&mut conn, topology_id)
+            .await
+            .map_err(|e| db_error("Database error", e))
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Get the tags of a node
+#[server(GetNodeTags, "/api")]
+pub async fn get_node_tags(node_id: i64) -> Result<Vec<Tag>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_item_role(&pool, TopologyItem::Node(node_id), Role::Viewer).await?;
+
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        fetch_item_tags(&mut conn, TaggedItem::Node(node_id))
+            .await
+            .map_err(|e| db_error("Database error", e))
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Replace the tags of a node, creating new tags as needed
+///
+/// Sent as JSON: the default URL encoding can't express an empty list.
+#[server(SetNodeTags, "/api", input = leptos::server_fn::codec::Json, client = LiveClient)]
+pub async fn set_node_tags(node_id: i64, tags: Vec<String>) -> Result<Vec<Tag>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        set_item_tags(TaggedItem::Node(node_id), &tags).await
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Get the tags of a connection
+#[server(GetConnectionTags, "/api")]
+pub async fn get_connection_tags(connection_id: i64) -> Result<Vec<Tag>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_item_role(&pool, TopologyItem::Connection(connection_id), Role::Viewer).await?;
+
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        fetch_item_tags(&mut conn, TaggedItem::Connection(connection_id))
+            .await
+            .map_err(|e| db_error("Database error", e))
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Replace the tags of a connection, creating new tags as needed
+///
+/// Sent as JSON for the same reason as `set_node_tags`.
+#[server(SetConnectionTags, "/api", input = leptos::server_fn::codec::Json, client = LiveClient)]
+pub async fn set_connection_tags(connection_id: i64, tags: Vec<String>) -> Result<Vec<Tag>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        set_item_tags(TaggedItem::Connection(connection_id), &tags).await
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Shared body of `set_node_tags` and `set_connection_tags`
+#[cfg(feature = "ssr")]
+async fn set_item_tags(item: TaggedItem, tags: &[String]) -> Result<Vec<Tag>, AppError> {
+    use axum::Extension;
+    use leptos_axum::extract;
+
+    let Extension(pool) = extract::<Extension<DbPool>>()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+    let user = require_item_role(&pool, item.into(), Role::Editor).await?;
+
+    let names = normalize_tag_names(tags)?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| db_error("Failed to start transaction", e))?;
+    let topology_id = item.topology_id(&mut tx).await?;
+    let previous = fetch_item_tags(&mut tx, item)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+    replace_item_tags(&mut tx, topology_id, item, &names)
+        .await
+        .map_err(constraint_error)?;
+    let tags = fetch_item_tags(&mut tx, item)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+
+    let (entity_type, kind) = match item {
+        TaggedItem::Node(_) => (AuditEntity::Node, "node"),
+        TaggedItem::Connection(_) => (AuditEntity::Connection, "connection"),
+    };
+    let tag_names = |tags: &[Tag]| serde_json::json!({ "tags": tags.iter().map(|tag| &tag.name).collect::<Vec<_>>() });
+    let summary = format!("Update tags of {} {}", kind, item.id());
+    let record = AuditRecord::new(Some(topology_id), entity_type, Some(item.id()), AuditAction::Update, summary)
+        .states(Some(&tag_names(&previous)), Some(&tag_names(&tags)));
+    record_audit(&mut *tx, &user, record).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+    publish_live(topology_id, LiveEvent::TopologyChanged).await;
+
+    Ok(tags)
+}
+
+/// Delete a tag, removing it from every node and connection
+#[server(DeleteTag, "/api", client = LiveClient)]
+pub async fn delete_tag(id: i64) -> Result<(), AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_item_role(&pool, TopologyItem::Tag(id), Role::Editor).await?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let tag = sqlx::query_as::<_, Tag>(&format!("SELECT {} FROM tags WHERE id = $1", TAG_COLUMNS))
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| AppError::not_found(format!("Tag not found: {}", e)))?;
+        sqlx::query("DELETE FROM tags WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        let record = AuditRecord::new(
+            Some(tag.topology_id),
+            AuditEntity::Tag,
+            Some(tag.id),
+            AuditAction::Delete,
+            format!("Delete tag '{}'", tag.name),
+        )
+        .states(Some(&tag), None);
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(tag.topology_id, LiveEvent::TopologyChanged).await;
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Find the nodes and connections of a topology matching a filter query
+///
+/// See `FilterQuery` for the syntax; an empty query matches everything.
+#[server(FilterTopology, "/api")]
+pub async fn filter_topology(topology_id: i64, query: String) -> Result<FilterMatches, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_role(&pool, topology_id, Role::Viewer).await?;
+
+        let query: FilterQuery = query.parse().map_err(AppError::invalid)?;
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        let full = load_topology_full(&mut conn, topology_id).await?;
+        Ok(query.apply(&full))
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Show only the nodes matching a filter query, hiding every other node
+///
+/// Connections follow their endpoints. An empty query shows everything again.
+/// Recorded as a single undo step; returns the matches.
+#[server(ApplyFilterVisibility, "/api", client = LiveClient)]
+pub async fn apply_filter_visibility(topology_id: i64, query: String) -> Result<FilterMatches, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_role(&pool, topology_id, Role::Editor).await?;
+
+        let parsed: FilterQuery = query.parse().map_err(AppError::invalid)?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let full = load_topology_full(&mut tx, topology_id).await?;
+        let matches = parsed.apply(&full);
+
+        let label = if parsed.is_all() {
+            "Show all nodes".to_string()
+        } else {
+            format!("Show only '{}'", query.trim())
+        };
+        let mut change_set_id = None;
+        for node in full.nodes.iter().filter(|node| node.visible != matches.has_node(node.id)) {
+            let after = Node { visible: !node.visible, version: node.version + 1, ..node.clone() };
+            sqlx::query("UPDATE nodes SET visible = $1, version = version + 1, updated_at = $2 WHERE id = $3")
+                .bind(after.visible)
+                .bind(unix_now())
+                .bind(node.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| db_error("Database error", e))?;
+
+            let change_set_id = match change_set_id {
+                Some(id) => id,
+                None => *change_set_id.insert(
+                    current_change_set(&mut tx, topology_id, &label)
+                        .await
+                        .map_err(|e| db_error("Failed to save undo history", e))?,
+                ),
+            };
+            let entry = UndoEntry::node("update", Some(node), Some(&after))
+                .map_err(|e| db_error("Failed to save undo history", e))?;
+            insert_undo_entry(&mut tx, topology_id, change_set_id, entry)
+                .await
+                .map_err(|e| db_error("Failed to save undo history", e))?;
+            record_audit(&mut *tx, &user, AuditRecord::node(AuditAction::Update, Some(node), Some(&after))).await?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        publish_live(topology_id, LiveEvent::TopologyChanged).await;
+
+        Ok(matches)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+// ============================================================================
+// Global Search
+// ============================================================================
+//
+// `search_index` is an FTS5 table over topology names and descriptions, node
+// names, addresses, vendors, models and metadata, and connection metadata. It
+// is maintained by triggers (see the add_search_index migration), so every
+// write path (CRUD, import, undo, revision restore) keeps it current. On
+// PostgreSQL the `search_documents` view carries a weighted tsvector instead.
+
+/// Search every topology for nodes, connections and topologies matching `query`
+#[server(SearchTopologies, "/api")]
+pub async fn search_topologies(query: String) -> Result<Vec<SearchHit>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_user(&pool).await?;
+
+        #[cfg(not(feature = "postgres"))]
+        let match_query = crate::models::fts_match_query(&query);
+        #[cfg(feature = "postgres")]
+        let match_query = crate::models::tsquery(&query);
+        let Some(match_query) = match_query else {
+            return Ok(Vec::new());
+        };
+
+        // Name matches rank well above matches in the detail text
+        #[cfg(not(feature = "postgres"))]
+        let sql =
+            "SELECT search_index.kind,
+                    CAST(search_index.item_id AS INTEGER) AS item_id,
+                    CAST(search_index.topology_id AS INTEGER) AS topology_id,
+                    t.name AS topology_name,
+                    CASE search_index.kind
+                        WHEN 'connection' THEN COALESCE((
+                            SELECT a.name || ' ↔ ' || b.name
+                            FROM connections c
+                            JOIN nodes a ON a.id = c.source_node_id
+                            JOIN nodes b ON b.id = c.target_node_id
+                            WHERE c.id = search_index.item_id), '')
+                        ELSE search_index.name
+                    END AS title,
+                    snippet(search_index, -1, '[', ']', '…', 8) AS snippet
+             FROM search_index
+             JOIN topologies t ON t.id = search_index.topology_id
+             JOIN topology_permissions p ON p.topology_id = t.id AND p.user_id = $1
+             WHERE search_index MATCH $2
+             ORDER BY bm25(search_index, 0.0, 0.0, 0.0, 10.0, 1.0)
+             LIMIT $3";
+        #[cfg(feature = "postgres")]
+        let sql =
+            "SELECT d.kind, d.item_id, d.topology_id,
+                    t.name AS topology_name,
+                    CASE d.kind
+                        WHEN 'connection' THEN COALESCE((
+                            SELECT a.name || ' ↔ ' || b.name
+                            FROM connections c
+                            JOIN nodes a ON a.id = c.source_node_id
+                            JOIN nodes b ON b.id = c.target_node_id
+                            WHERE c.id = d.item_id), '')
+                        ELSE d.name
+                    END AS title,
+                    ts_headline('simple', d.name || ' ' || d.detail, q,
+                                'StartSel=[, StopSel=], MaxFragments=1, MaxWords=8, MinWords=1, FragmentDelimiter=…') AS snippet
+             FROM search_documents d
+             CROSS JOIN to_tsquery('simple', $2) AS q
+             JOIN topologies t ON t.id = d.topology_id
+             JOIN topology_permissions p ON p.topology_id = t.id AND p.user_id = $1
+             WHERE d.document @@ q
+             ORDER BY ts_rank('{0, 0, 0.1, 1.0}'::real[], d.document, q) DESC
+             LIMIT $3";
+        let hits = sqlx::query_as::<_, SearchHit>(sql)
+            .bind(user.id)
+            .bind(&match_query)
+            .bind(MAX_SEARCH_RESULTS)
+            .fetch_all(&pool)
+            .await
+            .map_err(|e| db_error("Search failed", e))?;
+
+        Ok(hits)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+// ============================================================================
+// Accounts and Permissions
+// ============================================================================
+//
+// Browsers authenticate with the session cookie set by `login`; scripts send an API
+// token as `Authorization: Bearer ntb_...`. Both secrets are stored only as SHA-256
+// hashes, passwords as Argon2id hashes. Every server function checks the caller's role
+// on the topology it touches (viewer < editor < owner) with `require_role` or
+// `require_item_role`. Callers without any role are told the topology doesn't exist.
+
+#[cfg(feature = "ssr")]
+const SESSION_COOKIE: &str = "ntb_session";
+
+/// Sessions expire after 30 days
+#[cfg(feature = "ssr")]
+const SESSION_LIFETIME_SECS: i64 = 30 * 24 * 60 * 60;
+
+#[cfg(feature = "ssr")]
+const API_TOKEN_PREFIX: &str = "ntb_";
+
+#[cfg(feature = "ssr")]
+const USER_COLUMNS: &str = "u.id, u.username, u.created_at, u.is_admin";
+
+/// Request extension that makes server functions run as this user without credentials
+///
+/// Only code in this process can insert it: the `ntb` command line does, after looking
+/// the account up with `find_user`, and so does the server in single-user mode
+/// (`auth.mode = "single"`) for every request.
+#[cfg(feature = "ssr")]
+#[derive(Debug, Clone)]
+pub struct LocalUser(pub User);
+
+/// Look an account up by name (case-insensitively)
+#[cfg(feature = "ssr")]
+pub async fn find_user(pool: &DbPool, username: &str) -> Result<Option<User>, AppError> {
+    sqlx::query_as::<_, User>(&format!(
+        "SELECT {} FROM users u WHERE LOWER(u.username) = LOWER($1)",
+        USER_COLUMNS
+    ))
+    .bind(username.trim())
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| db_error("Database error", e))
+}
+
+/// The server's configuration (the defaults when it has none, e.g. outside a request)
+#[cfg(feature = "ssr")]
+async fn server_config() -> std::sync::Arc<NtbConfig> {
+    use axum::Extension;
+    use leptos_axum::extract;
+
+    extract::<Extension<std::sync::Arc<NtbConfig>>>()
+        .await
+        .map(|Extension(config)| config)
+        .unwrap_or_default()
+}
+
+/// Something that belongs to a topology, for permission checks
+#[cfg(feature = "ssr")]
+#[derive(Debug, Clone, Copy)]
+enum TopologyItem {
+    Node(i64),
+    Connection(i64),
+    Interface(i64),
+    Subnet(i64),
+    Group(i64),
+    Tag(i64),
+    Revision(i64),
+}
+
+#[cfg(feature = "ssr")]
+impl TopologyItem {
+    async fn topology_id(self, pool: &DbPool) -> Result<i64, AppError> {
+        let (kind, sql, id) = match self {
+            TopologyItem::Node(id) => ("Node", "SELECT topology_id FROM nodes WHERE id = $1", id),
+            TopologyItem::Connection(id) => ("Connection", "SELECT topology_id FROM connections WHERE id = $1", id),
+            TopologyItem::Interface(id) => (
+                "Interface",
+                "SELECT n.topology_id FROM interfaces i JOIN nodes n ON n.id = i.node_id WHERE i.id = $1",
+                id,
+            ),
+            TopologyItem::Subnet(id) => ("Subnet", "SELECT topology_id FROM subnets WHERE id = $1", id),
+            TopologyItem::Group(id) => ("Group", "SELECT topology_id FROM node_groups WHERE id = $1", id),
+            TopologyItem::Tag(id) => ("Tag", "SELECT topology_id FROM tags WHERE id = $1", id),
+            TopologyItem::Revision(id) => ("Revision", "SELECT topology_id FROM topology_revisions WHERE id = $1", id),
+        };
+        sqlx::query_scalar::<_, i64>(sql)
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| db_error("Database error", e))?
+            .ok_or_else(|| AppError::not_found(format!("{} not found: {}", kind, id)))
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl From<TaggedItem> for TopologyItem {
+    fn from(item: TaggedItem) -> Self {
+        match item {
+            TaggedItem::Node(id) => TopologyItem::Node(id),
+            TaggedItem::Connection(id) => TopologyItem::Connection(id),
+        }
+    }
+}
+
+/// 32 random bytes, hex encoded
+#[cfg(feature = "ssr")]
+fn random_secret() -> String {
+    use rand::RngCore;
+
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// How session ids and API tokens are stored
+#[cfg(feature = "ssr")]
+fn hash_secret(secret: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(secret.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(feature = "ssr")]
+fn hash_password(password: &str) -> Result<String, AppError> {
+    use argon2::password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+
+    let salt = SaltString::generate(&mut OsRng);
+    argon2::Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AppError::internal(format!("Failed to hash password: {}", e)))
+}
+
+#[cfg(feature = "ssr")]
+fn verify_password(password: &str, hash: &str) -> bool {
+    use argon2::password_hash::{PasswordHash, PasswordVerifier};
+
+    PasswordHash::new(hash)
+        .map(|parsed| argon2::Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+        .unwrap_or(false)
+}
+
+/// Value of the session cookie sent with the request
+#[cfg(feature = "ssr")]
+fn session_cookie(headers: &axum::http::HeaderMap) -> Option<String> {
+    headers
+        .get_all(axum::http::header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| pair.trim().strip_prefix(SESSION_COOKIE)?.strip_prefix('='))
+        .map(str::to_string)
+}
+
+/// API token sent as `Authorization: Bearer ...`
+#[cfg(feature = "ssr")]
+fn bearer_token(headers: &axum::http::HeaderMap) -> Option<&str> {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+/// Set (or with `max_age` 0, clear) the session cookie on the response
+///
+/// REST requests have no response options in context; they authenticate with tokens.
+/// The cookie is marked `Secure` when `server.secure_cookies` says ntb is served over HTTPS.
+#[cfg(feature = "ssr")]
+async fn set_session_cookie(value: &str, max_age: i64) {
+    use axum::http::{header, HeaderValue};
+    use leptos_axum::ResponseOptions;
+
+    let secure = if server_config().await.server.secure_cookies { "; Secure" } else { "" };
+    let cookie = format!(
+        "{}={}; Path=/; HttpOnly; SameSite=Lax; Max-Age={}{}",
+        SESSION_COOKIE, value, max_age, secure
+    );
+    if let (Some(options), Ok(cookie)) = (use_context::<ResponseOptions>(), HeaderValue::from_str(&cookie)) {
+        options.append_header(header::SET_COOKIE, cookie);
+    }
+}
+
+/// Start a session for a user and hand the cookie to the browser
+#[cfg(feature = "ssr")]
+async fn start_session(conn: &mut DbConnection, user_id: i64) -> Result<(), AppError> {
+    let session = random_secret();
+    sqlx::query(
+        "INSERT INTO sessions (user_id, session_hash, expires_at) VALUES ($1, $2, $3)",
+    )
+    .bind(user_id)
+    .bind(hash_secret(&session))
+    .bind(unix_now() + SESSION_LIFETIME_SECS)
+    .execute(conn)
+    .await
+    .map_err(|e| db_error("Failed to start session", e))?;
+
+    set_session_cookie(&session, SESSION_LIFETIME_SECS).await;
+    Ok(())
+}
+
+/// The user making the current request, from a bearer token or the session cookie
+#[cfg(feature = "ssr")]
+async fn request_user(pool: &DbPool) -> Result<Option<User>, AppError> {
+    use axum::http::HeaderMap;
+    use axum::Extension;
+    use leptos_axum::extract;
+
+    if let Ok(Extension(LocalUser(user))) = extract::<Extension<LocalUser>>().await {
+        return Ok(Some(user));
+    }
+
+    let headers: HeaderMap = extract()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to read request headers: {}", e)))?;
+
+    if let Some(token) = bearer_token(&headers) {
+        let token_hash = hash_secret(token);
+        let user = sqlx::query_as::<_, User>(&format!(
+            "SELECT {} FROM api_tokens t JOIN users u ON u.id = t.user_id WHERE t.token_hash = $1",
+            USER_COLUMNS
+        ))
+        .bind(&token_hash)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+        if user.is_some() {
+            sqlx::query("UPDATE api_tokens SET last_used_at = $1 WHERE token_hash = $2")
+                .bind(unix_now())
+                .bind(&token_hash)
+                .execute(pool)
+                .await
+                .map_err(|e| db_error("Database error", e))?;
+        }
+        return Ok(user);
+    }
+
+    let Some(session) = session_cookie(&headers) else {
+        return Ok(None);
+    };
+    sqlx::query_as::<_, User>(&format!(
+        "SELECT {} FROM sessions s JOIN users u ON u.id = s.user_id
+         WHERE s.session_hash = $1 AND s.expires_at > $2",
+        USER_COLUMNS
+    ))
+    .bind(hash_secret(&session))
+    .bind(unix_now())
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| db_error("Database error", e))
+}
+
+/// Hash of the bearer token or session cookie the request came with
+///
+/// Identifies one browser session (or script) without revealing its secret. It owns the
+/// session's open change sets and vouches for the viewer id a tab sends with its changes.
+#[cfg(feature = "ssr")]
+async fn request_origin() -> Option<String> {
+    use axum::http::HeaderMap;
+    use leptos_axum::extract;
+
+    let headers: HeaderMap = extract().await.ok()?;
+    match bearer_token(&headers) {
+        Some(token) => Some(hash_secret(token)),
+        None => session_cookie(&headers).map(|session| hash_secret(&session)),
+    }
+}
+
+/// The signed-in user, or an error for anonymous requests
+#[cfg(feature = "ssr")]
+async fn require_user(pool: &DbPool) -> Result<User, AppError> {
+    request_user(pool)
+        .await?
+        .ok_or_else(|| AppError::unauthenticated("Not signed in"))
+}
+
+/// A user's role on a topology (None = no access)
+#[cfg(feature = "ssr")]
+async fn topology_role(pool: &DbPool, user_id: i64, topology_id: i64) -> Result<Option<Role>, AppError> {
+    sqlx::query_scalar::<_, Role>("SELECT role FROM topology_permissions WHERE topology_id = $1 AND user_id = $2")
+        .bind(topology_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| db_error("Database error", e))
+}
+
+/// The signed-in user, or an error unless they are an administrator
+#[cfg(feature = "ssr")]
+async fn require_admin(pool: &DbPool) -> Result<User, AppError> {
+    let user = require_user(pool).await?;
+    if user.is_admin {
+        Ok(user)
+    } else {
+        Err(AppError::forbidden("Permission denied: administrator required"))
+    }
+}
+
+/// Check that the signed-in user has at least `role` on a topology
+#[cfg(feature = "ssr")]
+async fn require_role(pool: &DbPool, topology_id: i64, role: Role) -> Result<User, AppError> {
+    let user = require_user(pool).await?;
+    match topology_role(pool, user.id, topology_id).await? {
+        Some(granted) if granted >= role => Ok(user),
+        Some(_) => Err(AppError::forbidden(format!("Permission denied: {} role required", role.as_str()))),
+        None => Err(AppError::not_found(format!("Topology not found: {}", topology_id))),
+    }
+}
+
+/// Check the signed-in user's role on the topology an item belongs to
+#[cfg(feature = "ssr")]
+async fn require_item_role(pool: &DbPool, item: TopologyItem, role: Role) -> Result<User, AppError> {
+    let topology_id = item.topology_id(pool).await?;
+    require_role(pool, topology_id, role).await
+}
+
+/// Give a user a role on a topology (replacing any role they had)
+#[cfg(feature = "ssr")]
+async fn grant_role(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    user_id: i64,
+    role: Role,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO topology_permissions (topology_id, user_id, role) VALUES ($1, $2, $3)
+         ON CONFLICT (topology_id, user_id) DO UPDATE SET role = excluded.role",
+    )
+    .bind(topology_id)
+    .bind(user_id)
+    .bind(role)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Create an account and sign it in
+///
+/// The first account becomes the administrator and the owner of every topology created
+/// before accounts existed.
+#[server(RegisterUser, "/api")]
+pub async fn register_user(credentials: Credentials) -> Result<User, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        match server_config().await.auth.mode {
+            AuthMode::Open => {}
+            AuthMode::Closed => return Err(AppError::forbidden("Registration is disabled on this server")),
+            AuthMode::Single => {
+                return Err(AppError::forbidden("This server runs in single-user mode without accounts to register"))
+            }
+        }
+
+        validate_credentials(&credentials).map_err(AppError::invalid)?;
+        let password_hash = hash_password(&credentials.password)?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        let existing_users: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        let user_id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO users (username, password_hash, is_admin) VALUES ($1, $2, $3) RETURNING id",
+        )
+            .bind(credentials.username.trim())
+            .bind(&password_hash)
+            .bind(existing_users == 0)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db) if db.is_unique_violation() => AppError::conflict("Username is already taken"),
+                other => db_error("Database error", other),
+            })?;
+
+        if existing_users == 0 {
+            sqlx::query(
+                "INSERT INTO topology_permissions (topology_id, user_id, role)
+                 SELECT id, $1, 'owner' FROM topologies
+                 WHERE id NOT IN (SELECT topology_id FROM topology_permissions)",
+            )
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Failed to claim existing topologies", e))?;
+        }
+
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {} FROM users u WHERE u.id = $1", USER_COLUMNS))
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        let summary = format!("Create account '{}'", user.username);
+        let record = AuditRecord::new(None, AuditEntity::User, Some(user.id), AuditAction::Create, summary)
+            .states(None, Some(&user));
+        record_audit(&mut *tx, &user, record).await?;
+        start_session(&mut tx, user.id).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        Ok(user)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Sign in with a username and password (sets the session cookie)
+#[server(Login, "/api")]
+pub async fn login(credentials: Credentials) -> Result<User, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let row = sqlx::query("SELECT id, password_hash FROM users WHERE LOWER(username) = LOWER($1)")
+            .bind(credentials.username.trim())
+            .fetch_optional(&pool)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        let user_id = row
+            .filter(|row| verify_password(&credentials.password, row.get::<&str, _>("password_hash")))
+            .map(|row| row.get::<i64, _>("id"))
+            .ok_or_else(|| AppError::unauthenticated("Invalid username or password"))?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        // Drop this user's expired sessions while we're here
+        sqlx::query("DELETE FROM sessions WHERE user_id = $1 AND expires_at <= $2")
+            .bind(user_id)
+            .bind(unix_now())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {} FROM users u WHERE u.id = $1", USER_COLUMNS))
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        let record = AuditRecord::new(None, AuditEntity::User, Some(user.id), AuditAction::SignIn, "Sign in");
+        record_audit(&mut *tx, &user, record).await?;
+        start_session(&mut tx, user_id).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        Ok(user)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// End the current browser session
+#[server(Logout, "/api")]
+pub async fn logout() -> Result<(), AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::http::HeaderMap;
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = request_user(&pool).await?;
+        let headers: HeaderMap = extract()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to read request headers: {}", e)))?;
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        if let Some(session) = session_cookie(&headers) {
+            sqlx::query("DELETE FROM sessions WHERE session_hash = $1")
+                .bind(hash_secret(&session))
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| db_error("Database error", e))?;
+        }
+        if let Some(user) = user {
+            let record = AuditRecord::new(None, AuditEntity::User, Some(user.id), AuditAction::SignOut, "Sign out");
+            record_audit(&mut *tx, &user, record).await?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+        set_session_cookie("", 0).await;
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// The signed-in user (None when signed out)
+#[server(GetCurrentUser, "/api")]
+pub async fn get_current_user() -> Result<Option<User>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        request_user(&pool).await
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// API tokens of the signed-in user
+#[server(GetApiTokens, "/api")]
+pub async fn get_api_tokens() -> Result<Vec<ApiToken>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_user(&pool).await?;
+        sqlx::query_as::<_, ApiToken>(
+            "SELECT id, name, token_prefix, created_at, last_used_at FROM api_tokens WHERE user_id = $1 ORDER BY id",
+        )
+        .bind(user.id)
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| db_error("Database error", e))
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Create an API token for the signed-in user (the secret is only returned this once)
+#[server(CreateApiToken, "/api")]
+pub async fn create_api_token(name: String) -> Result<NewApiToken, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_user(&pool).await?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::invalid("Token name cannot be empty"));
+        }
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let secret = format!("{}{}", API_TOKEN_PREFIX, random_secret());
+        let token_prefix: String = secret.chars().take(API_TOKEN_PREFIX.len() + 6).collect();
+        let id = sqlx::query_scalar::<_, i64>("INSERT INTO api_tokens (user_id, name, token_prefix, token_hash) VALUES ($1, $2, $3, $4) RETURNING id")
+            .bind(user.id)
+            .bind(name)
+            .bind(&token_prefix)
+            .bind(hash_secret(&secret))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        let token = sqlx::query_as::<_, ApiToken>(
+            "SELECT id, name, token_prefix, created_at, last_used_at FROM api_tokens WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+
+        let summary = format!("Create API token '{}'", token.name);
+        let record = AuditRecord::new(None, AuditEntity::ApiToken, Some(token.id), AuditAction::Create, summary)
+            .states(None, Some(&token));
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        Ok(NewApiToken { token, secret })
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Revoke one of the signed-in user's API tokens
+#[server(DeleteApiToken, "/api")]
+pub async fn delete_api_token(id: i64) -> Result<(), AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_user(&pool).await?;
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let token = sqlx::query_as::<_, ApiToken>(
+            "SELECT id, name, token_prefix, created_at, last_used_at FROM api_tokens WHERE id = $1 AND user_id = $2",
+        )
+        .bind(id)
+        .bind(user.id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| db_error("Database error", e))?
+        .ok_or_else(|| AppError::not_found(format!("API token not found: {}", id)))?;
+
+        sqlx::query("DELETE FROM api_tokens WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        let summary = format!("Revoke API token '{}'", token.name);
+        let record = AuditRecord::new(None, AuditEntity::ApiToken, Some(token.id), AuditAction::Delete, summary)
+            .states(Some(&token), None);
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Who has access to a topology
+#[server(GetTopologyPermissions, "/api")]
+pub async fn get_topology_permissions(topology_id: i64) -> Result<Vec<TopologyPermission>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_role(&pool, topology_id, Role::Viewer).await?;
+        sqlx::query_as::<_, TopologyPermission>(
+            "SELECT p.topology_id, p.user_id, u.username, p.role
+             FROM topology_permissions p JOIN users u ON u.id = p.user_id
+             WHERE p.topology_id = $1
+             ORDER BY CASE p.role WHEN 'owner' THEN 0 WHEN 'editor' THEN 1 ELSE 2 END, u.username",
+        )
+        .bind(topology_id)
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| db_error("Database error", e))
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Give a user (by name) a role on a topology, or remove their access with `role: None`
+///
+/// Only owners can change permissions, and a topology always keeps at least one owner.
+#[server(SetTopologyPermission, "/api")]
+pub async fn set_topology_permission(
+    topology_id: i64,
+    username: String,
+    role: Option<Role>,
+) -> Result<Vec<TopologyPermission>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_role(&pool, topology_id, Role::Owner).await?;
+
+        let user_id: i64 = sqlx::query_scalar("SELECT id FROM users WHERE LOWER(username) = LOWER($1)")
+            .bind(username.trim())
+            .fetch_optional(&pool)
+            .await
+            .map_err(|e| db_error("Database error", e))?
+            .ok_or_else(|| AppError::not_found(format!("User not found: {}", username.trim())))?;
+        let previous = topology_role(&pool, user_id, topology_id).await?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        match role {
+            Some(role) => grant_role(&mut tx, topology_id, user_id, role)
+                .await
+                .map_err(|e| db_error("Database error", e))?,
+            None => {
+                sqlx::query("DELETE FROM topology_permissions WHERE topology_id = $1 AND user_id = $2")
+                    .bind(topology_id)
+                    .bind(user_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| db_error("Database error", e))?;
+            }
+        }
+
+        let owners: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM topology_permissions WHERE topology_id = $1 AND role = 'owner'",
+        )
+        .bind(topology_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+        if owners == 0 {
+            return Err(AppError::conflict("A topology must keep at least one owner"));
+        }
+
+        let action = match (previous, role) {
+            (None, _) => AuditAction::Create,
+            (Some(_), Some(_)) => AuditAction::Update,
+            (Some(_), None) => AuditAction::Delete,
+        };
+        let summary = match role {
+            Some(role) => format!("Give '{}' the {} role", username.trim(), role.as_str()),
+            None => format!("Remove access of '{}'", username.trim()),
+        };
+        let state = |role: Option<Role>| role.map(|role| serde_json::json!({ "username": username.trim(), "role": role }));
+        let record = AuditRecord::new(Some(topology_id), AuditEntity::Permission, Some(user_id), action, summary)
+            .states(state(previous).as_ref(), state(role).as_ref());
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        // Open editors of a user who lost access stop receiving changes
+        if role.is_none() {
+            publish_live(topology_id, LiveEvent::AccessRevoked { user_id }).await;
+        }
+
+        get_topology_permissions(topology_id).await
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+// ============================================================================
+// AUDIT LOG
+// ============================================================================
+//
+// Every mutating server function appends an entry per entity it changes to audit_log
+// (bulk operations like imports and revision restores append one summary entry): who,
+// when, which topology and entity, and the entity's JSON state before and after (only
+// the changed fields for updates). The table is append-only (triggers reject
+// UPDATE and DELETE) and, unlike undo_history, never trimmed. Bookkeeping calls
+// (`set_last_topology_id`, `begin_change_set`, `end_change_set`) aren't audited.
+
+/// One change to append to the audit log
+#[cfg(feature = "ssr")]
+struct AuditRecord {
+    topology_id: Option<i64>,
+    entity_type: AuditEntity,
+    entity_id: Option<i64>,
+    action: AuditAction,
+    summary: String,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+}
+
+#[cfg(feature = "ssr")]
+impl AuditRecord {
+    fn new(
+        topology_id: Option<i64>,
+        entity_type: AuditEntity,
+        entity_id: Option<i64>,
+        action: AuditAction,
+        summary: impl Into<String>,
+    ) -> Self {
+        Self {
+            topology_id,
+            entity_type,
+            entity_id,
+            action,
+            summary: summary.into(),
+            before: None,
+            after: None,
+        }
+    }
+
+    /// Attach the entity's state before and after the change
+    fn states<T: Serialize>(self, before: Option<&T>, after: Option<&T>) -> Self {
+        Self {
+            before: before.and_then(|state| serde_json::to_value(state).ok()),
+            after: after.and_then(|state| serde_json::to_value(state).ok()),
+            ..self
+        }
+    }
+
+    fn node(action: AuditAction, before: Option<&Node>, after: Option<&Node>) -> Self {
+        let node = after.or(before).expect("node state");
+        let summary = format!("{} node '{}'", action.label(), node.name);
+        Self::new(Some(node.topology_id), AuditEntity::Node, Some(node.id), action, summary).states(before, after)
+    }
+
+    fn connection(action: AuditAction, before: Option<&Connection>, after: Option<&Connection>) -> Self {
+        let connection = after.or(before).expect("connection state");
+        let summary = format!("{} connection {}", action.label(), connection.id);
+        Self::new(
+            Some(connection.topology_id),
+            AuditEntity::Connection,
+            Some(connection.id),
+            action,
+            summary,
+        )
+        .states(before, after)
+    }
+
+    fn interface(
+        topology_id: i64,
+        action: AuditAction,
+        before: Option<&Interface>,
+        after: Option<&Interface>,
+    ) -> Self {
+        let interface = after.or(before).expect("interface state");
+        let summary = format!("{} interface '{}' on node {}", action.label(), interface.name, interface.node_id);
+        Self::new(Some(topology_id), AuditEntity::Interface, Some(interface.id), action, summary).states(before, after)
+    }
+
+    fn subnet(action: AuditAction, before: Option<&Subnet>, after: Option<&Subnet>) -> Self {
+        let subnet = after.or(before).expect("subnet state");
+        let summary = format!("{} subnet {}", action.label(), subnet.cidr);
+        Self::new(Some(subnet.topology_id), AuditEntity::Subnet, Some(subnet.id), action, summary).states(before, after)
+    }
+
+    fn group(action: AuditAction, before: Option<&NodeGroup>, after: Option<&NodeGroup>) -> Self {
+        let group = after.or(before).expect("group state");
+        let summary = format!("{} group '{}'", action.label(), group.name);
+        Self::new(Some(group.topology_id), AuditEntity::Group, Some(group.id), action, summary).states(before, after)
+    }
+
+    /// Revisions are recorded by name only; their snapshots are kept with them
+    fn revision(action: AuditAction, revision: &TopologyRevision) -> Self {
+        let summary = format!("{} revision '{}'", action.label(), revision.name);
+        Self::new(Some(revision.topology_id), AuditEntity::Revision, Some(revision.id), action, summary)
+    }
+
+    fn topology(action: AuditAction, before: Option<&Topology>, after: Option<&Topology>) -> Self {
+        let topology = after.or(before).expect("topology state");
+        let summary = format!("{} topology '{}'", action.label(), topology.name);
+        Self::new(Some(topology.id), AuditEntity::Topology, Some(topology.id), action, summary).states(before, after)
+    }
+}
+
+/// Append an entry to the audit log (updates that changed nothing are skipped)
+#[cfg(feature = "ssr")]
+async fn record_audit<'e>(
+    executor: impl sqlx::Executor<'e, Database = Db>,
+    user: &User,
+    record: AuditRecord,
+) -> Result<(), AppError> {
+    let Some((before, after)) = audit_diff(record.before, record.after) else {
+        return Ok(());
+    };
+    sqlx::query(
+        "INSERT INTO audit_log (user_id, actor, topology_id, entity_type, entity_id, action, summary, before_state, after_state)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+    )
+    .bind(user.id)
+    .bind(&user.username)
+    .bind(record.topology_id)
+    .bind(record.entity_type)
+    .bind(record.entity_id)
+    .bind(record.action)
+    .bind(record.summary)
+    .bind(before.map(|state| state.to_string()))
+    .bind(after.map(|state| state.to_string()))
+    .execute(executor)
+    .await
+    .map_err(|e| db_error("Failed to write audit log", e))?;
+    Ok(())
+}
+
+/// Record an undo or redo of a change set, by its label
+#[cfg(feature = "ssr")]
+async fn record_change_set_audit(
+    conn: &mut DbConnection,
+    user: &User,
+    topology_id: i64,
+    change_set_id: i64,
+    action: AuditAction,
+) -> Result<(), AppError> {
+    let label: String = sqlx::query_scalar("SELECT label FROM undo_change_sets WHERE id = $1")
+        .bind(change_set_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+    let summary = format!("{} '{}'", action.label(), label);
+    let record = AuditRecord::new(Some(topology_id), AuditEntity::Topology, Some(topology_id), action, summary);
+    record_audit(conn, user, record).await
+}
+
+/// Audit log entries a user may see, newest first (at most `limit` of them)
+///
+/// That's everything on topologies they have a role on, plus their own changes, which
+/// covers account changes and topologies they have since deleted.
+#[cfg(feature = "ssr")]
+async fn fetch_audit_log(
+    pool: &DbPool,
+    user: &User,
+    filter: &AuditFilter,
+    limit: Option<i64>,
+) -> Result<Vec<AuditLogEntry>, AppError> {
+    let actor = filter
+        .actor
+        .as_deref()
+        .map(str::trim)
+        .filter(|actor| !actor.is_empty());
+    // SQLite refuses `LIMIT NULL`, so an unlimited query has no LIMIT clause at all
+    let sql = format!(
+        "SELECT id, created_at, user_id, actor, topology_id, entity_type, entity_id, action, summary, before_state, after_state
+         FROM audit_log
+         WHERE (user_id = $1 OR topology_id IN (SELECT topology_id FROM topology_permissions WHERE user_id = $1))
+         AND ($2 IS NULL OR topology_id = $2)
+         AND ($3 IS NULL OR LOWER(actor) = LOWER($3))
+         AND ($4 IS NULL OR entity_type = $4)
+         AND ($5 IS NULL OR action = $5)
+         AND ($6 IS NULL OR created_at >= $6)
+         AND ($7 IS NULL OR created_at < $7)
+         ORDER BY id DESC{}",
+        if limit.is_some() { " LIMIT $8" } else { "" }
+    );
+    let mut query = sqlx::query_as::<_, AuditLogEntry>(&sql)
+        .bind(user.id)
+        .bind(filter.topology_id)
+        .bind(actor)
+        .bind(filter.entity_type)
+        .bind(filter.action)
+        .bind(filter.since)
+        .bind(filter.until);
+    if let Some(limit) = limit {
+        query = query.bind(limit);
+    }
+    query
+        .fetch_all(pool)
+        .await
+        .map_err(|e| db_error("Database error", e))
+}
+
+/// Search the audit log (newest first, at most `MAX_AUDIT_ENTRIES`)
+#[server(GetAuditLog, "/api", input = leptos::server_fn::codec::Json)]
+pub async fn get_audit_log(filter: AuditFilter) -> Result<Vec<AuditLogEntry>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_user(&pool).await?;
+        fetch_audit_log(&pool, &user, &filter, Some(MAX_AUDIT_ENTRIES)).await
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Every audit log entry matching a filter, rendered as CSV or pretty-printed JSON
+#[server(ExportAuditLog, "/api", input = leptos::server_fn::codec::Json)]
+pub async fn export_audit_log(filter: AuditFilter, format: AuditExportFormat) -> Result<String, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_user(&pool).await?;
+        let entries = fetch_audit_log(&pool, &user, &filter, None).await?;
+        match format {
+            AuditExportFormat::Csv => Ok(audit_log_to_csv(&entries)),
+            AuditExportFormat::Json => serde_json::to_string_pretty(&entries)
+                .map_err(|e| AppError::internal(format!("JSON serialization error: {}", e))),
+        }
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+// ============================================================================
+// LIVE COLLABORATION
+// ============================================================================
+//
+// Browsers viewing a topology hold a WebSocket to `/api/v1/topologies/{id}/live`
+// (see `crate::server::live`). Server functions publish an event there once their change
+// is committed: node and connection edits carry the new state, everything else that
+// changes what the editor shows is a plain `TopologyChanged`. The editor calls the
+// publishing functions through `LiveClient`, which names the tab's socket so the change
+// isn't echoed back to the tab that made it.
+
+/// Request header naming the live socket (viewer id) of the tab making a change
+pub const LIVE_VIEWER_HEADER: &str = "x-ntb-viewer";
+
+#[cfg(feature = "hydrate")]
+thread_local! {
+    static LIVE_VIEWER: std::cell::Cell<Option<u64>> = const { std::cell::Cell::new(None) };
+}
+
+/// Remember the viewer id of this tab's live socket (None while it has none)
+#[cfg(feature = "hydrate")]
+pub fn set_live_viewer(viewer_id: Option<u64>) {
+    LIVE_VIEWER.with(|viewer| viewer.set(viewer_id));
+}
+
+/// Browser client of the server functions that publish live events
+///
+/// Sends the tab's viewer id along (see `LIVE_VIEWER_HEADER`), so its own socket skips
+/// the event while the user's other tabs still get it.
+pub struct LiveClient;
+
+impl<E, InputStreamError, OutputStreamError> Client<E, InputStreamError, OutputStreamError> for LiveClient
+where
+    E: FromServerFnError,
+    InputStreamError: FromServerFnError,
+    OutputStreamError: FromServerFnError,
+{
+    type Request = leptos::server_fn::request::browser::BrowserRequest;
+    type Response = leptos::server_fn::response::browser::BrowserResponse;
+
+    fn send(req: Self::Request) -> impl std::future::Future<Output = Result<Self::Response, E>> + Send {
+        #[cfg(feature = "hydrate")]
+        if let Some(viewer_id) = LIVE_VIEWER.with(|viewer| viewer.get()) {
+            req.headers().set(LIVE_VIEWER_HEADER, &viewer_id.to_string());
+        }
+        <BrowserClient as Client<E, InputStreamError, OutputStreamError>>::send(req)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn open_websocket(
+        path: &str,
+    ) -> impl std::future::Future<
+        Output = Result<
+            (
+                impl futures::Stream<Item = Result<Bytes, Bytes>> + Send + 'static,
+                impl futures::Sink<Bytes> + Send + 'static,
+            ),
+            E,
+        >,
+    > + Send {
+        <BrowserClient as Client<E, InputStreamError, OutputStreamError>>::open_websocket(path)
+    }
+
+    fn spawn(future: impl std::future::Future<Output = ()> + Send + 'static) {
+        <BrowserClient as Client<E, InputStreamError, OutputStreamError>>::spawn(future)
+    }
+}
+
+/// The viewer id a request names in `LIVE_VIEWER_HEADER`
+#[cfg(feature = "ssr")]
+async fn request_viewer_id() -> Option<u64> {
+    use axum::http::HeaderMap;
+    use leptos_axum::extract;
+
+    let headers: HeaderMap = extract().await.ok()?;
+    headers.get(LIVE_VIEWER_HEADER)?.to_str().ok()?.parse().ok()
+}
+
+/// Push an event to everyone viewing a topology, except the tab that caused it
+#[cfg(feature = "ssr")]
+async fn publish_live(topology_id: i64, event: LiveEvent) {
+    use crate::server::live::{LiveHub, LiveOrigin};
+    use axum::Extension;
+    use leptos_axum::extract;
+
+    if let Ok(Extension(hub)) = extract::<Extension<LiveHub>>().await {
+        let origin = match (request_origin().await, request_viewer_id().await) {
+            (Some(session), Some(viewer_id)) => Some(LiveOrigin { session, viewer_id }),
+            _ => None,
+        };
+        hub.publish(topology_id, origin, event);
+    }
+}
+
+/// Check who is opening a live socket to a topology (viewers and up may)
+///
+/// Returns the user and the key of their session (see `request_origin`). Not a server
+/// function: the socket handler runs it with the upgrade request's parts in context.
+#[cfg(feature = "ssr")]
+pub async fn join_live_topology(topology_id: i64) -> Result<(User, Option<String>), AppError> {
+    use axum::Extension;
+    use leptos_axum::extract;
+
+    let Extension(pool) = extract::<Extension<DbPool>>()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+    let user = require_role(&pool, topology_id, Role::Viewer).await?;
+    Ok((user, request_origin().await))
+}
+
+// ============================================================================
+// UI Settings Functions
+// ============================================================================
+
+/// Get UI settings (single row, id=1)
+#[server(GetUISettings, "/api")]
+pub async fn get_ui_settings() -> Result<UISettings, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_user(&pool).await?;
+
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        fetch_ui_settings(&mut conn)
+            .await
+            .map_err(|e| db_error("Database error", e))
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// The settings row
+#[cfg(feature = "ssr")]
+async fn fetch_ui_settings(conn: &mut DbConnection) -> Result<UISettings, sqlx::Error> {
+    sqlx::query_as::<_, UISettings>(
+        "SELECT id, show_grid, show_x_axis, show_y_axis, show_z_axis,
+                ambient_intensity, key_light_intensity, fill_light_intensity, rim_light_intensity,
+                use_environment_lighting, environment_map, undo_depth,
+                created_at, updated_at
+         FROM ui_settings WHERE id = 1",
+    )
+    .fetch_one(conn)
+    .await
+}
+
+/// Update UI settings (only updates provided fields)
+///
+/// The row is shared by every account, so only administrators may change it.
+#[server(UpdateUISettingsFn, "/api")]
+pub async fn update_ui_settings(data: UpdateUISettings) -> Result<UISettings, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_admin(&pool).await?;
+
+        if let Some(undo_depth) = data.undo_depth {
+            if undo_depth < 0 {
+                return Err(AppError::invalid("undo_depth must be 0 (unlimited) or positive"));
+            }
+        }
+
+        if data.show_grid.is_none()
+            && data.show_x_axis.is_none()
+            && data.show_y_axis.is_none()
+            && data.show_z_axis.is_none()
+            && data.ambient_intensity.is_none()
+            && data.key_light_intensity.is_none()
+            && data.fill_light_intensity.is_none()
+            && data.rim_light_intensity.is_none()
+            && data.use_environment_lighting.is_none()
+            && data.environment_map.is_none()
+            && data.undo_depth.is_none()
+        {
+            return Err(AppError::invalid("No fields to update"));
+        }
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        let previous = fetch_ui_settings(&mut tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        // Build dynamic UPDATE query
+        let mut query = sqlx::QueryBuilder::<Db>::new("UPDATE ui_settings SET ");
+        let mut updates = query.separated(", ");
+
+        if let Some(show_grid) = data.show_grid {
+            updates.push("show_grid = ").push_bind_unseparated(show_grid);
+        }
+
+        if let Some(show_x_axis) = data.show_x_axis {
+            updates.push("show_x_axis = ").push_bind_unseparated(show_x_axis);
+        }
+
+        if let Some(show_y_axis) = data.show_y_axis {
+            updates.push("show_y_axis = ").push_bind_unseparated(show_y_axis);
+        }
+
+        if let Some(show_z_axis) = data.show_z_axis {
+            updates.push("show_z_axis = ").push_bind_unseparated(show_z_axis);
+        }
+
+        if let Some(ambient_intensity) = data.ambient_intensity {
+            updates.push("ambient_intensity = ").push_bind_unseparated(ambient_intensity);
+        }
+
+        if let Some(key_light_intensity) = data.key_light_intensity {
+            updates.push("key_light_intensity = ").push_bind_unseparated(key_light_intensity);
+        }
+
+        if let Some(fill_light_intensity) = data.fill_light_intensity {
+            updates.push("fill_light_intensity = ").push_bind_unseparated(fill_light_intensity);
+        }
+
+        if let Some(rim_light_intensity) = data.rim_light_intensity {
+            updates.push("rim_light_intensity = ").push_bind_unseparated(rim_light_intensity);
+        }
+
+        if let Some(use_environment_lighting) = data.use_environment_lighting {
+            updates.push("use_environment_lighting = ").push_bind_unseparated(use_environment_lighting);
+        }
+
+        if let Some(environment_map) = data.environment_map {
+            updates.push("environment_map = ").push_bind_unseparated(environment_map);
+        }
+
+        if let Some(undo_depth) = data.undo_depth {
+            updates.push("undo_depth = ").push_bind_unseparated(undo_depth);
+        }
+
+        query.push(" WHERE id = 1");
+
+        query
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        // Fetch and return updated settings
+        let settings = fetch_ui_settings(&mut tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        let record = AuditRecord::new(None, AuditEntity::Settings, None, AuditAction::Update, "Update UI settings")
+            .states(Some(&previous), Some(&settings));
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        Ok(settings)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Get available vendors and models for a specific node type
+/// Served from the model registry (see `crate::server::model_registry`), which scans
+/// {root}/{node_type}/{vendor}/ of every model root and reads the vendor manifests
+#[server(GetVendorsForType, "/api")]
+pub async fn get_vendors_for_type(node_type: NodeType) -> Result<VendorListResponse, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_user(&pool).await?;
+
+        let registry = model_registry().await?;
+        let vendors = registry.vendors(node_type, &model_scan().await);
+        Ok(VendorListResponse { node_type, vendors })
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Check every model file: glTF structure, material colors and size
+///
+/// Results come from the server's startup scan, re-checking files changed since.
+#[server(ValidateModels, "/api")]
+pub async fn validate_models() -> Result<Vec<ModelValidation>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use crate::server::model_scan::find_models;
+        use axum::Extension;
+        use leptos::prelude::get_configuration;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_user(&pool).await?;
+
+        let conf = get_configuration(None).map_err(|e| {
+            AppError::internal(format!("Failed to get Leptos configuration: {}", e))
+        })?;
+        let scan = model_scan().await;
+
+        let mut results = Vec::new();
+        for root in server_config().await.model_roots(&conf.leptos_options.site_root) {
+            for file in find_models(&root) {
+                results.push(scan.check(&root, &file));
+            }
+        }
+        Ok(results)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Store an uploaded model, and optionally its vendor's icon, in `models.upload_dir`
+///
+/// The model must pass the same checks as `ValidateModels` without errors and have a
+/// bounding box; its warnings are returned in the report. Names of built-in models and
+/// of those in the search paths can't be reused, and an earlier upload is only
+/// overwritten with `replace`, by its uploader or an administrator, while no node uses
+/// it. Any signed-in user may upload a new model. Not a server function:
+/// the multipart handler in `crate::server::model_api` runs it with the request's parts
+/// in context.
+#[cfg(feature = "ssr")]
+pub async fn upload_model(upload: ModelUpload) -> Result<ModelUploadReport, AppError> {
+    use axum::Extension;
+    use leptos_axum::extract;
+
+    let Extension(pool) = extract::<Extension<DbPool>>()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+    let user = require_user(&pool).await?;
+    let config = server_config().await;
+
+    check_model_slug("Vendor", &upload.vendor).map_err(AppError::invalid)?;
+    check_model_slug("Model name", &upload.name).map_err(AppError::invalid)?;
+    if upload.model.len() as u64 > config.models.max_upload_bytes {
+        return Err(AppError::invalid(format!(
+            "Model is larger than the upload limit of {} bytes",
+            config.models.max_upload_bytes
+        )));
+    }
+    let path = format!("{}/{}/{}.glb", upload.node_type.as_str(), upload.vendor, upload.name);
+    let validation = validate_glb(&path, &upload.model);
+    if let Some(issue) = validation.first_error() {
+        return Err(AppError::invalid(match &issue.fix {
+            Some(fix) => format!("Model rejected: {} ({})", issue.message, fix),
+            None => format!("Model rejected: {}", issue.message),
+        }));
+    }
+    if validation.max_dimension <= 0.0 {
+        return Err(AppError::invalid(
+            "Model rejected: it has no geometry with a bounding box (position accessors need min and max)",
+        ));
+    }
+    if let Some(icon) = &upload.icon {
+        check_vendor_icon(icon).map_err(AppError::invalid)?;
+    }
+
+    let registry = model_registry().await?;
+    let target = registry.upload_path(upload.node_type, &upload.vendor, &upload.name);
+    let replaced = match registry.model_file(upload.node_type, &upload.vendor, &upload.name) {
+        Some((_, file)) if file != target => {
+            return Err(AppError::conflict(format!(
+                "{} is one of the server's own models; upload under another name",
+                path
+            )))
+        }
+        Some(_) if !upload.replace => {
+            return Err(AppError::conflict(format!(
+                "{} was already uploaded; upload it with replace to overwrite it",
+                path
+            )))
+        }
+        Some(_) => true,
+        None => false,
+    };
+    let uploader = model_uploader(&pool, upload.node_type, &upload.vendor, &upload.name).await?;
+    if replaced {
+        require_model_owner(&user, uploader, "replace", &path)?;
+        let nodes_using = count_nodes_using_model(&pool, upload.node_type, &upload.vendor, &upload.name).await?;
+        if nodes_using > 0 {
+            return Err(AppError::conflict(format!(
+                "{} is used by {} node(s); upload it under another name or give them another model first",
+                path, nodes_using
+            )));
+        }
+    }
+    let icon_path = config.uploaded_icon_dir().join(format!("{}.svg", upload.vendor));
+    if upload.icon.is_some() && !user.is_admin {
+        // The icon is shared by the vendor's models, so it's only theirs to change if they all are
+        let others: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM uploaded_models WHERE vendor = $1 AND (user_id IS NULL OR user_id <> $2)",
+        )
+        .bind(&upload.vendor)
+        .bind(user.id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+        let exists = tokio::fs::try_exists(&icon_path).await.unwrap_or(false);
+        if exists && others > 0 {
+            return Err(AppError::forbidden(format!(
+                "Permission denied: only administrators may replace the icon of {}, which others uploaded models for",
+                upload.vendor
+            )));
+        }
+    }
+
+    // The audit entry and the uploader are only committed once the files are in place
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| db_error("Failed to start transaction", e))?;
+    let action = if replaced { AuditAction::Update } else { AuditAction::Create };
+    let summary = format!("{} model {}", action.label(), path);
+    record_audit(&mut *tx, &user, AuditRecord::new(None, AuditEntity::Model, None, action, summary)).await?;
+    if !replaced || uploader.is_none() {
+        sqlx::query(
+            "INSERT INTO uploaded_models (node_type, vendor, name, user_id) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (node_type, vendor, name) DO UPDATE SET user_id = excluded.user_id",
+        )
+        .bind(upload.node_type)
+        .bind(&upload.vendor)
+        .bind(&upload.name)
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+    }
+
+    store_upload(&target, &upload.model)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to store model {}: {}", path, e)))?;
+    if let Some(icon) = &upload.icon {
+        store_upload(&icon_path, icon)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to store icon of {}: {}", upload.vendor, e)))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| db_error("Failed to commit transaction", e))?;
+    reload_models(&registry).await?;
+
+    let model = registry
+        .model(upload.node_type, &upload.vendor, &upload.name)
+        .ok_or_else(|| AppError::internal(format!("Failed to register model {}", path)))?;
+    Ok(ModelUploadReport {
+        node_type: upload.node_type,
+        vendor: upload.vendor,
+        model,
+        issues: validation.issues,
+        replaced,
+    })
+}
+
+/// Write an uploaded file next to its target and rename it, so it's never served half-written
+#[cfg(feature = "ssr")]
+async fn store_upload(target: &std::path::Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(dir) = target.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    let partial = target.with_extension("partial");
+    tokio::fs::write(&partial, bytes).await?;
+    tokio::fs::rename(&partial, target).await
+}
+
+/// Delete an uploaded model
+///
+/// Refused while any node (in any topology) still uses it. Only uploaded models can be
+/// deleted; the vendor's icon is kept.
+#[server(DeleteUploadedModel, "/api")]
+pub async fn delete_uploaded_model(
+    node_type: NodeType,
+    vendor: String,
+    model_name: String,
+) -> Result<(), AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+        let user = require_user(&pool).await?;
+
+        check_model_slug("Vendor", &vendor).map_err(AppError::invalid)?;
+        check_model_slug("Model name", &model_name).map_err(AppError::invalid)?;
+        let path = format!("{}/{}/{}.glb", node_type.as_str(), vendor, model_name);
+
+        let registry = model_registry().await?;
+        let target = registry.upload_path(node_type, &vendor, &model_name);
+        match registry.model_file(node_type, &vendor, &model_name) {
+            None => return Err(AppError::not_found(format!("Model not found: {}", path))),
+            Some((_, file)) if file != target => {
+                return Err(AppError::conflict(format!(
+                    "{} is one of the server's own models; only uploaded models can be deleted",
+                    path
+                )))
+            }
+            Some(_) => {}
+        }
+        let uploader = model_uploader(&pool, node_type, &vendor, &model_name).await?;
+        require_model_owner(&user, uploader, "delete", &path)?;
+        let nodes_using = count_nodes_using_model(&pool, node_type, &vendor, &model_name).await?;
+        if nodes_using > 0 {
+            return Err(AppError::conflict(format!(
+                "{} is used by {} node(s); give them another model before deleting it",
+                path, nodes_using
+            )));
+        }
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+        sqlx::query("DELETE FROM uploaded_models WHERE node_type = $1 AND vendor = $2 AND name = $3")
+            .bind(node_type)
+            .bind(&vendor)
+            .bind(&model_name)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+        let summary = format!("Delete model {}", path);
+        record_audit(&mut *tx, &user, AuditRecord::new(None, AuditEntity::Model, None, AuditAction::Delete, summary))
+            .await?;
+
+        tokio::fs::remove_file(&target)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to delete model {}: {}", path, e)))?;
+        // Drop the vendor folder with its last upload (fails harmlessly if not empty)
+        if let Some(dir) = target.parent() {
+            let _ = tokio::fs::remove_dir(dir).await;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+        reload_models(&registry).await?;
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Nodes in any topology using a model
+#[cfg(feature = "ssr")]
+async fn count_nodes_using_model(
+    pool: &DbPool,
+    node_type: NodeType,
+    vendor: &str,
+    model_name: &str,
+) -> Result<i64, AppError> {
+    sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM nodes WHERE node_type = $1 AND vendor = $2 AND model_name = $3")
+        .bind(node_type)
+        .bind(vendor)
+        .bind(model_name)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| db_error("Database error", e))
+}
+
+/// Who uploaded a model (None for uploads whose account is gone or that predate owners)
+#[cfg(feature = "ssr")]
+async fn model_uploader(
+    pool: &DbPool,
+    node_type: NodeType,
+    vendor: &str,
+    model_name: &str,
+) -> Result<Option<i64>, AppError> {
+    sqlx::query_scalar::<_, Option<i64>>(
+        "SELECT user_id FROM uploaded_models WHERE node_type = $1 AND vendor = $2 AND name = $3",
+    )
+    .bind(node_type)
+    .bind(vendor)
+    .bind(model_name)
+    .fetch_optional(pool)
+    .await
+    .map(Option::flatten)
+    .map_err(|e| db_error("Database error", e))
+}
+
+/// Only administrators and a model's uploader may replace or delete it
+#[cfg(feature = "ssr")]
+fn require_model_owner(user: &User, uploader: Option<i64>, what: &str, path: &str) -> Result<(), AppError> {
+    if user.is_admin || uploader == Some(user.id) {
+        Ok(())
+    } else {
+        Err(AppError::forbidden(format!(
+            "Permission denied: only administrators and its uploader may {} {}",
+            what, path
+        )))
+    }
+}
+
+/// Scan the model roots again without blocking the runtime
+#[cfg(feature = "ssr")]
+async fn reload_models(registry: &crate::server::model_registry::ModelRegistry) -> Result<(), AppError> {
+    let registry = registry.clone();
+    tokio::task::spawn_blocking(move || registry.reload())
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to reload models: {}", e)))
+}
+
+/// The server's model registry (scanned on the spot outside the server)
+#[cfg(feature = "ssr")]
+async fn model_registry() -> Result<crate::server::model_registry::ModelRegistry, AppError> {
+    use crate::server::model_registry::ModelRegistry;
+    use axum::Extension;
+    use leptos::prelude::get_configuration;
+    use leptos_axum::extract;
+
+    if let Ok(Extension(registry)) = extract::<Extension<ModelRegistry>>().await {
+        return Ok(registry);
+    }
+    let conf = get_configuration(None).map_err(|e| {
+        AppError::internal(format!("Failed to get Leptos configuration: {}", e))
+    })?;
+    let config = server_config().await;
+    Ok(ModelRegistry::load(&config, &conf.leptos_options.site_root))
+}
+
+/// The server's model validation cache (an empty one outside the server)
+#[cfg(feature = "ssr")]
+async fn model_scan() -> crate::server::model_scan::ModelScan {
+    use axum::Extension;
+    use leptos_axum::extract;
+
+    extract::<Extension<crate::server::model_scan::ModelScan>>()
+        .await
+        .map(|Extension(scan)| scan)
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Traffic Monitoring (Phase 6)
+// ============================================================================
+
+/// Generate mock traffic data for a specific topology
+/// This simulates realistic network traffic patterns for demonstration purposes
+#[server(GenerateMockTraffic, "/api")]
+pub async fn generate_mock_traffic(
+    topology_id: i64,
+    traffic_level: String,
+) -> Result<usize, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_role(&pool, topology_id, Role::Editor).await?;
+
+        let config = server_config().await;
+        let mock = &config.mock_traffic;
+        if !mock.enabled {
+            return Err(AppError::forbidden("Mock traffic is disabled on this server"));
+        }
+        let traffic_level = match traffic_level.trim() {
+            "" => mock.default_level.clone(),
+            level => level.to_string(),
+        };
+
+        // Get all connections for this topology
+        let connections = sqlx::query_as::<_, Connection>(
+            "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata, created_at, updated_at, version
+             FROM connections WHERE topology_id = $1"
+        )
+        .bind(topology_id)
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| db_error("Failed to fetch connections", e))?;
+
+        if connections.is_empty() {
+            return Ok(0);
+        }
+
+        // Traffic level acts as a BASELINE activity multiplier
+        // Individual link properties (bandwidth, latency, type) determine actual utilization
+        // This allows per-link automatic traffic level calculation based on throughput
+        let traffic_multiplier = match traffic_level.as_str() {
+            "low" => 0.3,    // 30% baseline activity
+            "medium" => 0.6, // 60% baseline activity
+            "high" => 0.9,   // 90% baseline activity
+            _ => 0.6,        // default to medium
+        };
+
+        use rand::Rng;
+        use rand::SeedableRng;
+        // Use StdRng which is Send-safe for async contexts
+        let mut rng = rand::rngs::StdRng::from_entropy();
+        let current_timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        // The metrics and their audit entry are saved together
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        let mut metrics_created = 0;
+
+        for connection in connections {
+            // Skip inactive, degraded or error connections
+            if connection.status != ConnectionStatus::Active {
+                continue;
+            }
+
+            // Get link properties with realistic defaults
+            let bandwidth_capacity = connection.bandwidth_mbps.unwrap_or(mock.default_bandwidth_mbps) as f64;
+            let base_latency = connection.latency_ms.unwrap_or(mock.default_latency_ms);
+            let baseline_packet_loss = connection.baseline_packet_loss_pct.unwrap_or(0.0); // Default: 0% (no packet loss)
+
+            // Connection type affects throughput efficiency
+            let type_efficiency = match connection.connection_type {
+                ConnectionType::Fiber => 0.95,    // Fiber is most efficient
+                ConnectionType::Ethernet => 0.85, // Ethernet slightly less
+                ConnectionType::Wireless => 0.70, // Wireless has more overhead
+                ConnectionType::Vpn => 0.75,      // VPN has encryption overhead
+                ConnectionType::Wan => 0.80,      // WAN links share upstream capacity
+            };
+
+            // Calculate realistic throughput using Mathis formula
+            // Throughput = Bandwidth × TrafficLevel × TypeEfficiency × RTT_factor × PacketLoss_factor
+
+            // Calculate base throughput (for display purposes - what user sees in tooltip)
+            // This SHOULD be reduced by latency/packet loss to show realistic throughput
+            let rtt_factor = 1.0 / (1.0 + base_latency / 50.0); // Higher latency = lower throughput
+            let packet_loss_factor = if baseline_packet_loss > 0.0 {
+                1.0 / (1.0 + baseline_packet_loss / 10.0) // Higher packet loss = lower throughput
+            } else {
+                1.0
+            };
+
+            let base_throughput = bandwidth_capacity
+                * traffic_multiplier
+                * type_efficiency
+                * rtt_factor
+                * packet_loss_factor;
+            let throughput_variance = rng.gen_range(0.8..1.2); // ±20% variance for realism
+            let throughput_mbps = (base_throughput * throughput_variance).max(0.1);
+
+            // Calculate utilization for COLOR/HEALTH (NOT just throughput/bandwidth)
+            // Key insight: degraded links (high latency, packet loss) should show ORANGE/RED even with low throughput
+            // Because the link is UNHEALTHY, not just underutilized
+
+            // Start with base traffic level utilization (ignoring degradation)
+            let ideal_throughput = bandwidth_capacity * traffic_multiplier * type_efficiency;
+            let base_utilization = (ideal_throughput / bandwidth_capacity * 100.0).min(100.0);
+
+            // Degradation factors INCREASE apparent utilization (makes link appear more stressed)
+            let latency_penalty = if base_latency > 50.0 {
+                // High latency increases apparent utilization
+                (base_latency - 50.0) / 2.0 // Each 2ms over 50ms adds 1% utilization
+            } else {
+                0.0
+            };
+
+            let packet_loss_penalty = baseline_packet_loss * 10.0; // Each 1% packet loss adds 10% utilization
+
+            // Final utilization = base + degradation penalties
+            let utilization_pct =
+                (base_utilization + latency_penalty + packet_loss_penalty).min(100.0);
+
+            // Packets per second (roughly 1000 packets per Mbps for standard Ethernet)
+            let packets_per_sec = (throughput_mbps * 1000.0) as i64;
+
+            // Latency: base latency + congestion penalty (higher utilization = higher latency)
+            let base_latency = connection.latency_ms.unwrap_or(mock.default_latency_ms);
+            let congestion_penalty = if utilization_pct > 70.0 {
+                // Heavy congestion: significant latency increase
+                (utilization_pct - 70.0) * rng.gen_range(0.5..1.5)
+            } else if utilization_pct > 40.0 {
+                // Moderate congestion: slight latency increase
+                (utilization_pct - 40.0) * rng.gen_range(0.1..0.3)
+            } else {
+                0.0
+            };
+            let jitter = rng.gen_range(-2.0..3.0); // Natural network jitter
+            let latency_ms = (base_latency + congestion_penalty + jitter).max(0.1);
+
+            // Packet loss: baseline + congestion-based
+            // Baseline packet loss is the inherent loss rate of the link (user-configured)
+            // Congestion packet loss increases exponentially with utilization (simulated)
+            let congestion_packet_loss: f64 = if utilization_pct > 90.0 {
+                // Critical: severe packet loss
+                rng.gen_range(2.0..5.0)
+            } else if utilization_pct > 80.0 {
+                // High: noticeable packet loss
+                rng.gen_range(0.5..2.0)
+            } else if utilization_pct > 60.0 {
+                // Moderate: occasional packet loss
+                rng.gen_range(0.1..0.5)
+            } else {
+                // Low: minimal packet loss
+                rng.gen_range(0.0..0.1)
+            };
+            // Total packet loss = baseline (from link properties) + congestion (from utilization)
+            let packet_loss_pct = (baseline_packet_loss + congestion_packet_loss).min(100.0);
+
+            // Calculate bytes and packets transferred (for 1 second interval)
+            let bytes_transferred = (throughput_mbps * 125000.0) as i64; // Convert Mbps to bytes/sec
+            let packets_transferred = packets_per_sec;
+
+            // Insert metric into database
+            let _result = sqlx::query(
+                "INSERT INTO connection_traffic_metrics
+                 (connection_id, timestamp, throughput_mbps, packets_per_sec, latency_ms,
+                  packet_loss_pct, utilization_pct, bytes_transferred, packets_transferred)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            )
+            .bind(connection.id)
+            .bind(current_timestamp)
+            .bind(throughput_mbps)
+            .bind(packets_per_sec)
+            .bind(latency_ms)
+            .bind(packet_loss_pct)
+            .bind(utilization_pct)
+            .bind(bytes_transferred)
+            .bind(packets_transferred)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Failed to insert traffic metric", e))?;
+
+            metrics_created += 1;
+        }
+
+        let summary = format!("Generate {} mock traffic ({} metrics)", traffic_level, metrics_created);
+        let record = AuditRecord::new(Some(topology_id), AuditEntity::Traffic, None, AuditAction::Create, summary);
+        record_audit(&mut *tx, &user, record).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        Ok(metrics_created)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Get latest traffic metrics for all connections in a topology
+#[server(GetConnectionTrafficMetrics, "/api")]
+pub async fn get_connection_traffic_metrics(
+    topology_id: i64,
+) -> Result<Vec<ConnectionTrafficMetric>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use crate::models::ConnectionTrafficMetric;
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_role(&pool, topology_id, Role::Viewer).await?;
+
+        // Get latest metric for each connection (using subquery for max timestamp per connection)
+        let metrics = sqlx::query_as::<_, ConnectionTrafficMetric>(
+            "SELECT ctm.*
+             FROM connection_traffic_metrics ctm
+             INNER JOIN connections c ON ctm.connection_id = c.id
+             INNER JOIN (
+                 SELECT connection_id, MAX(timestamp) as max_timestamp
+                 FROM connection_traffic_metrics
+                 GROUP BY connection_id
+             ) latest ON ctm.connection_id = latest.connection_id
+                     AND ctm.timestamp = latest.max_timestamp
+             WHERE c.topology_id = $1
+             ORDER BY ctm.utilization_pct DESC",
+        )
+        .bind(topology_id)
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| db_error("Failed to fetch traffic metrics", e))?;
+
+        Ok(metrics)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Get latest traffic metric for each connection (for real-time visualization)
+/// Returns a map of connection_id -> latest metric
+#[server(GetLatestTrafficMetrics, "/api")]
+pub async fn get_latest_traffic_metrics(
+    topology_id: i64,
+) -> Result<std::collections::HashMap<i64, ConnectionTrafficMetric>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+        use std::collections::HashMap;
+
+        let Extension(pool): Extension<DbPool> = extract()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        require_role(&pool, topology_id, Role::Viewer).await?;
+
+        // Get latest metric for each connection in this topology
+        // Uses a subquery to find the max timestamp per connection, then joins to get full row
+        let metrics = sqlx::query_as::<_, ConnectionTrafficMetric>(
+            r#"
+            SELECT ctm.*
+            FROM connection_traffic_metrics ctm
+            INNER JOIN connections c ON c.id = ctm.connection_id
+            INNER JOIN (
+                SELECT connection_id, MAX(timestamp) as max_timestamp
+                FROM connection_traffic_metrics
+                WHERE connection_id IN (
+                    SELECT id FROM connections WHERE topology_id = $1
+                )
+                GROUP BY connection_id
+            ) latest ON latest.connection_id = ctm.connection_id
+                    AND latest.max_timestamp = ctm.timestamp
+            WHERE c.topology_id = $2
+            "#,
+        )
+        .bind(topology_id)
+        .bind(topology_id)
+        .fetch_all(&pool)
+        .await?;
+
+        // Convert to HashMap for easy lookup
+        let mut map = HashMap::new();
+        for metric in metrics {
+            map.insert(metric.connection_id, metric);
+        }
+
+        Ok(map)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Clear all traffic data for a specific topology (restore manual colors)
+#[server(ClearTrafficData, "/api")]
+pub async fn clear_traffic_data(topology_id: i64) -> Result<usize, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool): Extension<DbPool> = extract()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_role(&pool, topology_id, Role::Editor).await?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        // Delete all traffic metrics for connections in this topology
+        let result = sqlx::query(
+            r#"
+            DELETE FROM connection_traffic_metrics
+            WHERE connection_id IN (
+                SELECT id FROM connections WHERE topology_id = $1
+            )
+            "#,
+        )
+        .bind(topology_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| db_error("Failed to clear traffic data", e))?;
+
+        // Clearing an already empty history (a repeated click of Clear Traffic Data) changes
+        // nothing, so only clears that removed metrics are audited
+        let cleared = result.rows_affected() as usize;
+        if cleared > 0 {
+            let summary = format!("Clear traffic data ({} metrics)", cleared);
+            let record = AuditRecord::new(Some(topology_id), AuditEntity::Traffic, None, AuditAction::Delete, summary);
+            record_audit(&mut *tx, &user, record).await?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        Ok(cleared)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Clean up old traffic metrics in the topologies the user may edit
+///
+/// Deletes metrics older than `metrics.retention_secs` (an hour unless configured) and,
+/// with `metrics.downsample_after_secs` set, averages older ones into one metric per
+/// connection and `metrics.downsample_interval_secs`. Returns the number of rows removed.
+#[server(CleanOldTrafficMetrics, "/api")]
+pub async fn clean_old_traffic_metrics() -> Result<usize, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_user(&pool).await?;
+        let config = server_config().await;
+        let now = unix_now();
+        let cutoff = now - config.metrics.retention_secs;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        // Only in topologies the user may edit
+        let result = sqlx::query(
+            "DELETE FROM connection_traffic_metrics
+             WHERE timestamp < $1
+               AND connection_id IN (
+                   SELECT c.id FROM connections c
+                   JOIN topology_permissions p ON p.topology_id = c.topology_id
+                   WHERE p.user_id = $2 AND p.role IN ('owner', 'editor')
+               )",
+        )
+        .bind(cutoff)
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| db_error("Failed to clean old metrics", e))?;
+
+        let cleaned = result.rows_affected() as usize;
+        let merged = match config.metrics.downsample_after_secs {
+            Some(after) => {
+                let interval = config.metrics.downsample_interval_secs;
+                downsample_traffic_metrics(&mut tx, user.id, cutoff, now - after, interval).await?
+            }
+            None => 0,
+        };
+
+        if cleaned + merged > 0 {
+            let mut summary = format!(
+                "Clean up traffic metrics older than {} ({} metrics)",
+                describe_duration(config.metrics.retention_secs),
+                cleaned
+            );
+            if merged > 0 {
+                let interval = config.metrics.downsample_interval_secs;
+                summary.push_str(&format!(", merge {} into {}-second averages", merged, interval));
+            }
+            let record = AuditRecord::new(None, AuditEntity::Traffic, None, AuditAction::Delete, summary);
+            record_audit(&mut *tx, &user, record).await?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        Ok(cleaned + merged)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Average each connection's metrics recorded between `from` and `until` into one
+/// metric per `interval` seconds; returns how many rows that removed
+#[cfg(feature = "ssr")]
+async fn downsample_traffic_metrics(
+    conn: &mut DbConnection,
+    user_id: i64,
+    from: i64,
+    until: i64,
+    interval: i64,
+) -> Result<usize, AppError> {
+    type Bucket = (i64, i64, i64, f64, f64, f64, f64, f64, f64, f64);
+
+    // Buckets that still hold more than one metric
+    let buckets = sqlx::query_as::<_, Bucket>(
+        "SELECT connection_id, bucket, COUNT(*),
+                CAST(COALESCE(AVG(throughput_mbps), 0) AS DOUBLE PRECISION),
+                CAST(COALESCE(AVG(packets_per_sec), 0) AS DOUBLE PRECISION),
+                CAST(COALESCE(AVG(latency_ms), 0) AS DOUBLE PRECISION),
+                CAST(COALESCE(AVG(packet_loss_pct), 0) AS DOUBLE PRECISION),
+                CAST(COALESCE(AVG(utilization_pct), 0) AS DOUBLE PRECISION),
+                CAST(COALESCE(AVG(bytes_transferred), 0) AS DOUBLE PRECISION),
+                CAST(COALESCE(AVG(packets_transferred), 0) AS DOUBLE PRECISION)
+         FROM (
+             SELECT m.*, (m.timestamp / $3) * $3 AS bucket
+             FROM connection_traffic_metrics m
+             WHERE m.timestamp >= $1 AND m.timestamp < $2
+               AND m.connection_id IN (
+                   SELECT c.id FROM connections c
+                   JOIN topology_permissions p ON p.topology_id = c.topology_id
+                   WHERE p.user_id = $4 AND p.role IN ('owner', 'editor')
+               )
+         ) AS recent
+         GROUP BY connection_id, bucket
+         HAVING COUNT(*) > 1",
+    )
+    .bind(from)
+    .bind(until)
+    .bind(interval)
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| db_error("Failed to downsample metrics", e))?;
+
+    let mut merged = 0;
+    for (connection_id, bucket, count, throughput, packets_per_sec, latency, loss, utilization, bytes, packets) in buckets {
+        sqlx::query(
+            "DELETE FROM connection_traffic_metrics
+             WHERE connection_id = $1 AND timestamp >= $2 AND timestamp < $3
+               AND timestamp >= $4 AND timestamp < $5",
+        )
+        .bind(connection_id)
+        .bind(bucket)
+        .bind(bucket + interval)
+        .bind(from)
+        .bind(until)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| db_error("Failed to downsample metrics", e))?;
+
+        sqlx::query(
+            "INSERT INTO connection_traffic_metrics
+             (connection_id, timestamp, throughput_mbps, packets_per_sec, latency_ms,
+              packet_loss_pct, utilization_pct, bytes_transferred, packets_transferred)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(connection_id)
+        .bind(bucket.max(from))
+        .bind(throughput)
+        .bind(packets_per_sec.round() as i64)
+        .bind(latency)
+        .bind(loss)
+        .bind(utilization)
+        .bind(bytes.round() as i64)
+        .bind(packets.round() as i64)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| db_error("Failed to downsample metrics", e))?;
+
+        merged += count as usize - 1;
+    }
+
+    Ok(merged)
+}
+
+/// "an hour", "90 seconds", "2 days", for audit summaries
+#[cfg(feature = "ssr")]
+fn describe_duration(secs: i64) -> String {
+    let (count, unit) = match secs {
+        s if s % 86400 == 0 => (s / 86400, "day"),
+        s if s % 3600 == 0 => (s / 3600, "hour"),
+        s if s % 60 == 0 => (s / 60, "minute"),
+        s => (s, "second"),
+    };
+    match (count, unit) {
+        (1, "hour") => "an hour".to_string(),
+        (1, unit) => format!("a {}", unit),
+        (count, unit) => format!("{} {}s", count, unit),
+    }
+}
+
+/// Get the topology the signed-in user last viewed
+#[server(GetLastTopologyId, "/api")]
+pub async fn get_last_topology_id() -> Result<Option<i64>, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool): Extension<DbPool> = extract()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_user(&pool).await?;
+
+        let result: Option<(Option<i64>,)> =
+            sqlx::query_as("SELECT last_topology_id FROM user_settings WHERE user_id = $1")
+                .bind(user.id)
+                .fetch_optional(&pool)
+                .await
+                .map_err(|e| db_error("Database error", e))?;
+
+        Ok(result.and_then(|(id,)| id))
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Remember the topology the signed-in user is viewing
+#[server(SetLastTopologyId, "/api")]
+pub async fn set_last_topology_id(topology_id: i64) -> Result<(), AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool): Extension<DbPool> = extract()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_role(&pool, topology_id, Role::Viewer).await?;
+
+        sqlx::query(
+            "INSERT INTO user_settings (user_id, last_topology_id) VALUES ($1, $2)
+             ON CONFLICT (user_id) DO UPDATE SET last_topology_id = excluded.last_topology_id",
+        )
+        .bind(user.id)
+        .bind(topology_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+/// Create a new blank topology with auto-generated name
+#[server(CreateBlankTopology, "/api")]
+pub async fn create_blank_topology() -> Result<Topology, AppError> {
+    #[cfg(feature = "ssr")]
+    {
+        use axum::Extension;
+        use leptos_axum::extract;
+
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_user(&pool).await?;
+
+        // Generate unique name with timestamp
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let name = format!("New Topology #{}", timestamp);
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        let id = sqlx::query_scalar::<_, i64>("INSERT INTO topologies (name, description) VALUES ($1, $2) RETURNING id")
+            .bind(&name)
+            .bind(Some("Created via UI"))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        grant_role(&mut tx, id, user.id, Role::Owner)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        // Fetch the created topology
+        let topology = sqlx::query_as::<_, Topology>(
+            "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+
+        record_audit(&mut *tx, &user, AuditRecord::topology(AuditAction::Create, None, Some(&topology))).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit transaction", e))?;
+
+        Ok(topology)
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("Server function called on client")
+    }
+}
+
+// ============================================================================
+// UNDO FUNCTIONALITY
+// ============================================================================
+//
+// Every mutation records one undo_history entry per entity it touches, holding the
+// JSON state before and after the change. Entries are grouped into change sets (one
+// user-level step). Undo restores `previous_state` in reverse order, redo re-applies
+// `new_state` in order. Change sets marked `undone` form the redo stack.
+
+/// One entity change to be recorded in undo_history
+#[cfg(feature = "ssr")]
+struct UndoEntry {
+    entity_type: &'static str, // 'node' or 'connection'
+    entity_id: i64,
+    action_type: &'static str, // 'create', 'update' or 'delete'
+    previous_state: String,    // JSON snapshot before the change ('null' for creates)
+    new_state: Option<String>, // JSON snapshot after the change (None for deletes)
+}
+
+#[cfg(feature = "ssr")]
+impl UndoEntry {
+    fn new<T: Serialize>(
+        entity_type: &'static str,
+        entity_id: i64,
+        action_type: &'static str,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            entity_type,
+            entity_id,
+            action_type,
+            previous_state: to_undo_json(&before)?,
+            new_state: after.map(to_undo_json).transpose()?,
+        })
+    }
+
+    fn node(action_type: &'static str, before: Option<&Node>, after: Option<&Node>) -> Result<Self, sqlx::Error> {
+        let id = before.or(after).map(|n| n.id).unwrap_or_default();
+        Self::new("node", id, action_type, before, after)
+    }
+
+    fn connection(
+        action_type: &'static str,
+        before: Option<&Connection>,
+        after: Option<&Connection>,
+    ) -> Result<Self, sqlx::Error> {
+        let id = before.or(after).map(|c| c.id).unwrap_or_default();
+        Self::new("connection", id, action_type, before, after)
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn to_undo_json<T: Serialize>(value: &T) -> Result<String, sqlx::Error> {
+    serde_json::to_string(value)
+        .map_err(|e| sqlx::Error::Protocol(format!("JSON serialization error: {}", e)))
+}
+
+/// Record a node change as its own undo step (or as part of the open change set)
+#[cfg(feature = "ssr")]
+async fn record_node_change(
+    conn: &mut DbConnection,
+    action_type: &'static str,
+    before: Option<&Node>,
+    after: Option<&Node>,
+) -> Result<(), sqlx::Error> {
+    let Some(node) = after.or(before) else {
+        return Ok(());
+    };
+    let label = format!("{} node '{}'", action_label(action_type), node.name);
+    record_undo_entry(conn, node.topology_id, &label, UndoEntry::node(action_type, before, after)?).await
+}
+
+/// Record a connection change as its own undo step (or as part of the open change set)
+#[cfg(feature = "ssr")]
+async fn record_connection_change(
+    conn: &mut DbConnection,
+    action_type: &'static str,
+    before: Option<&Connection>,
+    after: Option<&Connection>,
+) -> Result<(), sqlx::Error> {
+    let Some(connection) = after.or(before) else {
+        return Ok(());
+    };
+    let label = format!("{} connection", action_label(action_type));
+    record_undo_entry(
+        conn,
+        connection.topology_id,
+        &label,
+        UndoEntry::connection(action_type, before, after)?,
+    )
+    .await
+}
+
+/// Human-readable verb for an undo action type
+#[cfg(feature = "ssr")]
+fn action_label(action_type: &str) -> &'static str {
+    match action_type {
+        "create" => "Create",
+        "delete" => "Delete",
+        _ => "Update",
+    }
+}
+
+/// Open change sets nobody has written to for this long are closed
+#[cfg(feature = "ssr")]
+const OPEN_CHANGE_SET_IDLE_SECS: i64 = 5 * 60;
+
+/// The session whose open change set this request's writes may join ('' for the local user)
+#[cfg(feature = "ssr")]
+async fn change_set_owner() -> String {
+    request_origin().await.unwrap_or_default()
+}
+
+/// Start a new change set, discarding the redo stack and trimming to the configured depth
+#[cfg(feature = "ssr")]
+async fn start_change_set(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    owner: &str,
+    label: &str,
+    is_open: bool,
+) -> Result<i64, sqlx::Error> {
+    // A new change invalidates everything that was undone
+    sqlx::query("DELETE FROM undo_change_sets WHERE topology_id = $1 AND undone = TRUE")
+        .bind(topology_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let change_set_id = sqlx::query_scalar::<_, i64>(
+        "INSERT INTO undo_change_sets (topology_id, label, is_open, owner, last_used_at)
+         VALUES ($1, $2, $3, $4, $5) RETURNING id",
+    )
+    .bind(topology_id)
+    .bind(label)
+    .bind(is_open)
+    .bind(owner)
+    .bind(unix_now())
+    .fetch_one(&mut *conn)
+    .await?;
+
+    // Keep only the newest `undo_depth` change sets (0 = unlimited)
+    let depth: i64 = sqlx::query_scalar("SELECT undo_depth FROM ui_settings WHERE id = 1")
+        .fetch_optional(&mut *conn)
+        .await?
+        .unwrap_or(100);
+    if depth > 0 {
+        sqlx::query(
+            "DELETE FROM undo_change_sets
+             WHERE topology_id = $1
+             AND id NOT IN (
+                 SELECT id FROM undo_change_sets
+                 WHERE topology_id = $2
+                 ORDER BY id DESC
+                 LIMIT $3
+             )",
+        )
+        .bind(topology_id)
+        .bind(topology_id)
+        .bind(depth)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(change_set_id)
+}
+
+/// The change set this session has open on the topology, or a new single-step one
+#[cfg(feature = "ssr")]
+async fn current_change_set(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    label: &str,
+) -> Result<i64, sqlx::Error> {
+    let owner = change_set_owner().await;
+    expire_change_sets(conn, topology_id).await?;
+
+    let open_change_set: Option<i64> = sqlx::query_scalar(
+        "SELECT id FROM undo_change_sets
+         WHERE topology_id = $1 AND is_open = TRUE AND owner = $2 AND undone = FALSE
+         ORDER BY id DESC LIMIT 1",
+    )
+    .bind(topology_id)
+    .bind(&owner)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    match open_change_set {
+        Some(id) => {
+            sqlx::query("UPDATE undo_change_sets SET last_used_at = $1 WHERE id = $2")
+                .bind(unix_now())
+                .bind(id)
+                .execute(&mut *conn)
+                .await?;
+            Ok(id)
+        }
+        None => start_change_set(conn, topology_id, &owner, label, false).await,
+    }
+}
+
+/// Close open change sets left idle (e.g. by an editor that went away mid-drag)
+#[cfg(feature = "ssr")]
+async fn expire_change_sets(conn: &mut DbConnection, topology_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE undo_change_sets SET is_open = FALSE
+         WHERE topology_id = $1 AND is_open = TRUE AND last_used_at < $2",
+    )
+    .bind(topology_id)
+    .bind(unix_now() - OPEN_CHANGE_SET_IDLE_SECS)
+    .execute(&mut *conn)
+    .await?;
+
+    delete_empty_change_sets(conn, topology_id).await
+}
+
+/// Drop closed change sets that recorded nothing
+#[cfg(feature = "ssr")]
+async fn delete_empty_change_sets(conn: &mut DbConnection, topology_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM undo_change_sets
+         WHERE topology_id = $1 AND is_open = FALSE
+         AND NOT EXISTS (SELECT 1 FROM undo_history WHERE change_set_id = undo_change_sets.id)",
+    )
+    .bind(topology_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Record an entity change in the topology's open change set, or in a new single-step one
+#[cfg(feature = "ssr")]
+async fn record_undo_entry(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    label: &str,
+    entry: UndoEntry,
+) -> Result<(), sqlx::Error> {
+    let change_set_id = current_change_set(conn, topology_id, label).await?;
+    insert_undo_entry(conn, topology_id, change_set_id, entry).await
+}
+
+/// Append an entity change to a specific change set
+#[cfg(feature = "ssr")]
+async fn insert_undo_entry(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    change_set_id: i64,
+    entry: UndoEntry,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO undo_history (topology_id, change_set_id, entity_type, entity_id, action_type, previous_state, new_state)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)"
+    )
+    .bind(topology_id)
+    .bind(change_set_id)
+    .bind(entry.entity_type)
+    .bind(entry.entity_id)
+    .bind(entry.action_type)
+    .bind(entry.previous_state)
+    .bind(entry.new_state)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Snapshot a node, every connection attached to it and their traffic metrics ahead of
+/// deleting the node, so undo can restore the whole subgraph with the original IDs
+#[cfg(feature = "ssr")]
+async fn record_node_delete(
+    conn: &mut DbConnection,
+    change_set_id: i64,
+    node: &Node,
+) -> Result<(), sqlx::Error> {
+    // Connections go first: undo walks the change set backwards, so the node is
+    // re-inserted before the connections that reference it
+    let connections = sqlx::query_as::<_, Connection>(
+        "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata, created_at, updated_at, version
+         FROM connections WHERE source_node_id = $1 OR target_node_id = $2 ORDER BY id"
+    )
+    .bind(node.id)
+    .bind(node.id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for connection in &connections {
+        record_connection_delete(conn, change_set_id, connection).await?;
+    }
+
+    let metrics = sqlx::query_as::<_, TrafficMetric>(
+        "SELECT id, node_id, timestamp, bytes_in, bytes_out, packets_in, packets_out, packet_loss_percent, cpu_usage_percent, memory_usage_percent
+         FROM traffic_metrics WHERE node_id = $1 ORDER BY id"
+    )
+    .bind(node.id)
+    .fetch_all(&mut *conn)
+    .await?;
+    if !metrics.is_empty() {
+        let entry = UndoEntry::new("node_traffic", node.id, "delete", Some(&metrics), None)?;
+        insert_undo_entry(conn, node.topology_id, change_set_id, entry).await?;
+    }
+
+    // Interfaces come back before the connections that plug into them
+    let interfaces = fetch_node_interfaces(conn, node.id).await?;
+    if !interfaces.is_empty() {
+        let entry = UndoEntry::new("node_interfaces", node.id, "delete", Some(&interfaces), None)?;
+        insert_undo_entry(conn, node.topology_id, change_set_id, entry).await?;
+    }
+
+    record_tags_delete(conn, node.topology_id, change_set_id, TaggedItem::Node(node.id)).await?;
+
+    insert_undo_entry(conn, node.topology_id, change_set_id, UndoEntry::node("delete", Some(node), None)?).await
+}
+
+/// Snapshot a connection and its traffic metrics ahead of deleting it
+#[cfg(feature = "ssr")]
+async fn record_connection_delete(
+    conn: &mut DbConnection,
+    change_set_id: i64,
+    connection: &Connection,
+) -> Result<(), sqlx::Error> {
+    let metrics = sqlx::query_as::<_, ConnectionTrafficMetric>(
+        "SELECT id, connection_id, timestamp, throughput_mbps, packets_per_sec, latency_ms, packet_loss_pct, utilization_pct, bytes_transferred, packets_transferred
+         FROM connection_traffic_metrics WHERE connection_id = $1 ORDER BY id"
+    )
+    .bind(connection.id)
+    .fetch_all(&mut *conn)
+    .await?;
+    if !metrics.is_empty() {
+        let entry = UndoEntry::new("connection_traffic", connection.id, "delete", Some(&metrics), None)?;
+        insert_undo_entry(conn, connection.topology_id, change_set_id, entry).await?;
+    }
+
+    record_tags_delete(conn, connection.topology_id, change_set_id, TaggedItem::Connection(connection.id)).await?;
+
+    insert_undo_entry(
+        conn,
+        connection.topology_id,
+        change_set_id,
+        UndoEntry::connection("delete", Some(connection), None)?,
+    )
+    .await
+}
+
+/// Snapshot the tag names of a node or connection about to be deleted
+///
+/// Names rather than tag IDs, so undo can bring back a tag deleted in the meantime.
+#[cfg(feature = "ssr")]
+async fn record_tags_delete(
+    conn: &mut DbConnection,
+    topology_id: i64,
+    change_set_id: i64,
+    item: TaggedItem,
+) -> Result<(), sqlx::Error> {
+    let names: Vec<String> = fetch_item_tags(conn, item).await?.into_iter().map(|tag| tag.name).collect();
+    if names.is_empty() {
+        return Ok(());
+    }
+    let entity_type = match item {
+        TaggedItem::Node(_) => "node_tags",
+        TaggedItem::Connection(_) => "connection_tags",
+    };
+    let entry = UndoEntry::new(entity_type, item.id(), "delete", Some(&names), None)?;
+    insert_undo_entry(conn, topology_id, change_set_id, entry).await
+}
+
+/// Make an entity match a JSON snapshot (a `null` or missing snapshot deletes it)
+///
+/// `node_traffic` and `connection_traffic` snapshots hold every metric row of a node or
+/// connection, `node_interfaces` every interface of a node, and `node_tags` and
+/// `connection_tags` the tag names of a node or connection, so they come back along
+/// with their owner. `topology_groups`, `topology_subnets` and `topology_tags` hold all
+/// of a topology's groups, subnets or tags (revision restores swap them as a whole).
+#[cfg(feature = "ssr")]
+async fn apply_entity_state(
+    conn: &mut DbConnection,
+    entity_type: &str,
+    entity_id: i64,
+    state: Option<&str>,
+) -> Result<(), AppError> {
+    let parse_error = |e: serde_json::Error| AppError::internal(format!("JSON deserialization error: {}", e));
+    let state = state.unwrap_or("null");
+
+    match entity_type {
+        "node" => match serde_json::from_str::<Option<Node>>(state).map_err(parse_error)? {
+            Some(node) => upsert_node(conn, &node).await.map(|_| ()),
+            None => sqlx::query("DELETE FROM nodes WHERE id = $1")
+                .bind(entity_id)
+                .execute(&mut *conn)
+                .await
+                .map(|_| ()),
+        },
+        "connection" => match serde_json::from_str::<Option<Connection>>(state).map_err(parse_error)? {
+            Some(connection) => upsert_connection(conn, &connection).await.map(|_| ()),
+            None => sqlx::query("DELETE FROM connections WHERE id = $1")
+                .bind(entity_id)
+                .execute(&mut *conn)
+                .await
+                .map(|_| ()),
+        },
+        "node_interfaces" => match serde_json::from_str::<Option<Vec<Interface>>>(state).map_err(parse_error)? {
+            Some(interfaces) => {
+                // Interfaces the snapshot doesn't have were added after it was taken
+                for existing in fetch_node_interfaces(conn, entity_id).await.map_err(|e| db_error("Database error", e))? {
+                    if !interfaces.iter().any(|interface| interface.id == existing.id) {
+                        sqlx::query("DELETE FROM interfaces WHERE id = $1")
+                            .bind(existing.id)
+                            .execute(&mut *conn)
+                            .await
+                            .map_err(|e| db_error("Database error", e))?;
+                    }
+                }
+                for interface in &interfaces {
+                    upsert_interface(conn, interface).await?;
+                }
+                Ok(())
+            }
+            None => sqlx::query("DELETE FROM interfaces WHERE node_id = $1")
+                .bind(entity_id)
+                .execute(&mut *conn)
+                .await
+                .map(|_| ()),
+        },
+        "node_tags" | "connection_tags" => {
+            let item = if entity_type == "node_tags" {
+                TaggedItem::Node(entity_id)
+            } else {
+                TaggedItem::Connection(entity_id)
+            };
+            let names = serde_json::from_str::<Option<Vec<String>>>(state)
+                .map_err(parse_error)?
+                .unwrap_or_default();
+            // The owner is restored first (undo walks the change set backwards)
+            let topology_id = item.topology_id(conn).await?;
+            replace_item_tags(conn, topology_id, item, &names).await
+        }
+        "node_traffic" => match serde_json::from_str::<Option<Vec<TrafficMetric>>>(state).map_err(parse_error)? {
+            Some(metrics) => insert_node_traffic(conn, &metrics).await,
+            None => sqlx::query("DELETE FROM traffic_metrics WHERE node_id = $1")
+                .bind(entity_id)
+                .execute(&mut *conn)
+                .await
+                .map(|_| ()),
+        },
+        "connection_traffic" => {
+            match serde_json::from_str::<Option<Vec<ConnectionTrafficMetric>>>(state).map_err(parse_error)? {
+                Some(metrics) => insert_connection_traffic(conn, &metrics).await,
+                None => sqlx::query("DELETE FROM connection_traffic_metrics WHERE connection_id = $1")
+                    .bind(entity_id)
+                    .execute(&mut *conn)
+                    .await
+                    .map(|_| ()),
+            }
+        }
+        "topology_groups" => {
+            let groups = serde_json::from_str::<Option<Vec<NodeGroup>>>(state)
+                .map_err(parse_error)?
+                .unwrap_or_default();
+            replace_groups(conn, entity_id, &groups).await
+        }
+        "topology_subnets" => {
+            let subnets = serde_json::from_str::<Option<Vec<Subnet>>>(state)
+                .map_err(parse_error)?
+                .unwrap_or_default();
+            replace_subnets(conn, entity_id, &subnets).await
+        }
+        "topology_tags" => {
+            let tags = serde_json::from_str::<Option<Vec<Tag>>>(state)
+                .map_err(parse_error)?
+                .unwrap_or_default();
+            match replace_tags(conn, entity_id, &tags).await {
+                Ok(false) => return Err(AppError::conflict("A tag ID of the snapshot is now used by another topology")),
+                result => result.map(|_| ()),
+            }
+        }
+        other => return Err(AppError::internal(format!("Unknown undo entity type: {}", other))),
     }
+    .map_err(|e| db_error("Database error", e))
+}
 
-    #[cfg(not(feature = "ssr"))]
-    {
-        unreachable!("Server function called on client")
-    }
+/// Insert a node with its original ID, or overwrite it in place if it still exists
+///
+/// Returns false, writing nothing, if the ID belongs to a node of another topology.
+#[cfg(feature = "ssr")]
+async fn upsert_node(conn: &mut DbConnection, node: &Node) -> Result<bool, sqlx::Error> {
+    // ON CONFLICT DO UPDATE (not INSERT OR REPLACE) so attached connections aren't cascade-deleted.
+    // A group deleted since the snapshot was taken leaves the node ungrouped. Either way the
+    // version moves past the snapshot's, so edits based on the undone state are refused.
+    let result = sqlx::query(
+        "INSERT INTO nodes (id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, group_id, metadata, created_at, updated_at, version)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, (SELECT id FROM node_groups WHERE id = $17 AND topology_id = $18), $19, $20, $21, $22)
+         ON CONFLICT(id) DO UPDATE SET
+             name = excluded.name, node_type = excluded.node_type, vendor = excluded.vendor,
+             model_name = excluded.model_name, ip_address = excluded.ip_address,
+             position_x = excluded.position_x, position_y = excluded.position_y, position_z = excluded.position_z,
+             rotation_x = excluded.rotation_x, rotation_y = excluded.rotation_y, rotation_z = excluded.rotation_z,
+             scale = excluded.scale, color = excluded.color, visible = excluded.visible,
+             group_id = excluded.group_id, metadata = excluded.metadata, version = nodes.version + 1
+         WHERE nodes.topology_id = excluded.topology_id"
+    )
+    .bind(node.id)
+    .bind(node.topology_id)
+    .bind(&node.name)
+    .bind(node.node_type)
+    .bind(&node.vendor)
+    .bind(&node.model_name)
+    .bind(&node.ip_address)
+    .bind(node.position_x)
+    .bind(node.position_y)
+    .bind(node.position_z)
+    .bind(node.rotation_x)
+    .bind(node.rotation_y)
+    .bind(node.rotation_z)
+    .bind(node.scale)
+    .bind(node.color)
+    .bind(node.visible)
+    .bind(node.group_id)
+    .bind(node.topology_id)
+    .bind(&node.metadata)
+    .bind(node.created_at)
+    .bind(node.updated_at)
+    .bind(node.version + 1)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
 }
 
-/// Get latest traffic metric for each connection (for real-time visualization)
-/// Returns a map of connection_id -> latest metric
-#[server(GetLatestTrafficMetrics, "/api")]
-pub async fn get_latest_traffic_metrics(
-    topology_id: i64,
-) -> Result<std::collections::HashMap<i64, ConnectionTrafficMetric>, ServerFnError> {
-    #[cfg(feature = "ssr")]
-    {
-        use axum::Extension;
-        use leptos_axum::extract;
-        use std::collections::HashMap;
+/// Insert a connection with its original ID, or overwrite it in place if it still exists
+///
+/// Returns false, writing nothing, if the ID belongs to a connection of another topology.
+#[cfg(feature = "ssr")]
+async fn upsert_connection(
+    conn: &mut DbConnection,
+    connection: &Connection,
+) -> Result<bool, sqlx::Error> {
+    // An interface deleted since the snapshot was taken leaves that end node-level
+    let result = sqlx::query(
+        "INSERT INTO connections (id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, source_interface_id, target_interface_id, metadata, created_at, updated_at, version)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12,
+                 (SELECT id FROM interfaces WHERE id = $13 AND node_id = $14),
+                 (SELECT id FROM interfaces WHERE id = $15 AND node_id = $16),
+                 $17, $18, $19, $20)
+         ON CONFLICT(id) DO UPDATE SET
+             source_node_id = excluded.source_node_id, target_node_id = excluded.target_node_id,
+             connection_type = excluded.connection_type, bandwidth_mbps = excluded.bandwidth_mbps,
+             latency_ms = excluded.latency_ms, baseline_packet_loss_pct = excluded.baseline_packet_loss_pct,
+             status = excluded.status, color = excluded.color, carries_traffic = excluded.carries_traffic,
+             flow_direction = excluded.flow_direction, source_interface_id = excluded.source_interface_id,
+             target_interface_id = excluded.target_interface_id, metadata = excluded.metadata,
+             version = connections.version + 1
+         WHERE connections.topology_id = excluded.topology_id"
+    )
+    .bind(connection.id)
+    .bind(connection.topology_id)
+    .bind(connection.source_node_id)
+    .bind(connection.target_node_id)
+    .bind(connection.connection_type)
+    .bind(connection.bandwidth_mbps)
+    .bind(connection.latency_ms)
+    .bind(connection.baseline_packet_loss_pct.unwrap_or(0.0))
+    .bind(connection.status)
+    .bind(connection.color)
+    .bind(connection.carries_traffic)
+    .bind(connection.flow_direction)
+    .bind(connection.source_interface_id)
+    .bind(connection.source_node_id)
+    .bind(connection.target_interface_id)
+    .bind(connection.target_node_id)
+    .bind(&connection.metadata)
+    .bind(connection.created_at)
+    .bind(connection.updated_at)
+    .bind(connection.version + 1)
+    .execute(&mut *conn)
+    .await?;
 
-        let Extension(pool): Extension<SqlitePool> = extract().await?;
+    Ok(result.rows_affected() > 0)
+}
 
-        // Get latest metric for each connection in this topology
-        // Uses a subquery to find the max timestamp per connection, then joins to get full row
-        let metrics = sqlx::query_as::<_, ConnectionTrafficMetric>(
-            r#"
-            SELECT ctm.*
-            FROM connection_traffic_metrics ctm
-            INNER JOIN connections c ON c.id = ctm.connection_id
-            INNER JOIN (
-                SELECT connection_id, MAX(timestamp) as max_timestamp
-                FROM connection_traffic_metrics
-                WHERE connection_id IN (
-                    SELECT id FROM connections WHERE topology_id = ?
-                )
-                GROUP BY connection_id
-            ) latest ON latest.connection_id = ctm.connection_id
-                    AND latest.max_timestamp = ctm.timestamp
-            WHERE c.topology_id = ?
-            "#,
+/// Insert an interface with its original ID, or overwrite it in place if it still exists
+///
+/// Returns false, writing nothing, if the ID belongs to an interface of another node.
+#[cfg(feature = "ssr")]
+async fn upsert_interface(
+    conn: &mut DbConnection,
+    interface: &Interface,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO interfaces (id, node_id, name, speed_mbps, mac_address, ip_address, admin_state, description, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+         ON CONFLICT(id) DO UPDATE SET
+             name = excluded.name, speed_mbps = excluded.speed_mbps, mac_address = excluded.mac_address,
+             ip_address = excluded.ip_address, admin_state = excluded.admin_state,
+             description = excluded.description, updated_at = excluded.updated_at
+         WHERE interfaces.node_id = excluded.node_id"
+    )
+    .bind(interface.id)
+    .bind(interface.node_id)
+    .bind(&interface.name)
+    .bind(interface.speed_mbps)
+    .bind(&interface.mac_address)
+    .bind(&interface.ip_address)
+    .bind(interface.admin_state)
+    .bind(&interface.description)
+    .bind(interface.created_at)
+    .bind(interface.updated_at)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Re-insert node traffic metric rows with their original IDs
+#[cfg(feature = "ssr")]
+async fn insert_node_traffic(
+    conn: &mut DbConnection,
+    metrics: &[TrafficMetric],
+) -> Result<(), sqlx::Error> {
+    for metric in metrics {
+        sqlx::query(
+            "INSERT INTO traffic_metrics (id, node_id, timestamp, bytes_in, bytes_out, packets_in, packets_out, packet_loss_percent, cpu_usage_percent, memory_usage_percent)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) ON CONFLICT DO NOTHING"
         )
-        .bind(topology_id)
-        .bind(topology_id)
-        .fetch_all(&pool)
+        .bind(metric.id)
+        .bind(metric.node_id)
+        .bind(metric.timestamp)
+        .bind(metric.bytes_in)
+        .bind(metric.bytes_out)
+        .bind(metric.packets_in)
+        .bind(metric.packets_out)
+        .bind(metric.packet_loss_percent)
+        .bind(metric.cpu_usage_percent)
+        .bind(metric.memory_usage_percent)
+        .execute(&mut *conn)
         .await?;
+    }
 
-        // Convert to HashMap for easy lookup
-        let mut map = HashMap::new();
-        for metric in metrics {
-            map.insert(metric.connection_id, metric);
-        }
+    Ok(())
+}
 
-        Ok(map)
+/// Re-insert connection traffic metric rows with their original IDs
+#[cfg(feature = "ssr")]
+async fn insert_connection_traffic(
+    conn: &mut DbConnection,
+    metrics: &[ConnectionTrafficMetric],
+) -> Result<(), sqlx::Error> {
+    for metric in metrics {
+        sqlx::query(
+            "INSERT INTO connection_traffic_metrics (id, connection_id, timestamp, throughput_mbps, packets_per_sec, latency_ms, packet_loss_pct, utilization_pct, bytes_transferred, packets_transferred)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) ON CONFLICT DO NOTHING"
+        )
+        .bind(metric.id)
+        .bind(metric.connection_id)
+        .bind(metric.timestamp)
+        .bind(metric.throughput_mbps)
+        .bind(metric.packets_per_sec)
+        .bind(metric.latency_ms)
+        .bind(metric.packet_loss_pct)
+        .bind(metric.utilization_pct)
+        .bind(metric.bytes_transferred)
+        .bind(metric.packets_transferred)
+        .execute(&mut *conn)
+        .await?;
     }
 
-    #[cfg(not(feature = "ssr"))]
-    {
-        unreachable!("Server function called on client")
-    }
+    Ok(())
 }
 
-/// Clear all traffic data for a specific topology (restore manual colors)
-#[server(ClearTrafficData, "/api")]
-pub async fn clear_traffic_data(topology_id: i64) -> Result<usize, ServerFnError> {
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoHistoryEntry {
+    pub id: i64,
+    pub entity_type: String,
+    pub entity_id: i64,
+    pub action_type: String,
+    pub timestamp: String,
+}
+
+/// Labels of the next undo and redo steps for a topology (None = nothing to undo/redo)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UndoStatus {
+    pub undo_label: Option<String>,
+    pub redo_label: Option<String>,
+}
+
+/// Get undoable history entries for a topology (newest first, excludes the redo stack)
+#[server(GetUndoHistory, "/api")]
+pub async fn get_undo_history(topology_id: i64) -> Result<Vec<UndoHistoryEntry>, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool): Extension<SqlitePool> = extract().await?;
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        // Delete all traffic metrics for connections in this topology
-        let result = sqlx::query(
-            r#"
-            DELETE FROM connection_traffic_metrics
-            WHERE connection_id IN (
-                SELECT id FROM connections WHERE topology_id = ?
-            )
-            "#,
+        require_role(&pool, topology_id, Role::Viewer).await?;
+
+        let entries = sqlx::query_as::<_, UndoHistoryEntry>(
+            "SELECT h.id, h.entity_type, h.entity_id, h.action_type, h.timestamp
+             FROM undo_history h
+             JOIN undo_change_sets c ON c.id = h.change_set_id
+             WHERE h.topology_id = $1 AND c.undone = FALSE
+             ORDER BY h.id DESC",
         )
         .bind(topology_id)
-        .execute(&pool)
+        .fetch_all(&pool)
         .await
-        .map_err(|e| ServerFnError::new(format!("Failed to clear traffic data: {}", e)))?;
+        .map_err(|e| db_error("Database error", e))?;
 
-        Ok(result.rows_affected() as usize)
+        Ok(entries)
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -1450,31 +7501,40 @@ pub async fn clear_traffic_data(topology_id: i64) -> Result<usize, ServerFnError
     }
 }
 
-/// Clean up old traffic metrics (keep only last 1 hour of data)
-#[server(CleanOldTrafficMetrics, "/api")]
-pub async fn clean_old_traffic_metrics() -> Result<usize, ServerFnError> {
+/// Get the labels of the next undo and redo steps
+#[server(GetUndoStatus, "/api")]
+pub async fn get_undo_status(topology_id: i64) -> Result<UndoStatus, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        let one_hour_ago = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64
-            - 3600;
+        require_role(&pool, topology_id, Role::Viewer).await?;
 
-        let result = sqlx::query("DELETE FROM connection_traffic_metrics WHERE timestamp < ?")
-            .bind(one_hour_ago)
-            .execute(&pool)
-            .await
-            .map_err(|e| ServerFnError::new(format!("Failed to clean old metrics: {}", e)))?;
+        let undo_label: Option<String> = sqlx::query_scalar(
+            "SELECT label FROM undo_change_sets WHERE topology_id = $1 AND undone = FALSE ORDER BY id DESC LIMIT 1",
+        )
+        .bind(topology_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+
+        let redo_label: Option<String> = sqlx::query_scalar(
+            "SELECT label FROM undo_change_sets WHERE topology_id = $1 AND undone = TRUE ORDER BY id ASC LIMIT 1",
+        )
+        .bind(topology_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
 
-        Ok(result.rows_affected() as usize)
+        Ok(UndoStatus {
+            undo_label,
+            redo_label,
+        })
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -1483,54 +7543,51 @@ pub async fn clean_old_traffic_metrics() -> Result<usize, ServerFnError> {
     }
 }
 
-/// Get the last viewed topology ID from ui_settings
-#[server(GetLastTopologyId, "/api")]
-pub async fn get_last_topology_id() -> Result<Option<i64>, ServerFnError> {
+/// Start grouping writes to a topology into one undo step (e.g. "Moved 12 nodes")
+///
+/// Every node/connection change this session makes until `end_change_set` is undone and
+/// redone together; other sessions' edits meanwhile get their own steps.
+#[server(BeginChangeSet, "/api")]
+pub async fn begin_change_set(topology_id: i64, label: String) -> Result<i64, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool): Extension<SqlitePool> = extract()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
-
-        let result: Option<(Option<i64>,)> =
-            sqlx::query_as("SELECT last_topology_id FROM ui_settings WHERE id = 1")
-                .fetch_optional(&pool)
-                .await
-                .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
-
-        Ok(result.and_then(|(id,)| id))
-    }
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-    #[cfg(not(feature = "ssr"))]
-    {
-        unreachable!("Server function called on client")
-    }
-}
-
-/// Set the last viewed topology ID in ui_settings
-#[server(SetLastTopologyId, "/api")]
-pub async fn set_last_topology_id(topology_id: i64) -> Result<(), ServerFnError> {
-    #[cfg(feature = "ssr")]
-    {
-        use axum::Extension;
-        use leptos_axum::extract;
+        require_role(&pool, topology_id, Role::Editor).await?;
 
-        let Extension(pool): Extension<SqlitePool> = extract()
+        let owner = change_set_owner().await;
+        let mut tx = pool
+            .begin()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| db_error("Failed to start transaction", e))?;
 
+        // Each session has at most one open change set per topology
         sqlx::query(
-            "UPDATE ui_settings SET last_topology_id = ?, updated_at = CURRENT_TIMESTAMP WHERE id = 1"
+            "UPDATE undo_change_sets SET is_open = FALSE WHERE topology_id = $1 AND is_open = TRUE AND owner = $2",
         )
         .bind(topology_id)
-        .execute(&pool)
+        .bind(&owner)
+        .execute(&mut *tx)
         .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+        .map_err(|e| db_error("Database error", e))?;
+        expire_change_sets(&mut tx, topology_id)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
 
-        Ok(())
+        let change_set_id = start_change_set(&mut tx, topology_id, &owner, &label, true)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        Ok(change_set_id)
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -1539,45 +7596,25 @@ pub async fn set_last_topology_id(topology_id: i64) -> Result<(), ServerFnError>
     }
 }
 
-/// Create a new blank topology with auto-generated name
-#[server(CreateBlankTopology, "/api")]
-pub async fn create_blank_topology() -> Result<Topology, ServerFnError> {
+/// Close this session's open change set on a topology (empty change sets are discarded)
+#[server(EndChangeSet, "/api")]
+pub async fn end_change_set(topology_id: i64) -> Result<(), AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
-
-        // Generate unique name with timestamp
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        let name = format!("New Topology #{}", timestamp);
+        require_role(&pool, topology_id, Role::Editor).await?;
 
-        let result = sqlx::query("INSERT INTO topologies (name, description) VALUES (?, ?)")
-            .bind(&name)
-            .bind(Some("Created via UI"))
-            .execute(&pool)
+        close_change_sets(&pool, topology_id, &change_set_owner().await)
             .await
-            .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
-
-        let id = result.last_insert_rowid();
-
-        // Fetch the created topology
-        let topology = sqlx::query_as::<_, Topology>(
-            "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = ?",
-        )
-        .bind(id)
-        .fetch_one(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+            .map_err(|e| db_error("Database error", e))?;
 
-        Ok(topology)
+        Ok(())
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -1586,113 +7623,96 @@ pub async fn create_blank_topology() -> Result<Topology, ServerFnError> {
     }
 }
 
-// ============================================================================
-// UNDO FUNCTIONALITY
-// ============================================================================
-
-/// Helper function to save undo history for node updates
-#[cfg(feature = "ssr")]
-async fn save_node_undo_history(
-    pool: &SqlitePool,
-    node_id: i64,
-    topology_id: i64,
-) -> Result<(), sqlx::Error> {
-    // Get current state of the node before update
-    let node = sqlx::query_as::<_, Node>(
-        "SELECT id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, visible, metadata, created_at, updated_at
-         FROM nodes WHERE id = ?"
-    )
-    .bind(node_id)
-    .fetch_one(pool)
-    .await?;
-
-    // Serialize node to JSON
-    let previous_state = serde_json::to_string(&node)
-        .map_err(|e| sqlx::Error::Protocol(format!("JSON serialization error: {}", e)))?;
-
-    // Insert into undo_history
-    sqlx::query(
-        "INSERT INTO undo_history (topology_id, entity_type, entity_id, action_type, previous_state)
-         VALUES (?, 'node', ?, 'update', ?)"
-    )
-    .bind(topology_id)
-    .bind(node_id)
-    .bind(previous_state)
-    .execute(pool)
-    .await?;
-
-    Ok(())
-}
-
-/// Helper function to save undo history for connection updates
+/// Close a session's open change set on a topology and drop change sets that recorded nothing
 #[cfg(feature = "ssr")]
-async fn save_connection_undo_history(
-    pool: &SqlitePool,
-    connection_id: i64,
-    topology_id: i64,
-) -> Result<(), sqlx::Error> {
-    // Get current state of the connection before update
-    let connection = sqlx::query_as::<_, Connection>(
-        "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, metadata, created_at, updated_at
-         FROM connections WHERE id = ?"
-    )
-    .bind(connection_id)
-    .fetch_one(pool)
-    .await?;
-
-    // Serialize connection to JSON
-    let previous_state = serde_json::to_string(&connection)
-        .map_err(|e| sqlx::Error::Protocol(format!("JSON serialization error: {}", e)))?;
-
-    // Insert into undo_history
+async fn close_change_sets(pool: &DbPool, topology_id: i64, owner: &str) -> Result<(), sqlx::Error> {
+    let mut conn = pool.acquire().await?;
     sqlx::query(
-        "INSERT INTO undo_history (topology_id, entity_type, entity_id, action_type, previous_state)
-         VALUES (?, 'connection', ?, 'update', ?)"
+        "UPDATE undo_change_sets SET is_open = FALSE WHERE topology_id = $1 AND is_open = TRUE AND owner = $2",
     )
     .bind(topology_id)
-    .bind(connection_id)
-    .bind(previous_state)
-    .execute(pool)
+    .bind(owner)
+    .execute(&mut *conn)
     .await?;
 
-    Ok(())
-}
-
-#[cfg_attr(feature = "ssr", derive(FromRow))]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct UndoHistoryEntry {
-    pub id: i64,
-    pub entity_type: String,
-    pub entity_id: i64,
-    pub action_type: String,
-    pub timestamp: String,
+    delete_empty_change_sets(&mut conn, topology_id).await
 }
 
-/// Get undo history for a topology (last 5 entries)
-#[server(GetUndoHistory, "/api")]
-pub async fn get_undo_history(topology_id: i64) -> Result<Vec<UndoHistoryEntry>, ServerFnError> {
+/// Undo the most recent change set in a topology
+#[server(UndoLastChange, "/api", client = LiveClient)]
+pub async fn undo_last_change(topology_id: i64) -> Result<bool, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
 
-        let entries = sqlx::query_as::<_, UndoHistoryEntry>(
-            "SELECT id, entity_type, entity_id, action_type, timestamp
-             FROM undo_history
-             WHERE topology_id = ?
-             ORDER BY timestamp DESC
-             LIMIT 5",
+        let user = require_role(&pool, topology_id, Role::Editor).await?;
+
+        let owner = change_set_owner().await;
+
+        // Undo never appends to a change set this session is still recording
+        close_change_sets(&pool, topology_id, &owner)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to start transaction", e))?;
+
+        // Skip change sets another session is still recording so their group isn't split
+        let change_set_id: Option<i64> = sqlx::query_scalar(
+            "SELECT id FROM undo_change_sets
+             WHERE topology_id = $1 AND undone = FALSE AND NOT (is_open = TRUE AND owner <> $2)
+             ORDER BY id DESC LIMIT 1",
         )
         .bind(topology_id)
-        .fetch_all(&pool)
+        .bind(&owner)
+        .fetch_optional(&mut *tx)
         .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+        .map_err(|e| db_error("Database error", e))?;
 
-        Ok(entries)
+        let Some(change_set_id) = change_set_id else {
+            // No undo history available
+            return Ok(false);
+        };
+
+        // Restore previous states, newest entry first
+        let entries = sqlx::query(
+            "SELECT entity_type, entity_id, previous_state FROM undo_history WHERE change_set_id = $1 ORDER BY id DESC",
+        )
+        .bind(change_set_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+
+        for row in entries {
+            let entity_type: String = row.get("entity_type");
+            let entity_id: i64 = row.get("entity_id");
+            let previous_state: String = row.get("previous_state");
+            apply_entity_state(&mut tx, &entity_type, entity_id, Some(&previous_state)).await?;
+        }
+
+        // Move the change set onto the redo stack
+        sqlx::query("UPDATE undo_change_sets SET undone = TRUE WHERE id = $1")
+            .bind(change_set_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        record_change_set_audit(&mut tx, &user, topology_id, change_set_id, AuditAction::Undo).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        publish_live(topology_id, LiveEvent::TopologyChanged).await;
+
+        Ok(true)
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -1701,174 +7721,80 @@ pub async fn get_undo_history(topology_id: i64) -> Result<Vec<UndoHistoryEntry>,
     }
 }
 
-/// Undo the last change in a topology
-#[server(UndoLastChange, "/api")]
-pub async fn undo_last_change(topology_id: i64) -> Result<bool, ServerFnError> {
+/// Redo the most recently undone change set in a topology
+#[server(RedoLastChange, "/api", client = LiveClient)]
+pub async fn redo_last_change(topology_id: i64) -> Result<bool, AppError> {
     #[cfg(feature = "ssr")]
     {
         use axum::Extension;
         use leptos_axum::extract;
 
-        let Extension(pool) = extract::<Extension<SqlitePool>>()
+        let Extension(pool) = extract::<Extension<DbPool>>()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to extract database pool: {}", e)))?;
+
+        let user = require_role(&pool, topology_id, Role::Editor).await?;
+
+        let owner = change_set_owner().await;
+
+        // Redo never runs while this session is still recording a change set
+        close_change_sets(&pool, topology_id, &owner)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        let mut tx = pool
+            .begin()
             .await
-            .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
+            .map_err(|e| db_error("Failed to start transaction", e))?;
 
-        // Get the most recent undo entry
-        let entry = sqlx::query(
-            "SELECT id, entity_type, entity_id, action_type, previous_state
-             FROM undo_history
-             WHERE topology_id = ?
-             ORDER BY timestamp DESC
-             LIMIT 1",
+        // Undo walks backwards, so the next step to redo is the oldest undone change set
+        let change_set_id: Option<i64> = sqlx::query_scalar(
+            "SELECT id FROM undo_change_sets
+             WHERE topology_id = $1 AND undone = TRUE AND NOT (is_open = TRUE AND owner <> $2)
+             ORDER BY id ASC LIMIT 1",
         )
         .bind(topology_id)
-        .fetch_optional(&pool)
+        .bind(&owner)
+        .fetch_optional(&mut *tx)
         .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+        .map_err(|e| db_error("Database error", e))?;
 
-        if let Some(row) = entry {
-            let undo_id: i64 = row.get("id");
+        let Some(change_set_id) = change_set_id else {
+            // Nothing to redo
+            return Ok(false);
+        };
+
+        // Re-apply new states in the order they were recorded
+        let entries = sqlx::query(
+            "SELECT entity_type, entity_id, new_state FROM undo_history WHERE change_set_id = $1 ORDER BY id ASC",
+        )
+        .bind(change_set_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| db_error("Database error", e))?;
+
+        for row in entries {
             let entity_type: String = row.get("entity_type");
             let entity_id: i64 = row.get("entity_id");
-            let action_type: String = row.get("action_type");
-            let previous_state: String = row.get("previous_state");
+            let new_state: Option<String> = row.get("new_state");
+            apply_entity_state(&mut tx, &entity_type, entity_id, new_state.as_deref()).await?;
+        }
 
-            // Restore the previous state based on entity type and action type
-            if action_type == "update" {
-                // For updates, restore to the previous state
-                if entity_type == "node" {
-                    let node: Node = serde_json::from_str(&previous_state).map_err(|e| {
-                        ServerFnError::new(format!("JSON deserialization error: {}", e))
-                    })?;
-
-                    // Restore node to previous state
-                    sqlx::query(
-                        "UPDATE nodes SET name = ?, node_type = ?, vendor = ?, model_name = ?, ip_address = ?,
-                         position_x = ?, position_y = ?, position_z = ?, rotation_x = ?, rotation_y = ?, rotation_z = ?,
-                         scale = ?, color = ?, metadata = ?, updated_at = CURRENT_TIMESTAMP
-                         WHERE id = ?"
-                    )
-                    .bind(&node.name)
-                    .bind(&node.node_type)
-                    .bind(&node.vendor)
-                    .bind(&node.model_name)
-                    .bind(&node.ip_address)
-                    .bind(node.position_x)
-                    .bind(node.position_y)
-                    .bind(node.position_z)
-                    .bind(node.rotation_x)
-                    .bind(node.rotation_y)
-                    .bind(node.rotation_z)
-                    .bind(node.scale)
-                    .bind(&node.color)
-                    .bind(&node.metadata)
-                    .bind(entity_id)
-                    .execute(&pool)
-                    .await
-                    .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
-                } else if entity_type == "connection" {
-                    let connection: Connection =
-                        serde_json::from_str(&previous_state).map_err(|e| {
-                            ServerFnError::new(format!("JSON deserialization error: {}", e))
-                        })?;
-
-                    // Restore connection to previous state
-                    sqlx::query(
-                        "UPDATE connections SET connection_type = ?, bandwidth_mbps = ?, latency_ms = ?,
-                         baseline_packet_loss_pct = ?, status = ?, color = ?, carries_traffic = ?, flow_direction = ?,
-                         metadata = ?, updated_at = CURRENT_TIMESTAMP
-                         WHERE id = ?"
-                    )
-                    .bind(&connection.connection_type)
-                    .bind(connection.bandwidth_mbps)
-                    .bind(connection.latency_ms)
-                    .bind(connection.baseline_packet_loss_pct)
-                    .bind(&connection.status)
-                    .bind(&connection.color)
-                    .bind(connection.carries_traffic)
-                    .bind(&connection.flow_direction)
-                    .bind(&connection.metadata)
-                    .bind(entity_id)
-                    .execute(&pool)
-                    .await
-                    .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
-                }
-            } else if action_type == "delete" {
-                // For deletions, recreate the deleted entity
-                if entity_type == "node" {
-                    let node: Node = serde_json::from_str(&previous_state).map_err(|e| {
-                        ServerFnError::new(format!("JSON deserialization error: {}", e))
-                    })?;
-
-                    // Recreate the deleted node
-                    sqlx::query(
-                        "INSERT INTO nodes (id, topology_id, name, node_type, vendor, model_name, ip_address, position_x, position_y, position_z, rotation_x, rotation_y, rotation_z, scale, color, metadata, created_at, updated_at)
-                         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
-                    )
-                    .bind(node.id)
-                    .bind(node.topology_id)
-                    .bind(&node.name)
-                    .bind(&node.node_type)
-                    .bind(&node.vendor)
-                    .bind(&node.model_name)
-                    .bind(&node.ip_address)
-                    .bind(node.position_x)
-                    .bind(node.position_y)
-                    .bind(node.position_z)
-                    .bind(node.rotation_x)
-                    .bind(node.rotation_y)
-                    .bind(node.rotation_z)
-                    .bind(node.scale)
-                    .bind(&node.color)
-                    .bind(&node.metadata)
-                    .bind(&node.created_at)
-                    .bind(&node.updated_at)
-                    .execute(&pool)
-                    .await
-                    .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
-                } else if entity_type == "connection" {
-                    let connection: Connection = serde_json::from_str(&previous_state).map_err(|e| {
-                        ServerFnError::new(format!("JSON deserialization error: {}", e))
-                    })?;
-
-                    // Recreate the deleted connection
-                    sqlx::query(
-                        "INSERT INTO connections (id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, baseline_packet_loss_pct, status, color, carries_traffic, flow_direction, metadata, created_at, updated_at)
-                         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
-                    )
-                    .bind(connection.id)
-                    .bind(connection.topology_id)
-                    .bind(connection.source_node_id)
-                    .bind(connection.target_node_id)
-                    .bind(&connection.connection_type)
-                    .bind(connection.bandwidth_mbps)
-                    .bind(connection.latency_ms)
-                    .bind(connection.baseline_packet_loss_pct)
-                    .bind(&connection.status)
-                    .bind(&connection.color)
-                    .bind(connection.carries_traffic)
-                    .bind(&connection.flow_direction)
-                    .bind(&connection.metadata)
-                    .bind(&connection.created_at)
-                    .bind(&connection.updated_at)
-                    .execute(&pool)
-                    .await
-                    .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
-                }
-            }
+        sqlx::query("UPDATE undo_change_sets SET undone = FALSE WHERE id = $1")
+            .bind(change_set_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Database error", e))?;
 
-            // Delete the undo history entry that was just applied
-            sqlx::query("DELETE FROM undo_history WHERE id = ?")
-                .bind(undo_id)
-                .execute(&pool)
-                .await
-                .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+        record_change_set_audit(&mut tx, &user, topology_id, change_set_id, AuditAction::Redo).await?;
 
-            Ok(true)
-        } else {
-            // No undo history available
-            Ok(false)
-        }
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Database error", e))?;
+
+        publish_live(topology_id, LiveEvent::TopologyChanged).await;
+
+        Ok(true)
     }
 
     #[cfg(not(feature = "ssr"))]
@@ -51,6 +51,27 @@ pub fn App() -> impl IntoView {
 /// Renders the home page of your application.
 #[component]
 fn HomePage() -> impl IntoView {
+    use crate::islands::SignIn;
+    use crate::api::get_current_user;
+
+    // Everything requires an account, so show the sign-in form until there is a session
+    let current_user = Resource::new(|| (), |_| get_current_user());
+
+    view! {
+        <Suspense fallback=|| ()>
+            {move || Suspend::new(async move {
+                match current_user.await {
+                    Ok(Some(_)) => view! { <Workspace /> }.into_any(),
+                    _ => view! { <SignIn /> }.into_any(),
+                }
+            })}
+        </Suspense>
+    }
+}
+
+/// The topology editor for a signed-in user
+#[component]
+fn Workspace() -> impl IntoView {
     use crate::islands::TopologyEditor;
     use crate::api::{get_last_topology_id, clear_traffic_data, get_topologies};
     use leptos::task::spawn_local;
@@ -65,7 +86,7 @@ fn HomePage() -> impl IntoView {
             // Get the last viewed topology ID
             if let Ok(Some(last_id)) = get_last_topology_id().await {
                 // Verify the topology still exists before setting it
-                if let Ok(_) = crate::api::get_topology_full(last_id).await {
+                if crate::api::get_topology_full(last_id).await.is_ok() {
                     current_topology_id.set(last_id);
                     // Clear traffic data for this topology on startup
                     let _ = clear_traffic_data(last_id).await;
@@ -0,0 +1,327 @@
+//! The `ntb` command line: the web server plus headless topology management
+//!
+//! Management commands open the database directly and call the server functions from
+//! `crate::api` the way the REST API does (see `crate::server::run_server_fn`), so
+//! validation, undo recording and the audit log behave exactly as in the editor. They
+//! act as the account named with `--user` and need the same roles it would.
+
+use crate::api::{self, LocalUser};
+use crate::config::NtbConfig;
+use crate::db::{self, DbPool};
+use crate::models::{IssueSeverity, ModelValidation, TopologyDocument, TopologyFull, User};
+use crate::server::model_scan::{find_models, validate_file};
+use crate::server::{run_server_fn, ApiError};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Debug, Parser)]
+#[command(name = "ntb", version, about = "Network topology builder")]
+pub struct Cli {
+    /// Configuration file [default: ntb.toml, if present]
+    #[arg(long, env = "NTB_CONFIG", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Database to open, overriding the configuration and DATABASE_URL
+    #[arg(long, global = true)]
+    pub database_url: Option<String>,
+
+    /// Address the web server listens on, overriding the configuration and NTB_BIND
+    #[arg(long, global = true)]
+    pub bind: Option<SocketAddr>,
+
+    /// Account that management commands act as
+    #[arg(long, short, env = "NTB_USER", global = true)]
+    pub user: Option<String>,
+
+    /// What to do (runs the web server when omitted)
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// The configuration file and environment with this command line's flags on top
+    pub fn config(&self) -> Result<NtbConfig, String> {
+        let mut config = NtbConfig::load(self.config.as_deref())?;
+        if let Some(url) = &self.database_url {
+            config.database.url = url.clone();
+        }
+        if let Some(bind) = self.bind {
+            config.server.bind = Some(bind);
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the web server
+    Serve,
+    /// Bring the database schema up to date
+    Migrate,
+    /// Import a topology from an exported JSON file
+    Import {
+        file: PathBuf,
+    },
+    /// Export a topology
+    Export {
+        id: i64,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// List the topologies the user can see
+    List,
+    /// Delete a topology (owners only)
+    Delete {
+        id: i64,
+    },
+    /// Copy a topology with its nodes, connections, groups and tags
+    Clone {
+        id: i64,
+        /// Name of the copy [default: "<name> (copy)"]
+        #[arg(long)]
+        name: Option<String>,
+        /// Also copy the traffic history
+        #[arg(long)]
+        include_traffic: bool,
+    },
+    /// Check model files (all models when none are named); fails if any has errors
+    ValidateModels {
+        files: Vec<PathBuf>,
+        /// Only print the summary
+        #[arg(long)]
+        summary_only: bool,
+        /// Also list materials and informational notes
+        #[arg(long, short)]
+        verbose: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// Topology document, as exported from the editor (can be imported again)
+    Json,
+    /// Graphviz graph of the nodes and connections
+    Dot,
+}
+
+/// Run a management command; `serve` is started by the binary itself
+pub async fn run(config: NtbConfig, username: Option<&str>, command: Command) -> Result<(), String> {
+    // Works on files only, no database needed
+    if let Command::ValidateModels { files, summary_only, verbose } = command {
+        return validate_models(&config, files, summary_only, verbose);
+    }
+
+    let database_url = &config.database.url;
+    let pool = db::connect(database_url, config.database.max_connections)
+        .await
+        .map_err(|e| format!("Failed to open database {}: {}", database_url, e))?;
+    db::migrate(&pool)
+        .await
+        .map_err(|e| format!("Failed to run database migrations: {}", e))?;
+
+    if let Command::Serve | Command::Migrate = command {
+        println!("Database is up to date");
+        return Ok(());
+    }
+
+    let username = username.ok_or("Name the account to act as with --user (or NTB_USER)")?;
+    let user = api::find_user(&pool, username)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No user named '{}'", username))?;
+    let session = LocalSession {
+        pool,
+        config: Arc::new(config),
+        user,
+    };
+
+    match command {
+        Command::Serve | Command::Migrate | Command::ValidateModels { .. } => {}
+        Command::Import { file } => {
+            let json = std::fs::read_to_string(&file)
+                .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+            let report = session.call(|| api::import_topology(json)).await?;
+            for rejection in &report.rejected {
+                eprintln!(
+                    "warning: skipped {} {}: {}",
+                    rejection.entity_type, rejection.source_id, rejection.reason
+                );
+            }
+            for warning in &report.warnings {
+                eprintln!("warning: {} {}: {}", warning.entity_type, warning.source_id, warning.message);
+            }
+            println!(
+                "Imported topology {} '{}': {} nodes, {} connections",
+                report.topology.id, report.topology.name, report.nodes_created, report.connections_created
+            );
+        }
+        Command::Export { id, format, output } => {
+            let full = session.call(|| api::get_topology_full(id)).await?;
+            let contents = match format {
+                ExportFormat::Json => TopologyDocument::new(full, None, None).to_json_pretty()?,
+                ExportFormat::Dot => to_dot(&full),
+            };
+            match output {
+                Some(path) => std::fs::write(&path, contents)
+                    .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?,
+                None => println!("{}", contents),
+            }
+        }
+        Command::List => {
+            for topology in session.call(api::get_topologies).await? {
+                println!("{}\t{}", topology.id, topology.name);
+            }
+        }
+        Command::Delete { id } => {
+            session.call(|| api::delete_topology(id)).await?;
+            println!("Deleted topology {}", id);
+        }
+        Command::Clone { id, name, include_traffic } => {
+            let copy = session
+                .call(|| api::duplicate_topology(id, name, include_traffic))
+                .await?;
+            println!("Created topology {} '{}'", copy.id, copy.name);
+        }
+    }
+    Ok(())
+}
+
+/// `ntb validate-models`: the checks of `ValidateModels` with a readable report
+fn validate_models(config: &NtbConfig, files: Vec<PathBuf>, summary_only: bool, verbose: bool) -> Result<(), String> {
+    let results: Vec<ModelValidation> = if files.is_empty() {
+        let conf = leptos::prelude::get_configuration(None)
+            .map_err(|e| format!("Failed to get Leptos configuration: {}", e))?;
+        config
+            .model_roots(&conf.leptos_options.site_root)
+            .iter()
+            .flat_map(|root| find_models(root).into_iter().map(move |file| validate_file(root, &file)))
+            .collect()
+    } else {
+        files
+            .iter()
+            .map(|file| validate_file(file.parent().unwrap_or(file), file))
+            .collect()
+    };
+
+    if !summary_only {
+        for result in &results {
+            print_model_validation(result, verbose);
+        }
+    }
+
+    let broken: Vec<_> = results.iter().filter(|r| r.has_errors()).collect();
+    let warned = results.iter().filter(|r| r.has_warnings() && !r.has_errors()).count();
+    println!(
+        "{} models: {} healthy, {} with warnings only, {} with errors",
+        results.len(),
+        results.iter().filter(|r| r.is_healthy()).count(),
+        warned,
+        broken.len()
+    );
+    match broken.len() {
+        0 => Ok(()),
+        count => Err(format!(
+            "{} model(s) need fixing: {}",
+            count,
+            broken.iter().map(|r| r.path.as_str()).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+fn print_model_validation(result: &ModelValidation, verbose: bool) {
+    let status = if result.has_errors() {
+        "✗"
+    } else if result.has_warnings() {
+        "⚠"
+    } else {
+        "✓"
+    };
+    println!(
+        "{} {} ({:.2} MB, {:.3} units)",
+        status,
+        result.path,
+        result.file_size_bytes as f64 / (1024.0 * 1024.0),
+        result.max_dimension
+    );
+    if verbose {
+        for material in &result.materials {
+            let color = match material.base_color {
+                Some([r, g, b, a]) => format!("RGBA({:.2}, {:.2}, {:.2}, {:.2})", r, g, b, a),
+                None if material.has_texture => "texture".to_string(),
+                None => "none".to_string(),
+            };
+            println!(
+                "    material [{}] {}: {} | metallic {:.2} | roughness {:.2}",
+                material.index, material.name, color, material.metallic, material.roughness
+            );
+        }
+    }
+    for issue in &result.issues {
+        if issue.severity == IssueSeverity::Info && !verbose {
+            continue;
+        }
+        println!("    {} [{}] {}", issue.severity, issue.category, issue.message);
+        if let Some(fix) = &issue.fix {
+            println!("        fix: {}", fix);
+        }
+    }
+}
+
+/// Server functions run as one user, outside of any web request
+struct LocalSession {
+    pool: DbPool,
+    config: Arc<NtbConfig>,
+    user: User,
+}
+
+impl LocalSession {
+    /// Run a server function with the parts a signed-in request would carry
+    async fn call<T, E, F, Fut>(&self, server_fn: F) -> Result<T, String>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        ApiError: From<E>,
+    {
+        let (mut parts, ()) = axum::http::Request::new(()).into_parts();
+        parts.extensions.insert(self.pool.clone());
+        parts.extensions.insert(self.config.clone());
+        parts.extensions.insert(LocalUser(self.user.clone()));
+        run_server_fn(parts, server_fn).await.map_err(|e| e.message)
+    }
+}
+
+/// Graphviz source for a topology: one vertex per node, one edge per connection
+fn to_dot(full: &TopologyFull) -> String {
+    let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+
+    let mut dot = format!("graph {} {{\n", quote(&full.topology.name));
+    for node in &full.nodes {
+        let label = match node.ip_address.as_deref().filter(|ip| !ip.trim().is_empty()) {
+            Some(ip) => format!("{}\n{}", node.name, ip),
+            None => node.name.clone(),
+        };
+        dot.push_str(&format!(
+            "    n{} [label={}, shape=box, tooltip={}];\n",
+            node.id,
+            quote(&label).replace('\n', "\\n"),
+            quote(node.node_type.label())
+        ));
+    }
+    for connection in &full.connections {
+        dot.push_str(&format!(
+            "    n{} -- n{} [label={}];\n",
+            connection.source_node_id,
+            connection.target_node_id,
+            quote(connection.connection_type.label())
+        ));
+    }
+    dot.push_str("}\n");
+    dot
+}
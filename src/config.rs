@@ -0,0 +1,558 @@
+//! Server configuration
+//!
+//! Settings are layered, later layers winning: built-in defaults, the TOML file
+//! (`ntb.toml` in the working directory, or the one named by `--config` / `NTB_CONFIG`),
+//! `NTB_*` environment variables, then command line flags. `ntb.example.toml` documents
+//! every key.
+//!
+//! The server hands the loaded configuration to server functions as an
+//! `Extension<Arc<NtbConfig>>`; code running without one sees the defaults.
+
+use crate::db;
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Read when no `--config` / `NTB_CONFIG` is given, if it exists
+pub const DEFAULT_CONFIG_FILE: &str = "ntb.toml";
+
+/// Traffic levels `generate_mock_traffic` understands
+pub const TRAFFIC_LEVELS: &[&str] = &["low", "medium", "high"];
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NtbConfig {
+    pub database: DatabaseConfig,
+    pub server: ServerConfig,
+    pub models: ModelsConfig,
+    pub environments: EnvironmentsConfig,
+    pub metrics: MetricsConfig,
+    pub auth: AuthConfig,
+    pub mock_traffic: MockTrafficConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// `sqlite:...`, or `postgres://...` with the postgres feature
+    pub url: String,
+    pub max_connections: u32,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: db::DEFAULT_DATABASE_URL.to_string(),
+            max_connections: db::DEFAULT_MAX_CONNECTIONS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Address to listen on [default: `site-addr` of the Leptos configuration]
+    pub bind: Option<SocketAddr>,
+    /// Mark the session cookie `Secure`; set when ntb is served over HTTPS (e.g. behind
+    /// a TLS-terminating proxy), since browsers then only send it over HTTPS
+    pub secure_cookies: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelsConfig {
+    /// Directories laid out like `public/models` (`{node_type}/{vendor}/{model}.glb`),
+    /// searched after the site root's models
+    pub search_paths: Vec<PathBuf>,
+    /// Where models and vendor icons uploaded through the editor or API are stored,
+    /// laid out like `public/models` (icons under `icons/vendors/`); created on first upload
+    pub upload_dir: PathBuf,
+    /// Largest model file accepted for upload
+    pub max_upload_bytes: u64,
+}
+
+impl Default for ModelsConfig {
+    fn default() -> Self {
+        Self {
+            search_paths: Vec::new(),
+            upload_dir: PathBuf::from("user-models"),
+            max_upload_bytes: 20 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnvironmentsConfig {
+    /// Where the HDR environment maps are served from [default: the site root's `environments/`]
+    pub dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// Traffic metrics older than this are deleted by the cleanup
+    pub retention_secs: i64,
+    /// Metrics older than this are averaged into one per connection and interval
+    /// (None = never downsample)
+    pub downsample_after_secs: Option<i64>,
+    pub downsample_interval_secs: i64,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            retention_secs: 3600,
+            downsample_after_secs: None,
+            downsample_interval_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub mode: AuthMode,
+    /// The account every request acts as in single-user mode
+    pub user: Option<String>,
+}
+
+/// Who may use the server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthMode {
+    /// Accounts sign in; anyone may register
+    #[default]
+    Open,
+    /// Accounts sign in; registration is disabled
+    Closed,
+    /// No sign-in: every request acts as `auth.user` (a personal install)
+    Single,
+}
+
+impl AuthMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuthMode::Open => "open",
+            AuthMode::Closed => "closed",
+            AuthMode::Single => "single",
+        }
+    }
+}
+
+impl FromStr for AuthMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "open" => Ok(AuthMode::Open),
+            "closed" => Ok(AuthMode::Closed),
+            "single" => Ok(AuthMode::Single),
+            other => Err(format!("unknown auth mode '{}' (expected open, closed or single)", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MockTrafficConfig {
+    /// Whether the mock traffic generator may be used at all
+    pub enabled: bool,
+    /// Level used when a request doesn't name one
+    pub default_level: String,
+    /// Assumed for connections without a bandwidth
+    pub default_bandwidth_mbps: i64,
+    /// Assumed for connections without a latency
+    pub default_latency_ms: f64,
+}
+
+impl Default for MockTrafficConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_level: "medium".to_string(),
+            default_bandwidth_mbps: 1000,
+            default_latency_ms: 10.0,
+        }
+    }
+}
+
+impl NtbConfig {
+    /// Defaults overlaid with the config file and `NTB_*` environment variables
+    ///
+    /// `path` is the file named on the command line; without one `ntb.toml` is read if
+    /// present. Call `validate` once flags have been applied too.
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os("NTB_CONFIG").map(PathBuf::from));
+
+        let mut config = match path {
+            Some(path) => Self::from_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?,
+            None => Self::default(),
+        };
+        config.apply_env()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+        toml::from_str(&contents).map_err(|e| format!("Invalid config file {}: {}", path.display(), e))
+    }
+
+    /// Override settings from environment variables
+    fn apply_env(&mut self) -> Result<(), String> {
+        fn parsed<T: FromStr>(name: &str) -> Result<Option<T>, String>
+        where
+            T::Err: std::fmt::Display,
+        {
+            match std::env::var(name) {
+                Ok(value) => value
+                    .trim()
+                    .parse()
+                    .map(Some)
+                    .map_err(|e| format!("Invalid {} '{}': {}", name, value, e)),
+                Err(_) => Ok(None),
+            }
+        }
+
+        if let Some(url) = parsed::<String>("DATABASE_URL")? {
+            self.database.url = url;
+        }
+        if let Some(max_connections) = parsed("NTB_DB_MAX_CONNECTIONS")? {
+            self.database.max_connections = max_connections;
+        }
+        if let Some(bind) = parsed("NTB_BIND")? {
+            self.server.bind = Some(bind);
+        }
+        if let Some(secure) = parsed("NTB_SECURE_COOKIES")? {
+            self.server.secure_cookies = secure;
+        }
+        if let Some(paths) = std::env::var_os("NTB_MODEL_PATHS") {
+            self.models.search_paths = std::env::split_paths(&paths).collect();
+        }
+        if let Some(dir) = std::env::var_os("NTB_UPLOAD_DIR") {
+            self.models.upload_dir = PathBuf::from(dir);
+        }
+        if let Some(max_bytes) = parsed("NTB_MAX_UPLOAD_BYTES")? {
+            self.models.max_upload_bytes = max_bytes;
+        }
+        if let Some(dir) = std::env::var_os("NTB_ENVIRONMENT_DIR") {
+            self.environments.dir = Some(PathBuf::from(dir));
+        }
+        if let Some(retention) = parsed("NTB_METRICS_RETENTION_SECS")? {
+            self.metrics.retention_secs = retention;
+        }
+        if let Some(after) = parsed("NTB_METRICS_DOWNSAMPLE_AFTER_SECS")? {
+            self.metrics.downsample_after_secs = Some(after);
+        }
+        if let Some(interval) = parsed("NTB_METRICS_DOWNSAMPLE_INTERVAL_SECS")? {
+            self.metrics.downsample_interval_secs = interval;
+        }
+        if let Some(mode) = parsed("NTB_AUTH_MODE")? {
+            self.auth.mode = mode;
+        }
+        if let Some(user) = parsed::<String>("NTB_AUTH_USER")? {
+            self.auth.user = Some(user);
+        }
+        if let Some(enabled) = parsed("NTB_MOCK_TRAFFIC")? {
+            self.mock_traffic.enabled = enabled;
+        }
+        if let Some(level) = parsed::<String>("NTB_MOCK_TRAFFIC_LEVEL")? {
+            self.mock_traffic.default_level = level;
+        }
+        Ok(())
+    }
+
+    /// Check the settings, listing every problem at once
+    pub fn validate(&self) -> Result<(), String> {
+        let mut problems = Vec::new();
+
+        let url = self.database.url.trim();
+        let postgres_url = url.starts_with("postgres://") || url.starts_with("postgresql://");
+        if url.is_empty() {
+            problems.push("database.url is empty".to_string());
+        } else if cfg!(feature = "postgres") && !postgres_url {
+            problems.push(format!("database.url '{}' is not a postgres:// URL (this build uses PostgreSQL)", url));
+        } else if !cfg!(feature = "postgres") && postgres_url {
+            problems.push(format!(
+                "database.url '{}' is a PostgreSQL URL; build with the postgres feature to use it",
+                url
+            ));
+        } else if !cfg!(feature = "postgres") && !url.starts_with("sqlite:") {
+            problems.push(format!("database.url '{}' is not a sqlite: URL", url));
+        }
+        if self.database.max_connections == 0 {
+            problems.push("database.max_connections must be at least 1".to_string());
+        }
+
+        for path in &self.models.search_paths {
+            if !path.is_dir() {
+                problems.push(format!("models.search_paths: {} is not a directory", path.display()));
+            }
+        }
+        if self.models.upload_dir.as_os_str().is_empty() {
+            problems.push("models.upload_dir is empty".to_string());
+        } else if self.models.upload_dir.exists() && !self.models.upload_dir.is_dir() {
+            problems.push(format!("models.upload_dir: {} is not a directory", self.models.upload_dir.display()));
+        }
+        if self.models.max_upload_bytes == 0 {
+            problems.push("models.max_upload_bytes must be positive".to_string());
+        }
+        if let Some(dir) = &self.environments.dir {
+            if !dir.is_dir() {
+                problems.push(format!("environments.dir: {} is not a directory", dir.display()));
+            }
+        }
+
+        let metrics = &self.metrics;
+        if metrics.retention_secs <= 0 {
+            problems.push("metrics.retention_secs must be positive".to_string());
+        }
+        if metrics.downsample_interval_secs <= 0 {
+            problems.push("metrics.downsample_interval_secs must be positive".to_string());
+        }
+        if let Some(after) = metrics.downsample_after_secs {
+            if after < 0 {
+                problems.push("metrics.downsample_after_secs must not be negative".to_string());
+            } else if after >= metrics.retention_secs {
+                problems.push(format!(
+                    "metrics.downsample_after_secs ({}) must be less than metrics.retention_secs ({}), or nothing is ever downsampled",
+                    after, metrics.retention_secs
+                ));
+            }
+        }
+
+        match (self.auth.mode, self.auth.user.as_deref().map(str::trim)) {
+            (AuthMode::Single, None | Some("")) => {
+                problems.push("auth.mode = \"single\" needs auth.user, the account to act as".to_string())
+            }
+            (AuthMode::Open | AuthMode::Closed, Some(_)) => problems.push(format!(
+                "auth.user is only used with auth.mode = \"single\" (mode is \"{}\")",
+                self.auth.mode.as_str()
+            )),
+            _ => {}
+        }
+
+        let mock = &self.mock_traffic;
+        if !TRAFFIC_LEVELS.contains(&mock.default_level.as_str()) {
+            problems.push(format!(
+                "mock_traffic.default_level '{}' is not one of {}",
+                mock.default_level,
+                TRAFFIC_LEVELS.join(", ")
+            ));
+        }
+        if mock.default_bandwidth_mbps <= 0 {
+            problems.push("mock_traffic.default_bandwidth_mbps must be positive".to_string());
+        }
+        if mock.default_latency_ms.is_nan() || mock.default_latency_ms < 0.0 {
+            problems.push("mock_traffic.default_latency_ms must not be negative".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Invalid configuration:\n  - {}", problems.join("\n  - ")))
+        }
+    }
+
+    /// Directories holding models, in search order: the site root's, uploaded models,
+    /// then the extra paths
+    pub fn model_roots(&self, site_root: &str) -> Vec<PathBuf> {
+        [Path::new(site_root).join("models"), self.models.upload_dir.clone()]
+            .into_iter()
+            .chain(self.models.search_paths.iter().cloned())
+            .collect()
+    }
+
+    /// Directories holding vendor icons (`{vendor}.svg`): the site root's, then uploaded ones
+    pub fn icon_dirs(&self, site_root: &str) -> Vec<PathBuf> {
+        vec![
+            Path::new(site_root).join("icons/vendors"),
+            self.uploaded_icon_dir(),
+        ]
+    }
+
+    /// Where uploaded vendor icons are stored
+    pub fn uploaded_icon_dir(&self) -> PathBuf {
+        self.models.upload_dir.join("icons/vendors")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::Parser;
+    use std::sync::Mutex;
+
+    /// Environment variables are process-wide; tests that set them take turns
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn parse(toml: &str) -> Result<NtbConfig, toml::de::Error> {
+        toml::from_str(toml)
+    }
+
+    /// A config file unique to the test, removed when dropped
+    struct TempConfig(PathBuf);
+
+    impl TempConfig {
+        fn new(name: &str, contents: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("ntb-{}-{}.toml", name, std::process::id()));
+            std::fs::write(&path, contents).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempConfig {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn empty_file_is_the_defaults() {
+        let config = parse("").unwrap();
+        assert_eq!(config.database.url, db::DEFAULT_DATABASE_URL);
+        assert_eq!(config.models.upload_dir, PathBuf::from("user-models"));
+        assert_eq!(config.auth.mode, AuthMode::Open);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn partial_sections_keep_the_other_defaults() {
+        let config = parse(
+            r#"
+            [database]
+            max_connections = 3
+
+            [models]
+            search_paths = ["/srv/models", "vendor-models"]
+
+            [auth]
+            mode = "single"
+            user = "admin"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.database.max_connections, 3);
+        assert_eq!(config.database.url, db::DEFAULT_DATABASE_URL);
+        assert_eq!(
+            config.models.search_paths,
+            vec![PathBuf::from("/srv/models"), PathBuf::from("vendor-models")]
+        );
+        assert_eq!(config.models.max_upload_bytes, ModelsConfig::default().max_upload_bytes);
+        assert_eq!(config.auth.mode, AuthMode::Single);
+        assert_eq!(config.auth.user.as_deref(), Some("admin"));
+        assert_eq!(config.metrics.retention_secs, 3600);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(parse("[database]\nurll = \"sqlite:x.db\"").is_err());
+        assert!(parse("[databse]\nurl = \"sqlite:x.db\"").is_err());
+        assert!(parse("[auth]\nmode = \"everyone\"").is_err());
+    }
+
+    #[test]
+    fn validate_lists_every_problem() {
+        let config = parse(
+            r#"
+            [database]
+            max_connections = 0
+
+            [auth]
+            mode = "single"
+
+            [mock_traffic]
+            default_level = "extreme"
+            "#,
+        )
+        .unwrap();
+        let error = config.validate().unwrap_err();
+        assert!(error.contains("database.max_connections must be at least 1"), "{}", error);
+        assert!(error.contains("auth.mode = \"single\" needs auth.user"), "{}", error);
+        assert!(error.contains("mock_traffic.default_level 'extreme'"), "{}", error);
+        assert_eq!(error.lines().count(), 4);
+    }
+
+    #[test]
+    fn auth_mode_parses_case_insensitively() {
+        assert_eq!(" Closed ".parse::<AuthMode>(), Ok(AuthMode::Closed));
+        assert!("anyone".parse::<AuthMode>().is_err());
+    }
+
+    #[test]
+    fn environment_overrides_file_and_flags_override_both() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        // validate() only accepts URLs for the backend this build was compiled for
+        let url = |name: &str| {
+            if cfg!(feature = "postgres") {
+                format!("postgres://localhost/{}", name)
+            } else {
+                format!("sqlite:{}.db", name)
+            }
+        };
+        let file = TempConfig::new(
+            "precedence",
+            &format!(
+                r#"
+            [database]
+            url = "{}"
+            max_connections = 3
+
+            [server]
+            bind = "127.0.0.1:3001"
+            "#,
+                url("file")
+            ),
+        );
+        let config_arg = file.0.to_str().unwrap();
+        let cli = |flags: &[&str]| {
+            let args = ["ntb", "--config", config_arg].into_iter().chain(flags.iter().copied());
+            Cli::parse_from(args).config().unwrap()
+        };
+
+        std::env::remove_var("DATABASE_URL");
+        std::env::remove_var("NTB_BIND");
+        let config = cli(&[]);
+        assert_eq!(config.database.url, url("file"));
+        assert_eq!(config.server.bind, Some("127.0.0.1:3001".parse().unwrap()));
+
+        std::env::set_var("DATABASE_URL", url("env"));
+        std::env::set_var("NTB_BIND", "127.0.0.1:3002");
+        let config = cli(&[]);
+        assert_eq!(config.database.url, url("env"));
+        assert_eq!(config.server.bind, Some("127.0.0.1:3002".parse().unwrap()));
+        assert_eq!(config.database.max_connections, 3);
+
+        let flag_url = url("flag");
+        let config = cli(&["--database-url", &flag_url, "--bind", "127.0.0.1:3003"]);
+        assert_eq!(config.database.url, flag_url);
+        assert_eq!(config.server.bind, Some("127.0.0.1:3003".parse().unwrap()));
+
+        std::env::remove_var("DATABASE_URL");
+        std::env::remove_var("NTB_BIND");
+    }
+
+    #[test]
+    fn invalid_environment_values_are_reported() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let file = TempConfig::new("invalid-env", "");
+
+        std::env::set_var("NTB_DB_MAX_CONNECTIONS", "many");
+        let error = NtbConfig::load(Some(&file.0)).unwrap_err();
+        std::env::remove_var("NTB_DB_MAX_CONNECTIONS");
+        assert!(error.starts_with("Invalid NTB_DB_MAX_CONNECTIONS 'many'"), "{}", error);
+    }
+
+    #[test]
+    fn invalid_file_names_the_path() {
+        let file = TempConfig::new("invalid-file", "[server]\nbind = \"nowhere\"");
+        let error = NtbConfig::from_file(&file.0).unwrap_err();
+        assert!(error.starts_with(&format!("Invalid config file {}", file.0.display())), "{}", error);
+    }
+}
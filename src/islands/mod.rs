@@ -1,5 +1,7 @@
 pub mod topology_viewport;
 pub mod topology_editor;
+pub mod sign_in;
 
 pub use topology_viewport::TopologyViewport;
 pub use topology_editor::TopologyEditor;
+pub use sign_in::SignIn;
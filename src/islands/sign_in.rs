@@ -0,0 +1,120 @@
+use crate::api::{login, register_user};
+use crate::models::{Credentials, MIN_PASSWORD_LENGTH};
+use leptos::prelude::*;
+
+/// Sign-in / create-account form shown until the browser has a session
+#[component]
+pub fn SignIn() -> impl IntoView {
+    let username = RwSignal::new(String::new());
+    let password = RwSignal::new(String::new());
+    let creating_account = RwSignal::new(false);
+    let error = RwSignal::new(None::<String>);
+
+    let submit_action = Action::new(move |(credentials, register): &(Credentials, bool)| {
+        let credentials = credentials.clone();
+        let register = *register;
+        async move {
+            if register {
+                register_user(credentials).await
+            } else {
+                login(credentials).await
+            }
+        }
+    });
+
+    // The session cookie is set by now, so reload into the editor
+    Effect::new(move || match submit_action.value().get() {
+        Some(Ok(_)) => {
+            #[cfg(feature = "hydrate")]
+            if let Some(window) = web_sys::window() {
+                let _ = window.location().reload();
+            }
+        }
+        Some(Err(e)) => error.set(Some(e.to_string())),
+        None => {}
+    });
+
+    let submit = move || {
+        error.set(None);
+        let credentials = Credentials {
+            username: username.get_untracked().trim().to_string(),
+            password: password.get_untracked(),
+        };
+        submit_action.dispatch((credentials, creating_account.get_untracked()));
+    };
+
+    view! {
+        <div class="w-full h-screen flex items-center justify-center bg-gray-900 text-white">
+            <form
+                class="w-80 p-6 bg-gray-800 border border-gray-700 rounded-lg shadow-lg space-y-4"
+                on:submit=move |ev| {
+                    ev.prevent_default();
+                    submit();
+                }
+            >
+                <div class="flex items-center gap-2">
+                    <img src="/ntb_logo.svg" class="w-8 h-8" alt="" />
+                    <h1 class="text-lg font-semibold">"Network Topology Builder"</h1>
+                </div>
+
+                <div>
+                    <label class="block text-xs font-medium text-gray-400 mb-1">"Username"</label>
+                    <input
+                        type="text"
+                        autocomplete="username"
+                        class="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
+                        prop:value=move || username.get()
+                        on:input=move |ev| username.set(event_target_value(&ev))
+                    />
+                </div>
+
+                <div>
+                    <label class="block text-xs font-medium text-gray-400 mb-1">"Password"</label>
+                    <input
+                        type="password"
+                        autocomplete=move || if creating_account.get() { "new-password" } else { "current-password" }
+                        class="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
+                        prop:value=move || password.get()
+                        on:input=move |ev| password.set(event_target_value(&ev))
+                    />
+                    {move || creating_account.get().then(|| view! {
+                        <p class="mt-1 text-xs text-gray-500">
+                            {format!("At least {} characters", MIN_PASSWORD_LENGTH)}
+                        </p>
+                    })}
+                </div>
+
+                {move || error.get().map(|message| view! {
+                    <div class="text-xs text-red-400">"✗ " {message}</div>
+                })}
+
+                <button
+                    type="submit"
+                    class="w-full px-4 py-2 bg-blue-600 hover:bg-blue-700 rounded text-sm font-medium transition disabled:opacity-50"
+                    disabled=move || {
+                        username.get().trim().is_empty()
+                            || password.get().is_empty()
+                            || submit_action.pending().get()
+                    }
+                >
+                    {move || if creating_account.get() { "Create Account" } else { "Sign In" }}
+                </button>
+
+                <button
+                    type="button"
+                    class="w-full text-xs text-gray-400 hover:text-gray-200"
+                    on:click=move |_| {
+                        error.set(None);
+                        creating_account.update(|v| *v = !*v);
+                    }
+                >
+                    {move || if creating_account.get() {
+                        "Already have an account? Sign in"
+                    } else {
+                        "No account yet? Create one"
+                    }}
+                </button>
+            </form>
+        </div>
+    }
+}
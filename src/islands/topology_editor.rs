@@ -1,22 +1,35 @@
 use crate::api::{
-    create_connection as create_connection_fn, create_node, delete_connection, delete_node,
-    delete_topology, get_connection, get_node, get_topologies, get_topology_full, get_ui_settings,
-    get_undo_history, get_vendors_for_type, swap_connection_direction, undo_last_change,
-    update_connection, update_node, update_topology, update_ui_settings,
+    apply_filter_visibility, compare_topologies, create_api_token,
+    create_connection as create_connection_fn, create_interface, create_node, create_node_group,
+    create_subnet, create_topology_revision, delete_api_token, delete_connection,
+    delete_interface, delete_node, delete_node_group, delete_subnet, delete_tag, delete_topology,
+    delete_uploaded_model,
+    delete_topology_revision, duplicate_topology, export_audit_log, filter_topology,
+    get_api_tokens, get_audit_log, get_connection, get_connection_tags, get_current_user,
+    get_ipam_report, get_node, get_node_groups, get_node_interfaces, get_node_tags,
+    get_subnets, get_tags, get_topologies,
+    get_topology_full, get_topology_permissions, get_topology_revision, get_topology_revisions,
+    get_ui_settings, get_undo_status, get_vendors_for_type, import_topology, logout,
+    move_node_group, next_free_ip, redo_last_change, restore_topology_revision,
+    search_topologies, set_connection_interfaces, set_connection_tags, set_node_tags,
+    set_topology_permission, swap_connection_direction, undo_last_change, update_connection,
+    update_interface, update_node, update_node_group, update_topology, update_ui_settings,
 };
 use crate::islands::TopologyViewport;
 use crate::models::{
-    CreateConnection, CreateNode, UpdateConnection, UpdateNode, UpdateTopology, UpdateUISettings,
+    group_subtree, group_tree, merge_edits, parse_tag_list, AdminState, AppError, AuditAction, AuditEntity,
+    AuditExportFormat, AuditFilter, CameraView, ChangeKind, ConnectionStatus,
+    Connection, ConnectionType, CreateConnection, CreateInterface, CreateNode,
+    CreateNodeGroup, CreateRevision, CreateSubnet, DiffSource, FilterMatches, FlowDirection,
+    GroupType, LiveEvent, LiveSelection, LiveViewer, Node, NodeType, RevisionPreview, Rgb, Role, SearchHit, SearchKind, TopologyDiff,
+    UpdateConnection, UpdateError, UpdateInterface, UpdateNode, UpdateNodeGroup, UpdateTopology,
+    UpdateUISettings, ViewSettings,
 };
 use leptos::prelude::*;
 use leptos::task::spawn_local;
 
 // Import these only when hydrating
 #[cfg(feature = "hydrate")]
-use crate::api::create_topology;
-#[cfg(feature = "hydrate")]
-use crate::models::CreateTopology;
-#[cfg(feature = "hydrate")]
 use web_sys;
 
 /// Grid and axes visibility settings
@@ -43,6 +56,36 @@ pub struct LightingSettings {
     pub rim_light_intensity: RwSignal<f32>,
 }
 
+/// Topology list refresh trigger (wrapped in struct to avoid context collision with refetch_trigger)
+#[derive(Clone, Copy)]
+pub struct TopologyListTrigger(pub RwSignal<u32>);
+
+/// Active topology comparison; the viewport tints items by change kind while set
+#[derive(Clone, Copy)]
+pub struct CompareMode {
+    pub diff: RwSignal<Option<TopologyDiff>>,
+}
+
+/// Result of the filter bar's "Highlight"; the viewport outlines matching items while set
+#[derive(Clone, Copy)]
+pub struct FilterHighlight {
+    pub matches: RwSignal<Option<FilterMatches>>,
+}
+
+/// Live collaboration state, fed by the current topology's WebSocket
+#[derive(Clone, Copy)]
+pub struct LiveSession {
+    /// Everyone viewing the current topology, this browser included
+    pub viewers: RwSignal<Vec<LiveViewer>>,
+    /// This browser's id among `viewers`
+    pub viewer_id: RwSignal<Option<u64>>,
+    /// Node and connection changes made by others, drained by the viewport
+    pub remote_changes: RwSignal<Vec<LiveEvent>>,
+    /// Set when someone else deletes the current topology or takes away our access to
+    /// it; the toolbar switches away
+    pub topology_deleted: RwSignal<bool>,
+}
+
 /// Camera preset for quick navigation
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum CameraPreset {
@@ -58,19 +101,62 @@ pub enum CameraPreset {
 #[derive(Clone, Copy)]
 pub struct CameraControls {
     pub preset_trigger: RwSignal<Option<CameraPreset>>,
+    /// Latest camera position, published by the viewport (used for export)
+    pub current_view: RwSignal<Option<CameraView>>,
+    /// Camera position to restore once the viewport finishes loading (set by import)
+    pub pending_view: RwSignal<Option<CameraView>>,
+    /// Node or connection to fly to once the viewport shows it (set by search)
+    pub focus_item: RwSignal<Option<SelectedItem>>,
+}
+
+impl ViewportVisibility {
+    /// Snapshot the viewport and lighting settings for a topology document
+    pub fn to_view_settings(&self, lighting: &LightingSettings) -> ViewSettings {
+        ViewSettings {
+            show_grid: self.show_grid.get_untracked(),
+            show_x_axis: self.show_x_axis.get_untracked(),
+            show_y_axis: self.show_y_axis.get_untracked(),
+            show_z_axis: self.show_z_axis.get_untracked(),
+            background_color: self.background_color.get_untracked(),
+            use_environment_lighting: self.use_environment_lighting.get_untracked(),
+            environment_map: self.environment_map.get_untracked(),
+            ambient_intensity: lighting.ambient_intensity.get_untracked() as f64,
+            key_light_intensity: lighting.key_light_intensity.get_untracked() as f64,
+            fill_light_intensity: lighting.fill_light_intensity.get_untracked() as f64,
+            rim_light_intensity: lighting.rim_light_intensity.get_untracked() as f64,
+        }
+    }
+
+    /// Apply settings from an imported topology document (auto-saved like manual changes)
+    pub fn apply_view_settings(&self, lighting: &LightingSettings, settings: &ViewSettings) {
+        self.show_grid.set(settings.show_grid);
+        self.show_x_axis.set(settings.show_x_axis);
+        self.show_y_axis.set(settings.show_y_axis);
+        self.show_z_axis.set(settings.show_z_axis);
+        self.background_color.set(settings.background_color);
+        self.use_environment_lighting.set(settings.use_environment_lighting);
+        self.environment_map.set(settings.environment_map.clone());
+        lighting.ambient_intensity.set(settings.ambient_intensity as f32);
+        lighting.key_light_intensity.set(settings.key_light_intensity as f32);
+        lighting.fill_light_intensity.set(settings.fill_light_intensity as f32);
+        lighting.rim_light_intensity.set(settings.rim_light_intensity as f32);
+    }
 }
 
 /// Individual vendor section component - displays one vendor and its models
 #[component]
 fn VendorSection(
     vendor: crate::models::VendorInfo,
-    node_type: String,
+    node_type: NodeType,
     name_prefix: String,
     create_node_action: Action<
-        (String, String, String, String),
-        Result<crate::models::Node, leptos::prelude::ServerFnError>,
+        (NodeType, String, String, String),
+        Result<crate::models::Node, crate::models::AppError>,
     >,
     dropdown_open: RwSignal<bool>,
+    /// Bumped when an uploaded model is deleted, to reload the vendor list
+    models_changed: RwSignal<u32>,
+    model_status: RwSignal<Option<Result<String, String>>>,
 ) -> impl IntoView {
     // Clone values for use in closures
     let vendor_name = vendor.name.clone();
@@ -79,6 +165,25 @@ fn VendorSection(
     let has_icon = vendor.has_icon;
     let models = vendor.models.clone();
 
+    // Deleting is refused by the server while nodes still use the model
+    let delete_model_action = Action::new(move |(vendor, model): &(String, String)| {
+        let vendor = vendor.clone();
+        let model = model.clone();
+        async move {
+            delete_uploaded_model(node_type, vendor.clone(), model.clone())
+                .await
+                .map(|()| format!("Deleted {}/{}", vendor, model))
+        }
+    });
+    Effect::new(move |_| {
+        if let Some(result) = delete_model_action.value().get() {
+            if result.is_ok() {
+                models_changed.update(|v| *v += 1);
+            }
+            model_status.set(Some(result.map_err(|e| e.to_string())));
+        }
+    });
+
     view! {
         <div class="border-b border-gray-700 last:border-b-0">
             // Vendor header
@@ -114,29 +219,57 @@ fn VendorSection(
                     let vendor_name_clone = vendor_name.clone();
                     let model_file = model.file_name.clone();
                     let model_display = model.display_name.clone();
-                    let node_type_clone = node_type.clone();
                     let name_prefix_clone = name_prefix.clone();
+                    // Models that failed validation would only fail to load in the viewport
+                    let problem = model.problem.clone();
+                    let usable = is_available && problem.is_none();
+                    let uploaded = model.uploaded;
+                    let delete_target = (vendor_name.clone(), model.file_name.clone());
+                    // First click arms the delete, second one confirms
+                    let confirm_delete = RwSignal::new(false);
 
                     view! {
-                        <button
-                            class="w-full px-3 py-1.5 text-left hover:bg-gray-700 transition flex items-center gap-2"
-                            class:opacity-50=!is_available
-                            class:cursor-not-allowed=!is_available
-                            disabled=!is_available
-                            on:click=move |_| {
-                                if is_available {
-                                    create_node_action.dispatch((
-                                        node_type_clone.clone(),
-                                        name_prefix_clone.clone(),
-                                        vendor_name_clone.clone(),
-                                        model_file.clone(),
-                                    ));
-                                    dropdown_open.set(false);
+                        <div class="flex items-center">
+                            <button
+                                class="flex-1 px-3 py-1.5 text-left hover:bg-gray-700 transition flex items-center gap-2"
+                                class:opacity-50=!usable
+                                class:cursor-not-allowed=!usable
+                                disabled=!usable
+                                title=problem.clone().map(|problem| format!("Broken model: {}", problem))
+                                on:click=move |_| {
+                                    if usable {
+                                        create_node_action.dispatch((
+                                            node_type,
+                                            name_prefix_clone.clone(),
+                                            vendor_name_clone.clone(),
+                                            model_file.clone(),
+                                        ));
+                                        dropdown_open.set(false);
+                                    }
                                 }
-                            }
-                        >
-                            <span class="text-xs text-gray-300">{model_display}</span>
-                        </button>
+                            >
+                                <span class="text-xs text-gray-300">{model_display}</span>
+                                {problem.is_some().then(|| view! { <span class="text-[10px] text-red-400">"⚠ broken"</span> })}
+                                {uploaded.then(|| view! { <span class="text-[10px] text-gray-500">"uploaded"</span> })}
+                            </button>
+                            {uploaded.then(|| view! {
+                                <button
+                                    class="px-2 py-1.5 text-xs text-gray-500 hover:text-red-400 transition"
+                                    title="Delete this uploaded model"
+                                    on:click=move |e| {
+                                        e.stop_propagation();
+                                        if confirm_delete.get() {
+                                            confirm_delete.set(false);
+                                            delete_model_action.dispatch(delete_target.clone());
+                                        } else {
+                                            confirm_delete.set(true);
+                                        }
+                                    }
+                                >
+                                    {move || if confirm_delete.get() { "Delete?" } else { "×" }}
+                                </button>
+                            })}
+                        </div>
                     }
                 }).collect_view()}
             </div>
@@ -147,21 +280,22 @@ fn VendorSection(
 /// Vendor and model selection dropdown component
 #[component]
 fn VendorDropdown(
-    node_type: String,
+    node_type: NodeType,
     name_prefix: String,
     create_node_action: Action<
-        (String, String, String, String),
-        Result<crate::models::Node, leptos::prelude::ServerFnError>,
+        (NodeType, String, String, String),
+        Result<crate::models::Node, crate::models::AppError>,
     >,
     dropdown_open: RwSignal<bool>,
 ) -> impl IntoView {
-    // Clone for use in Resource
-    let node_type_for_resource = node_type.clone();
+    // Bumped after an upload or delete so the list shows the change
+    let models_changed = RwSignal::new(0u32);
+    let model_status = RwSignal::new(None::<Result<String, String>>);
 
     // Fetch vendors for this node type
     let vendors_resource = Resource::new(
-        move || node_type_for_resource.clone(),
-        |node_type| async move { get_vendors_for_type(node_type).await },
+        move || (node_type, models_changed.get()),
+        |(node_type, _)| async move { get_vendors_for_type(node_type).await },
     );
 
     view! {
@@ -184,10 +318,12 @@ fn VendorDropdown(
                                             view! {
                                                 <VendorSection
                                                     vendor=vendor
-                                                    node_type=node_type.clone()
+                                                    node_type=node_type
                                                     name_prefix=name_prefix.clone()
                                                     create_node_action=create_node_action
                                                     dropdown_open=dropdown_open
+                                                    models_changed=models_changed
+                                                    model_status=model_status
                                                 />
                                             }
                                         }).collect_view()}
@@ -198,10 +334,146 @@ fn VendorDropdown(
                     })
                 }}
             </Suspense>
+            <ModelUploadForm node_type=node_type models_changed=models_changed model_status=model_status />
+        </div>
+    }
+}
+
+/// Upload form at the bottom of the vendor dropdown (posts to `/api/v1/models`)
+#[component]
+fn ModelUploadForm(
+    node_type: NodeType,
+    models_changed: RwSignal<u32>,
+    model_status: RwSignal<Option<Result<String, String>>>,
+) -> impl IntoView {
+    let show_form = RwSignal::new(false);
+    let uploading = RwSignal::new(false);
+    let form_ref = NodeRef::<leptos::html::Form>::new();
+    // Only the browser uploads; the server render just draws the form
+    #[cfg(not(feature = "hydrate"))]
+    let _ = models_changed;
+
+    let input_class = "w-full px-2 py-1 bg-gray-700 border border-gray-600 rounded text-xs";
+    let file_class = "w-full text-xs text-gray-400 file:mr-2 file:py-1 file:px-2 file:rounded file:border-0 file:text-xs file:bg-gray-700 file:text-gray-300 hover:file:bg-gray-600 file:cursor-pointer";
+
+    view! {
+        <div class="border-t border-gray-700 p-2 space-y-2" on:click=move |e| e.stop_propagation()>
+            {move || if show_form.get() {
+                view! {
+                    <form
+                        node_ref=form_ref
+                        class="space-y-1.5"
+                        on:submit=move |ev| {
+                            ev.prevent_default();
+                            #[cfg(feature = "hydrate")]
+                            {
+                                let Some(form) = form_ref.get() else { return };
+                                let Ok(data) = web_sys::FormData::new_with_form(&form) else { return };
+                                uploading.set(true);
+                                model_status.set(None);
+                                spawn_local(async move {
+                                    let result = post_model_upload(data).await.map(|report| {
+                                        let mut message = format!(
+                                            "{} {}/{}",
+                                            if report.replaced { "Replaced" } else { "Uploaded" },
+                                            report.vendor,
+                                            report.model.file_name
+                                        );
+                                        if let Some(issue) = report.issues.first() {
+                                            message.push_str(&format!(" - note: {}", issue.message));
+                                        }
+                                        message
+                                    });
+                                    if result.is_ok() {
+                                        models_changed.update(|v| *v += 1);
+                                        show_form.set(false);
+                                    }
+                                    model_status.set(Some(result));
+                                    uploading.set(false);
+                                });
+                            }
+                        }
+                    >
+                        <input type="hidden" name="node_type" value=node_type.as_str() />
+                        <input type="text" name="vendor" placeholder="vendor (e.g. cisco)" required class=input_class />
+                        <input type="text" name="name" placeholder="model name (e.g. asr-9000)" required class=input_class />
+                        <label class="block text-[10px] text-gray-400">"Model (.glb)"</label>
+                        <input type="file" name="model" accept=".glb,model/gltf-binary" required class=file_class />
+                        <label class="block text-[10px] text-gray-400">"Vendor icon (.svg, optional)"</label>
+                        <input type="file" name="icon" accept=".svg,image/svg+xml" class=file_class />
+                        <label class="flex items-center gap-1.5 text-xs text-gray-400">
+                            <input type="checkbox" name="replace" value="true" />
+                            "Replace an earlier upload"
+                        </label>
+                        <div class="flex gap-2">
+                            <button
+                                type="submit"
+                                class="flex-1 px-2 py-1 bg-blue-600 hover:bg-blue-700 rounded text-xs transition"
+                                disabled=move || uploading.get()
+                            >
+                                {move || if uploading.get() { "Uploading..." } else { "Upload" }}
+                            </button>
+                            <button
+                                type="button"
+                                class="px-2 py-1 bg-gray-700 hover:bg-gray-600 rounded text-xs transition"
+                                on:click=move |_| show_form.set(false)
+                            >
+                                "Cancel"
+                            </button>
+                        </div>
+                    </form>
+                }.into_any()
+            } else {
+                view! {
+                    <button
+                        class="w-full px-2 py-1 text-left text-xs text-blue-400 hover:text-blue-300 transition"
+                        on:click=move |_| {
+                            model_status.set(None);
+                            show_form.set(true);
+                        }
+                    >
+                        "+ Upload model..."
+                    </button>
+                }.into_any()
+            }}
+            {move || model_status.get().map(|status| match status {
+                Ok(message) => view! { <div class="text-xs text-green-400">"✓ " {message}</div> }.into_any(),
+                Err(message) => view! { <div class="text-xs text-red-400">"✗ " {message}</div> }.into_any(),
+            })}
         </div>
     }
 }
 
+/// POST a model upload form; the error is the server's message
+#[cfg(feature = "hydrate")]
+async fn post_model_upload(data: web_sys::FormData) -> Result<crate::models::ModelUploadReport, String> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+
+    let init = web_sys::RequestInit::new();
+    init.set_method("POST");
+    init.set_body(&data);
+    let window = web_sys::window().ok_or("no window")?;
+    let response: web_sys::Response = JsFuture::from(window.fetch_with_str_and_init("/api/v1/models", &init))
+        .await
+        .map_err(|e| format!("Upload failed: {:?}", e))?
+        .dyn_into()
+        .map_err(|_| "Upload failed: unexpected response".to_string())?;
+    let body = match response.text() {
+        Ok(text) => JsFuture::from(text).await.ok().and_then(|text| text.as_string()).unwrap_or_default(),
+        Err(_) => String::new(),
+    };
+
+    if response.ok() {
+        serde_json::from_str(&body).map_err(|e| format!("Unexpected response: {}", e))
+    } else {
+        Err(serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|body| body["error"].as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("Upload failed (HTTP {})", response.status())))
+    }
+}
+
 /// Professional topology editor layout with panels
 /// Using regular component (not island) so we can share state via context
 #[component]
@@ -240,6 +512,24 @@ pub fn TopologyEditor(
     // Camera controls (wrapped in struct to avoid context collision)
     let camera_controls = CameraControls {
         preset_trigger: RwSignal::new(None),
+        current_view: RwSignal::new(None),
+        pending_view: RwSignal::new(None),
+        focus_item: RwSignal::new(None),
+    };
+
+    let compare_mode = CompareMode {
+        diff: RwSignal::new(None),
+    };
+
+    let filter_highlight = FilterHighlight {
+        matches: RwSignal::new(None),
+    };
+
+    let live_session = LiveSession {
+        viewers: RwSignal::new(Vec::new()),
+        viewer_id: RwSignal::new(None),
+        remote_changes: RwSignal::new(Vec::new()),
+        topology_deleted: RwSignal::new(false),
     };
 
     // Panel visibility controls - single fullscreen toggle
@@ -249,6 +539,9 @@ pub fn TopologyEditor(
     Effect::new(move || {
         let topology_id = current_topology_id.get();
 
+        // A highlight refers to the previous topology's items
+        filter_highlight.matches.set(None);
+
         // Save to database
         spawn_local(async move {
             use crate::api::set_last_topology_id;
@@ -263,6 +556,48 @@ pub fn TopologyEditor(
         }
     });
 
+    // Live collaboration: one socket per open topology, reopened if the server drops it
+    #[cfg(feature = "hydrate")]
+    {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let socket: Rc<RefCell<Option<web_sys::WebSocket>>> = Rc::new(RefCell::new(None));
+        let reconnect_trigger = RwSignal::new(0u32);
+
+        let socket_for_connect = socket.clone();
+        Effect::new(move || {
+            let topology_id = current_topology_id.get();
+            let _ = reconnect_trigger.get();
+
+            if let Some(previous) = socket_for_connect.borrow_mut().take() {
+                previous.set_onclose(None);
+                let _ = previous.close();
+            }
+            live_session.viewers.set(Vec::new());
+            live_session.viewer_id.set(None);
+            crate::api::set_live_viewer(None);
+            live_session.remote_changes.set(Vec::new());
+
+            if topology_id > 0 {
+                *socket_for_connect.borrow_mut() =
+                    open_live_socket(topology_id, live_session, selected_item, refetch_trigger, reconnect_trigger);
+            }
+        });
+
+        // Share the selection with the other viewers (again after every reconnect)
+        Effect::new(move || {
+            let selection = selected_item.get().map(LiveSelection::from);
+            if live_session.viewer_id.get().is_none() {
+                return;
+            }
+            let message = crate::models::LiveClientMessage::Select { selection };
+            if let (Some(socket), Ok(json)) = (socket.borrow().as_ref(), serde_json::to_string(&message)) {
+                let _ = socket.send_with_str(&json);
+            }
+        });
+    }
+
     // Keyboard shortcuts handler
     #[cfg(feature = "hydrate")]
     {
@@ -273,6 +608,8 @@ pub fn TopologyEditor(
         let fullscreen_mode_kb = fullscreen_mode;
         let selected_item_kb = selected_item;
         let selected_node_id_kb = selected_node_id;
+        let current_topology_id_kb = current_topology_id;
+        let refetch_trigger_kb = refetch_trigger;
 
         let keydown_handler = Closure::wrap(Box::new(move |e: KeyboardEvent| {
             // Don't intercept keys when typing in input fields
@@ -286,6 +623,24 @@ pub fn TopologyEditor(
             }
 
             match e.key().as_str() {
+                // Ctrl+Z / Cmd+Z = undo, with Shift = redo
+                "z" | "Z" if e.ctrl_key() || e.meta_key() => {
+                    e.prevent_default();
+                    let topology_id = current_topology_id_kb.get_untracked();
+                    let redo = e.shift_key();
+                    spawn_local(async move {
+                        let result = if redo {
+                            redo_last_change(topology_id).await
+                        } else {
+                            undo_last_change(topology_id).await
+                        };
+                        match result {
+                            Ok(true) => refetch_trigger_kb.update(|v| *v += 1),
+                            Ok(false) => {}
+                            Err(e) => leptos::logging::error!("Undo/redo failed: {}", e),
+                        }
+                    });
+                }
                 "f" | "F" => {
                     e.prevent_default();
                     fullscreen_mode_kb.update(|v| *v = !*v);
@@ -333,14 +688,21 @@ pub fn TopologyEditor(
     provide_context(viewport_visibility);
     provide_context(lighting_settings);
     provide_context(camera_controls);
+    provide_context(compare_mode);
+    provide_context(filter_highlight);
     provide_context(fullscreen_mode);
+    provide_context(live_session);
 
     // Track if settings have been loaded (prevent saving during initial load)
     let settings_loaded = RwSignal::new(false);
+    // The settings are shared by every account; only administrators save their changes
+    let settings_saved = RwSignal::new(false);
 
     // Effect: Load UI settings from database on mount
     Effect::new(move || {
         spawn_local(async move {
+            let is_admin = matches!(get_current_user().await, Ok(Some(user)) if user.is_admin);
+            settings_saved.set(is_admin);
             match get_ui_settings().await {
                 Ok(settings) => {
                     // Update viewport visibility
@@ -399,6 +761,9 @@ pub fn TopologyEditor(
         if loaded {
             // Trigger viewport refresh for environment lighting changes
             refetch_trigger.update(|v| *v += 1);
+            if !settings_saved.get_untracked() {
+                return;
+            }
 
             spawn_local(async move {
                 let data = UpdateUISettings {
@@ -412,6 +777,7 @@ pub fn TopologyEditor(
                     rim_light_intensity: None,
                     use_environment_lighting: Some(use_env_lighting),
                     environment_map: Some(env_map.clone()),
+                    undo_depth: None,
                 };
                 match update_ui_settings(data).await {
                     Ok(_) => {}
@@ -434,6 +800,9 @@ pub fn TopologyEditor(
         if loaded {
             // Trigger viewport refresh for lighting changes
             refetch_trigger.update(|v| *v += 1);
+            if !settings_saved.get_untracked() {
+                return;
+            }
 
             spawn_local(async move {
                 let data = UpdateUISettings {
@@ -447,6 +816,7 @@ pub fn TopologyEditor(
                     rim_light_intensity: Some(rim as f64),
                     use_environment_lighting: None,
                     environment_map: None,
+                    undo_depth: None,
                 };
                 match update_ui_settings(data).await {
                     Ok(_) => {}
@@ -502,11 +872,106 @@ pub fn TopologyEditor(
     }
 }
 
+/// Open the live socket of a topology and route its messages into the editor
+#[cfg(feature = "hydrate")]
+fn open_live_socket(
+    topology_id: i64,
+    live_session: LiveSession,
+    selected_item: RwSignal<Option<SelectedItem>>,
+    refetch_trigger: RwSignal<u32>,
+    reconnect_trigger: RwSignal<u32>,
+) -> Option<web_sys::WebSocket> {
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen::JsCast;
+
+    let location = web_sys::window()?.location();
+    let scheme = if location.protocol().ok()? == "https:" { "wss" } else { "ws" };
+    let url = format!("{}://{}/api/v1/topologies/{}/live", scheme, location.host().ok()?, topology_id);
+    let socket = match web_sys::WebSocket::new(&url) {
+        Ok(socket) => socket,
+        Err(e) => {
+            web_sys::console::error_1(&format!("Failed to open live socket: {:?}", e).into());
+            return None;
+        }
+    };
+
+    let on_message = Closure::wrap(Box::new(move |e: web_sys::MessageEvent| {
+        let Some(text) = e.data().as_string() else {
+            return;
+        };
+        let event = match serde_json::from_str::<LiveEvent>(&text) {
+            Ok(event) => event,
+            Err(e) => {
+                leptos::logging::error!("Malformed live event: {}", e);
+                return;
+            }
+        };
+        match event {
+            LiveEvent::Welcome { viewer_id } => {
+                live_session.viewer_id.set(Some(viewer_id));
+                crate::api::set_live_viewer(Some(viewer_id));
+            }
+            LiveEvent::Presence { viewers } => live_session.viewers.set(viewers),
+            LiveEvent::TopologyChanged => refetch_trigger.update(|v| *v += 1),
+            LiveEvent::TopologyDeleted | LiveEvent::AccessRevoked { .. } => live_session.topology_deleted.set(true),
+            change => {
+                // Someone else deleted what we had selected
+                let deleted = match (&change, selected_item.get_untracked()) {
+                    (LiveEvent::NodeDeleted { id }, Some(SelectedItem::Node(selected))) => *id == selected,
+                    (LiveEvent::ConnectionDeleted { id }, Some(SelectedItem::Connection(selected))) => *id == selected,
+                    _ => false,
+                };
+                if deleted {
+                    selected_item.set(None);
+                }
+                live_session.remote_changes.update(|changes| changes.push(change));
+            }
+        }
+    }) as Box<dyn FnMut(web_sys::MessageEvent)>);
+    socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    on_message.forget();
+
+    // Dropped by the server (restart, network): try again shortly
+    let on_close = Closure::wrap(Box::new(move |_: JsValue| {
+        leptos::leptos_dom::helpers::set_timeout(
+            move || {
+                let _ = reconnect_trigger.try_update(|v| *v += 1);
+            },
+            std::time::Duration::from_secs(3),
+        );
+    }) as Box<dyn FnMut(JsValue)>);
+    socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+    on_close.forget();
+
+    Some(socket)
+}
+
 /// Selected item type for properties panel
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum SelectedItem {
     Node(i64),
     Connection(i64),
+    Group(i64),
+}
+
+impl From<SelectedItem> for LiveSelection {
+    fn from(item: SelectedItem) -> Self {
+        match item {
+            SelectedItem::Node(id) => LiveSelection::Node(id),
+            SelectedItem::Connection(id) => LiveSelection::Connection(id),
+            SelectedItem::Group(id) => LiveSelection::Group(id),
+        }
+    }
+}
+
+impl From<LiveSelection> for SelectedItem {
+    fn from(selection: LiveSelection) -> Self {
+        match selection {
+            LiveSelection::Node(id) => SelectedItem::Node(id),
+            LiveSelection::Connection(id) => SelectedItem::Connection(id),
+            LiveSelection::Group(id) => SelectedItem::Group(id),
+        }
+    }
 }
 
 /// Fullscreen mode toggle button
@@ -531,6 +996,115 @@ fn PanelToggleButtons() -> impl IntoView {
     }
 }
 
+/// Search box in the top toolbar: finds nodes, connections and topologies across every topology
+#[component]
+fn GlobalSearch() -> impl IntoView {
+    let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
+    let refetch_trigger = use_context::<RwSignal<u32>>().expect("refetch_trigger context");
+    let selected_item =
+        use_context::<RwSignal<Option<SelectedItem>>>().expect("selected_item context");
+    let camera_controls = use_context::<CameraControls>().expect("camera_controls context");
+
+    let query = RwSignal::new(String::new());
+    let show_results = RwSignal::new(false);
+
+    let results = LocalResource::new(move || {
+        let query = query.get();
+        async move {
+            if query.trim().chars().count() < 2 {
+                return Ok(Vec::new());
+            }
+            search_topologies(query).await
+        }
+    });
+
+    // Switch to the hit's topology, select it and fly the camera there
+    let open_hit = move |hit: SearchHit| {
+        show_results.set(false);
+        let item = match hit.kind {
+            SearchKind::Node => Some(SelectedItem::Node(hit.item_id)),
+            SearchKind::Connection => Some(SelectedItem::Connection(hit.item_id)),
+            SearchKind::Topology => None,
+        };
+        if current_topology_id.get_untracked() != hit.topology_id {
+            current_topology_id.set(hit.topology_id);
+            refetch_trigger.update(|v| *v += 1);
+        }
+        selected_item.set(item.clone());
+        camera_controls.focus_item.set(item);
+    };
+
+    view! {
+        <div class="relative w-64">
+            <input
+                type="search"
+                class="w-full px-3 py-1.5 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
+                placeholder="Search nodes, IPs, metadata..."
+                prop:value=move || query.get()
+                on:input=move |ev| {
+                    query.set(event_target_value(&ev));
+                    show_results.set(true);
+                }
+                on:focus=move |_| show_results.set(true)
+                on:keydown=move |ev| {
+                    if ev.key() == "Escape" {
+                        show_results.set(false);
+                    } else if ev.key() == "Enter" {
+                        // Open the best match
+                        if let Some(Ok(hits)) = results.get_untracked() {
+                            if let Some(hit) = hits.into_iter().next() {
+                                open_hit(hit);
+                            }
+                        }
+                    }
+                }
+            />
+            {move || {
+                if !show_results.get() || query.get().trim().chars().count() < 2 {
+                    return None;
+                }
+                let body = match results.get() {
+                    None => view! { <div class="px-3 py-2 text-xs text-gray-500">"Searching..."</div> }.into_any(),
+                    Some(Err(e)) => view! { <div class="px-3 py-2 text-xs text-red-400">{e.to_string()}</div> }.into_any(),
+                    Some(Ok(hits)) if hits.is_empty() => {
+                        view! { <div class="px-3 py-2 text-xs text-gray-500">"No matches"</div> }.into_any()
+                    }
+                    Some(Ok(hits)) => hits.into_iter().map(|hit| {
+                        let kind = hit.kind.label();
+                        let title = hit.title.clone();
+                        let location = hit.topology_name.clone();
+                        let snippet = hit.snippet.clone();
+                        view! {
+                            <button
+                                class="block w-full text-left px-3 py-1.5 hover:bg-gray-700 border-b border-gray-700 last:border-b-0"
+                                on:click=move |_| open_hit(hit.clone())
+                            >
+                                <div class="flex items-center gap-2 text-sm">
+                                    <span class="text-[10px] uppercase text-gray-500 w-16 shrink-0">{kind}</span>
+                                    <span class="truncate text-gray-100">{title}</span>
+                                    <span class="ml-auto text-[10px] text-gray-500 truncate">{location}</span>
+                                </div>
+                                <div class="pl-[4.5rem] text-[10px] text-gray-400 truncate font-mono">{snippet}</div>
+                            </button>
+                        }
+                    }).collect_view().into_any(),
+                };
+                Some(view! {
+                    <div class="absolute right-0 top-full mt-1 w-96 max-h-96 overflow-y-auto bg-gray-800 border border-gray-600 rounded shadow-lg z-[9000]">
+                        {body}
+                        <button
+                            class="block w-full px-3 py-1 text-[10px] text-gray-500 hover:text-gray-300 text-right"
+                            on:click=move |_| show_results.set(false)
+                        >
+                            "Close"
+                        </button>
+                    </div>
+                })
+            }}
+        </div>
+    }
+}
+
 /// Top toolbar with action buttons
 #[component]
 fn TopToolbar() -> impl IntoView {
@@ -550,7 +1124,7 @@ fn TopToolbar() -> impl IntoView {
     );
 
     // Provide topology list trigger to child components
-    provide_context(topology_list_trigger);
+    provide_context(TopologyListTrigger(topology_list_trigger));
 
     // State for editing topology name
     let editing_name = RwSignal::new(false);
@@ -559,6 +1133,11 @@ fn TopToolbar() -> impl IntoView {
     // State for delete confirmation
     let show_delete_confirm = RwSignal::new(false);
 
+    // State for the duplicate dialog
+    let show_duplicate_dialog = RwSignal::new(false);
+    let duplicate_name = RwSignal::new(String::new());
+    let duplicate_include_traffic = RwSignal::new(false);
+
     // Update topology name action
     let update_topology_action = Action::new(move |new_name: &String| {
         let topology_id = current_topology_id.get_untracked();
@@ -603,6 +1182,25 @@ fn TopToolbar() -> impl IntoView {
         }
     });
 
+    // Duplicate topology action
+    let duplicate_topology_action = Action::new(move |(name, include_traffic): &(String, bool)| {
+        let topology_id = current_topology_id.get_untracked();
+        let name = Some(name.clone());
+        let include_traffic = *include_traffic;
+        async move { duplicate_topology(topology_id, name, include_traffic).await }
+    });
+
+    // After duplicating, switch to the copy
+    Effect::new(move || {
+        if let Some(Ok(copy)) = duplicate_topology_action.value().get() {
+            show_duplicate_dialog.set(false);
+            topology_list_trigger.update(|v| *v += 1);
+            current_topology_id.set(copy.id);
+            selected_item.set(None);
+            refetch_trigger.update(|v| *v += 1);
+        }
+    });
+
     // Delete topology action
     let delete_topology_action = Action::new(move |_: &()| {
         let topology_id = current_topology_id.get_untracked();
@@ -610,16 +1208,28 @@ fn TopToolbar() -> impl IntoView {
     });
 
     // After deleting, switch to another topology if available
+    let leave_deleted_topology = move || {
+        // Clear selection
+        selected_item.set(None);
+        // Refetch topologies list
+        topology_list_trigger.update(|v| *v += 1);
+        // Switch to topology 1 if available
+        current_topology_id.set(1);
+        // Trigger viewport refresh
+        refetch_trigger.update(|v| *v += 1);
+    };
     Effect::new(move || {
         if let Some(Ok(_)) = delete_topology_action.value().get() {
-            // Clear selection
-            selected_item.set(None);
-            // Refetch topologies list
-            topology_list_trigger.update(|v| *v += 1);
-            // Switch to topology 1 if available
-            current_topology_id.set(1);
-            // Trigger viewport refresh
-            refetch_trigger.update(|v| *v += 1);
+            leave_deleted_topology();
+        }
+    });
+
+    // Someone else deleted the topology open here, or our access to it
+    let live_session = use_context::<LiveSession>().expect("live_session context");
+    Effect::new(move || {
+        if live_session.topology_deleted.get() {
+            live_session.topology_deleted.set(false);
+            leave_deleted_topology();
         }
     });
 
@@ -730,6 +1340,20 @@ fn TopToolbar() -> impl IntoView {
                                                     >
                                                         "✏️"
                                                     </button>
+                                                    <button
+                                                        class="px-2 py-1.5 bg-gray-700 hover:bg-gray-600 border border-gray-600 rounded text-xs font-medium transition"
+                                                        on:click={
+                                                            let current_name = current_name.clone();
+                                                            move |_| {
+                                                                duplicate_name.set(format!("{} (copy)", current_name));
+                                                                duplicate_include_traffic.set(false);
+                                                                show_duplicate_dialog.set(true);
+                                                            }
+                                                        }
+                                                        title="Duplicate topology"
+                                                    >
+                                                        "Duplicate"
+                                                    </button>
                                                 </>
                                             }.into_any()
                                         }
@@ -764,6 +1388,68 @@ fn TopToolbar() -> impl IntoView {
                 }}
             </button>
 
+            // Duplicate topology dialog
+            {move || {
+                if show_duplicate_dialog.get() {
+                    Some(view! {
+                        <div class="fixed inset-0 bg-black bg-opacity-50 flex items-center justify-center z-[10000]">
+                            <div class="bg-gray-800 border border-gray-700 rounded-lg p-6 w-96 mx-4 space-y-4">
+                                <h3 class="text-lg font-bold text-blue-400">"Duplicate Topology"</h3>
+                                <div>
+                                    <label class="block text-xs font-medium text-gray-400 mb-1.5">"Name"</label>
+                                    <input
+                                        type="text"
+                                        class="w-full px-3 py-1.5 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
+                                        prop:value=move || duplicate_name.get()
+                                        on:input=move |ev| duplicate_name.set(event_target_value(&ev))
+                                    />
+                                </div>
+                                <label class="flex items-center gap-2 text-sm text-gray-300">
+                                    <input
+                                        type="checkbox"
+                                        prop:checked=move || duplicate_include_traffic.get()
+                                        on:change=move |ev| duplicate_include_traffic.set(event_target_checked(&ev))
+                                    />
+                                    "Include traffic history"
+                                </label>
+                                {move || match duplicate_topology_action.value().get() {
+                                    Some(Err(e)) => Some(view! {
+                                        <div class="text-xs text-red-400">"✗ " {e.to_string()}</div>
+                                    }),
+                                    _ => None,
+                                }}
+                                <div class="flex gap-3 justify-end">
+                                    <button
+                                        class="px-4 py-2 bg-gray-700 hover:bg-gray-600 rounded text-sm font-medium transition"
+                                        on:click=move |_| show_duplicate_dialog.set(false)
+                                    >
+                                        "Cancel"
+                                    </button>
+                                    <button
+                                        class="px-4 py-2 bg-blue-600 hover:bg-blue-700 rounded text-sm font-medium transition disabled:opacity-50 disabled:cursor-not-allowed"
+                                        on:click=move |_| {
+                                            duplicate_topology_action.dispatch((
+                                                duplicate_name.get_untracked(),
+                                                duplicate_include_traffic.get_untracked(),
+                                            ));
+                                        }
+                                        disabled=move || duplicate_topology_action.pending().get()
+                                    >
+                                        {move || if duplicate_topology_action.pending().get() {
+                                            "Duplicating..."
+                                        } else {
+                                            "Duplicate"
+                                        }}
+                                    </button>
+                                </div>
+                            </div>
+                        </div>
+                    })
+                } else {
+                    None
+                }
+            }}
+
             // Delete Topology button with confirmation
             <div class="relative">
                 <button
@@ -817,74 +1503,106 @@ fn TopToolbar() -> impl IntoView {
             // Spacer
             <div class="flex-1"></div>
 
+            // Search across all topologies
+            <GlobalSearch />
+
+            // Who else is viewing this topology
+            <PresenceBar />
+
             // Undo button
             <UndoButton />
 
             // Panel visibility toggle buttons
             <PanelToggleButtons />
 
+            // Revisions dropdown menu
+            <RevisionsDropdown />
+
+            // Subnets (IPAM) dropdown menu
+            <SubnetsDropdown />
+
+            // Compare dropdown menu
+            <CompareDropdown />
+
             // Import dropdown menu
             <ImportDropdown />
 
             // Export dropdown menu
             <ExportDropdown />
+
+            // Audit log viewer
+            <AuditLogPanel />
+
+            // Sharing, API tokens and sign out
+            <AccountDropdown />
         </div>
     }
 }
 
-/// Undo button for reverting the last change
+/// Other people viewing the current topology; click one to select what they have selected
 #[component]
-fn UndoButton() -> impl IntoView {
-    let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
-    let refetch_trigger = use_context::<RwSignal<u32>>().expect("refetch_trigger context");
-
-    // Check if undo history is available (use LocalResource to avoid hydration warnings)
-    let has_undo = LocalResource::new(move || {
-        let topology_id = current_topology_id.get();
-        let _ = refetch_trigger.get(); // Track refetch trigger for reactivity
-        async move {
-            match get_undo_history(topology_id).await {
-                Ok(history) => !history.is_empty(),
-                Err(_) => false,
-            }
-        }
-    });
+fn PresenceBar() -> impl IntoView {
+    let live_session = use_context::<LiveSession>().expect("live_session context");
+    let selected_item =
+        use_context::<RwSignal<Option<SelectedItem>>>().expect("selected_item context");
 
-    // Undo action
-    let undo_action = Action::new(move |_: &()| {
-        let topology_id = current_topology_id.get_untracked();
-        async move { undo_last_change(topology_id).await }
-    });
+    const COLORS: [&str; 6] = ["bg-pink-600", "bg-amber-600", "bg-emerald-600", "bg-sky-600", "bg-violet-600", "bg-rose-600"];
 
-    // Trigger viewport refresh on successful undo
-    Effect::new(move || {
-        if let Some(Ok(true)) = undo_action.value().get() {
-            refetch_trigger.update(|v| *v += 1);
-        }
-    });
+    let others = move || {
+        let own_id = live_session.viewer_id.get();
+        live_session
+            .viewers
+            .get()
+            .into_iter()
+            .filter(|viewer| Some(viewer.viewer_id) != own_id)
+            .collect::<Vec<_>>()
+    };
 
     view! {
-        <button
-            class="px-3 py-1.5 bg-gray-700 hover:bg-gray-600 rounded text-sm font-medium transition disabled:opacity-40 disabled:cursor-not-allowed flex items-center gap-2"
-            on:click=move |_| { undo_action.dispatch(()); }
-            disabled=move || !has_undo.get().unwrap_or(false) || undo_action.pending().get()
-            title="Undo last change (Ctrl+Z)"
-        >
-            <span>"↶"</span>
-            <span>"Undo"</span>
-        </button>
+        <div class="flex items-center -space-x-1 mr-2">
+            {move || others().into_iter().map(|viewer| {
+                let color = COLORS[viewer.user_id.rem_euclid(COLORS.len() as i64) as usize];
+                let initial = viewer.username.chars().next().unwrap_or('?').to_uppercase().to_string();
+                let title = match viewer.selection {
+                    Some(LiveSelection::Node(id)) => format!("{} - node {} selected", viewer.username, id),
+                    Some(LiveSelection::Connection(id)) => format!("{} - connection {} selected", viewer.username, id),
+                    Some(LiveSelection::Group(id)) => format!("{} - group {} selected", viewer.username, id),
+                    None => format!("{} - viewing", viewer.username),
+                };
+                let selection = viewer.selection;
+                view! {
+                    <button
+                        class=format!("w-7 h-7 rounded-full border-2 border-gray-800 text-xs font-bold text-white {}", color)
+                        class:ring-2=selection.is_some()
+                        class:ring-white=selection.is_some()
+                        title=title
+                        on:click=move |_| {
+                            if let Some(selection) = selection {
+                                selected_item.set(Some(selection.into()));
+                            }
+                        }
+                    >
+                        {initial}
+                    </button>
+                }
+            }).collect_view()}
+        </div>
     }
 }
 
-/// Export dropdown menu with format and resolution options
+/// Account menu: who has access to this topology, API tokens and sign out
 #[component]
-fn ExportDropdown() -> impl IntoView {
-    // Get current topology ID from context
+fn AccountDropdown() -> impl IntoView {
     let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
 
     let show_dropdown = RwSignal::new(false);
-    let export_format = RwSignal::new(String::from("png"));
-    let export_resolution = RwSignal::new(1);
+    let share_username = RwSignal::new(String::new());
+    let share_role = RwSignal::new(Role::Viewer);
+    let token_name = RwSignal::new(String::new());
+    let new_token_secret = RwSignal::new(None::<String>);
+    let status = RwSignal::new(None::<Result<String, String>>);
+    let permissions_trigger = RwSignal::new(0u32);
+    let tokens_trigger = RwSignal::new(0u32);
 
     // Close dropdown when clicking outside
     #[cfg(feature = "hydrate")]
@@ -911,36 +1629,111 @@ fn ExportDropdown() -> impl IntoView {
         });
     }
 
-    // Export action
-    let export_action = Action::new(move |(format, resolution): &(String, u32)| {
-        #[allow(unused_variables)]
-        let format = format.clone();
-        #[allow(unused_variables)]
-        let resolution = *resolution;
-        #[allow(unused_variables)]
+    let user = LocalResource::new(move || async move { get_current_user().await.ok().flatten() });
+
+    let permissions = LocalResource::new(move || {
+        let topology_id = current_topology_id.get();
+        let _ = permissions_trigger.get();
+        async move { get_topology_permissions(topology_id).await.unwrap_or_default() }
+    });
+
+    let tokens = LocalResource::new(move || {
+        let _ = tokens_trigger.get();
+        async move { get_api_tokens().await.unwrap_or_default() }
+    });
+
+    // Only owners may change who has access
+    let is_owner = move || {
+        let user_id = user.get().flatten().map(|user| user.id);
+        permissions.get().is_some_and(|permissions| {
+            permissions
+                .iter()
+                .any(|p| Some(p.user_id) == user_id && p.role == Role::Owner)
+        })
+    };
+
+    let share_action = Action::new(move |(username, role): &(String, Option<Role>)| {
         let topology_id = current_topology_id.get_untracked();
-        async move {
-            #[cfg(feature = "hydrate")]
-            {
-                if format == "json" {
-                    export_topology_json(topology_id).await;
-                } else {
-                    export_canvas(&format, resolution).await;
-                }
+        let username = username.clone();
+        let role = *role;
+        async move { set_topology_permission(topology_id, username, role).await }
+    });
+
+    Effect::new(move || match share_action.value().get() {
+        Some(Ok(_)) => {
+            share_username.set(String::new());
+            status.set(None);
+            permissions_trigger.update(|v| *v += 1);
+        }
+        Some(Err(e)) => status.set(Some(Err(e.to_string()))),
+        None => {}
+    });
+
+    let create_token_action = Action::new(move |name: &String| {
+        let name = name.clone();
+        async move { create_api_token(name).await }
+    });
+
+    Effect::new(move || match create_token_action.value().get() {
+        Some(Ok(created)) => {
+            token_name.set(String::new());
+            new_token_secret.set(Some(created.secret));
+            tokens_trigger.update(|v| *v += 1);
+        }
+        Some(Err(e)) => status.set(Some(Err(e.to_string()))),
+        None => {}
+    });
+
+    let revoke_token_action = Action::new(move |id: &i64| {
+        let id = *id;
+        async move { delete_api_token(id).await }
+    });
+
+    Effect::new(move || {
+        if let Some(result) = revoke_token_action.value().get() {
+            match result {
+                Ok(()) => tokens_trigger.update(|v| *v += 1),
+                Err(e) => status.set(Some(Err(e.to_string()))),
             }
         }
     });
 
+    // Back to the sign-in page
+    let logout_action = Action::new(move |_: &()| async move {
+        let _ = logout().await;
+        #[cfg(feature = "hydrate")]
+        if let Some(window) = web_sys::window() {
+            let _ = window.location().reload();
+        }
+    });
+
+    let share = move || {
+        let username = share_username.get_untracked();
+        if !username.trim().is_empty() {
+            share_action.dispatch((username, Some(share_role.get_untracked())));
+        }
+    };
+
+    let create_token = move || {
+        let name = token_name.get_untracked();
+        if !name.trim().is_empty() {
+            create_token_action.dispatch(name);
+        }
+    };
+
     view! {
-        <div class="relative">
+        <div class="relative mr-2">
             <button
-                class="px-4 py-2 bg-blue-600 hover:bg-blue-700 rounded text-sm font-medium transition flex items-center gap-2"
+                class="px-4 py-2 bg-gray-700 hover:bg-gray-600 rounded text-sm font-medium transition flex items-center gap-2"
                 on:click=move |e| {
                     e.stop_propagation();
                     show_dropdown.update(|v| *v = !*v);
+                    status.set(None);
+                    new_token_secret.set(None);
                 }
+                title="Sharing, API tokens and sign out"
             >
-                "Export"
+                {move || user.get().flatten().map(|user| user.username).unwrap_or_else(|| "Account".to_string())}
                 <span class="text-xs">"▼"</span>
             </button>
 
@@ -948,76 +1741,159 @@ fn ExportDropdown() -> impl IntoView {
                 if show_dropdown.get() {
                     Some(view! {
                         <div
-                            class="absolute right-0 mt-2 w-64 bg-gray-800 border border-gray-700 rounded-lg shadow-lg z-[9999]"
+                            class="absolute right-0 mt-2 w-80 bg-gray-800 border border-gray-700 rounded-lg shadow-lg z-[9999]"
                             on:click=move |e| e.stop_propagation()
                         >
                             <div class="p-3 space-y-3">
-                                // Format selection
-                                <div>
-                                    <label class="block text-xs font-medium text-gray-400 mb-1.5">"Format"</label>
-                                    <select
-                                        class="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
-                                        on:change=move |ev| {
-                                            export_format.set(event_target_value(&ev));
-                                        }
-                                        prop:value=move || export_format.get()
-                                    >
-                                        <option value="png">"PNG (High Quality)"</option>
-                                        <option value="jpeg">"JPEG (Smaller Size)"</option>
-                                        <option value="json">"JSON (Topology Data)"</option>
-                                    </select>
-                                </div>
-
-                                // Resolution selection (only for image formats)
-                                {move || {
-                                    let format = export_format.get();
-                                    if format == "png" || format == "jpeg" {
-                                        view! {
-                                            <div>
-                                                <label class="block text-xs font-medium text-gray-400 mb-1.5">"Resolution"</label>
-                                                <select
-                                                    class="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
-                                                    on:change=move |ev| {
-                                                        let value = event_target_value(&ev);
-                                                        if let Ok(res) = value.parse::<u32>() {
-                                                            export_resolution.set(res);
+                                // Access to the current topology
+                                <div class="space-y-2">
+                                    <label class="block text-xs font-medium text-gray-400">"Shared With"</label>
+                                    <Suspense fallback=move || view! { <div class="text-xs text-gray-500">"Loading..."</div> }>
+                                        {move || permissions.get().map(|permissions| {
+                                            let owner = is_owner();
+                                            view! {
+                                                <div class="max-h-40 overflow-y-auto space-y-1">
+                                                    {permissions.into_iter().map(|permission| {
+                                                        let username = permission.username.clone();
+                                                        view! {
+                                                            <div class="flex items-center justify-between gap-2 px-2 py-1 bg-gray-700 rounded text-xs">
+                                                                <span class="text-gray-200 truncate">{permission.username.clone()}</span>
+                                                                <span class="ml-auto text-gray-400">{permission.role.label()}</span>
+                                                                {owner.then(|| view! {
+                                                                    <button
+                                                                        class="text-gray-400 hover:text-red-400"
+                                                                        title="Remove access"
+                                                                        on:click=move |_| {
+                                                                            share_action.dispatch((username.clone(), None));
+                                                                        }
+                                                                    >
+                                                                        "×"
+                                                                    </button>
+                                                                })}
+                                                            </div>
                                                         }
+                                                    }).collect_view()}
+                                                </div>
+                                            }
+                                        })}
+                                    </Suspense>
+                                    {move || is_owner().then(|| view! {
+                                        <div class="flex gap-1">
+                                            <input
+                                                type="text"
+                                                placeholder="Username"
+                                                class="flex-1 min-w-0 px-2 py-1 bg-gray-700 border border-gray-600 rounded text-xs focus:outline-none focus:border-blue-500"
+                                                prop:value=move || share_username.get()
+                                                on:input=move |ev| share_username.set(event_target_value(&ev))
+                                                on:keydown=move |ev| {
+                                                    if ev.key() == "Enter" {
+                                                        share();
                                                     }
-                                                    prop:value=move || export_resolution.get().to_string()
-                                                >
-                                                    <option value="1">"1x (Current)"</option>
-                                                    <option value="2">"2x (High Quality)"</option>
-                                                    <option value="4">"4x (Print Quality)"</option>
-                                                </select>
-                                            </div>
-                                        }.into_any()
-                                    } else {
-                                        view! { <div></div> }.into_any()
-                                    }
-                                }}
+                                                }
+                                            />
+                                            <select
+                                                class="px-1 py-1 bg-gray-700 border border-gray-600 rounded text-xs"
+                                                on:change=move |ev| {
+                                                    if let Ok(role) = event_target_value(&ev).parse::<Role>() {
+                                                        share_role.set(role);
+                                                    }
+                                                }
+                                            >
+                                                {Role::ALL.iter().map(|role| view! {
+                                                    <option value=role.as_str() selected=move || share_role.get() == *role>
+                                                        {role.label()}
+                                                    </option>
+                                                }).collect_view()}
+                                            </select>
+                                            <button
+                                                class="px-2 py-1 bg-blue-600 hover:bg-blue-700 rounded text-xs font-medium transition disabled:opacity-50"
+                                                on:click=move |_| share()
+                                                disabled=move || share_username.get().trim().is_empty() || share_action.pending().get()
+                                            >
+                                                "Share"
+                                            </button>
+                                        </div>
+                                    })}
+                                </div>
 
-                                // Export button
-                                <button
-                                    class="w-full px-3 py-2 bg-blue-600 hover:bg-blue-700 rounded text-sm font-medium transition"
-                                    on:click=move |_| {
-                                        export_action.dispatch((export_format.get_untracked().to_string(), export_resolution.get_untracked()));
-                                        show_dropdown.set(false);
-                                    }
-                                    disabled=move || export_action.pending().get()
-                                >
-                                    {move || {
-                                        if export_action.pending().get() {
-                                            "Exporting..."
-                                        } else {
-                                            let format = export_format.get();
-                                            if format == "json" {
-                                                "Export JSON"
-                                            } else {
-                                                "Export Image"
+                                // API tokens for scripts
+                                <div class="border-t border-gray-700 pt-3 space-y-2">
+                                    <label class="block text-xs font-medium text-gray-400">"API Tokens"</label>
+                                    <Suspense fallback=move || view! { <div class="text-xs text-gray-500">"Loading..."</div> }>
+                                        {move || tokens.get().map(|tokens| {
+                                            if tokens.is_empty() {
+                                                return view! {
+                                                    <div class="text-xs text-gray-500">"No tokens"</div>
+                                                }.into_any();
                                             }
-                                        }
-                                    }}
-                                </button>
+                                            view! {
+                                                <div class="max-h-40 overflow-y-auto space-y-1">
+                                                    {tokens.into_iter().map(|token| {
+                                                        let id = token.id;
+                                                        view! {
+                                                            <div class="flex items-center gap-2 px-2 py-1 bg-gray-700 rounded text-xs">
+                                                                <span class="text-gray-200 truncate">{token.name.clone()}</span>
+                                                                <span class="ml-auto font-mono text-gray-400">{format!("{}…", token.token_prefix)}</span>
+                                                                <button
+                                                                    class="text-gray-400 hover:text-red-400"
+                                                                    title="Revoke token"
+                                                                    on:click=move |_| { revoke_token_action.dispatch(id); }
+                                                                >
+                                                                    "×"
+                                                                </button>
+                                                            </div>
+                                                        }
+                                                    }).collect_view()}
+                                                </div>
+                                            }.into_any()
+                                        })}
+                                    </Suspense>
+                                    {move || new_token_secret.get().map(|secret| view! {
+                                        <div class="p-2 bg-gray-900 border border-green-700 rounded text-xs space-y-1">
+                                            <div class="text-green-400">"Copy this token now; it won't be shown again:"</div>
+                                            <input
+                                                type="text"
+                                                readonly
+                                                class="w-full px-2 py-1 bg-gray-800 rounded font-mono text-gray-200"
+                                                prop:value=secret
+                                            />
+                                        </div>
+                                    })}
+                                    <div class="flex gap-1">
+                                        <input
+                                            type="text"
+                                            placeholder="Token name"
+                                            class="flex-1 min-w-0 px-2 py-1 bg-gray-700 border border-gray-600 rounded text-xs focus:outline-none focus:border-blue-500"
+                                            prop:value=move || token_name.get()
+                                            on:input=move |ev| token_name.set(event_target_value(&ev))
+                                            on:keydown=move |ev| {
+                                                if ev.key() == "Enter" {
+                                                    create_token();
+                                                }
+                                            }
+                                        />
+                                        <button
+                                            class="px-2 py-1 bg-blue-600 hover:bg-blue-700 rounded text-xs font-medium transition disabled:opacity-50"
+                                            on:click=move |_| create_token()
+                                            disabled=move || token_name.get().trim().is_empty() || create_token_action.pending().get()
+                                        >
+                                            "Create"
+                                        </button>
+                                    </div>
+                                </div>
+
+                                {move || status.get().and_then(|status| status.err()).map(|message| view! {
+                                    <div class="text-xs text-red-400">"✗ " {message}</div>
+                                })}
+
+                                <div class="border-t border-gray-700 pt-3">
+                                    <button
+                                        class="w-full px-3 py-1.5 bg-gray-700 hover:bg-gray-600 rounded text-sm font-medium transition"
+                                        on:click=move |_| { logout_action.dispatch(()); }
+                                    >
+                                        "Sign Out"
+                                    </button>
+                                </div>
                             </div>
                         </div>
                     })
@@ -1029,230 +1905,1561 @@ fn ExportDropdown() -> impl IntoView {
     }
 }
 
-/// Export the canvas to an image file
-#[cfg(feature = "hydrate")]
-async fn export_canvas(format: &str, resolution_multiplier: u32) {
-    use wasm_bindgen::JsCast;
-    use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+/// Audit log viewer: who changed what, filterable, with CSV/JSON export
+#[component]
+fn AuditLogPanel() -> impl IntoView {
+    let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
 
-    let window = match web_sys::window() {
-        Some(w) => w,
-        None => {
-            web_sys::console::error_1(&"No window available".into());
-            return;
-        }
-    };
+    let show_panel = RwSignal::new(false);
+    let this_topology_only = RwSignal::new(true);
+    let actor = RwSignal::new(String::new());
+    let entity_type = RwSignal::new(None::<AuditEntity>);
+    let action = RwSignal::new(None::<AuditAction>);
+    let expanded = RwSignal::new(None::<i64>);
+    let export_error = RwSignal::new(None::<String>);
+
+    let filter = Memo::new(move |_| AuditFilter {
+        topology_id: this_topology_only.get().then(|| current_topology_id.get()),
+        actor: Some(actor.get().trim().to_string()).filter(|actor| !actor.is_empty()),
+        entity_type: entity_type.get(),
+        action: action.get(),
+        ..Default::default()
+    });
 
-    let document = match window.document() {
-        Some(d) => d,
-        None => {
-            web_sys::console::error_1(&"No document available".into());
-            return;
+    // Only query while the panel is open
+    let entries = LocalResource::new(move || {
+        let open = show_panel.get();
+        let filter = filter.get();
+        async move {
+            if open {
+                get_audit_log(filter).await.map_err(|e| e.to_string())
+            } else {
+                Ok(Vec::new())
+            }
         }
-    };
+    });
 
-    // Find the canvas element
-    let canvas = match document.query_selector("canvas") {
-        Ok(Some(element)) => match element.dyn_into::<HtmlCanvasElement>() {
-            Ok(c) => c,
-            Err(_) => {
-                web_sys::console::error_1(&"Canvas element is not an HtmlCanvasElement".into());
-                return;
+    let export_action = Action::new(move |format: &AuditExportFormat| {
+        let format = *format;
+        let filter = filter.get_untracked();
+        async move {
+            match export_audit_log(filter, format).await {
+                Ok(_contents) => {
+                    #[cfg(feature = "hydrate")]
+                    download_audit_log(&_contents, format);
+                    export_error.set(None);
+                }
+                Err(e) => export_error.set(Some(e.to_string())),
             }
-        },
-        _ => {
-            web_sys::console::error_1(&"Canvas element not found".into());
-            return;
         }
-    };
-
-    // Get current canvas dimensions
-    let width = canvas.width();
-    let height = canvas.height();
+    });
 
-    // Create a temporary canvas at higher resolution if needed
-    let export_canvas = if resolution_multiplier > 1 {
-        let temp_canvas = match document.create_element("canvas") {
-            Ok(element) => match element.dyn_into::<HtmlCanvasElement>() {
-                Ok(c) => c,
-                Err(_) => {
-                    web_sys::console::error_1(&"Failed to create temporary canvas".into());
-                    return;
-                }
-            },
-            Err(_) => {
-                web_sys::console::error_1(&"Failed to create canvas element".into());
-                return;
+    view! {
+        <button
+            class="px-4 py-2 bg-gray-700 hover:bg-gray-600 rounded text-sm font-medium transition mr-2"
+            on:click=move |_| {
+                export_error.set(None);
+                show_panel.set(true);
             }
-        };
+            title="Audit log of changes"
+        >
+            "📋 Audit"
+        </button>
 
-        temp_canvas.set_width(width * resolution_multiplier);
-        temp_canvas.set_height(height * resolution_multiplier);
+        {move || {
+            if show_panel.get() {
+                Some(view! {
+                    <div class="fixed inset-0 bg-black bg-opacity-50 flex items-center justify-center z-[10000]">
+                        <div class="bg-gray-800 border border-gray-700 rounded-lg p-6 w-[48rem] max-w-full mx-4 space-y-4">
+                            <div class="flex items-center justify-between">
+                                <h3 class="text-lg font-bold text-blue-400">"Audit Log"</h3>
+                                <button
+                                    class="text-gray-400 hover:text-white text-lg"
+                                    on:click=move |_| show_panel.set(false)
+                                    title="Close"
+                                >
+                                    "×"
+                                </button>
+                            </div>
 
-        // Get 2D context and draw the original canvas scaled up
-        let context = match temp_canvas.get_context("2d") {
-            Ok(Some(ctx)) => match ctx.dyn_into::<CanvasRenderingContext2d>() {
-                Ok(c) => c,
-                Err(_) => {
-                    web_sys::console::error_1(&"Failed to get 2D context".into());
-                    return;
-                }
-            },
-            _ => {
-                web_sys::console::error_1(&"Failed to get canvas context".into());
-                return;
+                            // Filters
+                            <div class="flex flex-wrap items-center gap-2 text-xs">
+                                <label class="flex items-center gap-1.5 text-gray-300">
+                                    <input
+                                        type="checkbox"
+                                        prop:checked=move || this_topology_only.get()
+                                        on:change=move |ev| this_topology_only.set(event_target_checked(&ev))
+                                    />
+                                    "This topology only"
+                                </label>
+                                <input
+                                    type="text"
+                                    placeholder="Actor"
+                                    class="w-32 px-2 py-1 bg-gray-700 border border-gray-600 rounded focus:outline-none focus:border-blue-500"
+                                    prop:value=move || actor.get()
+                                    on:change=move |ev| actor.set(event_target_value(&ev))
+                                />
+                                <select
+                                    class="px-1 py-1 bg-gray-700 border border-gray-600 rounded"
+                                    on:change=move |ev| entity_type.set(event_target_value(&ev).parse().ok())
+                                >
+                                    <option value="" selected=move || entity_type.get().is_none()>"All entities"</option>
+                                    {AuditEntity::ALL.iter().map(|entity| view! {
+                                        <option value=entity.as_str() selected=move || entity_type.get() == Some(*entity)>
+                                            {entity.label()}
+                                        </option>
+                                    }).collect_view()}
+                                </select>
+                                <select
+                                    class="px-1 py-1 bg-gray-700 border border-gray-600 rounded"
+                                    on:change=move |ev| action.set(event_target_value(&ev).parse().ok())
+                                >
+                                    <option value="" selected=move || action.get().is_none()>"All actions"</option>
+                                    {AuditAction::ALL.iter().map(|audit_action| view! {
+                                        <option value=audit_action.as_str() selected=move || action.get() == Some(*audit_action)>
+                                            {audit_action.label()}
+                                        </option>
+                                    }).collect_view()}
+                                </select>
+                                <div class="ml-auto flex gap-1">
+                                    {AuditExportFormat::ALL.iter().map(|format| {
+                                        let format = *format;
+                                        view! {
+                                            <button
+                                                class="px-2 py-1 bg-gray-700 hover:bg-gray-600 rounded font-medium transition disabled:opacity-50"
+                                                on:click=move |_| { export_action.dispatch(format); }
+                                                disabled=move || export_action.pending().get()
+                                            >
+                                                {format!("Export {}", format.label())}
+                                            </button>
+                                        }
+                                    }).collect_view()}
+                                </div>
+                            </div>
+
+                            {move || export_error.get().map(|message| view! {
+                                <div class="text-xs text-red-400">"✗ " {message}</div>
+                            })}
+
+                            // Entries, newest first
+                            <div class="max-h-[60vh] overflow-y-auto">
+                                <Suspense fallback=move || view! { <div class="text-xs text-gray-500">"Loading..."</div> }>
+                                    {move || entries.get().map(|result| match result {
+                                        Err(message) => view! {
+                                            <div class="text-xs text-red-400">"✗ " {message}</div>
+                                        }.into_any(),
+                                        Ok(entries) if entries.is_empty() => view! {
+                                            <div class="text-xs text-gray-500">"No matching entries"</div>
+                                        }.into_any(),
+                                        Ok(entries) => view! {
+                                            <div class="space-y-1">
+                                                {entries.into_iter().map(|entry| {
+                                                    let id = entry.id;
+                                                    let has_states = entry.before_state.is_some() || entry.after_state.is_some();
+                                                    let before = entry.before_state.map(|state| pretty_audit_state(&state));
+                                                    let after = entry.after_state.map(|state| pretty_audit_state(&state));
+                                                    view! {
+                                                        <div class="px-2 py-1 bg-gray-700 rounded text-xs">
+                                                            <div
+                                                                class="flex items-center gap-2"
+                                                                class:cursor-pointer=has_states
+                                                                on:click=move |_| {
+                                                                    if has_states {
+                                                                        expanded.update(|v| *v = if *v == Some(id) { None } else { Some(id) });
+                                                                    }
+                                                                }
+                                                            >
+                                                                <span class="text-gray-400 whitespace-nowrap">{format_audit_time(entry.created_at)}</span>
+                                                                <span class="text-blue-300 whitespace-nowrap">{entry.actor.clone()}</span>
+                                                                <span class="text-gray-200 truncate" title=entry.summary.clone()>{entry.summary.clone()}</span>
+                                                                {has_states.then(|| view! {
+                                                                    <span class="ml-auto text-gray-500">
+                                                                        {move || if expanded.get() == Some(id) { "▲" } else { "▼" }}
+                                                                    </span>
+                                                                })}
+                                                            </div>
+                                                            {move || (expanded.get() == Some(id)).then(|| {
+                                                                let before = before.clone().unwrap_or_else(|| "—".to_string());
+                                                                let after = after.clone().unwrap_or_else(|| "—".to_string());
+                                                                view! {
+                                                                    <div class="grid grid-cols-2 gap-2 mt-1">
+                                                                        <pre class="p-1 bg-gray-900 rounded text-red-300 whitespace-pre-wrap break-all">
+                                                                            {before}
+                                                                        </pre>
+                                                                        <pre class="p-1 bg-gray-900 rounded text-green-300 whitespace-pre-wrap break-all">
+                                                                            {after}
+                                                                        </pre>
+                                                                    </div>
+                                                                }
+                                                            })}
+                                                        </div>
+                                                    }
+                                                }).collect_view()}
+                                            </div>
+                                        }.into_any(),
+                                    })}
+                                </Suspense>
+                            </div>
+                        </div>
+                    </div>
+                })
+            } else {
+                None
             }
-        };
+        }}
+    }
+}
 
-        // Scale and draw
-        context
-            .scale(resolution_multiplier as f64, resolution_multiplier as f64)
-            .ok();
-        context
-            .draw_image_with_html_canvas_element(&canvas, 0.0, 0.0)
-            .ok();
+/// Local date and time of an audit log entry
+fn format_audit_time(created_at: i64) -> String {
+    #[cfg(feature = "hydrate")]
+    {
+        let date = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(created_at as f64 * 1000.0));
+        String::from(date.to_locale_string("default", &wasm_bindgen::JsValue::UNDEFINED))
+    }
+    #[cfg(not(feature = "hydrate"))]
+    {
+        created_at.to_string()
+    }
+}
 
-        temp_canvas
-    } else {
-        canvas
-    };
+/// Pretty-print a stored before/after JSON state (falls back to the raw text)
+fn pretty_audit_state(state: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(state)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+        .unwrap_or_else(|| state.to_string())
+}
 
-    // Convert to data URL
-    let mime_type = if format == "jpeg" {
-        "image/jpeg"
-    } else {
-        "image/png"
-    };
+/// Download an exported audit log as a file
+#[cfg(feature = "hydrate")]
+fn download_audit_log(contents: &str, format: AuditExportFormat) {
+    use wasm_bindgen::JsCast;
 
-    let data_url = match export_canvas.to_data_url_with_type(mime_type) {
-        Ok(url) => url,
-        Err(_) => {
-            web_sys::console::error_1(&"Failed to convert canvas to data URL".into());
-            return;
-        }
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else {
+        web_sys::console::error_1(&"No document available".into());
+        return;
     };
 
-    // Create download link
-    let a = match document.create_element("a") {
-        Ok(element) => element,
-        Err(_) => {
-            web_sys::console::error_1(&"Failed to create anchor element".into());
-            return;
-        }
+    let (mime_type, extension) = match format {
+        AuditExportFormat::Csv => ("text/csv", "csv"),
+        AuditExportFormat::Json => ("application/json", "json"),
     };
 
-    let extension = if format == "jpeg" { "jpg" } else { "png" };
-    let filename = format!("topology-export.{}", extension);
+    let blob_parts = js_sys::Array::new();
+    blob_parts.push(&wasm_bindgen::JsValue::from_str(contents));
 
-    a.set_attribute("href", &data_url).ok();
+    let blob_options = web_sys::BlobPropertyBag::new();
+    blob_options.set_type(mime_type);
+
+    let Ok(blob) = web_sys::Blob::new_with_str_sequence_and_options(&blob_parts, &blob_options) else {
+        web_sys::console::error_1(&"Failed to create blob".into());
+        return;
+    };
+    let Ok(url) = web_sys::Url::create_object_url_with_blob(&blob) else {
+        web_sys::console::error_1(&"Failed to create object URL".into());
+        return;
+    };
+    let Ok(a) = document.create_element("a") else {
+        web_sys::console::error_1(&"Failed to create anchor element".into());
+        return;
+    };
+
+    let timestamp = js_sys::Date::new_0().get_time() as i64;
+    let filename = format!("audit-log-{}.{}", timestamp, extension);
+    a.set_attribute("href", &url).ok();
     a.set_attribute("download", &filename).ok();
 
-    // Trigger download
     if let Some(html_element) = a.dyn_ref::<web_sys::HtmlElement>() {
         html_element.click();
     }
 
-    web_sys::console::log_1(
-        &format!(
-            "Exported as {} at {}x resolution",
-            format, resolution_multiplier
-        )
-        .into(),
-    );
+    web_sys::Url::revoke_object_url(&url).ok();
 }
 
-/// Export topology as JSON file
-#[cfg(feature = "hydrate")]
-async fn export_topology_json(topology_id: i64) {
-    use wasm_bindgen::JsCast;
+/// Undo/redo buttons for stepping through the change set history
+#[component]
+fn UndoButton() -> impl IntoView {
+    let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
+    let refetch_trigger = use_context::<RwSignal<u32>>().expect("refetch_trigger context");
 
-    // Fetch full topology data from server
-    let topology_data = match get_topology_full(topology_id).await {
-        Ok(data) => data,
-        Err(e) => {
-            web_sys::console::error_1(&format!("Failed to fetch topology data: {}", e).into());
-            return;
-        }
-    };
+    // Check which undo/redo steps are available (use LocalResource to avoid hydration warnings)
+    let undo_status = LocalResource::new(move || {
+        let topology_id = current_topology_id.get();
+        let _ = refetch_trigger.get(); // Track refetch trigger for reactivity
+        async move { get_undo_status(topology_id).await.unwrap_or_default() }
+    });
 
-    // Serialize to JSON with pretty formatting
-    let json_string = match serde_json::to_string_pretty(&topology_data) {
-        Ok(json) => json,
-        Err(e) => {
-            web_sys::console::error_1(&format!("Failed to serialize topology: {}", e).into());
-            return;
+    // Undo/redo action (true = redo)
+    let undo_action = Action::new(move |redo: &bool| {
+        let redo = *redo;
+        let topology_id = current_topology_id.get_untracked();
+        async move {
+            if redo {
+                redo_last_change(topology_id).await
+            } else {
+                undo_last_change(topology_id).await
+            }
         }
-    };
+    });
 
-    let window = match web_sys::window() {
-        Some(w) => w,
-        None => {
-            web_sys::console::error_1(&"No window available".into());
-            return;
+    // Trigger viewport refresh on successful undo/redo
+    Effect::new(move || {
+        if let Some(Ok(true)) = undo_action.value().get() {
+            refetch_trigger.update(|v| *v += 1);
         }
-    };
+    });
 
-    let document = match window.document() {
-        Some(d) => d,
-        None => {
-            web_sys::console::error_1(&"No document available".into());
-            return;
-        }
-    };
+    let undo_label = move || undo_status.get().and_then(|status| status.undo_label);
+    let redo_label = move || undo_status.get().and_then(|status| status.redo_label);
 
-    // Create a blob with the JSON data
-    let blob_parts = js_sys::Array::new();
-    blob_parts.push(&wasm_bindgen::JsValue::from_str(&json_string));
+    view! {
+        <div class="flex items-center gap-1">
+            <button
+                class="px-3 py-1.5 bg-gray-700 hover:bg-gray-600 rounded text-sm font-medium transition disabled:opacity-40 disabled:cursor-not-allowed flex items-center gap-2"
+                on:click=move |_| { undo_action.dispatch(false); }
+                disabled=move || undo_label().is_none() || undo_action.pending().get()
+                title=move || match undo_label() {
+                    Some(label) => format!("Undo: {} (Ctrl+Z)", label),
+                    None => "Nothing to undo (Ctrl+Z)".to_string(),
+                }
+            >
+                <span>"↶"</span>
+                <span>"Undo"</span>
+            </button>
+            <button
+                class="px-3 py-1.5 bg-gray-700 hover:bg-gray-600 rounded text-sm font-medium transition disabled:opacity-40 disabled:cursor-not-allowed flex items-center gap-2"
+                on:click=move |_| { undo_action.dispatch(true); }
+                disabled=move || redo_label().is_none() || undo_action.pending().get()
+                title=move || match redo_label() {
+                    Some(label) => format!("Redo: {} (Ctrl+Shift+Z)", label),
+                    None => "Nothing to redo (Ctrl+Shift+Z)".to_string(),
+                }
+            >
+                <span>"↷"</span>
+                <span>"Redo"</span>
+            </button>
+        </div>
+    }
+}
 
-    let blob_options = web_sys::BlobPropertyBag::new();
-    blob_options.set_type("application/json");
+/// Begin an undo step when a field of a properties panel takes focus
+///
+/// The panels autosave on every keystroke or spinner step, so one edit of a field would
+/// otherwise take as many undos. Beginning a change set also closes the previous field's.
+#[cfg_attr(not(feature = "hydrate"), allow(unused_variables))]
+fn begin_field_edit(ev: &leptos::ev::FocusEvent, topology_id: i64, label: String) {
+    #[cfg(feature = "hydrate")]
+    {
+        use crate::api::begin_change_set;
+        use wasm_bindgen::JsCast;
 
-    let blob = match web_sys::Blob::new_with_str_sequence_and_options(&blob_parts, &blob_options) {
-        Ok(b) => b,
-        Err(_) => {
-            web_sys::console::error_1(&"Failed to create blob".into());
-            return;
+        let is_field = ev
+            .target()
+            .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+            .is_some_and(|element| matches!(element.tag_name().to_lowercase().as_str(), "input" | "select" | "textarea"));
+        if is_field {
+            spawn_local(async move {
+                if let Err(e) = begin_change_set(topology_id, label).await {
+                    leptos::logging::log!("Failed to begin undo step: {}", e);
+                }
+            });
         }
-    };
+    }
+}
 
-    // Create object URL for the blob
-    let url = match web_sys::Url::create_object_url_with_blob(&blob) {
-        Ok(u) => u,
-        Err(_) => {
-            web_sys::console::error_1(&"Failed to create object URL".into());
+/// End a properties panel's undo step once focus leaves the panel
+#[cfg_attr(not(feature = "hydrate"), allow(unused_variables))]
+fn end_field_edits(ev: &leptos::ev::FocusEvent, topology_id: i64) {
+    #[cfg(feature = "hydrate")]
+    {
+        use wasm_bindgen::JsCast;
+
+        let panel = ev.current_target().and_then(|target| target.dyn_into::<web_sys::Node>().ok());
+        let next = ev.related_target().and_then(|target| target.dyn_into::<web_sys::Node>().ok());
+        if panel.is_some_and(|panel| panel.contains(next.as_ref())) {
             return;
         }
-    };
+        close_field_edits(topology_id);
+    }
+}
 
-    // Create download link
-    let a = match document.create_element("a") {
-        Ok(element) => element,
-        Err(_) => {
-            web_sys::console::error_1(&"Failed to create anchor element".into());
-            return;
+/// End this session's open undo step on a topology (e.g. when a properties panel closes)
+#[cfg(feature = "hydrate")]
+fn close_field_edits(topology_id: i64) {
+    use crate::api::end_change_set;
+
+    spawn_local(async move {
+        if let Err(e) = end_change_set(topology_id).await {
+            leptos::logging::log!("Failed to end undo step: {}", e);
         }
-    };
+    });
+}
 
-    // Generate filename with topology name and timestamp
-    let timestamp = js_sys::Date::new_0().get_time() as i64;
-    let filename = format!(
-        "topology-{}-{}.json",
-        topology_data.topology.name.replace(" ", "_").to_lowercase(),
-        timestamp
-    );
+/// Export dropdown menu with format and resolution options
+#[component]
+fn ExportDropdown() -> impl IntoView {
+    // Get current topology ID from context
+    let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
+    // View settings and camera are bundled into JSON exports
+    let viewport_visibility = use_context::<ViewportVisibility>().expect("viewport_visibility context");
+    let lighting_settings = use_context::<LightingSettings>().expect("lighting_settings context");
+    let camera_controls = use_context::<CameraControls>().expect("camera_controls context");
 
-    a.set_attribute("href", &url).ok();
-    a.set_attribute("download", &filename).ok();
+    let show_dropdown = RwSignal::new(false);
+    let export_format = RwSignal::new(String::from("png"));
+    let export_resolution = RwSignal::new(1);
 
-    // Trigger download
-    if let Some(html_element) = a.dyn_ref::<web_sys::HtmlElement>() {
-        html_element.click();
+    // Close dropdown when clicking outside
+    #[cfg(feature = "hydrate")]
+    {
+        use wasm_bindgen::JsCast;
+        use web_sys::MouseEvent;
+
+        let show_dropdown_clone = show_dropdown;
+        Effect::new(move || {
+            if show_dropdown_clone.get() {
+                let window = web_sys::window().expect("no window");
+                let document = window.document().expect("no document");
+
+                let closure = wasm_bindgen::prelude::Closure::wrap(Box::new(move |_: MouseEvent| {
+                    show_dropdown_clone.set(false);
+                })
+                    as Box<dyn Fn(MouseEvent)>);
+
+                document
+                    .add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())
+                    .ok();
+                closure.forget();
+            }
+        });
+    }
+
+    // Export action
+    let export_action = Action::new(move |(format, resolution): &(String, u32)| {
+        #[allow(unused_variables)]
+        let format = format.clone();
+        #[allow(unused_variables)]
+        let resolution = *resolution;
+        #[allow(unused_variables)]
+        let topology_id = current_topology_id.get_untracked();
+        #[allow(unused_variables)]
+        let view_settings = viewport_visibility.to_view_settings(&lighting_settings);
+        #[allow(unused_variables)]
+        let camera = camera_controls.current_view.get_untracked();
+        async move {
+            #[cfg(feature = "hydrate")]
+            {
+                if format == "json" {
+                    export_topology_json(topology_id, view_settings, camera).await;
+                } else {
+                    export_canvas(&format, resolution).await;
+                }
+            }
+        }
+    });
+
+    view! {
+        <div class="relative">
+            <button
+                class="px-4 py-2 bg-blue-600 hover:bg-blue-700 rounded text-sm font-medium transition flex items-center gap-2"
+                on:click=move |e| {
+                    e.stop_propagation();
+                    show_dropdown.update(|v| *v = !*v);
+                }
+            >
+                "Export"
+                <span class="text-xs">"▼"</span>
+            </button>
+
+            {move || {
+                if show_dropdown.get() {
+                    Some(view! {
+                        <div
+                            class="absolute right-0 mt-2 w-64 bg-gray-800 border border-gray-700 rounded-lg shadow-lg z-[9999]"
+                            on:click=move |e| e.stop_propagation()
+                        >
+                            <div class="p-3 space-y-3">
+                                // Format selection
+                                <div>
+                                    <label class="block text-xs font-medium text-gray-400 mb-1.5">"Format"</label>
+                                    <select
+                                        class="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
+                                        on:change=move |ev| {
+                                            export_format.set(event_target_value(&ev));
+                                        }
+                                        prop:value=move || export_format.get()
+                                    >
+                                        <option value="png">"PNG (High Quality)"</option>
+                                        <option value="jpeg">"JPEG (Smaller Size)"</option>
+                                        <option value="json">"JSON (Topology Data)"</option>
+                                    </select>
+                                </div>
+
+                                // Resolution selection (only for image formats)
+                                {move || {
+                                    let format = export_format.get();
+                                    if format == "png" || format == "jpeg" {
+                                        view! {
+                                            <div>
+                                                <label class="block text-xs font-medium text-gray-400 mb-1.5">"Resolution"</label>
+                                                <select
+                                                    class="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
+                                                    on:change=move |ev| {
+                                                        let value = event_target_value(&ev);
+                                                        if let Ok(res) = value.parse::<u32>() {
+                                                            export_resolution.set(res);
+                                                        }
+                                                    }
+                                                    prop:value=move || export_resolution.get().to_string()
+                                                >
+                                                    <option value="1">"1x (Current)"</option>
+                                                    <option value="2">"2x (High Quality)"</option>
+                                                    <option value="4">"4x (Print Quality)"</option>
+                                                </select>
+                                            </div>
+                                        }.into_any()
+                                    } else {
+                                        view! { <div></div> }.into_any()
+                                    }
+                                }}
+
+                                // Export button
+                                <button
+                                    class="w-full px-3 py-2 bg-blue-600 hover:bg-blue-700 rounded text-sm font-medium transition"
+                                    on:click=move |_| {
+                                        export_action.dispatch((export_format.get_untracked().to_string(), export_resolution.get_untracked()));
+                                        show_dropdown.set(false);
+                                    }
+                                    disabled=move || export_action.pending().get()
+                                >
+                                    {move || {
+                                        if export_action.pending().get() {
+                                            "Exporting..."
+                                        } else {
+                                            let format = export_format.get();
+                                            if format == "json" {
+                                                "Export JSON"
+                                            } else {
+                                                "Export Image"
+                                            }
+                                        }
+                                    }}
+                                </button>
+                            </div>
+                        </div>
+                    })
+                } else {
+                    None
+                }
+            }}
+        </div>
+    }
+}
+
+/// Export the canvas to an image file
+#[cfg(feature = "hydrate")]
+async fn export_canvas(format: &str, resolution_multiplier: u32) {
+    use wasm_bindgen::JsCast;
+    use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+    let window = match web_sys::window() {
+        Some(w) => w,
+        None => {
+            web_sys::console::error_1(&"No window available".into());
+            return;
+        }
+    };
+
+    let document = match window.document() {
+        Some(d) => d,
+        None => {
+            web_sys::console::error_1(&"No document available".into());
+            return;
+        }
+    };
+
+    // Find the canvas element
+    let canvas = match document.query_selector("canvas") {
+        Ok(Some(element)) => match element.dyn_into::<HtmlCanvasElement>() {
+            Ok(c) => c,
+            Err(_) => {
+                web_sys::console::error_1(&"Canvas element is not an HtmlCanvasElement".into());
+                return;
+            }
+        },
+        _ => {
+            web_sys::console::error_1(&"Canvas element not found".into());
+            return;
+        }
+    };
+
+    // Get current canvas dimensions
+    let width = canvas.width();
+    let height = canvas.height();
+
+    // Create a temporary canvas at higher resolution if needed
+    let export_canvas = if resolution_multiplier > 1 {
+        let temp_canvas = match document.create_element("canvas") {
+            Ok(element) => match element.dyn_into::<HtmlCanvasElement>() {
+                Ok(c) => c,
+                Err(_) => {
+                    web_sys::console::error_1(&"Failed to create temporary canvas".into());
+                    return;
+                }
+            },
+            Err(_) => {
+                web_sys::console::error_1(&"Failed to create canvas element".into());
+                return;
+            }
+        };
+
+        temp_canvas.set_width(width * resolution_multiplier);
+        temp_canvas.set_height(height * resolution_multiplier);
+
+        // Get 2D context and draw the original canvas scaled up
+        let context = match temp_canvas.get_context("2d") {
+            Ok(Some(ctx)) => match ctx.dyn_into::<CanvasRenderingContext2d>() {
+                Ok(c) => c,
+                Err(_) => {
+                    web_sys::console::error_1(&"Failed to get 2D context".into());
+                    return;
+                }
+            },
+            _ => {
+                web_sys::console::error_1(&"Failed to get canvas context".into());
+                return;
+            }
+        };
+
+        // Scale and draw
+        context
+            .scale(resolution_multiplier as f64, resolution_multiplier as f64)
+            .ok();
+        context
+            .draw_image_with_html_canvas_element(&canvas, 0.0, 0.0)
+            .ok();
+
+        temp_canvas
+    } else {
+        canvas
+    };
+
+    // Convert to data URL
+    let mime_type = if format == "jpeg" {
+        "image/jpeg"
+    } else {
+        "image/png"
+    };
+
+    let data_url = match export_canvas.to_data_url_with_type(mime_type) {
+        Ok(url) => url,
+        Err(_) => {
+            web_sys::console::error_1(&"Failed to convert canvas to data URL".into());
+            return;
+        }
+    };
+
+    // Create download link
+    let a = match document.create_element("a") {
+        Ok(element) => element,
+        Err(_) => {
+            web_sys::console::error_1(&"Failed to create anchor element".into());
+            return;
+        }
+    };
+
+    let extension = if format == "jpeg" { "jpg" } else { "png" };
+    let filename = format!("topology-export.{}", extension);
+
+    a.set_attribute("href", &data_url).ok();
+    a.set_attribute("download", &filename).ok();
+
+    // Trigger download
+    if let Some(html_element) = a.dyn_ref::<web_sys::HtmlElement>() {
+        html_element.click();
+    }
+
+    web_sys::console::log_1(
+        &format!(
+            "Exported as {} at {}x resolution",
+            format, resolution_multiplier
+        )
+        .into(),
+    );
+}
+
+/// Export topology as a versioned `TopologyDocument` JSON file
+#[cfg(feature = "hydrate")]
+async fn export_topology_json(
+    topology_id: i64,
+    view_settings: ViewSettings,
+    camera: Option<CameraView>,
+) {
+    use crate::models::TopologyDocument;
+    use wasm_bindgen::JsCast;
+
+    // Fetch full topology data from server
+    let topology_data = match get_topology_full(topology_id).await {
+        Ok(data) => data,
+        Err(e) => {
+            web_sys::console::error_1(&format!("Failed to fetch topology data: {}", e).into());
+            return;
+        }
+    };
+
+    // Wrap in the versioned document envelope and serialize with pretty formatting
+    let topology_document = TopologyDocument::new(topology_data, Some(view_settings), camera);
+    let json_string = match topology_document.to_json_pretty() {
+        Ok(json) => json,
+        Err(e) => {
+            web_sys::console::error_1(&e.into());
+            return;
+        }
+    };
+
+    let window = match web_sys::window() {
+        Some(w) => w,
+        None => {
+            web_sys::console::error_1(&"No window available".into());
+            return;
+        }
+    };
+
+    let document = match window.document() {
+        Some(d) => d,
+        None => {
+            web_sys::console::error_1(&"No document available".into());
+            return;
+        }
+    };
+
+    // Create a blob with the JSON data
+    let blob_parts = js_sys::Array::new();
+    blob_parts.push(&wasm_bindgen::JsValue::from_str(&json_string));
+
+    let blob_options = web_sys::BlobPropertyBag::new();
+    blob_options.set_type("application/json");
+
+    let blob = match web_sys::Blob::new_with_str_sequence_and_options(&blob_parts, &blob_options) {
+        Ok(b) => b,
+        Err(_) => {
+            web_sys::console::error_1(&"Failed to create blob".into());
+            return;
+        }
+    };
+
+    // Create object URL for the blob
+    let url = match web_sys::Url::create_object_url_with_blob(&blob) {
+        Ok(u) => u,
+        Err(_) => {
+            web_sys::console::error_1(&"Failed to create object URL".into());
+            return;
+        }
+    };
+
+    // Create download link
+    let a = match document.create_element("a") {
+        Ok(element) => element,
+        Err(_) => {
+            web_sys::console::error_1(&"Failed to create anchor element".into());
+            return;
+        }
+    };
+
+    // Generate filename with topology name and timestamp
+    let timestamp = js_sys::Date::new_0().get_time() as i64;
+    let filename = format!(
+        "topology-{}-{}.json",
+        topology_document.topology.name.replace(" ", "_").to_lowercase(),
+        timestamp
+    );
+
+    a.set_attribute("href", &url).ok();
+    a.set_attribute("download", &filename).ok();
+
+    // Trigger download
+    if let Some(html_element) = a.dyn_ref::<web_sys::HtmlElement>() {
+        html_element.click();
+    }
+
+    // Clean up object URL
+    web_sys::Url::revoke_object_url(&url).ok();
+
+    web_sys::console::log_1(&format!("Exported topology as {}", filename).into());
+}
+
+/// Compare dropdown - diff the current topology against a revision or another topology
+#[component]
+fn CompareDropdown() -> impl IntoView {
+    let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
+    let refetch_trigger = use_context::<RwSignal<u32>>().expect("refetch_trigger context");
+    let compare_mode = use_context::<CompareMode>().expect("compare_mode context");
+
+    let show_dropdown = RwSignal::new(false);
+    let base_selection = RwSignal::new(String::new()); // "revision:<id>" or "topology:<id>"
+    let error = RwSignal::new(None::<String>);
+
+    // Close dropdown when clicking outside
+    #[cfg(feature = "hydrate")]
+    {
+        use wasm_bindgen::JsCast;
+        use web_sys::MouseEvent;
+
+        let show_dropdown_clone = show_dropdown;
+        Effect::new(move || {
+            if show_dropdown_clone.get() {
+                let window = web_sys::window().expect("no window");
+                let document = window.document().expect("no document");
+
+                let closure = wasm_bindgen::prelude::Closure::wrap(Box::new(move |_: MouseEvent| {
+                    show_dropdown_clone.set(false);
+                })
+                    as Box<dyn Fn(MouseEvent)>);
+
+                document
+                    .add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())
+                    .ok();
+                closure.forget();
+            }
+        });
+    }
+
+    // Candidate bases: revisions of this topology and the other topologies
+    let candidates = LocalResource::new(move || {
+        let topology_id = current_topology_id.get();
+        let _ = show_dropdown.get(); // Reload when the dropdown opens
+        async move {
+            let revisions = get_topology_revisions(topology_id).await.unwrap_or_default();
+            let topologies = get_topologies().await.unwrap_or_default();
+            (revisions, topologies)
+        }
+    });
+
+    // Leave compare mode when switching topologies
+    Effect::new(move || {
+        let _ = current_topology_id.get();
+        if compare_mode.diff.get_untracked().is_some() {
+            compare_mode.diff.set(None);
+            refetch_trigger.update(|v| *v += 1);
+        }
+    });
+
+    let compare_action = Action::new(move |selection: &String| {
+        let topology_id = current_topology_id.get_untracked();
+        let base = match selection.split_once(':') {
+            Some(("revision", id)) => id.parse().ok().map(|id| DiffSource { topology_id, revision_id: Some(id) }),
+            Some(("topology", id)) => id.parse().ok().map(|id| DiffSource { topology_id: id, revision_id: None }),
+            _ => None,
+        };
+        async move {
+            let base = base.ok_or_else(|| AppError::invalid("Select something to compare against"))?;
+            compare_topologies(base, DiffSource { topology_id, revision_id: None }).await
+        }
+    });
+
+    // Tint the viewport with the diff
+    Effect::new(move || match compare_action.value().get() {
+        Some(Ok(diff)) => {
+            error.set(None);
+            compare_mode.diff.set(Some(diff));
+            refetch_trigger.update(|v| *v += 1);
+        }
+        Some(Err(e)) => error.set(Some(e.to_string())),
+        None => {}
+    });
+
+    let exit_compare = move || {
+        compare_mode.diff.set(None);
+        refetch_trigger.update(|v| *v += 1);
+    };
+
+    let kind_badge = |kind: ChangeKind| match kind {
+        ChangeKind::Added => ("+", "text-green-400"),
+        ChangeKind::Removed => ("−", "text-red-400"),
+        ChangeKind::Modified => ("~", "text-amber-400"),
+    };
+
+    view! {
+        <div class="relative mr-2">
+            <button
+                class=move || if compare_mode.diff.get().is_some() {
+                    "px-4 py-2 bg-amber-600 hover:bg-amber-700 rounded text-sm font-medium transition flex items-center gap-2"
+                } else {
+                    "px-4 py-2 bg-gray-700 hover:bg-gray-600 rounded text-sm font-medium transition flex items-center gap-2"
+                }
+                on:click=move |e| {
+                    e.stop_propagation();
+                    show_dropdown.update(|v| *v = !*v);
+                }
+                title="Compare this topology with a revision or another topology"
+            >
+                "Compare"
+                <span class="text-xs">"▼"</span>
+            </button>
+
+            {move || {
+                if show_dropdown.get() {
+                    Some(view! {
+                        <div
+                            class="absolute right-0 mt-2 w-96 bg-gray-800 border border-gray-700 rounded-lg shadow-lg z-[9999]"
+                            on:click=move |e| e.stop_propagation()
+                        >
+                            <div class="p-3 space-y-3">
+                                <div>
+                                    <label class="block text-xs font-medium text-gray-400 mb-1.5">"Compare Against"</label>
+                                    <div class="flex gap-2">
+                                        <select
+                                            class="flex-1 min-w-0 px-3 py-1.5 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
+                                            on:change=move |ev| base_selection.set(event_target_value(&ev))
+                                            prop:value=move || base_selection.get()
+                                        >
+                                            <option value="">"Select..."</option>
+                                            <Suspense fallback=|| ()>
+                                                {move || candidates.get().map(|(revisions, topologies)| {
+                                                    let current_id = current_topology_id.get_untracked();
+                                                    view! {
+                                                        <optgroup label="Revisions">
+                                                            {revisions.into_iter().map(|revision| view! {
+                                                                <option value=format!("revision:{}", revision.id)>{revision.name}</option>
+                                                            }).collect_view()}
+                                                        </optgroup>
+                                                        <optgroup label="Topologies">
+                                                            {topologies.into_iter().filter(|t| t.id != current_id).map(|topology| view! {
+                                                                <option value=format!("topology:{}", topology.id)>{topology.name}</option>
+                                                            }).collect_view()}
+                                                        </optgroup>
+                                                    }
+                                                })}
+                                            </Suspense>
+                                        </select>
+                                        <button
+                                            class="px-3 py-1.5 bg-blue-600 hover:bg-blue-700 rounded text-sm font-medium transition disabled:opacity-50 disabled:cursor-not-allowed"
+                                            on:click=move |_| { compare_action.dispatch(base_selection.get_untracked()); }
+                                            disabled=move || base_selection.get().is_empty() || compare_action.pending().get()
+                                        >
+                                            "Compare"
+                                        </button>
+                                    </div>
+                                </div>
+
+                                {move || error.get().map(|msg| view! {
+                                    <div class="text-xs text-red-400 text-center">"✗ " {msg}</div>
+                                })}
+
+                                // Change list (base -> current topology)
+                                {move || compare_mode.diff.get().map(|diff| {
+                                    if diff.is_empty() {
+                                        return view! {
+                                            <div class="text-xs text-gray-400 text-center">"No differences"</div>
+                                        }.into_any();
+                                    }
+                                    let summary = format!(
+                                        "Nodes: +{} −{} ~{}   Connections: +{} −{} ~{}",
+                                        diff.node_count(ChangeKind::Added),
+                                        diff.node_count(ChangeKind::Removed),
+                                        diff.node_count(ChangeKind::Modified),
+                                        diff.connection_count(ChangeKind::Added),
+                                        diff.connection_count(ChangeKind::Removed),
+                                        diff.connection_count(ChangeKind::Modified),
+                                    );
+                                    let node_rows = diff.nodes.iter().map(|change| {
+                                        (kind_badge(change.kind), change.name().to_string(), change.changes.clone())
+                                    });
+                                    let connection_rows = diff.connections.iter().map(|change| {
+                                        (kind_badge(change.kind), change.label.clone(), change.changes.clone())
+                                    });
+                                    view! {
+                                        <div class="border-t border-gray-700 pt-3 text-xs">
+                                            <div class="text-gray-300 mb-2">{summary}</div>
+                                            <ul class="max-h-72 overflow-y-auto space-y-1">
+                                                {node_rows.chain(connection_rows).map(|((symbol, color), name, changes)| view! {
+                                                    <li class="p-1.5 bg-gray-700 rounded">
+                                                        <span class=format!("font-bold mr-1 {}", color)>{symbol}</span>
+                                                        <span class="text-gray-200">{name}</span>
+                                                        {changes.into_iter().map(|change| view! {
+                                                            <div class="ml-4 text-gray-400">
+                                                                {format!("{}: {} → {}", change.field, change.before, change.after)}
+                                                            </div>
+                                                        }).collect_view()}
+                                                    </li>
+                                                }).collect_view()}
+                                            </ul>
+                                        </div>
+                                    }.into_any()
+                                })}
+
+                                {move || compare_mode.diff.get().is_some().then(|| view! {
+                                    <button
+                                        class="w-full px-3 py-1.5 bg-gray-700 hover:bg-gray-600 rounded text-sm font-medium transition"
+                                        on:click=move |_| exit_compare()
+                                    >
+                                        "Exit Compare Mode"
+                                    </button>
+                                })}
+                            </div>
+                        </div>
+                    })
+                } else {
+                    None
+                }
+            }}
+        </div>
+    }
+}
+
+/// Revisions dropdown - checkpoint the topology, preview and restore earlier revisions
+#[component]
+fn RevisionsDropdown() -> impl IntoView {
+    let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
+    let refetch_trigger = use_context::<RwSignal<u32>>().expect("refetch_trigger context");
+    let selected_item =
+        use_context::<RwSignal<Option<SelectedItem>>>().expect("selected_item context");
+
+    let show_dropdown = RwSignal::new(false);
+    let revision_name = RwSignal::new(String::new());
+    let revision_note = RwSignal::new(String::new());
+    let preview = RwSignal::new(None::<RevisionPreview>);
+    let status = RwSignal::new(None::<Result<String, String>>);
+    let revisions_trigger = RwSignal::new(0u32);
+
+    // Close dropdown when clicking outside
+    #[cfg(feature = "hydrate")]
+    {
+        use wasm_bindgen::JsCast;
+        use web_sys::MouseEvent;
+
+        let show_dropdown_clone = show_dropdown;
+        Effect::new(move || {
+            if show_dropdown_clone.get() {
+                let window = web_sys::window().expect("no window");
+                let document = window.document().expect("no document");
+
+                let closure = wasm_bindgen::prelude::Closure::wrap(Box::new(move |_: MouseEvent| {
+                    show_dropdown_clone.set(false);
+                })
+                    as Box<dyn Fn(MouseEvent)>);
+
+                document
+                    .add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())
+                    .ok();
+                closure.forget();
+            }
+        });
+    }
+
+    // Load revisions for the current topology (use LocalResource to avoid hydration warnings)
+    let revisions = LocalResource::new(move || {
+        let topology_id = current_topology_id.get();
+        let _ = revisions_trigger.get();
+        async move { get_topology_revisions(topology_id).await.unwrap_or_default() }
+    });
+
+    // Clear the preview when switching topologies
+    Effect::new(move || {
+        let _ = current_topology_id.get();
+        preview.set(None);
+    });
+
+    let create_action = Action::new(move |(name, note): &(String, String)| {
+        let data = CreateRevision {
+            topology_id: current_topology_id.get_untracked(),
+            name: name.clone(),
+            note: Some(note.clone()).filter(|note| !note.trim().is_empty()),
+        };
+        async move { create_topology_revision(data).await }
+    });
+
+    Effect::new(move || match create_action.value().get() {
+        Some(Ok(revision)) => {
+            status.set(Some(Ok(format!("Saved revision '{}'", revision.name))));
+            revision_name.set(String::new());
+            revision_note.set(String::new());
+            revisions_trigger.update(|v| *v += 1);
+        }
+        Some(Err(e)) => status.set(Some(Err(e.to_string()))),
+        None => {}
+    });
+
+    let preview_action = Action::new(move |id: &i64| {
+        let id = *id;
+        async move { get_topology_revision(id).await }
+    });
+
+    Effect::new(move || match preview_action.value().get() {
+        Some(Ok(revision_preview)) => preview.set(Some(revision_preview)),
+        Some(Err(e)) => status.set(Some(Err(e.to_string()))),
+        None => {}
+    });
+
+    let restore_action = Action::new(move |id: &i64| {
+        let id = *id;
+        async move { restore_topology_revision(id).await }
+    });
+
+    // Reload the viewport after a restore
+    Effect::new(move || match restore_action.value().get() {
+        Some(Ok(revision)) => {
+            status.set(Some(Ok(revision.name.clone())));
+            preview.set(None);
+            selected_item.set(None);
+            revisions_trigger.update(|v| *v += 1);
+            refetch_trigger.update(|v| *v += 1);
+        }
+        Some(Err(e)) => status.set(Some(Err(e.to_string()))),
+        None => {}
+    });
+
+    let delete_action = Action::new(move |id: &i64| {
+        let id = *id;
+        async move { delete_topology_revision(id).await }
+    });
+
+    Effect::new(move || {
+        if let Some(result) = delete_action.value().get() {
+            match result {
+                Ok(()) => revisions_trigger.update(|v| *v += 1),
+                Err(e) => status.set(Some(Err(e.to_string()))),
+            }
+        }
+    });
+
+    let save_revision = move || {
+        let name = revision_name.get_untracked();
+        if !name.trim().is_empty() {
+            create_action.dispatch((name, revision_note.get_untracked()));
+        }
+    };
+
+    view! {
+        <div class="relative mr-2">
+            <button
+                class="px-4 py-2 bg-gray-700 hover:bg-gray-600 rounded text-sm font-medium transition flex items-center gap-2"
+                on:click=move |e| {
+                    e.stop_propagation();
+                    show_dropdown.update(|v| *v = !*v);
+                    status.set(None);
+                }
+                title="Save and restore named revisions of this topology"
+            >
+                "Revisions"
+                <span class="text-xs">"▼"</span>
+            </button>
+
+            {move || {
+                if show_dropdown.get() {
+                    Some(view! {
+                        <div
+                            class="absolute right-0 mt-2 w-80 bg-gray-800 border border-gray-700 rounded-lg shadow-lg z-[9999]"
+                            on:click=move |e| e.stop_propagation()
+                        >
+                            <div class="p-3 space-y-3">
+                                // New revision form
+                                <div class="space-y-2">
+                                    <label class="block text-xs font-medium text-gray-400">"Save Current State"</label>
+                                    <input
+                                        type="text"
+                                        placeholder="Revision name"
+                                        class="w-full px-3 py-1.5 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
+                                        prop:value=move || revision_name.get()
+                                        on:input=move |ev| revision_name.set(event_target_value(&ev))
+                                        on:keydown=move |ev| {
+                                            if ev.key() == "Enter" {
+                                                save_revision();
+                                            }
+                                        }
+                                    />
+                                    <textarea
+                                        placeholder="Note (optional)"
+                                        rows="2"
+                                        class="w-full px-3 py-1.5 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
+                                        prop:value=move || revision_note.get()
+                                        on:input=move |ev| revision_note.set(event_target_value(&ev))
+                                    />
+                                    <button
+                                        class="w-full px-3 py-1.5 bg-blue-600 hover:bg-blue-700 rounded text-sm font-medium transition disabled:opacity-50 disabled:cursor-not-allowed"
+                                        on:click=move |_| save_revision()
+                                        disabled=move || revision_name.get().trim().is_empty() || create_action.pending().get()
+                                    >
+                                        "Save Revision"
+                                    </button>
+                                </div>
+
+                                // Revision list
+                                <div class="border-t border-gray-700 pt-3">
+                                    <label class="block text-xs font-medium text-gray-400 mb-1.5">"History"</label>
+                                    <Suspense fallback=move || view! { <div class="text-xs text-gray-500">"Loading..."</div> }>
+                                        {move || revisions.get().map(|revisions| {
+                                            if revisions.is_empty() {
+                                                return view! {
+                                                    <div class="text-xs text-gray-500">"No revisions yet"</div>
+                                                }.into_any();
+                                            }
+                                            view! {
+                                                <div class="max-h-60 overflow-y-auto space-y-1">
+                                                    {revisions.into_iter().map(|revision| {
+                                                        let id = revision.id;
+                                                        view! {
+                                                            <div class="p-2 bg-gray-700 rounded text-xs">
+                                                                <div class="flex items-center justify-between gap-2">
+                                                                    <span class="font-medium text-gray-200 truncate" title=revision.note.clone().unwrap_or_default()>
+                                                                        {revision.name.clone()}
+                                                                    </span>
+                                                                    <span class="text-gray-400 whitespace-nowrap">
+                                                                        {format!("{} nodes, {} links", revision.node_count, revision.connection_count)}
+                                                                    </span>
+                                                                </div>
+                                                                <div class="flex gap-1 mt-1.5">
+                                                                    <button
+                                                                        class="px-2 py-0.5 bg-gray-600 hover:bg-gray-500 rounded transition"
+                                                                        on:click=move |_| { preview_action.dispatch(id); }
+                                                                    >
+                                                                        "Preview"
+                                                                    </button>
+                                                                    <button
+                                                                        class="px-2 py-0.5 bg-green-700 hover:bg-green-600 rounded transition disabled:opacity-50"
+                                                                        on:click=move |_| { restore_action.dispatch(id); }
+                                                                        disabled=move || restore_action.pending().get()
+                                                                    >
+                                                                        "Restore"
+                                                                    </button>
+                                                                    <button
+                                                                        class="px-2 py-0.5 bg-red-700 hover:bg-red-600 rounded transition ml-auto"
+                                                                        on:click=move |_| { delete_action.dispatch(id); }
+                                                                        title="Delete revision"
+                                                                    >
+                                                                        "✗"
+                                                                    </button>
+                                                                </div>
+                                                            </div>
+                                                        }
+                                                    }).collect_view()}
+                                                </div>
+                                            }.into_any()
+                                        })}
+                                    </Suspense>
+                                </div>
+
+                                // Preview of the selected revision
+                                {move || preview.get().map(|RevisionPreview { revision, snapshot }| {
+                                    view! {
+                                        <div class="border-t border-gray-700 pt-3 text-xs">
+                                            <div class="flex items-center justify-between mb-1">
+                                                <span class="font-medium text-gray-300">{format!("Preview: {}", revision.name)}</span>
+                                                <button class="text-gray-400 hover:text-gray-200" on:click=move |_| preview.set(None)>"✗"</button>
+                                            </div>
+                                            {revision.note.map(|note| view! { <p class="text-gray-400 italic mb-1">{note}</p> })}
+                                            <ul class="max-h-32 overflow-y-auto text-gray-400 space-y-0.5">
+                                                {snapshot.nodes.into_iter().map(|node| view! {
+                                                    <li>{format!("{} ({})", node.name, node.node_type)}</li>
+                                                }).collect_view()}
+                                            </ul>
+                                        </div>
+                                    }
+                                })}
+
+                                // Status message
+                                {move || status.get().map(|status| match status {
+                                    Ok(msg) => view! {
+                                        <div class="text-xs text-green-400 text-center">"✓ " {msg}</div>
+                                    }.into_any(),
+                                    Err(msg) => view! {
+                                        <div class="text-xs text-red-400 text-center">"✗ " {msg}</div>
+                                    }.into_any(),
+                                })}
+                            </div>
+                        </div>
+                    })
+                } else {
+                    None
+                }
+            }}
+        </div>
+    }
+}
+
+/// Subnets dropdown: the topology's address plan, duplicate addresses and links between subnets
+#[component]
+fn SubnetsDropdown() -> impl IntoView {
+    let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
+    let refetch_trigger = use_context::<RwSignal<u32>>().expect("refetch_trigger context");
+    let selected_item =
+        use_context::<RwSignal<Option<SelectedItem>>>().expect("selected_item context");
+
+    let show_dropdown = RwSignal::new(false);
+    let cidr = RwSignal::new(String::new());
+    let name = RwSignal::new(String::new());
+    let gateway = RwSignal::new(String::new());
+    let vlan_id = RwSignal::new(String::new());
+    let status = RwSignal::new(None::<Result<String, String>>);
+    let subnets_trigger = RwSignal::new(0u32);
+
+    // Close dropdown when clicking outside
+    #[cfg(feature = "hydrate")]
+    {
+        use wasm_bindgen::JsCast;
+        use web_sys::MouseEvent;
+
+        let show_dropdown_clone = show_dropdown;
+        Effect::new(move || {
+            if show_dropdown_clone.get() {
+                let window = web_sys::window().expect("no window");
+                let document = window.document().expect("no document");
+
+                let closure = wasm_bindgen::prelude::Closure::wrap(Box::new(move |_: MouseEvent| {
+                    show_dropdown_clone.set(false);
+                })
+                    as Box<dyn Fn(MouseEvent)>);
+
+                document
+                    .add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())
+                    .ok();
+                closure.forget();
+            }
+        });
+    }
+
+    // Reload while open, and whenever the topology changes (addresses may have been edited)
+    let report = LocalResource::new(move || {
+        let topology_id = current_topology_id.get();
+        let _ = subnets_trigger.get();
+        let _ = refetch_trigger.get();
+        let open = show_dropdown.get();
+        async move {
+            if open {
+                get_ipam_report(topology_id).await.ok()
+            } else {
+                None
+            }
+        }
+    });
+
+    let create_action = Action::new(move |_: &()| {
+        let data = CreateSubnet {
+            topology_id: current_topology_id.get_untracked(),
+            cidr: cidr.get_untracked(),
+            name: Some(name.get_untracked()),
+            gateway: Some(gateway.get_untracked()).filter(|g| !g.trim().is_empty()),
+            vlan_id: vlan_id.get_untracked().trim().parse().ok(),
+            description: None,
+        };
+        async move { create_subnet(data).await }
+    });
+
+    Effect::new(move || match create_action.value().get() {
+        Some(Ok(subnet)) => {
+            status.set(Some(Ok(format!("Added {}", subnet.cidr))));
+            cidr.set(String::new());
+            name.set(String::new());
+            gateway.set(String::new());
+            vlan_id.set(String::new());
+            subnets_trigger.update(|v| *v += 1);
+        }
+        Some(Err(e)) => status.set(Some(Err(e.to_string()))),
+        None => {}
+    });
+
+    let delete_action = Action::new(move |id: &i64| {
+        let id = *id;
+        async move { delete_subnet(id).await }
+    });
+
+    Effect::new(move || match delete_action.value().get() {
+        Some(Ok(())) => subnets_trigger.update(|v| *v += 1),
+        Some(Err(e)) => status.set(Some(Err(e.to_string()))),
+        None => {}
+    });
+
+    let input_class = "w-full px-2 py-1 bg-gray-700 border border-gray-600 rounded text-xs focus:outline-none focus:border-blue-500";
+
+    view! {
+        <div class="relative mr-2">
+            <button
+                class="px-4 py-2 bg-gray-700 hover:bg-gray-600 rounded text-sm font-medium transition flex items-center gap-2"
+                on:click=move |e| {
+                    e.stop_propagation();
+                    show_dropdown.update(|v| *v = !*v);
+                    status.set(None);
+                }
+                title="Subnets and IP address conflicts"
+            >
+                "Subnets"
+                <span class="text-xs">"▼"</span>
+            </button>
+
+            {move || {
+                if show_dropdown.get() {
+                    Some(view! {
+                        <div
+                            class="absolute right-0 mt-2 w-96 bg-gray-800 border border-gray-700 rounded-lg shadow-lg z-[9999]"
+                            on:click=move |e| e.stop_propagation()
+                        >
+                            <div class="p-3 space-y-3 max-h-[70vh] overflow-y-auto">
+                                // New subnet form
+                                <div class="space-y-1">
+                                    <label class="block text-xs font-medium text-gray-400">"Add Subnet"</label>
+                                    <div class="grid grid-cols-2 gap-1">
+                                        <input
+                                            type="text"
+                                            class=input_class
+                                            placeholder="CIDR (10.0.1.0/24)"
+                                            prop:value=move || cidr.get()
+                                            on:input=move |ev| cidr.set(event_target_value(&ev))
+                                        />
+                                        <input
+                                            type="text"
+                                            class=input_class
+                                            placeholder="Name"
+                                            prop:value=move || name.get()
+                                            on:input=move |ev| name.set(event_target_value(&ev))
+                                        />
+                                        <input
+                                            type="text"
+                                            class=input_class
+                                            placeholder="Gateway (optional)"
+                                            prop:value=move || gateway.get()
+                                            on:input=move |ev| gateway.set(event_target_value(&ev))
+                                        />
+                                        <input
+                                            type="number"
+                                            class=input_class
+                                            placeholder="VLAN (optional)"
+                                            prop:value=move || vlan_id.get()
+                                            on:input=move |ev| vlan_id.set(event_target_value(&ev))
+                                        />
+                                    </div>
+                                    <button
+                                        class="w-full px-3 py-1.5 bg-blue-600 hover:bg-blue-700 rounded text-sm font-medium transition disabled:opacity-50 disabled:cursor-not-allowed"
+                                        on:click=move |_| { create_action.dispatch(()); }
+                                        disabled=move || cidr.get().trim().is_empty() || create_action.pending().get()
+                                    >
+                                        "Add Subnet"
+                                    </button>
+                                </div>
+
+                                <Suspense fallback=move || view! { <div class="text-xs text-gray-500">"Loading..."</div> }>
+                                    {move || report.get().flatten().map(|report| view! {
+                                        // Subnet list with usage
+                                        <div class="border-t border-gray-700 pt-3">
+                                            <label class="block text-xs font-medium text-gray-400 mb-1.5">"Subnets"</label>
+                                            {if report.subnets.is_empty() {
+                                                view! { <div class="text-xs text-gray-500">"No subnets defined"</div> }.into_any()
+                                            } else {
+                                                view! {
+                                                    <div class="space-y-1">
+                                                        {report.subnets.into_iter().map(|usage| {
+                                                            let id = usage.subnet.id;
+                                                            let details = [
+                                                                Some(usage.subnet.name.clone()).filter(|n| !n.is_empty()),
+                                                                usage.subnet.vlan_id.map(|vlan| format!("VLAN {}", vlan)),
+                                                                usage.subnet.gateway.clone().map(|gw| format!("gw {}", gw)),
+                                                            ]
+                                                            .into_iter()
+                                                            .flatten()
+                                                            .collect::<Vec<_>>()
+                                                            .join(" · ");
+                                                            view! {
+                                                                <div class="p-2 bg-gray-700 rounded text-xs">
+                                                                    <div class="flex items-center justify-between gap-2">
+                                                                        <span class="font-mono text-gray-200">{usage.subnet.cidr.clone()}</span>
+                                                                        <span class="text-gray-400 whitespace-nowrap">
+                                                                            {format!("{} / {} used", usage.used, usage.capacity)}
+                                                                        </span>
+                                                                        <button
+                                                                            class="text-gray-400 hover:text-red-400"
+                                                                            title="Delete subnet (addresses are kept)"
+                                                                            on:click=move |_| { delete_action.dispatch(id); }
+                                                                        >"✗"</button>
+                                                                    </div>
+                                                                    <div class="flex justify-between text-[10px] text-gray-500 mt-0.5">
+                                                                        <span class="truncate">{details}</span>
+                                                                        <span>
+                                                                            {usage.next_free.map(|ip| format!("next free {}", ip)).unwrap_or_else(|| "full".to_string())}
+                                                                        </span>
+                                                                    </div>
+                                                                </div>
+                                                            }
+                                                        }).collect_view()}
+                                                    </div>
+                                                }.into_any()
+                                            }}
+                                        </div>
+
+                                        // Duplicate addresses
+                                        {(!report.conflicts.is_empty()).then(|| view! {
+                                            <div class="border-t border-gray-700 pt-3">
+                                                <label class="block text-xs font-medium text-red-400 mb-1.5">"Duplicate Addresses"</label>
+                                                <ul class="space-y-0.5 text-xs text-gray-300">
+                                                    {report.conflicts.into_iter().map(|conflict| view! {
+                                                        <li>
+                                                            <span class="font-mono">{conflict.address}</span>
+                                                            " — "
+                                                            {conflict.holders.iter().map(|holder| holder.label()).collect::<Vec<_>>().join(", ")}
+                                                        </li>
+                                                    }).collect_view()}
+                                                </ul>
+                                            </div>
+                                        })}
+
+                                        // Links whose ends are in different subnets with no router between them
+                                        {(!report.mismatched_links.is_empty()).then(|| view! {
+                                            <div class="border-t border-gray-700 pt-3">
+                                                <label class="block text-xs font-medium text-yellow-400 mb-1.5">"Links Between Subnets Without a Router"</label>
+                                                <ul class="space-y-1 text-xs text-gray-300">
+                                                    {report.mismatched_links.into_iter().map(|link| {
+                                                        let connection_id = link.connection_id;
+                                                        view! {
+                                                            <li>
+                                                                <button
+                                                                    class="text-left hover:text-white"
+                                                                    title="Select connection"
+                                                                    on:click=move |_| selected_item.set(Some(SelectedItem::Connection(connection_id)))
+                                                                >
+                                                                    {format!(
+                                                                        "{} ({}) ↔ {} ({})",
+                                                                        link.source.label(), link.source_subnet,
+                                                                        link.target.label(), link.target_subnet,
+                                                                    )}
+                                                                </button>
+                                                            </li>
+                                                        }
+                                                    }).collect_view()}
+                                                </ul>
+                                            </div>
+                                        })}
+
+                                        // Addresses outside the plan, and ones that don't parse
+                                        {(!report.unassigned.is_empty() || !report.invalid.is_empty()).then(|| view! {
+                                            <div class="border-t border-gray-700 pt-3 text-xs text-gray-400 space-y-0.5">
+                                                {report.unassigned.into_iter().map(|holder| view! {
+                                                    <div>{format!("{}: {} is outside every subnet", holder.label(), holder.address)}</div>
+                                                }).collect_view()}
+                                                {report.invalid.into_iter().map(|holder| view! {
+                                                    <div class="text-red-400">{format!("{}: '{}' is not a valid address", holder.label(), holder.address)}</div>
+                                                }).collect_view()}
+                                            </div>
+                                        })}
+                                    })}
+                                </Suspense>
+
+                                // Status message
+                                {move || status.get().map(|status| match status {
+                                    Ok(msg) => view! {
+                                        <div class="text-xs text-green-400 text-center">"✓ " {msg}</div>
+                                    }.into_any(),
+                                    Err(msg) => view! {
+                                        <div class="text-xs text-red-400 text-center">"✗ " {msg}</div>
+                                    }.into_any(),
+                                })}
+                            </div>
+                        </div>
+                    })
+                } else {
+                    None
+                }
+            }}
+        </div>
     }
-
-    // Clean up object URL
-    web_sys::Url::revoke_object_url(&url).ok();
-
-    web_sys::console::log_1(&format!("Exported topology as {}", filename).into());
 }
 
 /// Import dropdown menu for importing JSON topology data
@@ -1263,7 +3470,10 @@ fn ImportDropdown() -> impl IntoView {
     let refetch_trigger = use_context::<RwSignal<u32>>().expect("refetch_trigger context");
     let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
     let topology_list_trigger =
-        use_context::<RwSignal<u32>>().expect("topology_list_trigger context");
+        use_context::<TopologyListTrigger>().expect("topology_list_trigger context").0;
+    let viewport_visibility = use_context::<ViewportVisibility>().expect("viewport_visibility context");
+    let lighting_settings = use_context::<LightingSettings>().expect("lighting_settings context");
+    let camera_controls = use_context::<CameraControls>().expect("camera_controls context");
 
     // Close dropdown when clicking outside
     #[cfg(feature = "hydrate")]
@@ -1290,32 +3500,54 @@ fn ImportDropdown() -> impl IntoView {
         });
     }
 
-    // Import action
+    // Import action - validation and inserts happen server-side in one transaction
     let import_action = Action::new(move |file_content: &String| {
         let content = file_content.clone();
-        async move {
-            #[cfg(feature = "hydrate")]
-            {
-                import_topology_json(content).await
-            }
-            #[cfg(not(feature = "hydrate"))]
-            {
-                let _unused = content; // Suppress unused warning
-                Err::<ImportResult, String>("Import not available on server".to_string())
-            }
-        }
+        async move { import_topology(content).await }
     });
 
     // Handle import success - switch to new topology and trigger refresh
     Effect::new(move || {
-        if let Some(Ok(result)) = import_action.value().get() {
-            import_status.set(Some(Ok(result.message.clone())));
+        if let Some(Ok(report)) = import_action.value().get() {
+            let mut message = format!(
+                "Imported '{}' with {} nodes and {} connections",
+                report.topology.name, report.nodes_created, report.connections_created
+            );
+            if !report.rejected.is_empty() {
+                message.push_str(&format!(" ({} skipped)", report.rejected.len()));
+                #[cfg(feature = "hydrate")]
+                for rejection in &report.rejected {
+                    web_sys::console::warn_1(
+                        &format!(
+                            "Import skipped {} {}: {}",
+                            rejection.entity_type, rejection.source_id, rejection.reason
+                        )
+                        .into(),
+                    );
+                }
+            }
+            if !report.warnings.is_empty() {
+                message.push_str(&format!(" ({} with defaulted values)", report.warnings.len()));
+                #[cfg(feature = "hydrate")]
+                for warning in &report.warnings {
+                    web_sys::console::warn_1(
+                        &format!("Import {} {}: {}", warning.entity_type, warning.source_id, warning.message).into(),
+                    );
+                }
+            }
+            import_status.set(Some(Ok(message)));
+
+            // Restore the view settings and camera saved with the document
+            if let Some(settings) = &report.view_settings {
+                viewport_visibility.apply_view_settings(&lighting_settings, settings);
+            }
+            camera_controls.pending_view.set(report.camera);
 
             // First, refresh the topology list dropdown
             topology_list_trigger.update(|v| *v += 1);
 
             // Then, after a brief delay to allow the list to update, switch to the new topology
-            let new_topology_id = result.topology_id;
+            let new_topology_id = report.topology.id;
             spawn_local(async move {
                 #[cfg(feature = "hydrate")]
                 {
@@ -1442,116 +3674,6 @@ fn ImportDropdown() -> impl IntoView {
     }
 }
 
-/// Result type for import operation (available in both SSR and hydrate contexts)
-#[derive(Clone, Debug)]
-struct ImportResult {
-    topology_id: i64,
-    message: String,
-}
-
-/// Import topology from JSON content
-#[cfg(feature = "hydrate")]
-async fn import_topology_json(json_content: String) -> Result<ImportResult, String> {
-    use crate::models::TopologyFull;
-
-    // Parse JSON
-    let topology_data: TopologyFull =
-        serde_json::from_str(&json_content).map_err(|e| format!("Invalid JSON format: {}", e))?;
-
-    // Validate topology data
-    if topology_data.nodes.is_empty() {
-        return Err("Topology must contain at least one node".to_string());
-    }
-
-    // Create new topology with imported name (add "Imported" prefix to avoid conflicts)
-    let new_topology_name = format!("Imported {}", topology_data.topology.name);
-    let new_topology = CreateTopology {
-        name: new_topology_name.clone(),
-        description: topology_data.topology.description.clone(),
-    };
-
-    let created_topology = create_topology(new_topology)
-        .await
-        .map_err(|e| format!("Failed to create topology: {}", e))?;
-
-    let new_topology_id = created_topology.id;
-
-    // Create a mapping from old node IDs to new node IDs
-    let mut node_id_map = std::collections::HashMap::new();
-
-    // Import nodes
-    for node in &topology_data.nodes {
-        let create_node_data = CreateNode {
-            topology_id: new_topology_id,
-            name: node.name.clone(),
-            node_type: node.node_type.clone(),
-            ip_address: node.ip_address.clone(),
-            position_x: Some(node.position_x),
-            position_y: Some(node.position_y),
-            position_z: Some(node.position_z),
-            rotation_x: Some(node.rotation_x),
-            rotation_y: Some(node.rotation_y),
-            rotation_z: Some(node.rotation_z),
-            scale: Some(node.scale),
-            color: Some(node.color.clone()),
-            visible: Some(node.visible), // Preserve visibility when importing
-            vendor: Some(node.vendor.clone()),
-            model_name: Some(node.model_name.clone()),
-            metadata: node.metadata.clone(),
-        };
-
-        let created_node = create_node(create_node_data)
-            .await
-            .map_err(|e| format!("Failed to create node '{}': {}", node.name, e))?;
-
-        node_id_map.insert(node.id, created_node.id);
-    }
-
-    // Import connections
-    for connection in &topology_data.connections {
-        // Map old node IDs to new node IDs
-        let new_source_id = node_id_map.get(&connection.source_node_id).ok_or_else(|| {
-            format!(
-                "Source node ID {} not found in mapping",
-                connection.source_node_id
-            )
-        })?;
-        let new_target_id = node_id_map.get(&connection.target_node_id).ok_or_else(|| {
-            format!(
-                "Target node ID {} not found in mapping",
-                connection.target_node_id
-            )
-        })?;
-
-        let create_conn_data = CreateConnection {
-            topology_id: new_topology_id,
-            source_node_id: *new_source_id,
-            target_node_id: *new_target_id,
-            connection_type: Some(connection.connection_type.clone()),
-            bandwidth_mbps: connection.bandwidth_mbps,
-            latency_ms: connection.latency_ms,
-            baseline_packet_loss_pct: connection.baseline_packet_loss_pct,
-            status: Some(connection.status.clone()),
-            color: Some(connection.color.clone()),
-            metadata: connection.metadata.clone(),
-        };
-
-        create_connection_fn(create_conn_data)
-            .await
-            .map_err(|e| format!("Failed to create connection: {}", e))?;
-    }
-
-    Ok(ImportResult {
-        topology_id: new_topology_id,
-        message: format!(
-            "Successfully imported '{}' with {} nodes and {} connections",
-            new_topology_name,
-            topology_data.nodes.len(),
-            topology_data.connections.len()
-        ),
-    })
-}
-
 /// Left device palette/toolbar
 #[component]
 fn DevicePalette() -> impl IntoView {
@@ -1589,19 +3711,19 @@ fn DevicePalette() -> impl IntoView {
 
     // Device type configurations: (Display Name, Icon Path, type_id, name_prefix)
     let device_types = vec![
-        ("Routers", "/icons/vendors/router_white.png", "router", "Router"),
-        ("Switches", "/icons/vendors/switch_white.png", "switch", "Switch"),
-        ("Servers", "/icons/vendors/server_white.png", "server", "Server"),
-        ("Firewalls", "/icons/vendors/firewall_white.png", "firewall", "Firewall"),
-        ("Load Balancers", "/icons/vendors/loadbalancer_white.png", "load_balancer", "LoadBalancer"),
-        ("Clouds", "/icons/vendors/cloud_white.png", "cloud", "Cloud"),
-        ("Applications", "/icons/vendors/application_white.png", "application", "Application"),
+        ("Routers", "/icons/vendors/router_white.png", NodeType::Router, "Router"),
+        ("Switches", "/icons/vendors/switch_white.png", NodeType::Switch, "Switch"),
+        ("Servers", "/icons/vendors/server_white.png", NodeType::Server, "Server"),
+        ("Firewalls", "/icons/vendors/firewall_white.png", NodeType::Firewall, "Firewall"),
+        ("Load Balancers", "/icons/vendors/loadbalancer_white.png", NodeType::LoadBalancer, "LoadBalancer"),
+        ("Clouds", "/icons/vendors/cloud_white.png", NodeType::Cloud, "Cloud"),
+        ("Applications", "/icons/vendors/application_white.png", NodeType::Application, "Application"),
     ];
 
     // Action to create a node
     let create_node_action = Action::new(
-        move |(node_type, name_prefix, vendor, model_name): &(String, String, String, String)| {
-            let node_type = node_type.clone();
+        move |(node_type, name_prefix, vendor, model_name): &(NodeType, String, String, String)| {
+            let node_type = *node_type;
             let name_prefix = name_prefix.clone();
             let vendor = vendor.clone();
             let model_name = model_name.clone();
@@ -1639,6 +3761,7 @@ fn DevicePalette() -> impl IntoView {
                     scale: None,      // Will use default 1.0
                     color: None,      // Will use default blue
                     visible: None,    // Will use default true
+                    group_id: None,
                     metadata: None,
                 };
 
@@ -1663,7 +3786,6 @@ fn DevicePalette() -> impl IntoView {
 
             <div class="flex-1 overflow-y-auto p-2 space-y-2">
                 {device_types.into_iter().enumerate().map(|(index, (display_name, icon, type_id, name_prefix))| {
-                    let type_id_stored = StoredValue::new(type_id.to_string());
                     let name_prefix_stored = StoredValue::new(name_prefix.to_string());
 
                     // Track dropdown state for this device type
@@ -1701,7 +3823,7 @@ fn DevicePalette() -> impl IntoView {
                                     </div>
                                 }>
                                     <VendorDropdown
-                                        node_type=type_id_stored.get_value()
+                                        node_type=type_id
                                         name_prefix=name_prefix_stored.get_value()
                                         create_node_action=create_node_action
                                         dropdown_open=dropdown_open
@@ -1736,6 +3858,8 @@ fn DevicePalette() -> impl IntoView {
             // Scene Objects Panel (Blender-style outliner)
             <div class="p-2 border-t border-gray-700">
                 <div class="text-xs font-semibold text-gray-300 mb-2">"Scene Objects"</div>
+                <FilterBar />
+                <GroupsOutliner />
                 <div class="max-h-48 overflow-y-auto space-y-1">
                     <Suspense fallback=|| view! { <div class="text-xs text-gray-400">"Loading..."</div> }>
                         {move || {
@@ -1906,6 +4030,387 @@ fn DevicePalette() -> impl IntoView {
     }
 }
 
+/// Filter query box: highlight the matching nodes and connections, or show only them
+#[component]
+fn FilterBar() -> impl IntoView {
+    let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
+    let refetch_trigger = use_context::<RwSignal<u32>>().expect("refetch_trigger context");
+    let highlight = use_context::<FilterHighlight>().expect("filter_highlight context");
+
+    let query = RwSignal::new(String::new());
+    let error = RwSignal::new(None::<String>);
+    let status = RwSignal::new(None::<String>);
+
+    let tags = LocalResource::new(move || {
+        let topology_id = current_topology_id.get();
+        refetch_trigger.track();
+        async move { get_tags(topology_id).await.unwrap_or_default() }
+    });
+
+    let highlight_action = Action::new(move |query: &String| {
+        let (topology_id, query) = (current_topology_id.get_untracked(), query.clone());
+        async move { filter_topology(topology_id, query).await }
+    });
+
+    Effect::new(move || match highlight_action.value().get() {
+        Some(Ok(matches)) => {
+            error.set(None);
+            status.set(Some(match_summary(&matches)));
+            highlight.matches.set(Some(matches));
+            refetch_trigger.update(|v| *v += 1);
+        }
+        Some(Err(e)) => error.set(Some(e.to_string())),
+        None => {}
+    });
+
+    let show_only_action = Action::new(move |query: &String| {
+        let (topology_id, query) = (current_topology_id.get_untracked(), query.clone());
+        async move { apply_filter_visibility(topology_id, query).await }
+    });
+
+    Effect::new(move || match show_only_action.value().get() {
+        Some(Ok(matches)) => {
+            error.set(None);
+            status.set(Some(format!("Showing {}", match_summary(&matches))));
+            highlight.matches.set(None);
+            refetch_trigger.update(|v| *v += 1);
+        }
+        Some(Err(e)) => error.set(Some(e.to_string())),
+        None => {}
+    });
+
+    let delete_tag_action = Action::new(move |id: &i64| {
+        let id = *id;
+        async move { delete_tag(id).await }
+    });
+
+    Effect::new(move || {
+        if let Some(Ok(())) = delete_tag_action.value().get() {
+            refetch_trigger.update(|v| *v += 1);
+        }
+    });
+
+    let button_class = "px-2 py-1 bg-gray-700 hover:bg-gray-600 rounded text-xs transition disabled:opacity-50";
+
+    view! {
+        <div class="mb-2">
+            <input
+                type="text"
+                class="w-full px-2 py-1 bg-gray-700 border border-gray-600 rounded text-xs font-mono focus:outline-none focus:border-blue-500"
+                placeholder="type:router AND tag:core"
+                title="Fields: name, type, tag, vendor, model, ip, group, status, node, is (node/link). Combine with AND, OR, NOT or -, group with ( ), * is a wildcard."
+                prop:value=move || query.get()
+                on:input=move |ev| query.set(event_target_value(&ev))
+                on:keydown=move |ev| {
+                    if ev.key() == "Enter" {
+                        highlight_action.dispatch(query.get_untracked());
+                    }
+                }
+            />
+            <div class="grid grid-cols-3 gap-1 mt-1">
+                <button
+                    class=button_class
+                    disabled=move || highlight_action.pending().get()
+                    on:click=move |_| { highlight_action.dispatch(query.get_untracked()); }
+                >
+                    "Highlight"
+                </button>
+                <button
+                    class=button_class
+                    title="Hide every node that doesn't match (undoable)"
+                    disabled=move || show_only_action.pending().get() || query.get().trim().is_empty()
+                    on:click=move |_| { show_only_action.dispatch(query.get_untracked()); }
+                >
+                    "Show Only"
+                </button>
+                <button
+                    class=button_class
+                    title="Clear the highlight and show every node"
+                    disabled=move || show_only_action.pending().get()
+                    on:click=move |_| {
+                        highlight.matches.set(None);
+                        status.set(None);
+                        show_only_action.dispatch(String::new());
+                    }
+                >
+                    "Show All"
+                </button>
+            </div>
+            {move || status.get().map(|status| view! {
+                <div class="mt-1 text-[10px] text-gray-400">{status}</div>
+            })}
+            {move || error.get().map(|e| view! {
+                <div class="mt-1 text-xs text-red-400">{e}</div>
+            })}
+
+            // Tags in use: click to filter by one
+            {move || tags.get().filter(|tags| !tags.is_empty()).map(|tags| view! {
+                <div class="flex flex-wrap gap-1 mt-1">
+                    {tags.into_iter().map(|tag| {
+                        let tag_id = tag.id;
+                        let filter = tag_query(&tag.name);
+                        view! {
+                            <span class="inline-flex items-center bg-gray-700 rounded text-[10px] text-gray-300">
+                                <button
+                                    class="px-1.5 py-0.5 hover:text-white"
+                                    on:click=move |_| {
+                                        query.set(filter.clone());
+                                        highlight_action.dispatch(filter.clone());
+                                    }
+                                >
+                                    {format!("#{}", tag.name)}
+                                </button>
+                                <button
+                                    class="pr-1 text-gray-500 hover:text-red-400"
+                                    title="Delete this tag from every node and connection"
+                                    on:click=move |_| { delete_tag_action.dispatch(tag_id); }
+                                >
+                                    "×"
+                                </button>
+                            </span>
+                        }
+                    }).collect_view()}
+                </div>
+            })}
+        </div>
+    }
+}
+
+/// "2 nodes, 1 connection"
+fn match_summary(matches: &FilterMatches) -> String {
+    let plural = |count: usize, noun: &str| format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" });
+    format!(
+        "{}, {}",
+        plural(matches.node_ids.len(), "node"),
+        plural(matches.connection_ids.len(), "connection")
+    )
+}
+
+/// Filter query matching one tag, quoted when the name needs it
+fn tag_query(name: &str) -> String {
+    if name.contains(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | ':')) {
+        format!("tag:\"{}\"", name)
+    } else {
+        format!("tag:{}", name)
+    }
+}
+
+/// Comma-separated tags of a node or connection, saved when the field changes
+#[component]
+fn TagsEditor(#[prop(optional)] node_id: Option<i64>, #[prop(optional)] connection_id: Option<i64>) -> impl IntoView {
+    let refetch_trigger = use_context::<RwSignal<u32>>().expect("refetch_trigger context");
+
+    let text = RwSignal::new(String::new());
+
+    let tags = LocalResource::new(move || {
+        refetch_trigger.track();
+        async move {
+            match (node_id, connection_id) {
+                (Some(id), _) => get_node_tags(id).await,
+                (None, Some(id)) => get_connection_tags(id).await,
+                (None, None) => Ok(Vec::new()),
+            }
+        }
+    });
+
+    Effect::new(move || {
+        if let Some(Ok(tags)) = tags.get() {
+            text.set(tags.iter().map(|tag| tag.name.as_str()).collect::<Vec<_>>().join(", "));
+        }
+    });
+
+    let save_action = Action::new(move |list: &String| {
+        let names = parse_tag_list(list);
+        async move {
+            match (node_id, connection_id) {
+                (Some(id), _) => set_node_tags(id, names).await,
+                (None, Some(id)) => set_connection_tags(id, names).await,
+                (None, None) => Ok(Vec::new()),
+            }
+        }
+    });
+
+    Effect::new(move || {
+        if let Some(Ok(_)) = save_action.value().get() {
+            refetch_trigger.update(|v| *v += 1);
+        }
+    });
+
+    view! {
+        <div>
+            <label class="block text-xs font-medium text-gray-400 mb-1">"Tags"</label>
+            <input
+                type="text"
+                class="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
+                placeholder="core, dc1"
+                prop:value=move || text.get()
+                on:input=move |ev| text.set(event_target_value(&ev))
+                on:change=move |ev| { save_action.dispatch(event_target_value(&ev)); }
+            />
+            {move || match save_action.value().get() {
+                Some(Err(e)) => Some(view! { <div class="mt-1 text-xs text-red-400">{e.to_string()}</div> }),
+                _ => None,
+            }}
+        </div>
+    }
+}
+
+/// Group tree in the Scene Objects panel: select, collapse and add groups
+#[component]
+fn GroupsOutliner() -> impl IntoView {
+    let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
+    let refetch_trigger = use_context::<RwSignal<u32>>().expect("refetch_trigger context");
+    let selected_item =
+        use_context::<RwSignal<Option<SelectedItem>>>().expect("selected_item context");
+
+    let name = RwSignal::new(String::new());
+    let group_type = RwSignal::new(GroupType::Site);
+    let parent_id = RwSignal::new(0_i64); // 0 = top level
+    let error = RwSignal::new(None::<String>);
+
+    let groups = LocalResource::new(move || {
+        let topology_id = current_topology_id.get();
+        refetch_trigger.track();
+        async move { get_node_groups(topology_id).await.unwrap_or_default() }
+    });
+
+    let create_action = Action::new(move |_: &()| {
+        let data = CreateNodeGroup {
+            topology_id: current_topology_id.get_untracked(),
+            parent_id: Some(parent_id.get_untracked()).filter(|&id| id != 0),
+            name: name.get_untracked(),
+            group_type: group_type.get_untracked(),
+            color: None,
+        };
+        async move { create_node_group(data).await }
+    });
+
+    Effect::new(move || match create_action.value().get() {
+        Some(Ok(group)) => {
+            error.set(None);
+            name.set(String::new());
+            selected_item.set(Some(SelectedItem::Group(group.id)));
+            refetch_trigger.update(|v| *v += 1);
+        }
+        Some(Err(e)) => error.set(Some(e.to_string())),
+        None => {}
+    });
+
+    // Collapsing swaps the members for a proxy node, so the viewport reloads
+    let collapse_action = Action::new(move |(id, collapsed): &(i64, bool)| {
+        let (id, collapsed) = (*id, *collapsed);
+        async move {
+            let data = UpdateNodeGroup { collapsed: Some(collapsed), ..Default::default() };
+            update_node_group(id, data).await
+        }
+    });
+
+    Effect::new(move || match collapse_action.value().get() {
+        Some(Ok(_)) => refetch_trigger.update(|v| *v += 1),
+        Some(Err(e)) => error.set(Some(e.to_string())),
+        None => {}
+    });
+
+    let input_class = "w-full px-2 py-1 bg-gray-700 border border-gray-600 rounded text-xs focus:outline-none focus:border-blue-500";
+
+    view! {
+        <div class="mb-2">
+            <div class="text-[10px] uppercase tracking-wide text-gray-500 mb-1">"Groups"</div>
+            {move || groups.get().map(|groups| {
+                let tree: Vec<(crate::models::NodeGroup, usize)> =
+                    group_tree(&groups).into_iter().map(|(group, depth)| (group.clone(), depth)).collect();
+                let options = group_options(&groups);
+                view! {
+                    <div class="space-y-0.5 mb-1">
+                        {tree.into_iter().map(|(group, depth)| {
+                            let id = group.id;
+                            let collapsed = group.collapsed;
+                            let is_selected = move || matches!(selected_item.get(), Some(SelectedItem::Group(selected)) if selected == id);
+                            view! {
+                                <div
+                                    class=move || {
+                                        let base = "flex items-center gap-1 px-2 py-0.5 rounded text-xs";
+                                        if is_selected() {
+                                            format!("{} bg-blue-600 text-white", base)
+                                        } else {
+                                            format!("{} hover:bg-gray-700 text-gray-300", base)
+                                        }
+                                    }
+                                    style=format!("padding-left: {}px", 8 + depth * 12)
+                                >
+                                    <span class="w-2 h-2 rounded-sm" style=format!("background-color: {}", group.color.to_css())></span>
+                                    <button
+                                        class="flex-1 text-left truncate"
+                                        title=group.group_type.label()
+                                        on:click=move |_| selected_item.set(Some(SelectedItem::Group(id)))
+                                    >
+                                        {group.name.clone()}
+                                    </button>
+                                    <button
+                                        class="px-1 hover:bg-gray-600 rounded"
+                                        title=if collapsed { "Expand (show members)" } else { "Collapse to a single node" }
+                                        on:click=move |_| { collapse_action.dispatch((id, !collapsed)); }
+                                    >
+                                        {if collapsed { "▸" } else { "▾" }}
+                                    </button>
+                                </div>
+                            }
+                        }).collect_view()}
+                    </div>
+
+                    // New group form
+                    <div class="grid grid-cols-2 gap-1">
+                        <input
+                            type="text"
+                            class=input_class
+                            placeholder="New group"
+                            prop:value=move || name.get()
+                            on:input=move |ev| name.set(event_target_value(&ev))
+                        />
+                        <select
+                            class=input_class
+                            prop:value=move || group_type.get().as_str()
+                            on:change=move |ev| {
+                                if let Ok(value) = event_target_value(&ev).parse() {
+                                    group_type.set(value);
+                                }
+                            }
+                        >
+                            {GroupType::ALL.iter().map(|t| view! {
+                                <option value=t.as_str()>{t.label()}</option>
+                            }).collect_view()}
+                        </select>
+                        <select
+                            class=input_class
+                            prop:value=move || parent_id.get().to_string()
+                            on:change=move |ev| {
+                                if let Ok(id) = event_target_value(&ev).parse::<i64>() {
+                                    parent_id.set(id);
+                                }
+                            }
+                        >
+                            <option value="0">"Top level"</option>
+                            {options.into_iter().map(|(id, label)| view! {
+                                <option value=id.to_string()>{format!("In {}", label)}</option>
+                            }).collect_view()}
+                        </select>
+                        <button
+                            class="px-2 py-1 bg-blue-600 hover:bg-blue-700 rounded text-xs transition disabled:opacity-50"
+                            disabled=move || name.get().trim().is_empty() || create_action.pending().get()
+                            on:click=move |_| { create_action.dispatch(()); }
+                        >
+                            "Add Group"
+                        </button>
+                    </div>
+                }
+            })}
+            {move || error.get().map(|e| view! {
+                <div class="mt-1 text-xs text-red-400">{e}</div>
+            })}
+        </div>
+    }
+}
+
 /// Right properties panel
 #[component]
 fn PropertiesPanel(selected_item: RwSignal<Option<SelectedItem>>) -> impl IntoView {
@@ -1944,6 +4449,9 @@ fn PropertiesPanel(selected_item: RwSignal<Option<SelectedItem>>) -> impl IntoVi
                             Some(SelectedItem::Connection(id)) => view! {
                                 <ConnectionProperties connection_id=id />
                             }.into_any(),
+                            Some(SelectedItem::Group(id)) => view! {
+                                <GroupProperties group_id=id />
+                            }.into_any(),
                             None => view! {
                                 <div class="text-center text-gray-500 mt-8">
                                     <div class="text-4xl mb-2">"📋"</div>
@@ -2014,10 +4522,10 @@ fn PropertiesPanel(selected_item: RwSignal<Option<SelectedItem>>) -> impl IntoVi
                                         <div class="grid grid-cols-3 gap-1">
                                             <button
                                                 class="px-2 py-1 rounded text-[10px] border transition"
-                                                class:bg-gray-600=move || viewport_visibility.background_color.get() == None
-                                                class:border-gray-500=move || viewport_visibility.background_color.get() == None
-                                                class:bg-gray-700=move || viewport_visibility.background_color.get() != None
-                                                class:border-gray-600=move || viewport_visibility.background_color.get() != None
+                                                class:bg-gray-600=move || viewport_visibility.background_color.get().is_none()
+                                                class:border-gray-500=move || viewport_visibility.background_color.get().is_none()
+                                                class:bg-gray-700=move || viewport_visibility.background_color.get().is_some()
+                                                class:border-gray-600=move || viewport_visibility.background_color.get().is_some()
                                                 on:click=move |_| viewport_visibility.background_color.set(None)
                                             >
                                                 "Transparent"
@@ -2235,7 +4743,7 @@ fn NodeProperties(node_id: i64) -> impl IntoView {
 
     // Create signals for editable fields
     let name = RwSignal::new(String::new());
-    let node_type = RwSignal::new(String::new());
+    let node_type = RwSignal::new(NodeType::default());
     let vendor = RwSignal::new(String::from("generic"));
     let model_name = RwSignal::new(String::from("blob-router"));
     let ip_address = RwSignal::new(String::new());
@@ -2246,28 +4754,61 @@ fn NodeProperties(node_id: i64) -> impl IntoView {
     let rotation_y = RwSignal::new(0.0);
     let rotation_z = RwSignal::new(0.0);
     let scale = RwSignal::new(1.0);
-    let color = RwSignal::new(String::from("100,150,255")); // Default blue
+    let color = RwSignal::new(Rgb::NODE_DEFAULT);
+    let group_id = RwSignal::new(0_i64); // 0 = no group
 
     // Track whether initial data has loaded (prevents auto-save during initial load)
     let node_loaded = RwSignal::new(false);
 
-    // Populate signals when data loads
+    // The node as last loaded or saved; saves are rejected if it changed on the server since
+    let base = RwSignal::new(None::<Node>);
+    // The server's node after a rejected save, until the user merges or overwrites
+    let conflict = RwSignal::new(None::<Node>);
+    // An edit made while a save was in flight (saved once that one completes)
+    let save_queued = RwSignal::new(false);
+
     // Native Blender Z-up: Direct 1:1 mapping, no coordinate swapping
+    let populate = move |node: Node| {
+        name.set(node.name);
+        node_type.set(node.node_type);
+        vendor.set(node.vendor);
+        model_name.set(node.model_name);
+        ip_address.set(node.ip_address.unwrap_or_default());
+        position_x.set(node.position_x);
+        position_y.set(node.position_y); // Direct mapping (no swap)
+        position_z.set(node.position_z); // Direct mapping (no swap)
+        rotation_x.set(node.rotation_x);
+        rotation_y.set(node.rotation_y);
+        rotation_z.set(node.rotation_z);
+        scale.set(node.scale);
+        color.set(node.color);
+        group_id.set(node.group_id.unwrap_or(0));
+    };
+
+    // The panel's fields applied to the node they were edited from
+    let edited = move |from: Node| Node {
+        name: name.get_untracked(),
+        node_type: node_type.get_untracked(),
+        vendor: vendor.get_untracked(),
+        model_name: model_name.get_untracked(),
+        ip_address: Some(ip_address.get_untracked()).filter(|s| !s.is_empty()).or(from.ip_address.clone()),
+        position_x: position_x.get_untracked(),
+        position_y: position_y.get_untracked(),
+        position_z: position_z.get_untracked(),
+        rotation_x: rotation_x.get_untracked(),
+        rotation_y: rotation_y.get_untracked(),
+        rotation_z: rotation_z.get_untracked(),
+        scale: scale.get_untracked(),
+        color: color.get_untracked(),
+        group_id: Some(group_id.get_untracked()).filter(|&id| id != 0),
+        ..from
+    };
+
+    // Populate signals when data loads
     Effect::new(move || {
         if let Some(Some(node)) = node_data.get() {
-            name.set(node.name);
-            node_type.set(node.node_type);
-            vendor.set(node.vendor);
-            model_name.set(node.model_name);
-            ip_address.set(node.ip_address.unwrap_or_default());
-            position_x.set(node.position_x);
-            position_y.set(node.position_y); // Direct mapping (no swap)
-            position_z.set(node.position_z); // Direct mapping (no swap)
-            rotation_x.set(node.rotation_x);
-            rotation_y.set(node.rotation_y);
-            rotation_z.set(node.rotation_z);
-            scale.set(node.scale);
-            color.set(node.color);
+            base.set(Some(node.clone()));
+            populate(node);
             // Mark as loaded after initial data population
             node_loaded.set(true);
         }
@@ -2291,20 +4832,67 @@ fn NodeProperties(node_id: i64) -> impl IntoView {
             scale: Some(scale.get_untracked()),
             color: Some(color.get_untracked()),
             visible: None, // Don't update visibility from properties panel
+            group_id: Some(group_id.get_untracked()),
             metadata: None,
+            expected_version: base.get_untracked().map(|node| node.version),
         };
 
         async move { update_node(node_id, update_data).await }
     });
 
-    // Trigger viewport refetch on successful save
+    // One save at a time: each is checked against the node the previous one returned
+    let save = move || {
+        if save_action.pending().get_untracked() {
+            save_queued.set(true);
+        } else {
+            save_action.dispatch(());
+        }
+    };
+
+    // Track the saved node and trigger viewport refetch on successful save
     Effect::new(move || {
-        if let Some(Ok(_)) = save_action.value().get() {
-            // Increment trigger to cause viewport to refetch
-            refetch_trigger.update(|v| *v += 1);
+        match save_action.value().get() {
+            Some(Ok(node)) => {
+                base.set(Some(node));
+                // Increment trigger to cause viewport to refetch
+                refetch_trigger.update(|v| *v += 1);
+            }
+            Some(Err(UpdateError::Conflict(current))) => conflict.set(Some(current)),
+            _ => {}
+        }
+        if save_queued.get_untracked() {
+            save_queued.set(false);
+            if conflict.get_untracked().is_none() {
+                save_action.dispatch(());
+            }
         }
     });
 
+    // Keep the other user's changes to fields this panel didn't edit, then save mine
+    let merge_conflict = move |_: leptos::ev::MouseEvent| {
+        let (Some(current), Some(started_from)) = (conflict.get_untracked(), base.get_untracked()) else {
+            return;
+        };
+        match merge_edits(&started_from, &edited(started_from.clone()), &current) {
+            Ok(merged) => {
+                base.set(Some(current));
+                conflict.set(None);
+                populate(merged);
+                save();
+            }
+            Err(e) => leptos::logging::log!("Failed to merge node edits: {}", e),
+        }
+    };
+
+    // Save the panel's fields as they are, replacing the other user's changes to them
+    let overwrite_conflict = move |_: leptos::ev::MouseEvent| {
+        if let Some(current) = conflict.get_untracked() {
+            base.set(Some(current));
+            conflict.set(None);
+            save();
+        }
+    };
+
     // Auto-save effect: Save when any field changes
     Effect::new(move || {
         // Track all editable fields
@@ -2321,10 +4909,37 @@ fn NodeProperties(node_id: i64) -> impl IntoView {
         let _rot_z = rotation_z.get();
         let _scale_val = scale.get();
         let _color_val = color.get();
+        let _group_val = group_id.get();
 
-        // Only auto-save if data has been loaded (prevents save during initial load)
-        if node_loaded.get() {
-            save_action.dispatch(());
+        // Only auto-save if data has been loaded (prevents save during initial load),
+        // and not while a conflict waits for the user to merge or overwrite
+        if node_loaded.get() && conflict.get_untracked().is_none() {
+            save();
+        }
+    });
+
+    // Subnets offered for address allocation
+    let subnets = LocalResource::new(move || {
+        let topology_id = current_topology_id.get();
+        async move { get_subnets(topology_id).await.unwrap_or_default() }
+    });
+
+    // Groups the node can join
+    let groups = LocalResource::new(move || {
+        let topology_id = current_topology_id.get();
+        refetch_trigger.track();
+        async move { get_node_groups(topology_id).await.unwrap_or_default() }
+    });
+
+    let next_free_action = Action::new(move |subnet_id: &i64| {
+        let subnet_id = *subnet_id;
+        async move { next_free_ip(subnet_id).await }
+    });
+
+    // Filling in the address auto-saves it like typing would
+    Effect::new(move || {
+        if let Some(Ok(Some(address))) = next_free_action.value().get() {
+            ip_address.set(address);
         }
     });
 
@@ -2361,29 +4976,27 @@ fn NodeProperties(node_id: i64) -> impl IntoView {
         async move {
             if let Ok(target_id) = target_str.parse::<i64>() {
                 if target_id == node_id {
-                    return Err(leptos::server_fn::error::ServerFnError::ServerError(
-                        "Cannot connect node to itself".to_string(),
-                    ));
+                    return Err(AppError::invalid("Cannot connect node to itself"));
                 }
 
                 let data = CreateConnection {
                     topology_id: current_topology_id.get_untracked(),
                     source_node_id: node_id,
                     target_node_id: target_id,
-                    connection_type: Some("ethernet".to_string()),
+                    connection_type: Some(ConnectionType::Ethernet),
                     bandwidth_mbps: Some(1000),
                     latency_ms: Some(1.0),
                     baseline_packet_loss_pct: Some(0.0),
-                    status: Some("active".to_string()),
+                    status: Some(ConnectionStatus::Active),
                     color: None,
+                    source_interface_id: None,
+                    target_interface_id: None,
                     metadata: None,
                 };
 
                 create_connection_fn(data).await
             } else {
-                Err(leptos::server_fn::error::ServerFnError::ServerError(
-                    "Please select a target node".to_string(),
-                ))
+                Err(AppError::invalid("Please select a target node"))
             }
         }
     });
@@ -2398,8 +5011,22 @@ fn NodeProperties(node_id: i64) -> impl IntoView {
         }
     });
 
+    // Each field's edit is one undo step; the panel may close with a field still focused
+    #[cfg(feature = "hydrate")]
+    {
+        let topology_id = current_topology_id.get_untracked();
+        on_cleanup(move || close_field_edits(topology_id));
+    }
+
     view! {
-        <div class="space-y-4">
+        <div
+            class="space-y-4"
+            on:focusin=move |ev| {
+                let label = format!("Edit node '{}'", name.get_untracked());
+                begin_field_edit(&ev, current_topology_id.get_untracked(), label);
+            }
+            on:focusout=move |ev| end_field_edits(&ev, current_topology_id.get_untracked())
+        >
             <Suspense fallback=move || view! {
                 <div class="text-center text-gray-500 mt-8">
                     <div class="text-2xl mb-2">"⏳"</div>
@@ -2411,6 +5038,30 @@ fn NodeProperties(node_id: i64) -> impl IntoView {
                         match data {
                             Some(_) => view! {
                             <div class="space-y-4">
+                                {move || conflict.get().map(|current| view! {
+                                    <div class="p-3 bg-yellow-900 bg-opacity-40 border border-yellow-600 rounded space-y-2">
+                                        <p class="text-xs text-yellow-200">
+                                            {format!("'{}' was changed by someone else. Your latest edits aren't saved yet.", current.name)}
+                                        </p>
+                                        <div class="flex gap-2">
+                                            <button
+                                                class="flex-1 px-2 py-1 bg-blue-600 hover:bg-blue-700 rounded text-xs"
+                                                title="Keep their changes to the fields you didn't edit"
+                                                on:click=merge_conflict
+                                            >
+                                                "Merge"
+                                            </button>
+                                            <button
+                                                class="flex-1 px-2 py-1 bg-red-600 hover:bg-red-700 rounded text-xs"
+                                                title="Save every field as shown here"
+                                                on:click=overwrite_conflict
+                                            >
+                                                "Overwrite"
+                                            </button>
+                                        </div>
+                                    </div>
+                                })}
+
                                 <div>
                                     <label class="block text-xs font-medium text-gray-400 mb-1">"Node ID"</label>
                                     <div class="text-sm text-gray-300">{node_id}</div>
@@ -2431,16 +5082,16 @@ fn NodeProperties(node_id: i64) -> impl IntoView {
                                     <label class="block text-xs font-medium text-gray-400 mb-1">"Type"</label>
                                     <select
                                         class="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
-                                        prop:value=move || node_type.get()
-                                        on:change=move |ev| node_type.set(event_target_value(&ev))
+                                        prop:value=move || node_type.get().as_str()
+                                        on:change=move |ev| {
+                                            if let Ok(value) = event_target_value(&ev).parse() {
+                                                node_type.set(value);
+                                            }
+                                        }
                                     >
-                                        <option value="router">"Router"</option>
-                                        <option value="switch">"Switch"</option>
-                                        <option value="server">"Server"</option>
-                                        <option value="firewall">"Firewall"</option>
-                                        <option value="load_balancer">"Load Balancer"</option>
-                                        <option value="cloud">"Cloud"</option>
-                                        <option value="application">"Application"</option>
+                                        {NodeType::ALL.iter().map(|t| view! {
+                                            <option value=t.as_str()>{t.label()}</option>
+                                        }).collect_view()}
                                     </select>
                                 </div>
 
@@ -2477,8 +5128,57 @@ fn NodeProperties(node_id: i64) -> impl IntoView {
                                         prop:value=move || ip_address.get()
                                         on:input=move |ev| ip_address.set(event_target_value(&ev))
                                     />
+                                    {move || match save_action.value().get() {
+                                        Some(Err(UpdateError::Failed(error))) => Some(view! {
+                                            <p class="text-[10px] text-red-400 mt-0.5">{error.message}</p>
+                                        }),
+                                        _ => None,
+                                    }}
+                                    {move || subnets.get().filter(|subnets| !subnets.is_empty()).map(|subnets| view! {
+                                        <select
+                                            class="w-full mt-1 px-2 py-1 bg-gray-700 border border-gray-600 rounded text-xs focus:outline-none focus:border-blue-500"
+                                            prop:value=""
+                                            on:change=move |ev| {
+                                                if let Ok(subnet_id) = event_target_value(&ev).parse::<i64>() {
+                                                    next_free_action.dispatch(subnet_id);
+                                                }
+                                            }
+                                        >
+                                            <option value="">"Assign next free address from…"</option>
+                                            {subnets.into_iter().map(|subnet| {
+                                                let label = if subnet.name.is_empty() {
+                                                    subnet.cidr.clone()
+                                                } else {
+                                                    format!("{} ({})", subnet.cidr, subnet.name)
+                                                };
+                                                view! { <option value=subnet.id.to_string()>{label}</option> }
+                                            }).collect_view()}
+                                        </select>
+                                    })}
                                 </div>
 
+                                <div>
+                                    <label class="block text-xs font-medium text-gray-400 mb-1">"Group"</label>
+                                    {move || groups.get().map(|groups| view! {
+                                        <select
+                                            class="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
+                                            prop:value=move || group_id.get().to_string()
+                                            on:change=move |ev| {
+                                                if let Ok(id) = event_target_value(&ev).parse::<i64>() {
+                                                    group_id.set(id);
+                                                }
+                                            }
+                                        >
+                                            <option value="0" selected=move || group_id.get() == 0>"None"</option>
+                                            {group_options(&groups).into_iter().map(|(id, label)| view! {
+                                                <option value=id.to_string() selected=move || group_id.get() == id>{label}</option>
+                                            }).collect_view()}
+                                        </select>
+                                    })}
+                                </div>
+
+                                <TagsEditor node_id=node_id />
+
                                 <div class="grid grid-cols-3 gap-1">
                                     <div>
                                         <label class="block text-[10px] font-medium text-red-400 mb-0.5">"Pos X"</label>
@@ -2587,7 +5287,7 @@ fn NodeProperties(node_id: i64) -> impl IntoView {
                                         prop:value=move || scale.get()
                                         on:input=move |ev| {
                                             if let Ok(val) = event_target_value(&ev).parse::<f64>() {
-                                                scale.set(val.max(0.1).min(5.0));
+                                                scale.set(val.clamp(0.1, 5.0));
                                             }
                                         }
                                     />
@@ -2598,39 +5298,31 @@ fn NodeProperties(node_id: i64) -> impl IntoView {
                                     <label class="block text-xs font-medium text-gray-400 mb-1.5">"Color Presets"</label>
                                     <div class="grid grid-cols-6 gap-1 mb-2">
                                         {[
-                                            ("100,150,255", "Blue"),
-                                            ("255,140,60", "Orange"),
-                                            ("80,200,120", "Green"),
-                                            ("220,60,60", "Red"),
-                                            ("180,100,200", "Purple"),
-                                            ("150,150,150", "Gray"),
-                                            ("70,140,255", "Light Blue"),
-                                            ("249,115,22", "Bright Orange"),
-                                            ("34,197,94", "Bright Green"),
-                                            ("239,68,68", "Bright Red"),
-                                            ("236,72,153", "Pink"),
-                                            ("251,191,36", "Yellow"),
-                                            ("14,165,233", "Cyan"),
+                                            (Rgb::new(100, 150, 255), "Blue"),
+                                            (Rgb::new(255, 140, 60), "Orange"),
+                                            (Rgb::new(80, 200, 120), "Green"),
+                                            (Rgb::new(220, 60, 60), "Red"),
+                                            (Rgb::new(180, 100, 200), "Purple"),
+                                            (Rgb::new(150, 150, 150), "Gray"),
+                                            (Rgb::new(70, 140, 255), "Light Blue"),
+                                            (Rgb::new(249, 115, 22), "Bright Orange"),
+                                            (Rgb::new(34, 197, 94), "Bright Green"),
+                                            (Rgb::new(239, 68, 68), "Bright Red"),
+                                            (Rgb::new(236, 72, 153), "Pink"),
+                                            (Rgb::new(251, 191, 36), "Yellow"),
+                                            (Rgb::new(14, 165, 233), "Cyan"),
                                         ].iter().map(|(rgb, name)| {
-                                            let rgb_str = rgb.to_string();
-                                            let rgb_parts: Vec<u8> = rgb_str.split(',')
-                                                .filter_map(|s| s.parse().ok())
-                                                .collect();
-                                            let (r, g, b) = if rgb_parts.len() == 3 {
-                                                (rgb_parts[0], rgb_parts[1], rgb_parts[2])
-                                            } else {
-                                                (100, 150, 255)
-                                            };
+                                            let rgb = *rgb;
 
                                             view! {
                                                 <button
                                                     type="button"
                                                     class="w-full aspect-square rounded border-2 transition hover:scale-110"
-                                                    class:border-blue-400=move || color.get() == *rgb
-                                                    class:border-gray-600=move || color.get() != *rgb
-                                                    style=format!("background-color: rgb({},{},{})", r, g, b)
+                                                    class:border-blue-400=move || color.get() == rgb
+                                                    class:border-gray-600=move || color.get() != rgb
+                                                    style=format!("background-color: {}", rgb.to_css())
                                                     title=*name
-                                                    on:click=move |_| color.set(rgb_str.clone())
+                                                    on:click=move |_| color.set(rgb)
                                                 />
                                             }
                                         }).collect_view()}
@@ -2642,42 +5334,27 @@ fn NodeProperties(node_id: i64) -> impl IntoView {
                                         <input
                                             type="color"
                                             class="w-12 h-8 rounded border border-gray-600 cursor-pointer"
-                                            value=move || {
-                                                // Convert RGB string to hex for color input
-                                                let rgb_parts: Vec<u8> = color.get().split(',')
-                                                    .filter_map(|s| s.parse().ok())
-                                                    .collect();
-                                                if rgb_parts.len() == 3 {
-                                                    format!("#{:02x}{:02x}{:02x}", rgb_parts[0], rgb_parts[1], rgb_parts[2])
-                                                } else {
-                                                    "#6496ff".to_string()
-                                                }
-                                            }
+                                            value=move || color.get().to_hex()
                                             on:input=move |ev| {
-                                                // Convert hex to RGB format
-                                                let hex = event_target_value(&ev);
-                                                if hex.starts_with('#') && hex.len() == 7 {
-                                                    if let (Ok(r), Ok(g), Ok(b)) = (
-                                                        u8::from_str_radix(&hex[1..3], 16),
-                                                        u8::from_str_radix(&hex[3..5], 16),
-                                                        u8::from_str_radix(&hex[5..7], 16),
-                                                    ) {
-                                                        color.set(format!("{},{},{}", r, g, b));
-                                                    }
+                                                // Color inputs always report #rrggbb
+                                                if let Ok(rgb) = event_target_value(&ev).parse() {
+                                                    color.set(rgb);
                                                 }
                                             }
                                         />
-                                        <span class="text-xs text-gray-500 font-mono">{move || color.get()}</span>
+                                        <span class="text-xs text-gray-500 font-mono">{move || color.get().to_string()}</span>
                                     </div>
                                 </div>
 
+                                <NodeInterfaces node_id=node_id />
+
                                 <div class="pt-4 border-t border-gray-700">
                                     // Save button group
                                     <div class="mb-4">
                                         <button
                                             class="w-full px-4 py-2 bg-blue-600 hover:bg-blue-700 rounded text-sm font-medium transition disabled:opacity-50 disabled:cursor-not-allowed"
-                                            on:click=move |_| { save_action.dispatch(()); }
-                                            disabled=move || save_action.pending().get()
+                                            on:click=move |_| save()
+                                            disabled=move || save_action.pending().get() || conflict.get().is_some()
                                         >
                                             {move || if save_action.pending().get() {
                                                 "Saving..."
@@ -2809,12 +5486,324 @@ fn NodeProperties(node_id: i64) -> impl IntoView {
     }
 }
 
+/// Interfaces (ports) of a node: list, add, edit and delete
+#[component]
+fn NodeInterfaces(node_id: i64) -> impl IntoView {
+    let interfaces_trigger = RwSignal::new(0u32);
+    let editing = RwSignal::new(None::<i64>); // None = the form adds a new interface
+    let name = RwSignal::new(String::new());
+    let speed_mbps = RwSignal::new(String::new());
+    let mac_address = RwSignal::new(String::new());
+    let ip_address = RwSignal::new(String::new());
+    let admin_state = RwSignal::new(AdminState::Up);
+    let error = RwSignal::new(None::<String>);
+
+    let interfaces = LocalResource::new(move || {
+        let _ = interfaces_trigger.get();
+        async move { get_node_interfaces(node_id).await.unwrap_or_default() }
+    });
+
+    let reset_form = move || {
+        editing.set(None);
+        name.set(String::new());
+        speed_mbps.set(String::new());
+        mac_address.set(String::new());
+        ip_address.set(String::new());
+        admin_state.set(AdminState::Up);
+    };
+
+    let save_action = Action::new(move |editing_id: &Option<i64>| {
+        let editing_id = *editing_id;
+        let speed = speed_mbps.get_untracked().trim().parse::<i64>().ok();
+        let name = name.get_untracked();
+        let mac_address = mac_address.get_untracked();
+        let ip_address = ip_address.get_untracked();
+        let admin_state = admin_state.get_untracked();
+        async move {
+            match editing_id {
+                Some(id) => {
+                    let data = UpdateInterface {
+                        name: Some(name),
+                        speed_mbps: Some(speed.unwrap_or(0)),
+                        mac_address: Some(mac_address),
+                        ip_address: Some(ip_address),
+                        admin_state: Some(admin_state),
+                        description: None,
+                    };
+                    update_interface(id, data).await
+                }
+                None => {
+                    let data = CreateInterface {
+                        node_id,
+                        name,
+                        speed_mbps: speed,
+                        mac_address: Some(mac_address),
+                        ip_address: Some(ip_address),
+                        admin_state: Some(admin_state),
+                        description: None,
+                    };
+                    create_interface(data).await
+                }
+            }
+        }
+    });
+
+    Effect::new(move || match save_action.value().get() {
+        Some(Ok(_)) => {
+            error.set(None);
+            reset_form();
+            interfaces_trigger.update(|v| *v += 1);
+        }
+        Some(Err(e)) => error.set(Some(e.to_string())),
+        None => {}
+    });
+
+    let delete_action = Action::new(move |id: &i64| {
+        let id = *id;
+        async move { delete_interface(id).await }
+    });
+
+    Effect::new(move || match delete_action.value().get() {
+        Some(Ok(())) => {
+            if editing.get_untracked().is_some() {
+                reset_form();
+            }
+            interfaces_trigger.update(|v| *v += 1);
+        }
+        Some(Err(e)) => error.set(Some(e.to_string())),
+        None => {}
+    });
+
+    let input_class = "w-full px-2 py-1 bg-gray-700 border border-gray-600 rounded text-xs focus:outline-none focus:border-blue-500";
+
+    view! {
+        <div class="pt-3 border-t border-gray-700">
+            <label class="block text-xs font-medium text-gray-300 mb-2">"Interfaces"</label>
+
+            <Suspense fallback=move || view! { <div class="text-xs text-gray-500">"Loading..."</div> }>
+                {move || interfaces.get().map(|list| {
+                    if list.is_empty() {
+                        view! { <div class="text-xs text-gray-500 mb-2">"No interfaces"</div> }.into_any()
+                    } else {
+                        view! {
+                            <div class="space-y-1 mb-2">
+                                {list.into_iter().map(|interface| {
+                                    let edit = interface.clone();
+                                    let id = interface.id;
+                                    let details = [
+                                        interface.speed_mbps.map(|speed| format!("{} Mbps", speed)),
+                                        interface.ip_address.clone(),
+                                        interface.mac_address.clone(),
+                                    ]
+                                    .into_iter()
+                                    .flatten()
+                                    .collect::<Vec<_>>()
+                                    .join(" · ");
+                                    view! {
+                                        <div
+                                            class="flex items-center gap-2 px-2 py-1 bg-gray-750 rounded text-xs"
+                                            class:ring-1=move || editing.get() == Some(id)
+                                            class:ring-blue-500=move || editing.get() == Some(id)
+                                        >
+                                            <span
+                                                class="w-2 h-2 rounded-full"
+                                                class:bg-green-500=interface.admin_state == AdminState::Up
+                                                class:bg-gray-500=interface.admin_state == AdminState::Down
+                                                title=interface.admin_state.label()
+                                            ></span>
+                                            <div class="flex-1 min-w-0">
+                                                <div class="text-gray-200 font-mono">{interface.name.clone()}</div>
+                                                <div class="text-[10px] text-gray-500 truncate">{details}</div>
+                                            </div>
+                                            <button
+                                                class="text-gray-400 hover:text-white"
+                                                title="Edit interface"
+                                                on:click=move |_| {
+                                                    editing.set(Some(edit.id));
+                                                    name.set(edit.name.clone());
+                                                    speed_mbps.set(edit.speed_mbps.map(|s| s.to_string()).unwrap_or_default());
+                                                    mac_address.set(edit.mac_address.clone().unwrap_or_default());
+                                                    ip_address.set(edit.ip_address.clone().unwrap_or_default());
+                                                    admin_state.set(edit.admin_state);
+                                                }
+                                            >"✎"</button>
+                                            <button
+                                                class="text-gray-400 hover:text-red-400"
+                                                title="Delete interface (links using it are kept, without a port)"
+                                                on:click=move |_| { delete_action.dispatch(id); }
+                                            >"✗"</button>
+                                        </div>
+                                    }
+                                }).collect_view()}
+                            </div>
+                        }.into_any()
+                    }
+                })}
+            </Suspense>
+
+            // Add / edit form
+            <div class="grid grid-cols-2 gap-1">
+                <input
+                    type="text"
+                    class=input_class
+                    placeholder="Name (e.g. Gi0/0/1)"
+                    prop:value=move || name.get()
+                    on:input=move |ev| name.set(event_target_value(&ev))
+                />
+                <input
+                    type="number"
+                    class=input_class
+                    placeholder="Speed (Mbps)"
+                    prop:value=move || speed_mbps.get()
+                    on:input=move |ev| speed_mbps.set(event_target_value(&ev))
+                />
+                <input
+                    type="text"
+                    class=input_class
+                    placeholder="IP (10.0.0.1/24)"
+                    prop:value=move || ip_address.get()
+                    on:input=move |ev| ip_address.set(event_target_value(&ev))
+                />
+                <input
+                    type="text"
+                    class=input_class
+                    placeholder="MAC"
+                    prop:value=move || mac_address.get()
+                    on:input=move |ev| mac_address.set(event_target_value(&ev))
+                />
+                <select
+                    class=input_class
+                    prop:value=move || admin_state.get().as_str()
+                    on:change=move |ev| {
+                        if let Ok(value) = event_target_value(&ev).parse() {
+                            admin_state.set(value);
+                        }
+                    }
+                >
+                    {AdminState::ALL.iter().map(|state| view! {
+                        <option value=state.as_str()>{format!("Admin {}", state.label())}</option>
+                    }).collect_view()}
+                </select>
+                <div class="flex gap-1">
+                    <button
+                        class="flex-1 px-2 py-1 bg-blue-600 hover:bg-blue-700 rounded text-xs transition disabled:opacity-50"
+                        disabled=move || name.get().trim().is_empty() || save_action.pending().get()
+                        on:click=move |_| { save_action.dispatch(editing.get_untracked()); }
+                    >
+                        {move || if editing.get().is_some() { "Update" } else { "Add" }}
+                    </button>
+                    <Show when=move || editing.get().is_some()>
+                        <button
+                            class="px-2 py-1 bg-gray-700 hover:bg-gray-600 rounded text-xs transition"
+                            on:click=move |_| reset_form()
+                        >"Cancel"</button>
+                    </Show>
+                </div>
+            </div>
+
+            {move || error.get().map(|e| view! {
+                <div class="mt-1 text-xs text-red-400">{e}</div>
+            })}
+        </div>
+    }
+}
+
+/// Which interface a connection uses at each end
+#[component]
+fn ConnectionPorts(connection: crate::models::Connection) -> impl IntoView {
+    let refetch_trigger = use_context::<RwSignal<u32>>().expect("refetch_trigger context");
+
+    let connection_id = connection.id;
+    let source_node_id = connection.source_node_id;
+    let target_node_id = connection.target_node_id;
+    let source_port = RwSignal::new(connection.source_interface_id);
+    let target_port = RwSignal::new(connection.target_interface_id);
+    let error = RwSignal::new(None::<String>);
+
+    let source_interfaces = LocalResource::new(move || async move {
+        get_node_interfaces(source_node_id).await.unwrap_or_default()
+    });
+    let target_interfaces = LocalResource::new(move || async move {
+        get_node_interfaces(target_node_id).await.unwrap_or_default()
+    });
+
+    let set_action = Action::new(move |ports: &(Option<i64>, Option<i64>)| {
+        let (source, target) = *ports;
+        async move { set_connection_interfaces(connection_id, source, target).await }
+    });
+
+    // Keep the selects in sync with what the server accepted
+    Effect::new(move || match set_action.value().get() {
+        Some(Ok(updated)) => {
+            error.set(None);
+            source_port.set(updated.source_interface_id);
+            target_port.set(updated.target_interface_id);
+            refetch_trigger.update(|v| *v += 1);
+        }
+        Some(Err(e)) => {
+            error.set(Some(e.to_string()));
+            source_port.set(connection.source_interface_id);
+            target_port.set(connection.target_interface_id);
+        }
+        None => {}
+    });
+
+    let port_select = move |label: &'static str,
+                            port: RwSignal<Option<i64>>,
+                            interfaces: LocalResource<Vec<crate::models::Interface>>,
+                            is_source: bool| {
+        view! {
+            <div>
+                <label class="block text-xs font-medium text-gray-400 mb-1">{label}</label>
+                <select
+                    class="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
+                    prop:value=move || port.get().map(|id| id.to_string()).unwrap_or_default()
+                    on:change=move |ev| {
+                        let selected = event_target_value(&ev).parse::<i64>().ok();
+                        port.set(selected);
+                        let ports = if is_source {
+                            (selected, target_port.get_untracked())
+                        } else {
+                            (source_port.get_untracked(), selected)
+                        };
+                        set_action.dispatch(ports);
+                    }
+                >
+                    <option value="">"— Node (no port) —"</option>
+                    {move || interfaces.get().map(|list| {
+                        list.into_iter().map(|interface| {
+                            let text = match interface.admin_state {
+                                AdminState::Up => interface.name.clone(),
+                                AdminState::Down => format!("{} (admin down)", interface.name),
+                            };
+                            view! { <option value=interface.id.to_string()>{text}</option> }
+                        }).collect_view()
+                    })}
+                </select>
+            </div>
+        }
+    };
+
+    view! {
+        <div class="space-y-2">
+            {port_select("Source Port", source_port, source_interfaces, true)}
+            {port_select("Target Port", target_port, target_interfaces, false)}
+            {move || error.get().map(|e| view! {
+                <div class="text-xs text-red-400">{e}</div>
+            })}
+        </div>
+    }
+}
+
 /// Connection properties editor with live data loading and saving
 #[component]
 fn ConnectionProperties(connection_id: i64) -> impl IntoView {
     // Get refetch trigger from context
     let refetch_trigger = use_context::<RwSignal<u32>>().expect("refetch_trigger context");
 
+    // Get current_topology_id from context
+    let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
+
     // Get selected_item from context to clear selection after deletion
     let selected_item =
         use_context::<RwSignal<Option<SelectedItem>>>().expect("selected_item context");
@@ -2826,29 +5815,56 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
     );
 
     // Create signals for editable fields
-    let connection_type = RwSignal::new(String::new());
+    let connection_type = RwSignal::new(ConnectionType::default());
     let bandwidth_mbps = RwSignal::new(0i64);
     let latency_ms = RwSignal::new(0.0f64);
     let baseline_packet_loss_pct = RwSignal::new(0.0f64); // Default 0% (no packet loss)
-    let status = RwSignal::new(String::new());
-    let color = RwSignal::new(String::from("128,128,128")); // Default gray
+    let status = RwSignal::new(ConnectionStatus::default());
+    let color = RwSignal::new(Rgb::CONNECTION_DEFAULT);
     let carries_traffic = RwSignal::new(true); // Default enabled for traffic animation
-    let flow_direction = RwSignal::new(String::from("source_to_target")); // Default source to target
+    let flow_direction = RwSignal::new(FlowDirection::SourceToTarget); // Default source to target
 
     // Track whether initial data has loaded (prevents auto-save during initial load)
     let connection_loaded = RwSignal::new(false);
 
+    // The connection as last loaded or saved; saves are rejected if it changed on the server since
+    let base = RwSignal::new(None::<Connection>);
+    // The server's connection after a rejected save, until the user merges or overwrites
+    let conflict = RwSignal::new(None::<Connection>);
+    // An edit made while a save was in flight (saved once that one completes)
+    let save_queued = RwSignal::new(false);
+
+    let populate = move |connection: Connection| {
+        connection_type.set(connection.connection_type);
+        bandwidth_mbps.set(connection.bandwidth_mbps.unwrap_or(0));
+        latency_ms.set(connection.latency_ms.unwrap_or(0.0));
+        baseline_packet_loss_pct.set(connection.baseline_packet_loss_pct.unwrap_or(0.0));
+        status.set(connection.status);
+        color.set(connection.color);
+        carries_traffic.set(connection.carries_traffic);
+        flow_direction.set(connection.flow_direction);
+    };
+
+    // The panel's fields applied to the connection they were edited from
+    let edited = move |from: Connection| Connection {
+        connection_type: connection_type.get_untracked(),
+        bandwidth_mbps: Some(bandwidth_mbps.get_untracked()).filter(|&v| v > 0).or(from.bandwidth_mbps),
+        latency_ms: Some(latency_ms.get_untracked()).filter(|&v| v >= 0.0).or(from.latency_ms),
+        baseline_packet_loss_pct: Some(baseline_packet_loss_pct.get_untracked())
+            .filter(|&v| v >= 0.0)
+            .or(from.baseline_packet_loss_pct),
+        status: status.get_untracked(),
+        color: color.get_untracked(),
+        carries_traffic: carries_traffic.get_untracked(),
+        flow_direction: flow_direction.get_untracked(),
+        ..from
+    };
+
     // Populate signals when data loads
     Effect::new(move || {
         if let Some(Some(connection)) = connection_data.get() {
-            connection_type.set(connection.connection_type);
-            bandwidth_mbps.set(connection.bandwidth_mbps.unwrap_or(0));
-            latency_ms.set(connection.latency_ms.unwrap_or(0.0));
-            baseline_packet_loss_pct.set(connection.baseline_packet_loss_pct.unwrap_or(0.0));
-            status.set(connection.status);
-            color.set(connection.color);
-            carries_traffic.set(connection.carries_traffic);
-            flow_direction.set(connection.flow_direction);
+            base.set(Some(connection.clone()));
+            populate(connection);
             // Mark as loaded after initial data population
             connection_loaded.set(true);
         }
@@ -2867,18 +5883,64 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
             carries_traffic: Some(carries_traffic.get_untracked()),
             flow_direction: Some(flow_direction.get_untracked()),
             metadata: None,
+            expected_version: base.get_untracked().map(|connection| connection.version),
         };
 
         async move { update_connection(connection_id, update_data).await }
     });
 
-    // Trigger viewport refetch on successful save
+    // One save at a time: each is checked against the connection the previous one returned
+    let save = move || {
+        if save_action.pending().get_untracked() {
+            save_queued.set(true);
+        } else {
+            save_action.dispatch(());
+        }
+    };
+
+    // Track the saved connection and trigger viewport refetch on successful save
     Effect::new(move || {
-        if let Some(Ok(_)) = save_action.value().get() {
-            refetch_trigger.update(|v| *v += 1);
+        match save_action.value().get() {
+            Some(Ok(connection)) => {
+                base.set(Some(connection));
+                refetch_trigger.update(|v| *v += 1);
+            }
+            Some(Err(UpdateError::Conflict(current))) => conflict.set(Some(current)),
+            _ => {}
+        }
+        if save_queued.get_untracked() {
+            save_queued.set(false);
+            if conflict.get_untracked().is_none() {
+                save_action.dispatch(());
+            }
         }
     });
 
+    // Keep the other user's changes to fields this panel didn't edit, then save mine
+    let merge_conflict = move |_: leptos::ev::MouseEvent| {
+        let (Some(current), Some(started_from)) = (conflict.get_untracked(), base.get_untracked()) else {
+            return;
+        };
+        match merge_edits(&started_from, &edited(started_from.clone()), &current) {
+            Ok(merged) => {
+                base.set(Some(current));
+                conflict.set(None);
+                populate(merged);
+                save();
+            }
+            Err(e) => leptos::logging::log!("Failed to merge connection edits: {}", e),
+        }
+    };
+
+    // Save the panel's fields as they are, replacing the other user's changes to them
+    let overwrite_conflict = move |_: leptos::ev::MouseEvent| {
+        if let Some(current) = conflict.get_untracked() {
+            base.set(Some(current));
+            conflict.set(None);
+            save();
+        }
+    };
+
     // Auto-save effect: Save when any field changes
     Effect::new(move || {
         // Track all editable fields
@@ -2891,9 +5953,10 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
         let _carries = carries_traffic.get();
         let _direction = flow_direction.get();
 
-        // Only auto-save if data has been loaded (prevents save during initial load)
-        if connection_loaded.get() {
-            save_action.dispatch(());
+        // Only auto-save if data has been loaded (prevents save during initial load),
+        // and not while a conflict waits for the user to merge or overwrite
+        if connection_loaded.get() && conflict.get_untracked().is_none() {
+            save();
         }
     });
 
@@ -2925,8 +5988,21 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
         }
     });
 
+    // Each field's edit is one undo step; the panel may close with a field still focused
+    #[cfg(feature = "hydrate")]
+    {
+        let topology_id = current_topology_id.get_untracked();
+        on_cleanup(move || close_field_edits(topology_id));
+    }
+
     view! {
-        <div class="space-y-4">
+        <div
+            class="space-y-4"
+            on:focusin=move |ev| {
+                begin_field_edit(&ev, current_topology_id.get_untracked(), "Edit connection".to_string());
+            }
+            on:focusout=move |ev| end_field_edits(&ev, current_topology_id.get_untracked())
+        >
             <Suspense fallback=move || view! {
                 <div class="text-center text-gray-500 mt-8">
                     <div class="text-2xl mb-2">"⏳"</div>
@@ -2938,6 +6014,30 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
                         match data {
                             Some(connection) => view! {
                             <div class="space-y-4">
+                                {move || conflict.get().map(|_| view! {
+                                    <div class="p-3 bg-yellow-900 bg-opacity-40 border border-yellow-600 rounded space-y-2">
+                                        <p class="text-xs text-yellow-200">
+                                            "This connection was changed by someone else. Your latest edits aren't saved yet."
+                                        </p>
+                                        <div class="flex gap-2">
+                                            <button
+                                                class="flex-1 px-2 py-1 bg-blue-600 hover:bg-blue-700 rounded text-xs"
+                                                title="Keep their changes to the fields you didn't edit"
+                                                on:click=merge_conflict
+                                            >
+                                                "Merge"
+                                            </button>
+                                            <button
+                                                class="flex-1 px-2 py-1 bg-red-600 hover:bg-red-700 rounded text-xs"
+                                                title="Save every field as shown here"
+                                                on:click=overwrite_conflict
+                                            >
+                                                "Overwrite"
+                                            </button>
+                                        </div>
+                                    </div>
+                                })}
+
                                 <div>
                                     <label class="block text-xs font-medium text-gray-400 mb-1">"Connection ID"</label>
                                     <div class="text-sm text-gray-300">{connection_id}</div>
@@ -2969,16 +6069,22 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
                                     </button>
                                 </div>
 
+                                <ConnectionPorts connection=connection.clone() />
+
                                 <div>
                                     <label class="block text-xs font-medium text-gray-400 mb-1">"Type"</label>
                                     <select
                                         class="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
-                                        prop:value=move || connection_type.get()
-                                        on:change=move |ev| connection_type.set(event_target_value(&ev))
+                                        prop:value=move || connection_type.get().as_str()
+                                        on:change=move |ev| {
+                                            if let Ok(value) = event_target_value(&ev).parse() {
+                                                connection_type.set(value);
+                                            }
+                                        }
                                     >
-                                        <option value="ethernet">"Ethernet"</option>
-                                        <option value="fiber">"Fiber"</option>
-                                        <option value="wireless">"Wireless"</option>
+                                        {ConnectionType::ALL.iter().map(|t| view! {
+                                            <option value=t.as_str()>{t.label()}</option>
+                                        }).collect_view()}
                                     </select>
                                 </div>
 
@@ -3029,7 +6135,7 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
                                         on:input=move |ev| {
                                             if let Ok(val) = event_target_value(&ev).parse::<f64>() {
                                                 // Clamp between 0.0 and 10.0
-                                                baseline_packet_loss_pct.set(val.max(0.0).min(10.0));
+                                                baseline_packet_loss_pct.set(val.clamp(0.0, 10.0));
                                             }
                                         }
                                     />
@@ -3039,12 +6145,16 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
                                     <label class="block text-xs font-medium text-gray-400 mb-1">"Status"</label>
                                     <select
                                         class="w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500"
-                                        prop:value=move || status.get()
-                                        on:change=move |ev| status.set(event_target_value(&ev))
+                                        prop:value=move || status.get().as_str()
+                                        on:change=move |ev| {
+                                            if let Ok(value) = event_target_value(&ev).parse() {
+                                                status.set(value);
+                                            }
+                                        }
                                     >
-                                        <option value="active">"Active"</option>
-                                        <option value="inactive">"Inactive"</option>
-                                        <option value="error">"Error"</option>
+                                        {ConnectionStatus::ALL.iter().map(|s| view! {
+                                            <option value=s.as_str()>{s.label()}</option>
+                                        }).collect_view()}
                                     </select>
                                 </div>
 
@@ -3052,39 +6162,31 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
                                     <label class="block text-xs font-medium text-gray-400 mb-1.5">"Color Presets"</label>
                                     <div class="grid grid-cols-6 gap-1 mb-2">
                                         {[
-                                            ("128,128,128", "Gray"),
-                                            ("0,0,0", "Black"),
-                                            ("255,255,255", "White"),
-                                            ("59,130,246", "Blue"),
-                                            ("34,197,94", "Green"),
-                                            ("251,191,36", "Yellow"),
-                                            ("239,68,68", "Red"),
-                                            ("168,85,247", "Purple"),
-                                            ("236,72,153", "Pink"),
-                                            ("249,115,22", "Orange"),
-                                            ("14,165,233", "Cyan"),
-                                            ("132,204,22", "Lime"),
-                                            ("245,158,11", "Amber"),
+                                            (Rgb::new(128, 128, 128), "Gray"),
+                                            (Rgb::new(0, 0, 0), "Black"),
+                                            (Rgb::new(255, 255, 255), "White"),
+                                            (Rgb::new(59, 130, 246), "Blue"),
+                                            (Rgb::new(34, 197, 94), "Green"),
+                                            (Rgb::new(251, 191, 36), "Yellow"),
+                                            (Rgb::new(239, 68, 68), "Red"),
+                                            (Rgb::new(168, 85, 247), "Purple"),
+                                            (Rgb::new(236, 72, 153), "Pink"),
+                                            (Rgb::new(249, 115, 22), "Orange"),
+                                            (Rgb::new(14, 165, 233), "Cyan"),
+                                            (Rgb::new(132, 204, 22), "Lime"),
+                                            (Rgb::new(245, 158, 11), "Amber"),
                                         ].iter().map(|(rgb, name)| {
-                                            let rgb_str = rgb.to_string();
-                                            let rgb_parts: Vec<u8> = rgb_str.split(',')
-                                                .filter_map(|s| s.parse().ok())
-                                                .collect();
-                                            let (r, g, b) = if rgb_parts.len() == 3 {
-                                                (rgb_parts[0], rgb_parts[1], rgb_parts[2])
-                                            } else {
-                                                (128, 128, 128)
-                                            };
+                                            let rgb = *rgb;
 
                                             view! {
                                                 <button
                                                     type="button"
                                                     class="w-full aspect-square rounded border-2 transition hover:scale-110"
-                                                    class:border-blue-400=move || color.get() == *rgb
-                                                    class:border-gray-600=move || color.get() != *rgb
-                                                    style=format!("background-color: rgb({},{},{})", r, g, b)
+                                                    class:border-blue-400=move || color.get() == rgb
+                                                    class:border-gray-600=move || color.get() != rgb
+                                                    style=format!("background-color: {}", rgb.to_css())
                                                     title=*name
-                                                    on:click=move |_| color.set(rgb_str.clone())
+                                                    on:click=move |_| color.set(rgb)
                                                 />
                                             }
                                         }).collect_view()}
@@ -3096,32 +6198,15 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
                                         <input
                                             type="color"
                                             class="w-12 h-8 rounded border border-gray-600 cursor-pointer"
-                                            value=move || {
-                                                // Convert RGB string to hex for color input
-                                                let rgb_parts: Vec<u8> = color.get().split(',')
-                                                    .filter_map(|s| s.parse().ok())
-                                                    .collect();
-                                                if rgb_parts.len() == 3 {
-                                                    format!("#{:02x}{:02x}{:02x}", rgb_parts[0], rgb_parts[1], rgb_parts[2])
-                                                } else {
-                                                    "#808080".to_string()
-                                                }
-                                            }
+                                            value=move || color.get().to_hex()
                                             on:input=move |ev| {
-                                                // Convert hex to RGB format
-                                                let hex = event_target_value(&ev);
-                                                if hex.starts_with('#') && hex.len() == 7 {
-                                                    if let (Ok(r), Ok(g), Ok(b)) = (
-                                                        u8::from_str_radix(&hex[1..3], 16),
-                                                        u8::from_str_radix(&hex[3..5], 16),
-                                                        u8::from_str_radix(&hex[5..7], 16),
-                                                    ) {
-                                                        color.set(format!("{},{},{}", r, g, b));
-                                                    }
+                                                // Color inputs always report #rrggbb
+                                                if let Ok(rgb) = event_target_value(&ev).parse() {
+                                                    color.set(rgb);
                                                 }
                                             }
                                         />
-                                        <span class="text-xs text-gray-500 font-mono">{move || color.get()}</span>
+                                        <span class="text-xs text-gray-500 font-mono">{move || color.get().to_string()}</span>
                                     </div>
                                 </div>
 
@@ -3154,8 +6239,8 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
                                                 name="flow_direction"
                                                 value="source_to_target"
                                                 class="w-4 h-4 border-gray-600 bg-gray-700 text-blue-600 focus:ring-2 focus:ring-blue-500 cursor-pointer"
-                                                checked=move || flow_direction.get() == "source_to_target"
-                                                on:change=move |_| flow_direction.set("source_to_target".to_string())
+                                                checked=move || flow_direction.get() == FlowDirection::SourceToTarget
+                                                on:change=move |_| flow_direction.set(FlowDirection::SourceToTarget)
                                             />
                                             <label for="flow-source-to-target" class="text-sm text-gray-400 cursor-pointer">
                                                 "Source → Target"
@@ -3169,8 +6254,8 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
                                                 name="flow_direction"
                                                 value="target_to_source"
                                                 class="w-4 h-4 border-gray-600 bg-gray-700 text-blue-600 focus:ring-2 focus:ring-blue-500 cursor-pointer"
-                                                checked=move || flow_direction.get() == "target_to_source"
-                                                on:change=move |_| flow_direction.set("target_to_source".to_string())
+                                                checked=move || flow_direction.get() == FlowDirection::TargetToSource
+                                                on:change=move |_| flow_direction.set(FlowDirection::TargetToSource)
                                             />
                                             <label for="flow-target-to-source" class="text-sm text-gray-400 cursor-pointer">
                                                 "Target → Source"
@@ -3184,8 +6269,8 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
                                                 name="flow_direction"
                                                 value="bidirectional"
                                                 class="w-4 h-4 border-gray-600 bg-gray-700 text-blue-600 focus:ring-2 focus:ring-blue-500 cursor-pointer"
-                                                checked=move || flow_direction.get() == "bidirectional"
-                                                on:change=move |_| flow_direction.set("bidirectional".to_string())
+                                                checked=move || flow_direction.get() == FlowDirection::Bidirectional
+                                                on:change=move |_| flow_direction.set(FlowDirection::Bidirectional)
                                             />
                                             <label for="flow-bidirectional" class="text-sm text-gray-400 cursor-pointer">
                                                 "Bidirectional"
@@ -3194,13 +6279,15 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
                                     </div>
                                 </div>
 
+                                <TagsEditor connection_id=connection_id />
+
                                 <div class="pt-4 border-t border-gray-700">
                                     // Save button group
                                     <div class="mb-4">
                                         <button
                                             class="w-full px-4 py-2 bg-blue-600 hover:bg-blue-700 rounded text-sm font-medium transition disabled:opacity-50 disabled:cursor-not-allowed"
-                                            on:click=move |_| { save_action.dispatch(()); }
-                                            disabled=move || save_action.pending().get()
+                                            on:click=move |_| save()
+                                            disabled=move || save_action.pending().get() || conflict.get().is_some()
                                         >
                                             {move || if save_action.pending().get() {
                                                 "Saving..."
@@ -3276,3 +6363,278 @@ fn ConnectionProperties(connection_id: i64) -> impl IntoView {
         </div>
     }
 }
+
+/// Groups as `<select>` options in tree order, indented by depth
+fn group_options(groups: &[crate::models::NodeGroup]) -> Vec<(i64, String)> {
+    group_tree(groups)
+        .into_iter()
+        .map(|(group, depth)| (group.id, format!("{}{}", "\u{a0}\u{a0}".repeat(depth), group.name)))
+        .collect()
+}
+
+/// Group properties: name, type, parent, color, collapse, moving its members, and delete
+#[component]
+fn GroupProperties(group_id: i64) -> impl IntoView {
+    let refetch_trigger = use_context::<RwSignal<u32>>().expect("refetch_trigger context");
+    let current_topology_id = use_context::<RwSignal<i64>>().expect("current_topology_id context");
+    let selected_item =
+        use_context::<RwSignal<Option<SelectedItem>>>().expect("selected_item context");
+
+    let move_x = RwSignal::new(0.0_f64);
+    let move_y = RwSignal::new(0.0_f64);
+    let move_z = RwSignal::new(0.0_f64);
+
+    // The whole topology: the group, its child groups and its member nodes
+    let topology = LocalResource::new(move || {
+        let topology_id = current_topology_id.get();
+        refetch_trigger.track();
+        async move { get_topology_full(topology_id).await.ok() }
+    });
+
+    let update_action = Action::new(move |data: &UpdateNodeGroup| {
+        let data = data.clone();
+        async move { update_node_group(group_id, data).await }
+    });
+
+    Effect::new(move || {
+        if let Some(Ok(_)) = update_action.value().get() {
+            refetch_trigger.update(|v| *v += 1);
+        }
+    });
+
+    let move_action = Action::new(move |_: &()| {
+        let (dx, dy, dz) = (move_x.get_untracked(), move_y.get_untracked(), move_z.get_untracked());
+        async move { move_node_group(group_id, dx, dy, dz).await }
+    });
+
+    Effect::new(move || {
+        if let Some(Ok(_)) = move_action.value().get() {
+            move_x.set(0.0);
+            move_y.set(0.0);
+            move_z.set(0.0);
+            refetch_trigger.update(|v| *v += 1);
+        }
+    });
+
+    let delete_action = Action::new(move |_: &()| async move { delete_node_group(group_id).await });
+
+    Effect::new(move || {
+        if let Some(Ok(())) = delete_action.value().get() {
+            selected_item.set(None);
+            refetch_trigger.update(|v| *v += 1);
+        }
+    });
+
+    let input_class = "w-full px-3 py-2 bg-gray-700 border border-gray-600 rounded text-sm focus:outline-none focus:border-blue-500";
+    let small_input_class = "w-full px-1.5 py-1 bg-gray-700 border border-gray-600 rounded text-xs focus:outline-none focus:border-blue-500";
+
+    view! {
+        <div class="space-y-4">
+            {move || topology.get().map(|data| {
+                let Some(data) = data else {
+                    return view! { <div class="text-sm text-gray-500">"Failed to load group"</div> }.into_any();
+                };
+                let Some(group) = data.groups.iter().find(|g| g.id == group_id).cloned() else {
+                    return view! { <div class="text-sm text-gray-500">"Group not found"</div> }.into_any();
+                };
+
+                // Members of this group and of every group nested in it
+                let subtree = group_subtree(&data.groups, group_id);
+                let members: Vec<crate::models::Node> = data
+                    .nodes
+                    .iter()
+                    .filter(|node| node.group_id.is_some_and(|id| subtree.contains(&id)))
+                    .cloned()
+                    .collect();
+                let center = (!members.is_empty()).then(|| {
+                    let n = members.len() as f64;
+                    (
+                        members.iter().map(|node| node.position_x).sum::<f64>() / n,
+                        members.iter().map(|node| node.position_y).sum::<f64>() / n,
+                        members.iter().map(|node| node.position_z).sum::<f64>() / n,
+                    )
+                });
+
+                // A group can't move inside itself
+                let parent_options: Vec<(i64, String)> = group_options(&data.groups)
+                    .into_iter()
+                    .filter(|(id, _)| !subtree.contains(id))
+                    .collect();
+                let parent_id = group.parent_id.unwrap_or(0);
+                let group_type = group.group_type;
+                let collapsed = group.collapsed;
+
+                view! {
+                    <div class="space-y-4">
+                        <div>
+                            <label class="block text-xs font-medium text-gray-400 mb-1">"Group Name"</label>
+                            <input
+                                type="text"
+                                class=input_class
+                                prop:value=group.name.clone()
+                                on:change=move |ev| {
+                                    update_action.dispatch(UpdateNodeGroup {
+                                        name: Some(event_target_value(&ev)),
+                                        ..Default::default()
+                                    });
+                                }
+                            />
+                        </div>
+
+                        <div>
+                            <label class="block text-xs font-medium text-gray-400 mb-1">"Type"</label>
+                            <select
+                                class=input_class
+                                prop:value=group_type.as_str()
+                                on:change=move |ev| {
+                                    if let Ok(value) = event_target_value(&ev).parse() {
+                                        update_action.dispatch(UpdateNodeGroup {
+                                            group_type: Some(value),
+                                            ..Default::default()
+                                        });
+                                    }
+                                }
+                            >
+                                {GroupType::ALL.iter().map(|t| view! {
+                                    <option value=t.as_str() selected=*t == group_type>{t.label()}</option>
+                                }).collect_view()}
+                            </select>
+                        </div>
+
+                        <div>
+                            <label class="block text-xs font-medium text-gray-400 mb-1">"Parent"</label>
+                            <select
+                                class=input_class
+                                prop:value=parent_id.to_string()
+                                on:change=move |ev| {
+                                    if let Ok(id) = event_target_value(&ev).parse::<i64>() {
+                                        update_action.dispatch(UpdateNodeGroup {
+                                            parent_id: Some(id),
+                                            ..Default::default()
+                                        });
+                                    }
+                                }
+                            >
+                                <option value="0" selected=parent_id == 0>"Top level"</option>
+                                {parent_options.into_iter().map(|(id, label)| view! {
+                                    <option value=id.to_string() selected=id == parent_id>{label}</option>
+                                }).collect_view()}
+                            </select>
+                        </div>
+
+                        <div class="flex items-center gap-3">
+                            <div>
+                                <label class="block text-xs font-medium text-gray-400 mb-1">"Color"</label>
+                                <input
+                                    type="color"
+                                    class="w-12 h-8 bg-gray-700 border border-gray-600 rounded cursor-pointer"
+                                    prop:value=group.color.to_hex()
+                                    on:change=move |ev| {
+                                        if let Ok(color) = event_target_value(&ev).parse::<Rgb>() {
+                                            update_action.dispatch(UpdateNodeGroup {
+                                                color: Some(color),
+                                                ..Default::default()
+                                            });
+                                        }
+                                    }
+                                />
+                            </div>
+                            <label class="flex items-center gap-2 text-xs text-gray-300 mt-4 cursor-pointer">
+                                <input
+                                    type="checkbox"
+                                    prop:checked=collapsed
+                                    on:change=move |ev| {
+                                        update_action.dispatch(UpdateNodeGroup {
+                                            collapsed: Some(event_target_checked(&ev)),
+                                            ..Default::default()
+                                        });
+                                    }
+                                />
+                                "Collapsed (show as one node)"
+                            </label>
+                        </div>
+
+                        {move || match update_action.value().get() {
+                            Some(Err(e)) => Some(view! { <div class="text-xs text-red-400">{e.to_string()}</div> }),
+                            _ => None,
+                        }}
+
+                        <div>
+                            <label class="block text-xs font-medium text-gray-400 mb-1">"Move Members By"</label>
+                            <div class="grid grid-cols-3 gap-1">
+                                {[(move_x, "X", "text-red-400"), (move_y, "Y", "text-green-400"), (move_z, "Z", "text-blue-400")]
+                                    .into_iter()
+                                    .map(|(offset, axis, color)| view! {
+                                        <div>
+                                            <label class=format!("block text-[10px] font-medium mb-0.5 {}", color)>{axis}</label>
+                                            <input
+                                                type="number"
+                                                class=small_input_class
+                                                step="0.1"
+                                                prop:value=move || offset.get()
+                                                on:input=move |ev| {
+                                                    if let Ok(val) = event_target_value(&ev).parse::<f64>() {
+                                                        offset.set(val);
+                                                    }
+                                                }
+                                            />
+                                        </div>
+                                    })
+                                    .collect_view()}
+                            </div>
+                            <button
+                                class="w-full mt-1 px-3 py-1.5 bg-blue-600 hover:bg-blue-700 rounded text-xs font-medium transition disabled:opacity-50 disabled:cursor-not-allowed"
+                                disabled=move || move_action.pending().get()
+                                    || (move_x.get() == 0.0 && move_y.get() == 0.0 && move_z.get() == 0.0)
+                                on:click=move |_| { move_action.dispatch(()); }
+                            >
+                                "Move Group"
+                            </button>
+                            {center.map(|(x, y, z)| view! {
+                                <p class="text-[10px] text-gray-500 mt-0.5">{format!("Center: {:.1}, {:.1}, {:.1}", x, y, z)}</p>
+                            })}
+                            {move || match move_action.value().get() {
+                                Some(Err(e)) => Some(view! { <div class="text-xs text-red-400">{e.to_string()}</div> }),
+                                _ => None,
+                            }}
+                        </div>
+
+                        <div>
+                            <label class="block text-xs font-medium text-gray-400 mb-1">
+                                {format!("Members ({})", members.len())}
+                            </label>
+                            <div class="max-h-40 overflow-y-auto space-y-0.5">
+                                {members.into_iter().map(|node| {
+                                    let node_id = node.id;
+                                    view! {
+                                        <button
+                                            class="w-full text-left px-2 py-0.5 rounded text-xs text-gray-300 hover:bg-gray-700 truncate"
+                                            on:click=move |_| selected_item.set(Some(SelectedItem::Node(node_id)))
+                                        >
+                                            {node.name}
+                                        </button>
+                                    }
+                                }).collect_view()}
+                            </div>
+                        </div>
+
+                        <div class="pt-4 border-t border-gray-700">
+                            <button
+                                class="w-full px-4 py-2 bg-red-600 hover:bg-red-700 rounded text-sm font-medium transition disabled:opacity-50 disabled:cursor-not-allowed"
+                                title="Members and child groups move up to the parent group"
+                                on:click=move |_| { delete_action.dispatch(()); }
+                                disabled=move || delete_action.pending().get()
+                            >
+                                "Delete Group"
+                            </button>
+                            {move || match delete_action.value().get() {
+                                Some(Err(e)) => Some(view! { <div class="mt-2 text-xs text-red-400 text-center">{format!("Error: {}", e)}</div> }),
+                                _ => None,
+                            }}
+                        </div>
+                    </div>
+                }.into_any()
+            })}
+        </div>
+    }
+}
@@ -4,6 +4,8 @@ use leptos::html::Canvas;
 // Import server function from api module (available on both client and server)
 use crate::api::get_topology_full;
 
+#[cfg(feature = "hydrate")]
+use crate::models::{ConnectionStatus, NodeType, Rgb};
 #[cfg(feature = "hydrate")]
 use wasm_bindgen::JsCast;
 
@@ -37,6 +39,32 @@ impl CameraState {
     }
 }
 
+#[cfg(feature = "hydrate")]
+impl From<crate::models::CameraView> for CameraState {
+    fn from(view: crate::models::CameraView) -> Self {
+        CameraState {
+            distance: view.distance,
+            azimuth: view.azimuth,
+            elevation: view.elevation,
+            pan_x: view.pan_x,
+            pan_y: view.pan_y,
+        }
+    }
+}
+
+#[cfg(feature = "hydrate")]
+impl From<CameraState> for crate::models::CameraView {
+    fn from(state: CameraState) -> Self {
+        crate::models::CameraView {
+            distance: state.distance,
+            azimuth: state.azimuth,
+            elevation: state.elevation,
+            pan_x: state.pan_x,
+            pan_y: state.pan_y,
+        }
+    }
+}
+
 /// Traffic particle for animation along connections
 #[cfg(feature = "hydrate")]
 #[derive(Clone, Debug)]
@@ -129,16 +157,16 @@ pub async fn spawn_traffic_particles(topology_id: i64) {
     // Spawn particles
     let mut spawned_particles = Vec::new();
     for conn in &topology_data.connections {
-        // Only spawn particles if carries_traffic is true, traffic metrics exist, AND status is not "Error"
-        if conn.carries_traffic && !conn.status.eq_ignore_ascii_case("error") {
+        // Only spawn particles if carries_traffic is true, traffic metrics exist, AND status is not Error
+        if conn.carries_traffic && conn.status != ConnectionStatus::Error {
             if let Some(metric) = metrics.get(&conn.id) {
                 let particle_count = get_particle_count(metric.utilization_pct);
                 let particle_speed = get_particle_speed(); // Constant speed for all particles
                 let particle_color = get_traffic_color(metric.utilization_pct);
 
                 // Determine which directions to spawn particles
-                let spawn_forward = conn.flow_direction == "source_to_target" || conn.flow_direction == "bidirectional";
-                let spawn_backward = conn.flow_direction == "target_to_source" || conn.flow_direction == "bidirectional";
+                let spawn_forward = conn.flow_direction.forward();
+                let spawn_backward = conn.flow_direction.backward();
 
                 // Spawn particles evenly spaced along the connection
                 for i in 0..particle_count {
@@ -309,6 +337,40 @@ fn calculate_zoom_to_fit(nodes_data: &[NodeData]) -> CameraState {
     }
 }
 
+/// Camera state centered on a node or connection, keeping the current viewing angle
+///
+/// Returns None when the viewport isn't showing the item (hidden, or inside a collapsed group).
+#[cfg(feature = "hydrate")]
+fn focus_camera_on(
+    item: &crate::islands::topology_editor::SelectedItem,
+    nodes_data: &[NodeData],
+    connections_data: &[ConnectionData],
+    current: CameraState,
+) -> Option<CameraState> {
+    use crate::islands::topology_editor::SelectedItem;
+    use three_d::InnerSpace;
+
+    let (center, distance) = match item {
+        SelectedItem::Node(id) => {
+            let node = nodes_data.iter().find(|node| node.id == *id && !node.is_group_proxy)?;
+            (node.position, 6.0)
+        }
+        SelectedItem::Connection(id) => {
+            let connection = connections_data.iter().find(|connection| connection.id == *id)?;
+            let length = (connection.target_pos - connection.source_pos).magnitude();
+            ((connection.source_pos + connection.target_pos) * 0.5, (length * 1.5).max(6.0))
+        }
+        SelectedItem::Group(_) => return None,
+    };
+
+    Some(CameraState {
+        distance,
+        pan_x: center.x,
+        pan_y: center.y,
+        ..current
+    })
+}
+
 /// Animate camera from start to target position using smooth interpolation
 #[cfg(feature = "hydrate")]
 fn animate_camera(
@@ -413,6 +475,18 @@ pub fn TopologyViewport(
     // Get camera controls from context (optional - may not exist)
     let camera_controls = use_context::<crate::islands::topology_editor::CameraControls>();
     let preset_trigger = camera_controls.map(|c| c.preset_trigger);
+    #[allow(unused_variables)]
+    let pending_view = camera_controls.map(|c| c.pending_view);
+    #[allow(unused_variables)]
+    let focus_item = camera_controls.map(|c| c.focus_item);
+
+    // Get compare mode from context (optional - may not exist)
+    #[allow(unused_variables)]
+    let compare_diff = use_context::<crate::islands::topology_editor::CompareMode>().map(|c| c.diff);
+
+    // Get the filter bar highlight from context (optional - may not exist)
+    #[allow(unused_variables)]
+    let filter_highlight = use_context::<crate::islands::topology_editor::FilterHighlight>().map(|f| f.matches);
 
     let canvas_ref = NodeRef::<Canvas>::new();
     let error_signal = RwSignal::new(None::<String>);
@@ -424,6 +498,9 @@ pub fn TopologyViewport(
     #[cfg(not(feature = "hydrate"))]
     let tooltip_data = RwSignal::new(None::<(String, String, f64, f64)>);
 
+    // Group labels projected to canvas coordinates on every render: (name, x, y)
+    let group_labels = RwSignal::new(Vec::<(String, f64, f64)>::new());
+
     // Create signal for topology_id (needed for connection creation)
     #[allow(unused_variables)]
     let current_topology_id = RwSignal::new(topology_id.unwrap_or(1));
@@ -467,6 +544,27 @@ pub fn TopologyViewport(
         },
     );
 
+    // Latest topology data: the last fetch with other editors' node and connection
+    // changes applied on top, so remote edits re-render without a refetch
+    let live_topology = RwSignal::new(None::<crate::models::TopologyFull>);
+    Effect::new(move || live_topology.set(topology_data.get().flatten()));
+    if let Some(live_session) = use_context::<crate::islands::topology_editor::LiveSession>() {
+        Effect::new(move || {
+            let changes = live_session.remote_changes.get();
+            if changes.is_empty() {
+                return;
+            }
+            live_session.remote_changes.update_untracked(Vec::clear);
+            live_topology.update(|data| {
+                if let Some(data) = data {
+                    for change in &changes {
+                        data.apply_live_event(change);
+                    }
+                }
+            });
+        });
+    }
+
     // Clone render_fn and data storages for the Effect closure below
     #[cfg(feature = "hydrate")]
     let render_fn_for_effect = render_fn.clone();
@@ -481,11 +579,11 @@ pub fn TopologyViewport(
         if let Some(canvas_element) = canvas_ref.get() {
             #[cfg(feature = "hydrate")]
             {
-                // Access topology_data to make Effect reactive to it
-                let data_option = topology_data.get();
+                // Access live_topology to make Effect reactive to it
+                let data_option = live_topology.get();
 
                 // Wait for topology data to load
-                if let Some(Some(topo_data)) = data_option {
+                if let Some(topo_data) = data_option {
                     // Check if this is a refetch (already initialized)
                     let already_initialized = is_initialized.get_untracked();
 
@@ -501,6 +599,8 @@ pub fn TopologyViewport(
                     let render_fn = render_fn_for_effect.clone();
                     let nodes_storage = nodes_data_for_effect.clone();
                     let connections_storage = connections_data_for_effect.clone();
+                    let focus_nodes = nodes_storage.clone();
+                    let focus_connections = connections_storage.clone();
 
                     wasm_bindgen_futures::spawn_local(async move {
                         match initialize_threed_viewport(
@@ -509,10 +609,11 @@ pub fn TopologyViewport(
                             &topo,
                             selected_node_id,
                             selected_item,
-                            render_fn,
+                            render_fn.clone(),
                             nodes_storage,
                             connections_storage,
                             tooltip_data,
+                            group_labels,
                             show_grid,
                             show_x_axis,
                             show_y_axis,
@@ -526,10 +627,35 @@ pub fn TopologyViewport(
                             rim_light_intensity,
                             refetch_trigger,
                             Some(current_topology_id),
+                            compare_diff,
+                            filter_highlight,
                             already_initialized, // Skip event handlers on refetch
                         ).await {
                             Ok(_) => {
                                 // is_initialized was already set before spawning to prevent race condition
+
+                                // Restore a camera saved with an imported topology document
+                                if let Some(view) = pending_view.and_then(|p| p.try_get_untracked()).flatten() {
+                                    if let Some(pending) = pending_view {
+                                        pending.set(None);
+                                    }
+                                    if let Some(start_state) = camera_state.try_get_untracked() {
+                                        animate_camera(camera_state, start_state, view.into(), render_fn.clone());
+                                    }
+                                }
+
+                                // Fly to a search result picked in another topology
+                                if let Some(item) = focus_item.and_then(|f| f.try_get_untracked()).flatten() {
+                                    if let Some(focus) = focus_item {
+                                        focus.set(None);
+                                    }
+                                    if let Some(start_state) = camera_state.try_get_untracked() {
+                                        let target = focus_camera_on(&item, &focus_nodes.borrow(), &focus_connections.borrow(), start_state);
+                                        if let Some(target_state) = target {
+                                            animate_camera(camera_state, start_state, target_state, render_fn);
+                                        }
+                                    }
+                                }
                             }
                             Err(e) => {
                                 web_sys::console::error_1(&format!("Initialization failed: {}", e).into());
@@ -687,6 +813,42 @@ pub fn TopologyViewport(
         });
     }
 
+    // Component-level Effect to publish the camera position (read by JSON export)
+    #[cfg(feature = "hydrate")]
+    {
+        if let Some(controls) = camera_controls {
+            let _effect = Effect::new(move || {
+                if let Some(state) = camera_state.try_get() {
+                    controls.current_view.set(Some(state.into()));
+                }
+            });
+        }
+    }
+
+    // Component-level Effect to fly to a search result in the topology already shown
+    // (results in other topologies are handled once the viewport has reloaded)
+    #[cfg(feature = "hydrate")]
+    {
+        if let Some(focus_signal) = focus_item {
+            let render_fn = render_fn.clone();
+            let nodes_storage = nodes_data_storage.clone();
+            let connections_storage = connections_data_storage.clone();
+
+            let _effect = Effect::new(move || {
+                if let Some(item) = focus_signal.get() {
+                    let target = camera_state.try_get_untracked().and_then(|start_state| {
+                        focus_camera_on(&item, &nodes_storage.borrow(), &connections_storage.borrow(), start_state)
+                            .map(|target_state| (start_state, target_state))
+                    });
+                    if let Some((start_state, target_state)) = target {
+                        focus_signal.set(None);
+                        animate_camera(camera_state, start_state, target_state, render_fn.clone());
+                    }
+                }
+            });
+        }
+    }
+
     // Component-level Effect to handle camera preset triggers
     #[cfg(feature = "hydrate")]
     {
@@ -750,6 +912,17 @@ pub fn TopologyViewport(
                 }
             }}
 
+            // Group labels
+            {move || group_labels.get().into_iter().map(|(name, x, y)| view! {
+                <div
+                    class="absolute -translate-x-1/2 -translate-y-full px-1.5 py-0.5 rounded bg-gray-900 bg-opacity-60 text-gray-200 text-[11px] whitespace-nowrap pointer-events-none"
+                    style:left=format!("{}px", x)
+                    style:top=format!("{}px", y)
+                >
+                    {name}
+                </div>
+            }).collect_view()}
+
             // Tooltip display - shows node or connection info on hover
             {move || {
                 #[cfg(feature = "hydrate")]
@@ -951,6 +1124,7 @@ struct NodeData {
     node_type: String,
     position: three_d::Vec3,
     radius: f32,
+    is_group_proxy: bool, // Stands in for a collapsed group (`id` is then the group id)
 }
 
 // Connection data for selection and tooltips
@@ -1081,6 +1255,10 @@ fn calculate_model_bounding_radius(model: &three_d_asset::Model, scale: f32) ->
 #[cfg(feature = "hydrate")]
 type NodeMesh = three_d::Gm<three_d::Mesh, three_d::PhysicalMaterial>;
 
+// Type alias for a translucent group bounding volume
+#[cfg(feature = "hydrate")]
+type GroupVolume = three_d::Gm<three_d::Mesh, three_d::ColorMaterial>;
+
 /// Initialize three-d Context with topology data
 #[cfg(feature = "hydrate")]
 async fn initialize_threed_viewport(
@@ -1093,6 +1271,7 @@ async fn initialize_threed_viewport(
     nodes_data_storage: Rc<RefCell<Vec<NodeData>>>,
     connections_data_storage: Rc<RefCell<Vec<ConnectionData>>>,
     tooltip_data: RwSignal<Option<TooltipData>>,
+    group_labels: RwSignal<Vec<(String, f64, f64)>>,
     show_grid: RwSignal<bool>,
     show_x_axis: RwSignal<bool>,
     show_y_axis: RwSignal<bool>,
@@ -1106,6 +1285,8 @@ async fn initialize_threed_viewport(
     rim_light_intensity: RwSignal<f32>,
     refetch_trigger: Option<RwSignal<u32>>,
     current_topology_id: Option<RwSignal<i64>>,
+    compare_diff: Option<RwSignal<Option<crate::models::TopologyDiff>>>, // Tint items by change kind when set
+    filter_highlight: Option<RwSignal<Option<crate::models::FilterMatches>>>, // Outline matching items when set
     skip_event_handlers: bool, // Set to true on refetches to avoid duplicate handlers
 ) -> Result<(), String> {
     use web_sys::WebGl2RenderingContext as GL;
@@ -1193,7 +1374,7 @@ async fn initialize_threed_viewport(
         let mut unique_models: std::collections::HashSet<(String, String, String, String)> = std::collections::HashSet::new();
         for node in &topology_data.nodes {
             unique_models.insert((
-                node.node_type.clone(),
+                node.node_type.to_string(),
                 node.vendor.clone(),
                 node.model_name.clone(),
                 format!("{}/{}/{}", node.node_type, node.vendor, node.model_name)
@@ -1248,12 +1429,39 @@ async fn initialize_threed_viewport(
         },
     );
 
+    // Diff to visualize (compare mode)
+    let compare = compare_diff.and_then(|diff| diff.get_untracked());
+
     // Build node name map for connection tooltips
     let mut node_names = std::collections::HashMap::new();
     for node in &topology_data.nodes {
         node_names.insert(node.id, node.name.clone());
     }
 
+    // Groups: members of a collapsed group (at any depth) are drawn as one proxy node
+    let groups_by_id: HashMap<i64, &crate::models::NodeGroup> =
+        topology_data.groups.iter().map(|group| (group.id, group)).collect();
+    let collapsed_ancestor = |group_id: i64| -> Option<i64> {
+        // Outermost collapsed group on the way up to the top level
+        let mut outermost = None;
+        let mut seen = std::collections::HashSet::new();
+        let mut current = Some(group_id);
+        while let Some(id) = current.filter(|id| seen.insert(*id)) {
+            let Some(group) = groups_by_id.get(&id) else { break };
+            if group.collapsed {
+                outermost = Some(id);
+            }
+            current = group.parent_id;
+        }
+        outermost
+    };
+    let collapsed_into: HashMap<i64, i64> = topology_data
+        .nodes
+        .iter()
+        .filter(|node| node.visible)
+        .filter_map(|node| node.group_id.and_then(collapsed_ancestor).map(|group_id| (node.id, group_id)))
+        .collect();
+
     for node in &topology_data.nodes {
         // Skip invisible nodes (Blender-style outliner)
         if !node.visible {
@@ -1269,6 +1477,16 @@ async fn initialize_threed_viewport(
         );
         node_positions.insert(node.id, position);
 
+        // Drawn as its group's proxy instead
+        if collapsed_into.contains_key(&node.id) {
+            continue;
+        }
+
+        // In compare mode the node is drawn flat in its change color
+        let compare_tint = compare
+            .as_ref()
+            .map(|diff| get_compare_color(diff.node_change(node.id).map(|change| change.kind)));
+
         // Check if we have a loaded 3D model for this vendor/model combination
         let model_cache_key = format!("{}:{}:{}", node.node_type, node.vendor, node.model_name);
         let has_model = node_models.get(&model_cache_key).and_then(|opt| opt.as_ref()).is_some();
@@ -1287,9 +1505,10 @@ async fn initialize_threed_viewport(
         nodes_data.push(NodeData {
             id: node.id,
             name: node.name.clone(),
-            node_type: node.node_type.clone(),
+            node_type: node.node_type.to_string(),
             position,
             radius: selection_radius,
+            is_group_proxy: false,
         });
 
         if has_model {
@@ -1305,27 +1524,21 @@ async fn initialize_threed_viewport(
                         // tri_mesh is a TriMesh, which is the same as CpuMesh!
                         // Just pass it directly to Mesh::new()
 
-                        // Parse node color from database (format: "R,G,B")
-                        let node_color = {
-                            let parts: Vec<&str> = node.color.split(',').collect();
-                            if parts.len() == 3 {
-                                if let (Ok(r), Ok(g), Ok(b)) = (
-                                    parts[0].parse::<u8>(),
-                                    parts[1].parse::<u8>(),
-                                    parts[2].parse::<u8>(),
-                                ) {
-                                    Srgba::new(r, g, b, 255)
-                                } else {
-                                    get_node_color(&node.node_type) // Fallback to type-based color
-                                }
-                            } else {
-                                get_node_color(&node.node_type) // Fallback to type-based color
-                            }
-                        };
-                        let (metallic, roughness) = get_node_material_properties(&node.node_type);
+                        let node_color = rgb_to_srgba(node.color);
+                        let (metallic, roughness) = get_node_material_properties(node.node_type);
 
                         // Material system: Use glTF materials with full texture support
-                        let normal_material = if let Some(mat_idx) = primitive.material_index {
+                        let normal_material = if let Some(tint) = compare_tint {
+                            PhysicalMaterial::new_opaque(
+                                &context,
+                                &CpuMaterial {
+                                    albedo: tint,
+                                    metallic,
+                                    roughness,
+                                    ..Default::default()
+                                },
+                            )
+                        } else if let Some(mat_idx) = primitive.material_index {
                             // Model has glTF material
                             if let Some(gltf_mat) = cpu_model.materials.get(mat_idx) {
                                 // Check if material has textures
@@ -1417,24 +1630,9 @@ async fn initialize_threed_viewport(
             }
         } else {
             // Render nodes without 3D models as colored spheres (fallback)
-            // Parse node color from database (format: "R,G,B")
-            let node_color = {
-                let parts: Vec<&str> = node.color.split(',').collect();
-                if parts.len() == 3 {
-                    if let (Ok(r), Ok(g), Ok(b)) = (
-                        parts[0].parse::<u8>(),
-                        parts[1].parse::<u8>(),
-                        parts[2].parse::<u8>(),
-                    ) {
-                        Srgba::new(r, g, b, 255)
-                    } else {
-                        get_node_color(&node.node_type) // Fallback to type-based color
-                    }
-                } else {
-                    get_node_color(&node.node_type) // Fallback to type-based color
-                }
-            };
-            let (metallic, roughness) = get_node_material_properties(&node.node_type);
+            let node_color = rgb_to_srgba(node.color);
+            let node_color = compare_tint.unwrap_or(node_color);
+            let (metallic, roughness) = get_node_material_properties(node.node_type);
 
             // Create material for this node type with PBR properties
             let normal_material = PhysicalMaterial::new_opaque(
@@ -1470,6 +1668,123 @@ async fn initialize_threed_viewport(
         }
     }
 
+    // One proxy per collapsed group at its members' centroid; members' links reroute to it
+    let mut proxy_members: HashMap<i64, Vec<Vec3>> = HashMap::new();
+    for (node_id, group_id) in &collapsed_into {
+        if let Some(&position) = node_positions.get(node_id) {
+            proxy_members.entry(*group_id).or_default().push(position);
+        }
+    }
+    let proxy_radius = node_radius * 1.8;
+    let mut proxy_positions: HashMap<i64, Vec3> = HashMap::new();
+    let mut proxy_meshes: Vec<(i64, NodeMesh, NodeMesh)> = Vec::new();
+    let mut group_label_anchors: Vec<(String, Vec3)> = Vec::new();
+    for (&group_id, positions) in &proxy_members {
+        let group = groups_by_id[&group_id];
+        let center = positions.iter().fold(vec3(0.0, 0.0, 0.0), |sum, &p| sum + p) / positions.len() as f32;
+        proxy_positions.insert(group_id, center);
+
+        let transformation = Mat4::from_translation(center) * Mat4::from_scale(proxy_radius);
+        let mut normal_mesh = Gm::new(
+            Mesh::new(&context, &sphere_cpu_mesh),
+            PhysicalMaterial::new_opaque(
+                &context,
+                &CpuMaterial {
+                    albedo: Srgba::new(group.color.r, group.color.g, group.color.b, 255),
+                    metallic: 0.2,
+                    roughness: 0.5,
+                    ..Default::default()
+                },
+            ),
+        );
+        normal_mesh.set_transformation(transformation);
+        let mut selected_mesh = Gm::new(Mesh::new(&context, &sphere_cpu_mesh), selected_material.clone());
+        selected_mesh.set_transformation(transformation);
+        proxy_meshes.push((group_id, normal_mesh, selected_mesh));
+
+        nodes_data.push(NodeData {
+            id: group_id,
+            name: format!("{} ({} nodes)", group.name, positions.len()),
+            node_type: group.group_type.label().to_string(),
+            position: center,
+            radius: proxy_radius * 1.5,
+            is_group_proxy: true,
+        });
+        group_label_anchors.push((group.name.clone(), center + vec3(0.0, 0.0, proxy_radius + 0.15)));
+    }
+    for (node_id, group_id) in &collapsed_into {
+        if let Some(&center) = proxy_positions.get(group_id) {
+            node_positions.insert(*node_id, center);
+        }
+    }
+
+    // Bounding volumes of expanded groups, children first so each parent encloses them
+    let cube_cpu_mesh = CpuMesh::cube();
+    let mut group_bounds: HashMap<i64, (Vec3, Vec3)> = HashMap::new();
+    let mut group_volumes: Vec<(i64, GroupVolume, GroupVolume)> = Vec::new();
+    for (group, _) in crate::models::group_tree(&topology_data.groups).into_iter().rev() {
+        if collapsed_ancestor(group.id).is_some() {
+            continue;
+        }
+        let node_pad = vec3(0.5, 0.5, 0.5);
+        let child_pad = vec3(0.3, 0.3, 0.3);
+        let mut bounds = None;
+        for node in topology_data.nodes.iter().filter(|node| node.visible && node.group_id == Some(group.id)) {
+            if let Some(&position) = node_positions.get(&node.id) {
+                bounds = extend_bounds(bounds, position - node_pad, position + node_pad);
+            }
+        }
+        for child in topology_data.groups.iter().filter(|child| child.parent_id == Some(group.id)) {
+            if let Some(&(min, max)) = group_bounds.get(&child.id) {
+                bounds = extend_bounds(bounds, min - child_pad, max + child_pad);
+            } else if let Some(&center) = proxy_positions.get(&child.id) {
+                let pad = vec3(proxy_radius, proxy_radius, proxy_radius) + child_pad;
+                bounds = extend_bounds(bounds, center - pad, center + pad);
+            }
+        }
+        let Some((min, max)) = bounds else { continue }; // Empty group
+        group_bounds.insert(group.id, (min, max));
+
+        // Sites and buildings sit on a floor plate; racks and zones get a box around their members
+        let (center, half_size, alpha, anchor) = if group.group_type.is_floor() {
+            let z = min.z - 0.05;
+            let center = vec3((min.x + max.x) / 2.0, (min.y + max.y) / 2.0, z);
+            let half_size = vec3((max.x - min.x) / 2.0, (max.y - min.y) / 2.0, 0.02);
+            (center, half_size, 70, vec3(min.x, min.y, z))
+        } else {
+            let center = (min + max) / 2.0;
+            (center, (max - min) / 2.0, 40, vec3(center.x, center.y, max.z))
+        };
+        let volume = |alpha: u8| {
+            let mut mesh = Gm::new(
+                Mesh::new(&context, &cube_cpu_mesh),
+                ColorMaterial::new_transparent(
+                    &context,
+                    &CpuMaterial {
+                        albedo: Srgba::new(group.color.r, group.color.g, group.color.b, alpha),
+                        ..Default::default()
+                    },
+                ),
+            );
+            mesh.set_transformation(
+                Mat4::from_translation(center) * Mat4::from_nonuniform_scale(half_size.x, half_size.y, half_size.z),
+            );
+            mesh
+        };
+        group_volumes.push((group.id, volume(alpha), volume(alpha * 2)));
+        group_label_anchors.push((group.name.clone(), anchor));
+    }
+
+    // Links inside a collapsed group would start and end at its proxy
+    let connections: Vec<&crate::models::Connection> = topology_data
+        .connections
+        .iter()
+        .filter(|conn| match (collapsed_into.get(&conn.source_node_id), collapsed_into.get(&conn.target_node_id)) {
+            (Some(source_group), Some(target_group)) => source_group != target_group,
+            _ => true,
+        })
+        .collect();
+
     // Create grid and axes for spatial reference (always create all meshes, visibility controlled at render time)
     let grid_axes_meshes = create_grid_and_axes(
         &context,
@@ -1500,7 +1815,7 @@ async fn initialize_threed_viewport(
     // Note: Connections to invisible nodes are automatically filtered out
     // (node_positions only contains visible nodes)
     let mut connection_positions: HashMap<i64, (Vec3, Vec3)> = HashMap::new();
-    for conn in &topology_data.connections {
+    for conn in &connections {
         if let (Some(&source_pos), Some(&target_pos)) = (
             node_positions.get(&conn.source_node_id),
             node_positions.get(&conn.target_node_id),
@@ -1518,7 +1833,7 @@ async fn initialize_threed_viewport(
     let mut connection_meshes = Vec::new();
     let mut connections_data = Vec::new();
 
-    for conn in &topology_data.connections {
+    for conn in &connections {
         if let (Some(&start_pos), Some(&end_pos)) = (
             node_positions.get(&conn.source_node_id),
             node_positions.get(&conn.target_node_id),
@@ -1564,39 +1879,19 @@ async fn initialize_threed_viewport(
                     get_traffic_color(metric.utilization_pct)
                 } else {
                     // No traffic data for this connection - use custom color
-                    let parts: Vec<&str> = conn.color.split(',').collect();
-                    if parts.len() == 3 {
-                        if let (Ok(r), Ok(g), Ok(b)) = (
-                            parts[0].parse::<u8>(),
-                            parts[1].parse::<u8>(),
-                            parts[2].parse::<u8>(),
-                        ) {
-                            Srgba::new(r, g, b, 255)
-                        } else {
-                            Srgba::new(128, 128, 128, 255) // Fallback gray
-                        }
-                    } else {
-                        Srgba::new(128, 128, 128, 255) // Fallback gray
-                    }
+                    rgb_to_srgba(conn.color)
                 }
             } else {
                 // No traffic data at all - use custom color from database
-                let parts: Vec<&str> = conn.color.split(',').collect();
-                if parts.len() == 3 {
-                    if let (Ok(r), Ok(g), Ok(b)) = (
-                        parts[0].parse::<u8>(),
-                        parts[1].parse::<u8>(),
-                        parts[2].parse::<u8>(),
-                    ) {
-                        Srgba::new(r, g, b, 255)
-                    } else {
-                        Srgba::new(128, 128, 128, 255) // Fallback gray
-                    }
-                } else {
-                    Srgba::new(128, 128, 128, 255) // Fallback gray
-                }
+                rgb_to_srgba(conn.color)
             };
 
+            // Compare mode overrides traffic and custom colors
+            let normal_color = compare
+                .as_ref()
+                .map(|diff| get_compare_color(diff.connection_change(conn.id).map(|change| change.kind)))
+                .unwrap_or(normal_color);
+
             // Selected color - bright yellow/orange for visibility
             let selected_color = Srgba::new(255, 200, 0, 255);
 
@@ -1627,8 +1922,8 @@ async fn initialize_threed_viewport(
 
     // Create error icons for connections with "Error" status (Phase 6.4.2)
     let mut error_icons = Vec::new();
-    for conn in &topology_data.connections {
-        if conn.status.eq_ignore_ascii_case("error") {
+    for conn in &connections {
+        if conn.status == ConnectionStatus::Error {
             if let (Some(&start_pos), Some(&end_pos)) = (
                 node_positions.get(&conn.source_node_id),
                 node_positions.get(&conn.target_node_id),
@@ -1691,6 +1986,113 @@ async fn initialize_threed_viewport(
         }
     }
 
+    // Compare mode: ghost spheres for removed nodes, lines for removed connections and node moves
+    let mut compare_ghosts = Vec::new();
+    let mut compare_lines = Vec::new();
+    if let Some(diff) = &compare {
+        use crate::models::ChangeKind;
+
+        let removed_color = get_compare_color(Some(ChangeKind::Removed));
+        let base_position = |node: &crate::models::Node| {
+            vec3(node.position_x as f32, node.position_y as f32, node.position_z as f32)
+        };
+        let mut removed_positions = HashMap::new();
+
+        for change in &diff.nodes {
+            match (change.kind, &change.base, &change.target) {
+                (ChangeKind::Removed, Some(base), _) => {
+                    let position = base_position(base);
+                    removed_positions.insert(base.id, position);
+                    let mut ghost = Gm::new(
+                        Mesh::new(&context, &sphere_cpu_mesh),
+                        PhysicalMaterial::new_opaque(
+                            &context,
+                            &CpuMaterial {
+                                albedo: removed_color,
+                                roughness: 0.8,
+                                ..Default::default()
+                            },
+                        ),
+                    );
+                    ghost.set_transformation(
+                        Mat4::from_translation(position) * Mat4::from_scale(node_radius * base.scale as f32),
+                    );
+                    compare_ghosts.push(ghost);
+                }
+                // Trail from the old position to the new one
+                (ChangeKind::Modified, Some(base), Some(target))
+                    if change.changes.iter().any(|field| field.field == "position") =>
+                {
+                    if let Some(&new_position) = node_positions.get(&target.id) {
+                        let moved_color = get_compare_color(Some(ChangeKind::Modified));
+                        if let Some(line) = create_line_cylinder(
+                            &context,
+                            base_position(base),
+                            new_position,
+                            0.008,
+                            moved_color,
+                            &cylinder_cpu_mesh,
+                        ) {
+                            compare_lines.push(line);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Removed connections attach to the matching current node, or to a removed node's ghost
+        let endpoint_position = |base_node_id: i64| {
+            diff.matched_node(base_node_id)
+                .and_then(|id| node_positions.get(&id).copied())
+                .or_else(|| removed_positions.get(&base_node_id).copied())
+        };
+        for change in diff.connections.iter().filter(|change| change.kind == ChangeKind::Removed) {
+            let Some(base) = &change.base else { continue };
+            if let (Some(start_pos), Some(end_pos)) =
+                (endpoint_position(base.source_node_id), endpoint_position(base.target_node_id))
+            {
+                if let Some(line) =
+                    create_line_cylinder(&context, start_pos, end_pos, 0.012, removed_color, &cylinder_cpu_mesh)
+                {
+                    compare_lines.push(line);
+                }
+            }
+        }
+    }
+
+    // Filter highlight: a translucent halo around matching nodes, a bright sleeve around matching connections
+    let mut highlight_meshes = Vec::new();
+    if let Some(matches) = filter_highlight.and_then(|matches| matches.get_untracked()) {
+        let halo_color = Srgba::new(0, 230, 255, 90);
+        for node in nodes_data.iter().filter(|node| !node.is_group_proxy && matches.has_node(node.id)) {
+            let mut halo = Gm::new(
+                Mesh::new(&context, &sphere_cpu_mesh),
+                ColorMaterial::new_transparent(
+                    &context,
+                    &CpuMaterial {
+                        albedo: halo_color,
+                        ..Default::default()
+                    },
+                ),
+            );
+            halo.set_transformation(Mat4::from_translation(node.position) * Mat4::from_scale(node.radius * 1.4));
+            highlight_meshes.push(halo);
+        }
+        for connection in connections_data.iter().filter(|connection| matches.has_connection(connection.id)) {
+            if let Some(sleeve) = create_line_cylinder(
+                &context,
+                connection.source_pos,
+                connection.target_pos,
+                0.03,
+                Srgba::new(0, 230, 255, 255),
+                &cylinder_cpu_mesh,
+            ) {
+                highlight_meshes.push(sleeve);
+            }
+        }
+    }
+
     // NOTE: Lights will be created dynamically in render closure based on current signal values
     // This allows real-time updates when intensity settings change
 
@@ -1700,8 +2102,14 @@ async fn initialize_threed_viewport(
 
     // Wrap meshes in Rc<RefCell> for render closure
     let node_meshes = Rc::new(RefCell::new(node_meshes));
+    let proxy_meshes = Rc::new(proxy_meshes); // Collapsed groups
+    let group_volumes = Rc::new(group_volumes); // Expanded groups: (group_id, normal, selected)
+    let group_label_anchors = Rc::new(group_label_anchors);
     let connection_meshes = Rc::new(RefCell::new(connection_meshes));
     let error_icons = Rc::new(RefCell::new(error_icons)); // Error icons for connections with "Error" status
+    let compare_ghosts = Rc::new(compare_ghosts); // Removed nodes (compare mode)
+    let compare_lines = Rc::new(compare_lines); // Removed connections and node moves (compare mode)
+    let highlight_meshes = Rc::new(highlight_meshes); // Filter bar matches
     let grid_axes_meshes = Rc::new(RefCell::new(grid_axes_meshes)); // RefCell so we can update it
 
     // Get canvas dimensions
@@ -1716,8 +2124,14 @@ async fn initialize_threed_viewport(
     let render_scene = {
         let context = context.clone();
         let node_meshes = node_meshes.clone();
+        let proxy_meshes = proxy_meshes.clone();
+        let group_volumes = group_volumes.clone();
+        let group_label_anchors = group_label_anchors.clone();
         let connection_meshes = connection_meshes.clone();
         let error_icons = error_icons.clone(); // Clone for render closure
+        let compare_ghosts = compare_ghosts.clone();
+        let compare_lines = compare_lines.clone();
+        let highlight_meshes = highlight_meshes.clone();
         let grid_axes_meshes = grid_axes_meshes.clone();
         let canvas = canvas.clone();
         let connection_positions = connection_positions.clone(); // Capture connection positions for particle interpolation
@@ -1832,6 +2246,17 @@ async fn initialize_threed_viewport(
             let target = RenderTarget::screen(&context, width, height);
             target.clear(clear_state);
 
+            // Group labels are HTML over the canvas; skip anchors behind the camera
+            let labels: Vec<(String, f64, f64)> = group_label_anchors
+                .iter()
+                .filter(|(_, anchor)| (camera.view() * anchor.extend(1.0)).z < 0.0)
+                .map(|(name, anchor)| {
+                    let pixel = camera.pixel_at_position(*anchor);
+                    (name.clone(), pixel.x as f64, height as f64 - pixel.y as f64)
+                })
+                .collect();
+            group_labels.try_set(labels);
+
             // Render grid and axes if visible
             let grid_axes = grid_axes_meshes.borrow();
             if show_grid_val {
@@ -1875,6 +2300,14 @@ async fn initialize_threed_viewport(
                 }
             }
 
+            // Render compare mode overlays
+            for line in compare_lines.iter() {
+                target.render(&camera, line, &[]);
+            }
+            for ghost in compare_ghosts.iter() {
+                target.render(&camera, ghost, &[&*ambient, &*key_light, &*fill_light, &*rim_light]);
+            }
+
             // Render error icons for connections with "Error" status (Phase 6.4.2)
             let error_icons_to_render = error_icons.borrow();
             for error_icon in error_icons_to_render.iter() {
@@ -1907,6 +2340,29 @@ async fn initialize_threed_viewport(
                 }
             }
 
+            // Collapsed groups, then the translucent volumes of expanded ones (after everything opaque)
+            let selected_group = match selected_item_value {
+                Some(crate::islands::topology_editor::SelectedItem::Group(id)) => Some(id),
+                _ => None,
+            };
+            for (group_id, normal_mesh, selected_mesh) in proxy_meshes.iter() {
+                let mesh_to_render = if Some(*group_id) == selected_group { selected_mesh } else { normal_mesh };
+                if use_env_lighting {
+                    target.render(&camera, mesh_to_render, &[&*ambient]);
+                } else {
+                    target.render(&camera, mesh_to_render, &[&*ambient, &*key_light, &*fill_light, &*rim_light]);
+                }
+            }
+            for (group_id, normal_volume, selected_volume) in group_volumes.iter() {
+                let volume = if Some(*group_id) == selected_group { selected_volume } else { normal_volume };
+                target.render(&camera, volume, &[]);
+            }
+
+            // Filter highlight halos, drawn last so they wrap what they outline
+            for halo in highlight_meshes.iter() {
+                target.render(&camera, halo, &[]);
+            }
+
             // Render traffic particles (Phase 6.4.2) - Read from global storage
             if let Ok(particles) = GLOBAL_PARTICLES.lock() {
             for particle in particles.iter() {
@@ -2421,7 +2877,7 @@ fn setup_orbit_controls(
                         + camera_up * (ndc_y as f32 * tan_fov)).normalize();
 
                     // Test ray intersection with each node sphere
-                    let mut closest_node: Option<(i64, f32)> = None;
+                    let mut closest_node: Option<(&NodeData, f32)> = None;
 
                     for node in nodes.iter() {
                         // Ray-sphere intersection test
@@ -2435,8 +2891,8 @@ fn setup_orbit_controls(
                             let t = (-b - discriminant.sqrt()) / (2.0 * a);
                             if t > 0.0 {
                                 match closest_node {
-                                    None => closest_node = Some((node.id, t)),
-                                    Some((_, prev_t)) if t < prev_t => closest_node = Some((node.id, t)),
+                                    None => closest_node = Some((node, t)),
+                                    Some((_, prev_t)) if t < prev_t => closest_node = Some((node, t)),
                                     _ => {}
                                 }
                             }
@@ -2444,14 +2900,20 @@ fn setup_orbit_controls(
                     }
 
                     // Handle node click based on connection mode
-                    let selected_id = closest_node.map(|(id, _)| id);
+                    let clicked_node = closest_node.map(|(node, _)| (node.id, node.is_group_proxy));
 
                     // Normal selection mode - check nodes first, then connections
                     if !*is_disposed.lock().unwrap() {
-                        if selected_id.is_some() {
-                            // Node was clicked
-                            selected_node_id_signal.set(selected_id);
-                            selected_item_signal.set(selected_id.map(crate::islands::topology_editor::SelectedItem::Node));
+                        if let Some((id, is_group_proxy)) = clicked_node {
+                            if is_group_proxy {
+                                // Collapsed group was clicked
+                                selected_node_id_signal.set(None);
+                                selected_item_signal.set(Some(crate::islands::topology_editor::SelectedItem::Group(id)));
+                            } else {
+                                // Node was clicked
+                                selected_node_id_signal.set(Some(id));
+                                selected_item_signal.set(Some(crate::islands::topology_editor::SelectedItem::Node(id)));
+                            }
                         } else {
                             // No node clicked - check for connection clicks using stored data
                             let connections = connections_data.borrow();
@@ -2836,6 +3298,24 @@ fn create_grid_and_axes(
     }
 }
 
+/// Smallest box containing `bounds` and `min`..`max`
+#[cfg(feature = "hydrate")]
+fn extend_bounds(
+    bounds: Option<(three_d::Vec3, three_d::Vec3)>,
+    min: three_d::Vec3,
+    max: three_d::Vec3,
+) -> Option<(three_d::Vec3, three_d::Vec3)> {
+    use three_d::vec3;
+
+    Some(match bounds {
+        Some((lo, hi)) => (
+            vec3(lo.x.min(min.x), lo.y.min(min.y), lo.z.min(min.z)),
+            vec3(hi.x.max(max.x), hi.y.max(max.y), hi.z.max(max.z)),
+        ),
+        None => (min, max),
+    })
+}
+
 /// Helper function to create a thin cylinder between two points (for lines)
 #[cfg(feature = "hydrate")]
 fn create_line_cylinder(
@@ -2954,34 +3434,38 @@ fn create_emissive_line_cylinder(
     Some(box_bar)
 }
 
-/// Map node type to color
+/// Compare mode color for a change kind (None = unchanged, dimmed)
 #[cfg(feature = "hydrate")]
-fn get_node_color(node_type: &str) -> three_d::Srgba {
+fn get_compare_color(kind: Option<crate::models::ChangeKind>) -> three_d::Srgba {
+    use crate::models::ChangeKind;
     use three_d::Srgba;
 
-    match node_type.to_lowercase().as_str() {
-        "router" => Srgba::new(255, 140, 60, 255),   // Orange - routing/core device
-        "switch" => Srgba::new(80, 200, 120, 255),   // Green - switching/connecting
-        "server" => Srgba::new(70, 140, 255, 255),   // Blue - computing/services
-        "firewall" => Srgba::new(220, 60, 60, 255),  // Red - security/protection
-        "load_balancer" => Srgba::new(180, 100, 200, 255), // Purple - load distribution
-        "host" | "client" => Srgba::new(150, 150, 150, 255), // Gray - generic host
-        _ => Srgba::new(120, 120, 120, 255),         // Dark gray - unknown type
+    match kind {
+        Some(ChangeKind::Added) => Srgba::new(60, 210, 90, 255),     // Green
+        Some(ChangeKind::Removed) => Srgba::new(230, 60, 60, 255),   // Red
+        Some(ChangeKind::Modified) => Srgba::new(255, 170, 30, 255), // Amber
+        None => Srgba::new(90, 90, 90, 255),                         // Dim gray
     }
 }
 
+/// Convert a stored node/connection color to a three-d color
+#[cfg(feature = "hydrate")]
+fn rgb_to_srgba(color: Rgb) -> three_d::Srgba {
+    three_d::Srgba::new(color.r, color.g, color.b, color.alpha())
+}
+
 /// Get material properties (metallic, roughness) based on node type
 /// Returns (metallic, roughness) tuple
 #[cfg(feature = "hydrate")]
-fn get_node_material_properties(node_type: &str) -> (f32, f32) {
-    match node_type.to_lowercase().as_str() {
-        "router" => (0.6, 0.3),       // Metallic and smooth - metal enclosure
-        "switch" => (0.5, 0.4),       // Slightly metallic, medium roughness
-        "server" => (0.2, 0.6),       // Less metallic, more matte - server chassis
-        "firewall" => (0.7, 0.2),     // Very metallic and smooth - hardened hardware
-        "load_balancer" => (0.4, 0.5), // Medium metallic, medium roughness
-        "host" | "client" => (0.3, 0.7), // Low metallic, rough - desktop/laptop
-        _ => (0.4, 0.5),              // Default: medium values
+fn get_node_material_properties(node_type: NodeType) -> (f32, f32) {
+    match node_type {
+        NodeType::Router => (0.6, 0.3),       // Metallic and smooth - metal enclosure
+        NodeType::Switch => (0.5, 0.4),       // Slightly metallic, medium roughness
+        NodeType::Server => (0.2, 0.6),       // Less metallic, more matte - server chassis
+        NodeType::Firewall => (0.7, 0.2),     // Very metallic and smooth - hardened hardware
+        NodeType::LoadBalancer => (0.4, 0.5), // Medium metallic, medium roughness
+        NodeType::Host => (0.3, 0.7),         // Low metallic, rough - desktop/laptop
+        NodeType::Cloud | NodeType::Application => (0.4, 0.5), // Default: medium values
     }
 }
 
@@ -5,6 +5,12 @@ pub mod islands;
 pub mod models;
 pub mod api;  // Server functions - available on both client and server
 
+#[cfg(feature = "ssr")]
+pub mod cli;
+#[cfg(feature = "ssr")]
+pub mod config;
+#[cfg(feature = "ssr")]
+pub mod db;
 #[cfg(feature = "ssr")]
 pub mod server;
 
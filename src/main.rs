@@ -3,13 +3,53 @@
 #[cfg(feature = "ssr")]
 #[tokio::main]
 async fn main() {
+    use clap::Parser;
+    use ntb::cli::{Cli, Command};
+
+    // Load environment variables from .env file if it exists
+    dotenvy::dotenv().ok();
+
+    let cli = Cli::parse();
+    let config = cli.config().unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    });
+
+    match cli.command {
+        None | Some(Command::Serve) => serve(config).await,
+        Some(command) => {
+            // Keep stdout for command output (exports are often piped)
+            tracing_subscriber::fmt()
+                .with_target(false)
+                .compact()
+                .with_writer(std::io::stderr)
+                .init();
+
+            if let Err(e) = ntb::cli::run(config, cli.user.as_deref(), command).await {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Run the web server
+#[cfg(feature = "ssr")]
+async fn serve(config: ntb::config::NtbConfig) {
+    use axum::routing::get;
     use axum::{extract::Extension, Router};
     use leptos::logging::log;
     use leptos::prelude::*;
     use leptos_axum::{generate_route_list, LeptosRoutes};
+    use ntb::api::{self, LocalUser};
     use ntb::app::*;
-    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-    use std::str::FromStr;
+    use ntb::config::AuthMode;
+    use ntb::db;
+    use ntb::models::User;
+    use ntb::server::model_registry::ModelRegistry;
+    use ntb::server::model_scan::ModelScan;
+    use std::sync::Arc;
+    use tower_http::services::ServeDir;
 
     // Initialize logging
     tracing_subscriber::fmt()
@@ -17,49 +57,95 @@ async fn main() {
         .compact()
         .init();
 
-    // Load environment variables from .env file if it exists
-    dotenvy::dotenv().ok();
-
-    // Database setup
-    let database_url = std::env::var("DATABASE_URL")
-        .unwrap_or_else(|_| "sqlite:ntv.db".to_string());
-
+    // Database setup (SQLite, or PostgreSQL when built with the postgres feature)
+    let database_url = &config.database.url;
     log!("Connecting to database: {}", database_url);
 
-    // Create connection options with create_if_missing enabled
-    let connect_options = SqliteConnectOptions::from_str(&database_url)
-        .expect("Invalid database URL")
-        .create_if_missing(true);
-
-    let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect_with(connect_options)
+    let pool = db::connect(database_url, config.database.max_connections)
         .await
         .expect("Failed to create database pool");
 
     // Run migrations
     log!("Running database migrations...");
-    sqlx::migrate!("./migrations")
-        .run(&pool)
+    db::migrate(&pool)
         .await
         .expect("Failed to run database migrations");
 
     log!("Database setup complete");
 
+    // Single-user installs act as one account instead of signing in
+    let local_user = match (config.auth.mode, config.auth.user.as_deref()) {
+        (AuthMode::Single, Some(username)) => match api::find_user(&pool, username).await {
+            // The only account of a single-user server administers it
+            Ok(Some(user)) => Some(User { is_admin: true, ..user }),
+            Ok(None) => {
+                eprintln!(
+                    "error: auth.user '{}' doesn't exist; register it with auth.mode = \"open\" first",
+                    username
+                );
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        _ => None,
+    };
+
     let conf = get_configuration(None).unwrap();
-    let addr = conf.leptos_options.site_addr;
+    let addr = config.server.bind.unwrap_or(conf.leptos_options.site_addr);
     let leptos_options = conf.leptos_options;
+
+    // Register the models (reading vendor manifests) and check them up front so the
+    // editor can flag broken ones
+    let registry_config = config.clone();
+    let site_root = leptos_options.site_root.clone();
+    let (model_registry, model_scan) = tokio::task::spawn_blocking(move || {
+        let scan = ModelScan::scan(&registry_config.model_roots(&site_root));
+        (ModelRegistry::load(&registry_config, &site_root), scan)
+    })
+    .await
+    .expect("Failed to scan models");
+    let model_results = model_scan.results();
+    log!("Checked {} models", model_results.len());
+    for result in model_results.iter().filter(|result| result.has_errors()) {
+        if let Some(issue) = result.first_error() {
+            log!("Broken model {}: {}", result.path, issue.message);
+        }
+    }
+
     // Generate the list of routes in your Leptos App
     let routes = generate_route_list(App);
 
-    let app = Router::new()
+    let mut app = Router::new()
+        // Versioned REST API for scripts (the Leptos server functions live under /api)
+        .nest("/api/v1", ntb::server::api_v1_router(&config))
+        // Models, also from the configured search paths outside the site root
+        .route("/models/{*path}", get(ntb::server::assets::model_file))
+        // Vendor icons, including uploaded ones
+        .route("/icons/vendors/{file}", get(ntb::server::assets::vendor_icon));
+    if let Some(dir) = &config.environments.dir {
+        app = app.nest_service("/environments", ServeDir::new(dir));
+    }
+
+    let app = app
         .leptos_routes(&leptos_options, routes, {
             let leptos_options = leptos_options.clone();
             move || shell(leptos_options.clone())
         })
         .fallback(leptos_axum::file_and_error_handler(shell))
         .layer(Extension(pool))
+        // Rooms of the live collaboration sockets
+        .layer(Extension(ntb::server::live::LiveHub::default()))
+        .layer(Extension(model_registry))
+        .layer(Extension(model_scan))
+        .layer(Extension(Arc::new(config)))
         .with_state(leptos_options);
+    let app = match local_user {
+        Some(user) => app.layer(Extension(LocalUser(user))),
+        None => app,
+    };
 
     // run our app with hyper
     // `axum::Server` is a re-export of `hyper::Server`
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[cfg(feature = "ssr")]
+use sqlx::FromRow;
+
+/// Most entries one audit log query returns
+pub const MAX_AUDIT_ENTRIES: i64 = 1000;
+
+/// Fields left out of update diffs because every update touches them
+const UNAUDITED_FIELDS: &[&str] = &["updated_at"];
+
+text_enum! {
+    /// What an audit log entry is about
+    pub enum AuditEntity("audit entity") {
+        #[default]
+        Topology = "topology" => "Topology",
+        Node = "node" => "Node",
+        Connection = "connection" => "Connection",
+        Interface = "interface" => "Interface",
+        Subnet = "subnet" => "Subnet",
+        Group = "group" => "Group",
+        Tag = "tag" => "Tag",
+        Revision = "revision" => "Revision",
+        Traffic = "traffic" => "Traffic",
+        Permission = "permission" => "Permission",
+        User = "user" => "User",
+        ApiToken = "api_token" => "API Token",
+        Settings = "settings" => "Settings",
+        Model = "model" => "Model",
+    }
+}
+
+text_enum! {
+    /// What was done to the entity
+    pub enum AuditAction("audit action") {
+        #[default]
+        Create = "create" => "Create",
+        Update = "update" => "Update",
+        Delete = "delete" => "Delete",
+        Restore = "restore" => "Restore",
+        Undo = "undo" => "Undo",
+        Redo = "redo" => "Redo",
+        SignIn = "sign_in" => "Sign In",
+        SignOut = "sign_out" => "Sign Out",
+    }
+}
+
+text_enum! {
+    /// File format of an audit log export
+    pub enum AuditExportFormat("audit export format") {
+        #[default]
+        Csv = "csv" => "CSV",
+        Json = "json" => "JSON",
+    }
+}
+
+/// One recorded change (the log is append-only; entries are never updated or deleted)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub created_at: i64,
+    pub user_id: Option<i64>,
+    pub actor: String,            // Username at the time of the change
+    pub topology_id: Option<i64>, // None for account-level changes
+    pub entity_type: AuditEntity,
+    pub entity_id: Option<i64>,
+    pub action: AuditAction,
+    pub summary: String,              // e.g. "Update node 'core-sw1'"
+    pub before_state: Option<String>, // JSON; only the changed fields for updates
+    pub after_state: Option<String>,  // JSON; only the changed fields for updates
+}
+
+/// Which audit log entries to return (every field narrows the result)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditFilter {
+    pub topology_id: Option<i64>,
+    pub actor: Option<String>, // Case-insensitive username
+    pub entity_type: Option<AuditEntity>,
+    pub action: Option<AuditAction>,
+    pub since: Option<i64>, // Unix seconds, inclusive
+    pub until: Option<i64>, // Unix seconds, exclusive
+}
+
+/// Reduce two JSON snapshots to the top-level fields that differ
+///
+/// Creates (`before` is None) and deletes (`after` is None) keep the whole snapshot.
+/// Returns None for an update that changed nothing.
+pub fn audit_diff(before: Option<Value>, after: Option<Value>) -> Option<(Option<Value>, Option<Value>)> {
+    let (Some(Value::Object(before)), Some(Value::Object(after))) = (&before, &after) else {
+        return Some((before, after));
+    };
+
+    let mut changed_before = Map::new();
+    let mut changed_after = Map::new();
+    for key in before.keys().chain(after.keys().filter(|key| !before.contains_key(*key))) {
+        if UNAUDITED_FIELDS.contains(&key.as_str()) {
+            continue;
+        }
+        let old = before.get(key).unwrap_or(&Value::Null);
+        let new = after.get(key).unwrap_or(&Value::Null);
+        if old != new {
+            changed_before.insert(key.clone(), old.clone());
+            changed_after.insert(key.clone(), new.clone());
+        }
+    }
+
+    if changed_after.is_empty() {
+        return None;
+    }
+    Some((Some(Value::Object(changed_before)), Some(Value::Object(changed_after))))
+}
+
+/// Render entries as CSV (RFC 4180 quoting, timestamps as Unix seconds)
+pub fn audit_log_to_csv(entries: &[AuditLogEntry]) -> String {
+    fn field(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+    fn optional(value: Option<i64>) -> String {
+        value.map(|v| v.to_string()).unwrap_or_default()
+    }
+
+    let mut csv = String::from("id,created_at,actor,topology_id,entity_type,entity_id,action,summary,before,after\r\n");
+    for entry in entries {
+        let row = [
+            entry.id.to_string(),
+            entry.created_at.to_string(),
+            field(&entry.actor),
+            optional(entry.topology_id),
+            entry.entity_type.as_str().to_string(),
+            optional(entry.entity_id),
+            entry.action.as_str().to_string(),
+            field(&entry.summary),
+            field(entry.before_state.as_deref().unwrap_or_default()),
+            field(entry.after_state.as_deref().unwrap_or_default()),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
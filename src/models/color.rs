@@ -0,0 +1,208 @@
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "ssr")]
+use crate::db::Db;
+
+/// Color of a node or connection
+///
+/// Stored and serialized as `"R,G,B"` (or `"R,G,B,A"` when an alpha is set) so existing
+/// rows, exports and undo snapshots stay compatible. Parsing also accepts hex
+/// `#rrggbb` / `#rrggbbaa`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: Option<u8>, // None = opaque
+}
+
+impl Rgb {
+    /// Default node color (blue)
+    pub const NODE_DEFAULT: Rgb = Rgb::new(100, 150, 255);
+    /// Default connection color (gray)
+    pub const CONNECTION_DEFAULT: Rgb = Rgb::new(128, 128, 128);
+
+    /// Opaque color
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: None }
+    }
+
+    /// Alpha channel, 255 when unset
+    pub fn alpha(&self) -> u8 {
+        self.a.unwrap_or(255)
+    }
+
+    /// `#rrggbb` (alpha dropped), as used by `<input type="color">`
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// CSS color for inline styles
+    pub fn to_css(&self) -> String {
+        match self.a {
+            Some(a) => format!("rgba({},{},{},{:.3})", self.r, self.g, self.b, a as f32 / 255.0),
+            None => format!("rgb({},{},{})", self.r, self.g, self.b),
+        }
+    }
+
+    fn parse_hex(hex: &str) -> Option<Self> {
+        if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Self {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+            a: if hex.len() == 8 { Some(channel(6)?) } else { None },
+        })
+    }
+
+    fn parse_components(value: &str) -> Option<Self> {
+        let channels = value
+            .split(',')
+            .map(|part| part.trim().parse::<u8>().ok())
+            .collect::<Option<Vec<u8>>>()?;
+        match channels[..] {
+            [r, g, b] => Some(Self::new(r, g, b)),
+            [r, g, b, a] => Some(Self { r, g, b, a: Some(a) }),
+            _ => None,
+        }
+    }
+}
+
+/// A color string that is neither `R,G,B[,A]` nor `#rrggbb[aa]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidColor(pub String);
+
+impl fmt::Display for InvalidColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid color '{}': expected \"R,G,B\" or \"R,G,B,A\" with values 0-255, or hex #rrggbb / #rrggbbaa",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidColor {}
+
+impl FromStr for Rgb {
+    type Err = InvalidColor;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let trimmed = value.trim();
+        let parsed = match trimmed.strip_prefix('#') {
+            Some(hex) => Self::parse_hex(hex),
+            None => Self::parse_components(trimmed),
+        };
+        parsed.ok_or_else(|| InvalidColor(value.to_string()))
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.r, self.g, self.b)?;
+        if let Some(a) = self.a {
+            write!(f, ",{}", a)?;
+        }
+        Ok(())
+    }
+}
+
+impl serde::Serialize for Rgb {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Rgb {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl sqlx::Type<Db> for Rgb {
+    fn type_info() -> <Db as sqlx::Database>::TypeInfo {
+        <str as sqlx::Type<Db>>::type_info()
+    }
+
+    fn compatible(ty: &<Db as sqlx::Database>::TypeInfo) -> bool {
+        <str as sqlx::Type<Db>>::compatible(ty)
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl<'q> sqlx::Encode<'q, Db> for Rgb {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Db as sqlx::database::HasArguments<'q>>::ArgumentBuffer,
+    ) -> sqlx::encode::IsNull {
+        <String as sqlx::Encode<'q, Db>>::encode(self.to_string(), buf)
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl<'r> sqlx::Decode<'r, Db> for Rgb {
+    fn decode(value: <Db as sqlx::database::HasValueRef<'r>>::ValueRef) -> Result<Self, sqlx::error::BoxDynError> {
+        let value = <&str as sqlx::Decode<'r, Db>>::decode(value)?;
+        Ok(value.parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_round_trips() {
+        let color: Rgb = "#1a2B3c".parse().unwrap();
+        assert_eq!(color, Rgb::new(0x1a, 0x2b, 0x3c));
+        assert_eq!(color.to_hex(), "#1a2b3c");
+        assert_eq!(color.to_hex().parse::<Rgb>(), Ok(color));
+        assert_eq!(Rgb::NODE_DEFAULT.to_hex().parse::<Rgb>(), Ok(Rgb::NODE_DEFAULT));
+    }
+
+    #[test]
+    fn hex_with_alpha() {
+        let color: Rgb = "#ff000080".parse().unwrap();
+        assert_eq!(color, Rgb { r: 255, g: 0, b: 0, a: Some(0x80) });
+        // The color input has no alpha
+        assert_eq!(color.to_hex(), "#ff0000");
+    }
+
+    #[test]
+    fn components_round_trip() {
+        for value in ["0,0,0", "255,255,255", "100,150,255", "1,2,3,4"] {
+            let color: Rgb = value.parse().unwrap();
+            assert_eq!(color.to_string(), value);
+        }
+        assert_eq!(" 10, 20 ,30 ".parse::<Rgb>(), Ok(Rgb::new(10, 20, 30)));
+    }
+
+    #[test]
+    fn serializes_as_components() {
+        let color = Rgb { r: 1, g: 2, b: 3, a: Some(4) };
+        let json = serde_json::to_string(&color).unwrap();
+        assert_eq!(json, "\"1,2,3,4\"");
+        assert_eq!(serde_json::from_str::<Rgb>(&json).unwrap(), color);
+        assert_eq!(serde_json::from_str::<Rgb>("\"#0a0b0c\"").unwrap(), Rgb::new(10, 11, 12));
+        assert!(serde_json::from_str::<Rgb>("\"#12\"").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_hex() {
+        for value in ["#12", "#1234567", "#gggggg", "#", "123456", "ff0000", "#ff 000"] {
+            assert_eq!(value.parse::<Rgb>(), Err(InvalidColor(value.to_string())), "{}", value);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_components() {
+        for value in ["", "1,2", "1,2,3,4,5", "256,0,0", "-1,0,0", "a,b,c"] {
+            assert!(value.parse::<Rgb>().is_err(), "{}", value);
+        }
+    }
+}
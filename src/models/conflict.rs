@@ -0,0 +1,77 @@
+use super::AppError;
+use leptos::prelude::ServerFnError;
+use leptos::server_fn::codec::JsonEncoding;
+use leptos::server_fn::error::{FromServerFnError, ServerFnErrorErr};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// Why an update of a node or connection failed
+///
+/// Updates that name an `expected_version` are rejected with `Conflict` when the row
+/// has changed since; the caller gets the current state to merge with or overwrite.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum UpdateError<T> {
+    /// Someone else changed the row first; holds its current state
+    Conflict(T),
+    /// Anything else (validation, permissions, database)
+    Failed(AppError),
+}
+
+impl<T> fmt::Display for UpdateError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpdateError::Conflict(_) => write!(f, "Conflict: changed by someone else since it was loaded"),
+            UpdateError::Failed(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for UpdateError<T> {}
+
+impl<T> From<AppError> for UpdateError<T> {
+    fn from(error: AppError) -> Self {
+        UpdateError::Failed(error)
+    }
+}
+
+impl<T> From<ServerFnError> for UpdateError<T> {
+    fn from(error: ServerFnError) -> Self {
+        UpdateError::Failed(error.into())
+    }
+}
+
+impl<T> FromServerFnError for UpdateError<T>
+where
+    T: fmt::Debug + Serialize + DeserializeOwned + 'static,
+{
+    type Encoder = JsonEncoding;
+
+    fn from_server_fn_error(error: ServerFnErrorErr) -> Self {
+        UpdateError::Failed(AppError::from_server_fn_error(error))
+    }
+}
+
+/// Three-way merge of my edit with someone else's, field by field
+///
+/// `base` is the state my edit started from. Fields I changed keep my value; every
+/// other field takes `theirs`, so both sets of changes survive unless we edited the
+/// same field (then mine wins).
+pub fn merge_edits<T: Serialize + DeserializeOwned>(base: &T, mine: &T, theirs: &T) -> Result<T, String> {
+    let to_object = |value: &T| match serde_json::to_value(value) {
+        Ok(Value::Object(object)) => Ok(object),
+        Ok(_) => Err("Only records can be merged".to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let base = to_object(base)?;
+    let mine = to_object(mine)?;
+    let mut merged = to_object(theirs)?;
+
+    for (key, value) in mine {
+        if base.get(&key) != Some(&value) {
+            merged.insert(key, value);
+        }
+    }
+    serde_json::from_value(Value::Object(merged)).map_err(|e| e.to_string())
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::Rgb;
+
 #[cfg(feature = "ssr")]
 use sqlx::FromRow;
 
@@ -11,17 +13,21 @@ pub struct Connection {
     pub topology_id: i64,
     pub source_node_id: i64,
     pub target_node_id: i64,
-    pub connection_type: String,
+    pub connection_type: ConnectionType,
     pub bandwidth_mbps: Option<i64>,
     pub latency_ms: Option<f64>,
     pub baseline_packet_loss_pct: Option<f64>, // Baseline packet loss percentage (0.0-10.0%)
-    pub status: String,
-    pub color: String, // RGB color as "R,G,B" format (e.g., "128,128,128")
+    pub status: ConnectionStatus,
+    pub color: Rgb,
     pub carries_traffic: bool, // Whether this connection carries traffic for animations
-    pub flow_direction: String, // Traffic flow direction: 'source_to_target', 'target_to_source', 'bidirectional'
+    pub flow_direction: FlowDirection, // Direction traffic animates along the link
+    pub source_interface_id: Option<i64>, // Port on the source node (None = node-level link)
+    pub target_interface_id: Option<i64>, // Port on the target node
     pub metadata: Option<String>, // JSON string
     pub created_at: i64,
     pub updated_at: i64,
+    #[serde(default)]
+    pub version: i64, // Bumped on every write; see UpdateConnection::expected_version
 }
 
 /// Data transfer object for creating a new connection
@@ -30,48 +36,73 @@ pub struct CreateConnection {
     pub topology_id: i64,
     pub source_node_id: i64,
     pub target_node_id: i64,
-    pub connection_type: Option<String>,
+    pub connection_type: Option<ConnectionType>,
     pub bandwidth_mbps: Option<i64>,
     pub latency_ms: Option<f64>,
     pub baseline_packet_loss_pct: Option<f64>,
-    pub status: Option<String>,
-    pub color: Option<String>,
+    pub status: Option<ConnectionStatus>,
+    pub color: Option<Rgb>,
+    pub source_interface_id: Option<i64>,
+    pub target_interface_id: Option<i64>,
     pub metadata: Option<String>,
 }
 
 /// Data transfer object for updating a connection
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateConnection {
-    pub connection_type: Option<String>,
+    pub connection_type: Option<ConnectionType>,
     pub bandwidth_mbps: Option<i64>,
     pub latency_ms: Option<f64>,
     pub baseline_packet_loss_pct: Option<f64>,
-    pub status: Option<String>,
-    pub color: Option<String>,
+    pub status: Option<ConnectionStatus>,
+    pub color: Option<Rgb>,
     pub carries_traffic: Option<bool>,
-    pub flow_direction: Option<String>,
+    pub flow_direction: Option<FlowDirection>,
     pub metadata: Option<String>,
+    pub expected_version: Option<i64>, // Reject with a conflict unless the connection's version still matches
+}
+
+text_enum! {
+    /// Physical or logical medium of a link
+    pub enum ConnectionType("connection type") {
+        #[default]
+        Ethernet = "ethernet" | "eth" => "Ethernet",
+        Fiber = "fiber" | "fibre" => "Fiber",
+        Wireless = "wireless" | "wifi" => "Wireless",
+        Vpn = "vpn" => "VPN",
+        Wan = "wan" => "WAN",
+    }
 }
 
-/// Common connection types as constants
-pub mod connection_types {
-    pub const ETHERNET: &str = "ethernet";
-    pub const FIBER: &str = "fiber";
-    pub const WIRELESS: &str = "wireless";
-    pub const VPN: &str = "vpn";
-    pub const WAN: &str = "wan";
+text_enum! {
+    /// Operational state of a link
+    pub enum ConnectionStatus("connection status") {
+        #[default]
+        Active = "active" | "up" => "Active",
+        Inactive = "inactive" | "down" => "Inactive",
+        Degraded = "degraded" => "Degraded",
+        Error = "error" => "Error",
+    }
 }
 
-/// Connection status constants
-pub mod connection_status {
-    pub const ACTIVE: &str = "active";
-    pub const INACTIVE: &str = "inactive";
-    pub const DEGRADED: &str = "degraded";
+text_enum! {
+    /// Direction traffic flows along a link
+    pub enum FlowDirection("flow direction") {
+        #[default]
+        SourceToTarget = "source_to_target" => "Source → Target",
+        TargetToSource = "target_to_source" => "Target → Source",
+        Bidirectional = "bidirectional" | "both" => "Bidirectional",
+    }
 }
 
-/// Traffic flow direction constants
-pub mod flow_direction {
-    pub const SOURCE_TO_TARGET: &str = "source_to_target";
-    pub const TARGET_TO_SOURCE: &str = "target_to_source";
-    pub const BIDIRECTIONAL: &str = "bidirectional";
+impl FlowDirection {
+    /// Whether traffic moves from the source node towards the target node
+    pub fn forward(self) -> bool {
+        matches!(self, FlowDirection::SourceToTarget | FlowDirection::Bidirectional)
+    }
+
+    /// Whether traffic moves from the target node back towards the source node
+    pub fn backward(self) -> bool {
+        matches!(self, FlowDirection::TargetToSource | FlowDirection::Bidirectional)
+    }
 }
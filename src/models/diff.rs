@@ -0,0 +1,540 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Connection, Node, TopologyFull};
+
+/// Positions, rotations and other floats closer than this are considered equal
+const FLOAT_EPSILON: f64 = 1e-6;
+
+/// One side of a comparison: the live topology, or one of its saved revisions
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DiffSource {
+    pub topology_id: i64,
+    pub revision_id: Option<i64>, // None = current state of the topology
+}
+
+/// How an item differs between the base and the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,    // Only in the target
+    Removed,  // Only in the base
+    Modified, // In both, with at least one field changed
+}
+
+/// A single changed field, with both values formatted for display
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// A node that was added, removed or modified
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeChange {
+    pub kind: ChangeKind,
+    pub base: Option<Node>,   // None for added nodes
+    pub target: Option<Node>, // None for removed nodes
+    pub changes: Vec<FieldChange>,
+}
+
+/// A connection that was added, removed or modified
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionChange {
+    pub kind: ChangeKind,
+    pub base: Option<Connection>,
+    pub target: Option<Connection>,
+    pub label: String, // "source → target" using node names
+    pub changes: Vec<FieldChange>,
+}
+
+/// Structural differences between two topologies (unchanged items are omitted)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TopologyDiff {
+    pub nodes: Vec<NodeChange>,
+    pub connections: Vec<ConnectionChange>,
+    pub node_map: Vec<(i64, i64)>, // (base node ID, matched target node ID), including unchanged nodes
+}
+
+impl NodeChange {
+    /// Name of the node on whichever side it exists (target name preferred)
+    pub fn name(&self) -> &str {
+        self.target
+            .as_ref()
+            .or(self.base.as_ref())
+            .map(|node| node.name.as_str())
+            .unwrap_or_default()
+    }
+}
+
+impl TopologyDiff {
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.connections.is_empty()
+    }
+
+    /// Number of node changes of a kind
+    pub fn node_count(&self, kind: ChangeKind) -> usize {
+        self.nodes.iter().filter(|change| change.kind == kind).count()
+    }
+
+    /// Number of connection changes of a kind
+    pub fn connection_count(&self, kind: ChangeKind) -> usize {
+        self.connections.iter().filter(|change| change.kind == kind).count()
+    }
+
+    /// How a node of the target topology changed (None = unchanged or not in the target)
+    pub fn node_change(&self, target_node_id: i64) -> Option<&NodeChange> {
+        self.nodes
+            .iter()
+            .find(|change| change.target.as_ref().is_some_and(|node| node.id == target_node_id))
+    }
+
+    /// Target node matched to a base node
+    pub fn matched_node(&self, base_node_id: i64) -> Option<i64> {
+        self.node_map
+            .iter()
+            .find(|(base_id, _)| *base_id == base_node_id)
+            .map(|(_, target_id)| *target_id)
+    }
+
+    /// How a connection of the target topology changed (None = unchanged or not in the target)
+    pub fn connection_change(&self, target_connection_id: i64) -> Option<&ConnectionChange> {
+        self.connections
+            .iter()
+            .find(|change| change.target.as_ref().is_some_and(|conn| conn.id == target_connection_id))
+    }
+}
+
+/// Compare two topologies
+///
+/// Nodes are matched by ID first, then remaining nodes by name (so copies of a topology
+/// can be compared). Connections are matched by ID, then by their matched endpoints.
+pub fn diff_topologies(base: &TopologyFull, target: &TopologyFull) -> TopologyDiff {
+    let mut diff = TopologyDiff::default();
+
+    // Base node ID -> target node ID
+    let node_matches = match_nodes(&base.nodes, &target.nodes);
+    let matched_targets: HashSet<i64> = node_matches.values().copied().collect();
+    let target_nodes: HashMap<i64, &Node> = target.nodes.iter().map(|node| (node.id, node)).collect();
+
+    for base_node in &base.nodes {
+        match node_matches.get(&base_node.id).and_then(|id| target_nodes.get(id)) {
+            Some(target_node) => {
+                let changes = node_field_changes(base_node, target_node);
+                if !changes.is_empty() {
+                    diff.nodes.push(NodeChange {
+                        kind: ChangeKind::Modified,
+                        base: Some(base_node.clone()),
+                        target: Some((*target_node).clone()),
+                        changes,
+                    });
+                }
+            }
+            None => diff.nodes.push(NodeChange {
+                kind: ChangeKind::Removed,
+                base: Some(base_node.clone()),
+                target: None,
+                changes: Vec::new(),
+            }),
+        }
+    }
+    for target_node in target.nodes.iter().filter(|node| !matched_targets.contains(&node.id)) {
+        diff.nodes.push(NodeChange {
+            kind: ChangeKind::Added,
+            base: None,
+            target: Some(target_node.clone()),
+            changes: Vec::new(),
+        });
+    }
+
+    let base_names: HashMap<i64, &str> = base.nodes.iter().map(|n| (n.id, n.name.as_str())).collect();
+    let target_names: HashMap<i64, &str> = target.nodes.iter().map(|n| (n.id, n.name.as_str())).collect();
+    let base_label = |conn: &Connection| connection_label(conn, &base_names);
+    let target_label = |conn: &Connection| connection_label(conn, &target_names);
+
+    let connection_matches = match_connections(&base.connections, &target.connections, &node_matches);
+    let matched_targets: HashSet<i64> = connection_matches.values().copied().collect();
+    let target_connections: HashMap<i64, &Connection> =
+        target.connections.iter().map(|conn| (conn.id, conn)).collect();
+
+    for base_conn in &base.connections {
+        match connection_matches.get(&base_conn.id).and_then(|id| target_connections.get(id)) {
+            Some(target_conn) => {
+                let mut changes = Vec::new();
+                let (before, after) = (base_label(base_conn), target_label(target_conn));
+                // Endpoints are compared through the node matching, not raw IDs
+                if node_matches.get(&base_conn.source_node_id) != Some(&target_conn.source_node_id)
+                    || node_matches.get(&base_conn.target_node_id) != Some(&target_conn.target_node_id)
+                {
+                    push_change(&mut changes, "endpoints", before, after.clone());
+                }
+                changes.extend(connection_field_changes(base_conn, target_conn));
+                if !changes.is_empty() {
+                    diff.connections.push(ConnectionChange {
+                        kind: ChangeKind::Modified,
+                        base: Some(base_conn.clone()),
+                        target: Some((*target_conn).clone()),
+                        label: after,
+                        changes,
+                    });
+                }
+            }
+            None => diff.connections.push(ConnectionChange {
+                kind: ChangeKind::Removed,
+                base: Some(base_conn.clone()),
+                target: None,
+                label: base_label(base_conn),
+                changes: Vec::new(),
+            }),
+        }
+    }
+    for target_conn in target.connections.iter().filter(|conn| !matched_targets.contains(&conn.id)) {
+        diff.connections.push(ConnectionChange {
+            kind: ChangeKind::Added,
+            base: None,
+            target: Some(target_conn.clone()),
+            label: target_label(target_conn),
+            changes: Vec::new(),
+        });
+    }
+
+    diff.node_map = node_matches.into_iter().collect();
+    diff.node_map.sort_unstable();
+    diff
+}
+
+/// Match base nodes to target nodes, by ID and then by name
+fn match_nodes(base: &[Node], target: &[Node]) -> HashMap<i64, i64> {
+    let target_ids: HashSet<i64> = target.iter().map(|node| node.id).collect();
+    let mut matches: HashMap<i64, i64> = base
+        .iter()
+        .filter(|node| target_ids.contains(&node.id))
+        .map(|node| (node.id, node.id))
+        .collect();
+
+    // Name fallback among the nodes left over (first unmatched node with the name wins)
+    let mut unmatched_by_name: HashMap<&str, Vec<i64>> = HashMap::new();
+    for node in target.iter().rev().filter(|node| !matches.contains_key(&node.id)) {
+        unmatched_by_name.entry(node.name.as_str()).or_default().push(node.id);
+    }
+    let unmatched: Vec<&Node> = base.iter().filter(|node| !matches.contains_key(&node.id)).collect();
+    for node in unmatched {
+        if let Some(id) = unmatched_by_name.get_mut(node.name.as_str()).and_then(Vec::pop) {
+            matches.insert(node.id, id);
+        }
+    }
+
+    matches
+}
+
+/// Match base connections to target connections, by ID and then by matched endpoints
+fn match_connections(
+    base: &[Connection],
+    target: &[Connection],
+    node_matches: &HashMap<i64, i64>,
+) -> HashMap<i64, i64> {
+    let target_ids: HashSet<i64> = target.iter().map(|conn| conn.id).collect();
+    let mut matches: HashMap<i64, i64> = base
+        .iter()
+        .filter(|conn| target_ids.contains(&conn.id))
+        .map(|conn| (conn.id, conn.id))
+        .collect();
+    let matched_targets: HashSet<i64> = matches.values().copied().collect();
+
+    // Unordered endpoint pair -> unmatched target connections, so reversed links still match
+    let endpoints = |a: i64, b: i64| (a.min(b), a.max(b));
+    let mut unmatched_by_endpoints: HashMap<(i64, i64), Vec<i64>> = HashMap::new();
+    for conn in target.iter().rev().filter(|conn| !matched_targets.contains(&conn.id)) {
+        unmatched_by_endpoints
+            .entry(endpoints(conn.source_node_id, conn.target_node_id))
+            .or_default()
+            .push(conn.id);
+    }
+    let unmatched: Vec<&Connection> = base.iter().filter(|conn| !matches.contains_key(&conn.id)).collect();
+    for conn in unmatched {
+        let (Some(&source), Some(&target)) =
+            (node_matches.get(&conn.source_node_id), node_matches.get(&conn.target_node_id))
+        else {
+            continue;
+        };
+        if let Some(id) = unmatched_by_endpoints.get_mut(&endpoints(source, target)).and_then(Vec::pop) {
+            matches.insert(conn.id, id);
+        }
+    }
+
+    matches
+}
+
+fn node_field_changes(base: &Node, target: &Node) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    compare(&mut changes, "name", &base.name, &target.name);
+    compare(&mut changes, "node_type", &base.node_type, &target.node_type);
+    compare(&mut changes, "vendor", &base.vendor, &target.vendor);
+    compare(&mut changes, "model_name", &base.model_name, &target.model_name);
+    compare(&mut changes, "ip_address", &display_option(&base.ip_address), &display_option(&target.ip_address));
+    compare_vec3(
+        &mut changes,
+        "position",
+        [base.position_x, base.position_y, base.position_z],
+        [target.position_x, target.position_y, target.position_z],
+    );
+    compare_vec3(
+        &mut changes,
+        "rotation",
+        [base.rotation_x, base.rotation_y, base.rotation_z],
+        [target.rotation_x, target.rotation_y, target.rotation_z],
+    );
+    compare_float(&mut changes, "scale", Some(base.scale), Some(target.scale));
+    compare(&mut changes, "color", &base.color, &target.color);
+    compare(&mut changes, "visible", &base.visible, &target.visible);
+    compare(&mut changes, "metadata", &display_option(&base.metadata), &display_option(&target.metadata));
+    changes
+}
+
+fn connection_field_changes(base: &Connection, target: &Connection) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    compare(&mut changes, "connection_type", &base.connection_type, &target.connection_type);
+    compare(
+        &mut changes,
+        "bandwidth_mbps",
+        &display_option(&base.bandwidth_mbps),
+        &display_option(&target.bandwidth_mbps),
+    );
+    compare_float(&mut changes, "latency_ms", base.latency_ms, target.latency_ms);
+    compare_float(
+        &mut changes,
+        "baseline_packet_loss_pct",
+        base.baseline_packet_loss_pct,
+        target.baseline_packet_loss_pct,
+    );
+    compare(&mut changes, "status", &base.status, &target.status);
+    compare(&mut changes, "color", &base.color, &target.color);
+    compare(&mut changes, "carries_traffic", &base.carries_traffic, &target.carries_traffic);
+    compare(&mut changes, "flow_direction", &base.flow_direction, &target.flow_direction);
+    compare(&mut changes, "metadata", &display_option(&base.metadata), &display_option(&target.metadata));
+    changes
+}
+
+fn push_change(changes: &mut Vec<FieldChange>, field: &str, before: String, after: String) {
+    changes.push(FieldChange {
+        field: field.to_string(),
+        before,
+        after,
+    });
+}
+
+fn compare<T: PartialEq + ToString + ?Sized>(changes: &mut Vec<FieldChange>, field: &str, before: &T, after: &T) {
+    if before != after {
+        push_change(changes, field, before.to_string(), after.to_string());
+    }
+}
+
+fn compare_float(changes: &mut Vec<FieldChange>, field: &str, before: Option<f64>, after: Option<f64>) {
+    let equal = match (before, after) {
+        (Some(a), Some(b)) => (a - b).abs() < FLOAT_EPSILON,
+        (a, b) => a.is_none() && b.is_none(),
+    };
+    if !equal {
+        push_change(changes, field, display_option(&before), display_option(&after));
+    }
+}
+
+fn compare_vec3(changes: &mut Vec<FieldChange>, field: &str, before: [f64; 3], after: [f64; 3]) {
+    if before.iter().zip(after.iter()).any(|(a, b)| (a - b).abs() >= FLOAT_EPSILON) {
+        let format = |v: [f64; 3]| format!("({:.2}, {:.2}, {:.2})", v[0], v[1], v[2]);
+        push_change(changes, field, format(before), format(after));
+    }
+}
+
+fn display_option<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(T::to_string).unwrap_or_else(|| "—".to_string())
+}
+
+fn connection_label(conn: &Connection, names: &HashMap<i64, &str>) -> String {
+    let name = |id: i64| names.get(&id).map(|n| n.to_string()).unwrap_or_else(|| format!("Node {}", id));
+    format!("{} → {}", name(conn.source_node_id), name(conn.target_node_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Rgb, Topology};
+
+    fn node(id: i64, name: &str) -> Node {
+        Node {
+            id,
+            topology_id: 1,
+            name: name.to_string(),
+            node_type: Default::default(),
+            vendor: "generic".to_string(),
+            model_name: "blob-host".to_string(),
+            ip_address: None,
+            position_x: 0.0,
+            position_y: 0.0,
+            position_z: 0.0,
+            rotation_x: 0.0,
+            rotation_y: 0.0,
+            rotation_z: 0.0,
+            scale: 1.0,
+            color: Rgb::NODE_DEFAULT,
+            visible: true,
+            group_id: None,
+            metadata: None,
+            created_at: 0,
+            updated_at: 0,
+            version: 1,
+        }
+    }
+
+    fn connection(id: i64, source_node_id: i64, target_node_id: i64) -> Connection {
+        Connection {
+            id,
+            topology_id: 1,
+            source_node_id,
+            target_node_id,
+            connection_type: Default::default(),
+            bandwidth_mbps: None,
+            latency_ms: None,
+            baseline_packet_loss_pct: Some(0.0),
+            status: Default::default(),
+            color: Rgb::CONNECTION_DEFAULT,
+            carries_traffic: true,
+            flow_direction: Default::default(),
+            source_interface_id: None,
+            target_interface_id: None,
+            metadata: None,
+            created_at: 0,
+            updated_at: 0,
+            version: 1,
+        }
+    }
+
+    fn topology(nodes: Vec<Node>, connections: Vec<Connection>) -> TopologyFull {
+        TopologyFull {
+            topology: Topology {
+                id: 1,
+                name: "Lab".to_string(),
+                description: None,
+                created_at: 0,
+                updated_at: 0,
+            },
+            nodes,
+            connections,
+            interfaces: Vec::new(),
+            subnets: Vec::new(),
+            groups: Vec::new(),
+            tags: Vec::new(),
+            tag_assignments: Vec::new(),
+        }
+    }
+
+    fn fields(changes: &[FieldChange]) -> Vec<&str> {
+        changes.iter().map(|change| change.field.as_str()).collect()
+    }
+
+    #[test]
+    fn identical_topologies_have_no_changes() {
+        let full = topology(vec![node(1, "a"), node(2, "b")], vec![connection(10, 1, 2)]);
+        let diff = diff_topologies(&full, &full.clone());
+        assert!(diff.is_empty());
+        assert_eq!(diff.node_map, vec![(1, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn added_and_removed_items() {
+        let base = topology(vec![node(1, "a"), node(2, "b")], vec![connection(10, 1, 2)]);
+        let target = topology(vec![node(1, "a"), node(3, "c")], vec![connection(11, 1, 3)]);
+        let diff = diff_topologies(&base, &target);
+
+        assert_eq!(diff.node_count(ChangeKind::Removed), 1);
+        assert_eq!(diff.node_count(ChangeKind::Added), 1);
+        assert_eq!(diff.node_count(ChangeKind::Modified), 0);
+        let removed = diff.nodes.iter().find(|c| c.kind == ChangeKind::Removed).unwrap();
+        assert_eq!(removed.name(), "b");
+        assert!(removed.target.is_none());
+        assert_eq!(diff.node_change(3).map(|c| c.kind), Some(ChangeKind::Added));
+        assert_eq!(diff.matched_node(2), None);
+
+        assert_eq!(diff.connection_count(ChangeKind::Removed), 1);
+        assert_eq!(diff.connection_count(ChangeKind::Added), 1);
+        let added = diff.connection_change(11).unwrap();
+        assert_eq!(added.kind, ChangeKind::Added);
+        assert_eq!(added.label, "a → c");
+    }
+
+    #[test]
+    fn changed_fields_are_listed() {
+        let base = topology(vec![node(1, "a")], vec![]);
+        let mut moved = node(1, "core");
+        moved.position_x = 1.0;
+        moved.rotation_y = 1e-9; // Within the float tolerance
+        moved.ip_address = Some("10.0.0.1".to_string());
+        let target = topology(vec![moved], vec![]);
+
+        let diff = diff_topologies(&base, &target);
+        let change = diff.node_change(1).unwrap();
+        assert_eq!(change.kind, ChangeKind::Modified);
+        assert_eq!(fields(&change.changes), vec!["name", "ip_address", "position"]);
+        assert_eq!(
+            change.changes[1],
+            FieldChange {
+                field: "ip_address".to_string(),
+                before: "—".to_string(),
+                after: "10.0.0.1".to_string(),
+            }
+        );
+        assert_eq!(change.changes[2].before, "(0.00, 0.00, 0.00)");
+        assert_eq!(change.changes[2].after, "(1.00, 0.00, 0.00)");
+    }
+
+    #[test]
+    fn changed_connection_fields_are_listed() {
+        let nodes = vec![node(1, "a"), node(2, "b")];
+        let base = topology(nodes.clone(), vec![connection(10, 1, 2)]);
+        let mut slower = connection(10, 1, 2);
+        slower.latency_ms = Some(5.0);
+        slower.carries_traffic = false;
+        let target = topology(nodes, vec![slower]);
+
+        let diff = diff_topologies(&base, &target);
+        let change = diff.connection_change(10).unwrap();
+        assert_eq!(change.kind, ChangeKind::Modified);
+        assert_eq!(fields(&change.changes), vec!["latency_ms", "carries_traffic"]);
+        assert_eq!(change.changes[0].after, "5");
+    }
+
+    #[test]
+    fn copies_match_by_name_and_endpoints() {
+        let base = topology(vec![node(1, "a"), node(2, "b")], vec![connection(10, 1, 2)]);
+        // A copy has new IDs; its link was also turned around
+        let target = topology(vec![node(101, "a"), node(102, "b")], vec![connection(110, 102, 101)]);
+
+        let diff = diff_topologies(&base, &target);
+        assert_eq!(diff.node_map, vec![(1, 101), (2, 102)]);
+        assert_eq!(diff.matched_node(2), Some(102));
+        assert!(diff.nodes.is_empty());
+
+        let change = diff.connection_change(110).unwrap();
+        assert_eq!(change.kind, ChangeKind::Modified);
+        assert_eq!(
+            change.changes,
+            vec![FieldChange {
+                field: "endpoints".to_string(),
+                before: "a → b".to_string(),
+                after: "b → a".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn duplicate_names_match_in_order() {
+        let base = topology(vec![node(1, "host"), node(2, "host")], vec![]);
+        let target = topology(vec![node(11, "host"), node(12, "host"), node(13, "host")], vec![]);
+        let diff = diff_topologies(&base, &target);
+        assert_eq!(diff.node_map, vec![(1, 11), (2, 12)]);
+        assert_eq!(diff.node_count(ChangeKind::Added), 1);
+        assert_eq!(diff.node_change(13).map(|c| c.kind), Some(ChangeKind::Added));
+    }
+}
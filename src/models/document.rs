@@ -0,0 +1,494 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::{
+    AdminState, Connection, ConnectionStatus, ConnectionType, FlowDirection, GroupType, ImportWarning, Interface,
+    Node, NodeGroup, NodeType, Subnet, Tag, TagAssignment, Topology, TopologyFull,
+};
+
+/// Current version of the topology interchange format
+///
+/// Bump this whenever the document shape changes (including new columns on
+/// `nodes` or `connections`) and append a matching step to `MIGRATIONS`.
+pub const CURRENT_FORMAT_VERSION: u32 = 5;
+
+/// Versioned, self-describing topology export/import document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyDocument {
+    pub format_version: u32,
+    pub topology: Topology,
+    pub nodes: Vec<Node>,
+    pub connections: Vec<Connection>,
+    pub interfaces: Vec<Interface>,
+    pub subnets: Vec<Subnet>,
+    pub groups: Vec<NodeGroup>,
+    pub tags: Vec<Tag>,
+    pub tag_assignments: Vec<TagAssignment>,
+    #[serde(default)]
+    pub view_settings: Option<ViewSettings>,
+    #[serde(default)]
+    pub camera: Option<CameraView>,
+    #[serde(default)]
+    pub models: Vec<ModelReference>, // Vendor models referenced by the nodes
+}
+
+/// Viewport and lighting settings captured at export time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewSettings {
+    pub show_grid: bool,
+    pub show_x_axis: bool,
+    pub show_y_axis: bool,
+    pub show_z_axis: bool,
+    pub background_color: Option<(u8, u8, u8)>, // None = transparent
+    pub use_environment_lighting: bool,
+    pub environment_map: String,
+    pub ambient_intensity: f64,
+    pub key_light_intensity: f64,
+    pub fill_light_intensity: f64,
+    pub rim_light_intensity: f64,
+}
+
+/// Orbit camera position (mirrors the viewport's camera state)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraView {
+    pub distance: f32,
+    pub azimuth: f32,   // horizontal rotation (radians)
+    pub elevation: f32, // vertical rotation (radians)
+    pub pan_x: f32,
+    pub pan_y: f32,
+}
+
+/// A vendor model (GLB file) used by at least one node
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ModelReference {
+    pub node_type: String,
+    pub vendor: String,
+    pub model_name: String,
+}
+
+/// Migration step that upgrades a raw document by exactly one version
+type Migration = fn(Value) -> Result<Value, String>;
+
+/// Migrations indexed by the version they upgrade from
+const MIGRATIONS: [Migration; CURRENT_FORMAT_VERSION as usize] = [
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+];
+
+impl TopologyDocument {
+    /// Build a document at the current format version
+    pub fn new(
+        full: TopologyFull,
+        view_settings: Option<ViewSettings>,
+        camera: Option<CameraView>,
+    ) -> Self {
+        let models = referenced_models(&full.nodes);
+        Self {
+            format_version: CURRENT_FORMAT_VERSION,
+            topology: full.topology,
+            nodes: full.nodes,
+            connections: full.connections,
+            interfaces: full.interfaces,
+            subnets: full.subnets,
+            groups: full.groups,
+            tags: full.tags,
+            tag_assignments: full.tag_assignments,
+            view_settings,
+            camera,
+            models,
+        }
+    }
+
+    /// Parse a document of any supported version, migrating it to the current one
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        Self::from_json_with_version(json).map(|(document, _)| document)
+    }
+
+    /// Like `from_json`, also returning the format version the file was written with
+    ///
+    /// Files without a `format_version` field are legacy `TopologyFull` exports (version 0).
+    pub fn from_json_with_version(json: &str) -> Result<(Self, u32), String> {
+        Self::from_json_with_warnings(json).map(|(document, version, _)| (document, version))
+    }
+
+    /// Like `from_json_with_version`, also returning the values that had to be replaced
+    ///
+    /// An unknown node type, connection type, status, flow direction, admin state or group
+    /// type (e.g. written by a newer build) is imported as that field's default instead of
+    /// failing the whole document.
+    pub fn from_json_with_warnings(json: &str) -> Result<(Self, u32, Vec<ImportWarning>), String> {
+        let mut value: Value =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON format: {}", e))?;
+
+        let version = match value.get("format_version") {
+            None => 0,
+            Some(v) => v
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| format!("Invalid format_version: {}", v))?,
+        };
+        if version > CURRENT_FORMAT_VERSION {
+            return Err(format!(
+                "Document format version {} is newer than supported version {}",
+                version, CURRENT_FORMAT_VERSION
+            ));
+        }
+
+        for migration in &MIGRATIONS[version as usize..] {
+            value = migration(value)?;
+        }
+        let warnings = replace_unknown_variants(&mut value);
+
+        let mut document: TopologyDocument = serde_json::from_value(value)
+            .map_err(|e| format!("Invalid topology document: {}", e))?;
+        if document.models.is_empty() {
+            document.models = referenced_models(&document.nodes);
+        }
+        Ok((document, version, warnings))
+    }
+
+    /// Serialize with pretty formatting for download
+    pub fn to_json_pretty(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize topology: {}", e))
+    }
+
+    /// The topology data without the export context
+    pub fn into_topology_full(self) -> TopologyFull {
+        TopologyFull {
+            topology: self.topology,
+            nodes: self.nodes,
+            connections: self.connections,
+            interfaces: self.interfaces,
+            subnets: self.subnets,
+            groups: self.groups,
+            tags: self.tags,
+            tag_assignments: self.tag_assignments,
+        }
+    }
+}
+
+/// Collect the distinct vendor models used by a set of nodes
+fn referenced_models(nodes: &[Node]) -> Vec<ModelReference> {
+    let models: std::collections::BTreeSet<ModelReference> = nodes
+        .iter()
+        .map(|node| ModelReference {
+            node_type: node.node_type.to_string(),
+            vendor: node.vendor.clone(),
+            model_name: node.model_name.clone(),
+        })
+        .collect();
+    models.into_iter().collect()
+}
+
+/// Replace enum values this build doesn't know with the field's default
+fn replace_unknown_variants(value: &mut Value) -> Vec<ImportWarning> {
+    let mut warnings = Vec::new();
+    let Some(root) = value.as_object_mut() else {
+        return warnings;
+    };
+    replace_unknown::<NodeType>(root, "nodes", "node", "node_type", &mut warnings);
+    replace_unknown::<ConnectionType>(root, "connections", "connection", "connection_type", &mut warnings);
+    replace_unknown::<ConnectionStatus>(root, "connections", "connection", "status", &mut warnings);
+    replace_unknown::<FlowDirection>(root, "connections", "connection", "flow_direction", &mut warnings);
+    replace_unknown::<AdminState>(root, "interfaces", "interface", "admin_state", &mut warnings);
+    replace_unknown::<GroupType>(root, "groups", "group", "group_type", &mut warnings);
+    warnings
+}
+
+/// Default `key` on every entity in `root[list]` whose value doesn't parse as `T`
+fn replace_unknown<T>(
+    root: &mut Map<String, Value>,
+    list: &str,
+    entity_type: &str,
+    key: &str,
+    warnings: &mut Vec<ImportWarning>,
+) where
+    T: std::str::FromStr + Default + std::fmt::Display,
+    T::Err: std::fmt::Display,
+{
+    let Some(entities) = root.get_mut(list).and_then(Value::as_array_mut) else {
+        return;
+    };
+    for entity in entities.iter_mut().filter_map(Value::as_object_mut) {
+        let Some(Err(e)) = entity.get(key).and_then(Value::as_str).map(str::parse::<T>) else {
+            continue;
+        };
+        let default = T::default().to_string();
+        warnings.push(ImportWarning {
+            entity_type: entity_type.to_string(),
+            source_id: entity.get("id").and_then(Value::as_i64).unwrap_or_default(),
+            message: format!("{}; imported as '{}'", e, default),
+        });
+        entity.insert(key.to_string(), json!(default));
+    }
+}
+
+/// Insert `value` under `key` unless the object already has it
+fn fill_default(object: &mut Map<String, Value>, key: &str, value: Value) {
+    object.entry(key.to_string()).or_insert(value);
+}
+
+/// v0 (bare `TopologyFull`) -> v1 (versioned envelope)
+///
+/// Older builds exported nodes and connections before rotation, scale, color, vendor
+/// models, visibility and traffic flow columns existed, so fill in the schema defaults.
+fn migrate_v0_to_v1(mut value: Value) -> Result<Value, String> {
+    let root = value
+        .as_object_mut()
+        .ok_or("Topology document must be a JSON object")?;
+
+    if let Some(topology) = root.get_mut("topology").and_then(Value::as_object_mut) {
+        fill_default(topology, "description", Value::Null);
+        fill_default(topology, "created_at", json!(0));
+        fill_default(topology, "updated_at", json!(0));
+    }
+
+    if let Some(nodes) = root.get_mut("nodes").and_then(Value::as_array_mut) {
+        for node in nodes.iter_mut().filter_map(Value::as_object_mut) {
+            // An unknown type keeps its raw value here and is defaulted (with a warning) later
+            let node_type = node
+                .get("node_type")
+                .and_then(Value::as_str)
+                .and_then(|raw| raw.parse::<NodeType>().ok())
+                .unwrap_or_default();
+            fill_default(node, "topology_id", json!(0));
+            fill_default(node, "node_type", json!(node_type.as_str()));
+            fill_default(node, "vendor", json!("generic"));
+            fill_default(node, "model_name", json!(format!("blob-{}", node_type.as_str())));
+            fill_default(node, "ip_address", Value::Null);
+            for key in ["position_x", "position_y", "position_z", "rotation_x", "rotation_y", "rotation_z"] {
+                fill_default(node, key, json!(0.0));
+            }
+            fill_default(node, "scale", json!(1.0));
+            fill_default(node, "color", json!("100,150,255"));
+            fill_default(node, "visible", json!(true));
+            fill_default(node, "metadata", Value::Null);
+            fill_default(node, "created_at", json!(0));
+            fill_default(node, "updated_at", json!(0));
+        }
+    }
+
+    if let Some(connections) = root.get_mut("connections").and_then(Value::as_array_mut) {
+        for connection in connections.iter_mut().filter_map(Value::as_object_mut) {
+            fill_default(connection, "topology_id", json!(0));
+            fill_default(connection, "connection_type", json!("ethernet"));
+            fill_default(connection, "bandwidth_mbps", Value::Null);
+            fill_default(connection, "latency_ms", Value::Null);
+            fill_default(connection, "baseline_packet_loss_pct", json!(0.0));
+            fill_default(connection, "status", json!("active"));
+            fill_default(connection, "color", json!("128,128,128"));
+            fill_default(connection, "carries_traffic", json!(true));
+            fill_default(connection, "flow_direction", json!("source_to_target"));
+            fill_default(connection, "metadata", Value::Null);
+            fill_default(connection, "created_at", json!(0));
+            fill_default(connection, "updated_at", json!(0));
+        }
+    } else {
+        root.insert("connections".to_string(), json!([]));
+    }
+
+    root.insert("format_version".to_string(), json!(1));
+    Ok(value)
+}
+
+/// v1 -> v2: node interfaces, and the interfaces each connection attaches to
+fn migrate_v1_to_v2(mut value: Value) -> Result<Value, String> {
+    let root = value
+        .as_object_mut()
+        .ok_or("Topology document must be a JSON object")?;
+
+    fill_default(root, "interfaces", json!([]));
+    if let Some(connections) = root.get_mut("connections").and_then(Value::as_array_mut) {
+        for connection in connections.iter_mut().filter_map(Value::as_object_mut) {
+            fill_default(connection, "source_interface_id", Value::Null);
+            fill_default(connection, "target_interface_id", Value::Null);
+        }
+    }
+
+    root.insert("format_version".to_string(), json!(2));
+    Ok(value)
+}
+
+/// v2 -> v3: the topology's subnets
+fn migrate_v2_to_v3(mut value: Value) -> Result<Value, String> {
+    let root = value
+        .as_object_mut()
+        .ok_or("Topology document must be a JSON object")?;
+
+    fill_default(root, "subnets", json!([]));
+
+    root.insert("format_version".to_string(), json!(3));
+    Ok(value)
+}
+
+/// v3 -> v4: node groups, and the group each node belongs to
+fn migrate_v3_to_v4(mut value: Value) -> Result<Value, String> {
+    let root = value
+        .as_object_mut()
+        .ok_or("Topology document must be a JSON object")?;
+
+    fill_default(root, "groups", json!([]));
+    if let Some(nodes) = root.get_mut("nodes").and_then(Value::as_array_mut) {
+        for node in nodes.iter_mut().filter_map(Value::as_object_mut) {
+            fill_default(node, "group_id", Value::Null);
+        }
+    }
+
+    root.insert("format_version".to_string(), json!(4));
+    Ok(value)
+}
+
+/// v4 -> v5: tags on nodes and connections
+fn migrate_v4_to_v5(mut value: Value) -> Result<Value, String> {
+    let root = value
+        .as_object_mut()
+        .ok_or("Topology document must be a JSON object")?;
+
+    fill_default(root, "tags", json!([]));
+    fill_default(root, "tag_assignments", json!([]));
+
+    root.insert("format_version".to_string(), json!(5));
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ConnectionStatus, FlowDirection, NodeType, Rgb};
+
+    /// A bare `TopologyFull` export from before the format was versioned
+    const LEGACY_EXPORT: &str = r#"{
+        "topology": { "id": 1, "name": "Lab" },
+        "nodes": [
+            { "id": 1, "name": "r1", "node_type": "router", "position_x": 2.5 },
+            { "id": 2, "name": "h1" },
+            { "id": 3, "name": "lb1", "node_type": "LoadBalancer" }
+        ],
+        "connections": [
+            { "id": 10, "source_node_id": 1, "target_node_id": 2 }
+        ]
+    }"#;
+
+    #[test]
+    fn legacy_export_is_migrated_with_defaults() {
+        let (document, version) = TopologyDocument::from_json_with_version(LEGACY_EXPORT).unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(document.format_version, CURRENT_FORMAT_VERSION);
+        assert_eq!(document.topology.name, "Lab");
+        assert_eq!(document.topology.description, None);
+
+        let [router, host, balancer] = &document.nodes[..] else {
+            panic!("expected three nodes");
+        };
+        assert_eq!(router.node_type, NodeType::Router);
+        assert_eq!(router.model_name, "blob-router");
+        assert_eq!(router.position_x, 2.5);
+        assert_eq!(host.node_type, NodeType::Host);
+        assert_eq!(host.vendor, "generic");
+        assert_eq!(host.model_name, "blob-host");
+        assert_eq!(host.scale, 1.0);
+        assert_eq!(host.color, Rgb::NODE_DEFAULT);
+        assert!(host.visible);
+        assert_eq!(host.group_id, None);
+        assert_eq!(balancer.node_type, NodeType::LoadBalancer);
+        assert_eq!(balancer.model_name, "blob-load_balancer");
+
+        let connection = &document.connections[0];
+        assert_eq!(connection.status, ConnectionStatus::Active);
+        assert_eq!(connection.flow_direction, FlowDirection::SourceToTarget);
+        assert_eq!(connection.color, Rgb::CONNECTION_DEFAULT);
+        assert!(connection.carries_traffic);
+        assert_eq!(connection.source_interface_id, None);
+
+        assert!(document.interfaces.is_empty());
+        assert!(document.subnets.is_empty());
+        assert!(document.groups.is_empty());
+        assert!(document.tags.is_empty());
+        assert!(document.tag_assignments.is_empty());
+        assert!(document.view_settings.is_none());
+    }
+
+    #[test]
+    fn referenced_models_are_listed_once() {
+        let mut json: Value = serde_json::from_str(LEGACY_EXPORT).unwrap();
+        json["nodes"].as_array_mut().unwrap().push(serde_json::json!({ "id": 4, "name": "r2", "node_type": "router" }));
+        let document = TopologyDocument::from_json(&json.to_string()).unwrap();
+        let models: Vec<(&str, &str)> =
+            document.models.iter().map(|m| (m.node_type.as_str(), m.model_name.as_str())).collect();
+        assert_eq!(
+            models,
+            vec![("host", "blob-host"), ("load_balancer", "blob-load_balancer"), ("router", "blob-router")]
+        );
+    }
+
+    #[test]
+    fn current_version_round_trips() {
+        let original = TopologyDocument::from_json(LEGACY_EXPORT).unwrap();
+        let json = original.to_json_pretty().unwrap();
+        let (reloaded, version) = TopologyDocument::from_json_with_version(&json).unwrap();
+        assert_eq!(version, CURRENT_FORMAT_VERSION);
+        assert_eq!(serde_json::to_value(&reloaded).unwrap(), serde_json::to_value(&original).unwrap());
+    }
+
+    #[test]
+    fn intermediate_version_gains_later_fields() {
+        let json = r#"{
+            "format_version": 3,
+            "topology": { "id": 1, "name": "Lab", "description": null, "created_at": 0, "updated_at": 0 },
+            "nodes": [],
+            "connections": [],
+            "interfaces": [],
+            "subnets": []
+        }"#;
+        let (document, version) = TopologyDocument::from_json_with_version(json).unwrap();
+        assert_eq!(version, 3);
+        assert!(document.groups.is_empty());
+        assert!(document.tags.is_empty());
+        assert!(document.tag_assignments.is_empty());
+    }
+
+    #[test]
+    fn unknown_variants_are_imported_as_defaults() {
+        let mut json: Value = serde_json::from_str(LEGACY_EXPORT).unwrap();
+        json["nodes"][1]["node_type"] = json!("quantum_router");
+        json["connections"][0]["status"] = json!("flapping");
+        let (document, _, warnings) = TopologyDocument::from_json_with_warnings(&json.to_string()).unwrap();
+
+        assert_eq!(document.nodes.len(), 3);
+        assert_eq!(document.nodes[1].node_type, NodeType::Host);
+        assert_eq!(document.nodes[1].model_name, "blob-host");
+        assert_eq!(document.connections[0].status, ConnectionStatus::Active);
+
+        let warnings: Vec<(&str, i64, &str)> =
+            warnings.iter().map(|w| (w.entity_type.as_str(), w.source_id, w.message.as_str())).collect();
+        assert_eq!(
+            warnings,
+            vec![
+                ("node", 2, "Unknown node type 'quantum_router'; imported as 'host'"),
+                ("connection", 10, "Unknown connection status 'flapping'; imported as 'active'"),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_documents() {
+        let newer = format!(r#"{{ "format_version": {}, "topology": {{}} }}"#, CURRENT_FORMAT_VERSION + 1);
+        let error = TopologyDocument::from_json(&newer).unwrap_err();
+        assert!(error.contains("newer than supported"), "{}", error);
+
+        let error = TopologyDocument::from_json(r#"{ "format_version": "five" }"#).unwrap_err();
+        assert!(error.starts_with("Invalid format_version"), "{}", error);
+
+        let error = TopologyDocument::from_json("[]").unwrap_err();
+        assert_eq!(error, "Topology document must be a JSON object");
+
+        let error = TopologyDocument::from_json("{ nodes").unwrap_err();
+        assert!(error.starts_with("Invalid JSON format"), "{}", error);
+
+        let error = TopologyDocument::from_json(r#"{ "topology": { "id": 1, "name": "Lab" } }"#).unwrap_err();
+        assert!(error.starts_with("Invalid topology document"), "{}", error);
+    }
+}
@@ -0,0 +1,107 @@
+use leptos::prelude::ServerFnError;
+use leptos::server_fn::codec::JsonEncoding;
+use leptos::server_fn::error::{FromServerFnError, ServerFnErrorErr};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What kind of failure a server function reports
+///
+/// Travels with the message to the browser and to the REST API, which picks its HTTP
+/// status from the kind instead of the wording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorKind {
+    /// The request isn't signed in
+    Unauthenticated,
+    /// Signed in, but without the role or rights the operation needs
+    Forbidden,
+    /// The topology, row or file doesn't exist
+    NotFound,
+    /// Clashes with existing data, e.g. a name that's already taken
+    Conflict,
+    /// The input failed validation or a database constraint
+    Invalid,
+    /// The database or the server itself failed
+    Internal,
+}
+
+/// Error of the server functions: a kind and a message for the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl AppError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn unauthenticated(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Unauthenticated, message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Forbidden, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::NotFound, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Conflict, message)
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Invalid, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Internal, message)
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+/// Failures of the server function machinery (extractors, encoding, the network)
+impl From<ServerFnError> for AppError {
+    fn from(error: ServerFnError) -> Self {
+        match error {
+            ServerFnError::ServerError(message) => AppError::internal(message),
+            other => AppError::internal(other.to_string()),
+        }
+    }
+}
+
+/// Database failures, classified by what the database reported
+#[cfg(feature = "ssr")]
+impl From<sqlx::Error> for AppError {
+    fn from(error: sqlx::Error) -> Self {
+        let kind = match &error {
+            sqlx::Error::RowNotFound => ErrorKind::NotFound,
+            sqlx::Error::Database(db) if db.is_unique_violation() => ErrorKind::Conflict,
+            sqlx::Error::Database(db) if db.is_foreign_key_violation() || db.is_check_violation() => {
+                ErrorKind::Invalid
+            }
+            _ => ErrorKind::Internal,
+        };
+        AppError::new(kind, error.to_string())
+    }
+}
+
+impl FromServerFnError for AppError {
+    type Encoder = JsonEncoding;
+
+    fn from_server_fn_error(error: ServerFnErrorErr) -> Self {
+        AppError::internal(error.to_string())
+    }
+}
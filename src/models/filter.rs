@@ -0,0 +1,576 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use super::text_enum::normalize;
+use super::{Connection, ConnectionStatus, ConnectionType, Node, NodeType, TopologyFull};
+
+text_enum! {
+    /// Attribute a filter term matches against
+    pub enum FilterField("filter field") {
+        #[default]
+        Name = "name" => "Node name",
+        Kind = "is" | "kind" => "Item kind (node or connection)",
+        Type = "type" => "Node or connection type",
+        Tag = "tag" | "tags" => "Tag",
+        Vendor = "vendor" => "Node vendor",
+        Model = "model" | "model_name" => "Node model",
+        Ip = "ip" | "ip_address" | "address" => "IP address (node or interface)",
+        Group = "group" | "site" => "Group the node is in (at any level)",
+        Status = "status" => "Connection status",
+        Endpoint = "node" | "endpoint" => "Connection endpoint name",
+    }
+}
+
+/// A parsed filter query, e.g. `type:router AND tag:core AND vendor:cisco`
+///
+/// Terms are `field:value` or a bare word, which matches node names containing it.
+/// Terms combine with `AND` (also implied between adjacent terms), `OR` and `NOT` (or a
+/// leading `-`), and group with parentheses. Values are case-insensitive, may use `*`
+/// wildcards and may be "double quoted". An empty query matches everything.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterQuery {
+    All,
+    Term { field: FilterField, patterns: Vec<String> }, // Matches if any pattern does
+    Not(Box<FilterQuery>),
+    And(Vec<FilterQuery>),
+    Or(Vec<FilterQuery>),
+}
+
+/// Nodes and connections matching a filter
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FilterMatches {
+    pub node_ids: Vec<i64>,
+    pub connection_ids: Vec<i64>,
+}
+
+impl FilterMatches {
+    pub fn has_node(&self, id: i64) -> bool {
+        self.node_ids.contains(&id)
+    }
+
+    pub fn has_connection(&self, id: i64) -> bool {
+        self.connection_ids.contains(&id)
+    }
+}
+
+/// A node or connection with the values each filter field sees
+#[derive(Debug, Clone, Default)]
+pub struct FilterItem {
+    values: Vec<(FilterField, String)>,
+}
+
+impl FilterItem {
+    /// `tags` are the node's tag names; `groups` its group and every group above it
+    pub fn node<'a>(
+        node: &Node,
+        tags: impl IntoIterator<Item = &'a str>,
+        groups: impl IntoIterator<Item = &'a str>,
+        addresses: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        let mut item = Self::default();
+        item.push(FilterField::Kind, "node");
+        item.push(FilterField::Name, &node.name);
+        item.push(FilterField::Type, node.node_type.as_str());
+        item.push(FilterField::Vendor, &node.vendor);
+        item.push(FilterField::Model, &node.model_name);
+        if let Some(ip) = &node.ip_address {
+            item.push(FilterField::Ip, strip_prefix_len(ip));
+        }
+        for address in addresses {
+            item.push(FilterField::Ip, strip_prefix_len(address));
+        }
+        for tag in tags {
+            item.push(FilterField::Tag, tag);
+        }
+        for group in groups {
+            item.push(FilterField::Group, group);
+        }
+        item
+    }
+
+    /// `endpoints` are the names of the two nodes the connection joins
+    pub fn connection<'a>(
+        connection: &Connection,
+        tags: impl IntoIterator<Item = &'a str>,
+        endpoints: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        let mut item = Self::default();
+        item.push(FilterField::Kind, "connection");
+        item.push(FilterField::Kind, "link");
+        item.push(FilterField::Type, connection.connection_type.as_str());
+        item.push(FilterField::Status, connection.status.as_str());
+        for tag in tags {
+            item.push(FilterField::Tag, tag);
+        }
+        for endpoint in endpoints {
+            item.push(FilterField::Endpoint, endpoint);
+        }
+        item
+    }
+
+    fn push(&mut self, field: FilterField, value: &str) {
+        self.values.push((field, value.to_lowercase()));
+    }
+}
+
+impl FilterQuery {
+    /// Whether the query matches everything (nothing to filter)
+    pub fn is_all(&self) -> bool {
+        matches!(self, FilterQuery::All)
+    }
+
+    pub fn matches(&self, item: &FilterItem) -> bool {
+        match self {
+            FilterQuery::All => true,
+            FilterQuery::Term { field, patterns } => item
+                .values
+                .iter()
+                .any(|(f, value)| f == field && patterns.iter().any(|pattern| wildcard_match(pattern, value))),
+            FilterQuery::Not(inner) => !inner.matches(item),
+            FilterQuery::And(terms) => terms.iter().all(|term| term.matches(item)),
+            FilterQuery::Or(terms) => terms.iter().any(|term| term.matches(item)),
+        }
+    }
+
+    /// The nodes and connections of a topology this query matches
+    pub fn apply(&self, full: &TopologyFull) -> FilterMatches {
+        let tag_names: HashMap<i64, &str> = full.tags.iter().map(|tag| (tag.id, tag.name.as_str())).collect();
+        let mut node_tags: HashMap<i64, Vec<&str>> = HashMap::new();
+        let mut connection_tags: HashMap<i64, Vec<&str>> = HashMap::new();
+        for assignment in &full.tag_assignments {
+            let Some(&name) = tag_names.get(&assignment.tag_id) else { continue };
+            if let Some(node_id) = assignment.node_id {
+                node_tags.entry(node_id).or_default().push(name);
+            }
+            if let Some(connection_id) = assignment.connection_id {
+                connection_tags.entry(connection_id).or_default().push(name);
+            }
+        }
+
+        let mut node_addresses: HashMap<i64, Vec<&str>> = HashMap::new();
+        for interface in &full.interfaces {
+            if let Some(ip) = &interface.ip_address {
+                node_addresses.entry(interface.node_id).or_default().push(ip);
+            }
+        }
+
+        let group_names: HashMap<i64, &str> = full.groups.iter().map(|g| (g.id, g.name.as_str())).collect();
+        let group_parents: HashMap<i64, i64> =
+            full.groups.iter().filter_map(|g| g.parent_id.map(|parent| (g.id, parent))).collect();
+        let group_path = |group_id: Option<i64>| {
+            let mut path = Vec::new();
+            let mut seen = HashSet::new();
+            let mut current = group_id;
+            while let Some(id) = current.filter(|id| seen.insert(*id)) {
+                path.extend(group_names.get(&id).copied());
+                current = group_parents.get(&id).copied();
+            }
+            path
+        };
+
+        let node_names: HashMap<i64, &str> = full.nodes.iter().map(|n| (n.id, n.name.as_str())).collect();
+        let no_names = Vec::new();
+
+        FilterMatches {
+            node_ids: full
+                .nodes
+                .iter()
+                .filter(|node| {
+                    self.matches(&FilterItem::node(
+                        node,
+                        node_tags.get(&node.id).unwrap_or(&no_names).iter().copied(),
+                        group_path(node.group_id),
+                        node_addresses.get(&node.id).unwrap_or(&no_names).iter().copied(),
+                    ))
+                })
+                .map(|node| node.id)
+                .collect(),
+            connection_ids: full
+                .connections
+                .iter()
+                .filter(|connection| {
+                    let endpoints = [connection.source_node_id, connection.target_node_id]
+                        .into_iter()
+                        .filter_map(|id| node_names.get(&id).copied());
+                    self.matches(&FilterItem::connection(
+                        connection,
+                        connection_tags.get(&connection.id).unwrap_or(&no_names).iter().copied(),
+                        endpoints,
+                    ))
+                })
+                .map(|connection| connection.id)
+                .collect(),
+        }
+    }
+}
+
+impl FromStr for FilterQuery {
+    type Err = String;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(query)?;
+        if tokens.is_empty() {
+            return Ok(FilterQuery::All);
+        }
+        let mut parser = Parser { tokens: &tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(Token::RParen) => Err("Unexpected ')'".to_string()),
+            Some(token) => Err(format!("Unexpected {}", token.describe())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Term { field: Option<String>, value: String },
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::And => "'AND'".to_string(),
+            Token::Or => "'OR'".to_string(),
+            Token::Not => "'NOT'".to_string(),
+            Token::Term { field: Some(field), value } => format!("'{}:{}'", field, value),
+            Token::Term { field: None, value } => format!("'{}'", value),
+        }
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    // Reads a "quoted" value (the opening quote already consumed)
+    fn quoted(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, String> {
+        let mut value = String::new();
+        for c in chars.by_ref() {
+            if c == '"' {
+                return Ok(value);
+            }
+            value.push(c);
+        }
+        Err("Missing closing '\"'".to_string())
+    }
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            '-' => {
+                chars.next();
+                tokens.push(Token::Not);
+            }
+            '"' => {
+                chars.next();
+                tokens.push(Token::Term { field: None, value: quoted(&mut chars)? });
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '"') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                let token = match word.split_once(':') {
+                    Some((field, value)) => {
+                        let value = if value.is_empty() && chars.peek() == Some(&'"') {
+                            chars.next();
+                            quoted(&mut chars)?
+                        } else {
+                            value.to_string()
+                        };
+                        if value.is_empty() {
+                            return Err(format!("Missing value after '{}:'", field));
+                        }
+                        Token::Term { field: Some(field.to_string()), value }
+                    }
+                    None if word.eq_ignore_ascii_case("and") => Token::And,
+                    None if word.eq_ignore_ascii_case("or") => Token::Or,
+                    None if word.eq_ignore_ascii_case("not") => Token::Not,
+                    None => Token::Term { field: None, value: word },
+                };
+                tokens.push(token);
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over `or := and (OR and)*`, `and := unary (AND? unary)*`,
+/// `unary := NOT unary | '(' or ')' | term`
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<FilterQuery, String> {
+        let mut terms = vec![self.parse_and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            terms.push(self.parse_and()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { FilterQuery::Or(terms) })
+    }
+
+    fn parse_and(&mut self) -> Result<FilterQuery, String> {
+        let mut terms = vec![self.parse_unary()?];
+        loop {
+            match self.peek() {
+                None | Some(Token::Or) | Some(Token::RParen) => break,
+                Some(Token::And) => {
+                    self.next();
+                }
+                Some(_) => {}
+            }
+            terms.push(self.parse_unary()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { FilterQuery::And(terms) })
+    }
+
+    fn parse_unary(&mut self) -> Result<FilterQuery, String> {
+        match self.next().cloned() {
+            Some(Token::Not) => Ok(FilterQuery::Not(Box::new(self.parse_unary()?))),
+            Some(Token::LParen) => {
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(inner),
+                    _ => Err("Missing closing ')'".to_string()),
+                }
+            }
+            Some(Token::Term { field, value }) => term(field.as_deref(), &value),
+            Some(token) => Err(format!("Expected a term but found {}", token.describe())),
+            None => Err("Query ends where a term was expected".to_string()),
+        }
+    }
+}
+
+/// Build a term; bare words match names containing them
+fn term(field: Option<&str>, value: &str) -> Result<FilterQuery, String> {
+    let value = value.to_lowercase();
+    let Some(field) = field else {
+        let pattern = if value.contains('*') { value } else { format!("*{}*", value) };
+        return Ok(FilterQuery::Term { field: FilterField::Name, patterns: vec![pattern] });
+    };
+
+    let field = field.parse::<FilterField>().map_err(|_| {
+        let fields: Vec<&str> = FilterField::ALL.iter().map(|f| f.as_str()).collect();
+        format!("Unknown filter field '{}' (use one of: {})", field, fields.join(", "))
+    })?;
+
+    // Type and status values accept the same spellings as everywhere else ("lb", "up")
+    let mut patterns = vec![value.clone()];
+    if !value.contains('*') {
+        let canonical = match field {
+            FilterField::Type => vec![
+                NodeType::from_str(&value).ok().map(NodeType::as_str),
+                ConnectionType::from_str(&value).ok().map(ConnectionType::as_str),
+            ],
+            FilterField::Status => vec![ConnectionStatus::from_str(&value).ok().map(ConnectionStatus::as_str)],
+            _ => Vec::new(),
+        };
+        patterns.extend(canonical.into_iter().flatten().map(str::to_string));
+        if matches!(field, FilterField::Type | FilterField::Status) {
+            patterns.push(normalize(&value));
+        }
+        patterns.dedup();
+    }
+    Ok(FilterQuery::Term { field, patterns })
+}
+
+/// Case-folded glob match where `*` matches any run of characters
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == value;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !value.starts_with(first) || value.len() < first.len() + last.len() || !value.ends_with(last) {
+        return false;
+    }
+    let mut rest = &value[first.len()..value.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// "10.0.0.1/24" -> "10.0.0.1", so `ip:10.0.0.*` matches addresses stored in CIDR form
+fn strip_prefix_len(address: &str) -> &str {
+    address.split_once('/').map_or(address, |(address, _)| address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(query: &str) -> FilterQuery {
+        query.parse().unwrap_or_else(|e| panic!("'{}' failed to parse: {}", query, e))
+    }
+
+    fn parse_error(query: &str) -> String {
+        query.parse::<FilterQuery>().expect_err(query)
+    }
+
+    fn name(value: &str) -> FilterQuery {
+        FilterQuery::Term { field: FilterField::Name, patterns: vec![format!("*{}*", value)] }
+    }
+
+    fn item(values: &[(FilterField, &str)]) -> FilterItem {
+        let mut item = FilterItem::default();
+        for (field, value) in values {
+            item.push(*field, value);
+        }
+        item
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(parse(""), FilterQuery::All);
+        assert_eq!(parse("   "), FilterQuery::All);
+        assert!(parse("").matches(&FilterItem::default()));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            parse("a OR b AND c"),
+            FilterQuery::Or(vec![name("a"), FilterQuery::And(vec![name("b"), name("c")])])
+        );
+        // Adjacent terms are an implied AND
+        assert_eq!(
+            parse("a b or c"),
+            FilterQuery::Or(vec![FilterQuery::And(vec![name("a"), name("b")]), name("c")])
+        );
+    }
+
+    #[test]
+    fn parentheses_group() {
+        assert_eq!(
+            parse("(a OR b) c"),
+            FilterQuery::And(vec![FilterQuery::Or(vec![name("a"), name("b")]), name("c")])
+        );
+    }
+
+    #[test]
+    fn not_applies_to_the_next_term() {
+        assert_eq!(
+            parse("NOT a b"),
+            FilterQuery::And(vec![FilterQuery::Not(Box::new(name("a"))), name("b")])
+        );
+        assert_eq!(parse("-a"), FilterQuery::Not(Box::new(name("a"))));
+        assert_eq!(
+            parse("not (a or b)"),
+            FilterQuery::Not(Box::new(FilterQuery::Or(vec![name("a"), name("b")])))
+        );
+    }
+
+    #[test]
+    fn quoted_values_keep_spaces_and_keywords() {
+        assert_eq!(parse("\"Core Router\""), name("core router"));
+        assert_eq!(parse("\"and\""), name("and"));
+        assert_eq!(
+            parse("tag:\"data center\""),
+            FilterQuery::Term { field: FilterField::Tag, patterns: vec!["data center".to_string()] }
+        );
+    }
+
+    #[test]
+    fn field_aliases() {
+        assert_eq!(
+            parse("site:HQ"),
+            FilterQuery::Term { field: FilterField::Group, patterns: vec!["hq".to_string()] }
+        );
+        let FilterQuery::Term { field, patterns } = parse("type:lb") else {
+            panic!("expected a term");
+        };
+        assert_eq!(field, FilterField::Type);
+        assert!(patterns.contains(&"load_balancer".to_string()));
+    }
+
+    #[test]
+    fn matching() {
+        let router = item(&[
+            (FilterField::Kind, "node"),
+            (FilterField::Name, "Core-RTR-1"),
+            (FilterField::Type, "router"),
+            (FilterField::Vendor, "Cisco"),
+            (FilterField::Tag, "core"),
+        ]);
+        assert!(parse("type:router AND tag:core").matches(&router));
+        assert!(parse("type:switch OR tag:core").matches(&router));
+        assert!(parse("rtr").matches(&router));
+        assert!(parse("vendor:cis*").matches(&router));
+        assert!(!parse("NOT tag:core").matches(&router));
+        assert!(!parse("-type:router").matches(&router));
+        assert!(!parse("type:router tag:edge").matches(&router));
+        assert!(!parse("vendor:cis").matches(&router));
+
+        let link = item(&[(FilterField::Kind, "connection"), (FilterField::Status, "active")]);
+        assert!(parse("status:up").matches(&link));
+        assert!(!parse("status:down").matches(&link));
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(wildcard_match("a*c", "abc"));
+        assert!(wildcard_match("a*c", "ac"));
+        assert!(wildcard_match("*b*", "abc"));
+        assert!(wildcard_match("10.0.0.*", strip_prefix_len("10.0.0.5/24")));
+        assert!(!wildcard_match("a*c", "ab"));
+        assert!(!wildcard_match("ab*ab", "ab"));
+        assert!(!wildcard_match("abc", "abcd"));
+    }
+
+    #[test]
+    fn unknown_field() {
+        let error = parse_error("colour:red");
+        assert!(error.starts_with("Unknown filter field 'colour'"), "{}", error);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse_error("(a"), "Missing closing ')'");
+        assert_eq!(parse_error("a)"), "Unexpected ')'");
+        assert_eq!(parse_error("NOT"), "Query ends where a term was expected");
+        assert_eq!(parse_error("a OR"), "Query ends where a term was expected");
+        assert_eq!(parse_error("AND a"), "Expected a term but found 'AND'");
+        assert_eq!(parse_error("\"open"), "Missing closing '\"'");
+        assert_eq!(parse_error("tag:"), "Missing value after 'tag:'");
+    }
+}
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::Rgb;
+
+#[cfg(feature = "ssr")]
+use sqlx::FromRow;
+
+/// A container nodes can belong to (site, building, rack or security zone)
+///
+/// Groups nest through `parent_id`; each node belongs to at most one group, and a
+/// group's members include everything in its child groups.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct NodeGroup {
+    pub id: i64,
+    pub topology_id: i64,
+    pub parent_id: Option<i64>, // None = top level
+    pub name: String,
+    pub group_type: GroupType,
+    pub color: Rgb,
+    pub collapsed: bool, // Drawn as a single proxy node in the viewport
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Data transfer object for creating a group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateNodeGroup {
+    pub topology_id: i64,
+    pub parent_id: Option<i64>,
+    pub name: String,
+    pub group_type: GroupType,
+    pub color: Option<Rgb>, // Defaults to the group type's color
+}
+
+/// Data transfer object for updating a group
+///
+/// `parent_id: Some(0)` moves the group to the top level.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateNodeGroup {
+    pub parent_id: Option<i64>,
+    pub name: Option<String>,
+    pub group_type: Option<GroupType>,
+    pub color: Option<Rgb>,
+    pub collapsed: Option<bool>,
+}
+
+text_enum! {
+    /// What a group represents
+    pub enum GroupType("group type") {
+        #[default]
+        Site = "site" => "Site",
+        Building = "building" => "Building",
+        Rack = "rack" => "Rack",
+        Zone = "zone" | "security_zone" => "Security Zone",
+    }
+}
+
+impl GroupType {
+    /// Color a new group of this type gets
+    pub fn default_color(self) -> Rgb {
+        match self {
+            GroupType::Site => Rgb::new(120, 160, 220),
+            GroupType::Building => Rgb::new(140, 200, 160),
+            GroupType::Rack => Rgb::new(200, 170, 110),
+            GroupType::Zone => Rgb::new(220, 110, 110),
+        }
+    }
+
+    /// Whether the viewport draws the group as a floor plate (rather than a box around its members)
+    pub fn is_floor(self) -> bool {
+        matches!(self, GroupType::Site | GroupType::Building)
+    }
+}
+
+/// `root` and every group nested under it, parents before children
+pub fn group_subtree(groups: &[NodeGroup], root: i64) -> Vec<i64> {
+    let mut children: HashMap<i64, Vec<i64>> = HashMap::new();
+    for group in groups {
+        if let Some(parent_id) = group.parent_id {
+            children.entry(parent_id).or_default().push(group.id);
+        }
+    }
+
+    let mut subtree = vec![root];
+    let mut seen = HashSet::from([root]);
+    let mut i = 0;
+    while i < subtree.len() {
+        for &child in children.get(&subtree[i]).into_iter().flatten() {
+            // `seen` guards against cycles in hand-edited documents
+            if seen.insert(child) {
+                subtree.push(child);
+            }
+        }
+        i += 1;
+    }
+    subtree
+}
+
+/// Groups in depth-first tree order with their nesting depth (parents before children)
+///
+/// Groups whose parent is missing are treated as top level; groups caught in a parent
+/// cycle (only possible in hand-edited documents) come last, at depth 0.
+pub fn group_tree(groups: &[NodeGroup]) -> Vec<(&NodeGroup, usize)> {
+    fn visit<'a>(
+        group: &'a NodeGroup,
+        depth: usize,
+        children: &HashMap<i64, Vec<&'a NodeGroup>>,
+        placed: &mut HashSet<i64>,
+        tree: &mut Vec<(&'a NodeGroup, usize)>,
+    ) {
+        if !placed.insert(group.id) {
+            return;
+        }
+        tree.push((group, depth));
+        for &child in children.get(&group.id).into_iter().flatten() {
+            visit(child, depth + 1, children, placed, tree);
+        }
+    }
+
+    let ids: HashSet<i64> = groups.iter().map(|g| g.id).collect();
+    let mut children: HashMap<i64, Vec<&NodeGroup>> = HashMap::new();
+    for group in groups {
+        if let Some(parent_id) = group.parent_id.filter(|id| ids.contains(id)) {
+            children.entry(parent_id).or_default().push(group);
+        }
+    }
+
+    let mut tree = Vec::with_capacity(groups.len());
+    let mut placed = HashSet::new();
+    for group in groups.iter().filter(|g| g.parent_id.is_none_or(|p| !ids.contains(&p))) {
+        visit(group, 0, &children, &mut placed, &mut tree);
+    }
+    for group in groups {
+        visit(group, 0, &children, &mut placed, &mut tree);
+    }
+    tree
+}
+
+/// Validate a group name
+pub fn validate_group_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        Err("Group name cannot be empty".to_string())
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ssr")]
+use sqlx::FromRow;
+
+/// A named port on a node (e.g. "Gi0/0/1", "eth0") that connections can attach to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct Interface {
+    pub id: i64,
+    pub node_id: i64,
+    pub name: String,
+    pub speed_mbps: Option<i64>,
+    pub mac_address: Option<String>, // Normalized to "aa:bb:cc:dd:ee:ff"
+    pub ip_address: Option<String>,  // Address or CIDR (e.g., "10.0.0.1/24")
+    pub admin_state: AdminState,
+    pub description: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Data transfer object for creating an interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateInterface {
+    pub node_id: i64,
+    pub name: String,
+    pub speed_mbps: Option<i64>,
+    pub mac_address: Option<String>,
+    pub ip_address: Option<String>,
+    pub admin_state: Option<AdminState>,
+    pub description: Option<String>,
+}
+
+/// Data transfer object for updating an interface
+///
+/// For the optional text fields, an empty string clears the value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateInterface {
+    pub name: Option<String>,
+    pub speed_mbps: Option<i64>,
+    pub mac_address: Option<String>,
+    pub ip_address: Option<String>,
+    pub admin_state: Option<AdminState>,
+    pub description: Option<String>,
+}
+
+text_enum! {
+    /// Administrative state of an interface
+    pub enum AdminState("admin state") {
+        #[default]
+        Up = "up" | "enabled" | "no_shutdown" => "Up",
+        Down = "down" | "disabled" | "shutdown" => "Down",
+    }
+}
+
+/// Normalize a MAC address to lowercase colon-separated form
+///
+/// Accepts `:` / `-` separated pairs, Cisco dotted triples (`aabb.ccdd.eeff`) and bare hex.
+pub fn normalize_mac(value: &str) -> Result<String, String> {
+    let hex: String = value
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ':' | '-' | '.'))
+        .collect();
+    if hex.len() != 12 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid MAC address '{}'", value));
+    }
+    let hex = hex.to_ascii_lowercase();
+    Ok((0..6).map(|i| &hex[i * 2..i * 2 + 2]).collect::<Vec<_>>().join(":"))
+}
+
+/// Check an interface address: an IPv4/IPv6 address with an optional /prefix
+pub fn validate_interface_ip(value: &str) -> Result<(), String> {
+    super::ipam::parse_address(value).map(|_| ())
+}
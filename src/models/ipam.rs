@@ -0,0 +1,355 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+#[cfg(feature = "ssr")]
+use sqlx::FromRow;
+
+/// An address block (IPv4 or IPv6) planned for a topology
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct Subnet {
+    pub id: i64,
+    pub topology_id: i64,
+    pub cidr: String, // Canonical network prefix, e.g. "10.0.1.0/24"
+    pub name: String,
+    pub gateway: Option<String>,
+    pub vlan_id: Option<i64>,
+    pub description: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Data transfer object for creating a subnet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSubnet {
+    pub topology_id: i64,
+    pub cidr: String,
+    pub name: Option<String>,
+    pub gateway: Option<String>,
+    pub vlan_id: Option<i64>,
+    pub description: Option<String>,
+}
+
+/// Data transfer object for updating a subnet
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateSubnet {
+    pub cidr: Option<String>,
+    pub name: Option<String>,
+    pub gateway: Option<String>,
+    pub vlan_id: Option<i64>,
+    pub description: Option<String>,
+}
+
+/// Something in the topology that holds an address: a node, or one of its interfaces
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IpHolder {
+    pub node_id: i64,
+    pub node_name: String,
+    pub interface_id: Option<i64>,
+    pub interface_name: Option<String>,
+    pub address: String, // As stored, e.g. "10.0.1.5" or "10.0.1.5/24"
+}
+
+impl IpHolder {
+    /// "node" or "node:interface"
+    pub fn label(&self) -> String {
+        match &self.interface_name {
+            Some(interface) => format!("{}:{}", self.node_name, interface),
+            None => self.node_name.clone(),
+        }
+    }
+}
+
+/// A subnet together with how much of it is in use
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubnetUsage {
+    pub subnet: Subnet,
+    pub used: usize,
+    pub capacity: u64, // Usable host addresses (saturates for large IPv6 prefixes)
+    pub next_free: Option<String>,
+}
+
+/// The same address assigned to more than one node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpConflict {
+    pub address: String,
+    pub holders: Vec<IpHolder>,
+}
+
+/// A connection whose ends sit in different subnets with no router at either end
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubnetMismatch {
+    pub connection_id: i64,
+    pub source: IpHolder,
+    pub source_subnet: String,
+    pub target: IpHolder,
+    pub target_subnet: String,
+}
+
+/// Address plan of a topology: subnet usage and everything that needs attention
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IpamReport {
+    pub subnets: Vec<SubnetUsage>,
+    pub conflicts: Vec<IpConflict>,
+    pub mismatched_links: Vec<SubnetMismatch>,
+    pub unassigned: Vec<IpHolder>, // Addresses outside every defined subnet
+    pub invalid: Vec<IpHolder>,    // Addresses that don't parse (stored before validation existed)
+}
+
+/// A network prefix, e.g. `10.0.0.0/24` or `2001:db8::/64`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpPrefix {
+    network: IpAddr,
+    len: u8,
+}
+
+impl IpPrefix {
+    /// Prefix of `len` bits containing `address` (host bits are cleared)
+    pub fn containing(address: IpAddr, len: u8) -> Option<Self> {
+        if len > max_prefix_len(address) {
+            return None;
+        }
+        let network = from_bits(address, to_bits(address) & mask(address, len));
+        Some(Self { network, len })
+    }
+
+    pub fn network(&self) -> IpAddr {
+        self.network
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.len
+    }
+
+    pub fn contains(&self, address: IpAddr) -> bool {
+        address.is_ipv4() == self.network.is_ipv4()
+            && to_bits(address) & mask(address, self.len) == to_bits(self.network)
+    }
+
+    /// First and last address hosts can use
+    ///
+    /// IPv4 prefixes up to /30 exclude the network and broadcast addresses; /31 and /32
+    /// (point-to-point and host routes) use every address.
+    pub fn host_range(&self) -> (u128, u128) {
+        let first = to_bits(self.network);
+        let last = first | (!mask(self.network, self.len) & full_mask(self.network));
+        if self.network.is_ipv4() && self.len <= 30 {
+            (first + 1, last - 1)
+        } else {
+            (first, last)
+        }
+    }
+
+    /// Number of usable host addresses
+    pub fn capacity(&self) -> u64 {
+        let (first, last) = self.host_range();
+        u64::try_from((last - first).saturating_add(1)).unwrap_or(u64::MAX)
+    }
+
+    /// Whether `address` is in the prefix and usable by a host
+    pub fn is_host(&self, address: IpAddr) -> bool {
+        let (first, last) = self.host_range();
+        self.contains(address) && (first..=last).contains(&to_bits(address))
+    }
+
+    /// Lowest usable host address not in `taken`
+    pub fn first_free(&self, taken: &std::collections::HashSet<IpAddr>) -> Option<IpAddr> {
+        let (first, last) = self.host_range();
+        // Each candidate skipped is in `taken`, so this visits at most taken.len() + 1 addresses
+        (first..=last)
+            .map(|bits| from_bits(self.network, bits))
+            .find(|address| !taken.contains(address))
+    }
+}
+
+impl fmt::Display for IpPrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.len)
+    }
+}
+
+impl FromStr for IpPrefix {
+    type Err = String;
+
+    /// Parse a network prefix; an address with host bits set is rejected
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (address, len) = parse_address(value)?;
+        let len = len.ok_or_else(|| format!("Subnet '{}' needs a prefix length, e.g. 10.0.0.0/24", value.trim()))?;
+        let prefix = Self::containing(address, len).ok_or_else(|| format!("Invalid subnet '{}'", value.trim()))?;
+        if prefix.network != address {
+            return Err(format!(
+                "'{}' has host bits set (did you mean {}?)",
+                value.trim(),
+                prefix
+            ));
+        }
+        Ok(prefix)
+    }
+}
+
+/// Parse an address with an optional prefix length (`10.0.0.1` or `10.0.0.1/24`)
+pub fn parse_address(value: &str) -> Result<(IpAddr, Option<u8>), String> {
+    let invalid = || format!("Invalid IP address '{}'", value.trim());
+    let (address, len) = match value.trim().split_once('/') {
+        Some((address, len)) => (address, Some(len)),
+        None => (value.trim(), None),
+    };
+    let address: IpAddr = address.parse().map_err(|_| invalid())?;
+    let len = match len {
+        Some(len) => match len.parse::<u8>() {
+            Ok(len) if len <= max_prefix_len(address) => Some(len),
+            _ => return Err(invalid()),
+        },
+        None => None,
+    };
+    Ok((address, len))
+}
+
+fn max_prefix_len(address: IpAddr) -> u8 {
+    if address.is_ipv4() { 32 } else { 128 }
+}
+
+fn to_bits(address: IpAddr) -> u128 {
+    match address {
+        IpAddr::V4(v4) => u32::from(v4) as u128,
+        IpAddr::V6(v6) => u128::from(v6),
+    }
+}
+
+/// Address of the same family as `family` with the given bits
+fn from_bits(family: IpAddr, bits: u128) -> IpAddr {
+    match family {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from(bits as u32)),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(bits)),
+    }
+}
+
+/// All address bits of the family set
+fn full_mask(family: IpAddr) -> u128 {
+    if family.is_ipv4() { u32::MAX as u128 } else { u128::MAX }
+}
+
+/// Network mask of `len` bits for the family
+fn mask(family: IpAddr, len: u8) -> u128 {
+    let width = max_prefix_len(family) as u32;
+    let host_bits = width - len as u32;
+    full_mask(family) & full_mask(family).checked_shl(host_bits).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn prefix(value: &str) -> IpPrefix {
+        value.parse().unwrap_or_else(|e| panic!("'{}' failed to parse: {}", value, e))
+    }
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_and_displays_prefixes() {
+        assert_eq!(prefix("10.0.1.0/24").to_string(), "10.0.1.0/24");
+        assert_eq!(prefix(" 0.0.0.0/0 ").to_string(), "0.0.0.0/0");
+        assert_eq!(prefix("2001:db8::/32").to_string(), "2001:db8::/32");
+        assert_eq!(prefix("::/0").prefix_len(), 0);
+        assert_eq!(prefix("2001:db8::1/128").network(), ip("2001:db8::1"));
+    }
+
+    #[test]
+    fn rejects_bad_prefixes() {
+        assert_eq!(
+            "10.0.0.1/24".parse::<IpPrefix>().unwrap_err(),
+            "'10.0.0.1/24' has host bits set (did you mean 10.0.0.0/24?)"
+        );
+        assert!("10.0.0.0".parse::<IpPrefix>().unwrap_err().contains("needs a prefix length"));
+        assert!("10.0.0.0/33".parse::<IpPrefix>().is_err());
+        assert!("2001:db8::/129".parse::<IpPrefix>().is_err());
+        assert!("10.0.0.0/-1".parse::<IpPrefix>().is_err());
+        assert!("10.0.0.256/24".parse::<IpPrefix>().is_err());
+    }
+
+    #[test]
+    fn parses_addresses_with_optional_length() {
+        assert_eq!(parse_address("10.0.0.5"), Ok((ip("10.0.0.5"), None)));
+        assert_eq!(parse_address("10.0.0.5/24"), Ok((ip("10.0.0.5"), Some(24))));
+        assert_eq!(parse_address("fe80::1/64"), Ok((ip("fe80::1"), Some(64))));
+        assert_eq!(parse_address("router"), Err("Invalid IP address 'router'".to_string()));
+    }
+
+    #[test]
+    fn ipv4_host_ranges() {
+        let lan = prefix("192.168.1.0/24");
+        assert_eq!(lan.capacity(), 254);
+        assert!(!lan.is_host(ip("192.168.1.0")));
+        assert!(lan.is_host(ip("192.168.1.1")));
+        assert!(lan.is_host(ip("192.168.1.254")));
+        assert!(!lan.is_host(ip("192.168.1.255")));
+
+        assert_eq!(prefix("10.0.0.0/30").capacity(), 2);
+        assert_eq!(prefix("0.0.0.0/0").capacity(), (1u64 << 32) - 2);
+    }
+
+    #[test]
+    fn point_to_point_and_host_routes_use_every_address() {
+        let link = prefix("10.0.0.0/31");
+        assert_eq!(link.capacity(), 2);
+        assert!(link.is_host(ip("10.0.0.0")));
+        assert!(link.is_host(ip("10.0.0.1")));
+        assert!(!link.is_host(ip("10.0.0.2")));
+
+        let host = prefix("10.0.0.7/32");
+        assert_eq!(host.capacity(), 1);
+        assert!(host.is_host(ip("10.0.0.7")));
+        assert!(!host.contains(ip("10.0.0.6")));
+    }
+
+    #[test]
+    fn ipv6_host_ranges() {
+        assert_eq!(prefix("2001:db8::/127").capacity(), 2);
+        assert_eq!(prefix("2001:db8::1/128").capacity(), 1);
+        // Too many to count: saturates
+        assert_eq!(prefix("2001:db8::/64").capacity(), u64::MAX);
+        assert_eq!(prefix("::/0").capacity(), u64::MAX);
+
+        let net = prefix("2001:db8::/64");
+        assert!(net.is_host(ip("2001:db8::")));
+        assert!(net.is_host(ip("2001:db8::ffff:ffff:ffff:ffff")));
+        assert!(!net.contains(ip("2001:db8:0:1::")));
+    }
+
+    #[test]
+    fn families_never_mix() {
+        assert!(!prefix("0.0.0.0/0").contains(ip("::1")));
+        assert!(!prefix("::/0").contains(ip("10.0.0.1")));
+    }
+
+    #[test]
+    fn overlapping_prefixes_nest() {
+        let site = prefix("10.0.0.0/16");
+        let lan = prefix("10.0.1.0/24");
+        let other = prefix("10.1.0.0/24");
+        assert!(site.contains(lan.network()));
+        assert!(!lan.contains(site.network()));
+        assert!(!site.contains(other.network()));
+        assert_eq!(IpPrefix::containing(ip("10.0.1.77"), 16), Some(site));
+        assert_eq!(IpPrefix::containing(ip("10.0.1.77"), 33), None);
+    }
+
+    #[test]
+    fn first_free_skips_taken_addresses() {
+        let net = prefix("10.0.0.0/30");
+        let mut taken = HashSet::new();
+        assert_eq!(net.first_free(&taken), Some(ip("10.0.0.1")));
+        taken.insert(ip("10.0.0.1"));
+        assert_eq!(net.first_free(&taken), Some(ip("10.0.0.2")));
+        taken.insert(ip("10.0.0.2"));
+        assert_eq!(net.first_free(&taken), None);
+
+        assert_eq!(prefix("10.0.0.0/31").first_free(&HashSet::new()), Some(ip("10.0.0.0")));
+    }
+}
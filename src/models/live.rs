@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Connection, Node, TopologyFull};
+
+/// The item another viewer has selected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LiveSelection {
+    Node(i64),
+    Connection(i64),
+    Group(i64),
+}
+
+/// Someone with a topology open in the editor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiveViewer {
+    pub viewer_id: u64, // One per open socket, so a user may appear more than once
+    pub user_id: i64,
+    pub username: String,
+    pub selection: Option<LiveSelection>,
+}
+
+/// Message pushed to everyone viewing a topology over its live socket
+///
+/// Node and connection edits carry the new state so clients can apply them without
+/// refetching; anything broader (groups, tags, undo, revision restores) is reported as
+/// `TopologyChanged` and clients reload the topology.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    /// First message on a socket: the id other viewers know this one by
+    Welcome { viewer_id: u64 },
+    /// Everyone currently viewing, sent whenever someone joins, leaves or selects
+    Presence { viewers: Vec<LiveViewer> },
+    NodeUpserted { node: Node },
+    NodeDeleted { id: i64 },
+    ConnectionUpserted { connection: Connection },
+    ConnectionDeleted { id: i64 },
+    TopologyChanged,
+    /// The topology itself was deleted; clients leave it
+    TopologyDeleted,
+    /// The user lost access to the topology; only their sockets get this, then close
+    AccessRevoked { user_id: i64 },
+}
+
+/// Message a client sends over its live socket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveClientMessage {
+    Select { selection: Option<LiveSelection> },
+}
+
+impl TopologyFull {
+    /// Apply a node or connection event in place
+    ///
+    /// Returns false for events that don't describe a change to apply (presence,
+    /// `TopologyChanged`, which needs a reload, `TopologyDeleted` or `AccessRevoked`).
+    pub fn apply_live_event(&mut self, event: &LiveEvent) -> bool {
+        match event {
+            LiveEvent::NodeUpserted { node } => {
+                match self.nodes.iter_mut().find(|n| n.id == node.id) {
+                    Some(existing) => *existing = node.clone(),
+                    None => self.nodes.push(node.clone()),
+                }
+                true
+            }
+            LiveEvent::NodeDeleted { id } => {
+                // Mirror the cascades of the nodes table
+                self.nodes.retain(|n| n.id != *id);
+                self.connections
+                    .retain(|c| c.source_node_id != *id && c.target_node_id != *id);
+                self.interfaces.retain(|i| i.node_id != *id);
+                self.tag_assignments.retain(|a| a.node_id != Some(*id));
+                true
+            }
+            LiveEvent::ConnectionUpserted { connection } => {
+                match self.connections.iter_mut().find(|c| c.id == connection.id) {
+                    Some(existing) => *existing = connection.clone(),
+                    None => self.connections.push(connection.clone()),
+                }
+                true
+            }
+            LiveEvent::ConnectionDeleted { id } => {
+                self.connections.retain(|c| c.id != *id);
+                self.tag_assignments.retain(|a| a.connection_id != Some(*id));
+                true
+            }
+            LiveEvent::Welcome { .. }
+            | LiveEvent::Presence { .. }
+            | LiveEvent::TopologyChanged
+            | LiveEvent::TopologyDeleted
+            | LiveEvent::AccessRevoked { .. } => false,
+        }
+    }
+}
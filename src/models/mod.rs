@@ -1,13 +1,48 @@
+#[macro_use]
+mod text_enum;
+pub mod color;
 pub mod topology;
 pub mod node;
 pub mod connection;
+pub mod interface;
+pub mod ipam;
+pub mod group;
+pub mod tag;
+pub mod filter;
+pub mod search;
 pub mod traffic;
 pub mod ui_settings;
 pub mod vendor;
+pub mod model_check;
+pub mod user;
+pub mod audit;
+pub mod live;
+pub mod error;
+pub mod conflict;
+pub mod document;
+pub mod revision;
+pub mod diff;
 
-pub use topology::{Topology, CreateTopology, UpdateTopology, TopologyFull};
-pub use node::{Node, CreateNode, UpdateNode, node_types};
-pub use connection::{Connection, CreateConnection, UpdateConnection, connection_types, connection_status};
+pub use color::{InvalidColor, Rgb};
+pub use text_enum::UnknownVariant;
+pub use topology::{Topology, CreateTopology, UpdateTopology, TopologyFull, ImportReport, ImportRejection, ImportWarning};
+pub use node::{Node, CreateNode, UpdateNode, NodeType};
+pub use connection::{Connection, CreateConnection, UpdateConnection, ConnectionType, ConnectionStatus, FlowDirection};
+pub use interface::{Interface, CreateInterface, UpdateInterface, AdminState, normalize_mac, validate_interface_ip};
+pub use ipam::{Subnet, CreateSubnet, UpdateSubnet, IpPrefix, IpHolder, IpConflict, IpamReport, SubnetMismatch, SubnetUsage, parse_address};
+pub use group::{NodeGroup, CreateNodeGroup, UpdateNodeGroup, GroupType, group_subtree, group_tree, validate_group_name};
+pub use tag::{Tag, TagAssignment, normalize_tag_name, parse_tag_list};
+pub use filter::{FilterField, FilterItem, FilterMatches, FilterQuery};
+pub use search::{SearchHit, SearchKind, fts_match_query, tsquery, MAX_SEARCH_RESULTS};
 pub use traffic::{TrafficMetric, CreateTrafficMetric, ConnectionTrafficMetric, CreateConnectionTrafficMetric};
 pub use ui_settings::{UISettings, UpdateUISettings};
-pub use vendor::{VendorInfo, ModelInfo, VendorListResponse};
+pub use vendor::{VendorInfo, ModelInfo, ModelAnchor, VendorManifest, ModelManifest, VendorListResponse, ModelUpload, ModelUploadReport, display_name_from_slug, check_model_slug, MAX_SLUG_LEN};
+pub use model_check::{ModelValidation, ModelIssue, ModelMaterial, IssueSeverity, IssueCategory, validate_glb, check_vendor_icon, MAX_ICON_BYTES, NTB_IDEAL_SIZE, NTB_MAX_RECOMMENDED, NTB_MIN_SIZE};
+pub use user::{User, Credentials, Role, TopologyPermission, ApiToken, NewApiToken, validate_credentials, MIN_PASSWORD_LENGTH};
+pub use audit::{AuditLogEntry, AuditEntity, AuditAction, AuditExportFormat, AuditFilter, audit_diff, audit_log_to_csv, MAX_AUDIT_ENTRIES};
+pub use live::{LiveEvent, LiveClientMessage, LiveSelection, LiveViewer};
+pub use error::{AppError, ErrorKind};
+pub use conflict::{UpdateError, merge_edits};
+pub use document::{TopologyDocument, ViewSettings, CameraView, ModelReference, CURRENT_FORMAT_VERSION};
+pub use revision::{TopologyRevision, CreateRevision, RevisionPreview};
+pub use diff::{diff_topologies, ChangeKind, ConnectionChange, DiffSource, FieldChange, NodeChange, TopologyDiff};
@@ -0,0 +1,527 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Model size (largest bounding box side, in scene units) that looks right next to others
+pub const NTB_IDEAL_SIZE: f64 = 1.0;
+/// Models larger than this may look oversized
+pub const NTB_MAX_RECOMMENDED: f64 = 2.0;
+/// Models smaller than this may be hard to see
+pub const NTB_MIN_SIZE: f64 = 0.3;
+
+/// "glTF" read as a little-endian u32
+const GLB_MAGIC: u32 = 0x4654_6C67;
+/// "JSON" read as a little-endian u32
+const GLB_JSON_CHUNK: u32 = 0x4E4F_534A;
+
+/// Largest vendor icon accepted for upload
+pub const MAX_ICON_BYTES: usize = 256 * 1024;
+
+text_enum! {
+    /// How bad a model issue is (ordered: info < warning < error)
+    pub enum IssueSeverity("issue severity") {
+        /// Worth knowing, nothing to fix
+        #[default]
+        Info = "info" => "Info",
+        /// The model works but looks wrong
+        Warning = "warning" => "Warning",
+        /// The model won't render properly
+        Error = "error" => "Error",
+    }
+}
+
+text_enum! {
+    /// What part of a model an issue is about
+    pub enum IssueCategory("issue category") {
+        #[default]
+        Structure = "structure" => "Structure",
+        Material = "material" => "Material",
+        Scale = "scale" => "Scale",
+    }
+}
+
+/// One problem found in a model, with how to fix it where that's known
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelIssue {
+    pub severity: IssueSeverity,
+    pub category: IssueCategory,
+    pub message: String,
+    pub fix: Option<String>,
+}
+
+/// A material as declared in the model's glTF JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelMaterial {
+    pub index: usize,
+    pub name: String,
+    /// RGBA `baseColorFactor`, if set
+    pub base_color: Option<[f64; 4]>,
+    pub has_texture: bool,
+    pub metallic: f64,
+    pub roughness: f64,
+}
+
+/// Result of checking one .glb file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelValidation {
+    /// Path under the model root, e.g. "router/cisco/asr-9000.glb"
+    pub path: String,
+    pub file_size_bytes: u64,
+    pub materials: Vec<ModelMaterial>,
+    /// Largest side of any position accessor's bounding box
+    pub max_dimension: f64,
+    pub issues: Vec<ModelIssue>,
+}
+
+impl ModelValidation {
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == IssueSeverity::Error)
+    }
+
+    pub fn has_warnings(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == IssueSeverity::Warning)
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        !self.has_errors() && !self.has_warnings()
+    }
+
+    /// The first error, as shown next to a broken model in the editor
+    pub fn first_error(&self) -> Option<&ModelIssue> {
+        self.issues.iter().find(|i| i.severity == IssueSeverity::Error)
+    }
+
+    fn issue(&mut self, severity: IssueSeverity, category: IssueCategory, message: String, fix: Option<String>) {
+        self.issues.push(ModelIssue { severity, category, message, fix });
+    }
+}
+
+/// Check a .glb file: glTF structure, that every material has a color or texture, and
+/// that the model's size is close to `NTB_IDEAL_SIZE`
+///
+/// Same checks and severities as `validate_models.py`.
+pub fn validate_glb(path: &str, bytes: &[u8]) -> ModelValidation {
+    let mut result = ModelValidation {
+        path: path.to_string(),
+        file_size_bytes: bytes.len() as u64,
+        materials: Vec::new(),
+        max_dimension: 0.0,
+        issues: Vec::new(),
+    };
+
+    let json = match parse_glb_json(bytes) {
+        Ok(json) => json,
+        Err(reason) => {
+            result.issue(
+                IssueSeverity::Error,
+                IssueCategory::Structure,
+                format!("Failed to parse GLB file - may be corrupted ({})", reason),
+                None,
+            );
+            return result;
+        }
+    };
+
+    check_materials(&json, &mut result);
+    check_size(&json, &mut result);
+    result
+}
+
+/// The JSON chunk of a binary glTF file
+fn parse_glb_json(bytes: &[u8]) -> Result<Value, String> {
+    let word = |offset: usize| -> Option<u32> {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    let magic = word(0).ok_or("file is shorter than the GLB header")?;
+    if magic != GLB_MAGIC {
+        return Err("not a binary glTF file".to_string());
+    }
+    let version = word(4).ok_or("file is shorter than the GLB header")?;
+    if version != 2 {
+        return Err(format!("glTF version {} (expected 2)", version));
+    }
+    let length = word(8).ok_or("file is shorter than the GLB header")? as usize;
+    if length > bytes.len() {
+        return Err(format!("header declares {} bytes but the file has {}", length, bytes.len()));
+    }
+
+    let chunk_length = word(12).ok_or("missing JSON chunk")? as usize;
+    if word(16) != Some(GLB_JSON_CHUNK) {
+        return Err("first chunk is not JSON".to_string());
+    }
+    let chunk = bytes
+        .get(20..20 + chunk_length)
+        .ok_or("JSON chunk runs past the end of the file")?;
+    serde_json::from_slice(chunk).map_err(|e| format!("invalid JSON chunk: {}", e))
+}
+
+fn check_materials(json: &Value, result: &mut ModelValidation) {
+    let materials = json.get("materials").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+    if materials.is_empty() {
+        result.issue(
+            IssueSeverity::Error,
+            IssueCategory::Material,
+            "No materials found in model".to_string(),
+            Some("In Blender: Ensure objects have materials assigned before export".to_string()),
+        );
+        return;
+    }
+
+    for (index, material) in materials.iter().enumerate() {
+        let name = material
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Material_{}", index));
+        let pbr = material.get("pbrMetallicRoughness");
+        let factor = |key: &str| pbr.and_then(|p| p.get(key)).and_then(Value::as_f64).unwrap_or(1.0);
+
+        let base_color = pbr
+            .and_then(|p| p.get("baseColorFactor"))
+            .and_then(Value::as_array)
+            .map(|c| {
+                let channel = |i: usize| c.get(i).and_then(Value::as_f64).unwrap_or(1.0);
+                [channel(0), channel(1), channel(2), channel(3)]
+            });
+        let has_texture = pbr.is_some_and(|p| p.get("baseColorTexture").is_some());
+
+        if base_color.is_none() && !has_texture {
+            result.issue(
+                IssueSeverity::Error,
+                IssueCategory::Material,
+                format!("Material \"{}\" has no color or texture", name),
+                Some(format!("In Blender: Select material \"{}\" → Set Base Color in Principled BSDF", name)),
+            );
+        }
+        result.materials.push(ModelMaterial {
+            index,
+            name,
+            base_color,
+            has_texture,
+            metallic: factor("metallicFactor"),
+            roughness: factor("roughnessFactor"),
+        });
+    }
+}
+
+fn check_size(json: &Value, result: &mut ModelValidation) {
+    let Some(accessors) = json.get("accessors").and_then(Value::as_array) else {
+        return;
+    };
+
+    let vector = |value: Option<&Value>| -> Option<[f64; 3]> {
+        let values = value?.as_array()?;
+        if values.len() != 3 {
+            return None;
+        }
+        Some([values[0].as_f64()?, values[1].as_f64()?, values[2].as_f64()?])
+    };
+    let max_dim = accessors
+        .iter()
+        .filter_map(|accessor| Some((vector(accessor.get("min"))?, vector(accessor.get("max"))?)))
+        .flat_map(|(min, max)| (0..3).map(move |axis| max[axis] - min[axis]))
+        .fold(0.0_f64, f64::max);
+    result.max_dimension = max_dim;
+
+    // Selection radius is calculated from the geometry, so any size works; these are
+    // recommendations for a consistent look
+    let scale_factor = if max_dim > 0.0 { NTB_IDEAL_SIZE / max_dim } else { 1.0 };
+    if max_dim > NTB_MAX_RECOMMENDED {
+        result.issue(
+            IssueSeverity::Warning,
+            IssueCategory::Scale,
+            format!("Model larger than recommended ({:.2} units > {} units)", max_dim, NTB_MAX_RECOMMENDED),
+            Some(format!("Recommended: Scale by {:.3} in Blender for better visual proportion", scale_factor)),
+        );
+    } else if max_dim > NTB_IDEAL_SIZE * 1.5 {
+        result.issue(
+            IssueSeverity::Info,
+            IssueCategory::Scale,
+            format!("Model slightly larger than ideal ({:.2} units)", max_dim),
+            Some(format!("Optional: Scale by {:.3} in Blender for ideal size", scale_factor)),
+        );
+    } else if max_dim < NTB_MIN_SIZE {
+        result.issue(
+            IssueSeverity::Warning,
+            IssueCategory::Scale,
+            format!("Model very small ({:.2} units) - may be hard to see", max_dim),
+            Some("Consider scaling up in Blender".to_string()),
+        );
+    }
+}
+
+/// SVG elements an icon may use: shapes, text, paint servers and grouping
+const ICON_ELEMENTS: &[&str] = &[
+    "svg", "g", "defs", "symbol", "use", "title", "desc", "style",
+    "path", "rect", "circle", "ellipse", "line", "polyline", "polygon",
+    "text", "tspan",
+    "linearGradient", "radialGradient", "stop", "pattern", "clipPath", "mask",
+];
+
+/// Attributes an icon may use: geometry, presentation and local references
+const ICON_ATTRIBUTES: &[&str] = &[
+    "id", "class", "style", "version", "viewBox", "preserveAspectRatio", "transform",
+    "x", "y", "x1", "y1", "x2", "y2", "cx", "cy", "r", "rx", "ry", "fx", "fy", "fr",
+    "width", "height", "d", "points", "dx", "dy", "rotate", "textLength", "lengthAdjust",
+    "fill", "fill-opacity", "fill-rule", "stroke", "stroke-width", "stroke-linecap",
+    "stroke-linejoin", "stroke-miterlimit", "stroke-dasharray", "stroke-dashoffset",
+    "stroke-opacity", "opacity", "color", "display", "visibility", "overflow",
+    "clip-path", "clip-rule", "mask", "vector-effect", "shape-rendering",
+    "font-family", "font-size", "font-weight", "font-style", "text-anchor",
+    "dominant-baseline", "letter-spacing",
+    "offset", "stop-color", "stop-opacity", "gradientUnits", "gradientTransform", "spreadMethod",
+    "patternUnits", "patternContentUnits", "patternTransform",
+    "clipPathUnits", "maskUnits", "maskContentUnits",
+    "href",
+];
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Check an uploaded vendor icon: a reasonably small SVG made only of inert drawing
+///
+/// Icons are served from the app's own origin, so the file is parsed and every element
+/// and attribute must be on an allowlist; links may only point inside the icon. A DTD
+/// (and with it any entity) is refused outright.
+pub fn check_vendor_icon(bytes: &[u8]) -> Result<(), String> {
+    if bytes.len() > MAX_ICON_BYTES {
+        return Err(format!("Icon is larger than {} KB", MAX_ICON_BYTES / 1024));
+    }
+    let text = std::str::from_utf8(bytes).map_err(|_| "Icon is not a text SVG file".to_string())?;
+    let document = roxmltree::Document::parse(text).map_err(|e| match e {
+        roxmltree::Error::DtdDetected => "Icon declares a DOCTYPE or entities, which aren't allowed".to_string(),
+        other => format!("Icon is not a valid SVG file: {}", other),
+    })?;
+    if document.root_element().tag_name().name() != "svg" {
+        return Err("Icon is not an SVG file".to_string());
+    }
+
+    for node in document.descendants() {
+        match node.node_type() {
+            roxmltree::NodeType::Element => check_icon_element(node)?,
+            roxmltree::NodeType::PI => return Err("Icon contains processing instructions, which aren't allowed".to_string()),
+            roxmltree::NodeType::Text if node.parent().is_some_and(|parent| parent.has_tag_name("style")) => {
+                check_local_urls(node.text().unwrap_or_default())?
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// One element of an icon and its attributes against the allowlists
+fn check_icon_element(node: roxmltree::Node) -> Result<(), String> {
+    let tag = node.tag_name();
+    if !matches!(tag.namespace(), None | Some(SVG_NAMESPACE)) || !ICON_ELEMENTS.contains(&tag.name()) {
+        return Err(format!("Icon element <{}> isn't allowed", tag.name()));
+    }
+    for attribute in node.attributes() {
+        let allowed = match attribute.namespace() {
+            None => ICON_ATTRIBUTES.contains(&attribute.name()),
+            Some(XLINK_NAMESPACE) => attribute.name() == "href",
+            Some(XML_NAMESPACE) => attribute.name() == "space",
+            Some(_) => false,
+        };
+        if !allowed {
+            return Err(format!("Icon attribute '{}' on <{}> isn't allowed", attribute.name(), tag.name()));
+        }
+        if attribute.name() == "href" && !attribute.value().starts_with('#') {
+            return Err(format!("Icon links outside itself (<{} href=\"{}\">)", tag.name(), attribute.value()));
+        }
+        check_local_urls(attribute.value())?;
+    }
+    Ok(())
+}
+
+/// Every `url(...)` in an attribute or stylesheet must point inside the icon (`url(#id)`)
+fn check_local_urls(value: &str) -> Result<(), String> {
+    let lower = value.to_ascii_lowercase();
+    for (at, _) in lower.match_indices("url(") {
+        let target = lower[at + 4..].trim_start().trim_start_matches(['"', '\'']);
+        if !target.starts_with('#') {
+            return Err("Icon references something outside itself with url(...)".to_string());
+        }
+    }
+    if lower.contains("@import") {
+        return Err("Icon stylesheets can't use @import".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A GLB file holding just a JSON chunk
+    fn glb(json: &str) -> Vec<u8> {
+        let mut chunk = json.as_bytes().to_vec();
+        while !chunk.len().is_multiple_of(4) {
+            chunk.push(b' ');
+        }
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&((20 + chunk.len()) as u32).to_le_bytes());
+        bytes.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&GLB_JSON_CHUNK.to_le_bytes());
+        bytes.extend_from_slice(&chunk);
+        bytes
+    }
+
+    /// A model with one colored material and a bounding box `size` units across
+    fn model(size: f64) -> Vec<u8> {
+        glb(&format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "materials": [{{ "name": "Body", "pbrMetallicRoughness": {{ "baseColorFactor": [0.2, 0.4, 0.6, 1.0], "roughnessFactor": 0.5 }} }}],
+                "accessors": [{{ "min": [0, 0, 0], "max": [{size}, 0.5, 0.25] }}]
+            }}"#
+        ))
+    }
+
+    fn structure_error(bytes: &[u8]) -> String {
+        let result = validate_glb("test.glb", bytes);
+        assert_eq!(result.issues.len(), 1, "{:?}", result.issues);
+        let issue = &result.issues[0];
+        assert_eq!((issue.severity, issue.category), (IssueSeverity::Error, IssueCategory::Structure));
+        issue.message.clone()
+    }
+
+    #[test]
+    fn healthy_model() {
+        let bytes = model(1.0);
+        let result = validate_glb("router/generic/box.glb", &bytes);
+        assert!(result.is_healthy(), "{:?}", result.issues);
+        assert_eq!(result.file_size_bytes, bytes.len() as u64);
+        assert_eq!(result.max_dimension, 1.0);
+        assert_eq!(
+            result.materials,
+            vec![ModelMaterial {
+                index: 0,
+                name: "Body".to_string(),
+                base_color: Some([0.2, 0.4, 0.6, 1.0]),
+                has_texture: false,
+                metallic: 1.0,
+                roughness: 0.5,
+            }]
+        );
+    }
+
+    #[test]
+    fn rejects_broken_headers() {
+        let valid = model(1.0);
+
+        assert!(structure_error(&valid[..10]).contains("shorter than the GLB header"));
+        assert!(structure_error(b"PK\x03\x04 definitely a zip file").contains("not a binary glTF file"));
+
+        let mut version_one = valid.clone();
+        version_one[4..8].copy_from_slice(&1u32.to_le_bytes());
+        assert!(structure_error(&version_one).contains("glTF version 1 (expected 2)"));
+
+        let mut too_long = valid.clone();
+        too_long[8..12].copy_from_slice(&((valid.len() + 1) as u32).to_le_bytes());
+        assert!(structure_error(&too_long).contains("header declares"));
+
+        let mut binary_first = valid.clone();
+        binary_first[16..20].copy_from_slice(&0x004E_4942u32.to_le_bytes()); // "BIN\0"
+        assert!(structure_error(&binary_first).contains("first chunk is not JSON"));
+
+        let mut chunk_past_end = valid.clone();
+        chunk_past_end[12..16].copy_from_slice(&(valid.len() as u32).to_le_bytes());
+        assert!(structure_error(&chunk_past_end).contains("runs past the end"));
+
+        assert!(structure_error(&glb("{ not json")).contains("invalid JSON chunk"));
+    }
+
+    #[test]
+    fn materials_need_a_color_or_texture() {
+        let result = validate_glb("m.glb", &glb(r#"{ "asset": { "version": "2.0" } }"#));
+        assert!(result.has_errors());
+        assert_eq!(result.first_error().unwrap().message, "No materials found in model");
+
+        let result = validate_glb(
+            "m.glb",
+            &glb(r#"{ "materials": [
+                { "pbrMetallicRoughness": { "baseColorTexture": { "index": 0 } } },
+                { "pbrMetallicRoughness": { "metallicFactor": 0.0 } }
+            ] }"#),
+        );
+        assert_eq!(result.materials.len(), 2);
+        assert!(result.materials[0].has_texture);
+        assert_eq!(result.materials[1].name, "Material_1");
+        let errors: Vec<&str> = result
+            .issues
+            .iter()
+            .filter(|issue| issue.severity == IssueSeverity::Error)
+            .map(|issue| issue.message.as_str())
+            .collect();
+        assert_eq!(errors, vec!["Material \"Material_1\" has no color or texture"]);
+    }
+
+    #[test]
+    fn size_recommendations() {
+        let scale_issue = |size: f64| {
+            let result = validate_glb("m.glb", &model(size));
+            assert_eq!(result.max_dimension, size);
+            result
+                .issues
+                .iter()
+                .find(|issue| issue.category == IssueCategory::Scale)
+                .map(|issue| issue.severity)
+        };
+        assert_eq!(scale_issue(3.0), Some(IssueSeverity::Warning));
+        assert_eq!(scale_issue(1.75), Some(IssueSeverity::Info));
+        assert_eq!(scale_issue(1.5), None);
+        assert_eq!(scale_issue(0.5), None);
+        // `model` keeps the other sides at 0.5, so a tiny model needs a box of its own
+        let tiny = glb(r#"{ "materials": [{ "pbrMetallicRoughness": { "baseColorFactor": [1, 1, 1, 1] } }],
+                            "accessors": [{ "min": [0, 0, 0], "max": [0.1, 0.1, 0.1] }] }"#);
+        let result = validate_glb("m.glb", &tiny);
+        assert!(result.has_warnings());
+        assert!(result.issues[0].message.starts_with("Model very small"));
+    }
+
+    #[test]
+    fn vendor_icons() {
+        assert_eq!(check_vendor_icon(br#"<svg xmlns="http://www.w3.org/2000/svg"><g id="icon" font-family="x"/></svg>"#), Ok(()));
+        assert!(check_vendor_icon(b"<svg><script>alert(1)</script></svg>").is_err());
+        assert!(check_vendor_icon(br#"<svg onload="alert(1)"></svg>"#).is_err());
+        assert!(check_vendor_icon(br#"<svg/onload="alert(1)"></svg>"#).is_err());
+        assert!(check_vendor_icon(br#"<svg><a href="javascript:alert(1)"/></svg>"#).is_err());
+        assert!(check_vendor_icon(b"<svg><foreignObject/></svg>").is_err());
+        assert!(check_vendor_icon(b"<?xml-stylesheet href=\"x.css\"?><svg/>").is_err());
+        assert!(check_vendor_icon(br#"<svg><rect fill="url(https://example.com/x)"/></svg>"#).is_err());
+        assert!(check_vendor_icon(b"<svg><style>@import 'x.css';</style></svg>").is_err());
+        assert!(check_vendor_icon(b"<svg xmlns:ev=\"urn:x\"><g ev:event=\"x\"/></svg>").is_err());
+    }
+
+    #[test]
+    fn vendor_icons_survive_parser_tricks() {
+        // Entity-encoded scheme: the parser decodes it, so the href is not local
+        assert!(check_vendor_icon(br#"<svg><use href="javascript&#58;alert(1)"/></svg>"#).is_err());
+        // Internal DTD entity expanding to a script element
+        let entity = br#"<!DOCTYPE svg [<!ENTITY s "<script>alert(1)</script>">]><svg>&s;</svg>"#;
+        assert_eq!(
+            check_vendor_icon(entity),
+            Err("Icon declares a DOCTYPE or entities, which aren't allowed".to_string())
+        );
+        assert!(check_vendor_icon(br#"<svg><use href="data:image/svg+xml,&lt;svg onload=alert(1)&gt;"/></svg>"#).is_err());
+        assert!(check_vendor_icon(br#"<svg xmlns:xlink="http://www.w3.org/1999/xlink"><use xlink:href="data:x"/></svg>"#).is_err());
+
+        // Local references are fine
+        let local = br##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+            <defs><linearGradient id="g"><stop offset="0" stop-color="#fff"/></linearGradient><path id="p" d="M0 0h1"/></defs>
+            <use xlink:href="#p" fill="url(#g)"/><style>.a { fill: url('#g'); }</style>
+        </svg>"##;
+        assert_eq!(check_vendor_icon(local), Ok(()));
+        assert!(check_vendor_icon(b"\x89PNG\r\n").is_err());
+        assert!(check_vendor_icon(b"<html></html>").is_err());
+
+        let mut large = b"<svg>".to_vec();
+        large.resize(MAX_ICON_BYTES + 1, b' ');
+        assert!(check_vendor_icon(&large).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::Rgb;
+
 #[cfg(feature = "ssr")]
 use sqlx::FromRow;
 
@@ -10,7 +12,7 @@ pub struct Node {
     pub id: i64,
     pub topology_id: i64,
     pub name: String,
-    pub node_type: String,
+    pub node_type: NodeType,
     pub vendor: String, // Vendor name (e.g., "cisco", "versa", "generic")
     pub model_name: String, // Model file name without extension (e.g., "asr9000", "blob-router")
     pub ip_address: Option<String>,
@@ -21,11 +23,15 @@ pub struct Node {
     pub rotation_y: f64,
     pub rotation_z: f64,
     pub scale: f64,
-    pub color: String, // RGB format: "R,G,B"
+    pub color: Rgb,
     pub visible: bool,  // Whether node is visible in viewport (Blender-style outliner)
+    #[serde(default)]
+    pub group_id: Option<i64>, // Site/building/rack/zone the node belongs to
     pub metadata: Option<String>, // JSON string
     pub created_at: i64,
     pub updated_at: i64,
+    #[serde(default)]
+    pub version: i64, // Bumped on every write; see UpdateNode::expected_version
 }
 
 /// Data transfer object for creating a new node
@@ -33,7 +39,7 @@ pub struct Node {
 pub struct CreateNode {
     pub topology_id: i64,
     pub name: String,
-    pub node_type: String,
+    pub node_type: NodeType,
     pub vendor: Option<String>,
     pub model_name: Option<String>,
     pub ip_address: Option<String>,
@@ -44,16 +50,20 @@ pub struct CreateNode {
     pub rotation_y: Option<f64>,
     pub rotation_z: Option<f64>,
     pub scale: Option<f64>,
-    pub color: Option<String>,
+    pub color: Option<Rgb>,
     pub visible: Option<bool>,
+    #[serde(default)]
+    pub group_id: Option<i64>,
     pub metadata: Option<String>,
 }
 
 /// Data transfer object for updating a node
+///
+/// `group_id: Some(0)` takes the node out of its group.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct UpdateNode {
     pub name: Option<String>,
-    pub node_type: Option<String>,
+    pub node_type: Option<NodeType>,
     pub vendor: Option<String>,
     pub model_name: Option<String>,
     pub ip_address: Option<String>,
@@ -64,19 +74,53 @@ pub struct UpdateNode {
     pub rotation_y: Option<f64>,
     pub rotation_z: Option<f64>,
     pub scale: Option<f64>,
-    pub color: Option<String>,
+    pub color: Option<Rgb>,
     pub visible: Option<bool>,
+    pub group_id: Option<i64>,
     pub metadata: Option<String>,
+    pub expected_version: Option<i64>, // Reject with a conflict unless the node's version still matches
+}
+
+impl UpdateNode {
+    /// Whether the update sets no fields at all (`expected_version` alone changes nothing)
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.node_type.is_none()
+            && self.vendor.is_none()
+            && self.model_name.is_none()
+            && self.ip_address.is_none()
+            && self.position_x.is_none()
+            && self.position_y.is_none()
+            && self.position_z.is_none()
+            && self.rotation_x.is_none()
+            && self.rotation_y.is_none()
+            && self.rotation_z.is_none()
+            && self.scale.is_none()
+            && self.color.is_none()
+            && self.visible.is_none()
+            && self.group_id.is_none()
+            && self.metadata.is_none()
+    }
+}
+
+text_enum! {
+    /// Kind of network device a node represents (also the models/ folder name)
+    pub enum NodeType("node type") {
+        #[default]
+        Host = "host" | "client" => "Host",
+        Router = "router" => "Router",
+        Switch = "switch" => "Switch",
+        Server = "server" => "Server",
+        Firewall = "firewall" => "Firewall",
+        LoadBalancer = "load_balancer" | "loadbalancer" | "lb" => "Load Balancer",
+        Cloud = "cloud" => "Cloud",
+        Application = "application" | "app" => "Application",
+    }
 }
 
-/// Common node types as constants
-pub mod node_types {
-    pub const HOST: &str = "host";
-    pub const ROUTER: &str = "router";
-    pub const SWITCH: &str = "switch";
-    pub const FIREWALL: &str = "firewall";
-    pub const LOAD_BALANCER: &str = "load_balancer";
-    pub const SERVER: &str = "server";
-    pub const CLOUD: &str = "cloud";
-    pub const APPLICATION: &str = "application";
+impl NodeType {
+    /// Whether the device routes between subnets
+    pub fn is_layer3(self) -> bool {
+        matches!(self, NodeType::Router | NodeType::Firewall)
+    }
 }
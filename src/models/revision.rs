@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ssr")]
+use sqlx::FromRow;
+
+use super::TopologyFull;
+
+/// A named snapshot of a topology (the snapshot itself is loaded on demand)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct TopologyRevision {
+    pub id: i64,
+    pub topology_id: i64,
+    pub name: String,
+    pub note: Option<String>,
+    pub node_count: i64,
+    pub connection_count: i64,
+    pub restored_from: Option<i64>, // Set on revisions created by a restore
+    pub created_at: i64,
+}
+
+/// Data transfer object for creating a revision of a topology's current state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRevision {
+    pub topology_id: i64,
+    pub name: String,
+    pub note: Option<String>,
+}
+
+/// A revision together with the topology state it captured
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionPreview {
+    pub revision: TopologyRevision,
+    pub snapshot: TopologyFull,
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ssr")]
+use sqlx::FromRow;
+
+/// Most hits a search returns
+pub const MAX_SEARCH_RESULTS: i64 = 50;
+
+text_enum! {
+    /// What a search hit refers to
+    pub enum SearchKind("search result kind") {
+        #[default]
+        Topology = "topology" => "Topology",
+        Node = "node" => "Node",
+        Connection = "connection" => "Connection",
+    }
+}
+
+/// One match from the global search, best first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct SearchHit {
+    pub kind: SearchKind,
+    pub item_id: i64, // Topology, node or connection id, depending on `kind`
+    pub topology_id: i64,
+    pub topology_name: String,
+    pub title: String,   // Node name, "source ↔ target" for connections
+    pub snippet: String, // Matching text with the matched terms in [brackets]
+}
+
+/// Turn what the user typed into an FTS5 query: every word must match, as a prefix
+///
+/// Words are quoted so FTS5 operators and punctuation ("10.0.0.1", "core-sw") are taken
+/// literally. Returns None when nothing searchable is left.
+pub fn fts_match_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| format!("\"{}\"*", word))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// The PostgreSQL counterpart of `fts_match_query`: a `to_tsquery` string matching
+/// every word as a prefix
+pub fn tsquery(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|word| word.replace(['\\', '\''], ""))
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| format!("'{}':*", word))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" & "))
+}
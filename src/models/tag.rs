@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ssr")]
+use sqlx::FromRow;
+
+/// A free-form label shared by the nodes and connections of one topology
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct Tag {
+    pub id: i64,
+    pub topology_id: i64,
+    pub name: String, // Unique per topology, case-insensitively
+    pub created_at: i64,
+}
+
+/// A tag attached to a node or a connection (exactly one of the two is set)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct TagAssignment {
+    pub tag_id: i64,
+    pub node_id: Option<i64>,
+    pub connection_id: Option<i64>,
+}
+
+/// Longest tag name accepted
+pub const MAX_TAG_LEN: usize = 64;
+
+/// Validate a tag name, returning it trimmed
+///
+/// Commas separate tags in the editor, and double quotes delimit values in filter queries.
+pub fn normalize_tag_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Tag name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_TAG_LEN {
+        return Err(format!("Tag '{}' is longer than {} characters", name, MAX_TAG_LEN));
+    }
+    if name.contains([',', '"']) {
+        return Err(format!("Tag '{}' cannot contain commas or double quotes", name));
+    }
+    Ok(name.to_string())
+}
+
+/// Split a comma-separated tag list ("core, dc1") into distinct names, keeping the first spelling
+pub fn parse_tag_list(list: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in list.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        if !names.iter().any(|existing| existing.eq_ignore_ascii_case(name)) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
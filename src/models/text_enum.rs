@@ -0,0 +1,135 @@
+use std::fmt;
+
+/// A string that doesn't name any variant of a text-backed enum
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownVariant {
+    pub kind: &'static str,
+    pub value: String,
+}
+
+impl fmt::Display for UnknownVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown {} '{}'", self.kind, self.value)
+    }
+}
+
+impl std::error::Error for UnknownVariant {}
+
+/// Canonicalize a raw value before matching (" Load-Balancer " -> "load_balancer")
+pub fn normalize(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '-' | ' ' => '_',
+            c => c.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+/// Define an enum stored as lowercase TEXT in the database and as a plain string in JSON
+///
+/// Each variant lists its canonical value, any legacy aliases (already normalized)
+/// and a UI label. Parsing is case-insensitive and accepts the aliases, so rows and
+/// documents written before the enums existed still load; the canonical value is
+/// always written back.
+macro_rules! text_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident($kind:literal) {
+            $(
+                $(#[$vmeta:meta])*
+                $variant:ident = $value:literal $(| $alias:literal)* => $label:literal,
+            )+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum $name {
+            $( $(#[$vmeta])* $variant, )+
+        }
+
+        impl $name {
+            /// Every variant, in display order
+            pub const ALL: &'static [$name] = &[$($name::$variant),+];
+
+            /// Canonical value stored in the database
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $($name::$variant => $value,)+
+                }
+            }
+
+            /// Human-readable name for the UI
+            pub fn label(self) -> &'static str {
+                match self {
+                    $($name::$variant => $label,)+
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $crate::models::text_enum::UnknownVariant;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                match $crate::models::text_enum::normalize(value).as_str() {
+                    $($value $(| $alias)* => Ok($name::$variant),)+
+                    _ => Err($crate::models::text_enum::UnknownVariant {
+                        kind: $kind,
+                        value: value.to_string(),
+                    }),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                value.parse().map_err(serde::de::Error::custom)
+            }
+        }
+
+        #[cfg(feature = "ssr")]
+        impl sqlx::Type<$crate::db::Db> for $name {
+            fn type_info() -> <$crate::db::Db as sqlx::Database>::TypeInfo {
+                <str as sqlx::Type<$crate::db::Db>>::type_info()
+            }
+
+            fn compatible(ty: &<$crate::db::Db as sqlx::Database>::TypeInfo) -> bool {
+                <str as sqlx::Type<$crate::db::Db>>::compatible(ty)
+            }
+        }
+
+        #[cfg(feature = "ssr")]
+        impl<'q> sqlx::Encode<'q, $crate::db::Db> for $name {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <$crate::db::Db as sqlx::database::HasArguments<'q>>::ArgumentBuffer,
+            ) -> sqlx::encode::IsNull {
+                <&str as sqlx::Encode<'q, $crate::db::Db>>::encode(self.as_str(), buf)
+            }
+        }
+
+        #[cfg(feature = "ssr")]
+        impl<'r> sqlx::Decode<'r, $crate::db::Db> for $name {
+            fn decode(
+                value: <$crate::db::Db as sqlx::database::HasValueRef<'r>>::ValueRef,
+            ) -> Result<Self, sqlx::error::BoxDynError> {
+                let value = <&str as sqlx::Decode<'r, $crate::db::Db>>::decode(value)?;
+                Ok(value.parse()?)
+            }
+        }
+    };
+}
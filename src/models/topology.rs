@@ -28,10 +28,54 @@ pub struct UpdateTopology {
     pub description: Option<String>,
 }
 
-/// Complete topology data with nodes and connections
+/// Complete topology data with nodes, their interfaces, connections, subnets, groups and tags
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TopologyFull {
     pub topology: Topology,
     pub nodes: Vec<super::Node>,
     pub connections: Vec<super::Connection>,
+    #[serde(default)]
+    pub interfaces: Vec<super::Interface>,
+    #[serde(default)]
+    pub subnets: Vec<super::Subnet>,
+    #[serde(default)]
+    pub groups: Vec<super::NodeGroup>,
+    #[serde(default)]
+    pub tags: Vec<super::Tag>,
+    #[serde(default)]
+    pub tag_assignments: Vec<super::TagAssignment>,
+}
+
+/// Outcome of a server-side topology import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub topology: Topology,
+    pub nodes_created: usize,
+    pub connections_created: usize,
+    pub interfaces_created: usize,
+    pub subnets_created: usize,
+    pub groups_created: usize,
+    pub tags_created: usize,
+    pub node_id_map: Vec<(i64, i64)>, // (ID in the imported document, newly assigned ID)
+    pub rejected: Vec<ImportRejection>,
+    pub warnings: Vec<ImportWarning>, // Values that were replaced to import an entity
+    pub source_format_version: u32, // Format version of the file before migration
+    pub view_settings: Option<super::ViewSettings>,
+    pub camera: Option<super::CameraView>,
+}
+
+/// An entity from an imported document that was skipped, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRejection {
+    pub entity_type: String, // 'node', 'interface', 'connection', 'subnet', 'group' or 'tag'
+    pub source_id: i64,      // ID as it appeared in the imported document
+    pub reason: String,
+}
+
+/// An entity from an imported document that was imported with a value replaced, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportWarning {
+    pub entity_type: String, // 'node', 'interface', 'connection' or 'group'
+    pub source_id: i64,      // ID as it appeared in the imported document
+    pub message: String,
 }
@@ -18,6 +18,7 @@ pub struct UISettings {
     pub rim_light_intensity: f64,
     pub use_environment_lighting: bool,
     pub environment_map: String,
+    pub undo_depth: i64, // Undoable change sets kept per topology (0 = unlimited)
     pub created_at: String,
     pub updated_at: String,
 }
@@ -35,4 +36,5 @@ pub struct UpdateUISettings {
     pub rim_light_intensity: Option<f64>,
     pub use_environment_lighting: Option<bool>,
     pub environment_map: Option<String>,
+    pub undo_depth: Option<i64>,
 }
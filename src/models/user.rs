@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ssr")]
+use sqlx::FromRow;
+
+/// Shortest password accepted for a new account
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// A local user account (the password hash never leaves the server)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    pub created_at: i64,
+    #[serde(default)]
+    pub is_admin: bool, // May change server-wide settings and manage uploaded models
+}
+
+/// Credentials for registering or signing in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+text_enum! {
+    /// What a user may do with a topology; each role includes the ones before it
+    pub enum Role("role") {
+        #[default]
+        Viewer = "viewer" => "Viewer",
+        Editor = "editor" => "Editor",
+        Owner = "owner" => "Owner",
+    }
+}
+
+/// A user's role on a topology
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct TopologyPermission {
+    pub topology_id: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub role: Role,
+}
+
+/// A bearer token for scripts (the secret itself is only shown once, at creation)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ssr", derive(FromRow))]
+pub struct ApiToken {
+    pub id: i64,
+    pub name: String,
+    pub token_prefix: String, // e.g. "ntb_3f9a1c"
+    pub created_at: i64,
+    pub last_used_at: Option<i64>,
+}
+
+/// A freshly created API token together with its secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewApiToken {
+    pub token: ApiToken,
+    pub secret: String, // Send as `Authorization: Bearer <secret>`
+}
+
+/// Check a new account's username and password
+pub fn validate_credentials(credentials: &Credentials) -> Result<(), String> {
+    let username = credentials.username.trim();
+    if username.is_empty() || username.len() > 64 {
+        return Err("Username must be 1-64 characters".to_string());
+    }
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '@'))
+    {
+        return Err("Username may only contain letters, digits and . _ - @".to_string());
+    }
+    if credentials.password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(format!("Password must be at least {} characters", MIN_PASSWORD_LENGTH));
+    }
+    Ok(())
+}
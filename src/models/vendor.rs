@@ -1,4 +1,10 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{ModelIssue, NodeType};
+
+/// Longest vendor or model slug accepted for an upload
+pub const MAX_SLUG_LEN: usize = 64;
 
 /// Represents a vendor with available models
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,15 +17,213 @@ pub struct VendorInfo {
 }
 
 /// Represents a 3D model file
+///
+/// Everything but the names comes from the vendor folder's manifest (see
+/// `VendorManifest`); models without an entry get the defaults.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelInfo {
     pub file_name: String, // e.g., "blob-router.glb"
     pub display_name: String, // e.g., "Blob Router" or "ASR 9000"
+    /// Why the model can't be used (its first validation error), None if it loads fine
+    #[serde(default)]
+    pub problem: Option<String>,
+    /// Scale of new nodes using this model
+    #[serde(default = "default_model_scale")]
+    pub default_scale: f64,
+    /// Rotation (x, y, z in degrees) of new nodes using this model
+    #[serde(default)]
+    pub default_rotation: [f64; 3],
+    /// Radius of the model's bounding sphere at scale 1, if the manifest states it
+    #[serde(default)]
+    pub bounding_radius: Option<f64>,
+    /// Named points on the model (ports, cable anchors), in model coordinates
+    #[serde(default)]
+    pub anchors: Vec<ModelAnchor>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub license: Option<String>,
+    /// Stored in `models.upload_dir`, so it can be replaced or deleted from the editor
+    #[serde(default)]
+    pub uploaded: bool,
+}
+
+impl ModelInfo {
+    /// A model known only by its file name: display name derived from it, default placement
+    pub fn from_file_name(file_name: &str) -> Self {
+        Self {
+            file_name: file_name.to_string(),
+            display_name: display_name_from_slug(file_name),
+            problem: None,
+            default_scale: default_model_scale(),
+            default_rotation: [0.0; 3],
+            bounding_radius: None,
+            anchors: Vec::new(),
+            tags: Vec::new(),
+            license: None,
+            uploaded: false,
+        }
+    }
+}
+
+fn default_model_scale() -> f64 {
+    1.0
+}
+
+/// A named point on a model, e.g. a port where connections attach
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelAnchor {
+    pub name: String,
+    /// x, y, z in model coordinates (before the node's scale and rotation)
+    pub position: [f64; 3],
+}
+
+/// `models.toml` / `models.json` in a vendor folder, describing the folder's models
+///
+/// ```toml
+/// display_name = "Cisco"
+/// license = "CC-BY-4.0"        # default for the models below
+///
+/// [models.asr-9000]            # asr-9000.glb
+/// display_name = "ASR 9000"
+/// scale = 0.8
+/// rotation = [0.0, 0.0, 90.0]
+/// bounding_radius = 0.75
+/// tags = ["edge", "chassis"]
+/// anchors = [{ name = "ge-0/0/0", position = [0.4, 0.0, 0.1] }]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VendorManifest {
+    pub display_name: Option<String>,
+    pub license: Option<String>,
+    /// Models by file name without `.glb`
+    pub models: BTreeMap<String, ModelManifest>,
+}
+
+/// One model's entry in a `VendorManifest`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelManifest {
+    pub display_name: Option<String>,
+    pub scale: Option<f64>,
+    pub rotation: Option<[f64; 3]>,
+    pub bounding_radius: Option<f64>,
+    pub anchors: Vec<ModelAnchor>,
+    pub tags: Vec<String>,
+    pub license: Option<String>,
+}
+
+impl VendorManifest {
+    /// A model's info with this manifest's entry (and vendor defaults) applied
+    pub fn model_info(&self, file_name: &str) -> ModelInfo {
+        let mut info = ModelInfo::from_file_name(file_name);
+        info.license = self.license.clone();
+        if let Some(entry) = self.models.get(file_name) {
+            if let Some(display_name) = &entry.display_name {
+                info.display_name = display_name.clone();
+            }
+            info.default_scale = entry.scale.unwrap_or(info.default_scale);
+            info.default_rotation = entry.rotation.unwrap_or(info.default_rotation);
+            info.bounding_radius = entry.bounding_radius;
+            info.anchors = entry.anchors.clone();
+            info.tags = entry.tags.clone();
+            info.license = entry.license.clone().or(info.license);
+        }
+        info
+    }
+
+    /// Problems a manifest can have beyond its syntax
+    pub fn validate(&self) -> Result<(), String> {
+        for (file_name, entry) in &self.models {
+            if let Some(scale) = entry.scale {
+                if !(scale.is_finite() && scale > 0.0) {
+                    return Err(format!("{}: scale must be a positive number", file_name));
+                }
+            }
+            if let Some(radius) = entry.bounding_radius {
+                if !(radius.is_finite() && radius > 0.0) {
+                    return Err(format!("{}: bounding_radius must be a positive number", file_name));
+                }
+            }
+            if entry.rotation.is_some_and(|r| r.iter().any(|angle| !angle.is_finite())) {
+                return Err(format!("{}: rotation must be three numbers", file_name));
+            }
+            if let Some(anchor) = entry.anchors.iter().find(|a| a.name.trim().is_empty()) {
+                return Err(format!("{}: anchor at {:?} has no name", file_name, anchor.position));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// "asr-9000" / "blob_router" -> "Asr 9000" / "Blob Router"
+pub fn display_name_from_slug(slug: &str) -> String {
+    slug.replace(['-', '_'], " ")
+        .split_whitespace()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                None => String::new(),
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Check a vendor or model name given for an upload: 1 to `MAX_SLUG_LEN` lowercase
+/// letters, digits, '-' and '_', starting with a letter or digit
+///
+/// `what` names the field in the error, e.g. "Vendor".
+pub fn check_model_slug(what: &str, slug: &str) -> Result<(), String> {
+    if slug.is_empty() {
+        return Err(format!("{} is required", what));
+    }
+    if slug.len() > MAX_SLUG_LEN {
+        return Err(format!("{} must be at most {} characters", what, MAX_SLUG_LEN));
+    }
+    let valid_char = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_';
+    if !slug.chars().all(valid_char) || !slug.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        return Err(format!(
+            "{} '{}' may only contain lowercase letters, digits, '-' and '_', and must start with a letter or digit",
+            what, slug
+        ));
+    }
+    Ok(())
+}
+
+/// A model (and optionally its vendor's icon) uploaded by a user
+#[derive(Debug, Clone)]
+pub struct ModelUpload {
+    pub node_type: NodeType,
+    /// Vendor folder, e.g. "cisco"; a new vendor is created if needed
+    pub vendor: String,
+    /// File name without `.glb`, e.g. "asr-9000"
+    pub name: String,
+    /// The .glb file
+    pub model: Vec<u8>,
+    /// SVG replacing the vendor's uploaded icon
+    pub icon: Option<Vec<u8>>,
+    /// Overwrite an uploaded model of the same name instead of rejecting the upload
+    pub replace: bool,
+}
+
+/// Result of a successful upload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelUploadReport {
+    pub node_type: NodeType,
+    pub vendor: String,
+    pub model: ModelInfo,
+    /// Validation warnings and notes; uploads with errors are rejected
+    pub issues: Vec<ModelIssue>,
+    /// Whether an earlier upload was overwritten
+    pub replaced: bool,
 }
 
 /// Response containing all vendors for a specific node type
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VendorListResponse {
-    pub node_type: String,
+    pub node_type: NodeType,
     pub vendors: Vec<VendorInfo>,
 }
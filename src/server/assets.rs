@@ -0,0 +1,68 @@
+//! Static files that may live outside the site root
+//!
+//! `/models/...` is looked up in the site root's `models/` first, then in each of the
+//! configured `models.search_paths`, so dropping a vendor folder into any of them makes
+//! its models loadable (and listed by `get_vendors_for_type`). Uploaded models live in
+//! `models.upload_dir`, which is searched right after the site root, and their vendor
+//! icons are served from it under `/icons/vendors/...`.
+
+use crate::config::NtbConfig;
+use axum::body::Body;
+use axum::extract::{Extension, Path, Request, State};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use leptos::prelude::LeptosOptions;
+use std::path::{Component, PathBuf};
+use std::sync::Arc;
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
+
+/// GET /models/{*path} - the first model root holding the file wins
+pub async fn model_file(
+    State(options): State<LeptosOptions>,
+    Extension(config): Extension<Arc<NtbConfig>>,
+    Path(path): Path<String>,
+    request: Request<Body>,
+) -> Response {
+    serve_first(config.model_roots(&options.site_root), &path, request).await
+}
+
+/// GET /icons/vendors/{file} - the site root's icons, then uploaded ones
+///
+/// Icons are uploaded by users but served from the app's own origin, so they're locked
+/// down to inert images even when opened directly.
+pub async fn vendor_icon(
+    State(options): State<LeptosOptions>,
+    Extension(config): Extension<Arc<NtbConfig>>,
+    Path(file): Path<String>,
+    request: Request<Body>,
+) -> Response {
+    let mut response = serve_first(config.icon_dirs(&options.site_root), &file, request).await;
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static("default-src 'none'; style-src 'unsafe-inline'"),
+    );
+    headers.insert(header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    response
+}
+
+/// Serve `path` from the first directory that has it
+async fn serve_first(dirs: Vec<PathBuf>, path: &str, request: Request<Body>) -> Response {
+    let relative = std::path::Path::new(path);
+    if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let found = dirs
+        .into_iter()
+        .map(|dir| dir.join(relative))
+        .find(|file| file.is_file());
+    match found {
+        Some(file) => match ServeFile::new(file).oneshot(request).await {
+            Ok(response) => response.into_response(),
+            Err(never) => match never {},
+        },
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
@@ -0,0 +1,16 @@
+use super::{run_server_fn, ApiQuery, ApiResult};
+use crate::api;
+use crate::models::{AuditFilter, AuditLogEntry};
+use axum::http::request::Parts;
+use axum::Json;
+
+/// GET /audit-log - audit log entries visible to the caller, newest first
+///
+/// Query parameters narrow the result: `topology_id`, `actor`, `entity_type`, `action`,
+/// `since` and `until` (Unix seconds).
+pub async fn list_audit_log(
+    parts: Parts,
+    ApiQuery(filter): ApiQuery<AuditFilter>,
+) -> ApiResult<Json<Vec<AuditLogEntry>>> {
+    run_server_fn(parts, || api::get_audit_log(filter)).await.map(Json)
+}
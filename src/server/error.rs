@@ -0,0 +1,134 @@
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{FromRequest, FromRequestParts, Query, Request};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use crate::models::{AppError, ErrorKind, UpdateError};
+use leptos::prelude::ServerFnError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// An error response: an HTTP status with a JSON body `{"error": "..."}`
+///
+/// Update conflicts also include the row as it is now, under `"current"`.
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+    pub current: Option<serde_json::Value>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+            current: None,
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, message)
+    }
+}
+
+impl From<AppError> for ApiError {
+    fn from(error: AppError) -> Self {
+        let status = match error.kind {
+            ErrorKind::Unauthenticated => StatusCode::UNAUTHORIZED,
+            ErrorKind::Forbidden => StatusCode::FORBIDDEN,
+            ErrorKind::NotFound => StatusCode::NOT_FOUND,
+            ErrorKind::Conflict => StatusCode::CONFLICT,
+            ErrorKind::Invalid => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorKind::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self::new(status, error.message)
+    }
+}
+
+impl From<ServerFnError> for ApiError {
+    /// Failures outside the server functions' own checks are internal errors
+    fn from(error: ServerFnError) -> Self {
+        Self::from(AppError::from(error))
+    }
+}
+
+impl<T: Serialize> From<UpdateError<T>> for ApiError {
+    /// Conflicts are 409 with the current state attached; other failures map like any
+    /// server function error.
+    fn from(error: UpdateError<T>) -> Self {
+        match error {
+            UpdateError::Conflict(ref current) => Self {
+                current: serde_json::to_value(current).ok(),
+                ..Self::conflict(error.to_string())
+            },
+            UpdateError::Failed(error) => Self::from(error),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut body = serde_json::json!({ "error": self.message });
+        if let Some(current) = self.current {
+            body["current"] = current;
+        }
+        (self.status, Json(body)).into_response()
+    }
+}
+
+/// JSON request body whose rejections are reported as `ApiError`s
+pub struct ApiJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Json::<T>::from_request(req, state).await {
+            Ok(Json(value)) => Ok(Self(value)),
+            Err(rejection) => Err(rejection_error(rejection)),
+        }
+    }
+}
+
+fn rejection_error(rejection: JsonRejection) -> ApiError {
+    ApiError::new(rejection.status(), rejection.body_text())
+}
+
+/// Query string whose rejections are reported as `ApiError`s
+pub struct ApiQuery<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for ApiQuery<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match Query::<T>::from_request_parts(parts, state).await {
+            Ok(Query(value)) => Ok(Self(value)),
+            Err(rejection) => Err(query_rejection_error(rejection)),
+        }
+    }
+}
+
+fn query_rejection_error(rejection: QueryRejection) -> ApiError {
+    ApiError::new(rejection.status(), rejection.body_text())
+}
+
+/// Fallback for unknown `/api/v1` routes
+pub async fn not_found() -> ApiError {
+    ApiError::not_found("No such API route")
+}
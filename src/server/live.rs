@@ -0,0 +1,185 @@
+//! Live collaboration: a WebSocket per open editor that pushes changes and presence
+//!
+//! Each topology with at least one viewer has a room: a broadcast channel of events and
+//! the list of who is viewing. Server functions publish to the room after committing a
+//! change (see `publish_live` in `crate::api`); the socket task forwards events to its
+//! browser, skipping those the same tab caused, and relays the browser's selection into
+//! the room's presence list. A user whose access is revoked has their sockets closed.
+
+use super::{run_server_fn, ApiResult};
+use crate::api;
+use crate::models::{LiveClientMessage, LiveEvent, LiveSelection, LiveViewer, User};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Extension, Path};
+use axum::http::request::Parts;
+use axum::response::Response;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Events a slow socket may fall behind by before it is told to reload instead
+const ROOM_CAPACITY: usize = 256;
+
+/// The editor tab a change came from: its session key and the viewer id of its socket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveOrigin {
+    pub session: String,
+    pub viewer_id: u64,
+}
+
+/// An event together with the tab that caused it (None for presence updates and scripts)
+#[derive(Debug, Clone)]
+struct RoomEvent {
+    origin: Option<LiveOrigin>,
+    event: LiveEvent,
+}
+
+struct Room {
+    sender: broadcast::Sender<RoomEvent>,
+    viewers: BTreeMap<u64, LiveViewer>,
+}
+
+impl Room {
+    fn publish_presence(&self) {
+        let viewers = self.viewers.values().cloned().collect();
+        let _ = self.sender.send(RoomEvent {
+            origin: None,
+            event: LiveEvent::Presence { viewers },
+        });
+    }
+}
+
+/// Rooms of the topologies currently open in an editor (shared as an Axum extension)
+#[derive(Clone, Default)]
+pub struct LiveHub {
+    rooms: Arc<Mutex<HashMap<i64, Room>>>,
+    next_viewer_id: Arc<AtomicU64>,
+}
+
+impl LiveHub {
+    /// Send an event to a topology's viewers (nobody listening is fine)
+    pub fn publish(&self, topology_id: i64, origin: Option<LiveOrigin>, event: LiveEvent) {
+        let rooms = self.rooms.lock().expect("live rooms lock");
+        if let Some(room) = rooms.get(&topology_id) {
+            let _ = room.sender.send(RoomEvent { origin, event });
+        }
+    }
+
+    /// Add a viewer to a topology's room, creating the room for the first one
+    fn join(&self, topology_id: i64, user: &User) -> (u64, broadcast::Receiver<RoomEvent>) {
+        let viewer_id = self.next_viewer_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut rooms = self.rooms.lock().expect("live rooms lock");
+        let room = rooms.entry(topology_id).or_insert_with(|| Room {
+            sender: broadcast::channel(ROOM_CAPACITY).0,
+            viewers: BTreeMap::new(),
+        });
+        let receiver = room.sender.subscribe();
+        room.viewers.insert(
+            viewer_id,
+            LiveViewer {
+                viewer_id,
+                user_id: user.id,
+                username: user.username.clone(),
+                selection: None,
+            },
+        );
+        room.publish_presence();
+        (viewer_id, receiver)
+    }
+
+    fn select(&self, topology_id: i64, viewer_id: u64, selection: Option<LiveSelection>) {
+        let mut rooms = self.rooms.lock().expect("live rooms lock");
+        if let Some(room) = rooms.get_mut(&topology_id) {
+            if let Some(viewer) = room.viewers.get_mut(&viewer_id) {
+                if viewer.selection != selection {
+                    viewer.selection = selection;
+                    room.publish_presence();
+                }
+            }
+        }
+    }
+
+    /// Remove a viewer, dropping the room once it is empty
+    fn leave(&self, topology_id: i64, viewer_id: u64) {
+        let mut rooms = self.rooms.lock().expect("live rooms lock");
+        if let Some(room) = rooms.get_mut(&topology_id) {
+            room.viewers.remove(&viewer_id);
+            if room.viewers.is_empty() {
+                rooms.remove(&topology_id);
+            } else {
+                room.publish_presence();
+            }
+        }
+    }
+}
+
+/// GET /topologies/{id}/live - upgrade to a WebSocket of live events for a topology
+///
+/// Browsers authenticate with their session cookie, scripts with a bearer token; the
+/// caller needs at least the viewer role.
+pub async fn topology_socket(
+    parts: Parts,
+    Path(id): Path<i64>,
+    Extension(hub): Extension<LiveHub>,
+    upgrade: WebSocketUpgrade,
+) -> ApiResult<Response> {
+    let (user, session) = run_server_fn(parts, || api::join_live_topology(id)).await?;
+    Ok(upgrade.on_upgrade(move |socket| serve_socket(socket, hub, id, user, session)))
+}
+
+async fn serve_socket(mut socket: WebSocket, hub: LiveHub, topology_id: i64, user: User, session: Option<String>) {
+    let (viewer_id, mut events) = hub.join(topology_id, &user);
+    // The viewer id alone isn't trusted: another session could name it to hide its edits
+    let own = session.map(|session| LiveOrigin { session, viewer_id });
+
+    if send_event(&mut socket, &LiveEvent::Welcome { viewer_id }).await {
+        loop {
+            tokio::select! {
+                received = events.recv() => {
+                    let event = match received {
+                        // Sent even to the tab that revoked its own access, then the socket closes
+                        Ok(RoomEvent { event: LiveEvent::AccessRevoked { user_id }, .. }) => {
+                            if user_id == user.id {
+                                send_event(&mut socket, &LiveEvent::AccessRevoked { user_id }).await;
+                                break;
+                            }
+                            continue;
+                        }
+                        Ok(RoomEvent { origin: Some(origin), .. }) if Some(&origin) == own.as_ref() => continue,
+                        Ok(room_event) => room_event.event,
+                        // Missed some events: the client has to reload to catch up
+                        Err(broadcast::error::RecvError::Lagged(_)) => LiveEvent::TopologyChanged,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    };
+                    if !send_event(&mut socket, &event).await {
+                        break;
+                    }
+                }
+                message = socket.recv() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        match serde_json::from_str::<LiveClientMessage>(text.as_str()) {
+                            Ok(LiveClientMessage::Select { selection }) => hub.select(topology_id, viewer_id, selection),
+                            Err(e) => tracing::debug!("Ignoring malformed live message: {}", e),
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+    }
+
+    hub.leave(topology_id, viewer_id);
+}
+
+/// Send an event as a JSON text frame; false once the socket is gone
+async fn send_event(socket: &mut WebSocket, event: &LiveEvent) -> bool {
+    match serde_json::to_string(event) {
+        Ok(json) => socket.send(Message::Text(json.into())).await.is_ok(),
+        Err(e) => {
+            tracing::error!("Failed to serialize live event: {}", e);
+            true
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use super::{run_server_fn, ApiJson, ApiResult};
+use crate::api;
+use crate::models::ConnectionTrafficMetric;
+use axum::extract::Path;
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+/// Body of POST /topologies/{id}/metrics/mock
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockTrafficRequest {
+    #[serde(default)]
+    pub traffic_level: String, // "low", "medium" or "high"; empty = the server's default
+}
+
+/// Number of metric rows a request created or deleted
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsCount {
+    pub count: usize,
+}
+
+/// GET /topologies/{id}/metrics - latest metric of each connection, busiest first
+pub async fn list_metrics(
+    parts: Parts,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<Vec<ConnectionTrafficMetric>>> {
+    run_server_fn(parts, || api::get_connection_traffic_metrics(id)).await.map(Json)
+}
+
+/// POST /topologies/{id}/metrics/mock - record one round of simulated traffic
+pub async fn generate_mock_metrics(
+    parts: Parts,
+    Path(id): Path<i64>,
+    ApiJson(request): ApiJson<MockTrafficRequest>,
+) -> ApiResult<(StatusCode, Json<MetricsCount>)> {
+    let count = run_server_fn(parts, || api::generate_mock_traffic(id, request.traffic_level)).await?;
+    Ok((StatusCode::CREATED, Json(MetricsCount { count })))
+}
+
+/// DELETE /topologies/{id}/metrics - drop all traffic history of the topology
+pub async fn clear_metrics(parts: Parts, Path(id): Path<i64>) -> ApiResult<Json<MetricsCount>> {
+    let count = run_server_fn(parts, || api::clear_traffic_data(id)).await?;
+    Ok(Json(MetricsCount { count }))
+}
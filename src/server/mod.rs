@@ -1,7 +1,123 @@
-pub mod topology_api;
-pub mod node_api;
+//! Versioned JSON REST API (`/api/v1`) for scripts and automation
+//!
+//! The Leptos server functions under `/api` use encoded, unstable URLs meant for the
+//! browser. This module exposes the same operations as plain REST routes. Handlers don't
+//! duplicate any SQL: they run the server functions from `crate::api` with the request
+//! parts in context (the way leptos_axum does), so validation, IPAM checks and undo
+//! recording behave exactly as in the editor.
+
+pub mod assets;
+pub mod audit_api;
 pub mod connection_api;
+pub mod error;
+pub mod live;
+pub mod metrics_api;
+pub mod model_api;
+pub mod model_registry;
+pub mod model_scan;
+pub mod node_api;
+pub mod openapi;
+pub mod topology_api;
+pub mod undo_api;
+
+pub use error::{ApiError, ApiJson, ApiQuery, ApiResult};
+
+use crate::config::NtbConfig;
+use crate::models::MAX_ICON_BYTES;
+use axum::http::request::Parts;
+use axum::extract::DefaultBodyLimit;
+use axum::routing::{delete, get, post};
+use axum::Router;
+use leptos::prelude::*;
+use leptos::reactive::computed::ScopedFuture;
+use leptos::reactive::owner::Owner;
+use std::future::Future;
+
+/// Room for the form fields and multipart boundaries of a model upload
+const UPLOAD_FORM_OVERHEAD: usize = 64 * 1024;
+
+/// Routes of the REST API, to be nested under `/api/v1`
+pub fn api_v1_router<S>(config: &NtbConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    // A model and a vendor icon at their size limits, plus the rest of the form
+    let upload_limit = usize::try_from(config.models.max_upload_bytes)
+        .unwrap_or(usize::MAX)
+        .saturating_add(MAX_ICON_BYTES)
+        .saturating_add(UPLOAD_FORM_OVERHEAD);
+
+    Router::new()
+        .route("/openapi.json", get(openapi::openapi_document))
+        // Topologies
+        .route(
+            "/topologies",
+            get(topology_api::list_topologies).post(topology_api::create_topology),
+        )
+        .route(
+            "/topologies/{id}",
+            get(topology_api::get_topology)
+                .patch(topology_api::update_topology)
+                .delete(topology_api::delete_topology),
+        )
+        .route("/topologies/{id}/nodes", get(topology_api::list_topology_nodes))
+        .route("/topologies/{id}/connections", get(topology_api::list_topology_connections))
+        .route("/topologies/{id}/live", get(live::topology_socket))
+        // Nodes
+        .route("/nodes", post(node_api::create_node))
+        .route(
+            "/nodes/{id}",
+            get(node_api::get_node)
+                .patch(node_api::update_node)
+                .delete(node_api::delete_node),
+        )
+        // Connections
+        .route("/connections", post(connection_api::create_connection))
+        .route(
+            "/connections/{id}",
+            get(connection_api::get_connection)
+                .patch(connection_api::update_connection)
+                .delete(connection_api::delete_connection),
+        )
+        // Traffic metrics
+        .route(
+            "/topologies/{id}/metrics",
+            get(metrics_api::list_metrics).delete(metrics_api::clear_metrics),
+        )
+        .route("/topologies/{id}/metrics/mock", post(metrics_api::generate_mock_metrics))
+        // Undo / redo
+        .route("/topologies/{id}/history", get(undo_api::undo_history))
+        .route("/topologies/{id}/undo", get(undo_api::undo_status).post(undo_api::undo))
+        .route("/topologies/{id}/redo", post(undo_api::redo))
+        // Audit log
+        .route("/audit-log", get(audit_api::list_audit_log))
+        // Uploaded models (files are also checked against their own limits while read)
+        .route(
+            "/models",
+            post(model_api::upload_model).layer(DefaultBodyLimit::max(upload_limit)),
+        )
+        .route("/models/{node_type}/{vendor}/{model}", delete(model_api::delete_model))
+        .fallback(error::not_found)
+}
 
-pub use topology_api::*;
-pub use node_api::*;
-pub use connection_api::*;
+/// Run a server function from a plain Axum handler
+///
+/// Server functions read the database pool (and anything else they extract) from the
+/// request `Parts` in the reactive context, so provide them for the duration of the call.
+pub async fn run_server_fn<T, E, F, Fut>(parts: Parts, server_fn: F) -> ApiResult<T>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    ApiError: From<E>,
+{
+    let owner = Owner::new();
+    owner
+        .with(|| {
+            ScopedFuture::new(async move {
+                provide_context(parts);
+                server_fn().await
+            })
+        })
+        .await
+        .map_err(ApiError::from)
+}
@@ -0,0 +1,105 @@
+use super::{run_server_fn, ApiError, ApiResult};
+use crate::api;
+use crate::config::NtbConfig;
+use crate::models::{ModelUpload, ModelUploadReport, NodeType, MAX_ICON_BYTES};
+use axum::extract::multipart::{Field, Multipart, MultipartRejection};
+use axum::extract::Path;
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::Json;
+use std::sync::Arc;
+
+/// POST /models - multipart form with `node_type`, `vendor`, `name`, `model` (the .glb
+/// file), optionally `icon` (the vendor's SVG) and `replace` ("true" to overwrite an
+/// earlier upload)
+///
+/// The route's body limit leaves room for a model and an icon at their size limits;
+/// each file is also cut off at its own limit (413) while it's read.
+pub async fn upload_model(
+    parts: Parts,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> ApiResult<(StatusCode, Json<ModelUploadReport>)> {
+    let mut multipart = multipart.map_err(|rejection| ApiError::new(rejection.status(), rejection.body_text()))?;
+    let max_model_bytes = parts
+        .extensions
+        .get::<Arc<NtbConfig>>()
+        .map(|config| config.models.max_upload_bytes)
+        .unwrap_or_else(|| NtbConfig::default().models.max_upload_bytes);
+
+    let mut node_type = None;
+    let mut vendor = None;
+    let mut name = None;
+    let mut model = None;
+    let mut icon = None;
+    let mut replace = false;
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        let field_name = field.name().unwrap_or_default().to_string();
+        match field_name.as_str() {
+            "node_type" => {
+                let value = field.text().await.map_err(multipart_error)?;
+                node_type = Some(
+                    value
+                        .trim()
+                        .parse::<NodeType>()
+                        .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?,
+                );
+            }
+            "vendor" => vendor = Some(field.text().await.map_err(multipart_error)?.trim().to_string()),
+            "name" => name = Some(field.text().await.map_err(multipart_error)?.trim().to_string()),
+            "replace" => replace = matches!(field.text().await.map_err(multipart_error)?.trim(), "true" | "1" | "on"),
+            "model" => model = Some(read_file(field, max_model_bytes, "Model").await?),
+            "icon" => {
+                let bytes = read_file(field, MAX_ICON_BYTES as u64, "Icon").await?;
+                // Browsers send an empty part for a file input left empty
+                icon = (!bytes.is_empty()).then_some(bytes);
+            }
+            other => {
+                return Err(ApiError::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!("Unknown form field '{}'", other),
+                ))
+            }
+        }
+    }
+
+    let missing = |field: &str| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, format!("Missing form field '{}'", field));
+    let upload = ModelUpload {
+        node_type: node_type.ok_or_else(|| missing("node_type"))?,
+        vendor: vendor.ok_or_else(|| missing("vendor"))?,
+        name: name.ok_or_else(|| missing("name"))?,
+        model: model.ok_or_else(|| missing("model"))?,
+        icon,
+        replace,
+    };
+    let report = run_server_fn(parts, || api::upload_model(upload)).await?;
+    let status = if report.replaced { StatusCode::OK } else { StatusCode::CREATED };
+    Ok((status, Json(report)))
+}
+
+/// DELETE /models/{node_type}/{vendor}/{model} - only uploaded models no node uses
+pub async fn delete_model(
+    parts: Parts,
+    Path((node_type, vendor, model)): Path<(NodeType, String, String)>,
+) -> ApiResult<StatusCode> {
+    run_server_fn(parts, || api::delete_uploaded_model(node_type, vendor, model)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A file part, refused with 413 once it grows past `limit` bytes
+async fn read_file(mut field: Field<'_>, limit: u64, what: &str) -> ApiResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+        if (bytes.len() + chunk.len()) as u64 > limit {
+            return Err(ApiError::new(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("{} is larger than the upload limit of {} bytes", what, limit),
+            ));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+fn multipart_error(error: axum::extract::multipart::MultipartError) -> ApiError {
+    ApiError::new(error.status(), error.body_text())
+}
@@ -0,0 +1,221 @@
+//! Registry of the models the editor offers, by node type and vendor
+//!
+//! Built at startup from the model roots (see `NtbConfig::model_roots`): each vendor
+//! folder `{root}/{node_type}/{vendor}/` contributes its `.glb` files, described by the
+//! folder's `models.toml` or `models.json` manifest when it has one (see
+//! `VendorManifest`). Folders without a manifest, or with one that doesn't parse, and
+//! models the manifest doesn't list fall back to names derived from the file names. A
+//! vendor folder found in several roots is one vendor; the earlier root wins per model.
+//! Models under `models.upload_dir` are flagged as uploaded, which is what lets the
+//! editor replace or delete them; `reload` picks up an upload straight away.
+
+use super::model_scan::ModelScan;
+use crate::config::NtbConfig;
+use crate::models::{display_name_from_slug, ModelInfo, NodeType, VendorInfo, VendorManifest};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// Manifest file names, in the order they're looked for
+pub const MANIFEST_FILES: &[&str] = &["models.toml", "models.json"];
+
+#[derive(Debug, Clone)]
+struct RegisteredModel {
+    info: ModelInfo,
+    root: PathBuf,
+    file: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+struct RegisteredVendor {
+    /// Without its models, which are kept with their files below
+    info: VendorInfo,
+    models: Vec<RegisteredModel>,
+}
+
+/// The registered vendors of every node type, shared by all requests
+#[derive(Debug, Clone)]
+pub struct ModelRegistry {
+    roots: Arc<Vec<PathBuf>>,
+    icon_dirs: Arc<Vec<PathBuf>>,
+    upload_dir: Arc<PathBuf>,
+    vendors: Arc<RwLock<HashMap<NodeType, Vec<RegisteredVendor>>>>,
+}
+
+impl ModelRegistry {
+    /// Scan the configured model roots of a site
+    pub fn load(config: &NtbConfig, site_root: &str) -> Self {
+        let registry = Self {
+            roots: Arc::new(config.model_roots(site_root)),
+            icon_dirs: Arc::new(config.icon_dirs(site_root)),
+            upload_dir: Arc::new(config.models.upload_dir.clone()),
+            vendors: Arc::default(),
+        };
+        registry.reload();
+        registry
+    }
+
+    /// Scan the model roots again, e.g. after a model was uploaded or deleted
+    pub fn reload(&self) {
+        let vendors = NodeType::ALL
+            .iter()
+            .map(|&node_type| (node_type, self.scan_node_type(node_type)))
+            .collect();
+        *self.vendors.write().unwrap() = vendors;
+    }
+
+    /// Vendors offering models for a node type: generic first, then those with models
+    ///
+    /// Models failing validation carry their first error in `problem`.
+    pub fn vendors(&self, node_type: NodeType, scan: &ModelScan) -> Vec<VendorInfo> {
+        let registered = self.vendors.read().unwrap();
+        registered
+            .get(&node_type)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(|vendor| VendorInfo {
+                models: vendor
+                    .models
+                    .iter()
+                    .map(|model| ModelInfo {
+                        problem: scan
+                            .check(&model.root, &model.file)
+                            .first_error()
+                            .map(|issue| issue.message.clone()),
+                        ..model.info.clone()
+                    })
+                    .collect(),
+                ..vendor.info.clone()
+            })
+            .collect()
+    }
+
+    /// A registered model by vendor and file name (without `.glb`)
+    pub fn model(&self, node_type: NodeType, vendor: &str, file_name: &str) -> Option<ModelInfo> {
+        self.model_file(node_type, vendor, file_name).map(|(info, _)| info)
+    }
+
+    /// A registered model and the file it's served from
+    pub fn model_file(&self, node_type: NodeType, vendor: &str, file_name: &str) -> Option<(ModelInfo, PathBuf)> {
+        let registered = self.vendors.read().unwrap();
+        registered
+            .get(&node_type)?
+            .iter()
+            .find(|v| v.info.name == vendor)?
+            .models
+            .iter()
+            .find(|m| m.info.file_name == file_name)
+            .map(|m| (m.info.clone(), m.file.clone()))
+    }
+
+    /// Where an upload of this model is stored
+    pub fn upload_path(&self, node_type: NodeType, vendor: &str, file_name: &str) -> PathBuf {
+        self.upload_dir
+            .join(node_type.as_str())
+            .join(vendor)
+            .join(format!("{}.glb", file_name))
+    }
+
+    fn scan_node_type(&self, node_type: NodeType) -> Vec<RegisteredVendor> {
+        let mut vendors: Vec<RegisteredVendor> = Vec::new();
+
+        for root in self.roots.iter() {
+            let Ok(entries) = std::fs::read_dir(root.join(node_type.as_str())) else {
+                continue;
+            };
+            for dir in entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()) {
+                let Some(vendor_name) = dir.file_name().and_then(|n| n.to_str()).map(str::to_string) else {
+                    continue;
+                };
+                let manifest = read_manifest(&dir);
+
+                let vendor = match vendors.iter().position(|v| v.info.name == vendor_name) {
+                    Some(index) => &mut vendors[index],
+                    None => {
+                        let icon = format!("{}.svg", vendor_name);
+                        vendors.push(RegisteredVendor {
+                            info: VendorInfo {
+                                display_name: manifest
+                                    .as_ref()
+                                    .and_then(|m| m.display_name.clone())
+                                    .unwrap_or_else(|| display_name_from_slug(&vendor_name)),
+                                name: vendor_name,
+                                has_icon: self.icon_dirs.iter().any(|dir| dir.join(&icon).is_file()),
+                                models: Vec::new(),
+                                is_available: false,
+                            },
+                            models: Vec::new(),
+                        });
+                        vendors.last_mut().unwrap()
+                    }
+                };
+
+                let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "glb"))
+                    .collect();
+                files.sort();
+
+                for file in files {
+                    let Some(file_name) = file.file_stem().and_then(|n| n.to_str()) else {
+                        continue;
+                    };
+                    if vendor.models.iter().any(|m| m.info.file_name == file_name) {
+                        continue;
+                    }
+                    let mut info = match &manifest {
+                        Some(manifest) => manifest.model_info(file_name),
+                        None => ModelInfo::from_file_name(file_name),
+                    };
+                    info.uploaded = *root == *self.upload_dir;
+                    vendor.models.push(RegisteredModel {
+                        info,
+                        root: root.clone(),
+                        file: file.clone(),
+                    });
+                }
+
+                if let Some(manifest) = &manifest {
+                    for listed in manifest.models.keys() {
+                        if !dir.join(format!("{}.glb", listed)).is_file() {
+                            tracing::warn!("{}: manifest lists {}.glb, which doesn't exist", dir.display(), listed);
+                        }
+                    }
+                }
+                vendor.info.is_available = !vendor.models.is_empty();
+            }
+        }
+
+        // Sort vendors: generic first, then available vendors, then unavailable
+        vendors.sort_by(|a, b| {
+            let rank = |v: &RegisteredVendor| (v.info.name != "generic", !v.info.is_available);
+            rank(a).cmp(&rank(b)).then_with(|| a.info.display_name.cmp(&b.info.display_name))
+        });
+        vendors
+    }
+}
+
+/// The vendor folder's manifest; None (with a warning) if it has none or it's invalid
+fn read_manifest(dir: &Path) -> Option<VendorManifest> {
+    let path = MANIFEST_FILES.iter().map(|name| dir.join(name)).find(|path| path.is_file())?;
+
+    let parsed = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str::<VendorManifest>(&contents).map_err(|e| e.to_string()),
+            _ => toml::from_str::<VendorManifest>(&contents).map_err(|e| e.to_string()),
+        })
+        .and_then(|manifest| manifest.validate().map(|()| manifest));
+
+    match parsed {
+        Ok(manifest) => Some(manifest),
+        Err(e) => {
+            tracing::warn!("Ignoring invalid model manifest {}: {}", path.display(), e);
+            None
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Validation results of the model files on disk
+//!
+//! The server checks every model under its model roots at startup (see
+//! `crate::models::validate_glb`) and keeps the results, so the vendor list can flag
+//! broken models instead of letting them fail to load in the viewport. Files added or
+//! changed since are checked the first time they're asked about.
+
+use crate::models::{validate_glb, IssueCategory, IssueSeverity, ModelIssue, ModelValidation};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// What a cached result was computed from; a different stamp means the file changed
+type FileStamp = (u64, Option<SystemTime>);
+
+/// Cache of model validations by file, shared by all requests
+#[derive(Clone, Default)]
+pub struct ModelScan {
+    results: Arc<Mutex<HashMap<PathBuf, (FileStamp, ModelValidation)>>>,
+}
+
+impl ModelScan {
+    /// Check every model under the roots
+    pub fn scan(roots: &[PathBuf]) -> Self {
+        let scan = Self::default();
+        for root in roots {
+            for file in find_models(root) {
+                scan.check(root, &file);
+            }
+        }
+        scan
+    }
+
+    /// Validation of one model file, from the cache while the file is unchanged
+    pub fn check(&self, root: &Path, file: &Path) -> ModelValidation {
+        let stamp = std::fs::metadata(file)
+            .map(|meta| (meta.len(), meta.modified().ok()))
+            .unwrap_or_default();
+
+        if let Some((cached_stamp, result)) = self.results.lock().unwrap().get(file) {
+            if *cached_stamp == stamp {
+                return result.clone();
+            }
+        }
+        let result = validate_file(root, file);
+        self.results
+            .lock()
+            .unwrap()
+            .insert(file.to_path_buf(), (stamp, result.clone()));
+        result
+    }
+
+    /// Every cached result, sorted by path
+    pub fn results(&self) -> Vec<ModelValidation> {
+        let mut results: Vec<_> = self
+            .results
+            .lock()
+            .unwrap()
+            .values()
+            .map(|(_, result)| result.clone())
+            .collect();
+        results.sort_by(|a, b| a.path.cmp(&b.path));
+        results
+    }
+}
+
+/// Every .glb file under a directory, sorted
+pub fn find_models(root: &Path) -> Vec<PathBuf> {
+    let mut models = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "glb") {
+                models.push(path);
+            }
+        }
+    }
+    models.sort();
+    models
+}
+
+/// Check one model file (named relative to its root); an unreadable file is an error
+pub fn validate_file(root: &Path, file: &Path) -> ModelValidation {
+    let path = file
+        .strip_prefix(root)
+        .unwrap_or(file)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    match std::fs::read(file) {
+        Ok(bytes) => validate_glb(&path, &bytes),
+        Err(e) => ModelValidation {
+            path,
+            file_size_bytes: 0,
+            materials: Vec::new(),
+            max_dimension: 0.0,
+            issues: vec![ModelIssue {
+                severity: IssueSeverity::Error,
+                category: IssueCategory::Structure,
+                message: format!("Failed to read file: {}", e),
+                fix: None,
+            }],
+        },
+    }
+}
@@ -0,0 +1,397 @@
+use crate::models::{AuditAction, AuditEntity, ConnectionStatus, ConnectionType, FlowDirection, NodeType};
+use axum::Json;
+use serde_json::{json, Map, Value};
+
+/// GET /openapi.json - OpenAPI 3.0 description of the REST API
+pub async fn openapi_document() -> Json<Value> {
+    Json(document())
+}
+
+/// Build the OpenAPI document (kept next to the router in `mod.rs`; update both together)
+pub fn document() -> Value {
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "NTB REST API",
+            "version": "1.0.0",
+            "description": "Manage network topologies without the browser. Every write goes through the \
+                            same code as the editor, so it shows up in the undo history."
+        },
+        "servers": [{ "url": "/api/v1" }],
+        "security": [{ "bearerAuth": [] }],
+        "paths": {
+            "/topologies": {
+                "get": operation("List topologies", None, ok(array_of("Topology"))),
+                "post": operation("Create a topology", Some("CreateTopology"), created("Topology")),
+            },
+            "/topologies/{id}": {
+                "parameters": [id_parameter()],
+                "get": operation(
+                    "Get a topology with its nodes, connections, interfaces, subnets, groups and tags",
+                    None,
+                    ok(reference("TopologyFull")),
+                ),
+                "patch": operation("Update a topology's name or description", Some("UpdateTopology"), ok(reference("Topology"))),
+                "delete": operation("Delete a topology and everything in it", None, no_content()),
+            },
+            "/topologies/{id}/nodes": {
+                "parameters": [id_parameter()],
+                "get": operation("List the nodes of a topology", None, ok(array_of("Node"))),
+            },
+            "/topologies/{id}/connections": {
+                "parameters": [id_parameter()],
+                "get": operation("List the connections of a topology", None, ok(array_of("Connection"))),
+            },
+            "/topologies/{id}/live": {
+                "parameters": [id_parameter()],
+                "get": {
+                    "summary": "Open a WebSocket of live changes and presence for a topology",
+                    "description": "Messages are JSON objects tagged by `type`: welcome, presence, node_upserted, \
+                                    node_deleted, connection_upserted, connection_deleted, topology_changed \
+                                    (reload) and topology_deleted. Send `{\"type\": \"select\", \"selection\": {\"Node\": 1}}` to share a selection.",
+                    "responses": { "101": { "description": "Switching to the WebSocket protocol" } },
+                },
+            },
+            "/nodes": {
+                "post": operation("Create a node", Some("CreateNode"), created("Node")),
+            },
+            "/nodes/{id}": {
+                "parameters": [id_parameter()],
+                "get": operation("Get a node", None, ok(reference("Node"))),
+                "patch": operation(
+                    "Update the given fields of a node (409 with the current node if expected_version is stale)",
+                    Some("UpdateNode"),
+                    ok(reference("Node")),
+                ),
+                "delete": operation("Delete a node and its connections", None, no_content()),
+            },
+            "/connections": {
+                "post": operation("Create a connection", Some("CreateConnection"), created("Connection")),
+            },
+            "/connections/{id}": {
+                "parameters": [id_parameter()],
+                "get": operation("Get a connection", None, ok(reference("Connection"))),
+                "patch": operation(
+                    "Update the given fields of a connection (409 with the current connection if expected_version is stale)",
+                    Some("UpdateConnection"),
+                    ok(reference("Connection")),
+                ),
+                "delete": operation("Delete a connection", None, no_content()),
+            },
+            "/topologies/{id}/metrics": {
+                "parameters": [id_parameter()],
+                "get": operation(
+                    "Latest traffic metric of each connection, busiest first",
+                    None,
+                    ok(array_of("ConnectionTrafficMetric")),
+                ),
+                "delete": operation("Delete all traffic history of a topology", None, ok(reference("MetricsCount"))),
+            },
+            "/topologies/{id}/metrics/mock": {
+                "parameters": [id_parameter()],
+                "post": operation("Record one round of simulated traffic", Some("MockTrafficRequest"), created("MetricsCount")),
+            },
+            "/topologies/{id}/history": {
+                "parameters": [id_parameter()],
+                "get": operation("Undoable changes, newest first", None, ok(array_of("UndoHistoryEntry"))),
+            },
+            "/topologies/{id}/undo": {
+                "parameters": [id_parameter()],
+                "get": operation("Labels of the next undo and redo steps", None, ok(reference("UndoStatus"))),
+                "post": operation("Undo the latest change set (409 if there is none)", None, ok(reference("UndoStatus"))),
+            },
+            "/topologies/{id}/redo": {
+                "parameters": [id_parameter()],
+                "post": operation("Redo the latest undone change set (409 if there is none)", None, ok(reference("UndoStatus"))),
+            },
+            "/audit-log": {
+                "parameters": audit_filter_parameters(),
+                "get": operation(
+                    "Audit log entries on your topologies and account, newest first",
+                    None,
+                    ok(array_of("AuditLogEntry")),
+                ),
+            },
+            "/models": {
+                "post": upload_operation(),
+            },
+            "/models/{node_type}/{vendor}/{model}": {
+                "parameters": [
+                    { "name": "node_type", "in": "path", "required": true, "schema": string_enum(NodeType::ALL.iter().map(|v| v.as_str())) },
+                    { "name": "vendor", "in": "path", "required": true, "schema": string() },
+                    { "name": "model", "in": "path", "required": true, "schema": string() },
+                ],
+                "delete": operation("Delete an uploaded model (422 while nodes still use it)", None, no_content()),
+            },
+        },
+        "components": {
+            "schemas": schemas(),
+            "securitySchemes": {
+                "bearerAuth": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "API token created in the editor (Account > API tokens)"
+                }
+            }
+        },
+    })
+}
+
+fn schemas() -> Value {
+    let node_type = string_enum(NodeType::ALL.iter().map(|v| v.as_str()));
+    let connection_type = string_enum(ConnectionType::ALL.iter().map(|v| v.as_str()));
+    let connection_status = string_enum(ConnectionStatus::ALL.iter().map(|v| v.as_str()));
+    let flow_direction = string_enum(FlowDirection::ALL.iter().map(|v| v.as_str()));
+    let color = json!({ "type": "string", "example": "100,150,255", "description": "R,G,B or R,G,B,A (0-255)" });
+    let audit_entity = string_enum(AuditEntity::ALL.iter().map(|v| v.as_str()));
+    let audit_action = string_enum(AuditAction::ALL.iter().map(|v| v.as_str()));
+    let expected_version = json!({
+        "type": "integer",
+        "description": "version the change was based on; the update is rejected with 409 if the row has changed since",
+    });
+
+    json!({
+        "Error": object(
+            &[("error", string()),
+              ("current", json!({ "type": "object", "description": "Update conflicts only: the row as it is now" }))],
+            &["error"],
+        ),
+        "Topology": object(
+            &[("id", integer()), ("name", string()), ("description", nullable(string())),
+              ("created_at", integer()), ("updated_at", integer())],
+            &["id", "name", "created_at", "updated_at"],
+        ),
+        "CreateTopology": object(&[("name", string()), ("description", string())], &["name"]),
+        "UpdateTopology": object(&[("name", string()), ("description", string())], &[]),
+        "TopologyFull": object(
+            &[("topology", reference("Topology")),
+              ("nodes", array_of("Node")),
+              ("connections", array_of("Connection")),
+              ("interfaces", json!({ "type": "array", "items": { "type": "object" } })),
+              ("subnets", json!({ "type": "array", "items": { "type": "object" } })),
+              ("groups", json!({ "type": "array", "items": { "type": "object" } })),
+              ("tags", json!({ "type": "array", "items": { "type": "object" } })),
+              ("tag_assignments", json!({ "type": "array", "items": { "type": "object" } }))],
+            &["topology", "nodes", "connections"],
+        ),
+        "Node": object(
+            &[("id", integer()), ("topology_id", integer()), ("name", string()),
+              ("node_type", node_type.clone()), ("vendor", string()), ("model_name", string()),
+              ("ip_address", nullable(string())),
+              ("position_x", number()), ("position_y", number()), ("position_z", number()),
+              ("rotation_x", number()), ("rotation_y", number()), ("rotation_z", number()),
+              ("scale", number()), ("color", color.clone()), ("visible", boolean()),
+              ("group_id", nullable(integer())), ("metadata", nullable(string())),
+              ("created_at", integer()), ("updated_at", integer()), ("version", integer())],
+            &["id", "topology_id", "name", "node_type", "vendor", "model_name", "version"],
+        ),
+        "CreateNode": object(
+            &[("topology_id", integer()), ("name", string()), ("node_type", node_type.clone()),
+              ("vendor", string()), ("model_name", string()), ("ip_address", string()),
+              ("position_x", number()), ("position_y", number()), ("position_z", number()),
+              ("rotation_x", number()), ("rotation_y", number()), ("rotation_z", number()),
+              ("scale", number()), ("color", color.clone()), ("visible", boolean()),
+              ("group_id", integer()), ("metadata", string())],
+            &["topology_id", "name", "node_type"],
+        ),
+        "UpdateNode": object(
+            &[("name", string()), ("node_type", node_type.clone()), ("vendor", string()),
+              ("model_name", string()), ("ip_address", string()),
+              ("position_x", number()), ("position_y", number()), ("position_z", number()),
+              ("rotation_x", number()), ("rotation_y", number()), ("rotation_z", number()),
+              ("scale", number()), ("color", color.clone()), ("visible", boolean()),
+              ("group_id", json!({ "type": "integer", "description": "0 removes the node from its group" })),
+              ("metadata", string()), ("expected_version", expected_version.clone())],
+            &[],
+        ),
+        "Connection": object(
+            &[("id", integer()), ("topology_id", integer()),
+              ("source_node_id", integer()), ("target_node_id", integer()),
+              ("connection_type", connection_type.clone()),
+              ("bandwidth_mbps", nullable(integer())), ("latency_ms", nullable(number())),
+              ("baseline_packet_loss_pct", nullable(number())),
+              ("status", connection_status.clone()), ("color", color.clone()),
+              ("carries_traffic", boolean()), ("flow_direction", flow_direction.clone()),
+              ("source_interface_id", nullable(integer())), ("target_interface_id", nullable(integer())),
+              ("metadata", nullable(string())), ("created_at", integer()), ("updated_at", integer()),
+              ("version", integer())],
+            &["id", "topology_id", "source_node_id", "target_node_id", "connection_type", "status", "version"],
+        ),
+        "CreateConnection": object(
+            &[("topology_id", integer()), ("source_node_id", integer()), ("target_node_id", integer()),
+              ("connection_type", connection_type.clone()), ("bandwidth_mbps", integer()),
+              ("latency_ms", number()), ("baseline_packet_loss_pct", number()),
+              ("status", connection_status.clone()), ("color", color.clone()),
+              ("source_interface_id", integer()), ("target_interface_id", integer()),
+              ("metadata", string())],
+            &["topology_id", "source_node_id", "target_node_id"],
+        ),
+        "UpdateConnection": object(
+            &[("connection_type", connection_type), ("bandwidth_mbps", integer()),
+              ("latency_ms", number()), ("baseline_packet_loss_pct", number()),
+              ("status", connection_status), ("color", color), ("carries_traffic", boolean()),
+              ("flow_direction", flow_direction), ("metadata", string()),
+              ("expected_version", expected_version)],
+            &[],
+        ),
+        "ConnectionTrafficMetric": object(
+            &[("id", integer()), ("connection_id", integer()), ("timestamp", integer()),
+              ("throughput_mbps", number()), ("packets_per_sec", integer()), ("latency_ms", number()),
+              ("packet_loss_pct", number()), ("utilization_pct", number()),
+              ("bytes_transferred", integer()), ("packets_transferred", integer())],
+            &["id", "connection_id", "timestamp"],
+        ),
+        "MockTrafficRequest": object(
+            &[("traffic_level", json!({ "type": "string", "enum": ["low", "medium", "high"], "description": "Defaults to the server's mock_traffic.default_level (medium unless configured)" }))],
+            &[],
+        ),
+        "MetricsCount": object(&[("count", integer())], &["count"]),
+        "ModelUpload": object(
+            &[("node_type", node_type.clone()),
+              ("vendor", json!({ "type": "string", "pattern": "^[a-z0-9][a-z0-9_-]{0,63}$" })),
+              ("name", json!({ "type": "string", "pattern": "^[a-z0-9][a-z0-9_-]{0,63}$", "description": "File name without .glb" })),
+              ("model", json!({ "type": "string", "format": "binary", "description": "Binary glTF (.glb), at most models.max_upload_bytes" })),
+              ("icon", json!({ "type": "string", "format": "binary", "description": "Vendor icon (SVG without scripts), at most 256 KB" })),
+              ("replace", json!({ "type": "boolean", "description": "Overwrite an earlier upload of the same name" }))],
+            &["node_type", "vendor", "name", "model"],
+        ),
+        "ModelUploadReport": object(
+            &[("node_type", node_type), ("vendor", string()),
+              ("model", json!({ "type": "object", "description": "The registered model, as listed by the editor" })),
+              ("issues", json!({ "type": "array", "items": { "type": "object" }, "description": "Validation warnings and notes" })),
+              ("replaced", boolean())],
+            &["node_type", "vendor", "model", "issues", "replaced"],
+        ),
+        "UndoHistoryEntry": object(
+            &[("id", integer()), ("entity_type", string()), ("entity_id", integer()),
+              ("action_type", string()), ("timestamp", string())],
+            &["id", "entity_type", "entity_id", "action_type", "timestamp"],
+        ),
+        "UndoStatus": object(&[("undo_label", nullable(string())), ("redo_label", nullable(string()))], &[]),
+        "AuditLogEntry": object(
+            &[("id", integer()), ("created_at", integer()), ("user_id", nullable(integer())),
+              ("actor", string()), ("topology_id", nullable(integer())),
+              ("entity_type", audit_entity), ("entity_id", nullable(integer())),
+              ("action", audit_action), ("summary", string()),
+              ("before_state", nullable(json!({ "type": "string", "description": "JSON; changed fields only for updates" }))),
+              ("after_state", nullable(json!({ "type": "string", "description": "JSON; changed fields only for updates" })))],
+            &["id", "created_at", "actor", "entity_type", "action", "summary"],
+        ),
+    })
+}
+
+fn operation(summary: &str, body: Option<&str>, success: (u16, Value)) -> Value {
+    let (status, response) = success;
+    let mut responses = Map::new();
+    responses.insert(status.to_string(), response);
+    for (status, description) in [
+        ("400", "Malformed request"),
+        ("401", "Missing or invalid API token"),
+        ("403", "The token's user lacks the required role on the topology"),
+        ("404", "Not found"),
+        ("409", "Conflict"),
+        ("422", "Invalid data"),
+        ("500", "Server error"),
+    ] {
+        responses.insert(
+            status.to_string(),
+            json!({ "description": description, "content": { "application/json": { "schema": reference("Error") } } }),
+        );
+    }
+
+    let mut operation = json!({ "summary": summary, "responses": responses });
+    if let Some(schema) = body {
+        operation["requestBody"] = json!({
+            "required": true,
+            "content": { "application/json": { "schema": reference(schema) } },
+        });
+    }
+    operation
+}
+
+/// POST /models takes a multipart form rather than JSON
+fn upload_operation() -> Value {
+    let mut operation = operation(
+        "Upload a device model (201), or replace an earlier upload (200); 413 if a file is too large",
+        None,
+        created("ModelUploadReport"),
+    );
+    operation["requestBody"] = json!({
+        "required": true,
+        "content": { "multipart/form-data": { "schema": reference("ModelUpload") } },
+    });
+    operation
+}
+
+fn ok(schema: Value) -> (u16, Value) {
+    (200, json!({ "description": "OK", "content": { "application/json": { "schema": schema } } }))
+}
+
+fn created(schema: &str) -> (u16, Value) {
+    (201, json!({ "description": "Created", "content": { "application/json": { "schema": reference(schema) } } }))
+}
+
+fn no_content() -> (u16, Value) {
+    (204, json!({ "description": "Deleted" }))
+}
+
+fn id_parameter() -> Value {
+    json!({ "name": "id", "in": "path", "required": true, "schema": integer() })
+}
+
+fn audit_filter_parameters() -> Value {
+    let query = |name: &str, schema: Value| json!({ "name": name, "in": "query", "required": false, "schema": schema });
+    json!([
+        query("topology_id", integer()),
+        query("actor", string()),
+        query("entity_type", string_enum(AuditEntity::ALL.iter().map(|v| v.as_str()))),
+        query("action", string_enum(AuditAction::ALL.iter().map(|v| v.as_str()))),
+        query("since", json!({ "type": "integer", "description": "Unix seconds, inclusive" })),
+        query("until", json!({ "type": "integer", "description": "Unix seconds, exclusive" })),
+    ])
+}
+
+fn reference(schema: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", schema) })
+}
+
+fn array_of(schema: &str) -> Value {
+    json!({ "type": "array", "items": reference(schema) })
+}
+
+fn object(properties: &[(&str, Value)], required: &[&str]) -> Value {
+    let properties: Map<String, Value> = properties
+        .iter()
+        .map(|(name, schema)| (name.to_string(), schema.clone()))
+        .collect();
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = json!(required);
+    }
+    schema
+}
+
+fn string_enum<'a>(values: impl Iterator<Item = &'a str>) -> Value {
+    json!({ "type": "string", "enum": values.collect::<Vec<_>>() })
+}
+
+fn nullable(mut schema: Value) -> Value {
+    schema["nullable"] = json!(true);
+    schema
+}
+
+fn string() -> Value {
+    json!({ "type": "string" })
+}
+
+fn integer() -> Value {
+    json!({ "type": "integer", "format": "int64" })
+}
+
+fn number() -> Value {
+    json!({ "type": "number" })
+}
+
+fn boolean() -> Value {
+    json!({ "type": "boolean" })
+}
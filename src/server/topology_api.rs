@@ -1,121 +1,58 @@
-use crate::models::{Topology, CreateTopology, TopologyFull, Node, Connection};
-use leptos::prelude::*;
-use sqlx::SqlitePool;
-
-/// Get all topologies from the database
-#[server(GetTopologies, "/api")]
-pub async fn get_topologies() -> Result<Vec<Topology>, ServerFnError> {
-    use axum::Extension;
-    use leptos_axum::extract;
-
-    let Extension(pool) = extract::<Extension<SqlitePool>>()
-        .await
-        .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
-
-    let topologies = sqlx::query_as::<_, Topology>(
-        "SELECT id, name, description, created_at, updated_at FROM topologies ORDER BY created_at DESC"
-    )
-    .fetch_all(&pool)
-    .await
-    .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
-
-    Ok(topologies)
+use super::{run_server_fn, ApiJson, ApiResult};
+use crate::api;
+use crate::models::{Connection, CreateTopology, Node, Topology, TopologyFull, UpdateTopology};
+use axum::extract::Path;
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::Json;
+
+/// GET /topologies
+pub async fn list_topologies(parts: Parts) -> ApiResult<Json<Vec<Topology>>> {
+    run_server_fn(parts, api::get_topologies).await.map(Json)
 }
 
-/// Create a new topology
-#[server(CreateTopologyFn, "/api")]
-pub async fn create_topology(data: CreateTopology) -> Result<Topology, ServerFnError> {
-    use axum::Extension;
-    use leptos_axum::extract;
-
-    let Extension(pool) = extract::<Extension<SqlitePool>>()
-        .await
-        .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
-
-    let result = sqlx::query(
-        "INSERT INTO topologies (name, description) VALUES (?, ?)"
-    )
-    .bind(&data.name)
-    .bind(&data.description)
-    .execute(&pool)
-    .await
-    .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
-
-    let id = result.last_insert_rowid();
-
-    // Fetch the created topology
-    let topology = sqlx::query_as::<_, Topology>(
-        "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = ?"
-    )
-    .bind(id)
-    .fetch_one(&pool)
-    .await
-    .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
-
-    Ok(topology)
+/// POST /topologies
+pub async fn create_topology(
+    parts: Parts,
+    ApiJson(data): ApiJson<CreateTopology>,
+) -> ApiResult<(StatusCode, Json<Topology>)> {
+    let topology = run_server_fn(parts, || api::create_topology(data)).await?;
+    Ok((StatusCode::CREATED, Json(topology)))
 }
 
-/// Delete a topology
-#[server(DeleteTopology, "/api")]
-pub async fn delete_topology(id: i64) -> Result<(), ServerFnError> {
-    use axum::Extension;
-    use leptos_axum::extract;
-
-    let Extension(pool) = extract::<Extension<SqlitePool>>()
-        .await
-        .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
-
-    sqlx::query("DELETE FROM topologies WHERE id = ?")
-        .bind(id)
-        .execute(&pool)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
-
-    Ok(())
+/// GET /topologies/{id} - the topology with its nodes, connections, interfaces, subnets, groups and tags
+pub async fn get_topology(parts: Parts, Path(id): Path<i64>) -> ApiResult<Json<TopologyFull>> {
+    run_server_fn(parts, || api::get_topology_full(id)).await.map(Json)
 }
 
-/// Get complete topology with all nodes and connections
-#[server(GetTopologyFull, "/api")]
-pub async fn get_topology_full(id: i64) -> Result<TopologyFull, ServerFnError> {
-    use axum::Extension;
-    use leptos_axum::extract;
-
-    let Extension(pool) = extract::<Extension<SqlitePool>>()
-        .await
-        .map_err(|e| ServerFnError::new(format!("Failed to extract database pool: {}", e)))?;
-
-    // Fetch topology
-    let topology = sqlx::query_as::<_, Topology>(
-        "SELECT id, name, description, created_at, updated_at FROM topologies WHERE id = ?"
-    )
-    .bind(id)
-    .fetch_one(&pool)
-    .await
-    .map_err(|e| ServerFnError::new(format!("Topology not found: {}", e)))?;
+/// PATCH /topologies/{id}
+pub async fn update_topology(
+    parts: Parts,
+    Path(id): Path<i64>,
+    ApiJson(data): ApiJson<UpdateTopology>,
+) -> ApiResult<Json<Topology>> {
+    run_server_fn(parts, || api::update_topology(id, data)).await.map(Json)
+}
 
-    // Fetch all nodes for this topology
-    let nodes = sqlx::query_as::<_, Node>(
-        "SELECT id, topology_id, name, node_type, ip_address, position_x, position_y, position_z, metadata, created_at, updated_at
-         FROM nodes WHERE topology_id = ? ORDER BY created_at"
-    )
-    .bind(id)
-    .fetch_all(&pool)
-    .await
-    .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+/// DELETE /topologies/{id}
+pub async fn delete_topology(parts: Parts, Path(id): Path<i64>) -> ApiResult<StatusCode> {
+    // Deleting is idempotent in the server function; REST clients expect a 404
+    run_server_fn(parts.clone(), || api::get_topology_full(id)).await?;
+    run_server_fn(parts, || api::delete_topology(id)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    // Fetch all connections for this topology
-    let connections = sqlx::query_as::<_, Connection>(
-        "SELECT id, topology_id, source_node_id, target_node_id, connection_type, bandwidth_mbps, latency_ms, status, metadata, created_at, updated_at
-         FROM connections WHERE topology_id = ? ORDER BY created_at"
-    )
-    .bind(id)
-    .fetch_all(&pool)
-    .await
-    .map_err(|e| ServerFnError::new(format!("Database error: {}", e)))?;
+/// GET /topologies/{id}/nodes
+pub async fn list_topology_nodes(parts: Parts, Path(id): Path<i64>) -> ApiResult<Json<Vec<Node>>> {
+    let full = run_server_fn(parts, || api::get_topology_full(id)).await?;
+    Ok(Json(full.nodes))
+}
 
-    Ok(TopologyFull {
-        topology,
-        nodes,
-        connections,
-    })
+/// GET /topologies/{id}/connections
+pub async fn list_topology_connections(
+    parts: Parts,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<Vec<Connection>>> {
+    let full = run_server_fn(parts, || api::get_topology_full(id)).await?;
+    Ok(Json(full.connections))
 }
@@ -0,0 +1,34 @@
+use super::{run_server_fn, ApiError, ApiResult};
+use crate::api::{self, UndoHistoryEntry, UndoStatus};
+use axum::extract::Path;
+use axum::http::request::Parts;
+use axum::Json;
+
+/// GET /topologies/{id}/history - undoable changes, newest first
+pub async fn undo_history(
+    parts: Parts,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<Vec<UndoHistoryEntry>>> {
+    run_server_fn(parts, || api::get_undo_history(id)).await.map(Json)
+}
+
+/// GET /topologies/{id}/undo - labels of the next undo and redo steps
+pub async fn undo_status(parts: Parts, Path(id): Path<i64>) -> ApiResult<Json<UndoStatus>> {
+    run_server_fn(parts, || api::get_undo_status(id)).await.map(Json)
+}
+
+/// POST /topologies/{id}/undo - undo the latest change set, 409 when there is none
+pub async fn undo(parts: Parts, Path(id): Path<i64>) -> ApiResult<Json<UndoStatus>> {
+    if !run_server_fn(parts.clone(), || api::undo_last_change(id)).await? {
+        return Err(ApiError::conflict("Nothing to undo"));
+    }
+    run_server_fn(parts, || api::get_undo_status(id)).await.map(Json)
+}
+
+/// POST /topologies/{id}/redo - redo the latest undone change set, 409 when there is none
+pub async fn redo(parts: Parts, Path(id): Path<i64>) -> ApiResult<Json<UndoStatus>> {
+    if !run_server_fn(parts.clone(), || api::redo_last_change(id)).await? {
+        return Err(ApiError::conflict("Nothing to redo"));
+    }
+    run_server_fn(parts, || api::get_undo_status(id)).await.map(Json)
+}